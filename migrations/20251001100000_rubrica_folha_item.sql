@@ -0,0 +1,41 @@
+-- Migration: Rubricas (verbas) e itens da folha
+-- Catálogo de rubricas e lançamentos de proventos/descontos de cada contra-cheque
+
+-- Tabela de Rubricas
+CREATE TABLE IF NOT EXISTS public.cadastro_rubrica (
+    id SERIAL PRIMARY KEY,
+    codigo VARCHAR(10) NOT NULL,
+    descricao VARCHAR(150) NOT NULL,
+    tipo VARCHAR(1) NOT NULL, -- P = provento, D = desconto, I = informativa
+    incide_inss BOOLEAN NOT NULL DEFAULT false,
+    incide_irrf BOOLEAN NOT NULL DEFAULT false,
+    incide_fgts BOOLEAN NOT NULL DEFAULT false,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    CONSTRAINT cadastro_rubrica_codigo_key UNIQUE (codigo),
+    CONSTRAINT chk_rubrica_tipo CHECK (tipo IN ('P', 'D', 'I'))
+);
+
+-- Tabela de Itens da Folha (linhas do contra-cheque)
+CREATE TABLE IF NOT EXISTS public.cadastro_folha_item (
+    id BIGSERIAL PRIMARY KEY,
+    folha_id BIGINT NOT NULL,
+    rubrica_id INTEGER NOT NULL,
+    referencia NUMERIC(15,2) NOT NULL DEFAULT 0, -- quantidade, dias, horas ou percentual
+    valor NUMERIC(15,2) NOT NULL,
+    parcela VARCHAR(10), -- ex.: 3/12 para lançamentos parcelados
+    CONSTRAINT fk_folha_item_folha FOREIGN KEY (folha_id)
+        REFERENCES public.cadastro_folha (id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_folha_item_rubrica FOREIGN KEY (rubrica_id)
+        REFERENCES public.cadastro_rubrica (id)
+);
+
+CREATE INDEX idx_cadastro_folha_item_folha_id ON public.cadastro_folha_item(folha_id);
+CREATE INDEX idx_cadastro_folha_item_rubrica_id ON public.cadastro_folha_item(rubrica_id);
+
+-- Rubricas iniciais
+INSERT INTO public.cadastro_rubrica (codigo, descricao, tipo, incide_inss, incide_irrf, incide_fgts)
+VALUES
+('1', 'SALARIO CONTRATUAL', 'P', true, true, true),
+('106', 'SALARIO FAMILIA', 'P', false, false, false)
+ON CONFLICT (codigo) DO NOTHING;
@@ -0,0 +1,39 @@
+-- Migration: Tabela progressiva do INSS (RGPS)
+-- Faixas com vigência por competência; a cada janeiro basta inserir as faixas do novo ano
+
+-- Rubricas lançadas pelo cálculo automático da folha
+ALTER TABLE public.cadastro_rubrica ADD COLUMN IF NOT EXISTS codigo_sistema VARCHAR(30);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cadastro_rubrica_codigo_sistema
+    ON public.cadastro_rubrica(codigo_sistema) WHERE codigo_sistema IS NOT NULL;
+
+-- Tabela de faixas do INSS
+CREATE TABLE IF NOT EXISTS public.cadastro_tabela_inss (
+    id SERIAL PRIMARY KEY,
+    vigencia DATE NOT NULL, -- primeiro dia da competência em que a tabela passa a valer
+    limite NUMERIC(15,2) NOT NULL, -- limite superior da faixa; o maior limite é o teto
+    aliquota NUMERIC(7,4) NOT NULL, -- percentual
+    CONSTRAINT cadastro_tabela_inss_vigencia_limite_key UNIQUE (vigencia, limite)
+);
+
+CREATE INDEX idx_cadastro_tabela_inss_vigencia ON public.cadastro_tabela_inss(vigencia);
+
+INSERT INTO public.cadastro_tabela_inss (vigencia, limite, aliquota)
+VALUES
+('2024-01-01', 1412.00, 7.5),
+('2024-01-01', 2666.68, 9),
+('2024-01-01', 4000.03, 12),
+('2024-01-01', 7786.02, 14),
+('2025-01-01', 1518.00, 7.5),
+('2025-01-01', 2793.88, 9),
+('2025-01-01', 4190.83, 12),
+('2025-01-01', 8157.41, 14),
+('2026-01-01', 1621.00, 7.5),
+('2026-01-01', 2902.84, 9),
+('2026-01-01', 4354.27, 12),
+('2026-01-01', 8475.55, 14)
+ON CONFLICT (vigencia, limite) DO NOTHING;
+
+-- Rubrica de desconto do INSS
+INSERT INTO public.cadastro_rubrica (codigo, descricao, tipo, codigo_sistema)
+VALUES ('45', 'INSS', 'D', 'INSS')
+ON CONFLICT (codigo) DO UPDATE SET codigo_sistema = EXCLUDED.codigo_sistema;
@@ -0,0 +1,71 @@
+-- Migration: Tabela progressiva mensal do IRRF
+-- Faixas e parâmetros (dedução por dependente, desconto simplificado e redução da Lei 15.270/2025)
+-- com vigência por competência
+
+-- Tabela de faixas do IRRF; a última faixa não tem limite
+CREATE TABLE IF NOT EXISTS public.cadastro_tabela_irrf (
+    id SERIAL PRIMARY KEY,
+    vigencia DATE NOT NULL, -- primeiro dia da competência em que a tabela passa a valer
+    limite NUMERIC(15,2), -- limite superior da faixa; NULL na última faixa
+    aliquota NUMERIC(7,4) NOT NULL, -- percentual
+    deducao NUMERIC(15,2) NOT NULL DEFAULT 0 -- parcela a deduzir
+);
+
+CREATE INDEX idx_cadastro_tabela_irrf_vigencia ON public.cadastro_tabela_irrf(vigencia);
+
+-- Parâmetros do IRRF
+CREATE TABLE IF NOT EXISTS public.cadastro_parametro_irrf (
+    id SERIAL PRIMARY KEY,
+    vigencia DATE NOT NULL UNIQUE,
+    deducao_dependente NUMERIC(15,2) NOT NULL,
+    desconto_simplificado NUMERIC(15,2) NOT NULL,
+    -- Redução do imposto (Lei 15.270/2025): até reducao_limite_isencao o imposto é reduzido em até
+    -- reducao_valor_isencao; até reducao_limite a redução é reducao_constante - reducao_fator * rendimento
+    reducao_limite_isencao NUMERIC(15,2),
+    reducao_valor_isencao NUMERIC(15,2),
+    reducao_limite NUMERIC(15,2),
+    reducao_constante NUMERIC(15,2),
+    reducao_fator NUMERIC(15,6)
+);
+
+INSERT INTO public.cadastro_tabela_irrf (vigencia, limite, aliquota, deducao)
+VALUES
+('2023-05-01', 2112.00, 0, 0),
+('2023-05-01', 2826.65, 7.5, 158.40),
+('2023-05-01', 3751.05, 15, 370.40),
+('2023-05-01', 4664.68, 22.5, 651.73),
+('2023-05-01', NULL, 27.5, 884.96),
+('2024-02-01', 2259.20, 0, 0),
+('2024-02-01', 2826.65, 7.5, 169.44),
+('2024-02-01', 3751.05, 15, 381.44),
+('2024-02-01', 4664.68, 22.5, 662.77),
+('2024-02-01', NULL, 27.5, 896.00),
+('2025-05-01', 2428.80, 0, 0),
+('2025-05-01', 2826.65, 7.5, 182.16),
+('2025-05-01', 3751.05, 15, 394.16),
+('2025-05-01', 4664.68, 22.5, 675.49),
+('2025-05-01', NULL, 27.5, 908.73),
+('2026-01-01', 2428.80, 0, 0),
+('2026-01-01', 2826.65, 7.5, 182.16),
+('2026-01-01', 3751.05, 15, 394.16),
+('2026-01-01', 4664.68, 22.5, 675.49),
+('2026-01-01', NULL, 27.5, 908.73);
+
+INSERT INTO public.cadastro_parametro_irrf (
+    vigencia, deducao_dependente, desconto_simplificado,
+    reducao_limite_isencao, reducao_valor_isencao, reducao_limite, reducao_constante, reducao_fator)
+VALUES
+('2023-05-01', 189.59, 528.00, NULL, NULL, NULL, NULL, NULL),
+('2024-02-01', 189.59, 564.80, NULL, NULL, NULL, NULL, NULL),
+('2025-05-01', 189.59, 607.20, NULL, NULL, NULL, NULL, NULL),
+('2026-01-01', 189.59, 607.20, 5000.00, 312.89, 7350.00, 978.62, 0.133145)
+ON CONFLICT (vigencia) DO NOTHING;
+
+-- Dependentes para fins de IRRF e rubricas de pensão alimentícia (dedução legal)
+ALTER TABLE public.cadastro_folha ADD COLUMN IF NOT EXISTS dependentes_irrf INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE public.cadastro_rubrica ADD COLUMN IF NOT EXISTS pensao_alimenticia BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Rubrica de desconto do IRRF
+INSERT INTO public.cadastro_rubrica (codigo, descricao, tipo, codigo_sistema)
+VALUES ('50', 'IMPOSTO DE RENDA RETIDO NA FONTE', 'D', 'IRRF')
+ON CONFLICT (codigo) DO UPDATE SET codigo_sistema = EXCLUDED.codigo_sistema;
@@ -0,0 +1,87 @@
+-- Migration: Competência da folha por órgão (aberta, calculada, fechada, reaberta)
+-- e permissões de usuário (perfis)
+
+-- Permissões: módulo > permissão; perfis agrupam permissões e são atribuídos aos usuários
+CREATE TABLE IF NOT EXISTS public.core_module (
+    id SERIAL PRIMARY KEY,
+    title VARCHAR(100) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ DEFAULT now(),
+    updated_at TIMESTAMPTZ DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS public.core_permission (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE, -- ex.: competencia.fechar
+    description TEXT,
+    module_id INTEGER NOT NULL REFERENCES public.core_module(id),
+    created_at TIMESTAMPTZ DEFAULT now(),
+    updated_at TIMESTAMPTZ DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS public.core_role (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS public.core_user_roles (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES public.auth_user(id) ON DELETE CASCADE,
+    role_id INTEGER NOT NULL REFERENCES public.core_role(id) ON DELETE CASCADE,
+    CONSTRAINT core_user_roles_user_role_key UNIQUE (user_id, role_id)
+);
+
+CREATE TABLE IF NOT EXISTS public.core_role_permission (
+    id BIGSERIAL PRIMARY KEY,
+    role_id INTEGER NOT NULL REFERENCES public.core_role(id) ON DELETE CASCADE,
+    permission_id INTEGER NOT NULL REFERENCES public.core_permission(id) ON DELETE CASCADE,
+    CONSTRAINT core_role_permission_role_permission_key UNIQUE (role_id, permission_id)
+);
+
+INSERT INTO public.core_module (title) VALUES ('Folha') ON CONFLICT (title) DO NOTHING;
+
+INSERT INTO public.core_permission (name, description, module_id)
+SELECT p.name, p.description, m.id
+FROM (VALUES
+    ('competencia.calcular', 'Calcular a folha da competência'),
+    ('competencia.fechar', 'Fechar a competência'),
+    ('competencia.reabrir', 'Reabrir competência fechada')
+) AS p(name, description)
+CROSS JOIN public.core_module m
+WHERE m.title = 'Folha'
+ON CONFLICT (name) DO NOTHING;
+
+-- Competência (mês de folha) de cada órgão
+CREATE TABLE IF NOT EXISTS public.cadastro_competencia (
+    id SERIAL PRIMARY KEY,
+    orgao_id INTEGER NOT NULL REFERENCES public.cadastro_orgao(id),
+    ano INTEGER NOT NULL,
+    mes INTEGER NOT NULL CHECK (mes BETWEEN 1 AND 12),
+    situacao VARCHAR(20) NOT NULL DEFAULT 'ABERTA'
+        CHECK (situacao IN ('ABERTA', 'CALCULADA', 'FECHADA', 'REABERTA')),
+    -- Totais congelados no fechamento (o que foi enviado ao banco e ao tribunal de contas)
+    total_servidores INTEGER,
+    total_proventos NUMERIC(15,2),
+    total_descontos NUMERIC(15,2),
+    valor_liquido NUMERIC(15,2),
+    fechada_em TIMESTAMPTZ,
+    fechada_por VARCHAR(150),
+    CONSTRAINT cadastro_competencia_orgao_ano_mes_key UNIQUE (orgao_id, ano, mes)
+);
+
+-- Auditoria das mudanças de situação
+CREATE TABLE IF NOT EXISTS public.cadastro_competencia_historico (
+    id BIGSERIAL PRIMARY KEY,
+    competencia_id INTEGER NOT NULL REFERENCES public.cadastro_competencia(id) ON DELETE CASCADE,
+    situacao_anterior VARCHAR(20),
+    situacao_nova VARCHAR(20) NOT NULL,
+    usuario VARCHAR(150), -- NULL quando aberta automaticamente ao lançar a folha
+    motivo TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_cadastro_competencia_historico_competencia ON public.cadastro_competencia_historico(competencia_id);
+
+-- Competências das folhas já lançadas
+INSERT INTO public.cadastro_competencia (orgao_id, ano, mes)
+SELECT DISTINCT orgao_id, ano, mes FROM public.cadastro_folha
+ON CONFLICT (orgao_id, ano, mes) DO NOTHING;
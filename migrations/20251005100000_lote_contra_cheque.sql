@@ -0,0 +1,21 @@
+-- Geração em lote dos contra-cheques de um órgão/competência.
+-- Lotes grandes são processados em segundo plano e o arquivo fica guardado até o download.
+CREATE TABLE IF NOT EXISTS public.cadastro_lote_contra_cheque (
+    id SERIAL PRIMARY KEY,
+    orgao_id INTEGER NOT NULL REFERENCES public.cadastro_orgao(id),
+    ano INTEGER NOT NULL,
+    mes INTEGER NOT NULL CHECK (mes BETWEEN 1 AND 12),
+    setor_id INTEGER,
+    departamento_id INTEGER,
+    -- ZIP com um PDF por servidor ou um único PDF com todos os contra-cheques
+    formato VARCHAR(3) NOT NULL CHECK (formato IN ('ZIP', 'PDF')),
+    situacao VARCHAR(20) NOT NULL DEFAULT 'PENDENTE'
+        CHECK (situacao IN ('PENDENTE', 'PROCESSANDO', 'CONCLUIDO', 'ERRO')),
+    total_folhas INTEGER NOT NULL DEFAULT 0,
+    nome_arquivo VARCHAR(150),
+    arquivo BYTEA,
+    erro TEXT,
+    usuario VARCHAR(150),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    concluido_em TIMESTAMPTZ
+);
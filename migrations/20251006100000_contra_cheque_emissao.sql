@@ -0,0 +1,18 @@
+-- Contra-cheques emitidos, com o código de verificação impresso no documento.
+-- Os dados ficam congelados na emissão para que a validação mostre o que foi impresso,
+-- mesmo que a folha seja recalculada ou excluída depois.
+CREATE TABLE IF NOT EXISTS public.cadastro_contra_cheque_emissao (
+    id BIGSERIAL PRIMARY KEY,
+    codigo VARCHAR(20) NOT NULL UNIQUE,
+    folha_id BIGINT REFERENCES public.cadastro_folha(id) ON DELETE SET NULL,
+    orgao_nome VARCHAR(150) NOT NULL,
+    servidor_nome VARCHAR(150) NOT NULL,
+    ano INTEGER NOT NULL,
+    mes INTEGER NOT NULL,
+    total_proventos NUMERIC(15,2) NOT NULL,
+    total_descontos NUMERIC(15,2) NOT NULL,
+    valor_liquido NUMERIC(15,2) NOT NULL,
+    emitido_em TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_cadastro_contra_cheque_emissao_folha ON public.cadastro_contra_cheque_emissao(folha_id);
@@ -0,0 +1,30 @@
+-- Cadastro de servidores (já referenciado por cadastro_folha).
+-- Em bancos legados a tabela existe só com id/nome; as colunas novas são acrescentadas.
+CREATE TABLE IF NOT EXISTS public.cadastro_servidor (
+    id SERIAL PRIMARY KEY,
+    nome VARCHAR(150) NOT NULL
+);
+
+ALTER TABLE public.cadastro_servidor
+    ADD COLUMN IF NOT EXISTS matricula VARCHAR(20),
+    -- CPF e PIS/PASEP gravados só com os dígitos
+    ADD COLUMN IF NOT EXISTS cpf VARCHAR(11),
+    ADD COLUMN IF NOT EXISTS pis VARCHAR(11),
+    ADD COLUMN IF NOT EXISTS data_nascimento DATE,
+    ADD COLUMN IF NOT EXISTS data_admissao DATE,
+    -- Endereço
+    ADD COLUMN IF NOT EXISTS cep VARCHAR(8),
+    ADD COLUMN IF NOT EXISTS logradouro VARCHAR(150),
+    ADD COLUMN IF NOT EXISTS numero VARCHAR(10),
+    ADD COLUMN IF NOT EXISTS complemento VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS bairro VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS municipio_id INTEGER REFERENCES public.cadastro_municipio(id),
+    -- Dados bancários para o crédito do salário
+    ADD COLUMN IF NOT EXISTS banco VARCHAR(3),
+    ADD COLUMN IF NOT EXISTS agencia VARCHAR(10),
+    ADD COLUMN IF NOT EXISTS conta VARCHAR(20),
+    ADD COLUMN IF NOT EXISTS tipo_conta VARCHAR(1) CHECK (tipo_conta IN ('C', 'P')), -- C = corrente, P = poupança
+    ADD COLUMN IF NOT EXISTS is_active BOOLEAN NOT NULL DEFAULT true;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cadastro_servidor_cpf ON public.cadastro_servidor(cpf);
+CREATE INDEX IF NOT EXISTS idx_cadastro_servidor_nome ON public.cadastro_servidor(nome);
@@ -0,0 +1,25 @@
+-- Cadastro de órgãos (entidades municipais), com os dados do timbre dos documentos.
+-- Em bancos legados a tabela existe só com id/nome; as colunas novas são acrescentadas.
+CREATE TABLE IF NOT EXISTS public.cadastro_orgao (
+    id SERIAL PRIMARY KEY,
+    nome VARCHAR(150) NOT NULL
+);
+
+ALTER TABLE public.cadastro_orgao
+    -- CNPJ gravado sem máscara (14 posições, numérico ou alfanumérico)
+    ADD COLUMN IF NOT EXISTS cnpj VARCHAR(14),
+    -- Endereço
+    ADD COLUMN IF NOT EXISTS cep VARCHAR(8),
+    ADD COLUMN IF NOT EXISTS logradouro VARCHAR(150),
+    ADD COLUMN IF NOT EXISTS numero VARCHAR(10),
+    ADD COLUMN IF NOT EXISTS complemento VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS bairro VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS municipio_id INTEGER REFERENCES public.cadastro_municipio(id),
+    -- Contato
+    ADD COLUMN IF NOT EXISTS telefone VARCHAR(20),
+    ADD COLUMN IF NOT EXISTS email VARCHAR(150),
+    -- Brasão/logotipo em PNG, já redimensionado no upload
+    ADD COLUMN IF NOT EXISTS logo BYTEA,
+    ADD COLUMN IF NOT EXISTS is_active BOOLEAN NOT NULL DEFAULT true;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cadastro_orgao_cnpj ON public.cadastro_orgao(cnpj);
@@ -0,0 +1,34 @@
+-- Tabelas de apoio referenciadas por cadastro_folha (cargo, setor, departamento e vínculo).
+-- Em bancos legados as tabelas já existem só com id/nome; as colunas novas são acrescentadas.
+CREATE TABLE IF NOT EXISTS public.cadastro_cargo (
+    id SERIAL PRIMARY KEY,
+    nome VARCHAR(100) NOT NULL
+);
+
+-- Código CBO (Classificação Brasileira de Ocupações), exigido pelo eSocial
+ALTER TABLE public.cadastro_cargo
+    ADD COLUMN IF NOT EXISTS cbo VARCHAR(6);
+
+CREATE TABLE IF NOT EXISTS public.cadastro_setor (
+    id SERIAL PRIMARY KEY,
+    nome VARCHAR(100) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS public.cadastro_departamento (
+    id SERIAL PRIMARY KEY,
+    nome VARCHAR(100) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS public.cadastro_tipovinculo (
+    id SERIAL PRIMARY KEY,
+    nome VARCHAR(100) NOT NULL
+);
+
+-- Código da categoria do trabalhador na tabela 01 do eSocial (ex.: 301 = servidor efetivo)
+ALTER TABLE public.cadastro_tipovinculo
+    ADD COLUMN IF NOT EXISTS categoria_esocial INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_cadastro_cargo_nome ON public.cadastro_cargo(nome);
+CREATE INDEX IF NOT EXISTS idx_cadastro_setor_nome ON public.cadastro_setor(nome);
+CREATE INDEX IF NOT EXISTS idx_cadastro_departamento_nome ON public.cadastro_departamento(nome);
+CREATE INDEX IF NOT EXISTS idx_cadastro_tipovinculo_nome ON public.cadastro_tipovinculo(nome);
@@ -0,0 +1,10 @@
+-- Códigos IBGE de UFs (2 dígitos) e municípios (7 dígitos), usados no eSocial,
+-- nas exportações e na validação de endereços. A importação da planilha DTB faz upsert por eles.
+ALTER TABLE public.cadastro_uf
+    ADD COLUMN IF NOT EXISTS codigo_ibge INTEGER;
+
+ALTER TABLE public.cadastro_municipio
+    ADD COLUMN IF NOT EXISTS codigo_ibge INTEGER;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cadastro_uf_codigo_ibge ON public.cadastro_uf(codigo_ibge);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cadastro_municipio_codigo_ibge ON public.cadastro_municipio(codigo_ibge);
@@ -0,0 +1,16 @@
+-- Base local de CEPs, carregada por importação em lote ou preenchida pelo provedor
+-- remoto (cache). O município é ligado ao cadastro pelo código IBGE.
+CREATE TABLE IF NOT EXISTS public.core_cep (
+    cep VARCHAR(8) PRIMARY KEY,
+    logradouro VARCHAR(200) NOT NULL DEFAULT '',
+    complemento VARCHAR(200) NOT NULL DEFAULT '',
+    bairro VARCHAR(100) NOT NULL DEFAULT '',
+    localidade VARCHAR(100) NOT NULL DEFAULT '',
+    uf VARCHAR(2) NOT NULL DEFAULT '',
+    codigo_ibge INTEGER,
+    -- IMPORTACAO ou o nome do provedor remoto que respondeu
+    origem VARCHAR(20) NOT NULL DEFAULT 'IMPORTACAO',
+    atualizado_em TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_core_cep_codigo_ibge ON public.core_cep(codigo_ibge);
@@ -0,0 +1,36 @@
+-- eSocial: parâmetros do empregador no órgão e eventos de remuneração (S-1200) e
+-- pagamento (S-1210) gerados a partir das folhas da competência. A transmissão fica fora do sistema; aqui o
+-- XML é gerado, validado contra os XSD oficiais e guardado até ser assinado.
+ALTER TABLE public.cadastro_orgao
+    -- 1 = produção, 2 = produção restrita (testes)
+    ADD COLUMN IF NOT EXISTS esocial_ambiente INTEGER NOT NULL DEFAULT 2
+        CHECK (esocial_ambiente IN (1, 2)),
+    -- ideTabRubr da tabela de rubricas enviada no S-1010
+    ADD COLUMN IF NOT EXISTS esocial_tabela_rubrica VARCHAR(8),
+    -- codLotacao da lotação tributária enviada no S-1020
+    ADD COLUMN IF NOT EXISTS esocial_lotacao VARCHAR(30);
+
+CREATE TABLE IF NOT EXISTS public.cadastro_esocial_evento (
+    id BIGSERIAL PRIMARY KEY,
+    orgao_id INTEGER NOT NULL REFERENCES public.cadastro_orgao(id),
+    ano INTEGER NOT NULL,
+    mes INTEGER NOT NULL CHECK (mes BETWEEN 1 AND 12),
+    tipo VARCHAR(6) NOT NULL CHECK (tipo IN ('S-1200', 'S-1210')),
+    -- um evento por trabalhador no período, com um demonstrativo (dmDev) por folha
+    servidor_id INTEGER NOT NULL REFERENCES public.cadastro_servidor(id),
+    total_folhas INTEGER NOT NULL DEFAULT 1,
+    -- atributo Id do evento (ID + tipo/nº de inscrição + data/hora + sequencial)
+    id_evento VARCHAR(36) NOT NULL UNIQUE,
+    xml TEXT NOT NULL,
+    situacao VARCHAR(10) NOT NULL DEFAULT 'GERADO'
+        CHECK (situacao IN ('GERADO', 'VALIDADO', 'INVALIDO')),
+    erros TEXT,
+    usuario VARCHAR(150),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    validado_em TIMESTAMPTZ,
+    -- gerar de novo substitui o evento do servidor na competência
+    CONSTRAINT uk_esocial_evento UNIQUE (orgao_id, ano, mes, tipo, servidor_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_esocial_evento_competencia
+    ON public.cadastro_esocial_evento(orgao_id, ano, mes);
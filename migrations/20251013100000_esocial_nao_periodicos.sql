@@ -0,0 +1,43 @@
+-- eSocial: eventos não periódicos do servidor (admissão, alteração contratual e desligamento).
+-- As alterações do cadastro entram numa fila por órgão e o XML é gerado a partir dela.
+ALTER TABLE public.cadastro_servidor
+    -- Contrato atual: órgão (empregador no eSocial), cargo e tipo de vínculo
+    ADD COLUMN IF NOT EXISTS orgao_id INTEGER REFERENCES public.cadastro_orgao(id),
+    ADD COLUMN IF NOT EXISTS cargo_id INTEGER REFERENCES public.cadastro_cargo(id),
+    ADD COLUMN IF NOT EXISTS vinculo_id INTEGER REFERENCES public.cadastro_tipovinculo(id),
+    -- Dados pessoais exigidos na admissão (tabelas do leiaute: raça/cor 1-6, grau de instrução 01-12)
+    ADD COLUMN IF NOT EXISTS sexo VARCHAR(1) CHECK (sexo IN ('M', 'F')),
+    ADD COLUMN IF NOT EXISTS raca_cor INTEGER CHECK (raca_cor BETWEEN 1 AND 6),
+    ADD COLUMN IF NOT EXISTS grau_instrucao VARCHAR(2),
+    -- Desligamento: motivo na tabela 19 do eSocial (ex.: 01, 02, 10)
+    ADD COLUMN IF NOT EXISTS data_desligamento DATE,
+    ADD COLUMN IF NOT EXISTS motivo_desligamento VARCHAR(2);
+
+CREATE INDEX IF NOT EXISTS idx_cadastro_servidor_orgao ON public.cadastro_servidor(orgao_id);
+
+-- Regime de previdência do vínculo: 1 = RGPS, 2 = RPPS
+ALTER TABLE public.cadastro_tipovinculo
+    ADD COLUMN IF NOT EXISTS regime_previdencia INTEGER NOT NULL DEFAULT 1
+        CHECK (regime_previdencia IN (1, 2));
+
+CREATE TABLE IF NOT EXISTS public.cadastro_esocial_fila (
+    id BIGSERIAL PRIMARY KEY,
+    orgao_id INTEGER NOT NULL REFERENCES public.cadastro_orgao(id),
+    servidor_id INTEGER NOT NULL REFERENCES public.cadastro_servidor(id) ON DELETE CASCADE,
+    tipo VARCHAR(6) NOT NULL CHECK (tipo IN ('S-2200', 'S-2206', 'S-2299', 'S-2300')),
+    -- admissão, alteração ou desligamento que originou o evento
+    data_evento DATE NOT NULL,
+    situacao VARCHAR(10) NOT NULL DEFAULT 'PENDENTE'
+        CHECK (situacao IN ('PENDENTE', 'GERADO', 'REJEITADO')),
+    id_evento VARCHAR(36) UNIQUE,
+    xml TEXT,
+    erros TEXT,
+    usuario VARCHAR(150),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    gerado_em TIMESTAMPTZ
+);
+
+-- a mesma alteração salva duas vezes não duplica o evento pendente
+CREATE UNIQUE INDEX IF NOT EXISTS uk_esocial_fila_pendente
+    ON public.cadastro_esocial_fila(servidor_id, tipo, data_evento) WHERE situacao = 'PENDENTE';
+CREATE INDEX IF NOT EXISTS idx_esocial_fila_orgao ON public.cadastro_esocial_fila(orgao_id, situacao);
@@ -0,0 +1,14 @@
+-- Informe de rendimentos: linha do comprovante em que cada rubrica é somada.
+-- Sem linha informada vale a classificação pelas incidências da rubrica
+-- (tributável, isento, INSS, IRRF e pensão alimentícia).
+ALTER TABLE public.cadastro_rubrica
+    ADD COLUMN IF NOT EXISTS informe_rendimentos VARCHAR(30)
+        CHECK (informe_rendimentos IN (
+            'TRIBUTAVEL', 'PREVIDENCIA', 'PREVIDENCIA_COMPLEMENTAR', 'PENSAO', 'IRRF',
+            'DIARIAS', 'MOLESTIA_GRAVE', 'INDENIZACAO', 'ISENTO',
+            'DECIMO_TERCEIRO', 'PREVIDENCIA_DECIMO_TERCEIRO', 'IRRF_DECIMO_TERCEIRO',
+            'NAO_INFORMAR'
+        ));
+
+-- o informe soma o ano inteiro das folhas do órgão
+CREATE INDEX IF NOT EXISTS idx_cadastro_folha_orgao_ano ON public.cadastro_folha(orgao_id, ano);
@@ -0,0 +1,46 @@
+-- Remessa de crédito de salários em CNAB 240 (pagamentos, segmentos A e B): conta de
+-- débito e convênio do órgão no banco e os arquivos gerados a partir das competências
+-- fechadas. O envio ao banco fica fora do sistema.
+ALTER TABLE public.cadastro_orgao
+    ADD COLUMN IF NOT EXISTS cnab_banco VARCHAR(3),
+    -- agência e conta de débito com o DV depois do hífen ("1234-5", "12345-6")
+    ADD COLUMN IF NOT EXISTS cnab_agencia VARCHAR(10),
+    ADD COLUMN IF NOT EXISTS cnab_conta VARCHAR(20),
+    -- código do convênio de pagamentos no banco
+    ADD COLUMN IF NOT EXISTS cnab_convenio VARCHAR(20),
+    -- último número sequencial de arquivo (NSA) enviado; o banco recusa NSA repetido
+    ADD COLUMN IF NOT EXISTS cnab_nsa INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS public.cadastro_remessa_cnab (
+    id SERIAL PRIMARY KEY,
+    orgao_id INTEGER NOT NULL REFERENCES public.cadastro_orgao(id),
+    ano INTEGER NOT NULL,
+    mes INTEGER NOT NULL CHECK (mes BETWEEN 1 AND 12),
+    layout VARCHAR(10) NOT NULL CHECK (layout IN ('FEBRABAN', 'BB', 'CAIXA')),
+    banco VARCHAR(3) NOT NULL,
+    nsa INTEGER NOT NULL,
+    data_pagamento DATE NOT NULL,
+    total_pagamentos INTEGER NOT NULL,
+    valor_total NUMERIC(15, 2) NOT NULL,
+    nome_arquivo VARCHAR(100) NOT NULL,
+    conteudo BYTEA NOT NULL,
+    usuario VARCHAR(150),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_remessa_cnab_competencia
+    ON public.cadastro_remessa_cnab(orgao_id, ano, mes);
+
+-- um pagamento (segmentos A e B) por folha; o "seu número" identifica a folha no retorno
+CREATE TABLE IF NOT EXISTS public.cadastro_remessa_cnab_item (
+    id BIGSERIAL PRIMARY KEY,
+    remessa_id INTEGER NOT NULL REFERENCES public.cadastro_remessa_cnab(id) ON DELETE CASCADE,
+    folha_id BIGINT REFERENCES public.cadastro_folha(id) ON DELETE SET NULL,
+    servidor_id INTEGER NOT NULL REFERENCES public.cadastro_servidor(id),
+    seu_numero VARCHAR(20) NOT NULL,
+    banco VARCHAR(3) NOT NULL,
+    agencia VARCHAR(10) NOT NULL,
+    conta VARCHAR(20) NOT NULL,
+    valor NUMERIC(15, 2) NOT NULL,
+    CONSTRAINT uk_remessa_cnab_item UNIQUE (remessa_id, seu_numero)
+);
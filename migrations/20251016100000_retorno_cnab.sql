@@ -0,0 +1,16 @@
+-- Retorno CNAB 240: situação de cada crédito da remessa conforme as ocorrências
+-- devolvidas pelo banco. ENVIADO ainda não teve retorno, AGENDADO foi aceito para a
+-- data de pagamento, PAGO foi efetivado e REJEITADO precisa de correção e nova remessa.
+ALTER TABLE public.cadastro_remessa_cnab_item
+    ADD COLUMN IF NOT EXISTS situacao VARCHAR(10) NOT NULL DEFAULT 'ENVIADO'
+        CHECK (situacao IN ('ENVIADO', 'AGENDADO', 'PAGO', 'REJEITADO')),
+    -- até cinco códigos de ocorrência (tabela G059 da FEBRABAN), como vieram no arquivo
+    ADD COLUMN IF NOT EXISTS ocorrencias VARCHAR(10),
+    -- descrição das ocorrências no momento da importação
+    ADD COLUMN IF NOT EXISTS mensagem TEXT,
+    ADD COLUMN IF NOT EXISTS data_efetivacao DATE,
+    ADD COLUMN IF NOT EXISTS valor_pago NUMERIC(15, 2),
+    ADD COLUMN IF NOT EXISTS retorno_em TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_remessa_cnab_item_situacao
+    ON public.cadastro_remessa_cnab_item(situacao);
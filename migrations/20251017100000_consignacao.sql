@@ -0,0 +1,57 @@
+-- Consignações: empréstimos descontados em folha pelas consignatárias conveniadas, com as
+-- parcelas lançadas no cálculo e o limite legal de comprometimento (margem consignável).
+
+-- percentual da remuneração líquida dos descontos obrigatórios que pode ser consignado
+ALTER TABLE public.cadastro_orgao
+    ADD COLUMN IF NOT EXISTS margem_consignavel NUMERIC(5, 2) NOT NULL DEFAULT 35
+        CHECK (margem_consignavel BETWEEN 0 AND 100);
+
+-- cada consignatária desconta na sua própria rubrica, reservada ao cálculo
+CREATE TABLE IF NOT EXISTS public.cadastro_consignataria (
+    id SERIAL PRIMARY KEY,
+    nome VARCHAR(100) NOT NULL,
+    cnpj VARCHAR(14),
+    rubrica_id INTEGER NOT NULL REFERENCES public.cadastro_rubrica(id),
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    CONSTRAINT uk_consignataria_rubrica UNIQUE (rubrica_id)
+);
+
+CREATE TABLE IF NOT EXISTS public.cadastro_consignacao (
+    id SERIAL PRIMARY KEY,
+    servidor_id INTEGER NOT NULL REFERENCES public.cadastro_servidor(id),
+    consignataria_id INTEGER NOT NULL REFERENCES public.cadastro_consignataria(id),
+    contrato VARCHAR(30) NOT NULL,
+    valor_parcela NUMERIC(15, 2) NOT NULL CHECK (valor_parcela > 0),
+    total_parcelas INTEGER NOT NULL CHECK (total_parcelas > 0),
+    -- competência da primeira parcela
+    ano_inicio INTEGER NOT NULL,
+    mes_inicio INTEGER NOT NULL CHECK (mes_inicio BETWEEN 1 AND 12),
+    -- CANCELADO deixa de ser lançado nos próximos cálculos (quitação antecipada, portabilidade)
+    situacao VARCHAR(10) NOT NULL DEFAULT 'ATIVO' CHECK (situacao IN ('ATIVO', 'CANCELADO')),
+    usuario VARCHAR(150),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT uk_consignacao_contrato UNIQUE (consignataria_id, contrato)
+);
+
+CREATE INDEX IF NOT EXISTS idx_consignacao_servidor
+    ON public.cadastro_consignacao(servidor_id, situacao);
+
+-- parcela lançada em cada folha; sai junto com o item quando a folha é recalculada
+CREATE TABLE IF NOT EXISTS public.cadastro_consignacao_parcela (
+    id BIGSERIAL PRIMARY KEY,
+    consignacao_id INTEGER NOT NULL REFERENCES public.cadastro_consignacao(id),
+    folha_id BIGINT NOT NULL REFERENCES public.cadastro_folha(id) ON DELETE CASCADE,
+    folha_item_id BIGINT NOT NULL REFERENCES public.cadastro_folha_item(id) ON DELETE CASCADE,
+    numero INTEGER NOT NULL,
+    valor NUMERIC(15, 2) NOT NULL,
+    CONSTRAINT uk_consignacao_parcela UNIQUE (consignacao_id, folha_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_consignacao_parcela_folha
+    ON public.cadastro_consignacao_parcela(folha_id);
+
+INSERT INTO public.core_permission (name, description, module_id)
+SELECT 'consignacao.averbar', 'Averbar, alterar e excluir contratos de consignação', m.id
+FROM public.core_module m
+WHERE m.title = 'Folha'
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,59 @@
+-- API de margem consignável para as consignatárias: credenciais de acesso, reservas de
+-- margem com prazo de validade e o registro de todas as consultas.
+
+-- token enviado pelo banco: "<chave>.<segredo>"; do segredo só se guarda o hash
+CREATE TABLE IF NOT EXISTS public.cadastro_consignataria_credencial (
+    id SERIAL PRIMARY KEY,
+    consignataria_id INTEGER NOT NULL REFERENCES public.cadastro_consignataria(id),
+    chave VARCHAR(16) NOT NULL,
+    segredo_hash VARCHAR(255) NOT NULL,
+    descricao VARCHAR(100),
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    usuario VARCHAR(150),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ultimo_uso TIMESTAMPTZ,
+    CONSTRAINT uk_consignataria_credencial_chave UNIQUE (chave)
+);
+
+-- margem separada para um contrato em negociação; vencida, volta a ficar disponível
+CREATE TABLE IF NOT EXISTS public.cadastro_margem_reserva (
+    id SERIAL PRIMARY KEY,
+    codigo VARCHAR(17) NOT NULL,
+    consignataria_id INTEGER NOT NULL REFERENCES public.cadastro_consignataria(id),
+    servidor_id INTEGER NOT NULL REFERENCES public.cadastro_servidor(id),
+    valor NUMERIC(15, 2) NOT NULL CHECK (valor > 0),
+    expira_em TIMESTAMPTZ NOT NULL,
+    -- UTILIZADA quando o contrato é averbado com o código da reserva
+    situacao VARCHAR(10) NOT NULL DEFAULT 'RESERVADA'
+        CHECK (situacao IN ('RESERVADA', 'UTILIZADA', 'CANCELADA')),
+    consignacao_id INTEGER REFERENCES public.cadastro_consignacao(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT uk_margem_reserva_codigo UNIQUE (codigo)
+);
+
+CREATE INDEX IF NOT EXISTS idx_margem_reserva_servidor
+    ON public.cadastro_margem_reserva(servidor_id, situacao);
+
+-- toda chamada à API, inclusive as recusadas por credencial inválida
+CREATE TABLE IF NOT EXISTS public.cadastro_margem_consulta (
+    id BIGSERIAL PRIMARY KEY,
+    credencial_id INTEGER REFERENCES public.cadastro_consignataria_credencial(id),
+    consignataria_id INTEGER REFERENCES public.cadastro_consignataria(id),
+    operacao VARCHAR(12) NOT NULL CHECK (operacao IN ('CONSULTA', 'RESERVA', 'CANCELAMENTO')),
+    cpf VARCHAR(11),
+    reserva_codigo VARCHAR(17),
+    status SMALLINT NOT NULL,
+    disponivel NUMERIC(15, 2),
+    mensagem TEXT,
+    ip VARCHAR(45),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_margem_consulta_consignataria
+    ON public.cadastro_margem_consulta(consignataria_id, created_at);
+
+INSERT INTO public.core_permission (name, description, module_id)
+SELECT 'consignataria.credencial', 'Gerar e revogar credenciais da API de margem das consignatárias', m.id
+FROM public.core_module m
+WHERE m.title = 'Folha'
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,37 @@
+-- 13º salário: folhas próprias do adiantamento e da parcela final, fora da folha mensal,
+-- com os avos apurados pela admissão e pelos afastamentos do servidor.
+
+-- 0 = folha mensal; 1 = adiantamento do 13º; 2 = parcela final do 13º
+ALTER TABLE public.cadastro_folha
+    ADD COLUMN IF NOT EXISTS decimo_terceiro SMALLINT NOT NULL DEFAULT 0
+        CHECK (decimo_terceiro IN (0, 1, 2));
+
+-- uma folha de cada parcela do 13º por servidor no ano
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cadastro_folha_decimo_terceiro
+    ON public.cadastro_folha(orgao_id, servidor_id, ano, decimo_terceiro)
+    WHERE decimo_terceiro <> 0;
+
+-- licenças e afastamentos; os que reduzem avos tiram os dias do 13º
+CREATE TABLE IF NOT EXISTS public.cadastro_afastamento (
+    id SERIAL PRIMARY KEY,
+    servidor_id INTEGER NOT NULL REFERENCES public.cadastro_servidor(id) ON DELETE CASCADE,
+    data_inicio DATE NOT NULL,
+    -- sem data de fim o servidor continua afastado
+    data_fim DATE,
+    motivo VARCHAR(100) NOT NULL,
+    reduz_avos BOOLEAN NOT NULL DEFAULT true,
+    CONSTRAINT ck_afastamento_periodo CHECK (data_fim IS NULL OR data_fim >= data_inicio)
+);
+
+CREATE INDEX IF NOT EXISTS idx_cadastro_afastamento_servidor
+    ON public.cadastro_afastamento(servidor_id, data_inicio);
+
+-- rubricas lançadas pelo cálculo do 13º; INSS e IRRF são exclusivos da parcela final
+INSERT INTO public.cadastro_rubrica (codigo, descricao, tipo, codigo_sistema, informe_rendimentos)
+VALUES
+('130', 'ADIANTAMENTO 13 SALARIO', 'P', 'ADIANTAMENTO_13', 'NAO_INFORMAR'),
+('131', '13 SALARIO', 'P', 'DECIMO_TERCEIRO', 'DECIMO_TERCEIRO'),
+('132', 'DESCONTO ADIANTAMENTO 13 SALARIO', 'D', 'DESCONTO_ADIANTAMENTO_13', 'NAO_INFORMAR'),
+('133', 'INSS 13 SALARIO', 'D', 'INSS_13', 'PREVIDENCIA_DECIMO_TERCEIRO'),
+('134', 'IRRF 13 SALARIO', 'D', 'IRRF_13', 'IRRF_DECIMO_TERCEIRO')
+ON CONFLICT (codigo) DO UPDATE SET codigo_sistema = EXCLUDED.codigo_sistema;
@@ -0,0 +1,54 @@
+-- Férias: períodos aquisitivos do servidor, programação do gozo (fracionado ou não, com
+-- ou sem abono pecuniário) e pagamento automático no cálculo da folha da competência
+-- em que as férias começam.
+
+-- cada 12 meses de exercício a partir da admissão dão direito a 30 dias de férias
+CREATE TABLE IF NOT EXISTS public.cadastro_periodo_aquisitivo (
+    id SERIAL PRIMARY KEY,
+    servidor_id INTEGER NOT NULL REFERENCES public.cadastro_servidor(id) ON DELETE CASCADE,
+    data_inicio DATE NOT NULL,
+    data_fim DATE NOT NULL,
+    dias_direito INTEGER NOT NULL DEFAULT 30 CHECK (dias_direito BETWEEN 0 AND 30),
+    CONSTRAINT uk_periodo_aquisitivo UNIQUE (servidor_id, data_inicio),
+    CONSTRAINT ck_periodo_aquisitivo CHECK (data_fim > data_inicio)
+);
+
+-- PROGRAMADA = aguardando o cálculo da folha; CALCULADA = lançada na folha da competência
+CREATE TABLE IF NOT EXISTS public.cadastro_ferias (
+    id SERIAL PRIMARY KEY,
+    servidor_id INTEGER NOT NULL REFERENCES public.cadastro_servidor(id) ON DELETE CASCADE,
+    periodo_id INTEGER NOT NULL REFERENCES public.cadastro_periodo_aquisitivo(id),
+    data_inicio DATE NOT NULL,
+    data_fim DATE NOT NULL,
+    dias INTEGER NOT NULL CHECK (dias > 0),
+    dias_abono INTEGER NOT NULL DEFAULT 0 CHECK (dias_abono >= 0),
+    -- competência da folha que paga as férias (a do início do gozo)
+    ano INTEGER NOT NULL,
+    mes INTEGER NOT NULL CHECK (mes BETWEEN 1 AND 12),
+    situacao VARCHAR(20) NOT NULL DEFAULT 'PROGRAMADA'
+        CHECK (situacao IN ('PROGRAMADA', 'CALCULADA', 'CANCELADA')),
+    folha_id BIGINT REFERENCES public.cadastro_folha(id) ON DELETE SET NULL,
+    valor_ferias NUMERIC(15, 2),
+    valor_terco NUMERIC(15, 2),
+    valor_abono NUMERIC(15, 2),
+    valor_terco_abono NUMERIC(15, 2),
+    usuario VARCHAR(100),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT ck_ferias_periodo CHECK (data_fim >= data_inicio)
+);
+
+CREATE INDEX IF NOT EXISTS idx_cadastro_ferias_servidor
+    ON public.cadastro_ferias(servidor_id, ano, mes);
+CREATE INDEX IF NOT EXISTS idx_cadastro_ferias_periodo
+    ON public.cadastro_ferias(periodo_id);
+
+-- rubricas lançadas pelo cálculo das férias; o abono pecuniário e o terço dele são
+-- isentos de INSS e de IRRF
+INSERT INTO public.cadastro_rubrica
+    (codigo, descricao, tipo, incide_inss, incide_irrf, incide_fgts, codigo_sistema, informe_rendimentos)
+VALUES
+('140', 'FERIAS', 'P', true, true, true, 'FERIAS', 'TRIBUTAVEL'),
+('141', '1/3 CONSTITUCIONAL DE FERIAS', 'P', true, true, true, 'TERCO_FERIAS', 'TRIBUTAVEL'),
+('142', 'ABONO PECUNIARIO DE FERIAS', 'P', false, false, false, 'ABONO_FERIAS', 'ISENTO'),
+('143', '1/3 DO ABONO PECUNIARIO', 'P', false, false, false, 'TERCO_ABONO_FERIAS', 'ISENTO')
+ON CONFLICT (codigo) DO UPDATE SET codigo_sistema = EXCLUDED.codigo_sistema;
@@ -0,0 +1,34 @@
+-- Rescisão: as verbas do desligamento (saldo de salário, 13º proporcional e férias
+-- vencidas e proporcionais) numa folha própria, fora da folha mensal.
+
+-- 3 = folha de rescisão
+ALTER TABLE public.cadastro_folha DROP CONSTRAINT IF EXISTS cadastro_folha_decimo_terceiro_check;
+ALTER TABLE public.cadastro_folha
+    ADD CONSTRAINT cadastro_folha_decimo_terceiro_check CHECK (decimo_terceiro IN (0, 1, 2, 3));
+
+-- uma rescisão por servidor, com o que foi apurado para o termo
+CREATE TABLE IF NOT EXISTS public.cadastro_rescisao (
+    id SERIAL PRIMARY KEY,
+    servidor_id INTEGER NOT NULL UNIQUE REFERENCES public.cadastro_servidor(id) ON DELETE CASCADE,
+    folha_id BIGINT NOT NULL REFERENCES public.cadastro_folha(id) ON DELETE CASCADE,
+    data_desligamento DATE NOT NULL,
+    -- tabela 19 do eSocial
+    motivo_desligamento VARCHAR(2) NOT NULL,
+    dias_saldo_salario INTEGER NOT NULL,
+    avos_decimo_terceiro INTEGER NOT NULL,
+    dias_ferias_vencidas INTEGER NOT NULL,
+    avos_ferias_proporcionais INTEGER NOT NULL,
+    usuario VARCHAR(100),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- rubricas lançadas pela rescisão; o 13º proporcional usa as rubricas do 13º e as
+-- férias indenizadas (com o terço) são isentas de INSS e de IRRF
+INSERT INTO public.cadastro_rubrica
+    (codigo, descricao, tipo, incide_inss, incide_irrf, incide_fgts, codigo_sistema, informe_rendimentos)
+VALUES
+('150', 'SALDO DE SALARIO', 'P', true, true, true, 'SALDO_SALARIO', 'TRIBUTAVEL'),
+('151', 'FERIAS VENCIDAS INDENIZADAS', 'P', false, false, false, 'FERIAS_VENCIDAS', 'INDENIZACAO'),
+('152', 'FERIAS PROPORCIONAIS INDENIZADAS', 'P', false, false, false, 'FERIAS_PROPORCIONAIS', 'INDENIZACAO'),
+('153', '1/3 FERIAS INDENIZADAS', 'P', false, false, false, 'TERCO_FERIAS_RESCISAO', 'INDENIZACAO')
+ON CONFLICT (codigo) DO UPDATE SET codigo_sistema = EXCLUDED.codigo_sistema;
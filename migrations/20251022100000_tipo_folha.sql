@@ -0,0 +1,40 @@
+-- Tipo da folha: a coluna do 13º passa a identificar todos os tipos, com a sequência
+-- das complementares, e entra na identidade da folha junto com órgão, servidor e competência.
+
+-- 0 = mensal; 1 = adiantamento do 13º; 2 = parcela final do 13º; 3 = rescisão;
+-- 4 = complementar; 5 = férias
+ALTER TABLE public.cadastro_folha RENAME COLUMN decimo_terceiro TO tipo_folha;
+ALTER TABLE public.cadastro_folha DROP CONSTRAINT IF EXISTS cadastro_folha_decimo_terceiro_check;
+ALTER TABLE public.cadastro_folha
+    ADD CONSTRAINT cadastro_folha_tipo_folha_check CHECK (tipo_folha IN (0, 1, 2, 3, 4, 5));
+
+-- 1, 2, 3... nas complementares da competência; 0 nos demais tipos
+ALTER TABLE public.cadastro_folha ADD COLUMN IF NOT EXISTS sequencia SMALLINT NOT NULL DEFAULT 0;
+
+-- o índice antigo valia para todo tipo diferente de mensal
+DROP INDEX IF EXISTS public.idx_cadastro_folha_decimo_terceiro;
+
+-- folhas mensais repetidas na competência eram complementares lançadas como mensais:
+-- a primeira continua mensal e as outras viram complementares, na ordem de criação
+WITH repetidas AS (
+    SELECT id, ROW_NUMBER() OVER (
+        PARTITION BY orgao_id, servidor_id, ano, mes ORDER BY id) - 1 AS sequencia
+    FROM public.cadastro_folha
+    WHERE tipo_folha = 0
+)
+UPDATE public.cadastro_folha f SET tipo_folha = 4, sequencia = r.sequencia
+FROM repetidas r
+WHERE r.id = f.id AND r.sequencia > 0;
+
+ALTER TABLE public.cadastro_folha
+    ADD CONSTRAINT cadastro_folha_sequencia_check CHECK ((tipo_folha = 4) = (sequencia > 0));
+
+-- 13º e rescisão: uma de cada por servidor no ano
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cadastro_folha_anual
+    ON public.cadastro_folha(orgao_id, servidor_id, ano, tipo_folha)
+    WHERE tipo_folha IN (1, 2, 3);
+
+-- mensal, férias e cada complementar: uma por servidor na competência
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cadastro_folha_competencia
+    ON public.cadastro_folha(orgao_id, servidor_id, ano, mes, tipo_folha, sequencia)
+    WHERE tipo_folha IN (0, 4, 5);
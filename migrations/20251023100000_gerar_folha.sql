@@ -0,0 +1,32 @@
+-- Geração da folha: a folha mensal de uma competência é criada a partir da de outra, com
+-- os servidores ativos, as rubricas fixas e as alterações salariais pendentes.
+
+-- rubricas repetidas todo mês pelo mesmo valor; a do salário contratual recebe o salário
+-- da nova folha
+ALTER TABLE public.cadastro_rubrica ADD COLUMN IF NOT EXISTS fixa BOOLEAN NOT NULL DEFAULT false;
+UPDATE public.cadastro_rubrica SET fixa = true, codigo_sistema = 'SALARIO'
+WHERE codigo = '1' AND codigo_sistema IS NULL;
+
+-- novo salário a partir da competência; aplicado (folha_id preenchido) pela geração da
+-- folha dessa competência ou de uma posterior
+CREATE TABLE IF NOT EXISTS public.cadastro_alteracao_salarial (
+    id SERIAL PRIMARY KEY,
+    servidor_id INTEGER NOT NULL REFERENCES public.cadastro_servidor(id) ON DELETE CASCADE,
+    ano INTEGER NOT NULL,
+    mes INTEGER NOT NULL CHECK (mes BETWEEN 1 AND 12),
+    salario NUMERIC(15, 2) NOT NULL CHECK (salario > 0),
+    motivo VARCHAR(100) NOT NULL,
+    folha_id BIGINT REFERENCES public.cadastro_folha(id) ON DELETE SET NULL,
+    usuario VARCHAR(100),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT uk_alteracao_salarial UNIQUE (servidor_id, ano, mes)
+);
+
+CREATE INDEX IF NOT EXISTS idx_cadastro_alteracao_salarial_pendente
+    ON public.cadastro_alteracao_salarial(servidor_id) WHERE folha_id IS NULL;
+
+INSERT INTO public.core_permission (name, description, module_id)
+SELECT 'folha.gerar', 'Gerar a folha mensal a partir de outra competência', m.id
+FROM public.core_module m
+WHERE m.title = 'Folha'
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,4 @@
+-- O arquivo do lote de contra-cheques passa a ser gravado em disco e enviado aos poucos
+-- no download; a tabela guarda só o caminho. Lotes antigos ficam sem arquivo para baixar.
+ALTER TABLE public.cadastro_lote_contra_cheque ADD COLUMN IF NOT EXISTS caminho_arquivo TEXT;
+ALTER TABLE public.cadastro_lote_contra_cheque DROP COLUMN IF EXISTS arquivo;
@@ -0,0 +1,6 @@
+-- O cadastro de servidores passa a exigir usuário autenticado; gravar exige a permissão
+INSERT INTO public.core_permission (name, description, module_id)
+SELECT 'servidor.cadastrar', 'Incluir, alterar e excluir servidores e seus afastamentos', m.id
+FROM public.core_module m
+WHERE m.title = 'Folha'
+ON CONFLICT (name) DO NOTHING;
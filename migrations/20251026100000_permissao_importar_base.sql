@@ -0,0 +1,8 @@
+-- Importação das bases nacionais passa a exigir permissão de administração
+INSERT INTO public.core_module (title) VALUES ('Administração') ON CONFLICT (title) DO NOTHING;
+
+INSERT INTO public.core_permission (name, description, module_id)
+SELECT 'base.importar', 'Importar as bases nacionais de CEPs e de municípios do IBGE', m.id
+FROM public.core_module m
+WHERE m.title = 'Administração'
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,7 @@
+-- Cargos, setores, departamentos e tipos de vínculo passam a exigir usuário autenticado;
+-- gravar exige a permissão
+INSERT INTO public.core_permission (name, description, module_id)
+SELECT 'tabelas.cadastrar', 'Incluir, alterar e excluir cargos, setores, departamentos e tipos de vínculo', m.id
+FROM public.core_module m
+WHERE m.title = 'Folha'
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,10 @@
+-- Tipo da folha no contra-cheque emitido: a complementar ou as férias da mesma competência
+-- são outro documento e precisam de outro código de verificação.
+ALTER TABLE public.cadastro_contra_cheque_emissao
+    ADD COLUMN IF NOT EXISTS tipo_folha SMALLINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS sequencia SMALLINT NOT NULL DEFAULT 0;
+
+UPDATE public.cadastro_contra_cheque_emissao e
+SET tipo_folha = f.tipo_folha, sequencia = f.sequencia
+FROM public.cadastro_folha f
+WHERE f.id = e.folha_id;
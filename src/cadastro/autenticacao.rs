@@ -0,0 +1,90 @@
+/// Caminho público onde o contra-cheque é validado
+pub const CAMINHO_AUTENTICACAO: &str = "/autenticar-contra-cheque";
+
+/// Quantidade de caracteres do código de verificação (sem os separadores)
+const TAMANHO_CODIGO: usize = 15;
+
+/// Novo código de verificação aleatório, no formato "9939C.C06EB-75605"
+pub fn gerar_codigo() -> String {
+    let hex: String = rand::random::<[u8; 8]>().iter().map(|b| format!("{:02X}", b)).collect();
+    formatar_codigo(&hex[..TAMANHO_CODIGO])
+}
+
+fn formatar_codigo(codigo: &str) -> String {
+    format!("{}.{}-{}", &codigo[..5], &codigo[5..10], &codigo[10..])
+}
+
+/// Código digitado (ou lido do QR) no formato gravado; aceita minúsculas, espaços
+/// e o código sem os separadores
+pub fn normalizar_codigo(codigo: &str) -> Option<String> {
+    let limpo: String = codigo
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    if limpo.len() == TAMANHO_CODIGO && limpo.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(formatar_codigo(&limpo))
+    } else {
+        None
+    }
+}
+
+/// Endereço de validação impresso no contra-cheque e gravado no QR code
+pub fn url_autenticacao(codigo: &str) -> String {
+    let base = std::env::var("APP_URL").unwrap_or_else(|_| "http://localhost:2000".to_string());
+    format!("{}{}/{}", base.trim_end_matches('/'), CAMINHO_AUTENTICACAO, codigo)
+}
+
+/// Nome para exibição pública: mantém o primeiro nome e a inicial dos demais
+pub fn mascarar_nome(nome: &str) -> String {
+    nome.split_whitespace()
+        .enumerate()
+        .map(|(i, parte)| {
+            if i == 0 {
+                parte.to_string()
+            } else {
+                let mut chars = parte.chars();
+                let inicial = chars.next().unwrap_or_default();
+                format!("{}{}", inicial, "*".repeat(chars.count()))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gerar_codigo() {
+        let codigo = gerar_codigo();
+        assert_eq!(codigo.len(), 17);
+        assert_eq!(normalizar_codigo(&codigo), Some(codigo));
+
+        // nenhuma posição é fixa (o nibble de versão do UUID ficava sempre em '4')
+        let codigos: Vec<String> = (0..50).map(|_| gerar_codigo().replace(['.', '-'], "")).collect();
+        assert!(codigos.iter().any(|codigo| &codigo[13..14] != "4"));
+    }
+
+    #[test]
+    fn test_normalizar_codigo() {
+        assert_eq!(
+            normalizar_codigo(" 9939c.c06eb-75605 "),
+            Some("9939C.C06EB-75605".to_string())
+        );
+        assert_eq!(
+            normalizar_codigo("9939cc06eb75605"),
+            Some("9939C.C06EB-75605".to_string())
+        );
+        assert_eq!(normalizar_codigo("9939c.c06eb-7560"), None);
+        assert_eq!(normalizar_codigo("ZZZZZ.ZZZZZ-ZZZZZ"), None);
+    }
+
+    #[test]
+    fn test_mascarar_nome() {
+        assert_eq!(mascarar_nome("WILLIAM SANTOS COSTA"), "WILLIAM S***** C****");
+        assert_eq!(mascarar_nome("JOÃO DA SILVA"), "JOÃO D* S****");
+    }
+}
@@ -0,0 +1,500 @@
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use serde::Serialize;
+
+use crate::cadastro::model::{AcumuladoCompetencia, FaixaInss, FaixaIrrf, ParametroIrrf};
+
+/// Códigos de sistema das rubricas lançadas pelo cálculo (cadastro_rubrica.codigo_sistema)
+pub const SISTEMA_INSS: &str = "INSS";
+pub const SISTEMA_IRRF: &str = "IRRF";
+
+/// Modalidades de dedução do IRRF
+pub const IRRF_DEDUCOES_LEGAIS: &str = "LEGAL";
+pub const IRRF_DESCONTO_SIMPLIFICADO: &str = "SIMPLIFICADO";
+
+/// Arredonda para centavos (meio para cima)
+pub fn arredondar(valor: &BigDecimal) -> BigDecimal {
+    valor.with_scale_round(2, RoundingMode::HalfUp)
+}
+
+/// Parte da base que caiu em uma faixa da tabela
+#[derive(Debug, Serialize)]
+pub struct ParcelaInss {
+    pub faixa_inicial: BigDecimal,
+    pub faixa_final: BigDecimal,
+    pub aliquota: BigDecimal,
+    pub base: BigDecimal,
+    pub valor: BigDecimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResultadoInss {
+    /// Base informada (soma das rubricas que incidem INSS)
+    pub base: BigDecimal,
+    /// Base limitada ao teto da tabela
+    pub base_contribuicao: BigDecimal,
+    pub teto: BigDecimal,
+    pub desconto: BigDecimal,
+    pub aliquota_efetiva: BigDecimal,
+    pub parcelas: Vec<ParcelaInss>,
+}
+
+/// Aplica a tabela progressiva do INSS: cada faixa tributa somente a parte da base
+/// contida nela e o arredondamento é feito sobre o total.
+///
+/// As faixas devem estar ordenadas pelo limite; o último limite é o teto.
+pub fn calcular_inss(base: &BigDecimal, faixas: &[FaixaInss]) -> ResultadoInss {
+    let zero = BigDecimal::zero();
+    let cem = BigDecimal::from(100);
+
+    let teto = faixas
+        .last()
+        .map(|f| f.limite.clone())
+        .unwrap_or_else(BigDecimal::zero);
+
+    let base_contribuicao = if *base < zero {
+        zero.clone()
+    } else if *base > teto {
+        teto.clone()
+    } else {
+        base.clone()
+    };
+
+    let mut parcelas = Vec::new();
+    let mut total = BigDecimal::zero();
+    let mut anterior = BigDecimal::zero();
+
+    for faixa in faixas {
+        if base_contribuicao <= anterior {
+            break;
+        }
+
+        let limite = if base_contribuicao < faixa.limite {
+            base_contribuicao.clone()
+        } else {
+            faixa.limite.clone()
+        };
+        let base_faixa = &limite - &anterior;
+        let valor = &base_faixa * &faixa.aliquota / &cem;
+
+        total += &valor;
+        parcelas.push(ParcelaInss {
+            faixa_inicial: anterior.clone(),
+            faixa_final: faixa.limite.clone(),
+            aliquota: faixa.aliquota.clone(),
+            base: base_faixa,
+            valor: arredondar(&valor),
+        });
+
+        anterior = faixa.limite.clone();
+    }
+
+    let desconto = arredondar(&total);
+    let aliquota_efetiva = if base_contribuicao > zero {
+        arredondar(&(&desconto * &cem / &base_contribuicao))
+    } else {
+        zero
+    };
+
+    ResultadoInss {
+        base: base.clone(),
+        base_contribuicao,
+        teto,
+        desconto,
+        aliquota_efetiva,
+        parcelas,
+    }
+}
+
+#[derive(Debug)]
+pub struct EntradaIrrf {
+    /// Rendimentos tributáveis do mês
+    pub rendimento: BigDecimal,
+    /// Contribuição previdenciária oficial
+    pub inss: BigDecimal,
+    pub dependentes: i32,
+    pub pensao: BigDecimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResultadoIrrf {
+    pub rendimento: BigDecimal,
+    pub inss: BigDecimal,
+    pub dependentes: i32,
+    pub deducao_dependentes: BigDecimal,
+    pub pensao: BigDecimal,
+    pub deducoes_legais: BigDecimal,
+    pub desconto_simplificado: BigDecimal,
+    /// Modalidade escolhida (a de menor imposto)
+    pub modalidade: String,
+    /// Dedução efetivamente aplicada na base
+    pub deducao: BigDecimal,
+    pub base: BigDecimal,
+    pub aliquota: BigDecimal,
+    pub parcela_deduzir: BigDecimal,
+    /// Imposto pela tabela, antes da redução
+    pub imposto_tabela: BigDecimal,
+    pub reducao: BigDecimal,
+    pub imposto: BigDecimal,
+}
+
+/// Imposto da tabela progressiva para uma base: (alíquota, parcela a deduzir, imposto)
+fn imposto_tabela(base: &BigDecimal, faixas: &[FaixaIrrf]) -> (BigDecimal, BigDecimal, BigDecimal) {
+    let zero = BigDecimal::zero();
+    let faixa = faixas
+        .iter()
+        .find(|f| f.limite.as_ref().is_none_or(|limite| base <= limite))
+        .or(faixas.last());
+
+    match faixa {
+        Some(f) => {
+            let imposto = base * &f.aliquota / BigDecimal::from(100) - &f.deducao;
+            let imposto = if imposto < zero { zero } else { imposto };
+            (f.aliquota.clone(), f.deducao.clone(), imposto)
+        }
+        None => (zero.clone(), zero.clone(), zero),
+    }
+}
+
+/// Redução do imposto mensal (Lei 15.270/2025), calculada sobre os rendimentos tributáveis
+/// e limitada ao próprio imposto. Vigências sem os parâmetros não têm redução.
+fn reducao_irrf(rendimento: &BigDecimal, imposto: &BigDecimal, parametro: &ParametroIrrf) -> BigDecimal {
+    let zero = BigDecimal::zero();
+
+    let reducao = match (
+        &parametro.reducao_limite_isencao,
+        &parametro.reducao_valor_isencao,
+        &parametro.reducao_limite,
+        &parametro.reducao_constante,
+        &parametro.reducao_fator,
+    ) {
+        (Some(limite_isencao), Some(valor_isencao), _, _, _) if rendimento <= limite_isencao => {
+            valor_isencao.clone()
+        }
+        (_, _, Some(limite), Some(constante), Some(fator)) if rendimento <= limite => {
+            constante - fator * rendimento
+        }
+        _ => zero.clone(),
+    };
+
+    if reducao < zero {
+        zero
+    } else if reducao > *imposto {
+        imposto.clone()
+    } else {
+        reducao
+    }
+}
+
+/// Calcula o IRRF mensal pelas deduções legais (INSS, dependentes e pensão) e pelo desconto
+/// simplificado, ficando com a modalidade de menor imposto. Em caso de empate usa as
+/// deduções legais.
+pub fn calcular_irrf(
+    entrada: &EntradaIrrf,
+    faixas: &[FaixaIrrf],
+    parametro: &ParametroIrrf,
+) -> ResultadoIrrf {
+    let zero = BigDecimal::zero();
+
+    let deducao_dependentes = &parametro.deducao_dependente * BigDecimal::from(entrada.dependentes.max(0));
+    let deducoes_legais = &entrada.inss + &deducao_dependentes + &entrada.pensao;
+
+    let base_para = |deducao: &BigDecimal| {
+        let base = arredondar(&(&entrada.rendimento - deducao));
+        if base < zero { zero.clone() } else { base }
+    };
+
+    let base_legal = base_para(&deducoes_legais);
+    let base_simplificada = base_para(&parametro.desconto_simplificado);
+
+    let legal = imposto_tabela(&base_legal, faixas);
+    let simplificado = imposto_tabela(&base_simplificada, faixas);
+
+    let (modalidade, deducao, base, (aliquota, parcela_deduzir, imposto_tabela)) =
+        if simplificado.2 < legal.2 {
+            (
+                IRRF_DESCONTO_SIMPLIFICADO,
+                parametro.desconto_simplificado.clone(),
+                base_simplificada,
+                simplificado,
+            )
+        } else {
+            (IRRF_DEDUCOES_LEGAIS, deducoes_legais.clone(), base_legal, legal)
+        };
+
+    let reducao = reducao_irrf(&entrada.rendimento, &imposto_tabela, parametro);
+    let imposto = arredondar(&(&imposto_tabela - &reducao));
+
+    ResultadoIrrf {
+        rendimento: entrada.rendimento.clone(),
+        inss: entrada.inss.clone(),
+        dependentes: entrada.dependentes,
+        deducao_dependentes,
+        pensao: entrada.pensao.clone(),
+        deducoes_legais,
+        desconto_simplificado: parametro.desconto_simplificado.clone(),
+        modalidade: modalidade.to_string(),
+        deducao,
+        base,
+        aliquota,
+        parcela_deduzir,
+        imposto_tabela: arredondar(&imposto_tabela),
+        reducao: arredondar(&reducao),
+        imposto,
+    }
+}
+
+fn nao_negativo(valor: BigDecimal) -> BigDecimal {
+    if valor < BigDecimal::zero() { BigDecimal::zero().with_scale(2) } else { valor }
+}
+
+/// INSS de folha complementar ou de férias: o resultado calculado sobre as bases somadas
+/// da competência fica só com a parte que as folhas anteriores ainda não contribuíram
+pub fn descontar_inss_anterior(mut total: ResultadoInss, anterior: &AcumuladoCompetencia) -> ResultadoInss {
+    total.base_contribuicao = nao_negativo(&total.base_contribuicao - &anterior.base_inss);
+    total.desconto = nao_negativo(&total.desconto - &anterior.inss);
+    total
+}
+
+/// IRRF de folha complementar ou de férias: como no INSS, base, dedução e imposto ficam
+/// com a diferença para o que as folhas anteriores já gravaram e retiveram
+pub fn descontar_irrf_anterior(mut total: ResultadoIrrf, anterior: &AcumuladoCompetencia) -> ResultadoIrrf {
+    total.base = nao_negativo(&total.base - &anterior.base_irrf);
+    total.deducao = nao_negativo(&total.deducao - &anterior.ded_irrf);
+    total.imposto = nao_negativo(&total.imposto - &anterior.irrf);
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn dec(valor: &str) -> BigDecimal {
+        BigDecimal::from_str(valor).unwrap()
+    }
+
+    fn tabela_2025() -> Vec<FaixaInss> {
+        [("1518.00", "7.5"), ("2793.88", "9"), ("4190.83", "12"), ("8157.41", "14")]
+            .iter()
+            .enumerate()
+            .map(|(i, (limite, aliquota))| FaixaInss {
+                id: i as i32 + 1,
+                vigencia: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                limite: dec(limite),
+                aliquota: dec(aliquota),
+            })
+            .collect()
+    }
+
+    fn vigencia(ano: i32, mes: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(ano, mes, 1).unwrap()
+    }
+
+    fn tabela_irrf(ano: i32, mes: u32, isencao: &str, deducoes: [&str; 4]) -> Vec<FaixaIrrf> {
+        let limites = [Some(isencao), Some("2826.65"), Some("3751.05"), Some("4664.68"), None];
+        let aliquotas = ["0", "7.5", "15", "22.5", "27.5"];
+        let deducoes = ["0", deducoes[0], deducoes[1], deducoes[2], deducoes[3]];
+
+        (0..5)
+            .map(|i| FaixaIrrf {
+                id: i as i32 + 1,
+                vigencia: vigencia(ano, mes),
+                limite: limites[i].map(dec),
+                aliquota: dec(aliquotas[i]),
+                deducao: dec(deducoes[i]),
+            })
+            .collect()
+    }
+
+    fn parametro_irrf(ano: i32, mes: u32, simplificado: &str, com_reducao: bool) -> ParametroIrrf {
+        let reducao = |valor: &str| com_reducao.then(|| dec(valor));
+        ParametroIrrf {
+            id: 1,
+            vigencia: vigencia(ano, mes),
+            deducao_dependente: dec("189.59"),
+            desconto_simplificado: dec(simplificado),
+            reducao_limite_isencao: reducao("5000.00"),
+            reducao_valor_isencao: reducao("312.89"),
+            reducao_limite: reducao("7350.00"),
+            reducao_constante: reducao("978.62"),
+            reducao_fator: reducao("0.133145"),
+        }
+    }
+
+    /// Tabela vigente de 02/2024 a 04/2025
+    fn irrf_2024() -> (Vec<FaixaIrrf>, ParametroIrrf) {
+        (
+            tabela_irrf(2024, 2, "2259.20", ["169.44", "381.44", "662.77", "896.00"]),
+            parametro_irrf(2024, 2, "564.80", false),
+        )
+    }
+
+    /// Tabela vigente de 05/2025 a 12/2025
+    fn irrf_2025() -> (Vec<FaixaIrrf>, ParametroIrrf) {
+        (
+            tabela_irrf(2025, 5, "2428.80", ["182.16", "394.16", "675.49", "908.73"]),
+            parametro_irrf(2025, 5, "607.20", false),
+        )
+    }
+
+    /// Tabela de 2026, com a redução da Lei 15.270/2025
+    fn irrf_2026() -> (Vec<FaixaIrrf>, ParametroIrrf) {
+        (
+            tabela_irrf(2026, 1, "2428.80", ["182.16", "394.16", "675.49", "908.73"]),
+            parametro_irrf(2026, 1, "607.20", true),
+        )
+    }
+
+    fn entrada(rendimento: &str, inss: &str, dependentes: i32, pensao: &str) -> EntradaIrrf {
+        EntradaIrrf {
+            rendimento: dec(rendimento),
+            inss: dec(inss),
+            dependentes,
+            pensao: dec(pensao),
+        }
+    }
+
+    #[test]
+    fn test_inss_primeira_faixa() {
+        let res = calcular_inss(&dec("1518.00"), &tabela_2025());
+        assert_eq!(res.desconto, dec("113.85"));
+        assert_eq!(res.parcelas.len(), 1);
+    }
+
+    #[test]
+    fn test_inss_progressivo() {
+        // 113,85 + 114,8292 + 24,7344
+        let res = calcular_inss(&dec("3000.00"), &tabela_2025());
+        assert_eq!(res.desconto, dec("253.41"));
+        assert_eq!(res.parcelas.len(), 3);
+        assert_eq!(res.aliquota_efetiva, dec("8.45"));
+    }
+
+    #[test]
+    fn test_inss_teto() {
+        let res = calcular_inss(&dec("12000.00"), &tabela_2025());
+        assert_eq!(res.base_contribuicao, dec("8157.41"));
+        assert_eq!(res.desconto, dec("951.63"));
+    }
+
+    #[test]
+    fn test_inss_complementar() {
+        // mensal de 3.000,00 já reteve 253,41; a complementar de 1.000,00 paga a
+        // diferença do INSS sobre 4.000,00
+        let mensal = calcular_inss(&dec("3000.00"), &tabela_2025());
+        let anterior = AcumuladoCompetencia {
+            base_inss: mensal.base_contribuicao.clone(),
+            inss: mensal.desconto.clone(),
+            ..Default::default()
+        };
+        let res = descontar_inss_anterior(calcular_inss(&dec("4000.00"), &tabela_2025()), &anterior);
+        assert_eq!(res.base_contribuicao, dec("1000.00"));
+        assert_eq!(res.desconto, dec("120.00"));
+
+        // mensal já no teto: a complementar não contribui
+        let teto = calcular_inss(&dec("9000.00"), &tabela_2025());
+        let anterior = AcumuladoCompetencia {
+            base_inss: teto.base_contribuicao.clone(),
+            inss: teto.desconto.clone(),
+            ..Default::default()
+        };
+        let res = descontar_inss_anterior(calcular_inss(&dec("10000.00"), &tabela_2025()), &anterior);
+        assert_eq!(res.base_contribuicao, dec("0.00"));
+        assert_eq!(res.desconto, dec("0.00"));
+    }
+
+    #[test]
+    fn test_inss_sem_base() {
+        let res = calcular_inss(&BigDecimal::zero(), &tabela_2025());
+        assert_eq!(res.desconto, BigDecimal::zero());
+        assert!(res.parcelas.is_empty());
+    }
+
+    #[test]
+    fn test_irrf_2024_simplificado() {
+        let (faixas, parametro) = irrf_2024();
+        let res = calcular_irrf(&entrada("3000.00", "0", 1, "0"), &faixas, &parametro);
+        assert_eq!(res.modalidade, IRRF_DESCONTO_SIMPLIFICADO);
+        assert_eq!(res.base, dec("2435.20"));
+        assert_eq!(res.imposto, dec("13.20"));
+    }
+
+    #[test]
+    fn test_irrf_2024_deducoes_legais() {
+        // 10.000 - (908,86 + 2 x 189,59 + 1.000) = 7.711,96
+        let (faixas, parametro) = irrf_2024();
+        let res = calcular_irrf(&entrada("10000.00", "908.86", 2, "1000.00"), &faixas, &parametro);
+        assert_eq!(res.modalidade, IRRF_DEDUCOES_LEGAIS);
+        assert_eq!(res.base, dec("7711.96"));
+        assert_eq!(res.deducao, dec("2288.04"));
+        assert_eq!(res.imposto, dec("1224.79"));
+    }
+
+    #[test]
+    fn test_irrf_2025_limites_das_faixas() {
+        let (faixas, parametro) = irrf_2025();
+        let simplificado = &parametro.desconto_simplificado;
+        let imposto = |base: &str| {
+            let rendimento = dec(base) + simplificado;
+            calcular_irrf(
+                &EntradaIrrf {
+                    rendimento,
+                    inss: BigDecimal::zero(),
+                    dependentes: 0,
+                    pensao: BigDecimal::zero(),
+                },
+                &faixas,
+                &parametro,
+            )
+        };
+
+        assert_eq!(imposto("2428.80").imposto, dec("0.00"));
+        assert_eq!(imposto("2428.81").aliquota, dec("7.5"));
+        assert_eq!(imposto("2826.65").imposto, dec("29.84"));
+        assert_eq!(imposto("2826.66").aliquota, dec("15"));
+        assert_eq!(imposto("4664.68").aliquota, dec("22.5"));
+        assert_eq!(imposto("4664.69").aliquota, dec("27.5"));
+    }
+
+    #[test]
+    fn test_irrf_mudanca_de_tabela_em_2025() {
+        // mesmo rendimento: tributado até 04/2025, isento a partir de 05/2025
+        let (faixas, parametro) = irrf_2024();
+        let res = calcular_irrf(&entrada("3000.00", "0", 1, "0"), &faixas, &parametro);
+        assert_eq!(res.imposto, dec("13.20"));
+
+        let (faixas, parametro) = irrf_2025();
+        let res = calcular_irrf(&entrada("3000.00", "0", 1, "0"), &faixas, &parametro);
+        assert_eq!(res.imposto, dec("0.00"));
+    }
+
+    #[test]
+    fn test_irrf_2026_reducao_ate_5000() {
+        // sem a redução o imposto seria 312,89 (desconto simplificado)
+        let (faixas, parametro) = irrf_2026();
+        let res = calcular_irrf(&entrada("5000.00", "501.51", 0, "0"), &faixas, &parametro);
+        assert_eq!(res.imposto_tabela, dec("312.89"));
+        assert_eq!(res.reducao, dec("312.89"));
+        assert_eq!(res.imposto, dec("0.00"));
+    }
+
+    #[test]
+    fn test_irrf_2026_reducao_parcial() {
+        // redução de 978,62 - 0,133145 x 6.000 = 179,75
+        let (faixas, parametro) = irrf_2026();
+        let res = calcular_irrf(&entrada("6000.00", "0", 0, "0"), &faixas, &parametro);
+        assert_eq!(res.imposto_tabela, dec("574.29"));
+        assert_eq!(res.reducao, dec("179.75"));
+        assert_eq!(res.imposto, dec("394.54"));
+    }
+
+    #[test]
+    fn test_irrf_2026_acima_do_limite_da_reducao() {
+        let (faixas, parametro) = irrf_2026();
+        let res = calcular_irrf(&entrada("7350.01", "0", 0, "0"), &faixas, &parametro);
+        assert_eq!(res.reducao, dec("0.00"));
+    }
+}
@@ -0,0 +1,805 @@
+use anyhow::Result;
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::{
+    cadastro::model::{
+        CNAB_BANCO_DO_BRASIL, CNAB_CAIXA, CNAB_FEBRABAN, CONTA_POUPANCA, CREDITO_AGENDADO,
+        CREDITO_ENVIADO, CREDITO_PAGO, CREDITO_REJEITADO,
+    },
+    error::AppError,
+    utils::{
+        pdf::letra_base,
+        validator::{cpf_valido, somente_digitos},
+    },
+};
+
+/// Tamanho dos registros do CNAB 240
+pub const TAMANHO_REGISTRO: usize = 240;
+
+pub const BANCO_DO_BRASIL: &str = "001";
+pub const CAIXA: &str = "104";
+
+/// Tipo de serviço do lote: pagamento de salários
+const SERVICO_SALARIOS: &str = "30";
+
+/// Formas de lançamento, na ordem em que os lotes são gravados
+const CREDITO_CONTA_CORRENTE: &str = "01";
+const CREDITO_POUPANCA: &str = "05";
+const TED_OUTRA_TITULARIDADE: &str = "41";
+
+/// Câmara centralizadora: crédito no próprio banco ou TED (STR)
+const CAMARA_CONTA: &str = "000";
+const CAMARA_TED: &str = "018";
+
+/// Finalidade da TED: pagamento de salários
+const FINALIDADE_TED_SALARIOS: &str = "00004";
+
+/// Variação do leiaute escolhida na geração; o padrão FEBRABAN serve aos demais bancos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Febraban,
+    BancoDoBrasil,
+    Caixa,
+}
+
+impl Layout {
+    pub fn from_codigo(codigo: &str) -> Option<Self> {
+        match codigo {
+            CNAB_FEBRABAN => Some(Self::Febraban),
+            CNAB_BANCO_DO_BRASIL => Some(Self::BancoDoBrasil),
+            CNAB_CAIXA => Some(Self::Caixa),
+            _ => None,
+        }
+    }
+
+    pub fn codigo(self) -> &'static str {
+        match self {
+            Self::Febraban => CNAB_FEBRABAN,
+            Self::BancoDoBrasil => CNAB_BANCO_DO_BRASIL,
+            Self::Caixa => CNAB_CAIXA,
+        }
+    }
+
+    /// Banco da conta de débito exigido pela variação
+    pub fn banco(self) -> Option<&'static str> {
+        match self {
+            Self::Febraban => None,
+            Self::BancoDoBrasil => Some(BANCO_DO_BRASIL),
+            Self::Caixa => Some(CAIXA),
+        }
+    }
+
+    /// Versões do leiaute do arquivo e do lote. O Banco do Brasil não critica o campo
+    /// e aceita zeros
+    fn versoes(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Febraban => ("089", "046"),
+            Self::BancoDoBrasil => ("000", "000"),
+            Self::Caixa => ("080", "041"),
+        }
+    }
+
+    /// O que há de errado no convênio para a variação: no Banco do Brasil é o número
+    /// do convênio de pagamentos (até 9 dígitos); na Caixa, o convênio (6 dígitos)
+    /// seguido do código do compromisso (4 dígitos)
+    pub fn erro_convenio(self, convenio: &str) -> Option<String> {
+        let digitos = somente_digitos(convenio);
+        match self {
+            Self::Febraban if convenio.chars().count() > 20 => {
+                Some("Convênio deve ter até 20 caracteres".to_string())
+            }
+            Self::BancoDoBrasil if digitos.is_empty() || digitos.len() > 9 || digitos.len() != convenio.len() => {
+                Some("Convênio do Banco do Brasil deve ter até 9 dígitos".to_string())
+            }
+            Self::Caixa if digitos.len() != 10 || digitos.len() != convenio.len() => Some(
+                "Convênio da Caixa deve ter 10 dígitos: convênio (6) e código do compromisso (4)"
+                    .to_string(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Órgão pagador: conta de débito e convênio no banco
+#[derive(Debug, Clone)]
+pub struct Pagador {
+    pub banco: String,
+    pub cnpj: String,
+    pub convenio: String,
+    /// Agência e conta como cadastradas, com o DV depois do hífen
+    pub agencia: String,
+    pub conta: String,
+    pub nome: String,
+    pub logradouro: String,
+    pub numero: String,
+    pub complemento: String,
+    pub cidade: String,
+    pub cep: String,
+    pub uf: String,
+}
+
+/// Líquido de uma folha a creditar na conta do servidor
+#[derive(Debug, Clone)]
+pub struct Credito {
+    pub folha_id: i64,
+    pub servidor_id: i32,
+    pub nome: String,
+    pub cpf: Option<String>,
+    pub banco: Option<String>,
+    pub agencia: Option<String>,
+    pub conta: Option<String>,
+    pub tipo_conta: Option<String>,
+    pub logradouro: Option<String>,
+    pub numero: Option<String>,
+    pub complemento: Option<String>,
+    pub bairro: Option<String>,
+    pub cidade: Option<String>,
+    pub cep: Option<String>,
+    pub uf: Option<String>,
+    pub valor: BigDecimal,
+}
+
+impl Credito {
+    /// "Seu número" do pagamento, devolvido pelo banco no retorno: a folha paga
+    pub fn seu_numero(&self) -> String {
+        self.folha_id.to_string()
+    }
+
+    /// O que impede o pagamento: líquido zerado ou negativo, CPF inválido ou dados
+    /// bancários incompletos
+    pub fn pendencia(&self) -> Option<String> {
+        if self.valor <= BigDecimal::zero() {
+            return Some("sem líquido a pagar".to_string());
+        }
+        if !self.cpf.as_deref().is_some_and(cpf_valido) {
+            return Some("CPF inválido".to_string());
+        }
+        let banco = self.banco.as_deref().unwrap_or_default();
+        if banco.len() != 3 || somente_digitos(banco) != banco {
+            return Some("sem banco".to_string());
+        }
+        let (agencia, agencia_dv) = agencia_dv(self.agencia.as_deref().unwrap_or_default());
+        if agencia.is_empty() || agencia.len() > 5 || agencia_dv.chars().count() > 1 {
+            return Some("agência inválida".to_string());
+        }
+        let (conta, conta_dv) = conta_dv(self.conta.as_deref().unwrap_or_default());
+        if conta.is_empty() || conta.len() > 12 || conta_dv.chars().count() != 1 {
+            return Some("conta inválida".to_string());
+        }
+        None
+    }
+
+    /// Forma de lançamento: crédito em conta corrente ou poupança no banco do órgão,
+    /// TED nos demais bancos
+    fn forma(&self, banco_pagador: &str) -> &'static str {
+        if self.banco.as_deref() != Some(banco_pagador) {
+            TED_OUTRA_TITULARIDADE
+        } else if self.tipo_conta.as_deref() == Some(CONTA_POUPANCA) {
+            CREDITO_POUPANCA
+        } else {
+            CREDITO_CONTA_CORRENTE
+        }
+    }
+}
+
+/// Agência e DV ("1234-5"); sem hífen, a agência fica sem DV
+pub fn agencia_dv(valor: &str) -> (String, String) {
+    match valor.rsplit_once('-') {
+        Some((numero, dv)) => (somente_digitos(numero), dv.trim().to_uppercase()),
+        None => (somente_digitos(valor), String::new()),
+    }
+}
+
+/// Conta e DV ("12345-6"); sem hífen, o último dígito é o DV
+pub fn conta_dv(valor: &str) -> (String, String) {
+    match valor.rsplit_once('-') {
+        Some((numero, dv)) => (somente_digitos(numero), dv.trim().to_uppercase()),
+        None => {
+            let mut numero = somente_digitos(valor);
+            let dv = numero.pop().map(String::from).unwrap_or_default();
+            (numero, dv)
+        }
+    }
+}
+
+/// Valor em centavos, sem separador
+fn centavos(valor: &BigDecimal) -> String {
+    let (inteiro, _) = valor
+        .with_scale_round(2, RoundingMode::HalfUp)
+        .into_bigint_and_exponent();
+    inteiro.to_string()
+}
+
+fn nome_banco(codigo: &str) -> &'static str {
+    match codigo {
+        BANCO_DO_BRASIL => "BANCO DO BRASIL S.A.",
+        CAIXA => "CAIXA ECONOMICA FEDERAL",
+        _ => "",
+    }
+}
+
+/// Registro de 240 posições montado campo a campo
+struct Registro(String);
+
+impl Registro {
+    fn new() -> Self {
+        Self(String::with_capacity(TAMANHO_REGISTRO))
+    }
+
+    /// Alfanumérico: maiúsculas sem acento, à esquerda, completado com brancos
+    fn alfa(&mut self, valor: &str, tamanho: usize) -> &mut Self {
+        let texto: String = valor
+            .chars()
+            .map(|c| letra_base(c).to_ascii_uppercase())
+            .map(|c| if c.is_ascii() && !c.is_ascii_control() { c } else { ' ' })
+            .take(tamanho)
+            .collect();
+        self.0.push_str(&format!("{:<tamanho$}", texto));
+        self
+    }
+
+    /// Numérico: só os dígitos, à direita, completado com zeros. Os valores são
+    /// conferidos antes; contadores maiores que o campo mantêm os últimos dígitos
+    fn num(&mut self, valor: &str, tamanho: usize) -> &mut Self {
+        let digitos = somente_digitos(valor);
+        let digitos = &digitos[digitos.len().saturating_sub(tamanho)..];
+        self.0.push_str(&format!("{:0>tamanho$}", digitos));
+        self
+    }
+
+    fn brancos(&mut self, tamanho: usize) -> &mut Self {
+        self.alfa("", tamanho)
+    }
+
+    fn controle(&mut self, banco: &str, lote: usize, tipo: &str) -> &mut Self {
+        self.num(banco, 3).num(&lote.to_string(), 4).alfa(tipo, 1)
+    }
+
+    /// Agência, conta e DVs (20 posições)
+    fn conta(&mut self, agencia: &str, conta: &str) -> &mut Self {
+        let (agencia, agencia_dv) = agencia_dv(agencia);
+        let (conta, conta_dv) = conta_dv(conta);
+        self.num(&agencia, 5)
+            .alfa(&agencia_dv, 1)
+            .num(&conta, 12)
+            .alfa(&conta_dv, 1)
+            .brancos(1)
+    }
+
+    fn fim(&mut self) -> String {
+        debug_assert_eq!(self.0.len(), TAMANHO_REGISTRO);
+        std::mem::take(&mut self.0)
+    }
+}
+
+/// Convênio nas posições 33-52 dos headers
+fn convenio(layout: Layout, convenio: &str, lote: bool) -> String {
+    let mut registro = Registro::new();
+    match layout {
+        Layout::Febraban => registro.alfa(convenio, 20),
+        // convênio, produto 0126 (pagamentos) e brancos
+        Layout::BancoDoBrasil => registro.num(convenio, 9).num("0126", 4).brancos(7),
+        Layout::Caixa => {
+            let (convenio, compromisso) = convenio.split_at(convenio.len().min(6));
+            if lote {
+                // tipo de compromisso 02 (salários), compromisso e parâmetro de transmissão
+                registro.num(convenio, 6).num("02", 2).num(compromisso, 4).num("01", 2).brancos(6)
+            } else {
+                // parâmetro de transmissão, ambiente de produção e versão do aplicativo
+                registro.num(convenio, 6).num("01", 2).alfa("P", 1).brancos(4).num("0", 4).brancos(3)
+            }
+        }
+    };
+    registro.0
+}
+
+/// Arquivo de remessa CNAB 240 de pagamento de salários: um lote por forma de
+/// lançamento, com os segmentos A e B de cada crédito. Os créditos chegam já sem
+/// pendência; as linhas terminam em CRLF
+pub fn remessa(
+    layout: Layout,
+    pagador: &Pagador,
+    nsa: i32,
+    gerado_em: NaiveDateTime,
+    data_pagamento: NaiveDate,
+    creditos: &[Credito],
+) -> String {
+    let banco = pagador.banco.as_str();
+    let (versao_arquivo, versao_lote) = layout.versoes();
+    let data_pagamento = data_pagamento.format("%d%m%Y").to_string();
+    let mut linhas = Vec::new();
+
+    linhas.push(
+        Registro::new()
+            .controle(banco, 0, "0")
+            .brancos(9)
+            .num("2", 1)
+            .num(&pagador.cnpj, 14)
+            .alfa(&convenio(layout, &pagador.convenio, false), 20)
+            .conta(&pagador.agencia, &pagador.conta)
+            .alfa(&pagador.nome, 30)
+            .alfa(nome_banco(banco), 30)
+            .brancos(10)
+            .num("1", 1)
+            .num(&gerado_em.format("%d%m%Y").to_string(), 8)
+            .num(&gerado_em.format("%H%M%S").to_string(), 6)
+            .num(&nsa.to_string(), 6)
+            .num(versao_arquivo, 3)
+            .num("0", 5)
+            .brancos(20)
+            .brancos(20)
+            .brancos(29)
+            .fim(),
+    );
+
+    let mut lotes = 0;
+    for forma in [CREDITO_CONTA_CORRENTE, CREDITO_POUPANCA, TED_OUTRA_TITULARIDADE] {
+        let do_lote: Vec<&Credito> = creditos.iter().filter(|c| c.forma(banco) == forma).collect();
+        if do_lote.is_empty() {
+            continue;
+        }
+        lotes += 1;
+        let camara = if forma == TED_OUTRA_TITULARIDADE { CAMARA_TED } else { CAMARA_CONTA };
+        let finalidade_ted = if forma == TED_OUTRA_TITULARIDADE { FINALIDADE_TED_SALARIOS } else { "" };
+
+        linhas.push(
+            Registro::new()
+                .controle(banco, lotes, "1")
+                .alfa("C", 1)
+                .num(SERVICO_SALARIOS, 2)
+                .num(forma, 2)
+                .num(versao_lote, 3)
+                .brancos(1)
+                .num("2", 1)
+                .num(&pagador.cnpj, 14)
+                .alfa(&convenio(layout, &pagador.convenio, true), 20)
+                .conta(&pagador.agencia, &pagador.conta)
+                .alfa(&pagador.nome, 30)
+                .brancos(40)
+                .alfa(&pagador.logradouro, 30)
+                .num(&pagador.numero, 5)
+                .alfa(&pagador.complemento, 15)
+                .alfa(&pagador.cidade, 20)
+                .num(&pagador.cep, 8)
+                .alfa(&pagador.uf, 2)
+                .alfa(if layout == Layout::Febraban { "01" } else { "" }, 2)
+                .brancos(6)
+                .brancos(10)
+                .fim(),
+        );
+
+        let mut sequencial = 0;
+        let mut total = BigDecimal::zero();
+        for credito in &do_lote {
+            let valor = centavos(&credito.valor);
+            total += &credito.valor;
+
+            sequencial += 1;
+            linhas.push(
+                Registro::new()
+                    .controle(banco, lotes, "3")
+                    .num(&sequencial.to_string(), 5)
+                    .alfa("A", 1)
+                    .num("0", 1)
+                    .num("00", 2)
+                    .num(camara, 3)
+                    .num(credito.banco.as_deref().unwrap_or_default(), 3)
+                    .conta(
+                        credito.agencia.as_deref().unwrap_or_default(),
+                        credito.conta.as_deref().unwrap_or_default(),
+                    )
+                    .alfa(&credito.nome, 30)
+                    .alfa(&credito.seu_numero(), 20)
+                    .num(&data_pagamento, 8)
+                    .alfa("BRL", 3)
+                    .num("0", 15)
+                    .num(&valor, 15)
+                    .brancos(20)
+                    .num("0", 8)
+                    .num("0", 15)
+                    .brancos(40)
+                    .brancos(2)
+                    .alfa(finalidade_ted, 5)
+                    .brancos(2)
+                    .brancos(3)
+                    .num("0", 1)
+                    .brancos(10)
+                    .fim(),
+            );
+
+            sequencial += 1;
+            linhas.push(
+                Registro::new()
+                    .controle(banco, lotes, "3")
+                    .num(&sequencial.to_string(), 5)
+                    .alfa("B", 1)
+                    .brancos(3)
+                    .num("1", 1)
+                    .num(credito.cpf.as_deref().unwrap_or_default(), 14)
+                    .alfa(credito.logradouro.as_deref().unwrap_or_default(), 30)
+                    .num(credito.numero.as_deref().unwrap_or_default(), 5)
+                    .alfa(credito.complemento.as_deref().unwrap_or_default(), 15)
+                    .alfa(credito.bairro.as_deref().unwrap_or_default(), 15)
+                    .alfa(credito.cidade.as_deref().unwrap_or_default(), 20)
+                    .num(credito.cep.as_deref().unwrap_or_default(), 8)
+                    .alfa(credito.uf.as_deref().unwrap_or_default(), 2)
+                    .num(&data_pagamento, 8)
+                    .num(&valor, 15)
+                    .num("0", 15)
+                    .num("0", 15)
+                    .num("0", 15)
+                    .num("0", 15)
+                    .brancos(15)
+                    .num("0", 1)
+                    .brancos(6)
+                    .num("0", 8)
+                    .fim(),
+            );
+        }
+
+        linhas.push(
+            Registro::new()
+                .controle(banco, lotes, "5")
+                .brancos(9)
+                .num(&(sequencial + 2).to_string(), 6)
+                .num(&centavos(&total), 18)
+                .num("0", 18)
+                .num("0", 6)
+                .brancos(165)
+                .brancos(10)
+                .fim(),
+        );
+    }
+
+    let registros = linhas.len() + 1;
+    linhas.push(
+        Registro::new()
+            .controle(banco, 9999, "9")
+            .brancos(9)
+            .num(&lotes.to_string(), 6)
+            .num(&registros.to_string(), 6)
+            .num("0", 6)
+            .brancos(205)
+            .fim(),
+    );
+
+    let mut arquivo = linhas.join("\r\n");
+    arquivo.push_str("\r\n");
+    arquivo
+}
+
+/// Ocorrências do retorno que efetivam o crédito
+const OCORRENCIAS_PAGO: [&str; 2] = ["00", "03"];
+
+/// Ocorrências de inclusão aceita, com o crédito agendado para a data de pagamento
+const OCORRENCIAS_AGENDADO: [&str; 2] = ["BD", "BE"];
+
+/// Ocorrências mais comuns da tabela G059 da FEBRABAN
+const OCORRENCIAS: [(&str, &str); 30] = [
+    ("00", "Crédito efetivado"),
+    ("01", "Insuficiência de fundos"),
+    ("02", "Crédito cancelado pelo pagador"),
+    ("03", "Débito autorizado pela agência"),
+    ("AA", "Controle inválido"),
+    ("AC", "Tipo de serviço inválido"),
+    ("AD", "Forma de lançamento inválida"),
+    ("AE", "Tipo/número de inscrição inválido"),
+    ("AF", "Código de convênio inválido"),
+    ("AG", "Agência/conta corrente/DV inválido"),
+    ("AH", "Nº sequencial do registro no lote inválido"),
+    ("AK", "Câmara de compensação do favorecido inválida"),
+    ("AL", "Banco do favorecido inválido"),
+    ("AM", "Agência do favorecido inválida"),
+    ("AN", "Conta corrente/DV do favorecido inválido"),
+    ("AO", "Nome do favorecido não informado"),
+    ("AP", "Data do lançamento inválida"),
+    ("AR", "Valor do lançamento inválido"),
+    ("AS", "Aviso ao favorecido: identificação inválida"),
+    ("BD", "Inclusão efetuada com sucesso (agendado)"),
+    ("BE", "Alteração efetuada com sucesso"),
+    ("CA", "Código de barras inválido"),
+    ("HA", "Lote não aceito"),
+    ("HB", "Inscrição da empresa inválida para o contrato"),
+    ("HC", "Convênio inexistente ou inválido para o contrato"),
+    ("HD", "Agência/conta da empresa inválida para o contrato"),
+    ("HE", "Tipo de serviço inválido para o contrato"),
+    ("HF", "Conta da empresa com saldo insuficiente"),
+    ("TA", "Lote não aceito: totais com diferença"),
+    ("ZA", "Agência/conta do favorecido substituída"),
+];
+
+pub fn descricao_ocorrencia(codigo: &str) -> &'static str {
+    OCORRENCIAS
+        .iter()
+        .find(|(c, _)| *c == codigo)
+        .map(|(_, descricao)| *descricao)
+        .unwrap_or("Ocorrência não cadastrada")
+}
+
+/// Crédito como devolvido pelo banco no segmento A do retorno
+#[derive(Debug, Clone)]
+pub struct CreditoRetorno {
+    pub seu_numero: String,
+    pub valor: BigDecimal,
+    pub data_efetivacao: Option<NaiveDate>,
+    pub valor_pago: Option<BigDecimal>,
+    /// Códigos de ocorrência do segmento ou, sem eles, os do header do lote
+    pub ocorrencias: Vec<String>,
+}
+
+impl CreditoRetorno {
+    /// Situação pelas ocorrências: efetivado, agendado ou rejeitado; sem ocorrência o
+    /// crédito continua como enviado
+    pub fn situacao(&self) -> &'static str {
+        let tem = |codigos: &[&str]| self.ocorrencias.iter().any(|o| codigos.contains(&o.as_str()));
+        if self.ocorrencias.is_empty() {
+            CREDITO_ENVIADO
+        } else if tem(&OCORRENCIAS_PAGO) {
+            CREDITO_PAGO
+        } else if self.ocorrencias.iter().all(|o| OCORRENCIAS_AGENDADO.contains(&o.as_str())) {
+            CREDITO_AGENDADO
+        } else {
+            CREDITO_REJEITADO
+        }
+    }
+
+    /// Valor efetivado: o pago informado pelo banco ou, sem ele, o do pagamento quando
+    /// o crédito foi efetivado
+    pub fn valor_efetivado(&self) -> Option<BigDecimal> {
+        self.valor_pago
+            .clone()
+            .or_else(|| (self.situacao() == CREDITO_PAGO).then(|| self.valor.clone()))
+    }
+
+    /// "AG - Agência/conta corrente/DV inválido; AN - ..."
+    pub fn mensagem(&self) -> String {
+        self.ocorrencias
+            .iter()
+            .map(|o| format!("{} - {}", o, descricao_ocorrencia(o)))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Arquivo de retorno: banco e CNPJ do header e os créditos de todos os lotes
+#[derive(Debug)]
+pub struct Retorno {
+    pub banco: String,
+    pub cnpj: String,
+    pub creditos: Vec<CreditoRetorno>,
+}
+
+/// Campo do registro pelas posições (a partir de 0, fim exclusivo); os bytes fora do
+/// ASCII (acentos em Latin-1) valem um caractere cada
+fn campo(linha: &[u8], inicio: usize, fim: usize) -> String {
+    linha[inicio..fim].iter().map(|&b| b as char).collect()
+}
+
+fn ocorrencias(linha: &[u8]) -> Vec<String> {
+    linha[230..240]
+        .chunks(2)
+        .map(|c| c.iter().map(|&b| b as char).collect::<String>().trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+fn valor_centavos(campo: &str) -> Option<BigDecimal> {
+    campo.trim().parse::<i64>().ok().map(|centavos| BigDecimal::new(centavos.into(), 2))
+}
+
+/// Lê o retorno CNAB 240 de pagamentos: header de arquivo, headers de lote e segmentos A
+pub fn ler_retorno(conteudo: &[u8]) -> Result<Retorno> {
+    let mut linhas = conteudo
+        .split(|&b| b == b'\n')
+        .map(|l| l.strip_suffix(b"\r").unwrap_or(l))
+        .enumerate()
+        .filter(|(_, l)| !l.iter().all(u8::is_ascii_whitespace));
+
+    let erro = |numero: usize, mensagem: &str| AppError::InvalidInput(format!("Linha {}: {}", numero + 1, mensagem));
+    let registro = |numero: usize, linha: &[u8]| -> Result<()> {
+        if linha.len() < TAMANHO_REGISTRO {
+            return Err(erro(numero, "registro com menos de 240 posições; o arquivo não é CNAB 240").into());
+        }
+        Ok(())
+    };
+
+    let (numero, header) = linhas
+        .next()
+        .ok_or_else(|| AppError::InvalidInput("Arquivo de retorno vazio".to_string()))?;
+    registro(numero, header)?;
+    if header[7] != b'0' {
+        return Err(erro(numero, "o arquivo não começa pelo header de arquivo").into());
+    }
+    if header[142] != b'2' {
+        return Err(AppError::InvalidInput(
+            "O arquivo é uma remessa, não um retorno do banco".to_string(),
+        )
+        .into());
+    }
+    let mut retorno = Retorno {
+        banco: campo(header, 0, 3),
+        cnpj: campo(header, 18, 32),
+        creditos: Vec::new(),
+    };
+
+    let mut ocorrencias_lote = Vec::new();
+    for (numero, linha) in linhas {
+        registro(numero, linha)?;
+        match (linha[7], linha[13]) {
+            (b'1', _) => ocorrencias_lote = ocorrencias(linha),
+            (b'3', b'A') => {
+                let mut ocorrencias = ocorrencias(linha);
+                if ocorrencias.is_empty() {
+                    ocorrencias = ocorrencias_lote.clone();
+                }
+                retorno.creditos.push(CreditoRetorno {
+                    seu_numero: campo(linha, 73, 93).trim().to_string(),
+                    valor: valor_centavos(&campo(linha, 119, 134))
+                        .ok_or_else(|| erro(numero, "valor do pagamento inválido"))?,
+                    data_efetivacao: NaiveDate::parse_from_str(&campo(linha, 154, 162), "%d%m%Y").ok(),
+                    valor_pago: valor_centavos(&campo(linha, 162, 177)).filter(|v| !v.is_zero()),
+                    ocorrencias,
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(retorno)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn credito(folha_id: i64, banco: &str, tipo_conta: &str, valor: &str) -> Credito {
+        Credito {
+            folha_id,
+            servidor_id: folha_id as i32,
+            nome: "José da Conceição".to_string(),
+            cpf: Some("52998224725".to_string()),
+            banco: Some(banco.to_string()),
+            agencia: Some("1234-X".to_string()),
+            conta: Some("98765-4".to_string()),
+            tipo_conta: Some(tipo_conta.to_string()),
+            logradouro: Some("Rua A".to_string()),
+            numero: Some("10".to_string()),
+            complemento: None,
+            bairro: Some("Centro".to_string()),
+            cidade: Some("Palmas".to_string()),
+            cep: Some("77000000".to_string()),
+            uf: Some("TO".to_string()),
+            valor: BigDecimal::from_str(valor).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_pendencia() {
+        assert_eq!(credito(1, "001", "C", "10").pendencia(), None);
+        assert!(credito(1, "001", "C", "0").pendencia().is_some());
+
+        let mut sem_conta = credito(1, "001", "C", "10");
+        sem_conta.conta = None;
+        assert_eq!(sem_conta.pendencia().as_deref(), Some("conta inválida"));
+
+        assert_eq!(conta_dv("12345-6"), ("12345".to_string(), "6".to_string()));
+        assert_eq!(conta_dv("123456"), ("12345".to_string(), "6".to_string()));
+        assert_eq!(agencia_dv("1234"), ("1234".to_string(), String::new()));
+        assert!(Layout::Caixa.erro_convenio("1234561234").is_none());
+        assert!(Layout::Caixa.erro_convenio("123456").is_some());
+        assert!(Layout::BancoDoBrasil.erro_convenio("123456789").is_none());
+    }
+
+    fn pagador() -> Pagador {
+        Pagador {
+            banco: "001".to_string(),
+            cnpj: "11222333000181".to_string(),
+            convenio: "123456".to_string(),
+            agencia: "4321-0".to_string(),
+            conta: "55555-5".to_string(),
+            nome: "Prefeitura de Brasilândia".to_string(),
+            logradouro: "Praça Central".to_string(),
+            numero: "1".to_string(),
+            complemento: String::new(),
+            cidade: "Brasilândia".to_string(),
+            cep: "77735000".to_string(),
+            uf: "TO".to_string(),
+        }
+    }
+
+    fn data_hora() -> (NaiveDateTime, NaiveDate) {
+        (
+            NaiveDate::from_ymd_opt(2025, 3, 1).unwrap().and_hms_opt(8, 30, 0).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 3, 5).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_remessa() {
+        let pagador = pagador();
+        let creditos = [
+            credito(1, "104", "C", "300.00"),
+            credito(2, "001", "C", "1500.50"),
+            credito(3, "001", "P", "99.99"),
+            credito(4, "001", "C", "0.01"),
+        ];
+        let (gerado_em, pagamento) = data_hora();
+        let arquivo = remessa(Layout::BancoDoBrasil, &pagador, 7, gerado_em, pagamento, &creditos);
+
+        let linhas: Vec<&str> = arquivo.split_terminator("\r\n").collect();
+        assert!(linhas.iter().all(|l| l.len() == TAMANHO_REGISTRO));
+        // header, 3 lotes (corrente com 2 créditos, poupança e TED) e trailer
+        assert_eq!(linhas.len(), 1 + (2 + 4) + (2 + 2) + (2 + 2) + 1);
+
+        let header = linhas[0];
+        assert_eq!(&header[32..45], "0001234560126");
+        assert_eq!(&header[157..163], "000007");
+
+        // lote 1: crédito em conta corrente no banco do órgão
+        assert_eq!(&linhas[1][8..16], "C3001000");
+        assert_eq!(&linhas[2][13..14], "A");
+        assert_eq!(&linhas[2][17..20], "000");
+        assert_eq!(&linhas[2][43..73], "JOSE DA CONCEICAO             ");
+        assert_eq!(&linhas[2][119..134], "000000000150050");
+        assert_eq!(&linhas[3][13..14], "B");
+        assert_eq!(&linhas[3][18..32], "00052998224725");
+        let trailer_lote = linhas[6];
+        assert_eq!(&trailer_lote[7..8], "5");
+        assert_eq!(&trailer_lote[17..23], "000006");
+        assert_eq!(&trailer_lote[23..41], "000000000000150051");
+
+        // lote 3: TED para a Caixa
+        assert_eq!(&linhas[11][11..13], "41");
+        assert_eq!(&linhas[12][17..20], "018");
+        assert_eq!(&linhas[12][219..224], "00004");
+
+        let trailer = linhas[linhas.len() - 1];
+        assert_eq!(&trailer[3..8], "99999");
+        assert_eq!(&trailer[17..23], "000003");
+        assert_eq!(&trailer[23..29], "000016");
+    }
+
+    #[test]
+    fn test_ler_retorno() {
+        let (gerado_em, pagamento) = data_hora();
+        let creditos = [
+            credito(1, "001", "C", "100"),
+            credito(2, "001", "P", "50.5"),
+            credito(3, "104", "C", "10"),
+        ];
+        let arquivo = remessa(Layout::Febraban, &pagador(), 1, gerado_em, pagamento, &creditos);
+        let mut linhas: Vec<String> = arquivo.split_terminator("\r\n").map(String::from).collect();
+
+        // o banco devolve o arquivo como retorno: conta corrente efetivada, poupança
+        // agendada pelo header do lote e TED recusada no segmento
+        linhas[0].replace_range(142..143, "2");
+        linhas[2].replace_range(230..240, "00        ");
+        linhas[5].replace_range(230..240, "BD        ");
+        linhas[10].replace_range(230..240, "AGAN      ");
+        let retorno = ler_retorno(linhas.join("\r\n").as_bytes()).unwrap();
+
+        assert_eq!(retorno.banco, "001");
+        assert_eq!(retorno.cnpj, "11222333000181");
+        let situacoes: Vec<(&str, &str)> = retorno
+            .creditos
+            .iter()
+            .map(|c| (c.seu_numero.as_str(), c.situacao()))
+            .collect();
+        assert_eq!(
+            situacoes,
+            [("1", CREDITO_PAGO), ("2", CREDITO_AGENDADO), ("3", CREDITO_REJEITADO)]
+        );
+        assert_eq!(retorno.creditos[1].valor, BigDecimal::from_str("50.50").unwrap());
+        assert_eq!(retorno.creditos[0].valor_efetivado(), Some(BigDecimal::from_str("100").unwrap()));
+        assert_eq!(retorno.creditos[1].valor_efetivado(), None);
+        assert_eq!(
+            retorno.creditos[2].mensagem(),
+            "AG - Agência/conta corrente/DV inválido; AN - Conta corrente/DV do favorecido inválido"
+        );
+
+        // a própria remessa não é aceita como retorno
+        assert!(ler_retorno(arquivo.as_bytes()).is_err());
+    }
+}
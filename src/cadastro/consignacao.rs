@@ -0,0 +1,130 @@
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Prazo para a consignatária averbar o contrato com a margem reservada
+pub const VALIDADE_RESERVA_HORAS: i64 = 48;
+
+/// Tamanho da chave pública da credencial; o segredo tem 32 caracteres
+const TAMANHO_CHAVE: usize = 16;
+
+/// Apuração da margem consignável sobre uma folha
+#[derive(Debug, Serialize)]
+pub struct Margem {
+    /// Folha usada como referência (a última do servidor)
+    pub folha_id: i64,
+    pub ano: i32,
+    pub mes: i32,
+    /// Soma dos proventos
+    pub bruto: BigDecimal,
+    /// INSS, IRRF e pensão alimentícia
+    pub descontos_obrigatorios: BigDecimal,
+    /// Percentual do órgão aplicado sobre o bruto menos os descontos obrigatórios
+    pub percentual: BigDecimal,
+    pub margem: BigDecimal,
+    /// Parcelas dos contratos ativos que ainda estarão sendo descontadas
+    pub comprometida: BigDecimal,
+    /// Reservas das consignatárias feitas pela API e ainda no prazo
+    pub reservada: BigDecimal,
+    pub disponivel: BigDecimal,
+}
+
+/// Calcula a margem e o quanto dela ainda está livre. A margem é arredondada para baixo,
+/// para que um contrato no limite nunca passe do percentual legal.
+pub fn calcular_margem(
+    bruto: &BigDecimal,
+    descontos_obrigatorios: &BigDecimal,
+    percentual: &BigDecimal,
+    comprometida: &BigDecimal,
+) -> (BigDecimal, BigDecimal) {
+    let zero = BigDecimal::zero();
+
+    let liquido = bruto - descontos_obrigatorios;
+    let margem = if liquido > zero {
+        (liquido * percentual / BigDecimal::from(100)).with_scale_round(2, RoundingMode::Down)
+    } else {
+        zero.with_scale(2)
+    };
+
+    let disponivel = &margem - comprometida;
+    let disponivel = if disponivel > zero { disponivel.with_scale(2) } else { zero.with_scale(2) };
+
+    (margem, disponivel)
+}
+
+/// Índice contínuo de uma competência, para contar meses entre duas delas
+pub fn indice_competencia(ano: i32, mes: i32) -> i32 {
+    ano * 12 + mes - 1
+}
+
+/// Número da parcela do contrato que cai na competência, se cair alguma
+pub fn numero_parcela(
+    ano_inicio: i32,
+    mes_inicio: i32,
+    total_parcelas: i32,
+    ano: i32,
+    mes: i32,
+) -> Option<i32> {
+    let numero = indice_competencia(ano, mes) - indice_competencia(ano_inicio, mes_inicio) + 1;
+    (1..=total_parcelas).contains(&numero).then_some(numero)
+}
+
+/// Nova credencial da API: chave pública e segredo, entregues juntos como "<chave>.<segredo>"
+pub fn gerar_credencial() -> (String, String) {
+    let chave = Uuid::new_v4().simple().to_string()[..TAMANHO_CHAVE].to_uppercase();
+    let segredo = Uuid::new_v4().simple().to_string();
+    (chave, segredo)
+}
+
+/// Chave e segredo do cabeçalho "Authorization: Bearer <chave>.<segredo>"
+pub fn separar_token(authorization: &str) -> Option<(&str, &str)> {
+    let token = authorization.trim().strip_prefix("Bearer ")?.trim();
+    let (chave, segredo) = token.split_once('.')?;
+    (chave.len() == TAMANHO_CHAVE && !segredo.is_empty()).then_some((chave, segredo))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn dec(valor: &str) -> BigDecimal {
+        BigDecimal::from_str(valor).unwrap()
+    }
+
+    #[test]
+    fn test_calcular_margem() {
+        // (5.000,00 - 1.000,01) x 35% = 1.399,9965 -> 1.399,99
+        let (margem, disponivel) =
+            calcular_margem(&dec("5000.00"), &dec("1000.01"), &dec("35"), &dec("400.00"));
+        assert_eq!(margem, dec("1399.99"));
+        assert_eq!(disponivel, dec("999.99"));
+
+        // margem já toda comprometida (salário reduzido depois do contrato)
+        let (_, disponivel) = calcular_margem(&dec("2000.00"), &dec("200.00"), &dec("35"), &dec("900.00"));
+        assert_eq!(disponivel, dec("0.00"));
+
+        let (margem, _) = calcular_margem(&dec("100.00"), &dec("150.00"), &dec("35"), &dec("0"));
+        assert_eq!(margem, dec("0.00"));
+    }
+
+    #[test]
+    fn test_numero_parcela() {
+        assert_eq!(numero_parcela(2025, 11, 3, 2025, 10), None);
+        assert_eq!(numero_parcela(2025, 11, 3, 2025, 11), Some(1));
+        assert_eq!(numero_parcela(2025, 11, 3, 2026, 1), Some(3));
+        assert_eq!(numero_parcela(2025, 11, 3, 2026, 2), None);
+    }
+
+    #[test]
+    fn test_separar_token() {
+        let (chave, segredo) = gerar_credencial();
+        let authorization = format!("Bearer {}.{}", chave, segredo);
+        assert_eq!(separar_token(&authorization), Some((chave.as_str(), segredo.as_str())));
+
+        assert_eq!(separar_token("Basic YWRtaW46MTIzNA=="), None);
+        assert_eq!(separar_token("Bearer 0123456789ABCDEF"), None);
+        assert_eq!(separar_token("Bearer ABC.segredo"), None);
+    }
+}
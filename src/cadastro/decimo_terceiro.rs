@@ -0,0 +1,119 @@
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+
+use crate::cadastro::calculo::arredondar;
+
+/// Códigos de sistema das rubricas lançadas no cálculo do 13º (cadastro_rubrica.codigo_sistema)
+pub const SISTEMA_ADIANTAMENTO_13: &str = "ADIANTAMENTO_13";
+pub const SISTEMA_DECIMO_TERCEIRO: &str = "DECIMO_TERCEIRO";
+pub const SISTEMA_DESCONTO_ADIANTAMENTO_13: &str = "DESCONTO_ADIANTAMENTO_13";
+pub const SISTEMA_INSS_13: &str = "INSS_13";
+pub const SISTEMA_IRRF_13: &str = "IRRF_13";
+
+/// Dias trabalhados no mês a partir dos quais o mês conta um avo
+const DIAS_AVO: i64 = 15;
+
+/// Período de afastamento que não conta para o 13º; sem fim, segue afastado
+#[derive(Debug, Clone)]
+pub struct PeriodoAfastamento {
+    pub inicio: NaiveDate,
+    pub fim: Option<NaiveDate>,
+}
+
+impl PeriodoAfastamento {
+    fn contem(&self, dia: NaiveDate) -> bool {
+        dia >= self.inicio && self.fim.is_none_or(|fim| dia <= fim)
+    }
+}
+
+fn ultimo_dia(ano: i32, mes: u32) -> NaiveDate {
+    let (ano, mes) = if mes == 12 { (ano + 1, 1) } else { (ano, mes + 1) };
+    NaiveDate::from_ymd_opt(ano, mes, 1).unwrap().pred_opt().unwrap()
+}
+
+/// Avos do 13º no ano até o mês informado: cada mês com pelo menos 15 dias de
+/// exercício (entre a admissão e o desligamento, fora dos afastamentos) vale 1/12
+pub fn avos(
+    ano: i32,
+    ate_mes: u32,
+    admissao: Option<NaiveDate>,
+    desligamento: Option<NaiveDate>,
+    afastamentos: &[PeriodoAfastamento],
+) -> i32 {
+    let mut avos = 0;
+    for mes in 1..=ate_mes.min(12) {
+        let mut inicio = NaiveDate::from_ymd_opt(ano, mes, 1).unwrap();
+        let mut fim = ultimo_dia(ano, mes);
+        if let Some(admissao) = admissao {
+            inicio = inicio.max(admissao);
+        }
+        if let Some(desligamento) = desligamento {
+            fim = fim.min(desligamento);
+        }
+
+        let trabalhados = inicio
+            .iter_days()
+            .take_while(|dia| *dia <= fim)
+            .filter(|dia| !afastamentos.iter().any(|a| a.contem(*dia)))
+            .count() as i64;
+        if trabalhados >= DIAS_AVO {
+            avos += 1;
+        }
+    }
+    avos
+}
+
+/// 13º proporcional aos avos: salário / 12 x avos, em centavos
+pub fn proporcional(salario: &BigDecimal, avos: i32) -> BigDecimal {
+    arredondar(&(salario * BigDecimal::from(avos) / BigDecimal::from(12)))
+}
+
+/// Adiantamento: metade do 13º proporcional aos avos já adquiridos
+pub fn adiantamento(salario: &BigDecimal, avos: i32) -> BigDecimal {
+    arredondar(&(salario * BigDecimal::from(avos) / BigDecimal::from(24)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn dec(valor: &str) -> BigDecimal {
+        BigDecimal::from_str(valor).unwrap()
+    }
+
+    fn data(ano: i32, mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(ano, mes, dia).unwrap()
+    }
+
+    #[test]
+    fn test_avos() {
+        assert_eq!(avos(2025, 12, None, None, &[]), 12);
+        assert_eq!(avos(2025, 6, Some(data(2020, 5, 1)), None, &[]), 6);
+
+        // admitido em 20/03 trabalha 12 dias em março; em 17/03, 15 dias
+        assert_eq!(avos(2025, 12, Some(data(2025, 3, 20)), None, &[]), 9);
+        assert_eq!(avos(2025, 12, Some(data(2025, 3, 17)), None, &[]), 10);
+
+        // fevereiro de 2025 tem 28 dias: desligado em 14/02 fica só com janeiro
+        assert_eq!(avos(2025, 12, None, Some(data(2025, 2, 14)), &[]), 1);
+
+        // afastado de 01/06 a 20/07: perde junho e julho (11 dias em julho)
+        let licenca = [PeriodoAfastamento { inicio: data(2025, 6, 1), fim: Some(data(2025, 7, 20)) }];
+        assert_eq!(avos(2025, 12, None, None, &licenca), 10);
+
+        // afastamento sem fim a partir de 10/11
+        let aberto = [PeriodoAfastamento { inicio: data(2025, 11, 10), fim: None }];
+        assert_eq!(avos(2025, 12, None, None, &aberto), 10);
+    }
+
+    #[test]
+    fn test_valores() {
+        assert_eq!(proporcional(&dec("3000.00"), 9), dec("2250.00"));
+        assert_eq!(adiantamento(&dec("3000.00"), 9), dec("1125.00"));
+        // 3.333,33 x 7 / 12 = 1.944,4425
+        assert_eq!(proporcional(&dec("3333.33"), 7), dec("1944.44"));
+        assert_eq!(adiantamento(&dec("3333.33"), 7), dec("972.22"));
+    }
+}
@@ -0,0 +1,1037 @@
+use std::{
+    fs,
+    io::{Cursor, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::Result;
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use chrono::{NaiveDate, NaiveDateTime};
+use regex::Regex;
+use uuid::Uuid;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    cadastro::model::{
+        ESOCIAL_S1210, ESOCIAL_S2200, ESOCIAL_S2206, ESOCIAL_S2299, ESOCIAL_S2300,
+    },
+    utils::validator::{cpf_valido, somente_digitos},
+};
+
+/// Versão do leiaute dos eventos (namespace e XSD)
+pub const VERSAO_LEIAUTE: &str = "v_S_01_03_00";
+
+const VERSAO_PROCESSO: &str = concat!("folhaweb ", env!("CARGO_PKG_VERSION"));
+
+/// Categorias com o grupo infoAgNocivo obrigatório no S-1200 (1XX entra pela faixa)
+const CATEGORIAS_AGENTE_NOCIVO: [i32; 11] = [301, 302, 303, 305, 306, 309, 310, 312, 313, 401, 410];
+
+/// Categorias com vínculo (S-2200/S-2206/S-2299) além das 1XX; as demais são
+/// trabalhadores sem vínculo de emprego (S-2300)
+const CATEGORIAS_COM_VINCULO: [i32; 8] = [301, 302, 303, 306, 307, 309, 310, 312];
+
+/// Código do Brasil na tabela de países (nascimento e nacionalidade)
+const PAIS_BRASIL: &str = "105";
+
+/// Parâmetros do órgão como empregador no eSocial
+#[derive(Debug, Clone)]
+pub struct Empregador {
+    /// CNPJ completo: órgão público informa os 14 dígitos, não só a raiz
+    pub cnpj: String,
+    pub ambiente: i32,
+    pub tabela_rubrica: String,
+    pub lotacao: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemRemuneracao {
+    pub codigo: String,
+    pub valor: BigDecimal,
+}
+
+/// Uma folha do servidor na competência: vira um dmDev no S-1200 e um infoPgto no S-1210
+#[derive(Debug, Clone)]
+pub struct Demonstrativo {
+    pub folha_id: i64,
+    pub categoria: Option<i32>,
+    pub itens: Vec<ItemRemuneracao>,
+    pub valor_liquido: BigDecimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct Trabalhador {
+    pub servidor_id: i32,
+    pub nome: String,
+    pub cpf: Option<String>,
+    pub matricula: Option<String>,
+    pub demonstrativos: Vec<Demonstrativo>,
+}
+
+/// XML gerado e o que impede o envio (dados faltando no cadastro)
+#[derive(Debug)]
+pub struct EventoGerado {
+    pub id: String,
+    pub xml: String,
+    pub pendencias: Vec<String>,
+}
+
+/// Escrita sequencial do XML, sem espaços entre os elementos (o XML é assinado depois)
+struct Xml {
+    texto: String,
+    abertos: Vec<&'static str>,
+}
+
+impl Xml {
+    fn new() -> Self {
+        Self {
+            texto: r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+            abertos: Vec::new(),
+        }
+    }
+
+    fn abrir(&mut self, tag: &'static str, atributos: &[(&str, &str)]) -> &mut Self {
+        self.texto.push('<');
+        self.texto.push_str(tag);
+        for (nome, valor) in atributos {
+            self.texto.push_str(&format!(" {}=\"{}\"", nome, escapar(valor)));
+        }
+        self.texto.push('>');
+        self.abertos.push(tag);
+        self
+    }
+
+    fn campo(&mut self, tag: &str, valor: impl AsRef<str>) -> &mut Self {
+        self.texto
+            .push_str(&format!("<{}>{}</{}>", tag, escapar(valor.as_ref()), tag));
+        self
+    }
+
+    fn fechar(&mut self) -> &mut Self {
+        if let Some(tag) = self.abertos.pop() {
+            self.texto.push_str(&format!("</{}>", tag));
+        }
+        self
+    }
+
+    fn concluir(mut self) -> String {
+        while !self.abertos.is_empty() {
+            self.fechar();
+        }
+        self.texto
+    }
+}
+
+fn escapar(valor: &str) -> String {
+    let mut escapado = String::with_capacity(valor.len());
+    for c in valor.chars() {
+        match c {
+            '&' => escapado.push_str("&amp;"),
+            '<' => escapado.push_str("&lt;"),
+            '>' => escapado.push_str("&gt;"),
+            '"' => escapado.push_str("&quot;"),
+            '\'' => escapado.push_str("&apos;"),
+            c => escapado.push(c),
+        }
+    }
+    escapado
+}
+
+/// Valor monetário com 2 casas e ponto decimal
+fn valor(valor: &BigDecimal) -> String {
+    valor.with_scale_round(2, RoundingMode::HalfUp).to_string()
+}
+
+fn periodo(ano: i32, mes: i32) -> String {
+    format!("{}-{:02}", ano, mes)
+}
+
+/// Id do evento: "ID" + tipo de inscrição (1 = CNPJ) + inscrição com 14 posições +
+/// AAAAMMDDHHMMSS + sequencial de 5 dígitos dentro do mesmo segundo
+pub fn id_evento(cnpj: &str, gerado_em: NaiveDateTime, sequencial: u32) -> String {
+    format!("{}{:05}", prefixo_id_evento(cnpj, gerado_em), sequencial)
+}
+
+/// Id do evento sem o sequencial: igual para todos os eventos gerados no mesmo segundo
+pub fn prefixo_id_evento(cnpj: &str, gerado_em: NaiveDateTime) -> String {
+    format!("ID1{:0<14}{}", cnpj, gerado_em.format("%Y%m%d%H%M%S"))
+}
+
+fn abrir_evento(xml: &mut Xml, evento: &'static str, id: &str) {
+    let namespace = format!(
+        "http://www.esocial.gov.br/schema/evt/{}/{}",
+        evento, VERSAO_LEIAUTE
+    );
+    xml.abrir("eSocial", &[("xmlns", &namespace)])
+        .abrir(evento, &[("Id", id)]);
+}
+
+fn ide_empregador(xml: &mut Xml, empregador: &Empregador) {
+    xml.abrir("ideEmpregador", &[])
+        .campo("tpInsc", "1")
+        .campo("nrInsc", &empregador.cnpj)
+        .fechar();
+}
+
+fn pendencias_trabalhador(trabalhador: &Trabalhador) -> Vec<String> {
+    match trabalhador.cpf.as_deref() {
+        Some(cpf) if cpf_valido(cpf) => Vec::new(),
+        Some(_) => vec![format!("{}: CPF inválido", trabalhador.nome)],
+        None => vec![format!("{}: servidor sem CPF", trabalhador.nome)],
+    }
+}
+
+/// S-1200 - Remuneração do trabalhador no período de apuração (mensal)
+pub fn s1200(
+    id: String,
+    empregador: &Empregador,
+    ano: i32,
+    mes: i32,
+    trabalhador: &Trabalhador,
+) -> EventoGerado {
+    let mut pendencias = pendencias_trabalhador(trabalhador);
+    let mut xml = Xml::new();
+
+    abrir_evento(&mut xml, "evtRemun", &id);
+    xml.abrir("ideEvento", &[])
+        .campo("indRetif", "1")
+        .campo("indApuracao", "1")
+        .campo("perApur", periodo(ano, mes))
+        .campo("tpAmb", empregador.ambiente.to_string())
+        .campo("procEmi", "1")
+        .campo("verProc", VERSAO_PROCESSO)
+        .fechar();
+    ide_empregador(&mut xml, empregador);
+    xml.abrir("ideTrabalhador", &[])
+        .campo("cpfTrab", somente_digitos(trabalhador.cpf.as_deref().unwrap_or_default()))
+        .fechar();
+
+    for dm in &trabalhador.demonstrativos {
+        let categoria = dm.categoria.unwrap_or_default();
+        if dm.categoria.is_none() {
+            pendencias.push(format!(
+                "{}: folha {} com vínculo sem categoria do eSocial",
+                trabalhador.nome, dm.folha_id
+            ));
+        }
+        if dm.itens.is_empty() {
+            pendencias.push(format!(
+                "{}: folha {} sem rubricas com valor",
+                trabalhador.nome, dm.folha_id
+            ));
+        }
+
+        xml.abrir("dmDev", &[])
+            .campo("ideDmDev", dm.folha_id.to_string())
+            .campo("codCateg", categoria.to_string())
+            .abrir("infoPerApur", &[])
+            .abrir("ideEstabLot", &[])
+            .campo("tpInsc", "1")
+            .campo("nrInsc", &empregador.cnpj)
+            .campo("codLotacao", &empregador.lotacao)
+            .abrir("remunPerApur", &[]);
+        if let Some(matricula) = &trabalhador.matricula {
+            xml.campo("matricula", matricula);
+        }
+        for item in &dm.itens {
+            xml.abrir("itensRemun", &[])
+                .campo("codRubr", &item.codigo)
+                .campo("ideTabRubr", &empregador.tabela_rubrica)
+                .campo("vrRubr", valor(&item.valor))
+                .campo("indApurIR", "0")
+                .fechar();
+        }
+        if (100..200).contains(&categoria) || CATEGORIAS_AGENTE_NOCIVO.contains(&categoria) {
+            // 1 = sem exposição a agente nocivo
+            xml.abrir("infoAgNocivo", &[]).campo("grauExp", "1").fechar();
+        }
+        // remunPerApur, ideEstabLot, infoPerApur, dmDev
+        xml.fechar().fechar().fechar().fechar();
+    }
+
+    EventoGerado {
+        id,
+        xml: xml.concluir(),
+        pendencias,
+    }
+}
+
+/// S-1210 - Pagamentos de rendimentos do trabalho. O período de apuração é o mês
+/// do pagamento; perRef aponta a competência do S-1200
+pub fn s1210(
+    id: String,
+    empregador: &Empregador,
+    ano: i32,
+    mes: i32,
+    data_pagamento: NaiveDate,
+    trabalhador: &Trabalhador,
+) -> EventoGerado {
+    let mut pendencias = pendencias_trabalhador(trabalhador);
+    let mut xml = Xml::new();
+
+    abrir_evento(&mut xml, "evtPgtos", &id);
+    xml.abrir("ideEvento", &[])
+        .campo("indRetif", "1")
+        .campo("perApur", data_pagamento.format("%Y-%m").to_string())
+        .campo("tpAmb", empregador.ambiente.to_string())
+        .campo("procEmi", "1")
+        .campo("verProc", VERSAO_PROCESSO)
+        .fechar();
+    ide_empregador(&mut xml, empregador);
+    xml.abrir("ideBenef", &[])
+        .campo("cpfBenef", somente_digitos(trabalhador.cpf.as_deref().unwrap_or_default()));
+
+    for dm in &trabalhador.demonstrativos {
+        if dm.valor_liquido < BigDecimal::zero() {
+            pendencias.push(format!(
+                "{}: folha {} com líquido negativo",
+                trabalhador.nome, dm.folha_id
+            ));
+        }
+        xml.abrir("infoPgto", &[])
+            .campo("dtPgto", data_pagamento.format("%Y-%m-%d").to_string())
+            // 1 = pagamento da remuneração apurada no S-1200
+            .campo("tpPgto", "1")
+            .campo("perRef", periodo(ano, mes))
+            .campo("ideDmDev", dm.folha_id.to_string())
+            .campo("vrLiq", valor(&dm.valor_liquido))
+            .fechar();
+    }
+
+    EventoGerado {
+        id,
+        xml: xml.concluir(),
+        pendencias,
+    }
+}
+
+/// 1XX e as categorias de servidor com vínculo vão no S-2200; as demais no S-2300
+pub fn categoria_com_vinculo(categoria: i32) -> bool {
+    (100..200).contains(&categoria) || CATEGORIAS_COM_VINCULO.contains(&categoria)
+}
+
+/// Dados do cadastro do servidor que definem os eventos não periódicos
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contrato {
+    pub orgao_id: Option<i32>,
+    pub data_admissao: Option<NaiveDate>,
+    pub cargo_id: Option<i32>,
+    pub vinculo_id: Option<i32>,
+    pub categoria: Option<i32>,
+    pub data_desligamento: Option<NaiveDate>,
+}
+
+/// Eventos (tipo e data) que a alteração do cadastro coloca na fila, em ordem. Sem
+/// órgão e data de admissão não há evento; o primeiro é sempre a admissão (S-2200,
+/// ou S-2300 para categoria sem vínculo). Depois dela, troca de cargo ou de vínculo
+/// gera S-2206 e a data de desligamento gera S-2299. O desligamento do trabalhador
+/// sem vínculo (S-2399) não é gerado
+pub fn eventos_da_alteracao(
+    antes: Option<&Contrato>,
+    depois: &Contrato,
+    admissao_na_fila: bool,
+    hoje: NaiveDate,
+) -> Vec<(&'static str, NaiveDate)> {
+    let (Some(_), Some(admissao)) = (depois.orgao_id, depois.data_admissao) else {
+        return Vec::new();
+    };
+    // sem categoria o evento vai como vínculo e a geração aponta a pendência
+    let com_vinculo = depois.categoria.is_none_or(categoria_com_vinculo);
+
+    let mut eventos = Vec::new();
+    if !admissao_na_fila {
+        eventos.push((if com_vinculo { ESOCIAL_S2200 } else { ESOCIAL_S2300 }, admissao));
+    } else if com_vinculo
+        && depois.data_desligamento.is_none()
+        && antes.is_some_and(|a| a.cargo_id != depois.cargo_id || a.vinculo_id != depois.vinculo_id)
+    {
+        eventos.push((ESOCIAL_S2206, hoje));
+    }
+    if com_vinculo
+        && let Some(desligamento) = depois.data_desligamento
+        && antes.is_none_or(|a| a.data_desligamento != Some(desligamento))
+    {
+        eventos.push((ESOCIAL_S2299, desligamento));
+    }
+    eventos
+}
+
+/// Cadastro do servidor lido na geração dos eventos não periódicos
+#[derive(Debug, Clone)]
+pub struct CadastroTrabalhador {
+    pub nome: String,
+    pub cpf: Option<String>,
+    pub matricula: Option<String>,
+    pub sexo: Option<String>,
+    pub raca_cor: Option<i32>,
+    pub grau_instrucao: Option<String>,
+    pub data_nascimento: Option<NaiveDate>,
+    pub logradouro: Option<String>,
+    pub numero: Option<String>,
+    pub complemento: Option<String>,
+    pub bairro: Option<String>,
+    pub cep: Option<String>,
+    /// Código IBGE do município e sigla da UF do endereço
+    pub codigo_municipio: Option<i32>,
+    pub uf: Option<String>,
+    pub data_admissao: Option<NaiveDate>,
+    pub cargo: Option<String>,
+    pub cbo: Option<String>,
+    pub categoria: Option<i32>,
+    /// 1 = RGPS, 2 = RPPS
+    pub regime_previdencia: i32,
+    /// Salário da folha mais recente do servidor
+    pub salario: Option<BigDecimal>,
+    pub data_desligamento: Option<NaiveDate>,
+    pub motivo_desligamento: Option<String>,
+}
+
+impl CadastroTrabalhador {
+    fn cpf(&self) -> String {
+        somente_digitos(self.cpf.as_deref().unwrap_or_default())
+    }
+
+    fn celetista(&self) -> bool {
+        self.categoria.is_some_and(|c| (100..200).contains(&c))
+    }
+
+    /// Campos obrigatórios em todos os eventos do trabalhador
+    fn pendencias(&self, campos: &[(&str, bool)]) -> Vec<String> {
+        let mut pendencias = match self.cpf.as_deref() {
+            Some(cpf) if cpf_valido(cpf) => Vec::new(),
+            Some(_) => vec![format!("{}: CPF inválido", self.nome)],
+            None => vec![format!("{}: servidor sem CPF", self.nome)],
+        };
+        pendencias.extend(
+            campos
+                .iter()
+                .filter(|(_, preenchido)| !preenchido)
+                .map(|(campo, _)| format!("{}: {} não informado", self.nome, campo)),
+        );
+        pendencias
+    }
+
+    fn pendencias_admissao(&self) -> Vec<String> {
+        self.pendencias(&[
+            ("sexo", self.sexo.is_some()),
+            ("raça/cor", self.raca_cor.is_some()),
+            ("grau de instrução", self.grau_instrucao.is_some()),
+            ("data de nascimento", self.data_nascimento.is_some()),
+            ("logradouro", self.logradouro.is_some()),
+            ("CEP", self.cep.is_some()),
+            ("município com código IBGE", self.codigo_municipio.is_some() && self.uf.is_some()),
+            ("data de admissão", self.data_admissao.is_some()),
+            ("cargo", self.cargo.is_some()),
+            ("categoria do eSocial no vínculo", self.categoria.is_some()),
+        ])
+    }
+}
+
+fn data(data: Option<NaiveDate>) -> String {
+    data.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default()
+}
+
+fn ide_evento_nao_periodico(xml: &mut Xml, empregador: &Empregador) {
+    xml.abrir("ideEvento", &[])
+        .campo("indRetif", "1")
+        .campo("tpAmb", empregador.ambiente.to_string())
+        .campo("procEmi", "1")
+        .campo("verProc", VERSAO_PROCESSO)
+        .fechar();
+}
+
+/// Dados pessoais, nascimento e endereço (S-2200 e S-2300)
+fn dados_trabalhador(xml: &mut Xml, cadastro: &CadastroTrabalhador) {
+    xml.abrir("trabalhador", &[])
+        .campo("cpfTrab", cadastro.cpf())
+        .campo("nmTrab", &cadastro.nome)
+        .campo("sexo", cadastro.sexo.as_deref().unwrap_or_default())
+        .campo("racaCor", cadastro.raca_cor.unwrap_or_default().to_string())
+        .campo("grauInstr", cadastro.grau_instrucao.as_deref().unwrap_or_default())
+        .abrir("nascimento", &[])
+        .campo("dtNascto", data(cadastro.data_nascimento))
+        .campo("paisNascto", PAIS_BRASIL)
+        .campo("paisNac", PAIS_BRASIL)
+        .fechar()
+        .abrir("endereco", &[])
+        .abrir("brasil", &[])
+        .campo("dscLograd", cadastro.logradouro.as_deref().unwrap_or_default())
+        .campo("nrLograd", cadastro.numero.as_deref().unwrap_or("S/N"));
+    if let Some(complemento) = &cadastro.complemento {
+        xml.campo("complemento", complemento);
+    }
+    if let Some(bairro) = &cadastro.bairro {
+        xml.campo("bairro", bairro);
+    }
+    xml.campo("cep", cadastro.cep.as_deref().unwrap_or_default())
+        .campo("codMunic", cadastro.codigo_municipio.unwrap_or_default().to_string())
+        .campo("uf", cadastro.uf.as_deref().unwrap_or_default())
+        // brasil, endereco, trabalhador
+        .fechar()
+        .fechar()
+        .fechar();
+}
+
+/// Remuneração fixa: salário mensal da última folha, ou "não aplicável" sem folha
+fn remuneracao(xml: &mut Xml, cadastro: &CadastroTrabalhador) {
+    let (salario, unidade) = match &cadastro.salario {
+        Some(salario) => (valor(salario), "5"),
+        None => ("0.00".to_string(), "7"),
+    };
+    xml.abrir("remuneracao", &[])
+        .campo("vrSalFx", salario)
+        .campo("undSalFixo", unidade)
+        .fechar();
+}
+
+/// Cargo, categoria e local de trabalho (S-2200 e S-2206); remuneração e duração
+/// só para o celetista
+fn info_contrato(xml: &mut Xml, empregador: &Empregador, cadastro: &CadastroTrabalhador) {
+    xml.abrir("infoContrato", &[])
+        .campo("nmCargo", cadastro.cargo.as_deref().unwrap_or_default());
+    if let Some(cbo) = &cadastro.cbo {
+        xml.campo("CBOCargo", cbo);
+    }
+    xml.campo("codCateg", cadastro.categoria.unwrap_or_default().to_string());
+    if cadastro.celetista() {
+        remuneracao(xml, cadastro);
+        // 1 = prazo indeterminado
+        xml.abrir("duracao", &[]).campo("tpContr", "1").fechar();
+    }
+    xml.abrir("localTrabalho", &[])
+        .abrir("localTrabGeral", &[])
+        .campo("tpInsc", "1")
+        .campo("nrInsc", &empregador.cnpj)
+        .fechar()
+        .fechar()
+        .fechar();
+}
+
+/// Dados do RPPS no grupo infoEstatutario: sem segregação de massa, sem teto do RGPS e sem abono
+fn info_rpps(xml: &mut Xml, cadastro: &CadastroTrabalhador) {
+    if cadastro.regime_previdencia == 2 {
+        xml.campo("tpPlanRP", "0")
+            .campo("indTetoRGPS", "N")
+            .campo("indAbonoPerm", "N");
+    }
+}
+
+/// Tipo de provimento do estatutário pela categoria
+fn tipo_provimento(categoria: i32) -> &'static str {
+    match categoria {
+        301 => "1",
+        302 => "2",
+        303 => "6",
+        306 => "7",
+        307 => "3",
+        _ => "99",
+    }
+}
+
+/// S-2200 - Cadastramento inicial do vínculo e admissão/ingresso de trabalhador
+pub fn s2200(id: String, empregador: &Empregador, cadastro: &CadastroTrabalhador) -> EventoGerado {
+    let pendencias = cadastro.pendencias_admissao();
+    let admissao = data(cadastro.data_admissao);
+    let mut xml = Xml::new();
+
+    abrir_evento(&mut xml, "evtAdmissao", &id);
+    ide_evento_nao_periodico(&mut xml, empregador);
+    ide_empregador(&mut xml, empregador);
+    dados_trabalhador(&mut xml, cadastro);
+
+    xml.abrir("vinculo", &[])
+        .campo("matricula", cadastro.matricula.as_deref().unwrap_or_default())
+        .campo("tpRegTrab", if cadastro.celetista() { "1" } else { "2" })
+        .campo("tpRegPrev", cadastro.regime_previdencia.to_string())
+        .campo("cadIni", "N")
+        .abrir("infoRegimeTrab", &[]);
+    if cadastro.celetista() {
+        xml.abrir("infoCeletista", &[])
+            .campo("dtAdm", &admissao)
+            .campo("tpAdmissao", "1")
+            .campo("indAdmissao", "1")
+            .campo("tpRegJor", "1")
+            .campo("natAtividade", "1")
+            .abrir("FGTS", &[])
+            .campo("dtOpcFGTS", &admissao)
+            .fechar()
+            .fechar();
+    } else {
+        xml.abrir("infoEstatutario", &[])
+            .campo("tpProv", tipo_provimento(cadastro.categoria.unwrap_or_default()))
+            .campo("dtExercicio", &admissao);
+        info_rpps(&mut xml, cadastro);
+        xml.fechar();
+    }
+    xml.fechar();
+    info_contrato(&mut xml, empregador, cadastro);
+
+    EventoGerado {
+        id,
+        xml: xml.concluir(),
+        pendencias,
+    }
+}
+
+/// S-2206 - Alteração de contrato de trabalho/relação estatutária (cargo e vínculo atuais)
+pub fn s2206(
+    id: String,
+    empregador: &Empregador,
+    cadastro: &CadastroTrabalhador,
+    data_alteracao: NaiveDate,
+) -> EventoGerado {
+    let pendencias = cadastro.pendencias(&[
+        ("matrícula", cadastro.matricula.is_some()),
+        ("cargo", cadastro.cargo.is_some()),
+        ("categoria do eSocial no vínculo", cadastro.categoria.is_some()),
+    ]);
+    let mut xml = Xml::new();
+
+    abrir_evento(&mut xml, "evtAltContratual", &id);
+    ide_evento_nao_periodico(&mut xml, empregador);
+    ide_empregador(&mut xml, empregador);
+    xml.abrir("ideVinculo", &[])
+        .campo("cpfTrab", cadastro.cpf())
+        .campo("matricula", cadastro.matricula.as_deref().unwrap_or_default())
+        .fechar()
+        .abrir("altContratual", &[])
+        .campo("dtAlteracao", data(Some(data_alteracao)))
+        .abrir("vinculo", &[])
+        .campo("tpRegPrev", cadastro.regime_previdencia.to_string())
+        .fechar()
+        .abrir("infoRegimeTrab", &[]);
+    if cadastro.celetista() {
+        xml.abrir("infoCeletista", &[])
+            .campo("tpRegJor", "1")
+            .campo("natAtividade", "1")
+            .fechar();
+    } else {
+        xml.abrir("infoEstatutario", &[]);
+        info_rpps(&mut xml, cadastro);
+        xml.fechar();
+    }
+    xml.fechar();
+    info_contrato(&mut xml, empregador, cadastro);
+
+    EventoGerado {
+        id,
+        xml: xml.concluir(),
+        pendencias,
+    }
+}
+
+/// S-2299 - Desligamento
+pub fn s2299(id: String, empregador: &Empregador, cadastro: &CadastroTrabalhador) -> EventoGerado {
+    let pendencias = cadastro.pendencias(&[
+        ("matrícula", cadastro.matricula.is_some()),
+        ("data de desligamento", cadastro.data_desligamento.is_some()),
+        ("motivo do desligamento", cadastro.motivo_desligamento.is_some()),
+    ]);
+    let mut xml = Xml::new();
+
+    abrir_evento(&mut xml, "evtDeslig", &id);
+    ide_evento_nao_periodico(&mut xml, empregador);
+    ide_empregador(&mut xml, empregador);
+    xml.abrir("ideVinculo", &[])
+        .campo("cpfTrab", cadastro.cpf())
+        .campo("matricula", cadastro.matricula.as_deref().unwrap_or_default())
+        .fechar()
+        .abrir("infoDeslig", &[])
+        .campo("mtvDeslig", cadastro.motivo_desligamento.as_deref().unwrap_or_default())
+        .campo("dtDeslig", data(cadastro.data_desligamento));
+    if cadastro.celetista() {
+        // aviso prévio indenizado não é pago pela folha
+        xml.campo("indPagtoAPI", "N");
+    }
+    // 0 = sem pensão alimentícia nas verbas rescisórias
+    xml.campo("pensAlim", "0");
+
+    EventoGerado {
+        id,
+        xml: xml.concluir(),
+        pendencias,
+    }
+}
+
+/// S-2300 - Trabalhador sem vínculo de emprego/estatutário - início
+pub fn s2300(id: String, empregador: &Empregador, cadastro: &CadastroTrabalhador) -> EventoGerado {
+    let pendencias = cadastro.pendencias_admissao();
+    let mut xml = Xml::new();
+
+    abrir_evento(&mut xml, "evtTSVInicio", &id);
+    ide_evento_nao_periodico(&mut xml, empregador);
+    ide_empregador(&mut xml, empregador);
+    dados_trabalhador(&mut xml, cadastro);
+
+    xml.abrir("infoTSVInicio", &[])
+        .campo("cadIni", "N");
+    if let Some(matricula) = &cadastro.matricula {
+        xml.campo("matricula", matricula);
+    }
+    xml.campo("codCateg", cadastro.categoria.unwrap_or_default().to_string())
+        .campo("dtInicio", data(cadastro.data_admissao))
+        .abrir("infoComplementares", &[])
+        .abrir("cargoFuncao", &[])
+        .campo("nmCargo", cadastro.cargo.as_deref().unwrap_or_default());
+    if let Some(cbo) = &cadastro.cbo {
+        xml.campo("CBOCargo", cbo);
+    }
+    xml.fechar();
+    remuneracao(&mut xml, cadastro);
+
+    EventoGerado {
+        id,
+        xml: xml.concluir(),
+        pendencias,
+    }
+}
+
+/// Evento não periódico do tipo pedido; `data_evento` é a data de alteração no S-2206
+pub fn nao_periodico(
+    tipo: &str,
+    id: String,
+    empregador: &Empregador,
+    cadastro: &CadastroTrabalhador,
+    data_evento: NaiveDate,
+) -> EventoGerado {
+    match tipo {
+        ESOCIAL_S2206 => s2206(id, empregador, cadastro, data_evento),
+        ESOCIAL_S2299 => s2299(id, empregador, cadastro),
+        ESOCIAL_S2300 => s2300(id, empregador, cadastro),
+        _ => s2200(id, empregador, cadastro),
+    }
+}
+
+/// Resultado da validação do XML contra o XSD
+#[derive(Debug, PartialEq)]
+pub enum Validacao {
+    Valido,
+    Invalido(Vec<String>),
+    /// Validador ou XSD indisponível: o evento fica só gerado
+    Indisponivel(String),
+}
+
+fn arquivo_xsd(tipo: &str) -> &'static str {
+    match tipo {
+        ESOCIAL_S1210 => "evtPgtos.xsd",
+        ESOCIAL_S2200 => "evtAdmissao.xsd",
+        ESOCIAL_S2206 => "evtAltContratual.xsd",
+        ESOCIAL_S2299 => "evtDeslig.xsd",
+        ESOCIAL_S2300 => "evtTSVInicio.xsd",
+        _ => "evtRemun.xsd",
+    }
+}
+
+/// Valida os eventos com o xmllint (libxml2) e os XSD oficiais do eSocial, sem acesso
+/// à rede. Os XSD exigem a assinatura digital, que só existe depois de assinado;
+/// por isso a validação usa uma cópia dos XSD com a ds:Signature opcional
+pub struct ValidadorXsd {
+    pasta: Option<PathBuf>,
+    xmllint: String,
+    indisponivel: Option<String>,
+}
+
+impl ValidadorXsd {
+    /// XSD em ESOCIAL_XSD_DIR (padrão esocial/xsd) e o xmllint em ESOCIAL_XMLLINT
+    pub fn new() -> Self {
+        let origem = PathBuf::from(
+            std::env::var("ESOCIAL_XSD_DIR").unwrap_or_else(|_| "esocial/xsd".to_string()),
+        );
+        let xmllint = std::env::var("ESOCIAL_XMLLINT").unwrap_or_else(|_| "xmllint".to_string());
+
+        match Self::preparar(&origem) {
+            Ok(pasta) => Self {
+                pasta: Some(pasta),
+                xmllint,
+                indisponivel: None,
+            },
+            Err(err) => Self {
+                pasta: None,
+                xmllint,
+                indisponivel: Some(err.to_string()),
+            },
+        }
+    }
+
+    fn preparar(origem: &Path) -> Result<PathBuf> {
+        if !origem.is_dir() {
+            anyhow::bail!("Pasta dos XSD do eSocial não encontrada: {}", origem.display());
+        }
+
+        let assinatura = Regex::new(r#"<xs:element\s+ref="ds:Signature"\s*/>"#)?;
+        let pasta = std::env::temp_dir().join(format!("esocial-xsd-{}", Uuid::new_v4()));
+        fs::create_dir_all(&pasta)?;
+        for entrada in fs::read_dir(origem)? {
+            let caminho = entrada?.path();
+            if caminho.extension().is_some_and(|e| e.eq_ignore_ascii_case("xsd")) {
+                let conteudo = fs::read_to_string(&caminho)?;
+                let conteudo =
+                    assinatura.replace_all(&conteudo, r#"<xs:element ref="ds:Signature" minOccurs="0"/>"#);
+                fs::write(pasta.join(caminho.file_name().unwrap_or_default()), conteudo.as_bytes())?;
+            }
+        }
+        Ok(pasta)
+    }
+
+    /// Processo bloqueante; chamar fora das threads do runtime
+    pub fn validar(&self, tipo: &str, xml: &str) -> Validacao {
+        let Some(pasta) = &self.pasta else {
+            return Validacao::Indisponivel(self.indisponivel.clone().unwrap_or_default());
+        };
+        let xsd = pasta.join(arquivo_xsd(tipo));
+        if !xsd.is_file() {
+            return Validacao::Indisponivel(format!(
+                "XSD do eSocial não encontrado: {}",
+                arquivo_xsd(tipo)
+            ));
+        }
+
+        let processo = Command::new(&self.xmllint)
+            .arg("--noout")
+            .arg("--nonet")
+            .arg("--schema")
+            .arg(xsd)
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|mut processo| {
+                if let Some(mut entrada) = processo.stdin.take() {
+                    entrada.write_all(xml.as_bytes())?;
+                }
+                processo.wait_with_output()
+            });
+        let saida = match processo {
+            Ok(saida) => saida,
+            Err(err) => {
+                return Validacao::Indisponivel(format!(
+                    "Não foi possível executar o {}: {}",
+                    self.xmllint, err
+                ));
+            }
+        };
+
+        let mensagens: Vec<String> = String::from_utf8_lossy(&saida.stderr)
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.ends_with("validates") && !l.ends_with("fails to validate"))
+            .map(|l| l.trim_start_matches("-:1: ").to_string())
+            .collect();
+        match saida.status.code() {
+            Some(0) => Validacao::Valido,
+            // 3 = documento não confere com o XSD; 1 = XML mal formado
+            Some(1) | Some(3) => Validacao::Invalido(mensagens),
+            _ => Validacao::Indisponivel(format!(
+                "Falha ao validar com os XSD do eSocial: {}",
+                mensagens.join("; ")
+            )),
+        }
+    }
+}
+
+impl Drop for ValidadorXsd {
+    fn drop(&mut self) {
+        if let Some(pasta) = &self.pasta {
+            let _ = fs::remove_dir_all(pasta);
+        }
+    }
+}
+
+/// ZIP com um XML por evento (nome = Id do evento), para o assinador/transmissor
+pub fn eventos_zip(eventos: &[(String, String, String)]) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let opcoes = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for (id_evento, tipo, xml) in eventos {
+        zip.start_file(format!("{}/{}.xml", tipo, id_evento), opcoes)?;
+        zip.write_all(xml.as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn trabalhador(cpf: Option<&str>, categoria: Option<i32>) -> Trabalhador {
+        Trabalhador {
+            servidor_id: 1,
+            nome: "Maria & Filhos".to_string(),
+            cpf: cpf.map(str::to_string),
+            matricula: Some("123".to_string()),
+            demonstrativos: vec![Demonstrativo {
+                folha_id: 10,
+                categoria,
+                itens: vec![ItemRemuneracao {
+                    codigo: "001".to_string(),
+                    valor: BigDecimal::from_str("1500.5").unwrap(),
+                }],
+                valor_liquido: BigDecimal::from_str("1320.456").unwrap(),
+            }],
+        }
+    }
+
+    fn empregador() -> Empregador {
+        Empregador {
+            cnpj: "11222333000181".to_string(),
+            ambiente: 2,
+            tabela_rubrica: "FOLHA".to_string(),
+            lotacao: "LOT<1>".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_id_evento() {
+        let gerado_em = NaiveDate::from_ymd_opt(2025, 3, 5)
+            .unwrap()
+            .and_hms_opt(14, 7, 9)
+            .unwrap();
+        let id = id_evento("11222333000181", gerado_em, 12);
+        assert_eq!(id, "ID1112223330001812025030514070900012");
+        assert_eq!(id.len(), 36);
+    }
+
+    #[test]
+    fn test_s1200() {
+        let evento = s1200("ID1".to_string(), &empregador(), 2025, 3, &trabalhador(Some("52998224725"), Some(301)));
+        assert!(evento.pendencias.is_empty());
+        assert!(evento.xml.starts_with(
+            r#"<?xml version="1.0" encoding="UTF-8"?><eSocial xmlns="http://www.esocial.gov.br/schema/evt/evtRemun/v_S_01_03_00"><evtRemun Id="ID1">"#
+        ));
+        assert!(evento.xml.contains("<perApur>2025-03</perApur>"));
+        assert!(evento.xml.contains("<codLotacao>LOT&lt;1&gt;</codLotacao>"));
+        assert!(evento.xml.contains(
+            "<itensRemun><codRubr>001</codRubr><ideTabRubr>FOLHA</ideTabRubr><vrRubr>1500.50</vrRubr><indApurIR>0</indApurIR></itensRemun><infoAgNocivo>"
+        ));
+        assert!(evento.xml.ends_with("</dmDev></evtRemun></eSocial>"));
+
+        let evento = s1200("ID1".to_string(), &empregador(), 2025, 3, &trabalhador(None, None));
+        assert_eq!(evento.pendencias.len(), 2);
+        assert!(!evento.xml.contains("<infoAgNocivo>"));
+    }
+
+    #[test]
+    fn test_s1210() {
+        let pagamento = NaiveDate::from_ymd_opt(2025, 4, 5).unwrap();
+        let evento = s1210(
+            "ID1".to_string(),
+            &empregador(),
+            2025,
+            3,
+            pagamento,
+            &trabalhador(Some("529.982.247-25"), Some(301)),
+        );
+        assert!(evento.pendencias.is_empty());
+        assert!(evento.xml.contains("<cpfBenef>52998224725</cpfBenef>"));
+        assert!(evento.xml.contains("<perApur>2025-04</perApur>"));
+        assert!(evento.xml.contains(
+            "<infoPgto><dtPgto>2025-04-05</dtPgto><tpPgto>1</tpPgto><perRef>2025-03</perRef><ideDmDev>10</ideDmDev><vrLiq>1320.46</vrLiq></infoPgto>"
+        ));
+    }
+
+    fn contrato(cargo_id: i32, categoria: i32, desligamento: Option<NaiveDate>) -> Contrato {
+        Contrato {
+            orgao_id: Some(1),
+            data_admissao: NaiveDate::from_ymd_opt(2024, 2, 1),
+            cargo_id: Some(cargo_id),
+            vinculo_id: Some(1),
+            categoria: Some(categoria),
+            data_desligamento: desligamento,
+        }
+    }
+
+    #[test]
+    fn test_eventos_da_alteracao() {
+        let hoje = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let admissao = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+        let desligamento = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+        // admissão ainda fora da fila: S-2200 para vínculo, S-2300 sem vínculo
+        assert_eq!(
+            eventos_da_alteracao(None, &contrato(1, 301, None), false, hoje),
+            vec![(ESOCIAL_S2200, admissao)]
+        );
+        assert_eq!(
+            eventos_da_alteracao(None, &contrato(1, 701, None), false, hoje),
+            vec![(ESOCIAL_S2300, admissao)]
+        );
+
+        let antes = contrato(1, 301, None);
+        assert!(eventos_da_alteracao(Some(&antes), &contrato(1, 301, None), true, hoje).is_empty());
+        assert_eq!(
+            eventos_da_alteracao(Some(&antes), &contrato(2, 301, None), true, hoje),
+            vec![(ESOCIAL_S2206, hoje)]
+        );
+        assert_eq!(
+            eventos_da_alteracao(Some(&antes), &contrato(1, 301, Some(desligamento)), true, hoje),
+            vec![(ESOCIAL_S2299, desligamento)]
+        );
+        // desligamento já enfileirado não repete
+        let desligado = contrato(1, 301, Some(desligamento));
+        assert!(eventos_da_alteracao(Some(&desligado), &desligado, true, hoje).is_empty());
+
+        let mut sem_orgao = contrato(1, 301, None);
+        sem_orgao.orgao_id = None;
+        assert!(eventos_da_alteracao(None, &sem_orgao, false, hoje).is_empty());
+    }
+
+    fn cadastro() -> CadastroTrabalhador {
+        CadastroTrabalhador {
+            nome: "Maria".to_string(),
+            cpf: Some("52998224725".to_string()),
+            matricula: Some("123".to_string()),
+            sexo: Some("F".to_string()),
+            raca_cor: Some(3),
+            grau_instrucao: Some("09".to_string()),
+            data_nascimento: NaiveDate::from_ymd_opt(1990, 5, 20),
+            logradouro: Some("Rua A".to_string()),
+            numero: None,
+            complemento: None,
+            bairro: Some("Centro".to_string()),
+            cep: Some("64000000".to_string()),
+            codigo_municipio: Some(2211001),
+            uf: Some("PI".to_string()),
+            data_admissao: NaiveDate::from_ymd_opt(2024, 2, 1),
+            cargo: Some("Professor".to_string()),
+            cbo: Some("231205".to_string()),
+            categoria: Some(301),
+            regime_previdencia: 2,
+            salario: None,
+            data_desligamento: None,
+            motivo_desligamento: None,
+        }
+    }
+
+    #[test]
+    fn test_s2200_estatutario() {
+        let evento = s2200("ID1".to_string(), &empregador(), &cadastro());
+        assert!(evento.pendencias.is_empty());
+        assert!(evento.xml.contains("<nrLograd>S/N</nrLograd><bairro>Centro</bairro><cep>64000000</cep>"));
+        assert!(evento.xml.contains(
+            "<tpRegTrab>2</tpRegTrab><tpRegPrev>2</tpRegPrev><cadIni>N</cadIni><infoRegimeTrab><infoEstatutario><tpProv>1</tpProv><dtExercicio>2024-02-01</dtExercicio><tpPlanRP>0</tpPlanRP>"
+        ));
+        assert!(!evento.xml.contains("<remuneracao>"));
+
+        let mut incompleto = cadastro();
+        incompleto.sexo = None;
+        incompleto.codigo_municipio = None;
+        assert_eq!(s2200("ID1".to_string(), &empregador(), &incompleto).pendencias.len(), 2);
+    }
+
+    #[test]
+    fn test_s2299() {
+        let mut desligado = cadastro();
+        desligado.data_desligamento = NaiveDate::from_ymd_opt(2025, 3, 31);
+        desligado.motivo_desligamento = Some("02".to_string());
+        let evento = s2299("ID1".to_string(), &empregador(), &desligado);
+        assert!(evento.pendencias.is_empty());
+        assert!(evento.xml.ends_with(
+            "<infoDeslig><mtvDeslig>02</mtvDeslig><dtDeslig>2025-03-31</dtDeslig><pensAlim>0</pensAlim></infoDeslig></evtDeslig></eSocial>"
+        ));
+
+        assert_eq!(s2299("ID1".to_string(), &empregador(), &cadastro()).pendencias.len(), 2);
+    }
+}
@@ -0,0 +1,180 @@
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use chrono::{Months, NaiveDate};
+
+use crate::{cadastro::calculo::arredondar, error::AppError};
+
+/// Códigos de sistema das rubricas lançadas no cálculo das férias (cadastro_rubrica.codigo_sistema)
+pub const SISTEMA_FERIAS: &str = "FERIAS";
+pub const SISTEMA_TERCO_FERIAS: &str = "TERCO_FERIAS";
+pub const SISTEMA_ABONO_FERIAS: &str = "ABONO_FERIAS";
+pub const SISTEMA_TERCO_ABONO_FERIAS: &str = "TERCO_ABONO_FERIAS";
+
+/// Dias de férias a cada período aquisitivo completo
+pub const DIAS_DIREITO: i32 = 30;
+
+/// Fracionamento (CLT, art. 134, § 1º): até três períodos, um deles com pelo menos 14
+/// dias corridos e os demais com pelo menos 5
+const MAXIMO_PERIODOS: usize = 3;
+const DIAS_PERIODO_PRINCIPAL: i32 = 14;
+const DIAS_MINIMOS: i32 = 5;
+
+/// Períodos aquisitivos de 12 meses contados da admissão, do primeiro até o que está
+/// em curso na data informada
+pub fn periodos_aquisitivos(admissao: NaiveDate, ate: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut periodos = Vec::new();
+    for anos in 0.. {
+        let Some(inicio) = admissao.checked_add_months(Months::new(12 * anos)) else {
+            break;
+        };
+        if inicio > ate {
+            break;
+        }
+        let fim = admissao
+            .checked_add_months(Months::new(12 * (anos + 1)))
+            .and_then(|proximo| proximo.pred_opt())
+            .unwrap_or(NaiveDate::MAX);
+        periodos.push((inicio, fim));
+    }
+    periodos
+}
+
+/// Abono pecuniário (conversão de 1/3 das férias em dinheiro, CLT art. 143) só para
+/// empregados celetistas: categorias 1xx do eSocial
+pub fn abono_permitido(categoria_esocial: Option<i32>) -> bool {
+    matches!(categoria_esocial, Some(100..=199))
+}
+
+/// Confere uma nova fração de férias contra as já programadas no período aquisitivo:
+/// `gozos` são os dias de cada fração existente e `abono` os dias já vendidos
+pub fn validar_fracionamento(
+    direito: i32,
+    gozos: &[i32],
+    abono: i32,
+    dias: i32,
+    dias_abono: i32,
+) -> Result<()> {
+    let erro = |mensagem: String| Err(AppError::InvalidInput(mensagem).into());
+
+    if dias < DIAS_MINIMOS {
+        return erro(format!("Cada período de férias deve ter pelo menos {} dias", DIAS_MINIMOS));
+    }
+    if gozos.len() >= MAXIMO_PERIODOS {
+        return erro(format!("As férias podem ser divididas em até {} períodos", MAXIMO_PERIODOS));
+    }
+    if dias_abono > 0 && abono > 0 {
+        return erro("O abono pecuniário deste período aquisitivo já foi concedido".to_string());
+    }
+    if dias_abono > direito / 3 {
+        return erro(format!("O abono pecuniário é de no máximo {} dias", direito / 3));
+    }
+
+    let usados = gozos.iter().sum::<i32>() + abono + dias + dias_abono;
+    if usados > direito {
+        return erro(format!(
+            "O período aquisitivo tem {} dias de férias; restam {}",
+            direito,
+            direito - (usados - dias - dias_abono)
+        ));
+    }
+
+    // sem uma fração de 14 dias, ela ainda precisa caber no que sobra
+    let tem_principal = gozos.iter().chain([&dias]).any(|d| *d >= DIAS_PERIODO_PRINCIPAL);
+    let ultima_fracao = gozos.len() + 1 == MAXIMO_PERIODOS;
+    if !tem_principal && (ultima_fracao || direito - usados < DIAS_PERIODO_PRINCIPAL) {
+        return erro(format!(
+            "Um dos períodos de férias deve ter pelo menos {} dias corridos",
+            DIAS_PERIODO_PRINCIPAL
+        ));
+    }
+    Ok(())
+}
+
+/// Último dia de gozo das férias que começam na data
+pub fn data_fim(inicio: NaiveDate, dias: i32) -> NaiveDate {
+    inicio + chrono::Duration::days(i64::from(dias) - 1)
+}
+
+/// Valores pagos pelas férias, em reais (arredondados ao centavo)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValoresFerias {
+    pub ferias: BigDecimal,
+    pub terco: BigDecimal,
+    pub abono: BigDecimal,
+    pub terco_abono: BigDecimal,
+}
+
+/// Remuneração dos dias de gozo e dos dias vendidos (salário / 30 por dia), cada uma
+/// acrescida do terço constitucional
+pub fn valores(salario: &BigDecimal, dias: i32, dias_abono: i32) -> ValoresFerias {
+    let dias_do_mes = BigDecimal::from(DIAS_DIREITO);
+    let ferias = arredondar(&(salario * BigDecimal::from(dias) / &dias_do_mes));
+    let abono = arredondar(&(salario * BigDecimal::from(dias_abono) / &dias_do_mes));
+    ValoresFerias {
+        terco: arredondar(&(&ferias / BigDecimal::from(3))),
+        terco_abono: arredondar(&(&abono / BigDecimal::from(3))),
+        ferias,
+        abono,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn dec(valor: &str) -> BigDecimal {
+        BigDecimal::from_str(valor).unwrap()
+    }
+
+    fn data(ano: i32, mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(ano, mes, dia).unwrap()
+    }
+
+    #[test]
+    fn test_periodos_aquisitivos() {
+        let periodos = periodos_aquisitivos(data(2023, 3, 15), data(2025, 3, 14));
+        assert_eq!(
+            periodos,
+            vec![
+                (data(2023, 3, 15), data(2024, 3, 14)),
+                (data(2024, 3, 15), data(2025, 3, 14)),
+            ]
+        );
+        // no dia seguinte começa o terceiro
+        assert_eq!(periodos_aquisitivos(data(2023, 3, 15), data(2025, 3, 15)).len(), 3);
+        assert!(periodos_aquisitivos(data(2025, 1, 1), data(2024, 12, 31)).is_empty());
+    }
+
+    #[test]
+    fn test_fracionamento() {
+        assert!(validar_fracionamento(30, &[], 0, 30, 0).is_ok());
+        assert!(validar_fracionamento(30, &[], 0, 20, 10).is_ok());
+        assert!(validar_fracionamento(30, &[14], 0, 11, 5).is_ok());
+        assert!(validar_fracionamento(30, &[14, 5], 0, 11, 0).is_ok());
+        // frações de 10 + 10 deixam 10 dias: nenhuma chega a 14
+        assert!(validar_fracionamento(30, &[10], 0, 10, 0).is_err());
+        assert!(validar_fracionamento(30, &[10], 0, 14, 0).is_ok());
+        // quarta fração, menos de 5 dias, acima do direito
+        assert!(validar_fracionamento(30, &[14, 5, 5], 0, 5, 0).is_err());
+        assert!(validar_fracionamento(30, &[14], 0, 4, 0).is_err());
+        assert!(validar_fracionamento(30, &[20], 0, 11, 0).is_err());
+        // abono de no máximo 1/3 e uma vez só
+        assert!(validar_fracionamento(30, &[], 0, 19, 11).is_err());
+        assert!(validar_fracionamento(30, &[15], 5, 5, 5).is_err());
+    }
+
+    #[test]
+    fn test_valores() {
+        let valores = valores(&dec("3000.00"), 20, 10);
+        assert_eq!(valores.ferias, dec("2000.00"));
+        assert_eq!(valores.terco, dec("666.67"));
+        assert_eq!(valores.abono, dec("1000.00"));
+        assert_eq!(valores.terco_abono, dec("333.33"));
+        assert_eq!(data_fim(data(2025, 2, 10), 20), data(2025, 3, 1));
+        assert!(abono_permitido(Some(101)));
+        assert!(!abono_permitido(Some(301)));
+        assert!(!abono_permitido(None));
+    }
+}
@@ -0,0 +1,68 @@
+use bigdecimal::BigDecimal;
+
+use crate::cadastro::model::AlteracaoSalarial;
+
+/// Código de sistema da rubrica do salário contratual (cadastro_rubrica.codigo_sistema):
+/// na geração ela recebe o salário da nova folha em vez do valor da origem
+pub const SISTEMA_SALARIO: &str = "SALARIO";
+
+/// Referência do salário contratual na folha gerada: o mês comercial inteiro
+pub const DIAS_MES: i32 = 30;
+
+/// Salário do servidor na competência gerada e as alterações pendentes que ele aplica: as
+/// com vigência até a competência, valendo a mais recente. Sem nenhuma, fica o da origem
+pub fn salario_vigente(
+    salario: &BigDecimal,
+    pendentes: &[AlteracaoSalarial],
+    ano: i32,
+    mes: i32,
+) -> (BigDecimal, Vec<i32>) {
+    let mut vigentes: Vec<_> = pendentes
+        .iter()
+        .filter(|alteracao| (alteracao.ano, alteracao.mes) <= (ano, mes))
+        .collect();
+    vigentes.sort_by_key(|alteracao| (alteracao.ano, alteracao.mes));
+
+    let novo = vigentes.last().map_or_else(|| salario.clone(), |alteracao| alteracao.salario.clone());
+    (novo, vigentes.iter().map(|alteracao| alteracao.id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::Utc;
+
+    use super::*;
+
+    fn dec(valor: &str) -> BigDecimal {
+        BigDecimal::from_str(valor).unwrap()
+    }
+
+    fn alteracao(id: i32, ano: i32, mes: i32, salario: &str) -> AlteracaoSalarial {
+        AlteracaoSalarial {
+            id,
+            servidor_id: 1,
+            ano,
+            mes,
+            salario: dec(salario),
+            motivo: "REAJUSTE".to_string(),
+            folha_id: None,
+            usuario: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_salario_vigente() {
+        let pendentes = [
+            alteracao(3, 2025, 5, "4000.00"),
+            alteracao(1, 2025, 1, "3200.00"),
+            alteracao(2, 2025, 3, "3500.00"),
+        ];
+        // a de maio ainda não vale em março; as anteriores são aplicadas juntas
+        assert_eq!(salario_vigente(&dec("3000.00"), &pendentes, 2025, 3), (dec("3500.00"), vec![1, 2]));
+        assert_eq!(salario_vigente(&dec("3000.00"), &pendentes, 2024, 12), (dec("3000.00"), vec![]));
+        assert_eq!(salario_vigente(&dec("3000.00"), &[], 2025, 3), (dec("3000.00"), vec![]));
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+
+use crate::{
+    error::AppError,
+    utils::csv::{decodificar, detectar_separador, dividir_linha, normalizar_cabecalho},
+};
+
+/// Códigos IBGE das UFs e as siglas correspondentes (a planilha do IBGE não traz a sigla)
+const SIGLAS_UF: [(i32, &str); 27] = [
+    (11, "RO"),
+    (12, "AC"),
+    (13, "AM"),
+    (14, "RR"),
+    (15, "PA"),
+    (16, "AP"),
+    (17, "TO"),
+    (21, "MA"),
+    (22, "PI"),
+    (23, "CE"),
+    (24, "RN"),
+    (25, "PB"),
+    (26, "PE"),
+    (27, "AL"),
+    (28, "SE"),
+    (29, "BA"),
+    (31, "MG"),
+    (32, "ES"),
+    (33, "RJ"),
+    (35, "SP"),
+    (41, "PR"),
+    (42, "SC"),
+    (43, "RS"),
+    (50, "MS"),
+    (51, "MT"),
+    (52, "GO"),
+    (53, "DF"),
+];
+
+/// Cabeçalhos aceitos para cada coluna, já normalizados (minúsculas, sem acento, "_")
+const COLUNAS_CODIGO_UF: [&str; 3] = ["uf", "codigo_uf", "cod_uf"];
+const COLUNAS_NOME_UF: [&str; 2] = ["nome_uf", "uf_nome"];
+const COLUNAS_CODIGO_MUNICIPIO: [&str; 4] = [
+    "codigo_municipio_completo",
+    "cod_municipio_completo",
+    "codigo_municipio",
+    "cod_municipio",
+];
+const COLUNAS_NOME_MUNICIPIO: [&str; 2] = ["nome_municipio", "municipio_nome"];
+
+#[derive(Debug, PartialEq)]
+pub struct UfIbge {
+    pub codigo: i32,
+    pub sigla: &'static str,
+    pub nome: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MunicipioIbge {
+    pub codigo: i32,
+    pub codigo_uf: i32,
+    pub nome: String,
+}
+
+/// Conteúdo da planilha de divisão territorial (DTB): UFs sem repetição e municípios
+#[derive(Debug)]
+pub struct DivisaoTerritorial {
+    pub ufs: Vec<UfIbge>,
+    pub municipios: Vec<MunicipioIbge>,
+}
+
+pub fn sigla_uf(codigo: i32) -> Option<&'static str> {
+    SIGLAS_UF
+        .iter()
+        .find(|(c, _)| *c == codigo)
+        .map(|(_, sigla)| *sigla)
+}
+
+/// Código IBGE de município: 7 dígitos, começando pelo código da UF
+pub fn codigo_municipio_valido(codigo: i32) -> bool {
+    (1_000_000..=9_999_999).contains(&codigo) && sigla_uf(codigo / 100_000).is_some()
+}
+
+/// Lê o CSV exportado da planilha DTB do IBGE. As colunas são localizadas pelo
+/// cabeçalho; o código da UF, se faltar, sai dos dois primeiros dígitos do município
+pub fn ler_dtb(conteudo: &[u8]) -> Result<DivisaoTerritorial> {
+    let texto = decodificar(conteudo);
+    let mut linhas = texto.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+
+    let (_, cabecalho) = linhas
+        .next()
+        .ok_or_else(|| AppError::InvalidInput("Arquivo do IBGE vazio".to_string()))?;
+    let separador = detectar_separador(cabecalho);
+    let colunas: Vec<String> = dividir_linha(cabecalho, separador)
+        .iter()
+        .map(|c| normalizar_cabecalho(c))
+        .collect();
+    let posicao = |nomes: &[&str]| {
+        nomes
+            .iter()
+            .find_map(|nome| colunas.iter().position(|c| c == nome))
+    };
+
+    let col_codigo_uf = posicao(&COLUNAS_CODIGO_UF);
+    let (col_nome_uf, col_codigo, col_nome) = match (
+        posicao(&COLUNAS_NOME_UF),
+        posicao(&COLUNAS_CODIGO_MUNICIPIO),
+        posicao(&COLUNAS_NOME_MUNICIPIO),
+    ) {
+        (Some(nome_uf), Some(codigo), Some(nome)) => (nome_uf, codigo, nome),
+        _ => {
+            return Err(AppError::InvalidInput(
+                "Cabeçalho do arquivo do IBGE deve ter as colunas Nome_UF, Código Município Completo e Nome_Município"
+                    .to_string(),
+            )
+            .into());
+        }
+    };
+
+    let mut ufs = BTreeMap::new();
+    let mut municipios = BTreeMap::new();
+    for (indice, linha) in linhas {
+        let numero = indice + 1;
+        let campos = dividir_linha(linha, separador);
+        let campo = |posicao: usize| campos.get(posicao).map(|c| c.trim()).unwrap_or_default();
+        let erro = |mensagem: &str| AppError::InvalidInput(format!("Linha {}: {}", numero, mensagem));
+
+        let codigo: i32 = campo(col_codigo)
+            .parse()
+            .ok()
+            .filter(|c| codigo_municipio_valido(*c))
+            .ok_or_else(|| erro("código do município inválido"))?;
+        let codigo_uf = match col_codigo_uf {
+            Some(posicao) => campo(posicao)
+                .parse()
+                .map_err(|_| erro("código da UF inválido"))?,
+            None => codigo / 100_000,
+        };
+        let sigla = sigla_uf(codigo_uf).ok_or_else(|| erro("código da UF inválido"))?;
+        if codigo / 100_000 != codigo_uf {
+            return Err(erro("código do município não pertence à UF").into());
+        }
+        let nome = campo(col_nome);
+        let nome_uf = campo(col_nome_uf);
+        if nome.is_empty() || nome_uf.is_empty() {
+            return Err(erro("nome do município ou da UF em branco").into());
+        }
+
+        ufs.entry(codigo_uf).or_insert_with(|| UfIbge {
+            codigo: codigo_uf,
+            sigla,
+            nome: nome_uf.to_string(),
+        });
+        municipios.insert(
+            codigo,
+            MunicipioIbge {
+                codigo,
+                codigo_uf,
+                nome: nome.to_string(),
+            },
+        );
+    }
+
+    if municipios.is_empty() {
+        return Err(AppError::InvalidInput("Arquivo do IBGE sem municípios".to_string()).into());
+    }
+
+    Ok(DivisaoTerritorial {
+        ufs: ufs.into_values().collect(),
+        municipios: municipios.into_values().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ler_dtb() {
+        let csv = "UF;Nome_UF;Região Geográfica Intermediária;Município;Código Município Completo;Nome_Município\n\
+                   11;Rondônia;1101;00015;1100015;Alta Floresta D'Oeste\n\
+                   11;Rondônia;1101;00205;1100205;\"Porto Velho\"\n\
+                   35;São Paulo;3501;50308;3550308;São Paulo\n";
+        let dtb = ler_dtb(csv.as_bytes()).unwrap();
+        assert_eq!(dtb.ufs.len(), 2);
+        assert_eq!(dtb.ufs[1], UfIbge { codigo: 35, sigla: "SP", nome: "São Paulo".to_string() });
+        assert_eq!(dtb.municipios.len(), 3);
+        assert_eq!(
+            dtb.municipios[1],
+            MunicipioIbge { codigo: 1100205, codigo_uf: 11, nome: "Porto Velho".to_string() }
+        );
+
+        // Latin-1 com vírgula e sem a coluna do código da UF
+        let latin1: Vec<u8> = "Nome_UF,Código Município Completo,Nome_Município\nRondônia,1100205,Porto Velho\n"
+            .chars()
+            .map(|c| c as u8)
+            .collect();
+        let dtb = ler_dtb(&latin1).unwrap();
+        assert_eq!(dtb.ufs[0].nome, "Rondônia");
+        assert_eq!(dtb.municipios[0].codigo_uf, 11);
+
+        let errado = "UF;Nome_UF;Código Município Completo;Nome_Município\n11;Rondônia;3550308;São Paulo\n";
+        assert!(ler_dtb(errado.as_bytes()).is_err());
+        assert!(ler_dtb("Nome;Codigo\nx;1".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+use std::sync::Arc;
+
+use bigdecimal::{BigDecimal, Zero};
+
+use crate::cadastro::{
+    calculo::{SISTEMA_INSS, SISTEMA_IRRF},
+    model::{RUBRICA_DESCONTO, RUBRICA_PROVENTO, Timbre},
+};
+
+/// Linha do comprovante anual de rendimentos pagos e de IRRF onde a rubrica é somada
+/// (cadastro_rubrica.informe_rendimentos)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinhaInforme {
+    Tributavel,
+    Previdencia,
+    PrevidenciaComplementar,
+    Pensao,
+    Irrf,
+    Diarias,
+    MolestiaGrave,
+    Indenizacao,
+    Isento,
+    DecimoTerceiro,
+    PrevidenciaDecimoTerceiro,
+    IrrfDecimoTerceiro,
+    NaoInformar,
+}
+
+/// Código gravado na rubrica e descrição mostrada no cadastro
+pub const LINHAS_INFORME: [(LinhaInforme, &str, &str); 13] = [
+    (LinhaInforme::Tributavel, "TRIBUTAVEL", "Rendimento tributável (inclusive férias)"),
+    (LinhaInforme::Previdencia, "PREVIDENCIA", "Contribuição previdenciária oficial"),
+    (
+        LinhaInforme::PrevidenciaComplementar,
+        "PREVIDENCIA_COMPLEMENTAR",
+        "Previdência complementar",
+    ),
+    (LinhaInforme::Pensao, "PENSAO", "Pensão alimentícia"),
+    (LinhaInforme::Irrf, "IRRF", "Imposto de renda retido na fonte"),
+    (LinhaInforme::Diarias, "DIARIAS", "Isento: diárias e ajudas de custo"),
+    (LinhaInforme::MolestiaGrave, "MOLESTIA_GRAVE", "Isento: proventos por moléstia grave"),
+    (LinhaInforme::Indenizacao, "INDENIZACAO", "Isento: indenizações por rescisão"),
+    (LinhaInforme::Isento, "ISENTO", "Isento: outros"),
+    (LinhaInforme::DecimoTerceiro, "DECIMO_TERCEIRO", "13º salário"),
+    (
+        LinhaInforme::PrevidenciaDecimoTerceiro,
+        "PREVIDENCIA_DECIMO_TERCEIRO",
+        "Contribuição previdenciária do 13º",
+    ),
+    (LinhaInforme::IrrfDecimoTerceiro, "IRRF_DECIMO_TERCEIRO", "IRRF sobre o 13º"),
+    (LinhaInforme::NaoInformar, "NAO_INFORMAR", "Não informar"),
+];
+
+impl LinhaInforme {
+    pub fn from_codigo(codigo: &str) -> Option<Self> {
+        LINHAS_INFORME
+            .iter()
+            .find(|(_, c, _)| *c == codigo)
+            .map(|(linha, _, _)| *linha)
+    }
+
+    /// Linhas de retenção e dedução: somam os descontos. As de rendimento somam os
+    /// proventos e abatem os descontos (ex.: faltas)
+    fn retencao(self) -> bool {
+        matches!(
+            self,
+            Self::Previdencia
+                | Self::PrevidenciaComplementar
+                | Self::Pensao
+                | Self::Irrf
+                | Self::PrevidenciaDecimoTerceiro
+                | Self::IrrfDecimoTerceiro
+        )
+    }
+}
+
+/// Linha do informe da rubrica. Sem linha informada, classifica pelas mesmas regras
+/// da base do IRRF: rubricas de sistema INSS e IRRF, pensão alimentícia, proventos
+/// tributáveis ou isentos e descontos que abatem o tributável. Os demais descontos
+/// (consignações, por exemplo) e as informativas ficam fora
+pub fn classificar(
+    tipo: &str,
+    incide_irrf: bool,
+    codigo_sistema: Option<&str>,
+    pensao_alimenticia: bool,
+    informe_rendimentos: Option<&str>,
+) -> Option<LinhaInforme> {
+    if tipo != RUBRICA_PROVENTO && tipo != RUBRICA_DESCONTO {
+        return None;
+    }
+    if let Some(linha) = informe_rendimentos.and_then(LinhaInforme::from_codigo) {
+        return (linha != LinhaInforme::NaoInformar).then_some(linha);
+    }
+
+    match codigo_sistema {
+        Some(SISTEMA_INSS) => return Some(LinhaInforme::Previdencia),
+        Some(SISTEMA_IRRF) => return Some(LinhaInforme::Irrf),
+        _ => {}
+    }
+    match (tipo, incide_irrf) {
+        (RUBRICA_DESCONTO, _) if pensao_alimenticia => Some(LinhaInforme::Pensao),
+        (_, true) => Some(LinhaInforme::Tributavel),
+        (RUBRICA_PROVENTO, false) => Some(LinhaInforme::Isento),
+        _ => None,
+    }
+}
+
+/// Valores do ano nas linhas do comprovante
+#[derive(Debug, Clone, Default)]
+pub struct ValoresInforme {
+    pub tributavel: BigDecimal,
+    pub previdencia: BigDecimal,
+    pub previdencia_complementar: BigDecimal,
+    pub pensao: BigDecimal,
+    pub irrf: BigDecimal,
+    pub diarias: BigDecimal,
+    pub molestia_grave: BigDecimal,
+    pub indenizacao: BigDecimal,
+    pub isento: BigDecimal,
+    pub decimo_terceiro: BigDecimal,
+    pub previdencia_decimo_terceiro: BigDecimal,
+    pub irrf_decimo_terceiro: BigDecimal,
+}
+
+impl ValoresInforme {
+    /// Soma o valor de uma rubrica do tipo informado na linha
+    pub fn somar(&mut self, linha: LinhaInforme, tipo: &str, valor: &BigDecimal) {
+        let positivo = (tipo == RUBRICA_DESCONTO) == linha.retencao();
+        let total = match linha {
+            LinhaInforme::Tributavel => &mut self.tributavel,
+            LinhaInforme::Previdencia => &mut self.previdencia,
+            LinhaInforme::PrevidenciaComplementar => &mut self.previdencia_complementar,
+            LinhaInforme::Pensao => &mut self.pensao,
+            LinhaInforme::Irrf => &mut self.irrf,
+            LinhaInforme::Diarias => &mut self.diarias,
+            LinhaInforme::MolestiaGrave => &mut self.molestia_grave,
+            LinhaInforme::Indenizacao => &mut self.indenizacao,
+            LinhaInforme::Isento => &mut self.isento,
+            LinhaInforme::DecimoTerceiro => &mut self.decimo_terceiro,
+            LinhaInforme::PrevidenciaDecimoTerceiro => &mut self.previdencia_decimo_terceiro,
+            LinhaInforme::IrrfDecimoTerceiro => &mut self.irrf_decimo_terceiro,
+            LinhaInforme::NaoInformar => return,
+        };
+        if positivo {
+            *total += valor;
+        } else {
+            *total -= valor;
+        }
+    }
+
+    /// 13º salário informado na tributação exclusiva: líquido da contribuição
+    /// previdenciária do 13º
+    pub fn decimo_terceiro_liquido(&self) -> BigDecimal {
+        &self.decimo_terceiro - &self.previdencia_decimo_terceiro
+    }
+
+    pub fn vazio(&self) -> bool {
+        [
+            &self.tributavel,
+            &self.previdencia,
+            &self.previdencia_complementar,
+            &self.pensao,
+            &self.irrf,
+            &self.diarias,
+            &self.molestia_grave,
+            &self.indenizacao,
+            &self.isento,
+            &self.decimo_terceiro,
+            &self.previdencia_decimo_terceiro,
+            &self.irrf_decimo_terceiro,
+        ]
+        .iter()
+        .all(|valor| valor.is_zero())
+    }
+}
+
+/// Total do ano de uma rubrica para um servidor, como lido das folhas
+#[derive(Debug, Clone)]
+pub struct ValorRubricaInforme {
+    pub servidor_id: i32,
+    pub nome: String,
+    pub cpf: Option<String>,
+    pub matricula: Option<String>,
+    pub tipo: String,
+    pub incide_irrf: bool,
+    pub codigo_sistema: Option<String>,
+    pub pensao_alimenticia: bool,
+    pub informe_rendimentos: Option<String>,
+    pub valor: BigDecimal,
+}
+
+/// Informe de rendimentos de um servidor no ano-calendário, com o órgão como fonte pagadora
+#[derive(Debug, Clone)]
+pub struct InformeRendimentos {
+    pub ano: i32,
+    /// Compartilhado entre os informes do mesmo órgão na geração em lote
+    pub timbre: Arc<Timbre>,
+    pub servidor_id: i32,
+    pub nome: String,
+    pub cpf: Option<String>,
+    pub matricula: Option<String>,
+    pub valores: ValoresInforme,
+}
+
+/// Um informe por servidor, na ordem das linhas (agrupadas por servidor); servidores
+/// sem valor a informar ficam de fora
+pub fn montar(ano: i32, timbre: Arc<Timbre>, linhas: Vec<ValorRubricaInforme>) -> Vec<InformeRendimentos> {
+    let mut informes: Vec<InformeRendimentos> = Vec::new();
+    for linha in linhas {
+        if informes.last().is_none_or(|i| i.servidor_id != linha.servidor_id) {
+            informes.push(InformeRendimentos {
+                ano,
+                timbre: timbre.clone(),
+                servidor_id: linha.servidor_id,
+                nome: linha.nome.clone(),
+                cpf: linha.cpf.clone(),
+                matricula: linha.matricula.clone(),
+                valores: ValoresInforme::default(),
+            });
+        }
+        let classificacao = classificar(
+            &linha.tipo,
+            linha.incide_irrf,
+            linha.codigo_sistema.as_deref(),
+            linha.pensao_alimenticia,
+            linha.informe_rendimentos.as_deref(),
+        );
+        if let (Some(informe), Some(classificacao)) = (informes.last_mut(), classificacao) {
+            informe.valores.somar(classificacao, &linha.tipo, &linha.valor);
+        }
+    }
+    informes.retain(|informe| !informe.valores.vazio());
+    informes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_classificar() {
+        assert_eq!(classificar("P", true, None, false, None), Some(LinhaInforme::Tributavel));
+        assert_eq!(classificar("P", false, None, false, None), Some(LinhaInforme::Isento));
+        // falta abate o tributável; consignação não entra
+        assert_eq!(classificar("D", true, None, false, None), Some(LinhaInforme::Tributavel));
+        assert_eq!(classificar("D", false, None, false, None), None);
+        assert_eq!(classificar("D", false, Some("INSS"), false, None), Some(LinhaInforme::Previdencia));
+        assert_eq!(classificar("D", false, Some("IRRF"), false, None), Some(LinhaInforme::Irrf));
+        assert_eq!(classificar("D", false, None, true, None), Some(LinhaInforme::Pensao));
+        assert_eq!(classificar("I", true, None, false, None), None);
+
+        // a linha da rubrica vale sobre as incidências
+        assert_eq!(
+            classificar("P", true, None, false, Some("DECIMO_TERCEIRO")),
+            Some(LinhaInforme::DecimoTerceiro)
+        );
+        assert_eq!(classificar("P", true, None, false, Some("NAO_INFORMAR")), None);
+    }
+
+    #[test]
+    fn test_somar() {
+        let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+        let mut valores = ValoresInforme::default();
+        valores.somar(LinhaInforme::Tributavel, "P", &valor("3000"));
+        valores.somar(LinhaInforme::Tributavel, "D", &valor("100"));
+        valores.somar(LinhaInforme::Previdencia, "D", &valor("250"));
+        valores.somar(LinhaInforme::Previdencia, "P", &valor("50"));
+        valores.somar(LinhaInforme::DecimoTerceiro, "P", &valor("3000"));
+        valores.somar(LinhaInforme::PrevidenciaDecimoTerceiro, "D", &valor("280"));
+
+        assert_eq!(valores.tributavel, valor("2900"));
+        assert_eq!(valores.previdencia, valor("200"));
+        assert_eq!(valores.decimo_terceiro_liquido(), valor("2720"));
+        assert!(!valores.vazio());
+        assert!(ValoresInforme::default().vazio());
+    }
+}
@@ -4,6 +4,19 @@ mod repository;
 mod view;
 mod schema;
 mod router;
+mod calculo;
+mod relatorio;
+mod autenticacao;
+mod ibge;
+mod esocial;
+mod informe;
+mod cnab;
+mod consignacao;
+mod decimo_terceiro;
+mod ferias;
+mod rescisao;
+mod geracao_folha;
 
-pub use router::router;
+pub use router::{router_autenticado, router_publico};
+pub use service::LoteContraChequeService;
 
@@ -1,7 +1,12 @@
-use bigdecimal::BigDecimal;
+use std::sync::Arc;
+
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use crate::utils::validator::formatar_cep;
+
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct  Folha {
     pub id: i64,
@@ -18,18 +23,1036 @@ pub struct  Folha {
     pub setor_id: i32,
     pub departamento_id: i32,
     pub vinculo_id: i32,
+    pub dependentes_irrf: i32,
+    /// Tipo da folha (`FOLHA_*`, `DECIMO_TERCEIRO_*`)
+    pub tipo_folha: i16,
+    /// Número da complementar na competência; 0 nos demais tipos
+    pub sequencia: i16,
 
     // campos de outra tabela
     pub serv_nome: Option<String>,
     pub org_nome: Option<String>,
 }
 
+/// Tipos da folha (cadastro_folha.tipo_folha)
+pub const FOLHA_MENSAL: i16 = 0;
+pub const DECIMO_TERCEIRO_ADIANTAMENTO: i16 = 1;
+pub const DECIMO_TERCEIRO_FINAL: i16 = 2;
+pub const FOLHA_RESCISAO: i16 = 3;
+pub const FOLHA_COMPLEMENTAR: i16 = 4;
+pub const FOLHA_FERIAS: i16 = 5;
+
+/// Tipos pagos na competência junto com a mensal, na ordem de cálculo: as bases de
+/// INSS e IRRF de cada um acumulam com as dos anteriores. 13º e rescisão têm cálculo próprio
+pub const FOLHAS_DA_COMPETENCIA: [i16; 3] = [FOLHA_MENSAL, FOLHA_FERIAS, FOLHA_COMPLEMENTAR];
+
+/// Ordem de impressão dos tipos de folha do servidor: os da competência, 13º e rescisão
+pub const ORDEM_TIPOS_FOLHA: [i16; 6] = [
+    FOLHA_MENSAL,
+    FOLHA_FERIAS,
+    FOLHA_COMPLEMENTAR,
+    DECIMO_TERCEIRO_ADIANTAMENTO,
+    DECIMO_TERCEIRO_FINAL,
+    FOLHA_RESCISAO,
+];
+
+/// Complemento do título da folha no contra-cheque; a mensal não tem
+pub fn descricao_tipo_folha(tipo: i16, sequencia: i16) -> String {
+    match tipo {
+        DECIMO_TERCEIRO_ADIANTAMENTO => "ADIANTAMENTO DO 13º SALÁRIO".to_string(),
+        DECIMO_TERCEIRO_FINAL => "13º SALÁRIO".to_string(),
+        FOLHA_RESCISAO => "RESCISÃO".to_string(),
+        FOLHA_COMPLEMENTAR => format!("COMPLEMENTAR {}", sequencia),
+        FOLHA_FERIAS => "FÉRIAS".to_string(),
+        _ => String::new(),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Orgao {
+    pub id: i32,
+    pub nome: String,
+    pub cnpj: Option<String>,
+    pub cep: Option<String>,
+    pub logradouro: Option<String>,
+    pub numero: Option<String>,
+    pub complemento: Option<String>,
+    pub bairro: Option<String>,
+    pub municipio_id: Option<i32>,
+    pub telefone: Option<String>,
+    pub email: Option<String>,
+    pub is_active: bool,
+    /// O logotipo fica fora da listagem; aqui só se ele foi enviado
+    pub possui_logo: bool,
+    pub esocial_ambiente: i32,
+    pub esocial_tabela_rubrica: Option<String>,
+    pub esocial_lotacao: Option<String>,
+    pub cnab_banco: Option<String>,
+    pub cnab_agencia: Option<String>,
+    pub cnab_conta: Option<String>,
+    pub cnab_convenio: Option<String>,
+    pub cnab_nsa: i32,
+    pub margem_consignavel: BigDecimal,
+
+    // campos de outra tabela
+    pub municipio_nome: Option<String>,
+    pub uf_sigla: Option<String>,
+}
+
+/// Dados do órgão impressos no cabeçalho dos documentos (contra-cheques, relatórios)
+#[derive(Debug, FromRow)]
+pub struct Timbre {
+    pub nome: String,
+    pub cnpj: Option<String>,
+    pub cep: Option<String>,
+    pub logradouro: Option<String>,
+    pub numero: Option<String>,
+    pub complemento: Option<String>,
+    pub bairro: Option<String>,
+    pub municipio_nome: Option<String>,
+    pub uf_sigla: Option<String>,
+    pub telefone: Option<String>,
+    pub email: Option<String>,
+    /// PNG
+    pub logo: Option<Vec<u8>>,
+}
+
+impl Timbre {
+    /// Endereço em uma linha: "Rua X, 10, Sala 2 - Centro - PALMAS/TO - CEP 77000-000"
+    pub fn endereco(&self) -> Option<String> {
+        let rua = [&self.logradouro, &self.numero, &self.complemento]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        let cidade = match (&self.municipio_nome, &self.uf_sigla) {
+            (Some(municipio), Some(uf)) => Some(format!("{}/{}", municipio, uf)),
+            (municipio, _) => municipio.clone(),
+        };
+
+        let partes: Vec<String> = [
+            Some(rua).filter(|r| !r.is_empty()),
+            self.bairro.clone(),
+            cidade,
+            self.cep.as_deref().map(|cep| format!("CEP {}", formatar_cep(cep))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        (!partes.is_empty()).then(|| partes.join(" - "))
+    }
+
+    /// Telefone e e-mail em uma linha
+    pub fn contato(&self) -> Option<String> {
+        let partes: Vec<String> = [
+            self.telefone.as_ref().map(|t| format!("Tel.: {}", t)),
+            self.email.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        (!partes.is_empty()).then(|| partes.join(" - "))
+    }
+}
+
+/// Permissão para incluir, alterar e excluir cargos, setores, departamentos e tipos de vínculo
+pub const PERM_TABELAS_CADASTRAR: &str = "tabelas.cadastrar";
+
+/// Permissão para incluir, alterar e excluir servidores e seus afastamentos
+pub const PERM_SERVIDOR_CADASTRAR: &str = "servidor.cadastrar";
+
+/// Tipos de conta bancária do servidor
+pub const CONTA_CORRENTE: &str = "C";
+pub const CONTA_POUPANCA: &str = "P";
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Servidor {
+    pub id: i32,
+    pub nome: String,
+    pub matricula: Option<String>,
+    pub cpf: Option<String>,
+    pub pis: Option<String>,
+    pub data_nascimento: Option<NaiveDate>,
+    pub data_admissao: Option<NaiveDate>,
+    pub cep: Option<String>,
+    pub logradouro: Option<String>,
+    pub numero: Option<String>,
+    pub complemento: Option<String>,
+    pub bairro: Option<String>,
+    pub municipio_id: Option<i32>,
+    pub banco: Option<String>,
+    pub agencia: Option<String>,
+    pub conta: Option<String>,
+    pub tipo_conta: Option<String>,
+    pub is_active: bool,
+    pub orgao_id: Option<i32>,
+    pub cargo_id: Option<i32>,
+    pub vinculo_id: Option<i32>,
+    pub sexo: Option<String>,
+    pub raca_cor: Option<i32>,
+    pub grau_instrucao: Option<String>,
+    pub data_desligamento: Option<NaiveDate>,
+    pub motivo_desligamento: Option<String>,
+
+    // campos de outra tabela
+    pub municipio_nome: Option<String>,
+    pub org_nome: Option<String>,
+    pub cargo_nome: Option<String>,
+    pub vinculo_nome: Option<String>,
+}
+
+/// Licença ou afastamento do servidor; sem data de fim, continua afastado
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Afastamento {
+    pub id: i32,
+    pub servidor_id: i32,
+    pub data_inicio: NaiveDate,
+    pub data_fim: Option<NaiveDate>,
+    pub motivo: String,
+    /// Os dias afastados não contam para os avos do 13º
+    pub reduz_avos: bool,
+}
+
+/// Tipos de rubrica
+pub const RUBRICA_PROVENTO: &str = "P";
+pub const RUBRICA_DESCONTO: &str = "D";
+pub const RUBRICA_INFORMATIVA: &str = "I";
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Rubrica {
+    pub id: i32,
+    pub codigo: String,
+    pub descricao: String,
+    pub tipo: String,
+    pub incide_inss: bool,
+    pub incide_irrf: bool,
+    pub incide_fgts: bool,
+    pub is_active: bool,
+    /// Preenchido nas rubricas lançadas pelo cálculo automático (ex.: INSS)
+    pub codigo_sistema: Option<String>,
+    /// Desconto de pensão alimentícia, dedutível da base do IRRF
+    pub pensao_alimenticia: bool,
+    /// Linha do informe de rendimentos; sem ela vale a classificação pelas incidências
+    pub informe_rendimentos: Option<String>,
+    /// Copiada de uma competência para a outra na geração da folha
+    pub fixa: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct FolhaItem {
+    pub id: i64,
+    pub folha_id: i64,
+    pub rubrica_id: i32,
+    pub referencia: BigDecimal,
+    pub valor: BigDecimal,
+    pub parcela: Option<String>,
+
+    // campos de outra tabela
+    pub rubrica_codigo: Option<String>,
+    pub rubrica_descricao: Option<String>,
+    pub rubrica_tipo: Option<String>,
+}
+
+/// Folha com seus itens (proventos e descontos) e totais do contra-cheque
+#[derive(Debug, Serialize)]
+pub struct FolhaDetalhe {
+    #[serde(flatten)]
+    pub folha: Folha,
+    pub itens: Vec<FolhaItem>,
+    pub total_proventos: BigDecimal,
+    pub total_descontos: BigDecimal,
+    pub valor_liquido: BigDecimal,
+}
+
+impl FolhaDetalhe {
+    pub fn new(folha: Folha, itens: Vec<FolhaItem>) -> Self {
+        let mut total_proventos = BigDecimal::zero();
+        let mut total_descontos = BigDecimal::zero();
+
+        for item in &itens {
+            match item.rubrica_tipo.as_deref() {
+                Some(RUBRICA_PROVENTO) => total_proventos += &item.valor,
+                Some(RUBRICA_DESCONTO) => total_descontos += &item.valor,
+                _ => {}
+            }
+        }
+
+        let valor_liquido = &total_proventos - &total_descontos;
+
+        Self {
+            folha,
+            itens,
+            total_proventos,
+            total_descontos,
+            valor_liquido,
+        }
+    }
+}
+
+/// Identificação do servidor e do órgão impressa no contra-cheque
+#[derive(Debug, Serialize, FromRow)]
+pub struct CabecalhoContraCheque {
+    pub org_nome: String,
+    pub serv_nome: String,
+    pub serv_matricula: Option<String>,
+    pub serv_cpf: Option<String>,
+    pub serv_data_admissao: Option<NaiveDate>,
+    pub cargo_nome: Option<String>,
+    pub setor_nome: Option<String>,
+    pub departamento_nome: Option<String>,
+    pub vinculo_nome: Option<String>,
+}
+
+/// Emissão do contra-cheque com o código de verificação e os valores impressos
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct EmissaoContraCheque {
+    pub id: i64,
+    pub codigo: String,
+    pub folha_id: Option<i64>,
+    pub orgao_nome: String,
+    pub servidor_nome: String,
+    pub ano: i32,
+    pub mes: i32,
+    pub total_proventos: BigDecimal,
+    pub total_descontos: BigDecimal,
+    pub valor_liquido: BigDecimal,
+    pub emitido_em: DateTime<Utc>,
+    /// Tipo e sequência da folha emitida (ver `descricao_tipo_folha`)
+    pub tipo_folha: i16,
+    pub sequencia: i16,
+}
+
+/// O que a página pública de validação mostra sobre um contra-cheque emitido
+#[derive(Debug, Serialize)]
+pub struct AutenticacaoContraCheque {
+    pub codigo: String,
+    pub orgao_nome: String,
+    /// Nome do servidor mascarado
+    pub servidor: String,
+    pub competencia: String,
+    /// Folha do documento: mensal, complementar, férias...
+    pub folha: String,
+    pub valor_liquido: BigDecimal,
+    pub emitido_em: DateTime<Utc>,
+}
+
+/// Tudo o que é impresso no contra-cheque de uma folha
+#[derive(Debug)]
+pub struct ContraCheque {
+    /// Compartilhado entre os contra-cheques do mesmo órgão na geração em lote
+    pub timbre: Arc<Timbre>,
+    pub cabecalho: CabecalhoContraCheque,
+    pub detalhe: FolhaDetalhe,
+    pub emissao: EmissaoContraCheque,
+}
+
+/// Formatos do lote de contra-cheques
+pub const LOTE_FORMATO_ZIP: &str = "ZIP";
+pub const LOTE_FORMATO_PDF: &str = "PDF";
+
+/// Situações do lote de contra-cheques
+pub const LOTE_PENDENTE: &str = "PENDENTE";
+pub const LOTE_PROCESSANDO: &str = "PROCESSANDO";
+pub const LOTE_CONCLUIDO: &str = "CONCLUIDO";
+pub const LOTE_ERRO: &str = "ERRO";
+
+/// Lote de contra-cheques gerado em segundo plano (sem o conteúdo do arquivo)
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct LoteContraCheque {
+    pub id: i32,
+    pub orgao_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+    pub setor_id: Option<i32>,
+    pub departamento_id: Option<i32>,
+    pub formato: String,
+    pub situacao: String,
+    pub total_folhas: i32,
+    pub nome_arquivo: Option<String>,
+    pub erro: Option<String>,
+    pub usuario: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub concluido_em: Option<DateTime<Utc>>,
+}
+
+/// Eventos do eSocial gerados a partir da folha
+pub const ESOCIAL_S1200: &str = "S-1200";
+pub const ESOCIAL_S1210: &str = "S-1210";
+
+/// Situações do evento do eSocial: GERADO ainda não passou pelo XSD (validador
+/// indisponível), VALIDADO está pronto para assinar, INVALIDO traz os erros
+pub const EVENTO_GERADO: &str = "GERADO";
+pub const EVENTO_VALIDADO: &str = "VALIDADO";
+pub const EVENTO_INVALIDO: &str = "INVALIDO";
+
+/// Permissão para gerar os eventos do eSocial de uma competência
+pub const PERM_ESOCIAL_GERAR: &str = "esocial.gerar";
+
+/// Evento do eSocial guardado (sem o XML, lido à parte no download)
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct EventoEsocial {
+    pub id: i64,
+    pub orgao_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+    pub tipo: String,
+    pub servidor_id: i32,
+    pub total_folhas: i32,
+    pub id_evento: String,
+    pub situacao: String,
+    pub erros: Option<String>,
+    pub usuario: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub validado_em: Option<DateTime<Utc>>,
+
+    // campos de outra tabela
+    pub org_nome: Option<String>,
+    pub serv_nome: Option<String>,
+}
+
+/// Totais da geração dos eventos de uma competência
+#[derive(Debug, Default, Serialize)]
+pub struct ResultadoEsocial {
+    pub gerados: usize,
+    pub validados: usize,
+    pub invalidos: usize,
+}
+
+/// Eventos não periódicos, gerados a partir das alterações do cadastro do servidor
+pub const ESOCIAL_S2200: &str = "S-2200";
+pub const ESOCIAL_S2206: &str = "S-2206";
+pub const ESOCIAL_S2299: &str = "S-2299";
+pub const ESOCIAL_S2300: &str = "S-2300";
+
+/// Situações do evento na fila: PENDENTE aguarda a geração, GERADO tem o XML pronto
+/// para assinar e REJEITADO traz as pendências do cadastro ou os erros do XSD
+pub const FILA_PENDENTE: &str = "PENDENTE";
+pub const FILA_GERADO: &str = "GERADO";
+pub const FILA_REJEITADO: &str = "REJEITADO";
+
+/// Evento não periódico na fila do órgão (sem o XML, lido à parte no download)
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct EventoFilaEsocial {
+    pub id: i64,
+    pub orgao_id: i32,
+    pub servidor_id: i32,
+    pub tipo: String,
+    pub data_evento: NaiveDate,
+    pub situacao: String,
+    pub id_evento: Option<String>,
+    pub erros: Option<String>,
+    pub usuario: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub gerado_em: Option<DateTime<Utc>>,
+
+    // campos de outra tabela
+    pub org_nome: Option<String>,
+    pub serv_nome: Option<String>,
+}
+
+/// Totais da geração da fila
+#[derive(Debug, Default, Serialize)]
+pub struct ResultadoFilaEsocial {
+    pub gerados: usize,
+    pub rejeitados: usize,
+}
+
+/// Leiautes da remessa CNAB 240: o padrão FEBRABAN e as variações do Banco do
+/// Brasil e da Caixa
+pub const CNAB_FEBRABAN: &str = "FEBRABAN";
+pub const CNAB_BANCO_DO_BRASIL: &str = "BB";
+pub const CNAB_CAIXA: &str = "CAIXA";
+
+/// Permissão para gerar a remessa de pagamento da competência
+pub const PERM_CNAB_GERAR: &str = "cnab.gerar";
+
+/// Remessa CNAB 240 gerada (sem o conteúdo do arquivo, lido à parte no download)
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct RemessaCnab {
+    pub id: i32,
+    pub orgao_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+    pub layout: String,
+    pub banco: String,
+    pub nsa: i32,
+    pub data_pagamento: NaiveDate,
+    pub total_pagamentos: i32,
+    pub valor_total: BigDecimal,
+    pub nome_arquivo: String,
+    pub usuario: Option<String>,
+    pub created_at: DateTime<Utc>,
+
+    // campos de outra tabela
+    pub org_nome: Option<String>,
+    pub pagos: i64,
+    pub rejeitados: i64,
+}
+
+/// Remessa gerada e as folhas deixadas de fora (sem conta bancária ou sem líquido a pagar)
+#[derive(Debug, Serialize)]
+pub struct ResultadoRemessaCnab {
+    pub id: i32,
+    pub nsa: i32,
+    pub pagamentos: usize,
+    pub valor_total: BigDecimal,
+    pub ignorados: Vec<String>,
+}
+
+/// Situações do crédito da remessa conforme o retorno do banco
+pub const CREDITO_ENVIADO: &str = "ENVIADO";
+pub const CREDITO_AGENDADO: &str = "AGENDADO";
+pub const CREDITO_PAGO: &str = "PAGO";
+pub const CREDITO_REJEITADO: &str = "REJEITADO";
+
+/// Crédito de uma remessa CNAB com a situação devolvida no retorno
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ItemRemessaCnab {
+    pub id: i64,
+    pub remessa_id: i32,
+    pub folha_id: Option<i64>,
+    pub servidor_id: i32,
+    pub seu_numero: String,
+    pub banco: String,
+    pub agencia: String,
+    pub conta: String,
+    pub valor: BigDecimal,
+    pub situacao: String,
+    pub ocorrencias: Option<String>,
+    pub mensagem: Option<String>,
+    pub data_efetivacao: Option<NaiveDate>,
+    pub valor_pago: Option<BigDecimal>,
+    pub retorno_em: Option<DateTime<Utc>>,
+
+    // campos de outra tabela
+    pub orgao_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+    pub nsa: i32,
+    pub org_nome: Option<String>,
+    pub serv_nome: Option<String>,
+    pub cpf: Option<String>,
+}
+
+/// Totais da importação do retorno; os "seus números" que não são da remessa voltam à parte
+#[derive(Debug, Default, Serialize)]
+pub struct ResultadoRetornoCnab {
+    pub pagos: usize,
+    pub agendados: usize,
+    pub rejeitados: usize,
+    pub nao_encontrados: Vec<String>,
+}
+
+/// Períodos aquisitivos de férias do servidor, com o que já foi programado em cada um
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PeriodoAquisitivo {
+    pub id: i32,
+    pub servidor_id: i32,
+    pub data_inicio: NaiveDate,
+    pub data_fim: NaiveDate,
+    pub dias_direito: i32,
+
+    /// Último dia para conceder as férias: 12 meses depois do fim do período
+    pub fim_concessivo: NaiveDate,
+
+    // das férias programadas no período, fora as canceladas
+    pub dias_gozo: i64,
+    pub dias_abono: i64,
+    pub fracoes: i64,
+    pub saldo: i64,
+}
+
+/// Situações das férias programadas
+pub const FERIAS_PROGRAMADA: &str = "PROGRAMADA";
+pub const FERIAS_CALCULADA: &str = "CALCULADA";
+pub const FERIAS_CANCELADA: &str = "CANCELADA";
+
+/// Férias (ou uma fração delas) programadas para o servidor; são pagas na folha da
+/// competência do início do gozo
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Ferias {
+    pub id: i32,
+    pub servidor_id: i32,
+    pub periodo_id: i32,
+    pub data_inicio: NaiveDate,
+    pub data_fim: NaiveDate,
+    pub dias: i32,
+    pub dias_abono: i32,
+    pub ano: i32,
+    pub mes: i32,
+    pub situacao: String,
+    /// Folha em que o cálculo lançou as férias
+    pub folha_id: Option<i64>,
+    pub valor_ferias: Option<BigDecimal>,
+    pub valor_terco: Option<BigDecimal>,
+    pub valor_abono: Option<BigDecimal>,
+    pub valor_terco_abono: Option<BigDecimal>,
+    pub usuario: Option<String>,
+    pub created_at: DateTime<Utc>,
+
+    // campos de outra tabela
+    pub periodo_inicio: Option<NaiveDate>,
+    pub periodo_fim: Option<NaiveDate>,
+}
+
+/// Tudo o que é impresso no aviso e recibo de férias
+#[derive(Debug)]
+pub struct AvisoFerias {
+    pub timbre: Timbre,
+    pub servidor: Servidor,
+    pub ferias: Ferias,
+}
+
+/// Rescisão do servidor: a folha com as verbas e o que foi apurado para o termo
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Rescisao {
+    pub id: i32,
+    pub servidor_id: i32,
+    pub folha_id: i64,
+    pub data_desligamento: NaiveDate,
+    pub motivo_desligamento: String,
+    pub dias_saldo_salario: i32,
+    pub avos_decimo_terceiro: i32,
+    pub dias_ferias_vencidas: i32,
+    pub avos_ferias_proporcionais: i32,
+    pub usuario: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Tudo o que é impresso no termo de rescisão
+#[derive(Debug)]
+pub struct TermoRescisao {
+    pub timbre: Timbre,
+    pub cabecalho: CabecalhoContraCheque,
+    pub detalhe: FolhaDetalhe,
+    pub rescisao: Rescisao,
+}
+
+/// Novo salário do servidor a partir da competência; pendente até a geração da folha
+/// aplicá-lo
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct AlteracaoSalarial {
+    pub id: i32,
+    pub servidor_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+    pub salario: BigDecimal,
+    pub motivo: String,
+    /// Folha gerada com o novo salário; vazio enquanto pendente
+    pub folha_id: Option<i64>,
+    pub usuario: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Permissão para gerar a folha mensal a partir de outra competência
+pub const PERM_FOLHA_GERAR: &str = "folha.gerar";
+
+/// Situação de cada servidor da origem na geração da folha
+pub const GERACAO_GERAR: &str = "GERAR";
+pub const GERACAO_EXISTENTE: &str = "EXISTENTE";
+pub const GERACAO_DESLIGADO: &str = "DESLIGADO";
+pub const GERACAO_INATIVO: &str = "INATIVO";
+
+/// Folha mensal da competência de origem, com a situação do servidor
+#[derive(Debug, FromRow)]
+pub struct FolhaOrigem {
+    pub folha_id: i64,
+    pub servidor_id: i32,
+    pub servidor_nome: String,
+    pub servidor_ativo: bool,
+    pub data_desligamento: Option<NaiveDate>,
+    pub salario: BigDecimal,
+    pub cargo_id: i32,
+    pub setor_id: i32,
+    pub departamento_id: i32,
+    pub vinculo_id: i32,
+    pub dependentes_irrf: i32,
+    /// Se o servidor já tem folha mensal na competência de destino
+    pub gerada: bool,
+}
+
+/// Item de rubrica fixa de uma folha
+#[derive(Debug, FromRow)]
+pub struct ItemFixo {
+    pub folha_id: i64,
+    pub rubrica_id: i32,
+    pub codigo: String,
+    pub descricao: String,
+    pub codigo_sistema: Option<String>,
+    pub referencia: BigDecimal,
+    pub valor: BigDecimal,
+}
+
+/// Rubrica da folha gerada com o valor que tinha na origem
+#[derive(Debug, Serialize)]
+pub struct ItemGerado {
+    pub rubrica_id: i32,
+    pub codigo: String,
+    pub descricao: String,
+    pub referencia: BigDecimal,
+    pub valor_anterior: BigDecimal,
+    pub valor: BigDecimal,
+}
+
+/// Linha da prévia: o que a geração faz com o servidor e o que muda da origem
+#[derive(Debug, Serialize)]
+pub struct ServidorGeracao {
+    pub servidor_id: i32,
+    pub nome: String,
+    pub situacao: &'static str,
+    pub salario_anterior: BigDecimal,
+    pub salario: BigDecimal,
+    /// Alterações salariais aplicadas
+    pub alteracoes: Vec<i32>,
+    pub itens: Vec<ItemGerado>,
+    #[serde(skip)]
+    pub origem: FolhaOrigem,
+}
+
+/// Prévia da geração, conferida antes de gravar
+#[derive(Debug, Serialize)]
+pub struct PreviaGeracaoFolha {
+    pub orgao_id: i32,
+    pub ano_origem: i32,
+    pub mes_origem: i32,
+    pub ano: i32,
+    pub mes: i32,
+    pub servidores: Vec<ServidorGeracao>,
+    /// Quantas folhas serão geradas
+    pub gerar: usize,
+}
+
+/// Situações do contrato de consignação
+pub const CONSIGNACAO_ATIVO: &str = "ATIVO";
+pub const CONSIGNACAO_CANCELADO: &str = "CANCELADO";
+
+/// Permissão para averbar, alterar e excluir contratos de consignação
+pub const PERM_CONSIGNACAO_AVERBAR: &str = "consignacao.averbar";
+
+/// Instituição conveniada para desconto em folha, com a rubrica em que desconta
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Consignataria {
+    pub id: i32,
+    pub nome: String,
+    pub cnpj: Option<String>,
+    pub rubrica_id: i32,
+    pub is_active: bool,
+
+    // campos de outra tabela
+    pub rubrica_codigo: Option<String>,
+    pub rubrica_descricao: Option<String>,
+}
+
+/// Contrato de empréstimo consignado do servidor
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Consignacao {
+    pub id: i32,
+    pub servidor_id: i32,
+    pub consignataria_id: i32,
+    pub contrato: String,
+    pub valor_parcela: BigDecimal,
+    pub total_parcelas: i32,
+    pub ano_inicio: i32,
+    pub mes_inicio: i32,
+    pub situacao: String,
+    pub usuario: Option<String>,
+    pub created_at: DateTime<Utc>,
+
+    // campos de outra tabela
+    pub serv_nome: Option<String>,
+    pub cpf: Option<String>,
+    pub consignataria_nome: Option<String>,
+    /// Parcelas já lançadas em folha
+    pub parcelas_lancadas: i64,
+}
+
+/// Contrato que tem parcela na competência da folha em cálculo
+#[derive(Debug, FromRow)]
+pub struct ParcelaConsignacao {
+    pub consignacao_id: i32,
+    pub rubrica_id: i32,
+    pub valor_parcela: BigDecimal,
+    pub total_parcelas: i32,
+    pub ano_inicio: i32,
+    pub mes_inicio: i32,
+}
+
+/// Permissão para gerar e revogar as credenciais da API de margem
+pub const PERM_CONSIGNATARIA_CREDENCIAL: &str = "consignataria.credencial";
+
+/// Situações da reserva de margem feita pela API
+pub const RESERVA_RESERVADA: &str = "RESERVADA";
+pub const RESERVA_UTILIZADA: &str = "UTILIZADA";
+pub const RESERVA_CANCELADA: &str = "CANCELADA";
+
+/// Operações registradas no log da API de margem
+pub const API_MARGEM_CONSULTA: &str = "CONSULTA";
+pub const API_MARGEM_RESERVA: &str = "RESERVA";
+pub const API_MARGEM_CANCELAMENTO: &str = "CANCELAMENTO";
+
+/// Credencial de acesso da consignatária à API de margem
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct CredencialConsignataria {
+    pub id: i32,
+    pub consignataria_id: i32,
+    pub chave: String,
+    #[serde(skip_serializing)]
+    pub segredo_hash: String,
+    pub descricao: Option<String>,
+    pub is_active: bool,
+    pub usuario: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub ultimo_uso: Option<DateTime<Utc>>,
+}
+
+/// Margem separada pela consignatária enquanto o contrato é negociado
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ReservaMargem {
+    pub id: i32,
+    pub codigo: String,
+    pub consignataria_id: i32,
+    pub servidor_id: i32,
+    pub valor: BigDecimal,
+    pub expira_em: DateTime<Utc>,
+    pub situacao: String,
+    pub consignacao_id: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Chamada registrada da API de margem
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ConsultaMargem {
+    pub id: i64,
+    pub credencial_id: Option<i32>,
+    pub consignataria_id: Option<i32>,
+    pub operacao: String,
+    pub cpf: Option<String>,
+    pub reserva_codigo: Option<String>,
+    pub status: i16,
+    pub disponivel: Option<BigDecimal>,
+    pub mensagem: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+
+    // campos de outra tabela
+    pub consignataria_nome: Option<String>,
+    pub credencial_chave: Option<String>,
+}
+
+/// Resposta da consulta de margem: só o necessário para a consignatária conceder o crédito
+#[derive(Debug, Serialize)]
+pub struct MargemConsultada {
+    pub cpf: String,
+    pub nome: String,
+    /// Competência da folha usada como base
+    pub ano: i32,
+    pub mes: i32,
+    pub margem_disponivel: BigDecimal,
+}
+
+/// Resposta da reserva de margem
+#[derive(Debug, Serialize)]
+pub struct ReservaConcedida {
+    pub codigo: String,
+    pub cpf: String,
+    pub valor: BigDecimal,
+    pub expira_em: DateTime<Utc>,
+    /// Margem que sobra depois da reserva
+    pub margem_disponivel: BigDecimal,
+}
+
+/// Servidor ativo do órgão no cálculo do 13º, com a folha mensal mais recente do ano
+/// (salário e lotação); sem folha mensal no ano, os campos da folha vêm vazios
+#[derive(Debug, FromRow)]
+pub struct BaseDecimoTerceiro {
+    pub servidor_id: i32,
+    pub nome: String,
+    pub data_admissao: Option<NaiveDate>,
+    pub data_desligamento: Option<NaiveDate>,
+    pub salario: Option<BigDecimal>,
+    pub cargo_id: Option<i32>,
+    pub setor_id: Option<i32>,
+    pub departamento_id: Option<i32>,
+    pub vinculo_id: Option<i32>,
+    pub dependentes_irrf: Option<i32>,
+}
+
+/// Resumo do cálculo de uma parcela do 13º no órgão
+#[derive(Debug, Default, Serialize)]
+pub struct ResultadoDecimoTerceiro {
+    pub folhas: usize,
+    /// Servidores sem nenhum avo no período
+    pub sem_avos: usize,
+    /// Servidores sem folha mensal no ano, de onde viriam salário e lotação
+    pub sem_folha: Vec<String>,
+    pub total_liquido: BigDecimal,
+}
+
+/// Faixa da tabela progressiva do INSS
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FaixaInss {
+    pub id: i32,
+    pub vigencia: NaiveDate,
+    pub limite: BigDecimal,
+    pub aliquota: BigDecimal,
+}
+
+/// Faixa da tabela progressiva mensal do IRRF; a última faixa não tem limite
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FaixaIrrf {
+    pub id: i32,
+    pub vigencia: NaiveDate,
+    pub limite: Option<BigDecimal>,
+    pub aliquota: BigDecimal,
+    pub deducao: BigDecimal,
+}
+
+/// Parâmetros do IRRF vigentes na competência
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ParametroIrrf {
+    pub id: i32,
+    pub vigencia: NaiveDate,
+    pub deducao_dependente: BigDecimal,
+    pub desconto_simplificado: BigDecimal,
+    pub reducao_limite_isencao: Option<BigDecimal>,
+    pub reducao_valor_isencao: Option<BigDecimal>,
+    pub reducao_limite: Option<BigDecimal>,
+    pub reducao_constante: Option<BigDecimal>,
+    pub reducao_fator: Option<BigDecimal>,
+}
+
+/// Valores da folha que entram no cálculo do IRRF
+#[derive(Debug)]
+pub struct BaseIrrfFolha {
+    /// Rendimentos tributáveis (proventos menos descontos que incidem IRRF, exceto pensão)
+    pub rendimento: BigDecimal,
+    pub inss: BigDecimal,
+    pub pensao: BigDecimal,
+}
+
+/// Bases gravadas e impostos retidos nas folhas anteriores da competência do servidor
+#[derive(Debug, Default)]
+pub struct AcumuladoCompetencia {
+    pub base_inss: BigDecimal,
+    pub inss: BigDecimal,
+    pub base_irrf: BigDecimal,
+    pub ded_irrf: BigDecimal,
+    pub irrf: BigDecimal,
+}
+
+/// Situações da competência
+pub const COMPETENCIA_ABERTA: &str = "ABERTA";
+pub const COMPETENCIA_CALCULADA: &str = "CALCULADA";
+pub const COMPETENCIA_FECHADA: &str = "FECHADA";
+pub const COMPETENCIA_REABERTA: &str = "REABERTA";
+
+/// Permissões das transições da competência
+pub const PERM_COMPETENCIA_CALCULAR: &str = "competencia.calcular";
+pub const PERM_COMPETENCIA_FECHAR: &str = "competencia.fechar";
+pub const PERM_COMPETENCIA_REABRIR: &str = "competencia.reabrir";
+
+/// Transições aceitas: calcula (ou recalcula) enquanto não estiver fechada,
+/// só fecha depois de calculada e só reabre o que estiver fechado
+pub fn transicao_permitida(atual: &str, nova: &str) -> bool {
+    matches!(
+        (atual, nova),
+        (
+            COMPETENCIA_ABERTA | COMPETENCIA_CALCULADA | COMPETENCIA_REABERTA,
+            COMPETENCIA_CALCULADA
+        ) | (COMPETENCIA_CALCULADA, COMPETENCIA_FECHADA)
+            | (COMPETENCIA_FECHADA, COMPETENCIA_REABERTA)
+    )
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Competencia {
+    pub id: i32,
+    pub orgao_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+    pub situacao: String,
+    pub total_servidores: Option<i32>,
+    pub total_proventos: Option<BigDecimal>,
+    pub total_descontos: Option<BigDecimal>,
+    pub valor_liquido: Option<BigDecimal>,
+    pub fechada_em: Option<DateTime<Utc>>,
+    pub fechada_por: Option<String>,
+
+    // campos de outra tabela
+    pub org_nome: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct CompetenciaHistorico {
+    pub id: i64,
+    pub competencia_id: i32,
+    pub situacao_anterior: Option<String>,
+    pub situacao_nova: String,
+    pub usuario: Option<String>,
+    pub motivo: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Totais das folhas de uma competência
+#[derive(Debug)]
+pub struct TotaisCompetencia {
+    pub total_servidores: i32,
+    pub total_proventos: BigDecimal,
+    pub total_descontos: BigDecimal,
+}
+
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Uf {
     pub id: i32,
     pub sigla: String,
-    pub nome: String
+    pub nome: String,
+    pub codigo_ibge: Option<i32>,
+}
+
+
+/// Totais da importação da planilha de divisão territorial do IBGE
+#[derive(Debug, Default, Serialize)]
+pub struct ResultadoImportacaoIbge {
+    pub ufs_inseridas: i64,
+    pub ufs_atualizadas: i64,
+    pub municipios_inseridos: i64,
+    pub municipios_atualizados: i64,
+}
+
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Cargo {
+    pub id: i32,
+    pub nome: String,
+    pub cbo: Option<String>,
+}
+
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Setor {
+    pub id: i32,
+    pub nome: String,
+}
+
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Departamento {
+    pub id: i32,
+    pub nome: String,
+}
+
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TipoVinculo {
+    pub id: i32,
+    pub nome: String,
+    pub categoria_esocial: Option<i32>,
+    /// 1 = RGPS, 2 = RPPS
+    pub regime_previdencia: i32,
 }
 
 
@@ -38,7 +1061,93 @@ pub struct Municipio {
     pub id: i32,
     pub uf_id: i32,
     pub nome: String,
+    pub codigo_ibge: Option<i32>,
 
     //outros
     pub uf_nome: Option<String>,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn dec(valor: &str) -> BigDecimal {
+        BigDecimal::from_str(valor).unwrap()
+    }
+
+    fn item(tipo: &str, valor: &str) -> FolhaItem {
+        FolhaItem {
+            id: 0,
+            folha_id: 1,
+            rubrica_id: 1,
+            referencia: BigDecimal::zero(),
+            valor: dec(valor),
+            parcela: None,
+            rubrica_codigo: None,
+            rubrica_descricao: None,
+            rubrica_tipo: Some(tipo.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_folha_detalhe_totais() {
+        let folha = Folha {
+            id: 1,
+            orgao_id: 1,
+            ano: 2025,
+            mes: 2,
+            servidor_id: 1,
+            salario: dec("3500"),
+            base_fgts: BigDecimal::zero(),
+            base_inss: BigDecimal::zero(),
+            base_irrf: BigDecimal::zero(),
+            ded_irrf: BigDecimal::zero(),
+            cargo_id: 1,
+            setor_id: 1,
+            departamento_id: 1,
+            vinculo_id: 1,
+            dependentes_irrf: 0,
+            tipo_folha: FOLHA_MENSAL,
+            sequencia: 0,
+            serv_nome: None,
+            org_nome: None,
+        };
+        let itens = vec![
+            item(RUBRICA_PROVENTO, "3500.00"),
+            item(RUBRICA_PROVENTO, "250.50"),
+            item(RUBRICA_DESCONTO, "310.25"),
+            // informativa aparece no contra-cheque mas não entra nos totais
+            item(RUBRICA_INFORMATIVA, "1000.00"),
+        ];
+
+        let detalhe = FolhaDetalhe::new(folha, itens);
+        assert_eq!(detalhe.total_proventos, dec("3750.50"));
+        assert_eq!(detalhe.total_descontos, dec("310.25"));
+        assert_eq!(detalhe.valor_liquido, dec("3440.25"));
+        assert_eq!(detalhe.itens.len(), 4);
+    }
+
+    #[test]
+    fn test_transicao_permitida() {
+        assert!(transicao_permitida(COMPETENCIA_ABERTA, COMPETENCIA_CALCULADA));
+        assert!(transicao_permitida(COMPETENCIA_CALCULADA, COMPETENCIA_CALCULADA));
+        assert!(transicao_permitida(COMPETENCIA_CALCULADA, COMPETENCIA_FECHADA));
+        assert!(transicao_permitida(COMPETENCIA_FECHADA, COMPETENCIA_REABERTA));
+        assert!(transicao_permitida(COMPETENCIA_REABERTA, COMPETENCIA_CALCULADA));
+    }
+
+    #[test]
+    fn test_transicao_rejeitada() {
+        // fecha só depois de calculada, reabre só o que está fechado
+        assert!(!transicao_permitida(COMPETENCIA_ABERTA, COMPETENCIA_FECHADA));
+        assert!(!transicao_permitida(COMPETENCIA_REABERTA, COMPETENCIA_FECHADA));
+        assert!(!transicao_permitida(COMPETENCIA_ABERTA, COMPETENCIA_REABERTA));
+        assert!(!transicao_permitida(COMPETENCIA_CALCULADA, COMPETENCIA_REABERTA));
+        // fechada não volta a ser calculada nem aberta sem reabrir
+        assert!(!transicao_permitida(COMPETENCIA_FECHADA, COMPETENCIA_CALCULADA));
+        assert!(!transicao_permitida(COMPETENCIA_FECHADA, COMPETENCIA_ABERTA));
+        assert!(!transicao_permitida(COMPETENCIA_CALCULADA, COMPETENCIA_ABERTA));
+    }
+}
@@ -0,0 +1,788 @@
+use std::io::{Cursor, Seek, Write};
+
+use anyhow::Result;
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::America::Sao_Paulo;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    cadastro::{
+        autenticacao,
+        informe::InformeRendimentos,
+        rescisao::MOTIVO_JUSTA_CAUSA,
+        model::{
+            AvisoFerias, ContraCheque, descricao_tipo_folha,
+            RUBRICA_DESCONTO, RUBRICA_PROVENTO, TermoRescisao, Timbre,
+        },
+    },
+    utils::{
+        pdf::{
+            A4_LARGURA, Alinhamento, Documento, ajustar_texto, decimal_br, largura_texto,
+            letra_base,
+        },
+        validator::{formatar_cnpj, formatar_cpf},
+    },
+};
+
+const MESES: [&str; 12] = [
+    "JANEIRO", "FEVEREIRO", "MARÇO", "ABRIL", "MAIO", "JUNHO", "JULHO", "AGOSTO", "SETEMBRO",
+    "OUTUBRO", "NOVEMBRO", "DEZEMBRO",
+];
+
+const MARGEM: f32 = 15.0;
+const DIREITA: f32 = A4_LARGURA - MARGEM;
+const LARGURA_UTIL: f32 = DIREITA - MARGEM;
+/// A partir desta altura a tabela de itens continua na página seguinte
+const LIMITE_PAGINA: f32 = 265.0;
+const ALTURA_LINHA: f32 = 5.0;
+
+/// Nome da competência por extenso, como "FEVEREIRO DE 2025"
+pub fn competencia_por_extenso(ano: i32, mes: i32) -> String {
+    let nome = MESES.get((mes - 1) as usize).copied().unwrap_or("");
+    format!("{} DE {}", nome, ano)
+}
+
+/// Gera o contra-cheque em PDF (A4) a partir da folha, dos seus itens e do cabeçalho
+/// com servidor e órgão. A mesma emissão produz sempre o mesmo documento
+pub fn contra_cheque(contra_cheque: &ContraCheque) -> Result<Vec<u8>> {
+    let folha = &contra_cheque.detalhe.folha;
+    let mut doc = Documento::new(
+        &format!(
+            "Contra-cheque {:02}/{} - {}",
+            folha.mes, folha.ano, contra_cheque.cabecalho.serv_nome
+        ),
+        &contra_cheque.emissao.codigo,
+        contra_cheque.emissao.emitido_em,
+    )?;
+    desenhar_contra_cheque(&mut doc, contra_cheque)?;
+    doc.salvar()
+}
+
+/// Todos os contra-cheques num único PDF, cada um começando em uma página nova
+pub fn contra_cheques_pdf<W: Write>(
+    destino: W,
+    titulo: &str,
+    contra_cheques: &[ContraCheque],
+    emitido_em: DateTime<Utc>,
+) -> Result<()> {
+    let mut doc = Documento::new(titulo, titulo, emitido_em)?;
+    for (i, contra_cheque) in contra_cheques.iter().enumerate() {
+        if i > 0 {
+            doc.nova_pagina();
+        }
+        desenhar_contra_cheque(&mut doc, contra_cheque)?;
+    }
+    doc.salvar_em(destino)
+}
+
+/// ZIP com um PDF por contra-cheque, gravado no destino um PDF de cada vez
+pub fn contra_cheques_zip<W: Write + Seek>(destino: W, contra_cheques: &[ContraCheque]) -> Result<W> {
+    let mut zip = ZipWriter::new(destino);
+    let opcoes = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for contra_cheque in contra_cheques {
+        let folha = &contra_cheque.detalhe.folha;
+        let nome = format!(
+            "{}-{}.pdf",
+            nome_arquivo(&contra_cheque.cabecalho.serv_nome),
+            folha.id
+        );
+        zip.start_file(nome, opcoes)?;
+        zip.write_all(&self::contra_cheque(contra_cheque)?)?;
+    }
+
+    Ok(zip.finish()?)
+}
+
+/// Texto seguro para nome de arquivo: sem acentos, espaços ou separadores
+pub fn nome_arquivo(texto: &str) -> String {
+    let mut nome = String::new();
+    for c in texto.trim().chars() {
+        let c = letra_base(c);
+        if c.is_ascii_alphanumeric() {
+            nome.push(c.to_ascii_uppercase());
+        } else if !nome.ends_with('_') {
+            nome.push('_');
+        }
+    }
+    nome.trim_matches('_').to_string()
+}
+
+/// Cabeçalho com logotipo, nome, CNPJ, endereço e contato do órgão e o título do
+/// documento à direita; ocupa a página até a altura 30
+pub fn desenhar_timbre(doc: &Documento, timbre: &Timbre, titulo: &str) -> Result<()> {
+    let x = match &timbre.logo {
+        Some(logo) => {
+            doc.imagem(logo, MARGEM, 9.0, 20.0, 20.0)?;
+            MARGEM + 24.0
+        }
+        None => MARGEM,
+    };
+
+    let largura_titulo = largura_texto(titulo, 12.0, true) + 5.0;
+    let nome = ajustar_texto(&timbre.nome.to_uppercase(), DIREITA - x - largura_titulo, 12.0, true);
+    doc.texto(&nome, x, 14.0, 12.0, true, Alinhamento::Esquerda);
+    doc.texto(titulo, DIREITA, 14.0, 12.0, true, Alinhamento::Direita);
+
+    let linhas = [
+        timbre.cnpj.as_deref().map(|cnpj| format!("CNPJ: {}", formatar_cnpj(cnpj))),
+        timbre.endereco(),
+        timbre.contato(),
+    ];
+    let mut y = 14.0;
+    for linha in linhas.into_iter().flatten() {
+        y += 4.0;
+        doc.texto(&ajustar_texto(&linha, DIREITA - x, 8.0, false), x, y, 8.0, false, Alinhamento::Esquerda);
+    }
+
+    Ok(())
+}
+
+fn desenhar_contra_cheque(doc: &mut Documento, contra_cheque: &ContraCheque) -> Result<()> {
+    let cabecalho = &contra_cheque.cabecalho;
+    let detalhe = &contra_cheque.detalhe;
+    let emissao = &contra_cheque.emissao;
+    let folha = &detalhe.folha;
+
+    // cabeçalho
+    desenhar_timbre(doc, &contra_cheque.timbre, "Recibo de Pagamento")?;
+    let tipo = descricao_tipo_folha(folha.tipo_folha, folha.sequencia);
+    doc.texto(
+        &format!(
+            "Mês de Referência: {}{}",
+            competencia_por_extenso(folha.ano, folha.mes),
+            if tipo.is_empty() { tipo } else { format!(" - {}", tipo) }
+        ),
+        MARGEM,
+        33.0,
+        9.0,
+        false,
+        Alinhamento::Esquerda,
+    );
+    doc.texto(
+        &format!(
+            "Impressão: {}",
+            emissao.emitido_em.with_timezone(&Sao_Paulo).format("%d/%m/%Y %H:%M")
+        ),
+        DIREITA,
+        33.0,
+        9.0,
+        false,
+        Alinhamento::Direita,
+    );
+    doc.linha(MARGEM, 35.5, DIREITA, 35.5, 0.8);
+
+    // dados do servidor
+    let nao_informado = |valor: &Option<String>| valor.clone().unwrap_or_else(|| "-".to_string());
+    let servidor = match &cabecalho.serv_matricula {
+        Some(matricula) => format!("{} - {}", matricula, cabecalho.serv_nome),
+        None => cabecalho.serv_nome.clone(),
+    };
+    let dados = [
+        (
+            format!("Servidor: {}", servidor),
+            format!("Cargo Atual: {}", nao_informado(&cabecalho.cargo_nome)),
+        ),
+        (
+            format!("CPF: {}", nao_informado(&cabecalho.serv_cpf.as_deref().map(formatar_cpf))),
+            format!(
+                "Admissão: {}",
+                nao_informado(&cabecalho.serv_data_admissao.map(|d| d.format("%d/%m/%Y").to_string()))
+            ),
+        ),
+        (
+            format!("Vínculo: {}", nao_informado(&cabecalho.vinculo_nome)),
+            format!("Departamento: {}", nao_informado(&cabecalho.departamento_nome)),
+        ),
+        (
+            format!("Lotação: {}", nao_informado(&cabecalho.setor_nome)),
+            format!("Salário Base: {}", decimal_br(&folha.salario)),
+        ),
+    ];
+
+    let meio = MARGEM + LARGURA_UTIL / 2.0;
+    let mut y = 40.0;
+    doc.retangulo(MARGEM, y, LARGURA_UTIL, dados.len() as f32 * ALTURA_LINHA + 3.0, false);
+    for (esquerda, direita) in &dados {
+        y += ALTURA_LINHA;
+        let largura = LARGURA_UTIL / 2.0 - 4.0;
+        doc.texto(&ajustar_texto(esquerda, largura, 9.0, false), MARGEM + 2.0, y, 9.0, false, Alinhamento::Esquerda);
+        doc.texto(&ajustar_texto(direita, largura, 9.0, false), meio + 2.0, y, 9.0, false, Alinhamento::Esquerda);
+    }
+    y += 12.0;
+
+    // proventos e descontos
+    doc.texto("Proventos e Descontos", MARGEM, y, 11.0, true, Alinhamento::Esquerda);
+    y += 3.0;
+    y = cabecalho_itens(doc, y);
+
+    let itens = detalhe
+        .itens
+        .iter()
+        .filter(|i| matches!(i.rubrica_tipo.as_deref(), Some(RUBRICA_PROVENTO | RUBRICA_DESCONTO)));
+    for item in itens {
+        if y + ALTURA_LINHA > LIMITE_PAGINA {
+            doc.nova_pagina();
+            doc.texto("Proventos e Descontos (continuação)", MARGEM, 18.0, 11.0, true, Alinhamento::Esquerda);
+            y = cabecalho_itens(doc, 21.0);
+        }
+
+        y += ALTURA_LINHA;
+        let (provento, desconto) = match item.rubrica_tipo.as_deref() {
+            Some(RUBRICA_PROVENTO) => (decimal_br(&item.valor), "0,00".to_string()),
+            _ => ("0,00".to_string(), decimal_br(&item.valor)),
+        };
+        let descricao = ajustar_texto(item.rubrica_descricao.as_deref().unwrap_or(""), 70.0, 9.0, false);
+
+        doc.texto(item.rubrica_codigo.as_deref().unwrap_or(""), MARGEM + 2.0, y, 9.0, false, Alinhamento::Esquerda);
+        doc.texto(&descricao, MARGEM + 18.0, y, 9.0, false, Alinhamento::Esquerda);
+        doc.texto(item.parcela.as_deref().unwrap_or("-"), 111.0, y, 9.0, false, Alinhamento::Centro);
+        doc.texto(&decimal_br(&item.referencia), 134.0, y, 9.0, false, Alinhamento::Direita);
+        doc.texto(&provento, 163.0, y, 9.0, false, Alinhamento::Direita);
+        doc.texto(&desconto, DIREITA - 2.0, y, 9.0, false, Alinhamento::Direita);
+    }
+    y += 2.0;
+    doc.linha(MARGEM, y, DIREITA, y, 0.5);
+
+    // totais e autenticação
+    if y + 60.0 > LIMITE_PAGINA {
+        doc.nova_pagina();
+        y = 12.0;
+    }
+    y += 8.0;
+    doc.texto("Totais", MARGEM, y, 11.0, true, Alinhamento::Esquerda);
+
+    let coluna = LARGURA_UTIL / 4.0;
+    let totais = [
+        [
+            ("BASE PREVIDÊNCIA", decimal_br(&folha.base_inss)),
+            ("BASE IRRF", decimal_br(&folha.base_irrf)),
+            ("BASE FGTS", decimal_br(&folha.base_fgts)),
+            ("Dep. IRRF", folha.dependentes_irrf.to_string()),
+        ],
+        [
+            ("Bruto", decimal_br(&detalhe.total_proventos)),
+            ("Descontos", decimal_br(&detalhe.total_descontos)),
+            ("", String::new()),
+            ("Líquido", decimal_br(&detalhe.valor_liquido)),
+        ],
+    ];
+    for linha in &totais {
+        y += 6.0;
+        for (i, (rotulo, valor)) in linha.iter().enumerate() {
+            if rotulo.is_empty() {
+                continue;
+            }
+            let x = MARGEM + coluna * i as f32;
+            let destaque = *rotulo == "Líquido";
+            doc.texto(rotulo, x, y, 8.0, destaque, Alinhamento::Esquerda);
+            doc.texto(valor, x, y + 4.5, 10.0, destaque, Alinhamento::Esquerda);
+        }
+        y += 4.5;
+    }
+
+    // autenticidade: código de verificação e QR code com o endereço de validação
+    let url = autenticacao::url_autenticacao(&emissao.codigo);
+    let topo = y + 8.0;
+    doc.linha(MARGEM, topo, DIREITA, topo, 0.5);
+    doc.qrcode(&url, DIREITA - 25.0, topo + 3.0, 25.0)?;
+
+    let largura = LARGURA_UTIL - 30.0;
+    doc.texto(
+        "Para verificar a autenticidade desse contra-cheque acesse:",
+        MARGEM,
+        topo + 8.0,
+        9.0,
+        false,
+        Alinhamento::Esquerda,
+    );
+    doc.cor_preenchimento(0.0, 0.0, 0.8);
+    doc.texto(&ajustar_texto(&url, largura, 9.0, false), MARGEM, topo + 13.0, 9.0, false, Alinhamento::Esquerda);
+    doc.cor_preenchimento(0.0, 0.0, 0.0);
+    doc.texto("Ou informe o código de verificação:", MARGEM, topo + 19.0, 9.0, false, Alinhamento::Esquerda);
+    doc.texto(&emissao.codigo, MARGEM, topo + 25.0, 12.0, true, Alinhamento::Esquerda);
+
+    Ok(())
+}
+
+/// Linha de títulos da tabela de itens; retorna a altura onde ela termina
+fn cabecalho_itens(doc: &Documento, y: f32) -> f32 {
+    doc.cor_preenchimento(0.9, 0.9, 0.9);
+    doc.retangulo(MARGEM, y, LARGURA_UTIL, 6.5, true);
+    doc.cor_preenchimento(0.0, 0.0, 0.0);
+
+    let base = y + 4.5;
+    doc.texto("Código", MARGEM + 2.0, base, 9.0, true, Alinhamento::Esquerda);
+    doc.texto("Descrição", MARGEM + 18.0, base, 9.0, true, Alinhamento::Esquerda);
+    doc.texto("Parc.", 111.0, base, 9.0, true, Alinhamento::Centro);
+    doc.texto("Ref.", 134.0, base, 9.0, true, Alinhamento::Direita);
+    doc.texto("Proventos", 163.0, base, 9.0, true, Alinhamento::Direita);
+    doc.texto("Descontos", DIREITA - 2.0, base, 9.0, true, Alinhamento::Direita);
+
+    y + 6.5
+}
+
+/// Gera o informe de rendimentos (comprovante de rendimentos pagos e de IRRF) em PDF
+pub fn informe_rendimentos(informe: &InformeRendimentos, emitido_em: DateTime<Utc>) -> Result<Vec<u8>> {
+    let titulo = format!("Informe de Rendimentos {} - {}", informe.ano, informe.nome);
+    let doc = Documento::new(&titulo, &titulo, emitido_em)?;
+    desenhar_informe(&doc, informe, emitido_em)?;
+    doc.salvar()
+}
+
+/// Todos os informes num único PDF, um por página
+pub fn informes_rendimentos_pdf(
+    titulo: &str,
+    informes: &[InformeRendimentos],
+    emitido_em: DateTime<Utc>,
+) -> Result<Vec<u8>> {
+    let mut doc = Documento::new(titulo, titulo, emitido_em)?;
+    for (i, informe) in informes.iter().enumerate() {
+        if i > 0 {
+            doc.nova_pagina();
+        }
+        desenhar_informe(&doc, informe, emitido_em)?;
+    }
+    doc.salvar()
+}
+
+/// ZIP com um PDF por servidor
+pub fn informes_rendimentos_zip(informes: &[InformeRendimentos], emitido_em: DateTime<Utc>) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let opcoes = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for informe in informes {
+        let nome = format!("{}-{}.pdf", nome_arquivo(&informe.nome), informe.servidor_id);
+        zip.start_file(nome, opcoes)?;
+        zip.write_all(&informe_rendimentos(informe, emitido_em)?)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+fn desenhar_informe(doc: &Documento, informe: &InformeRendimentos, emitido_em: DateTime<Utc>) -> Result<()> {
+    let timbre = &informe.timbre;
+    let valores = &informe.valores;
+    let zero = BigDecimal::zero();
+
+    desenhar_timbre(doc, timbre, "Informe de Rendimentos")?;
+    let centro = MARGEM + LARGURA_UTIL / 2.0;
+    doc.texto(
+        "COMPROVANTE DE RENDIMENTOS PAGOS E DE IMPOSTO SOBRE A RENDA RETIDO NA FONTE",
+        centro,
+        37.0,
+        10.0,
+        true,
+        Alinhamento::Centro,
+    );
+    doc.texto(
+        &format!("Ano-calendário de {}", informe.ano),
+        centro,
+        42.0,
+        9.0,
+        false,
+        Alinhamento::Centro,
+    );
+
+    let nao_informado = |valor: Option<String>| valor.unwrap_or_else(|| "-".to_string());
+    let mut y = 47.0;
+    y = quadro_informe(
+        doc,
+        y,
+        "1. Fonte pagadora pessoa jurídica",
+        &[
+            ("CNPJ".to_string(), nao_informado(timbre.cnpj.as_deref().map(formatar_cnpj))),
+            ("Nome empresarial".to_string(), timbre.nome.to_uppercase()),
+        ],
+    );
+    y = quadro_informe(
+        doc,
+        y,
+        "2. Pessoa física beneficiária dos rendimentos",
+        &[
+            ("CPF".to_string(), nao_informado(informe.cpf.as_deref().map(formatar_cpf))),
+            ("Nome completo".to_string(), informe.nome.to_uppercase()),
+            ("Matrícula".to_string(), nao_informado(informe.matricula.clone())),
+            (
+                "Natureza do rendimento".to_string(),
+                "Rendimentos do trabalho assalariado".to_string(),
+            ),
+        ],
+    );
+
+    let linhas = |itens: &[(&str, &BigDecimal)]| {
+        itens
+            .iter()
+            .enumerate()
+            .map(|(i, (descricao, valor))| (format!("{:02}. {}", i + 1, descricao), decimal_br(valor)))
+            .collect::<Vec<_>>()
+    };
+    y = quadro_informe(
+        doc,
+        y,
+        "3. Rendimentos tributáveis, deduções e imposto sobre a renda retido na fonte (R$)",
+        &linhas(&[
+            ("Total dos rendimentos (inclusive férias)", &valores.tributavel),
+            ("Contribuição previdenciária oficial", &valores.previdencia),
+            ("Contribuição a entidades de previdência complementar", &valores.previdencia_complementar),
+            ("Pensão alimentícia", &valores.pensao),
+            ("Imposto sobre a renda retido na fonte", &valores.irrf),
+        ]),
+    );
+    y = quadro_informe(
+        doc,
+        y,
+        "4. Rendimentos isentos e não tributáveis (R$)",
+        &linhas(&[
+            ("Parcela isenta dos proventos de aposentadoria (65 anos ou mais)", &zero),
+            ("Diárias e ajudas de custo", &valores.diarias),
+            ("Pensão e proventos de aposentadoria ou reforma por moléstia grave", &valores.molestia_grave),
+            ("Indenizações por rescisão de contrato de trabalho", &valores.indenizacao),
+            ("Outros", &valores.isento),
+        ]),
+    );
+    let decimo_terceiro = valores.decimo_terceiro_liquido();
+    y = quadro_informe(
+        doc,
+        y,
+        "5. Rendimentos sujeitos à tributação exclusiva (rendimento líquido) (R$)",
+        &linhas(&[
+            ("Décimo terceiro salário", &decimo_terceiro),
+            ("Imposto sobre a renda retido na fonte sobre 13º salário", &valores.irrf_decimo_terceiro),
+            ("Outros", &zero),
+        ]),
+    );
+
+    let mut complementares = vec![(
+        format!("Valores das folhas do órgão nas competências de janeiro a dezembro de {}.", informe.ano),
+        String::new(),
+    )];
+    if !valores.previdencia_decimo_terceiro.is_zero() {
+        complementares.push((
+            "Contribuição previdenciária sobre o 13º salário (deduzida da linha 5.01)".to_string(),
+            decimal_br(&valores.previdencia_decimo_terceiro),
+        ));
+    }
+    y = quadro_informe(doc, y, "6. Informações complementares", &complementares);
+
+    // responsável pelas informações
+    let data = emitido_em.with_timezone(&Sao_Paulo).format("%d/%m/%Y").to_string();
+    y = quadro_informe(
+        doc,
+        y,
+        "7. Responsável pelas informações",
+        &[(timbre.nome.to_uppercase(), format!("Data: {}", data))],
+    );
+    let assinatura = y + 14.0;
+    doc.linha(DIREITA - 80.0, assinatura, DIREITA, assinatura, 0.3);
+    doc.texto("Assinatura", DIREITA - 40.0, assinatura + 4.0, 8.0, false, Alinhamento::Centro);
+
+    Ok(())
+}
+
+/// Gera o aviso e recibo de férias em PDF: período aquisitivo, gozo, abono e, quando as
+/// férias já foram lançadas na folha, os valores pagos
+pub fn aviso_ferias(aviso: &AvisoFerias, emitido_em: DateTime<Utc>) -> Result<Vec<u8>> {
+    let ferias = &aviso.ferias;
+    let servidor = &aviso.servidor;
+    let titulo = format!("Aviso de Férias - {}", servidor.nome);
+    let doc = Documento::new(&titulo, &titulo, emitido_em)?;
+
+    desenhar_timbre(&doc, &aviso.timbre, "Aviso e Recibo de Férias")?;
+    let data = |dia: NaiveDate| dia.format("%d/%m/%Y").to_string();
+    let nao_informado = |valor: Option<String>| valor.unwrap_or_else(|| "-".to_string());
+
+    let mut y = 37.0;
+    y = quadro_informe(
+        &doc,
+        y,
+        "1. Servidor",
+        &[
+            ("Nome".to_string(), servidor.nome.to_uppercase()),
+            ("Matrícula".to_string(), nao_informado(servidor.matricula.clone())),
+            ("CPF".to_string(), nao_informado(servidor.cpf.as_deref().map(formatar_cpf))),
+            ("Cargo".to_string(), nao_informado(servidor.cargo_nome.clone())),
+            ("Admissão".to_string(), nao_informado(servidor.data_admissao.map(data))),
+        ],
+    );
+
+    let periodo = match (ferias.periodo_inicio, ferias.periodo_fim) {
+        (Some(inicio), Some(fim)) => format!("{} a {}", data(inicio), data(fim)),
+        _ => "-".to_string(),
+    };
+    let abono = if ferias.dias_abono > 0 {
+        format!("{} dias", ferias.dias_abono)
+    } else {
+        "Não".to_string()
+    };
+    y = quadro_informe(
+        &doc,
+        y,
+        "2. Férias",
+        &[
+            ("Período aquisitivo".to_string(), periodo),
+            (
+                "Período de gozo".to_string(),
+                format!("{} a {} ({} dias)", data(ferias.data_inicio), data(ferias.data_fim), ferias.dias),
+            ),
+            (
+                "Retorno ao trabalho".to_string(),
+                nao_informado(ferias.data_fim.succ_opt().map(data)),
+            ),
+            ("Abono pecuniário".to_string(), abono),
+            (
+                "Competência de pagamento".to_string(),
+                competencia_por_extenso(ferias.ano, ferias.mes),
+            ),
+        ],
+    );
+
+    let valores = match (
+        &ferias.valor_ferias,
+        &ferias.valor_terco,
+        &ferias.valor_abono,
+        &ferias.valor_terco_abono,
+    ) {
+        (Some(valor_ferias), Some(terco), Some(abono), Some(terco_abono)) => {
+            let mut linhas = vec![
+                (format!("Férias ({} dias)", ferias.dias), decimal_br(valor_ferias)),
+                ("1/3 constitucional de férias".to_string(), decimal_br(terco)),
+            ];
+            if ferias.dias_abono > 0 {
+                linhas.push((format!("Abono pecuniário ({} dias)", ferias.dias_abono), decimal_br(abono)));
+                linhas.push(("1/3 do abono pecuniário".to_string(), decimal_br(terco_abono)));
+            }
+            let total = valor_ferias + terco + abono + terco_abono;
+            linhas.push(("Total bruto (INSS e IRRF na folha da competência)".to_string(), decimal_br(&total)));
+            linhas
+        }
+        _ => vec![(
+            format!(
+                "Valores apurados no cálculo da folha de {}",
+                competencia_por_extenso(ferias.ano, ferias.mes)
+            ),
+            String::new(),
+        )],
+    };
+    y = quadro_informe(&doc, y, "3. Valores (R$)", &valores);
+
+    // aviso, com a ciência do servidor, e recibo
+    let local = match &aviso.timbre.municipio_nome {
+        Some(municipio) => format!("{}, ", municipio),
+        None => String::new(),
+    };
+    let emissao = emitido_em.with_timezone(&Sao_Paulo).format("%d/%m/%Y");
+    doc.texto(
+        "Comunicamos que as férias acima serão concedidas no período de gozo indicado.",
+        MARGEM,
+        y + 2.0,
+        9.0,
+        false,
+        Alinhamento::Esquerda,
+    );
+    doc.texto(
+        &format!("{}{}", local, emissao),
+        MARGEM,
+        y + 8.0,
+        9.0,
+        false,
+        Alinhamento::Esquerda,
+    );
+    let assinatura = y + 26.0;
+    assinaturas(&doc, assinatura, &aviso.timbre.nome.to_uppercase(), "Ciente: servidor");
+
+    let recibo = assinatura + 14.0;
+    doc.texto(
+        "Recebi a importância acima, referente às férias, e declaro estar ciente do período de gozo.",
+        MARGEM,
+        recibo,
+        9.0,
+        false,
+        Alinhamento::Esquerda,
+    );
+    doc.texto("Data: ____/____/________", MARGEM, recibo + 6.0, 9.0, false, Alinhamento::Esquerda);
+    assinaturas(&doc, recibo + 24.0, "", &servidor.nome.to_uppercase());
+
+    doc.salvar()
+}
+
+/// Termo de rescisão: servidor, contrato, o que foi apurado e as verbas da folha da
+/// rescisão, com as assinaturas do órgão e do servidor
+pub fn termo_rescisao(termo: &TermoRescisao, emitido_em: DateTime<Utc>) -> Result<Vec<u8>> {
+    let cabecalho = &termo.cabecalho;
+    let detalhe = &termo.detalhe;
+    let folha = &detalhe.folha;
+    let rescisao = &termo.rescisao;
+    let titulo = format!("Termo de Rescisão - {}", cabecalho.serv_nome);
+    let mut doc = Documento::new(&titulo, &titulo, emitido_em)?;
+
+    desenhar_timbre(&doc, &termo.timbre, "Termo de Rescisão")?;
+    let data = |dia: NaiveDate| dia.format("%d/%m/%Y").to_string();
+    let nao_informado = |valor: Option<String>| valor.unwrap_or_else(|| "-".to_string());
+
+    let mut y = 37.0;
+    y = quadro_informe(
+        &doc,
+        y,
+        "1. Servidor",
+        &[
+            ("Nome".to_string(), cabecalho.serv_nome.to_uppercase()),
+            ("Matrícula".to_string(), nao_informado(cabecalho.serv_matricula.clone())),
+            ("CPF".to_string(), nao_informado(cabecalho.serv_cpf.as_deref().map(formatar_cpf))),
+            ("Cargo".to_string(), nao_informado(cabecalho.cargo_nome.clone())),
+            ("Vínculo".to_string(), nao_informado(cabecalho.vinculo_nome.clone())),
+            ("Lotação".to_string(), nao_informado(cabecalho.setor_nome.clone())),
+        ],
+    );
+
+    let motivo = if rescisao.motivo_desligamento == MOTIVO_JUSTA_CAUSA {
+        format!("{} - justa causa", rescisao.motivo_desligamento)
+    } else {
+        rescisao.motivo_desligamento.clone()
+    };
+    y = quadro_informe(
+        &doc,
+        y,
+        "2. Contrato",
+        &[
+            ("Admissão".to_string(), nao_informado(cabecalho.serv_data_admissao.map(data))),
+            ("Desligamento".to_string(), data(rescisao.data_desligamento)),
+            ("Motivo (tabela 19 do eSocial)".to_string(), motivo),
+            ("Salário base".to_string(), decimal_br(&folha.salario)),
+            ("Competência de pagamento".to_string(), competencia_por_extenso(folha.ano, folha.mes)),
+        ],
+    );
+
+    y = quadro_informe(
+        &doc,
+        y,
+        "3. Apuração",
+        &[
+            ("Saldo de salário".to_string(), format!("{} dias", rescisao.dias_saldo_salario)),
+            ("13º salário proporcional".to_string(), format!("{}/12", rescisao.avos_decimo_terceiro)),
+            ("Férias vencidas".to_string(), format!("{} dias", rescisao.dias_ferias_vencidas)),
+            ("Férias proporcionais".to_string(), format!("{}/12", rescisao.avos_ferias_proporcionais)),
+        ],
+    );
+
+    let mut verbas: Vec<(String, String)> = detalhe
+        .itens
+        .iter()
+        .filter_map(|item| {
+            let descricao = item.rubrica_descricao.clone().unwrap_or_default();
+            match item.rubrica_tipo.as_deref() {
+                Some(RUBRICA_PROVENTO) => Some((descricao, decimal_br(&item.valor))),
+                Some(RUBRICA_DESCONTO) => Some((format!("(-) {}", descricao), decimal_br(&item.valor))),
+                _ => None,
+            }
+        })
+        .collect();
+    verbas.push(("Total bruto".to_string(), decimal_br(&detalhe.total_proventos)));
+    verbas.push(("Total de descontos".to_string(), decimal_br(&detalhe.total_descontos)));
+    verbas.push(("Líquido a receber".to_string(), decimal_br(&detalhe.valor_liquido)));
+    if y + verbas.len() as f32 * ALTURA_LINHA + 12.0 > LIMITE_PAGINA {
+        doc.nova_pagina();
+        y = 12.0;
+    }
+    y = quadro_informe(&doc, y, "4. Verbas rescisórias (R$)", &verbas);
+
+    // quitação, com local e data
+    if y + 40.0 > LIMITE_PAGINA {
+        doc.nova_pagina();
+        y = 12.0;
+    }
+    let local = match &termo.timbre.municipio_nome {
+        Some(municipio) => format!("{}, ", municipio),
+        None => String::new(),
+    };
+    doc.texto(
+        "Recebi a importância líquida acima, referente às verbas rescisórias discriminadas neste termo.",
+        MARGEM,
+        y + 2.0,
+        9.0,
+        false,
+        Alinhamento::Esquerda,
+    );
+    doc.texto(
+        &format!("{}{}", local, emitido_em.with_timezone(&Sao_Paulo).format("%d/%m/%Y")),
+        MARGEM,
+        y + 8.0,
+        9.0,
+        false,
+        Alinhamento::Esquerda,
+    );
+    assinaturas(
+        &doc,
+        y + 26.0,
+        &termo.timbre.nome.to_uppercase(),
+        &cabecalho.serv_nome.to_uppercase(),
+    );
+
+    doc.salvar()
+}
+
+/// Linhas de assinatura lado a lado; sem legenda, a linha da esquerda não é desenhada
+fn assinaturas(doc: &Documento, y: f32, esquerda: &str, direita: &str) {
+    let largura = 80.0;
+    if !esquerda.is_empty() {
+        doc.linha(MARGEM, y, MARGEM + largura, y, 0.3);
+        doc.texto(
+            &ajustar_texto(esquerda, largura, 8.0, false),
+            MARGEM + largura / 2.0,
+            y + 4.0,
+            8.0,
+            false,
+            Alinhamento::Centro,
+        );
+    }
+    doc.linha(DIREITA - largura, y, DIREITA, y, 0.3);
+    doc.texto(
+        &ajustar_texto(direita, largura, 8.0, false),
+        DIREITA - largura / 2.0,
+        y + 4.0,
+        8.0,
+        false,
+        Alinhamento::Centro,
+    );
+}
+
+/// Quadro do informe: título sobre fundo cinza e uma linha por item, com a descrição
+/// à esquerda e o valor à direita; retorna a altura onde o próximo quadro começa
+fn quadro_informe(doc: &Documento, y: f32, titulo: &str, linhas: &[(String, String)]) -> f32 {
+    doc.cor_preenchimento(0.9, 0.9, 0.9);
+    doc.retangulo(MARGEM, y, LARGURA_UTIL, 6.0, true);
+    doc.cor_preenchimento(0.0, 0.0, 0.0);
+    doc.texto(
+        &ajustar_texto(titulo, LARGURA_UTIL - 4.0, 9.0, true),
+        MARGEM + 2.0,
+        y + 4.2,
+        9.0,
+        true,
+        Alinhamento::Esquerda,
+    );
+
+    let altura = linhas.len() as f32 * ALTURA_LINHA + 2.0;
+    doc.retangulo(MARGEM, y + 6.0, LARGURA_UTIL, altura, false);
+    let mut base = y + 6.0;
+    for (descricao, valor) in linhas {
+        base += ALTURA_LINHA;
+        let largura_valor = largura_texto(valor, 9.0, false);
+        let descricao = ajustar_texto(descricao, LARGURA_UTIL - largura_valor - 8.0, 9.0, false);
+        doc.texto(&descricao, MARGEM + 2.0, base, 9.0, false, Alinhamento::Esquerda);
+        doc.texto(valor, DIREITA - 2.0, base, 9.0, false, Alinhamento::Direita);
+    }
+
+    y + 6.0 + altura + 4.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nome_arquivo() {
+        assert_eq!(nome_arquivo("João da Conceição"), "JOAO_DA_CONCEICAO");
+        assert_eq!(nome_arquivo("  MARIA  D'ÁVILA / 2 "), "MARIA_D_AVILA_2");
+    }
+}
@@ -1,4 +1,8 @@
-use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
 
 use async_trait::async_trait;
 
@@ -6,11 +10,87 @@ use anyhow::Ok;
 use anyhow::Result;
 use uuid::Uuid;
 
+use crate::cadastro::esocial::{
+    CadastroTrabalhador, Demonstrativo, EventoGerado, ItemRemuneracao, Trabalhador,
+};
+use crate::cadastro::cnab::{Credito, CreditoRetorno};
+use crate::cadastro::ibge::{MunicipioIbge, UfIbge};
+use crate::cadastro::decimo_terceiro::SISTEMA_ADIANTAMENTO_13;
+use crate::cadastro::ferias::ValoresFerias;
+use crate::cadastro::model::{Afastamento, BaseDecimoTerceiro, DECIMO_TERCEIRO_ADIANTAMENTO};
+use crate::cadastro::model::{AlteracaoSalarial, FolhaOrigem, ItemFixo};
+use crate::cadastro::model::{DECIMO_TERCEIRO_FINAL, FOLHA_MENSAL, Rescisao};
+use crate::error::AppError;
+use crate::cadastro::calculo::{SISTEMA_INSS, SISTEMA_IRRF};
+use crate::cadastro::model::{AcumuladoCompetencia, FOLHA_COMPLEMENTAR, FOLHAS_DA_COMPETENCIA, ORDEM_TIPOS_FOLHA};
+use crate::cadastro::model::{FERIAS_CALCULADA, FERIAS_CANCELADA, FERIAS_PROGRAMADA, Ferias, PeriodoAquisitivo};
+use crate::cadastro::model::BaseIrrfFolha;
+use crate::cadastro::model::{COMPETENCIA_ABERTA, COMPETENCIA_CALCULADA, COMPETENCIA_FECHADA, COMPETENCIA_REABERTA};
+use crate::cadastro::model::CabecalhoContraCheque;
+use crate::cadastro::model::Cargo;
+use crate::cadastro::model::Competencia;
+use crate::cadastro::model::CompetenciaHistorico;
+use crate::cadastro::model::{Consignacao, Consignataria, ParcelaConsignacao};
+use crate::cadastro::model::{ConsultaMargem, CredencialConsignataria, ReservaMargem};
+use crate::cadastro::model::{RESERVA_CANCELADA, RESERVA_RESERVADA, RESERVA_UTILIZADA};
+use crate::cadastro::model::Departamento;
+use crate::cadastro::model::FaixaInss;
+use crate::cadastro::model::FaixaIrrf;
+use crate::cadastro::model::EmissaoContraCheque;
+use crate::cadastro::model::EventoEsocial;
+use crate::cadastro::model::{EventoFilaEsocial, FILA_PENDENTE};
+use crate::cadastro::informe::ValorRubricaInforme;
+use crate::cadastro::model::{ESOCIAL_S2200, ESOCIAL_S2300, RUBRICA_DESCONTO, RUBRICA_PROVENTO};
+use crate::cadastro::model::FolhaDetalhe;
+use crate::cadastro::model::FolhaItem;
+use crate::cadastro::model::LoteContraCheque;
+use crate::cadastro::model::{LOTE_CONCLUIDO, LOTE_ERRO, LOTE_PENDENTE, LOTE_PROCESSANDO};
 use crate::cadastro::model::Municipio;
+use crate::cadastro::model::Orgao;
+use crate::cadastro::model::{ItemRemessaCnab, RemessaCnab};
+use crate::cadastro::model::{CREDITO_AGENDADO, CREDITO_ENVIADO, CREDITO_PAGO};
+use crate::cadastro::model::ParametroIrrf;
+use crate::cadastro::model::Rubrica;
+use crate::cadastro::model::Servidor;
+use crate::cadastro::model::Setor;
+use crate::cadastro::model::Timbre;
+use crate::cadastro::model::TipoVinculo;
+use crate::cadastro::model::TotaisCompetencia;
 use crate::cadastro::model::Uf;
+use crate::cadastro::schema::CreateAfastamento;
+use crate::cadastro::schema::{CreateAlteracaoSalarial, GerarFolha};
+use crate::cadastro::schema::CreateCargo;
+use crate::cadastro::schema::CreateCompetencia;
+use crate::cadastro::schema::{CreateConsignacao, CreateConsignataria, CreateConsultaMargem};
+use crate::cadastro::schema::CreateDepartamento;
+use crate::cadastro::schema::CreateFaixaInss;
+use crate::cadastro::schema::CreateFaixaIrrf;
+use crate::cadastro::schema::CreateFerias;
+use crate::cadastro::schema::CreateFolhaItem;
 use crate::cadastro::schema::CreateMunicipio;
+use crate::cadastro::schema::CreateOrgao;
+use crate::cadastro::schema::CreateParametroIrrf;
+use crate::cadastro::schema::CreateRubrica;
+use crate::cadastro::schema::CreateServidor;
+use crate::cadastro::schema::CreateSetor;
+use crate::cadastro::schema::CreateTipoVinculo;
+use crate::cadastro::schema::GerarLoteContraCheque;
+use crate::cadastro::schema::{GerarEsocial, GravarEventoEsocial};
+use crate::cadastro::schema::{GerarRemessaCnab, GravarRemessaCnab};
 use crate::cadastro::schema::CreateUf;
+use crate::cadastro::schema::UpdateCargo;
+use crate::cadastro::schema::{UpdateConsignacao, UpdateConsignataria};
+use crate::cadastro::schema::UpdateDepartamento;
+use crate::cadastro::schema::UpdateFaixaInss;
+use crate::cadastro::schema::UpdateFaixaIrrf;
+use crate::cadastro::schema::UpdateFolhaItem;
 use crate::cadastro::schema::UpdateMunicipio;
+use crate::cadastro::schema::UpdateOrgao;
+use crate::cadastro::schema::UpdateParametroIrrf;
+use crate::cadastro::schema::UpdateRubrica;
+use crate::cadastro::schema::UpdateServidor;
+use crate::cadastro::schema::UpdateSetor;
+use crate::cadastro::schema::UpdateTipoVinculo;
 use crate::cadastro::schema::UpdateUf;
 use crate::{
     cadastro::{
@@ -22,51 +102,30 @@ use crate::{
 
 pub struct FolhaRepository;
 
-#[async_trait]
-impl Repository<Folha, i64> for FolhaRepository {
-    type CreateInput = CreateFolha;
-    type UpdateInput = UpdateFolha;
-
-    fn table_name(&self) -> &str {
-        "cadastro_folha f"
-    }
-
-    fn id_column(&self) -> &str {
-        "f.id"
-    }
-
-    fn order_by_column(&self) -> &str {
-        "f.ano DESC, f.mes ASC"
-    }
-
-    fn searchable_fields(&self) -> &[(&str, &str)] {
-        &[
-            ("f.ano", "="),
-            ("f.mes", "="),
-            ("serv.nome", "ILIKE"),
-        ]
-    }
-
-    fn select_clause(&self) -> &str {
-        "f.id, f.orgao_id, f.ano, f.mes, 
-        f.servidor_id, f.salario, f.base_fgts, f.base_inss,
-        f.base_irrf, f.ded_irrf, f.cargo_id, f.setor_id,
-        f.departamento_id, f.vinculo_id, serv.nome as serv_nome, org.nome as org_nome"
-    }
-
-    fn from_clause(&self) -> &str {
-        "cadastro_folha f
-        INNER JOIN cadastro_servidor serv ON serv.id = f.servidor_id
-        INNER JOIN cadastro_orgao org ON org.id = f.orgao_id
-        "
+impl FolhaRepository {
+    /// Folha lida pela transação, com o que ela já gravou
+    pub async fn get_em(&self, tx: &mut Transaction<'_, Postgres>, id: i64) -> Result<Folha> {
+        Ok(sqlx::query_as!(
+            Folha,
+            r#"SELECT *, NULL as "serv_nome?", NULL as "org_nome?" FROM cadastro_folha WHERE id = $1"#,
+            id
+        )
+        .fetch_one(&mut **tx)
+        .await?)
     }
 
-    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Folha> {
+    /// Gravações da folha na transação de quem chama, que trava a competência antes
+    pub async fn criar(&self, tx: &mut Transaction<'_, Postgres>, input: CreateFolha) -> Result<Folha> {
         Ok(sqlx::query_as!(
             Folha,
             r#"INSERT INTO cadastro_folha(
-            orgao_id, ano, mes, servidor_id, salario, base_fgts, base_inss, base_irrf, ded_irrf, cargo_id, setor_id, departamento_id, vinculo_id)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13) RETURNING *, NULL as "serv_nome?", NULL as "org_nome?" "#,
+            orgao_id, ano, mes, servidor_id, salario, base_fgts, base_inss, base_irrf, ded_irrf, cargo_id, setor_id, departamento_id, vinculo_id, dependentes_irrf, tipo_folha, sequencia)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15,
+                -- a complementar recebe o próximo número da competência
+                CASE WHEN $15::smallint = $16::smallint THEN (
+                    SELECT COALESCE(MAX(sequencia), 0) + 1 FROM cadastro_folha
+                    WHERE orgao_id = $1 AND servidor_id = $4 AND ano = $2 AND mes = $3 AND tipo_folha = $16
+                ) ELSE 0 END) RETURNING *, NULL as "serv_nome?", NULL as "org_nome?" "#,
             input.orgao_id,
             input.ano,
             input.mes,
@@ -79,13 +138,21 @@ impl Repository<Folha, i64> for FolhaRepository {
             input.cargo_id,
             input.setor_id,
             input.departamento_id,
-            input.vinculo_id
+            input.vinculo_id,
+            input.dependentes_irrf,
+            input.tipo_folha,
+            FOLHA_COMPLEMENTAR
         )
-        .fetch_one(pool)
+        .fetch_one(&mut **tx)
         .await?)
     }
 
-    async fn update(&self, pool: &PgPool, id: i64, input: Self::UpdateInput) -> Result<Folha> {
+    pub async fn alterar(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i64,
+        input: UpdateFolha,
+    ) -> Result<Folha> {
         Ok(sqlx::query_as!(
             Folha,
             r#"
@@ -103,8 +170,9 @@ impl Repository<Folha, i64> for FolhaRepository {
                 cargo_id = COALESCE($10, cargo_id),
                 setor_id = COALESCE($11, setor_id),
                 departamento_id = COALESCE($12, departamento_id),
-                vinculo_id = COALESCE($13, vinculo_id)
-            WHERE id = $14
+                vinculo_id = COALESCE($13, vinculo_id),
+                dependentes_irrf = COALESCE($14, dependentes_irrf)
+            WHERE id = $15
             RETURNING *, NULL as "serv_nome?", NULL as "org_nome?" "#,
             input.orgao_id,
             input.ano,
@@ -119,23 +187,639 @@ impl Repository<Folha, i64> for FolhaRepository {
             input.setor_id,
             input.departamento_id,
             input.vinculo_id,
+            input.dependentes_irrf,
             id
         )
-        .fetch_one(pool)
+        .fetch_one(&mut **tx)
         .await?)
     }
 
-    async fn delete(&self, pool: &PgPool, id: i64) -> Result<()> {
-        sqlx::query!("DELETE FROM cadastro_folha WHERE id = $1", id as i32)
-            .execute(pool)
+    pub async fn excluir(&self, tx: &mut Transaction<'_, Postgres>, id: i64) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_folha WHERE id = $1", id)
+            .execute(&mut **tx)
             .await?;
         Ok(())
     }
+
+    pub async fn atualizar_base_inss(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i64,
+        base_inss: &BigDecimal,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE cadastro_folha SET base_inss = $1 WHERE id = $2",
+            base_inss,
+            id
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn atualizar_base_irrf(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i64,
+        base_irrf: &BigDecimal,
+        ded_irrf: &BigDecimal,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE cadastro_folha SET base_irrf = $1, ded_irrf = $2 WHERE id = $3",
+            base_irrf,
+            ded_irrf,
+            id
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Nomes do servidor, órgão e lotação da folha para o cabeçalho do contra-cheque
+    pub async fn cabecalho_contra_cheque(
+        &self,
+        pool: &PgPool,
+        id: i64,
+    ) -> Result<CabecalhoContraCheque> {
+        Ok(sqlx::query_as!(
+            CabecalhoContraCheque,
+            r#"
+            SELECT org.nome AS org_nome, serv.nome AS serv_nome,
+                serv.matricula AS serv_matricula, serv.cpf AS serv_cpf,
+                serv.data_admissao AS serv_data_admissao,
+                cargo.nome AS "cargo_nome?", setor.nome AS "setor_nome?",
+                dep.nome AS "departamento_nome?", vinc.nome AS "vinculo_nome?"
+            FROM cadastro_folha f
+            INNER JOIN cadastro_servidor serv ON serv.id = f.servidor_id
+            INNER JOIN cadastro_orgao org ON org.id = f.orgao_id
+            LEFT JOIN cadastro_cargo cargo ON cargo.id = f.cargo_id
+            LEFT JOIN cadastro_setor setor ON setor.id = f.setor_id
+            LEFT JOIN cadastro_departamento dep ON dep.id = f.departamento_id
+            LEFT JOIN cadastro_tipovinculo vinc ON vinc.id = f.vinculo_id
+            WHERE f.id = $1
+            "#,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Ids das folhas do órgão na competência, de todos os tipos ou só do tipo pedido, em
+    /// ordem alfabética de servidor e, para cada um, na ordem de `ORDEM_TIPOS_FOLHA`
+    pub async fn ids_para_lote(&self, pool: &PgPool, filtro: &GerarLoteContraCheque) -> Result<Vec<i64>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT f.id
+            FROM cadastro_folha f
+            INNER JOIN cadastro_servidor serv ON serv.id = f.servidor_id
+            WHERE f.orgao_id = $1 AND f.ano = $2 AND f.mes = $3
+                AND ($4::int IS NULL OR f.setor_id = $4)
+                AND ($5::int IS NULL OR f.departamento_id = $5)
+                AND ($7::smallint IS NULL OR f.tipo_folha = $7)
+            ORDER BY serv.nome, f.servidor_id, array_position($6, f.tipo_folha), f.sequencia, f.id
+            "#,
+            filtro.orgao_id,
+            filtro.ano,
+            filtro.mes,
+            filtro.setor_id,
+            filtro.departamento_id,
+            &ORDEM_TIPOS_FOLHA[..],
+            filtro.tipo_folha
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Total do ano por servidor e rubrica nas folhas do órgão (opcionalmente de um só
+    /// servidor), agrupado por servidor em ordem alfabética
+    pub async fn valores_informe(
+        &self,
+        pool: &PgPool,
+        orgao_id: i32,
+        ano: i32,
+        servidor_id: Option<i32>,
+    ) -> Result<Vec<ValorRubricaInforme>> {
+        Ok(sqlx::query_as!(
+            ValorRubricaInforme,
+            r#"
+            SELECT f.servidor_id, serv.nome, serv.cpf, serv.matricula, r.tipo, r.incide_irrf,
+                r.codigo_sistema, r.pensao_alimenticia, r.informe_rendimentos,
+                SUM(i.valor) AS "valor!"
+            FROM cadastro_folha f
+            INNER JOIN cadastro_servidor serv ON serv.id = f.servidor_id
+            INNER JOIN cadastro_folha_item i ON i.folha_id = f.id
+            INNER JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE f.orgao_id = $1 AND f.ano = $2 AND ($3::int IS NULL OR f.servidor_id = $3)
+            GROUP BY f.servidor_id, serv.nome, serv.cpf, serv.matricula, r.tipo, r.incide_irrf,
+                r.codigo_sistema, r.pensao_alimenticia, r.informe_rendimentos
+            ORDER BY serv.nome, f.servidor_id
+            "#,
+            orgao_id,
+            ano,
+            servidor_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Servidores ativos do órgão admitidos até o fim do mês, cada um com o salário e a
+    /// lotação da folha mensal mais recente do ano até o mês
+    pub async fn bases_decimo_terceiro(
+        &self,
+        pool: &PgPool,
+        orgao_id: i32,
+        ano: i32,
+        mes: i32,
+    ) -> Result<Vec<BaseDecimoTerceiro>> {
+        Ok(sqlx::query_as!(
+            BaseDecimoTerceiro,
+            r#"
+            SELECT s.id AS servidor_id, s.nome, s.data_admissao, s.data_desligamento,
+                f.salario AS "salario?", f.cargo_id AS "cargo_id?", f.setor_id AS "setor_id?",
+                f.departamento_id AS "departamento_id?", f.vinculo_id AS "vinculo_id?",
+                f.dependentes_irrf AS "dependentes_irrf?"
+            FROM cadastro_servidor s
+            LEFT JOIN LATERAL (
+                SELECT salario, cargo_id, setor_id, departamento_id, vinculo_id, dependentes_irrf
+                FROM cadastro_folha
+                WHERE servidor_id = s.id AND orgao_id = $1 AND ano = $2 AND mes <= $3
+                    AND tipo_folha = 0
+                ORDER BY mes DESC, id DESC
+                LIMIT 1
+            ) f ON true
+            WHERE s.orgao_id = $1 AND s.is_active AND s.data_desligamento IS NULL
+                AND (s.data_admissao IS NULL
+                    OR s.data_admissao < make_date($2, $3, 1) + INTERVAL '1 month')
+            ORDER BY s.nome, s.id
+            "#,
+            orgao_id,
+            ano,
+            mes
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Se a parcela do 13º do ano já foi calculada para algum servidor do órgão
+    pub async fn existe_decimo_terceiro(
+        &self,
+        pool: &PgPool,
+        orgao_id: i32,
+        ano: i32,
+        parcela: i16,
+    ) -> Result<bool> {
+        Ok(sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM cadastro_folha WHERE orgao_id = $1 AND ano = $2 AND tipo_folha = $3
+            ) AS "existe!""#,
+            orgao_id,
+            ano,
+            parcela
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Adiantamento do 13º pago no ano a cada servidor do órgão
+    pub async fn adiantamentos_decimo_terceiro(
+        &self,
+        pool: &PgPool,
+        orgao_id: i32,
+        ano: i32,
+    ) -> Result<Vec<(i32, BigDecimal)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT f.servidor_id, SUM(i.valor) AS "valor!"
+            FROM cadastro_folha f
+            INNER JOIN cadastro_folha_item i ON i.folha_id = f.id
+            INNER JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE f.orgao_id = $1 AND f.ano = $2 AND f.tipo_folha = $3 AND r.codigo_sistema = $4
+            GROUP BY f.servidor_id
+            "#,
+            orgao_id,
+            ano,
+            DECIMO_TERCEIRO_ADIANTAMENTO,
+            SISTEMA_ADIANTAMENTO_13
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(|row| (row.servidor_id, row.valor)).collect())
+    }
+
+    /// Grava a folha de parcela do 13º ou de rescisão do servidor (uma de cada tipo por
+    /// ano) e devolve o id; no recálculo a folha existente recebe o salário e a lotação atuais
+    /// e continua na competência em que foi lançada. None quando essa competência está fechada
+    pub async fn gravar_por_tipo(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        input: &CreateFolha,
+    ) -> Result<Option<i64>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            INSERT INTO cadastro_folha(
+                orgao_id, ano, mes, servidor_id, salario, base_fgts, base_inss, base_irrf, ded_irrf,
+                cargo_id, setor_id, departamento_id, vinculo_id, dependentes_irrf, tipo_folha)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            ON CONFLICT (orgao_id, servidor_id, ano, tipo_folha) WHERE tipo_folha IN (1, 2, 3)
+            DO UPDATE SET
+                salario = EXCLUDED.salario,
+                cargo_id = EXCLUDED.cargo_id,
+                setor_id = EXCLUDED.setor_id,
+                departamento_id = EXCLUDED.departamento_id,
+                vinculo_id = EXCLUDED.vinculo_id,
+                dependentes_irrf = EXCLUDED.dependentes_irrf
+            WHERE NOT EXISTS (
+                SELECT 1 FROM cadastro_competencia c
+                WHERE c.orgao_id = cadastro_folha.orgao_id AND c.ano = cadastro_folha.ano
+                    AND c.mes = cadastro_folha.mes AND c.situacao = $16
+            )
+            RETURNING id
+            "#,
+            input.orgao_id,
+            input.ano,
+            input.mes,
+            input.servidor_id,
+            input.salario,
+            input.base_fgts,
+            input.base_inss,
+            input.base_irrf,
+            input.ded_irrf,
+            input.cargo_id,
+            input.setor_id,
+            input.departamento_id,
+            input.vinculo_id,
+            input.dependentes_irrf,
+            input.tipo_folha,
+            COMPETENCIA_FECHADA
+        )
+        .fetch_optional(&mut **tx)
+        .await?)
+    }
+
+    /// Folhas mensais da competência de origem, com a situação do servidor e se ele já tem
+    /// folha mensal no destino
+    pub async fn origem_geracao(&self, pool: &PgPool, input: &GerarFolha) -> Result<Vec<FolhaOrigem>> {
+        Ok(sqlx::query_as!(
+            FolhaOrigem,
+            r#"
+            SELECT f.id AS folha_id, f.servidor_id, s.nome AS servidor_nome, s.is_active AS servidor_ativo,
+                s.data_desligamento, f.salario, f.cargo_id, f.setor_id, f.departamento_id, f.vinculo_id,
+                f.dependentes_irrf,
+                EXISTS(
+                    SELECT 1 FROM cadastro_folha d
+                    WHERE d.orgao_id = f.orgao_id AND d.servidor_id = f.servidor_id
+                        AND d.ano = $4 AND d.mes = $5 AND d.tipo_folha = $6
+                ) AS "gerada!"
+            FROM cadastro_folha f
+            INNER JOIN cadastro_servidor s ON s.id = f.servidor_id
+            WHERE f.orgao_id = $1 AND f.ano = $2 AND f.mes = $3 AND f.tipo_folha = $6
+            ORDER BY s.nome, f.servidor_id
+            "#,
+            input.orgao_id,
+            input.ano_origem,
+            input.mes_origem,
+            input.ano,
+            input.mes,
+            FOLHA_MENSAL
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Grava a folha na transação da geração e devolve o id
+    pub async fn gerar(&self, tx: &mut Transaction<'_, Postgres>, input: &CreateFolha) -> Result<i64> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            INSERT INTO cadastro_folha(
+                orgao_id, ano, mes, servidor_id, salario, base_fgts, base_inss, base_irrf, ded_irrf,
+                cargo_id, setor_id, departamento_id, vinculo_id, dependentes_irrf, tipo_folha)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            RETURNING id
+            "#,
+            input.orgao_id,
+            input.ano,
+            input.mes,
+            input.servidor_id,
+            input.salario,
+            input.base_fgts,
+            input.base_inss,
+            input.base_irrf,
+            input.ded_irrf,
+            input.cargo_id,
+            input.setor_id,
+            input.departamento_id,
+            input.vinculo_id,
+            input.dependentes_irrf,
+            input.tipo_folha
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    /// Se o servidor já tem folha do tipo na competência
+    pub async fn existe_na_competencia(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        orgao_id: i32,
+        servidor_id: i32,
+        (ano, mes): (i32, i32),
+        tipo: i16,
+    ) -> Result<bool> {
+        Ok(sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM cadastro_folha
+                WHERE orgao_id = $1 AND servidor_id = $2 AND ano = $3 AND mes = $4 AND tipo_folha = $5
+            ) AS "existe!""#,
+            orgao_id,
+            servidor_id,
+            ano,
+            mes,
+            tipo
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    /// Folhas do servidor na competência calculadas antes desta (ver `FOLHAS_DA_COMPETENCIA`)
+    pub async fn anteriores(&self, tx: &mut Transaction<'_, Postgres>, folha: &Folha) -> Result<Vec<i64>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT id FROM cadastro_folha
+            WHERE orgao_id = $1 AND servidor_id = $2 AND ano = $3 AND mes = $4
+                AND tipo_folha = ANY($5)
+                AND (array_position($5, tipo_folha), sequencia)
+                    < (array_position($5, $6::smallint), $7::smallint)
+            ORDER BY array_position($5, tipo_folha), sequencia
+            "#,
+            folha.orgao_id,
+            folha.servidor_id,
+            folha.ano,
+            folha.mes,
+            &FOLHAS_DA_COMPETENCIA[..],
+            folha.tipo_folha,
+            folha.sequencia
+        )
+        .fetch_all(&mut **tx)
+        .await?)
+    }
+
+    /// Bases gravadas e INSS e IRRF retidos nas folhas, lidos pela transação do cálculo
+    pub async fn acumulado(&self, tx: &mut Transaction<'_, Postgres>, ids: &[i64]) -> Result<AcumuladoCompetencia> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                COALESCE(SUM(f.base_inss), 0) AS "base_inss!",
+                COALESCE(SUM(f.base_irrf), 0) AS "base_irrf!",
+                COALESCE(SUM(f.ded_irrf), 0) AS "ded_irrf!",
+                COALESCE((SELECT SUM(i.valor) FROM cadastro_folha_item i
+                    INNER JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+                    WHERE i.folha_id = ANY($1) AND r.codigo_sistema = $2), 0) AS "inss!",
+                COALESCE((SELECT SUM(i.valor) FROM cadastro_folha_item i
+                    INNER JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+                    WHERE i.folha_id = ANY($1) AND r.codigo_sistema = $3), 0) AS "irrf!"
+            FROM cadastro_folha f
+            WHERE f.id = ANY($1)
+            "#,
+            ids,
+            SISTEMA_INSS,
+            SISTEMA_IRRF
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(AcumuladoCompetencia {
+            base_inss: row.base_inss,
+            inss: row.inss,
+            base_irrf: row.base_irrf,
+            ded_irrf: row.ded_irrf,
+            irrf: row.irrf,
+        })
+    }
+
+    /// Folha mensal mais recente do servidor, de onde vêm salário e lotação das folhas
+    /// fora do mês
+    pub async fn ultima_mensal(&self, pool: &PgPool, servidor_id: i32) -> Result<Option<Folha>> {
+        Ok(sqlx::query_as!(
+            Folha,
+            r#"
+            SELECT f.*, NULL AS "serv_nome?", NULL AS "org_nome?"
+            FROM cadastro_folha f
+            WHERE f.servidor_id = $1 AND f.tipo_folha = $2
+            ORDER BY f.ano DESC, f.mes DESC, f.id DESC
+            LIMIT 1
+            "#,
+            servidor_id,
+            FOLHA_MENSAL
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Primeira competência (ano, mês) com folha mensal do servidor a partir da informada
+    pub async fn mensal_a_partir(
+        &self,
+        pool: &PgPool,
+        servidor_id: i32,
+        ano: i32,
+        mes: i32,
+    ) -> Result<Option<(i32, i32)>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT ano, mes FROM cadastro_folha
+            WHERE servidor_id = $1 AND tipo_folha = $4 AND (ano, mes) >= ($2, $3)
+            ORDER BY ano, mes
+            LIMIT 1
+            "#,
+            servidor_id,
+            ano,
+            mes,
+            FOLHA_MENSAL
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.map(|row| (row.ano, row.mes)))
+    }
+
+    /// Adiantamento do 13º pago ao servidor no ano e se a parcela final já saiu
+    pub async fn decimo_terceiro_pago(
+        &self,
+        pool: &PgPool,
+        servidor_id: i32,
+        ano: i32,
+    ) -> Result<(BigDecimal, bool)> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                COALESCE(SUM(i.valor) FILTER (
+                    WHERE f.tipo_folha = $3 AND r.codigo_sistema = $5), 0) AS "adiantamento!",
+                COALESCE(BOOL_OR(f.tipo_folha = $4), false) AS "final!"
+            FROM cadastro_folha f
+            LEFT JOIN cadastro_folha_item i ON i.folha_id = f.id
+            LEFT JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE f.servidor_id = $1 AND f.ano = $2 AND f.tipo_folha IN ($3, $4)
+            "#,
+            servidor_id,
+            ano,
+            DECIMO_TERCEIRO_ADIANTAMENTO,
+            DECIMO_TERCEIRO_FINAL,
+            SISTEMA_ADIANTAMENTO_13
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok((row.adiantamento, row.r#final))
+    }
+
+    /// Remove as folhas da parcela do 13º de servidores que saíram do cálculo; as de
+    /// competência fechada ficam
+    pub async fn remover_decimo_terceiro(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        orgao_id: i32,
+        ano: i32,
+        parcela: i16,
+        manter: &[i32],
+    ) -> Result<u64> {
+        let removidas = sqlx::query!(
+            r#"DELETE FROM cadastro_folha f
+            WHERE f.orgao_id = $1 AND f.ano = $2 AND f.tipo_folha = $3 AND NOT (f.servidor_id = ANY($4))
+                AND NOT EXISTS (
+                    SELECT 1 FROM cadastro_competencia c
+                    WHERE c.orgao_id = f.orgao_id AND c.ano = f.ano AND c.mes = f.mes
+                        AND c.situacao = $5
+                )"#,
+            orgao_id,
+            ano,
+            parcela,
+            manter,
+            COMPETENCIA_FECHADA
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(removidas.rows_affected())
+    }
+}
+
+#[async_trait]
+impl Repository<Folha, i64> for FolhaRepository {
+    type CreateInput = CreateFolha;
+    type UpdateInput = UpdateFolha;
+
+    fn table_name(&self) -> &str {
+        "cadastro_folha f"
+    }
+
+    fn id_column(&self) -> &str {
+        "f.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "f.ano DESC, f.mes ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("f.ano", "="),
+            ("f.mes", "="),
+            ("serv.nome", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "f.id, f.orgao_id, f.ano, f.mes, 
+        f.servidor_id, f.salario, f.base_fgts, f.base_inss,
+        f.base_irrf, f.ded_irrf, f.cargo_id, f.setor_id,
+        f.departamento_id, f.vinculo_id, f.dependentes_irrf, f.tipo_folha, f.sequencia,
+        serv.nome as serv_nome, org.nome as org_nome"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_folha f
+        INNER JOIN cadastro_servidor serv ON serv.id = f.servidor_id
+        INNER JOIN cadastro_orgao org ON org.id = f.orgao_id
+        "
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Folha> {
+        let mut tx = pool.begin().await?;
+        let folha = self.criar(&mut tx, input).await?;
+        tx.commit().await?;
+        Ok(folha)
+    }
+
+    async fn update(&self, pool: &PgPool, id: i64, input: Self::UpdateInput) -> Result<Folha> {
+        let mut tx = pool.begin().await?;
+        let folha = self.alterar(&mut tx, id, input).await?;
+        tx.commit().await?;
+        Ok(folha)
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i64) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        self.excluir(&mut tx, id).await?;
+        Ok(tx.commit().await?)
+    }
 }
 
 
 pub struct UfRepository;
 
+impl UfRepository {
+    /// Verifica se o código IBGE já pertence a outra UF (ignora a própria na alteração)
+    pub async fn codigo_ibge_em_uso(&self, pool: &PgPool, codigo: i32, ignorar_id: Option<i32>) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM cadastro_uf WHERE codigo_ibge = $1 AND ($2::int IS NULL OR id <> $2)
+            ) AS "existe!""#,
+            codigo,
+            ignorar_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+
+    /// Grava as UFs da planilha do IBGE pelo código. UFs cadastradas à mão, ainda sem
+    /// código, são reaproveitadas pela sigla. Devolve o id de cada código e quantas foram inseridas
+    pub async fn upsert_ibge(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        ufs: &[UfIbge],
+    ) -> Result<(HashMap<i32, i32>, i64)> {
+        let codigos: Vec<i32> = ufs.iter().map(|u| u.codigo).collect();
+        let siglas: Vec<String> = ufs.iter().map(|u| u.sigla.to_string()).collect();
+        let nomes: Vec<String> = ufs.iter().map(|u| u.nome.clone()).collect();
+
+        sqlx::query!(
+            r#"
+            UPDATE cadastro_uf u SET codigo_ibge = d.codigo
+            FROM UNNEST($1::int[], $2::text[]) AS d(codigo, sigla)
+            WHERE u.codigo_ibge IS NULL
+              AND UPPER(u.sigla) = d.sigla
+              AND NOT EXISTS (SELECT 1 FROM cadastro_uf x WHERE x.codigo_ibge = d.codigo)"#,
+            &codigos,
+            &siglas
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        let linhas = sqlx::query!(
+            r#"
+            INSERT INTO cadastro_uf(codigo_ibge, sigla, nome)
+            SELECT * FROM UNNEST($1::int[], $2::text[], $3::text[])
+            ON CONFLICT (codigo_ibge) DO UPDATE SET sigla = EXCLUDED.sigla, nome = EXCLUDED.nome
+            RETURNING id, codigo_ibge AS "codigo_ibge!", (xmax = 0) AS "inserido!""#,
+            &codigos,
+            &siglas,
+            &nomes
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        let inseridas = linhas.iter().filter(|l| l.inserido).count() as i64;
+        Ok((linhas.into_iter().map(|l| (l.codigo_ibge, l.id)).collect(), inseridas))
+    }
+}
+
 #[async_trait]
 impl Repository<Uf, i32> for UfRepository {
     type CreateInput = CreateUf;
@@ -157,11 +841,12 @@ impl Repository<Uf, i32> for UfRepository {
         &[
             ("u.sigla", "ILIKE"),
             ("u.nome", "ILIKE"),
+            ("u.codigo_ibge", "="),
         ]
     }
 
     fn select_clause(&self) -> &str {
-        "u.id, u.sigla, u.nome"
+        "u.id, u.sigla, u.nome, u.codigo_ibge"
     }
 
     fn from_clause(&self) -> &str {
@@ -171,10 +856,11 @@ impl Repository<Uf, i32> for UfRepository {
     async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Uf> {
         Ok(sqlx::query_as!(
             Uf,
-            r#"INSERT INTO cadastro_uf(sigla, nome)
-            VALUES ($1, $2) RETURNING *"#,
+            r#"INSERT INTO cadastro_uf(sigla, nome, codigo_ibge)
+            VALUES ($1, $2, $3) RETURNING *"#,
             input.sigla,
-            input.nome
+            input.nome,
+            input.codigo_ibge
         )
         .fetch_one(pool)
         .await?)
@@ -187,11 +873,13 @@ impl Repository<Uf, i32> for UfRepository {
             UPDATE cadastro_uf
             SET
                 sigla = COALESCE($1, sigla),
-                nome = COALESCE($2, nome)
-            WHERE id = $3
+                nome = COALESCE($2, nome),
+                codigo_ibge = COALESCE($3, codigo_ibge)
+            WHERE id = $4
             RETURNING *"#,
             input.sigla,
             input.nome,
+            input.codigo_ibge,
             id
         )
         .fetch_one(pool)
@@ -207,70 +895,500 @@ impl Repository<Uf, i32> for UfRepository {
 }
 
 
-pub struct MunicipioRepository;
+pub struct CargoRepository;
+
+impl CargoRepository {
+    /// Verifica se alguma folha referencia o registro (bloqueia a exclusão)
+    pub async fn em_uso(&self, pool: &PgPool, id: i32) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM cadastro_folha WHERE cargo_id = $1) AS "existe!""#,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+}
 
 #[async_trait]
-impl Repository<Municipio, i32> for MunicipioRepository {
-    type CreateInput = CreateMunicipio;
-    type UpdateInput = UpdateMunicipio;
+impl Repository<Cargo, i32> for CargoRepository {
+    type CreateInput = CreateCargo;
+    type UpdateInput = UpdateCargo;
 
     fn table_name(&self) -> &str {
-        "cadastro_municipio m"
+        "cadastro_cargo c"
     }
 
     fn id_column(&self) -> &str {
-        "m.id"
+        "c.id"
     }
 
     fn order_by_column(&self) -> &str {
-        "u.nome ASC, m.nome ASC"
+        "c.nome ASC"
     }
 
     fn searchable_fields(&self) -> &[(&str, &str)] {
         &[
-            ("m.nome", "ILIKE"),
-            ("u.nome", "ILIKE"),
+            ("c.nome", "ILIKE"),
+            ("c.cbo", "ILIKE"),
         ]
     }
 
     fn select_clause(&self) -> &str {
-        "m.id, m.uf_id, m.nome, u.nome AS uf_nome"
+        "c.id, c.nome, c.cbo"
     }
 
     fn from_clause(&self) -> &str {
-        "cadastro_municipio m
-        INNER JOIN cadastro_uf u ON u.id = m.uf_id
-        "
+        "cadastro_cargo c"
     }
 
-    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Municipio> {
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Cargo> {
         Ok(sqlx::query_as!(
-            Municipio,
-            r#"INSERT INTO cadastro_municipio(uf_id, nome)
-            VALUES ($1, $2) RETURNING *, NULL as "uf_nome?" "#,
-            input.uf_id,
-            input.nome
+            Cargo,
+            r#"INSERT INTO cadastro_cargo(nome, cbo)
+            VALUES ($1, $2) RETURNING id, nome, cbo"#,
+            input.nome,
+            input.cbo,
         )
         .fetch_one(pool)
         .await?)
     }
 
-    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<Municipio> {
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<Cargo> {
         Ok(sqlx::query_as!(
-            Municipio,
+            Cargo,
             r#"
-            UPDATE cadastro_municipio
+            UPDATE cadastro_cargo
+            SET
+                nome = $1,
+                cbo = $2
+            WHERE id = $3
+            RETURNING id, nome, cbo"#,
+            input.nome,
+            input.cbo,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_cargo WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+
+pub struct SetorRepository;
+
+impl SetorRepository {
+    /// Verifica se alguma folha referencia o registro (bloqueia a exclusão)
+    pub async fn em_uso(&self, pool: &PgPool, id: i32) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM cadastro_folha WHERE setor_id = $1) AS "existe!""#,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+}
+
+#[async_trait]
+impl Repository<Setor, i32> for SetorRepository {
+    type CreateInput = CreateSetor;
+    type UpdateInput = UpdateSetor;
+
+    fn table_name(&self) -> &str {
+        "cadastro_setor st"
+    }
+
+    fn id_column(&self) -> &str {
+        "st.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "st.nome ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("st.nome", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "st.id, st.nome"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_setor st"
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Setor> {
+        Ok(sqlx::query_as!(
+            Setor,
+            r#"INSERT INTO cadastro_setor(nome)
+            VALUES ($1) RETURNING id, nome"#,
+            input.nome,
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<Setor> {
+        Ok(sqlx::query_as!(
+            Setor,
+            r#"
+            UPDATE cadastro_setor
+            SET
+                nome = $1
+            WHERE id = $2
+            RETURNING id, nome"#,
+            input.nome,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_setor WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+
+pub struct DepartamentoRepository;
+
+impl DepartamentoRepository {
+    /// Verifica se alguma folha referencia o registro (bloqueia a exclusão)
+    pub async fn em_uso(&self, pool: &PgPool, id: i32) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM cadastro_folha WHERE departamento_id = $1) AS "existe!""#,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+}
+
+#[async_trait]
+impl Repository<Departamento, i32> for DepartamentoRepository {
+    type CreateInput = CreateDepartamento;
+    type UpdateInput = UpdateDepartamento;
+
+    fn table_name(&self) -> &str {
+        "cadastro_departamento d"
+    }
+
+    fn id_column(&self) -> &str {
+        "d.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "d.nome ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("d.nome", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "d.id, d.nome"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_departamento d"
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Departamento> {
+        Ok(sqlx::query_as!(
+            Departamento,
+            r#"INSERT INTO cadastro_departamento(nome)
+            VALUES ($1) RETURNING id, nome"#,
+            input.nome,
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<Departamento> {
+        Ok(sqlx::query_as!(
+            Departamento,
+            r#"
+            UPDATE cadastro_departamento
             SET
-                nome = COALESCE($1, nome)
+                nome = $1
             WHERE id = $2
-            RETURNING *, NULL as "uf_nome?" "#,
+            RETURNING id, nome"#,
+            input.nome,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_departamento WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+
+pub struct TipoVinculoRepository;
+
+impl TipoVinculoRepository {
+    /// Verifica se alguma folha referencia o registro (bloqueia a exclusão)
+    pub async fn em_uso(&self, pool: &PgPool, id: i32) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM cadastro_folha WHERE vinculo_id = $1) AS "existe!""#,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+}
+
+#[async_trait]
+impl Repository<TipoVinculo, i32> for TipoVinculoRepository {
+    type CreateInput = CreateTipoVinculo;
+    type UpdateInput = UpdateTipoVinculo;
+
+    fn table_name(&self) -> &str {
+        "cadastro_tipovinculo v"
+    }
+
+    fn id_column(&self) -> &str {
+        "v.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "v.nome ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("v.nome", "ILIKE"),
+            ("v.categoria_esocial", "="),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "v.id, v.nome, v.categoria_esocial, v.regime_previdencia"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_tipovinculo v"
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<TipoVinculo> {
+        Ok(sqlx::query_as!(
+            TipoVinculo,
+            r#"INSERT INTO cadastro_tipovinculo(nome, categoria_esocial, regime_previdencia)
+            VALUES ($1, $2, COALESCE($3, 1))
+            RETURNING id, nome, categoria_esocial, regime_previdencia"#,
+            input.nome,
+            input.categoria_esocial,
+            input.regime_previdencia,
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<TipoVinculo> {
+        Ok(sqlx::query_as!(
+            TipoVinculo,
+            r#"
+            UPDATE cadastro_tipovinculo
+            SET
+                nome = $1,
+                categoria_esocial = $2,
+                regime_previdencia = COALESCE($3, regime_previdencia)
+            WHERE id = $4
+            RETURNING id, nome, categoria_esocial, regime_previdencia"#,
             input.nome,
+            input.categoria_esocial,
+            input.regime_previdencia,
             id
         )
         .fetch_one(pool)
         .await?)
     }
 
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_tipovinculo WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+
+pub struct MunicipioRepository;
+
+impl MunicipioRepository {
+    /// Verifica se algum servidor ou órgão tem endereço no município (bloqueia a exclusão)
+    pub async fn em_uso(&self, pool: &PgPool, id: i32) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT (
+                EXISTS(SELECT 1 FROM cadastro_servidor WHERE municipio_id = $1)
+                OR EXISTS(SELECT 1 FROM cadastro_orgao WHERE municipio_id = $1)
+            ) AS "existe!""#,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+
+    /// Verifica se o código IBGE já pertence a outro município (ignora o próprio na alteração)
+    pub async fn codigo_ibge_em_uso(&self, pool: &PgPool, codigo: i32, ignorar_id: Option<i32>) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM cadastro_municipio WHERE codigo_ibge = $1 AND ($2::int IS NULL OR id <> $2)
+            ) AS "existe!""#,
+            codigo,
+            ignorar_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+
+    /// Grava os municípios da planilha do IBGE pelo código. Municípios cadastrados à mão,
+    /// ainda sem código, são reaproveitados pela UF e pelo nome. Devolve (inseridos, atualizados)
+    pub async fn upsert_ibge(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        municipios: &[MunicipioIbge],
+        uf_ids: &HashMap<i32, i32>,
+    ) -> Result<(i64, i64)> {
+        let mut codigos = Vec::with_capacity(municipios.len());
+        let mut ufs = Vec::with_capacity(municipios.len());
+        let mut nomes = Vec::with_capacity(municipios.len());
+        for municipio in municipios {
+            let Some(uf_id) = uf_ids.get(&municipio.codigo_uf) else {
+                continue;
+            };
+            codigos.push(municipio.codigo);
+            ufs.push(*uf_id);
+            nomes.push(municipio.nome.clone());
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE cadastro_municipio m SET codigo_ibge = d.codigo
+            FROM UNNEST($1::int[], $2::int[], $3::text[]) AS d(codigo, uf_id, nome)
+            WHERE m.codigo_ibge IS NULL
+              AND m.uf_id = d.uf_id
+              AND LOWER(m.nome) = LOWER(d.nome)
+              AND NOT EXISTS (SELECT 1 FROM cadastro_municipio x WHERE x.codigo_ibge = d.codigo)"#,
+            &codigos,
+            &ufs,
+            &nomes
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        let inseridos = sqlx::query_scalar!(
+            r#"
+            INSERT INTO cadastro_municipio(codigo_ibge, uf_id, nome)
+            SELECT * FROM UNNEST($1::int[], $2::int[], $3::text[])
+            ON CONFLICT (codigo_ibge) DO UPDATE SET uf_id = EXCLUDED.uf_id, nome = EXCLUDED.nome
+            RETURNING (xmax = 0) AS "inserido!""#,
+            &codigos,
+            &ufs,
+            &nomes
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        let total = inseridos.len() as i64;
+        let novos = inseridos.into_iter().filter(|inserido| *inserido).count() as i64;
+        Ok((novos, total - novos))
+    }
+}
+
+#[async_trait]
+impl Repository<Municipio, i32> for MunicipioRepository {
+    type CreateInput = CreateMunicipio;
+    type UpdateInput = UpdateMunicipio;
+
+    fn table_name(&self) -> &str {
+        "cadastro_municipio m"
+    }
+
+    fn id_column(&self) -> &str {
+        "m.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "u.nome ASC, m.nome ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("m.nome", "ILIKE"),
+            ("u.nome", "ILIKE"),
+            ("m.codigo_ibge", "="),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "m.id, m.uf_id, m.nome, m.codigo_ibge, u.nome AS uf_nome"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_municipio m
+        INNER JOIN cadastro_uf u ON u.id = m.uf_id
+        "
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Municipio> {
+        let id = sqlx::query_scalar!(
+            r#"INSERT INTO cadastro_municipio(uf_id, nome, codigo_ibge)
+            VALUES ($1, $2, $3) RETURNING id"#,
+            input.uf_id,
+            input.nome,
+            input.codigo_ibge
+        )
+        .fetch_one(pool)
+        .await?;
+
+        self.get_by_id(pool, id).await
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<Municipio> {
+        sqlx::query!(
+            r#"
+            UPDATE cadastro_municipio
+            SET
+                uf_id = COALESCE($1, uf_id),
+                nome = COALESCE($2, nome),
+                codigo_ibge = COALESCE($3, codigo_ibge)
+            WHERE id = $4"#,
+            input.uf_id,
+            input.nome,
+            input.codigo_ibge,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        self.get_by_id(pool, id).await
+    }
+
     async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
         sqlx::query!("DELETE FROM cadastro_municipio WHERE id = $1", id as i32)
             .execute(pool)
@@ -279,3 +1397,3433 @@ impl Repository<Municipio, i32> for MunicipioRepository {
     }
 }
 
+
+
+pub struct OrgaoRepository;
+
+impl OrgaoRepository {
+    /// Verifica se o CNPJ já pertence a outro órgão (ignora o próprio na alteração)
+    pub async fn cnpj_em_uso(&self, pool: &PgPool, cnpj: &str, ignorar_id: Option<i32>) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM cadastro_orgao WHERE cnpj = $1 AND ($2::int IS NULL OR id <> $2)
+            ) AS "existe!""#,
+            cnpj,
+            ignorar_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+
+    /// Dados do cabeçalho dos documentos emitidos em nome do órgão
+    pub async fn timbre(&self, pool: &PgPool, id: i32) -> Result<Timbre> {
+        Ok(sqlx::query_as!(
+            Timbre,
+            r#"
+            SELECT o.nome, o.cnpj, o.cep, o.logradouro, o.numero, o.complemento, o.bairro,
+                m.nome AS "municipio_nome?", u.sigla AS "uf_sigla?", o.telefone, o.email, o.logo
+            FROM cadastro_orgao o
+            LEFT JOIN cadastro_municipio m ON m.id = o.municipio_id
+            LEFT JOIN cadastro_uf u ON u.id = m.uf_id
+            WHERE o.id = $1
+            "#,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    pub async fn logo(&self, pool: &PgPool, id: i32) -> Result<Option<Vec<u8>>> {
+        Ok(sqlx::query_scalar!("SELECT logo FROM cadastro_orgao WHERE id = $1", id)
+            .fetch_one(pool)
+            .await?)
+    }
+
+    /// Grava o logotipo; None remove
+    pub async fn salvar_logo(&self, pool: &PgPool, id: i32, logo: Option<&[u8]>) -> Result<()> {
+        let resultado = sqlx::query!("UPDATE cadastro_orgao SET logo = $1 WHERE id = $2", logo, id)
+            .execute(pool)
+            .await?;
+        if resultado.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound.into());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Repository<Orgao, i32> for OrgaoRepository {
+    type CreateInput = CreateOrgao;
+    type UpdateInput = UpdateOrgao;
+
+    fn table_name(&self) -> &str {
+        "cadastro_orgao o"
+    }
+
+    fn id_column(&self) -> &str {
+        "o.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "o.nome ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("o.nome", "ILIKE"),
+            ("o.cnpj", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "o.id, o.nome, o.cnpj, o.cep, o.logradouro, o.numero, o.complemento, o.bairro,
+        o.municipio_id, o.telefone, o.email, o.is_active, o.logo IS NOT NULL AS possui_logo,
+        o.esocial_ambiente, o.esocial_tabela_rubrica, o.esocial_lotacao,
+        o.cnab_banco, o.cnab_agencia, o.cnab_conta, o.cnab_convenio, o.cnab_nsa, o.margem_consignavel,
+        m.nome AS municipio_nome, u.sigla AS uf_sigla"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_orgao o
+        LEFT JOIN cadastro_municipio m ON m.id = o.municipio_id
+        LEFT JOIN cadastro_uf u ON u.id = m.uf_id
+        "
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Orgao> {
+        let id = sqlx::query_scalar!(
+            r#"INSERT INTO cadastro_orgao(nome, cnpj, cep, logradouro, numero, complemento, bairro,
+                municipio_id, telefone, email, is_active, esocial_ambiente, esocial_tabela_rubrica,
+                esocial_lotacao, cnab_banco, cnab_agencia, cnab_conta, cnab_convenio, margem_consignavel)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, COALESCE($12, 2), $13, $14,
+                $15, $16, $17, $18, $19)
+            RETURNING id"#,
+            input.nome,
+            input.cnpj,
+            input.cep,
+            input.logradouro,
+            input.numero,
+            input.complemento,
+            input.bairro,
+            input.municipio_id,
+            input.telefone,
+            input.email,
+            input.is_active,
+            input.esocial_ambiente,
+            input.esocial_tabela_rubrica,
+            input.esocial_lotacao,
+            input.cnab_banco,
+            input.cnab_agencia,
+            input.cnab_conta,
+            input.cnab_convenio,
+            input.margem_consignavel
+        )
+        .fetch_one(pool)
+        .await?;
+
+        self.get_by_id(pool, id).await
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<Orgao> {
+        sqlx::query!(
+            r#"
+            UPDATE cadastro_orgao
+            SET
+                nome = $1,
+                cnpj = $2,
+                cep = $3,
+                logradouro = $4,
+                numero = $5,
+                complemento = $6,
+                bairro = $7,
+                municipio_id = $8,
+                telefone = $9,
+                email = $10,
+                is_active = $11,
+                esocial_ambiente = COALESCE($12, esocial_ambiente),
+                esocial_tabela_rubrica = $13,
+                esocial_lotacao = $14,
+                cnab_banco = $15,
+                cnab_agencia = $16,
+                cnab_conta = $17,
+                cnab_convenio = $18,
+                margem_consignavel = $19
+            WHERE id = $20"#,
+            input.nome,
+            input.cnpj,
+            input.cep,
+            input.logradouro,
+            input.numero,
+            input.complemento,
+            input.bairro,
+            input.municipio_id,
+            input.telefone,
+            input.email,
+            input.is_active,
+            input.esocial_ambiente,
+            input.esocial_tabela_rubrica,
+            input.esocial_lotacao,
+            input.cnab_banco,
+            input.cnab_agencia,
+            input.cnab_conta,
+            input.cnab_convenio,
+            input.margem_consignavel,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        self.get_by_id(pool, id).await
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_orgao WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct ServidorRepository;
+
+impl ServidorRepository {
+    /// Grava (ou limpa, no estorno da rescisão) a data e o motivo do desligamento
+    pub async fn desligar(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        data: Option<NaiveDate>,
+        motivo: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE cadastro_servidor SET data_desligamento = $2, motivo_desligamento = $3 WHERE id = $1",
+            id,
+            data,
+            motivo
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Verifica se o CPF já pertence a outro servidor (ignora o próprio na alteração)
+    pub async fn cpf_em_uso(&self, pool: &PgPool, cpf: &str, ignorar_id: Option<i32>) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM cadastro_servidor WHERE cpf = $1 AND ($2::int IS NULL OR id <> $2)
+            ) AS "existe!""#,
+            cpf,
+            ignorar_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+
+    /// Id e nome do servidor ativo com o CPF (somente dígitos)
+    pub async fn get_ativo_by_cpf(&self, pool: &PgPool, cpf: &str) -> Result<Option<(i32, String)>> {
+        let row = sqlx::query!(
+            "SELECT id, nome FROM cadastro_servidor WHERE cpf = $1 AND is_active",
+            cpf
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.map(|row| (row.id, row.nome)))
+    }
+}
+
+pub struct AfastamentoRepository;
+
+impl AfastamentoRepository {
+    pub async fn get_by_servidor(&self, pool: &PgPool, servidor_id: i32) -> Result<Vec<Afastamento>> {
+        Ok(sqlx::query_as!(
+            Afastamento,
+            r#"
+            SELECT id, servidor_id, data_inicio, data_fim, motivo, reduz_avos
+            FROM cadastro_afastamento
+            WHERE servidor_id = $1
+            ORDER BY data_inicio DESC, id DESC
+            "#,
+            servidor_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Afastamentos que reduzem avos dos servidores do órgão e alcançam o ano
+    pub async fn reduzem_avos(&self, pool: &PgPool, orgao_id: i32, ano: i32) -> Result<Vec<Afastamento>> {
+        Ok(sqlx::query_as!(
+            Afastamento,
+            r#"
+            SELECT a.id, a.servidor_id, a.data_inicio, a.data_fim, a.motivo, a.reduz_avos
+            FROM cadastro_afastamento a
+            INNER JOIN cadastro_servidor s ON s.id = a.servidor_id
+            WHERE s.orgao_id = $1 AND a.reduz_avos
+                AND a.data_inicio <= make_date($2, 12, 31)
+                AND (a.data_fim IS NULL OR a.data_fim >= make_date($2, 1, 1))
+            ORDER BY a.servidor_id, a.data_inicio
+            "#,
+            orgao_id,
+            ano
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn create(
+        &self,
+        pool: &PgPool,
+        servidor_id: i32,
+        input: &CreateAfastamento,
+    ) -> Result<Afastamento> {
+        Ok(sqlx::query_as!(
+            Afastamento,
+            r#"INSERT INTO cadastro_afastamento(servidor_id, data_inicio, data_fim, motivo, reduz_avos)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, servidor_id, data_inicio, data_fim, motivo, reduz_avos"#,
+            servidor_id,
+            input.data_inicio,
+            input.data_fim,
+            input.motivo,
+            input.reduz_avos
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Exclui o afastamento e devolve o servidor dele
+    pub async fn delete(&self, pool: &PgPool, id: i32) -> Result<i32> {
+        Ok(sqlx::query_scalar!(
+            "DELETE FROM cadastro_afastamento WHERE id = $1 RETURNING servidor_id",
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+}
+
+pub struct AlteracaoSalarialRepository;
+
+impl AlteracaoSalarialRepository {
+    pub async fn get_by_servidor(&self, pool: &PgPool, servidor_id: i32) -> Result<Vec<AlteracaoSalarial>> {
+        Ok(sqlx::query_as!(
+            AlteracaoSalarial,
+            r#"
+            SELECT * FROM cadastro_alteracao_salarial
+            WHERE servidor_id = $1
+            ORDER BY ano DESC, mes DESC
+            "#,
+            servidor_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Alterações ainda não aplicadas dos servidores
+    pub async fn pendentes(&self, pool: &PgPool, servidores: &[i32]) -> Result<Vec<AlteracaoSalarial>> {
+        Ok(sqlx::query_as!(
+            AlteracaoSalarial,
+            r#"
+            SELECT * FROM cadastro_alteracao_salarial
+            WHERE servidor_id = ANY($1) AND folha_id IS NULL
+            ORDER BY servidor_id, ano, mes
+            "#,
+            servidores
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn create(
+        &self,
+        pool: &PgPool,
+        servidor_id: i32,
+        input: &CreateAlteracaoSalarial,
+        usuario: &str,
+    ) -> Result<AlteracaoSalarial> {
+        Ok(sqlx::query_as!(
+            AlteracaoSalarial,
+            r#"INSERT INTO cadastro_alteracao_salarial(servidor_id, ano, mes, salario, motivo, usuario)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *"#,
+            servidor_id,
+            input.ano,
+            input.mes,
+            input.salario,
+            input.motivo,
+            usuario
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Marca as alterações como aplicadas na folha gerada
+    pub async fn aplicar(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        ids: &[i32],
+        folha_id: i64,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE cadastro_alteracao_salarial SET folha_id = $2 WHERE id = ANY($1)",
+            ids,
+            folha_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Exclui a alteração ainda pendente e devolve o servidor dela
+    pub async fn delete_pendente(&self, pool: &PgPool, id: i32) -> Result<Option<i32>> {
+        Ok(sqlx::query_scalar!(
+            "DELETE FROM cadastro_alteracao_salarial WHERE id = $1 AND folha_id IS NULL RETURNING servidor_id",
+            id
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+}
+
+pub struct RescisaoRepository;
+
+impl RescisaoRepository {
+    pub async fn get_by_servidor(&self, pool: &PgPool, servidor_id: i32) -> Result<Option<Rescisao>> {
+        Ok(sqlx::query_as!(
+            Rescisao,
+            "SELECT * FROM cadastro_rescisao WHERE servidor_id = $1",
+            servidor_id
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn create(&self, tx: &mut Transaction<'_, Postgres>, rescisao: &Rescisao) -> Result<Rescisao> {
+        Ok(sqlx::query_as!(
+            Rescisao,
+            r#"
+            INSERT INTO cadastro_rescisao(
+                servidor_id, folha_id, data_desligamento, motivo_desligamento, dias_saldo_salario,
+                avos_decimo_terceiro, dias_ferias_vencidas, avos_ferias_proporcionais, usuario)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#,
+            rescisao.servidor_id,
+            rescisao.folha_id,
+            rescisao.data_desligamento,
+            rescisao.motivo_desligamento,
+            rescisao.dias_saldo_salario,
+            rescisao.avos_decimo_terceiro,
+            rescisao.dias_ferias_vencidas,
+            rescisao.avos_ferias_proporcionais,
+            rescisao.usuario
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    /// Exclui a folha da rescisão, que leva junto os itens e a própria rescisão
+    pub async fn estornar(&self, tx: &mut Transaction<'_, Postgres>, folha_id: i64) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_folha WHERE id = $1", folha_id)
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct PeriodoAquisitivoRepository;
+
+impl PeriodoAquisitivoRepository {
+    /// Grava os períodos aquisitivos apurados pela admissão; os que deixaram de existir
+    /// (admissão corrigida) saem se ainda não tiverem férias
+    pub async fn sincronizar(
+        &self,
+        pool: &PgPool,
+        servidor_id: i32,
+        periodos: &[(NaiveDate, NaiveDate)],
+    ) -> Result<()> {
+        let (inicios, fins): (Vec<NaiveDate>, Vec<NaiveDate>) = periodos.iter().copied().unzip();
+
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM cadastro_periodo_aquisitivo p
+            WHERE p.servidor_id = $1 AND NOT (p.data_inicio = ANY($2))
+                AND NOT EXISTS (SELECT 1 FROM cadastro_ferias f WHERE f.periodo_id = p.id)
+            "#,
+            servidor_id,
+            &inicios
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO cadastro_periodo_aquisitivo(servidor_id, data_inicio, data_fim)
+            SELECT $1, inicio, fim FROM UNNEST($2::date[], $3::date[]) AS p(inicio, fim)
+            ON CONFLICT (servidor_id, data_inicio) DO UPDATE SET data_fim = EXCLUDED.data_fim
+            "#,
+            servidor_id,
+            &inicios,
+            &fins
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_by_servidor(&self, pool: &PgPool, servidor_id: i32) -> Result<Vec<PeriodoAquisitivo>> {
+        Ok(sqlx::query_as!(
+            PeriodoAquisitivo,
+            r#"
+            SELECT p.id, p.servidor_id, p.data_inicio, p.data_fim, p.dias_direito,
+                (p.data_fim + INTERVAL '12 months')::date AS "fim_concessivo!",
+                COALESCE(SUM(f.dias), 0) AS "dias_gozo!",
+                COALESCE(SUM(f.dias_abono), 0) AS "dias_abono!",
+                COUNT(f.id) AS "fracoes!",
+                p.dias_direito - COALESCE(SUM(f.dias + f.dias_abono), 0) AS "saldo!"
+            FROM cadastro_periodo_aquisitivo p
+            LEFT JOIN cadastro_ferias f ON f.periodo_id = p.id AND f.situacao <> $2
+            WHERE p.servidor_id = $1
+            GROUP BY p.id
+            ORDER BY p.data_inicio DESC
+            "#,
+            servidor_id,
+            FERIAS_CANCELADA
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<PeriodoAquisitivo> {
+        Ok(sqlx::query_as!(
+            PeriodoAquisitivo,
+            r#"
+            SELECT p.id, p.servidor_id, p.data_inicio, p.data_fim, p.dias_direito,
+                (p.data_fim + INTERVAL '12 months')::date AS "fim_concessivo!",
+                COALESCE(SUM(f.dias), 0) AS "dias_gozo!",
+                COALESCE(SUM(f.dias_abono), 0) AS "dias_abono!",
+                COUNT(f.id) AS "fracoes!",
+                p.dias_direito - COALESCE(SUM(f.dias + f.dias_abono), 0) AS "saldo!"
+            FROM cadastro_periodo_aquisitivo p
+            LEFT JOIN cadastro_ferias f ON f.periodo_id = p.id AND f.situacao <> $2
+            WHERE p.id = $1
+            GROUP BY p.id
+            "#,
+            id,
+            FERIAS_CANCELADA
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+}
+
+pub struct FeriasRepository;
+
+impl FeriasRepository {
+    pub async fn get_by_servidor(&self, pool: &PgPool, servidor_id: i32) -> Result<Vec<Ferias>> {
+        Ok(sqlx::query_as!(
+            Ferias,
+            r#"
+            SELECT f.*, p.data_inicio AS "periodo_inicio?", p.data_fim AS "periodo_fim?"
+            FROM cadastro_ferias f
+            INNER JOIN cadastro_periodo_aquisitivo p ON p.id = f.periodo_id
+            WHERE f.servidor_id = $1
+            ORDER BY f.data_inicio DESC, f.id DESC
+            "#,
+            servidor_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<Ferias> {
+        Ok(sqlx::query_as!(
+            Ferias,
+            r#"
+            SELECT f.*, p.data_inicio AS "periodo_inicio?", p.data_fim AS "periodo_fim?"
+            FROM cadastro_ferias f
+            INNER JOIN cadastro_periodo_aquisitivo p ON p.id = f.periodo_id
+            WHERE f.id = $1
+            "#,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Dias de gozo e de abono de cada fração programada no período aquisitivo
+    pub async fn fracoes(&self, pool: &PgPool, periodo_id: i32) -> Result<Vec<(i32, i32)>> {
+        let rows = sqlx::query!(
+            r#"SELECT dias, dias_abono FROM cadastro_ferias
+            WHERE periodo_id = $1 AND situacao <> $2
+            ORDER BY data_inicio"#,
+            periodo_id,
+            FERIAS_CANCELADA
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(|row| (row.dias, row.dias_abono)).collect())
+    }
+
+    /// Se o servidor já tem férias que cruzam o intervalo
+    pub async fn sobreposta(
+        &self,
+        pool: &PgPool,
+        servidor_id: i32,
+        inicio: NaiveDate,
+        fim: NaiveDate,
+    ) -> Result<bool> {
+        Ok(sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM cadastro_ferias
+                WHERE servidor_id = $1 AND situacao <> $4 AND data_inicio <= $3 AND data_fim >= $2
+            ) AS "existe!""#,
+            servidor_id,
+            inicio,
+            fim,
+            FERIAS_CANCELADA
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    pub async fn create(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        servidor_id: i32,
+        input: &CreateFerias,
+        data_fim: NaiveDate,
+        usuario: &str,
+    ) -> Result<i32> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            INSERT INTO cadastro_ferias(
+                servidor_id, periodo_id, data_inicio, data_fim, dias, dias_abono, ano, mes, situacao, usuario)
+            VALUES ($1, $2, $3, $4, $5, $6,
+                EXTRACT(YEAR FROM $3::date)::int, EXTRACT(MONTH FROM $3::date)::int, $7, $8)
+            RETURNING id
+            "#,
+            servidor_id,
+            input.periodo_id,
+            input.data_inicio,
+            data_fim,
+            input.dias,
+            input.dias_abono.unwrap_or_default(),
+            FERIAS_PROGRAMADA,
+            usuario
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    /// Férias do servidor pagas na competência, fora as canceladas; lidas pela transação
+    /// do cálculo, que vê o cancelamento ainda não gravado
+    pub async fn da_competencia(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        servidor_id: i32,
+        ano: i32,
+        mes: i32,
+    ) -> Result<Vec<Ferias>> {
+        Ok(sqlx::query_as!(
+            Ferias,
+            r#"
+            SELECT f.*, p.data_inicio AS "periodo_inicio?", p.data_fim AS "periodo_fim?"
+            FROM cadastro_ferias f
+            INNER JOIN cadastro_periodo_aquisitivo p ON p.id = f.periodo_id
+            WHERE f.servidor_id = $1 AND f.ano = $2 AND f.mes = $3 AND f.situacao <> $4
+            ORDER BY f.data_inicio
+            "#,
+            servidor_id,
+            ano,
+            mes,
+            FERIAS_CANCELADA
+        )
+        .fetch_all(&mut **tx)
+        .await?)
+    }
+
+    /// Marca as férias como lançadas na folha, com os valores calculados
+    pub async fn registrar_calculo(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        folha_id: i64,
+        valores: &ValoresFerias,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE cadastro_ferias
+            SET situacao = $3, folha_id = $2, valor_ferias = $4, valor_terco = $5,
+                valor_abono = $6, valor_terco_abono = $7
+            WHERE id = $1
+            "#,
+            id,
+            folha_id,
+            FERIAS_CALCULADA,
+            valores.ferias,
+            valores.terco,
+            valores.abono,
+            valores.terco_abono
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Cancela as férias ainda não pagas que começariam depois da data
+    pub async fn cancelar_programadas_apos(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        servidor_id: i32,
+        data: NaiveDate,
+    ) -> Result<u64> {
+        let canceladas = sqlx::query!(
+            r#"UPDATE cadastro_ferias SET situacao = $4
+            WHERE servidor_id = $1 AND data_inicio > $2 AND situacao = $3"#,
+            servidor_id,
+            data,
+            FERIAS_PROGRAMADA,
+            FERIAS_CANCELADA
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(canceladas.rows_affected())
+    }
+
+    pub async fn cancelar(&self, tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE cadastro_ferias
+            SET situacao = $2, folha_id = NULL, valor_ferias = NULL, valor_terco = NULL,
+                valor_abono = NULL, valor_terco_abono = NULL
+            WHERE id = $1
+            "#,
+            id,
+            FERIAS_CANCELADA
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Repository<Servidor, i32> for ServidorRepository {
+    type CreateInput = CreateServidor;
+    type UpdateInput = UpdateServidor;
+
+    fn table_name(&self) -> &str {
+        "cadastro_servidor s"
+    }
+
+    fn id_column(&self) -> &str {
+        "s.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "s.nome ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("s.nome", "ILIKE"),
+            ("s.cpf", "ILIKE"),
+            ("s.matricula", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "s.id, s.nome, s.matricula, s.cpf, s.pis, s.data_nascimento, s.data_admissao,
+        s.cep, s.logradouro, s.numero, s.complemento, s.bairro, s.municipio_id,
+        s.banco, s.agencia, s.conta, s.tipo_conta, s.is_active,
+        s.orgao_id, s.cargo_id, s.vinculo_id, s.sexo, s.raca_cor, s.grau_instrucao,
+        s.data_desligamento, s.motivo_desligamento,
+        m.nome AS municipio_nome, org.nome AS org_nome, c.nome AS cargo_nome, v.nome AS vinculo_nome"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_servidor s
+        LEFT JOIN cadastro_municipio m ON m.id = s.municipio_id
+        LEFT JOIN cadastro_orgao org ON org.id = s.orgao_id
+        LEFT JOIN cadastro_cargo c ON c.id = s.cargo_id
+        LEFT JOIN cadastro_tipovinculo v ON v.id = s.vinculo_id
+        "
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Servidor> {
+        let id = sqlx::query_scalar!(
+            r#"INSERT INTO cadastro_servidor(nome, matricula, cpf, pis, data_nascimento, data_admissao,
+                cep, logradouro, numero, complemento, bairro, municipio_id,
+                banco, agencia, conta, tipo_conta, is_active,
+                orgao_id, cargo_id, vinculo_id, sexo, raca_cor, grau_instrucao)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17,
+                $18, $19, $20, $21, $22, $23)
+            RETURNING id"#,
+            input.nome,
+            input.matricula,
+            input.cpf,
+            input.pis,
+            input.data_nascimento,
+            input.data_admissao,
+            input.cep,
+            input.logradouro,
+            input.numero,
+            input.complemento,
+            input.bairro,
+            input.municipio_id,
+            input.banco,
+            input.agencia,
+            input.conta,
+            input.tipo_conta,
+            input.is_active,
+            input.orgao_id,
+            input.cargo_id,
+            input.vinculo_id,
+            input.sexo,
+            input.raca_cor,
+            input.grau_instrucao
+        )
+        .fetch_one(pool)
+        .await?;
+
+        self.get_by_id(pool, id).await
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<Servidor> {
+        sqlx::query!(
+            r#"
+            UPDATE cadastro_servidor
+            SET
+                nome = $1,
+                matricula = $2,
+                cpf = $3,
+                pis = $4,
+                data_nascimento = $5,
+                data_admissao = $6,
+                cep = $7,
+                logradouro = $8,
+                numero = $9,
+                complemento = $10,
+                bairro = $11,
+                municipio_id = $12,
+                banco = $13,
+                agencia = $14,
+                conta = $15,
+                tipo_conta = $16,
+                is_active = $17,
+                orgao_id = $18,
+                cargo_id = $19,
+                vinculo_id = $20,
+                sexo = $21,
+                raca_cor = $22,
+                grau_instrucao = $23
+            WHERE id = $24"#,
+            input.nome,
+            input.matricula,
+            input.cpf,
+            input.pis,
+            input.data_nascimento,
+            input.data_admissao,
+            input.cep,
+            input.logradouro,
+            input.numero,
+            input.complemento,
+            input.bairro,
+            input.municipio_id,
+            input.banco,
+            input.agencia,
+            input.conta,
+            input.tipo_conta,
+            input.is_active,
+            input.orgao_id,
+            input.cargo_id,
+            input.vinculo_id,
+            input.sexo,
+            input.raca_cor,
+            input.grau_instrucao,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        self.get_by_id(pool, id).await
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_servidor WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct RubricaRepository;
+
+impl RubricaRepository {
+    /// Rubrica usada pelo cálculo automático (ex.: INSS)
+    pub async fn get_by_codigo_sistema(&self, pool: &PgPool, codigo: &str) -> Result<Rubrica> {
+        Ok(sqlx::query_as!(
+            Rubrica,
+            "SELECT * FROM cadastro_rubrica WHERE codigo_sistema = $1",
+            codigo
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Se a rubrica tem itens em folha de competência fechada
+    pub async fn usada_em_competencia_fechada(&self, pool: &PgPool, id: i32) -> Result<bool> {
+        Ok(sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM cadastro_folha_item i
+                INNER JOIN cadastro_folha f ON f.id = i.folha_id
+                INNER JOIN cadastro_competencia c
+                    ON c.orgao_id = f.orgao_id AND c.ano = f.ano AND c.mes = f.mes
+                WHERE i.rubrica_id = $1 AND c.situacao = $2
+            ) AS "existe!""#,
+            id,
+            COMPETENCIA_FECHADA
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+}
+
+#[async_trait]
+impl Repository<Rubrica, i32> for RubricaRepository {
+    type CreateInput = CreateRubrica;
+    type UpdateInput = UpdateRubrica;
+
+    fn table_name(&self) -> &str {
+        "cadastro_rubrica r"
+    }
+
+    fn id_column(&self) -> &str {
+        "r.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "r.codigo ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("r.codigo", "ILIKE"),
+            ("r.descricao", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "r.id, r.codigo, r.descricao, r.tipo, r.incide_inss, r.incide_irrf, r.incide_fgts, r.is_active,
+        r.codigo_sistema, r.pensao_alimenticia, r.informe_rendimentos, r.fixa"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_rubrica r"
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Rubrica> {
+        Ok(sqlx::query_as!(
+            Rubrica,
+            r#"INSERT INTO cadastro_rubrica(codigo, descricao, tipo, incide_inss, incide_irrf, incide_fgts, is_active, pensao_alimenticia,
+                informe_rendimentos, fixa)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *"#,
+            input.codigo,
+            input.descricao,
+            input.tipo,
+            input.incide_inss,
+            input.incide_irrf,
+            input.incide_fgts,
+            input.is_active,
+            input.pensao_alimenticia,
+            input.informe_rendimentos,
+            input.fixa
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<Rubrica> {
+        Ok(sqlx::query_as!(
+            Rubrica,
+            r#"
+            UPDATE cadastro_rubrica
+            SET
+                codigo = COALESCE($1, codigo),
+                descricao = COALESCE($2, descricao),
+                tipo = COALESCE($3, tipo),
+                incide_inss = $4,
+                incide_irrf = $5,
+                incide_fgts = $6,
+                is_active = $7,
+                pensao_alimenticia = $8,
+                informe_rendimentos = $9,
+                fixa = $10
+            WHERE id = $11
+            RETURNING *"#,
+            input.codigo,
+            input.descricao,
+            input.tipo,
+            input.incide_inss,
+            input.incide_irrf,
+            input.incide_fgts,
+            input.is_active,
+            input.pensao_alimenticia,
+            input.informe_rendimentos,
+            input.fixa,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_rubrica WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+
+pub struct FolhaItemRepository;
+
+impl FolhaItemRepository {
+    /// Itens de uma folha: proventos, descontos e por último as informativas
+    pub async fn get_by_folha(&self, pool: &PgPool, folha_id: i64) -> Result<Vec<FolhaItem>> {
+        Ok(sqlx::query_as!(
+            FolhaItem,
+            r#"
+            SELECT i.id, i.folha_id, i.rubrica_id, i.referencia, i.valor, i.parcela,
+                r.codigo AS "rubrica_codigo?", r.descricao AS "rubrica_descricao?", r.tipo AS "rubrica_tipo?"
+            FROM cadastro_folha_item i
+            INNER JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE i.folha_id = $1
+            ORDER BY CASE r.tipo WHEN 'P' THEN 0 WHEN 'D' THEN 1 ELSE 2 END, r.codigo ASC, i.id ASC
+            "#,
+            folha_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Soma das rubricas que incidem INSS (proventos menos descontos)
+    pub async fn base_inss(&self, tx: &mut Transaction<'_, Postgres>, folhas: &[i64]) -> Result<BigDecimal> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(CASE r.tipo WHEN 'P' THEN i.valor ELSE -i.valor END), 0) AS "base!"
+            FROM cadastro_folha_item i
+            INNER JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE i.folha_id = ANY($1) AND r.incide_inss AND r.tipo IN ('P', 'D')
+            "#,
+            folhas
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    /// Rendimento tributável, INSS oficial e pensão alimentícia lançados nas folhas
+    pub async fn base_irrf(&self, tx: &mut Transaction<'_, Postgres>, folhas: &[i64]) -> Result<BaseIrrfFolha> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                COALESCE(SUM(CASE
+                    WHEN NOT r.incide_irrf OR r.pensao_alimenticia THEN 0
+                    WHEN r.tipo = 'P' THEN i.valor
+                    WHEN r.tipo = 'D' THEN -i.valor
+                    ELSE 0 END), 0) AS "rendimento!",
+                COALESCE(SUM(i.valor) FILTER (WHERE r.codigo_sistema = 'INSS'), 0) AS "inss!",
+                COALESCE(SUM(i.valor) FILTER (WHERE r.tipo = 'D' AND r.pensao_alimenticia), 0) AS "pensao!"
+            FROM cadastro_folha_item i
+            INNER JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE i.folha_id = ANY($1)
+            "#,
+            folhas
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(BaseIrrfFolha {
+            rendimento: row.rendimento,
+            inss: row.inss,
+            pensao: row.pensao,
+        })
+    }
+
+    /// Itens das rubricas fixas ativas lançados nas folhas
+    pub async fn fixos(&self, pool: &PgPool, folhas: &[i64]) -> Result<Vec<ItemFixo>> {
+        Ok(sqlx::query_as!(
+            ItemFixo,
+            r#"
+            SELECT i.folha_id, i.rubrica_id, r.codigo, r.descricao, r.codigo_sistema, i.referencia, i.valor
+            FROM cadastro_folha_item i
+            INNER JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE i.folha_id = ANY($1) AND r.fixa AND r.is_active
+            ORDER BY i.folha_id, r.codigo, i.id
+            "#,
+            folhas
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Gravações do item na transação de quem chama, que trava a competência antes
+    pub async fn criar(&self, tx: &mut Transaction<'_, Postgres>, input: CreateFolhaItem) -> Result<FolhaItem> {
+        Ok(sqlx::query_as!(
+            FolhaItem,
+            r#"INSERT INTO cadastro_folha_item(folha_id, rubrica_id, referencia, valor, parcela)
+            VALUES ($1, $2, COALESCE($3::numeric, 0), $4, $5)
+            RETURNING *, NULL as "rubrica_codigo?", NULL as "rubrica_descricao?", NULL as "rubrica_tipo?" "#,
+            input.folha_id,
+            input.rubrica_id,
+            input.referencia,
+            input.valor,
+            input.parcela
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    pub async fn alterar(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i64,
+        input: UpdateFolhaItem,
+    ) -> Result<FolhaItem> {
+        Ok(sqlx::query_as!(
+            FolhaItem,
+            r#"
+            UPDATE cadastro_folha_item
+            SET
+                rubrica_id = COALESCE($1, rubrica_id),
+                referencia = COALESCE($2, referencia),
+                valor = COALESCE($3, valor),
+                parcela = CASE WHEN $4 THEN $5 ELSE parcela END
+            WHERE id = $6
+            RETURNING *, NULL as "rubrica_codigo?", NULL as "rubrica_descricao?", NULL as "rubrica_tipo?" "#,
+            input.rubrica_id,
+            input.referencia,
+            input.valor,
+            input.parcela.is_some(),
+            input.parcela.flatten(),
+            id
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    pub async fn excluir(&self, tx: &mut Transaction<'_, Postgres>, id: i64) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_folha_item WHERE id = $1", id)
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn inserir(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        folha_id: i64,
+        rubrica_id: i32,
+        referencia: &BigDecimal,
+        valor: &BigDecimal,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO cadastro_folha_item(folha_id, rubrica_id, referencia, valor)
+            VALUES ($1, $2, $3, $4)"#,
+            folha_id,
+            rubrica_id,
+            referencia,
+            valor
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Substitui o item de uma rubrica de sistema na folha; sem valor, apenas remove
+    pub async fn substituir_item_sistema(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        folha_id: i64,
+        rubrica_id: i32,
+        referencia: &BigDecimal,
+        valor: &BigDecimal,
+    ) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM cadastro_folha_item WHERE folha_id = $1 AND rubrica_id = $2",
+            folha_id,
+            rubrica_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        if *valor > BigDecimal::zero() {
+            sqlx::query!(
+                r#"INSERT INTO cadastro_folha_item(folha_id, rubrica_id, referencia, valor)
+                VALUES ($1, $2, $3, $4)"#,
+                folha_id,
+                rubrica_id,
+                referencia,
+                valor
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Repository<FolhaItem, i64> for FolhaItemRepository {
+    type CreateInput = CreateFolhaItem;
+    type UpdateInput = UpdateFolhaItem;
+
+    fn table_name(&self) -> &str {
+        "cadastro_folha_item i"
+    }
+
+    fn id_column(&self) -> &str {
+        "i.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "i.folha_id DESC, r.codigo ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("r.codigo", "ILIKE"),
+            ("r.descricao", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "i.id, i.folha_id, i.rubrica_id, i.referencia, i.valor, i.parcela,
+        r.codigo AS rubrica_codigo, r.descricao AS rubrica_descricao, r.tipo AS rubrica_tipo"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_folha_item i
+        INNER JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+        "
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<FolhaItem> {
+        let mut tx = pool.begin().await?;
+        let item = self.criar(&mut tx, input).await?;
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    async fn update(&self, pool: &PgPool, id: i64, input: Self::UpdateInput) -> Result<FolhaItem> {
+        let mut tx = pool.begin().await?;
+        let item = self.alterar(&mut tx, id, input).await?;
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i64) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        self.excluir(&mut tx, id).await?;
+        Ok(tx.commit().await?)
+    }
+}
+
+
+pub struct TabelaInssRepository;
+
+impl TabelaInssRepository {
+    /// Faixas da tabela vigente na competência (a vigência mais recente até o mês informado)
+    pub async fn get_vigente(&self, pool: &PgPool, ano: i32, mes: i32) -> Result<Vec<FaixaInss>> {
+        Ok(sqlx::query_as!(
+            FaixaInss,
+            r#"
+            SELECT id, vigencia, limite, aliquota
+            FROM cadastro_tabela_inss
+            WHERE vigencia = (
+                SELECT MAX(vigencia) FROM cadastro_tabela_inss
+                WHERE vigencia <= make_date($1, $2, 1)
+            )
+            ORDER BY limite ASC
+            "#,
+            ano,
+            mes
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+}
+
+#[async_trait]
+impl Repository<FaixaInss, i32> for TabelaInssRepository {
+    type CreateInput = CreateFaixaInss;
+    type UpdateInput = UpdateFaixaInss;
+
+    fn table_name(&self) -> &str {
+        "cadastro_tabela_inss t"
+    }
+
+    fn id_column(&self) -> &str {
+        "t.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "t.vigencia DESC, t.limite ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[("t.vigencia::text", "ILIKE")]
+    }
+
+    fn select_clause(&self) -> &str {
+        "t.id, t.vigencia, t.limite, t.aliquota"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_tabela_inss t"
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<FaixaInss> {
+        Ok(sqlx::query_as!(
+            FaixaInss,
+            r#"INSERT INTO cadastro_tabela_inss(vigencia, limite, aliquota)
+            VALUES ($1, $2, $3) RETURNING *"#,
+            input.vigencia,
+            input.limite,
+            input.aliquota
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<FaixaInss> {
+        Ok(sqlx::query_as!(
+            FaixaInss,
+            r#"
+            UPDATE cadastro_tabela_inss
+            SET
+                vigencia = COALESCE($1, vigencia),
+                limite = COALESCE($2, limite),
+                aliquota = COALESCE($3, aliquota)
+            WHERE id = $4
+            RETURNING *"#,
+            input.vigencia,
+            input.limite,
+            input.aliquota,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_tabela_inss WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+
+pub struct TabelaIrrfRepository;
+
+impl TabelaIrrfRepository {
+    /// Faixas da tabela vigente na competência, terminando na faixa sem limite
+    pub async fn get_vigente(&self, pool: &PgPool, ano: i32, mes: i32) -> Result<Vec<FaixaIrrf>> {
+        Ok(sqlx::query_as!(
+            FaixaIrrf,
+            r#"
+            SELECT id, vigencia, limite, aliquota, deducao
+            FROM cadastro_tabela_irrf
+            WHERE vigencia = (
+                SELECT MAX(vigencia) FROM cadastro_tabela_irrf
+                WHERE vigencia <= make_date($1, $2, 1)
+            )
+            ORDER BY limite ASC NULLS LAST
+            "#,
+            ano,
+            mes
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+}
+
+#[async_trait]
+impl Repository<FaixaIrrf, i32> for TabelaIrrfRepository {
+    type CreateInput = CreateFaixaIrrf;
+    type UpdateInput = UpdateFaixaIrrf;
+
+    fn table_name(&self) -> &str {
+        "cadastro_tabela_irrf t"
+    }
+
+    fn id_column(&self) -> &str {
+        "t.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "t.vigencia DESC, t.limite ASC NULLS LAST"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[("t.vigencia::text", "ILIKE")]
+    }
+
+    fn select_clause(&self) -> &str {
+        "t.id, t.vigencia, t.limite, t.aliquota, t.deducao"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_tabela_irrf t"
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<FaixaIrrf> {
+        Ok(sqlx::query_as!(
+            FaixaIrrf,
+            r#"INSERT INTO cadastro_tabela_irrf(vigencia, limite, aliquota, deducao)
+            VALUES ($1, $2, $3, $4) RETURNING *"#,
+            input.vigencia,
+            input.limite,
+            input.aliquota,
+            input.deducao
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<FaixaIrrf> {
+        Ok(sqlx::query_as!(
+            FaixaIrrf,
+            r#"
+            UPDATE cadastro_tabela_irrf
+            SET
+                vigencia = COALESCE($1, vigencia),
+                limite = COALESCE($2, limite),
+                aliquota = COALESCE($3, aliquota),
+                deducao = COALESCE($4, deducao)
+            WHERE id = $5
+            RETURNING *"#,
+            input.vigencia,
+            input.limite,
+            input.aliquota,
+            input.deducao,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_tabela_irrf WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+
+pub struct ParametroIrrfRepository;
+
+impl ParametroIrrfRepository {
+    pub async fn get_vigente(&self, pool: &PgPool, ano: i32, mes: i32) -> Result<Option<ParametroIrrf>> {
+        Ok(sqlx::query_as!(
+            ParametroIrrf,
+            r#"
+            SELECT * FROM cadastro_parametro_irrf
+            WHERE vigencia <= make_date($1, $2, 1)
+            ORDER BY vigencia DESC
+            LIMIT 1
+            "#,
+            ano,
+            mes
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+}
+
+#[async_trait]
+impl Repository<ParametroIrrf, i32> for ParametroIrrfRepository {
+    type CreateInput = CreateParametroIrrf;
+    type UpdateInput = UpdateParametroIrrf;
+
+    fn table_name(&self) -> &str {
+        "cadastro_parametro_irrf p"
+    }
+
+    fn id_column(&self) -> &str {
+        "p.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "p.vigencia DESC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[("p.vigencia::text", "ILIKE")]
+    }
+
+    fn select_clause(&self) -> &str {
+        "p.id, p.vigencia, p.deducao_dependente, p.desconto_simplificado, p.reducao_limite_isencao,
+        p.reducao_valor_isencao, p.reducao_limite, p.reducao_constante, p.reducao_fator"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_parametro_irrf p"
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<ParametroIrrf> {
+        Ok(sqlx::query_as!(
+            ParametroIrrf,
+            r#"INSERT INTO cadastro_parametro_irrf(
+            vigencia, deducao_dependente, desconto_simplificado, reducao_limite_isencao,
+            reducao_valor_isencao, reducao_limite, reducao_constante, reducao_fator)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *"#,
+            input.vigencia,
+            input.deducao_dependente,
+            input.desconto_simplificado,
+            input.reducao_limite_isencao,
+            input.reducao_valor_isencao,
+            input.reducao_limite,
+            input.reducao_constante,
+            input.reducao_fator
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<ParametroIrrf> {
+        Ok(sqlx::query_as!(
+            ParametroIrrf,
+            r#"
+            UPDATE cadastro_parametro_irrf
+            SET
+                vigencia = COALESCE($1, vigencia),
+                deducao_dependente = COALESCE($2, deducao_dependente),
+                desconto_simplificado = COALESCE($3, desconto_simplificado),
+                reducao_limite_isencao = $4,
+                reducao_valor_isencao = $5,
+                reducao_limite = $6,
+                reducao_constante = $7,
+                reducao_fator = $8
+            WHERE id = $9
+            RETURNING *"#,
+            input.vigencia,
+            input.deducao_dependente,
+            input.desconto_simplificado,
+            input.reducao_limite_isencao,
+            input.reducao_valor_isencao,
+            input.reducao_limite,
+            input.reducao_constante,
+            input.reducao_fator,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_parametro_irrf WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+
+pub struct CompetenciaRepository;
+
+impl CompetenciaRepository {
+    pub async fn get_by_orgao_ano_mes(
+        &self,
+        pool: &PgPool,
+        orgao_id: i32,
+        ano: i32,
+        mes: i32,
+    ) -> Result<Option<Competencia>> {
+        Ok(sqlx::query_as!(
+            Competencia,
+            r#"SELECT *, NULL as "org_nome?" FROM cadastro_competencia
+            WHERE orgao_id = $1 AND ano = $2 AND mes = $3"#,
+            orgao_id,
+            ano,
+            mes
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Situação da competência, travada até o fim da transação de quem grava numa folha
+    /// dela: o fechamento espera a gravação terminar
+    pub async fn situacao_travada(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        orgao_id: i32,
+        ano: i32,
+        mes: i32,
+    ) -> Result<Option<String>> {
+        Ok(sqlx::query_scalar!(
+            r#"SELECT situacao FROM cadastro_competencia
+            WHERE orgao_id = $1 AND ano = $2 AND mes = $3
+            FOR SHARE"#,
+            orgao_id,
+            ano,
+            mes
+        )
+        .fetch_optional(&mut **tx)
+        .await?)
+    }
+
+    /// Competência travada até o fim da transação, para duas transições não correrem juntas
+    pub async fn get_para_transicao(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<Competencia> {
+        Ok(sqlx::query_as!(
+            Competencia,
+            r#"SELECT *, NULL as "org_nome?" FROM cadastro_competencia
+            WHERE id = $1
+            FOR UPDATE"#,
+            id
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    /// Abre a competência; None se ela já existia
+    pub async fn inserir(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        input: &CreateCompetencia,
+    ) -> Result<Option<Competencia>> {
+        Ok(sqlx::query_as!(
+            Competencia,
+            r#"INSERT INTO cadastro_competencia(orgao_id, ano, mes)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (orgao_id, ano, mes) DO NOTHING
+            RETURNING *, NULL as "org_nome?""#,
+            input.orgao_id,
+            input.ano,
+            input.mes
+        )
+        .fetch_optional(&mut **tx)
+        .await?)
+    }
+
+    /// Volta a competência calculada para aberta, ou para reaberta se ela já foi fechada
+    /// antes. Devolve o id e a nova situação; None se ela não estava calculada
+    pub async fn descalcular(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        orgao_id: i32,
+        ano: i32,
+        mes: i32,
+    ) -> Result<Option<(i32, String)>> {
+        let competencia = sqlx::query!(
+            r#"
+            UPDATE cadastro_competencia
+            SET situacao = CASE WHEN fechada_em IS NULL THEN $5 ELSE $6 END
+            WHERE orgao_id = $1 AND ano = $2 AND mes = $3 AND situacao = $4
+            RETURNING id, situacao
+            "#,
+            orgao_id,
+            ano,
+            mes,
+            COMPETENCIA_CALCULADA,
+            COMPETENCIA_ABERTA,
+            COMPETENCIA_REABERTA
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+        Ok(competencia.map(|c| (c.id, c.situacao)))
+    }
+
+    /// Ids das folhas da competência na ordem de cálculo: mensal, férias e complementares,
+    /// para as bases acumularem. As do 13º e da rescisão têm cálculo próprio
+    pub async fn folhas(&self, tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<Vec<i64>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT f.id
+            FROM cadastro_folha f
+            INNER JOIN cadastro_competencia c
+                ON c.orgao_id = f.orgao_id AND c.ano = f.ano AND c.mes = f.mes
+            WHERE c.id = $1 AND f.tipo_folha = ANY($2)
+            ORDER BY array_position($2, f.tipo_folha), f.sequencia, f.id
+            "#,
+            id,
+            &FOLHAS_DA_COMPETENCIA[..]
+        )
+        .fetch_all(&mut **tx)
+        .await?)
+    }
+
+    /// Trava as folhas da competência e os itens delas até o fim da transação: os totais
+    /// lidos no fechamento não mudam antes de serem gravados
+    pub async fn travar_folhas(&self, tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<()> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT f.id
+            FROM cadastro_folha f
+            INNER JOIN cadastro_competencia c
+                ON c.orgao_id = f.orgao_id AND c.ano = f.ano AND c.mes = f.mes
+            WHERE c.id = $1 AND f.tipo_folha = ANY($2)
+            FOR UPDATE OF f
+            "#,
+            id,
+            &FOLHAS_DA_COMPETENCIA[..]
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        sqlx::query_scalar!(
+            r#"
+            SELECT i.id
+            FROM cadastro_folha_item i
+            INNER JOIN cadastro_folha f ON f.id = i.folha_id
+            INNER JOIN cadastro_competencia c
+                ON c.orgao_id = f.orgao_id AND c.ano = f.ano AND c.mes = f.mes
+            WHERE c.id = $1 AND f.tipo_folha = ANY($2)
+            FOR UPDATE OF i
+            "#,
+            id,
+            &FOLHAS_DA_COMPETENCIA[..]
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn totais(&self, tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<TotaisCompetencia> {
+        Ok(sqlx::query_as!(
+            TotaisCompetencia,
+            r#"
+            SELECT
+                COUNT(DISTINCT f.servidor_id)::int AS "total_servidores!",
+                COALESCE(SUM(i.valor) FILTER (WHERE r.tipo = 'P'), 0) AS "total_proventos!",
+                COALESCE(SUM(i.valor) FILTER (WHERE r.tipo = 'D'), 0) AS "total_descontos!"
+            FROM cadastro_competencia c
+            INNER JOIN cadastro_folha f
+                ON f.orgao_id = c.orgao_id AND f.ano = c.ano AND f.mes = c.mes
+                AND f.tipo_folha = ANY($2)
+            LEFT JOIN cadastro_folha_item i ON i.folha_id = f.id
+            LEFT JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE c.id = $1
+            "#,
+            id,
+            &FOLHAS_DA_COMPETENCIA[..]
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    /// Muda a situação; os totais só são gravados no fechamento (None mantém os atuais)
+    pub async fn atualizar_situacao(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        situacao: &str,
+        totais: Option<&TotaisCompetencia>,
+        usuario: &str,
+    ) -> Result<()> {
+        match totais {
+            Some(totais) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE cadastro_competencia
+                    SET situacao = $1, total_servidores = $2, total_proventos = $3,
+                        total_descontos = $4, valor_liquido = $3::numeric - $4::numeric,
+                        fechada_em = now(), fechada_por = $5
+                    WHERE id = $6"#,
+                    situacao,
+                    totais.total_servidores,
+                    totais.total_proventos,
+                    totais.total_descontos,
+                    usuario,
+                    id
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            None => {
+                sqlx::query!(
+                    "UPDATE cadastro_competencia SET situacao = $1 WHERE id = $2",
+                    situacao,
+                    id
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn registrar_historico(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        competencia_id: i32,
+        situacao_anterior: Option<&str>,
+        situacao_nova: &str,
+        usuario: Option<&str>,
+        motivo: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO cadastro_competencia_historico(
+            competencia_id, situacao_anterior, situacao_nova, usuario, motivo)
+            VALUES ($1, $2, $3, $4, $5)"#,
+            competencia_id,
+            situacao_anterior,
+            situacao_nova,
+            usuario,
+            motivo
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn historico(&self, pool: &PgPool, competencia_id: i32) -> Result<Vec<CompetenciaHistorico>> {
+        Ok(sqlx::query_as!(
+            CompetenciaHistorico,
+            r#"SELECT * FROM cadastro_competencia_historico
+            WHERE competencia_id = $1
+            ORDER BY created_at DESC, id DESC"#,
+            competencia_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+}
+
+#[async_trait]
+impl Repository<Competencia, i32> for CompetenciaRepository {
+    type CreateInput = CreateCompetencia;
+    type UpdateInput = CreateCompetencia;
+
+    fn table_name(&self) -> &str {
+        "cadastro_competencia c"
+    }
+
+    fn id_column(&self) -> &str {
+        "c.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "c.ano DESC, c.mes DESC, org.nome ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("org.nome", "ILIKE"),
+            ("c.situacao", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "c.id, c.orgao_id, c.ano, c.mes, c.situacao, c.total_servidores, c.total_proventos,
+        c.total_descontos, c.valor_liquido, c.fechada_em, c.fechada_por, org.nome AS org_nome"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_competencia c
+        INNER JOIN cadastro_orgao org ON org.id = c.orgao_id
+        "
+    }
+
+    /// Abre a competência; se já existir, devolve a existente
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Competencia> {
+        sqlx::query!(
+            r#"INSERT INTO cadastro_competencia(orgao_id, ano, mes)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (orgao_id, ano, mes) DO NOTHING"#,
+            input.orgao_id,
+            input.ano,
+            input.mes
+        )
+        .execute(pool)
+        .await?;
+
+        self.get_by_orgao_ano_mes(pool, input.orgao_id, input.ano, input.mes)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Competência não encontrada"))
+    }
+
+    async fn update(&self, _pool: &PgPool, _id: i32, _input: Self::UpdateInput) -> Result<Competencia> {
+        anyhow::bail!("A competência só muda pelas transições (calcular, fechar e reabrir)")
+    }
+
+    async fn delete(&self, _pool: &PgPool, _id: i32) -> Result<()> {
+        anyhow::bail!("Competência não pode ser excluída; o histórico é mantido para auditoria")
+    }
+}
+
+
+pub struct LoteContraChequeRepository;
+
+impl LoteContraChequeRepository {
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<LoteContraCheque> {
+        Ok(sqlx::query_as!(
+            LoteContraCheque,
+            r#"SELECT id, orgao_id, ano, mes, setor_id, departamento_id, formato, situacao,
+                total_folhas, nome_arquivo, erro, usuario, created_at, concluido_em
+            FROM cadastro_lote_contra_cheque WHERE id = $1"#,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    pub async fn create(
+        &self,
+        pool: &PgPool,
+        input: &GerarLoteContraCheque,
+        total_folhas: i32,
+        usuario: &str,
+    ) -> Result<LoteContraCheque> {
+        Ok(sqlx::query_as!(
+            LoteContraCheque,
+            r#"INSERT INTO cadastro_lote_contra_cheque(
+                orgao_id, ano, mes, setor_id, departamento_id, formato, total_folhas, usuario)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, orgao_id, ano, mes, setor_id, departamento_id, formato, situacao,
+                total_folhas, nome_arquivo, erro, usuario, created_at, concluido_em"#,
+            input.orgao_id,
+            input.ano,
+            input.mes,
+            input.setor_id,
+            input.departamento_id,
+            input.formato,
+            total_folhas,
+            usuario
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    pub async fn atualizar_situacao(&self, pool: &PgPool, id: i32, situacao: &str) -> Result<()> {
+        sqlx::query!(
+            "UPDATE cadastro_lote_contra_cheque SET situacao = $1 WHERE id = $2",
+            situacao,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn concluir(
+        &self,
+        pool: &PgPool,
+        id: i32,
+        nome_arquivo: &str,
+        caminho_arquivo: &str,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE cadastro_lote_contra_cheque
+            SET situacao = $1, nome_arquivo = $2, caminho_arquivo = $3, concluido_em = now()
+            WHERE id = $4"#,
+            LOTE_CONCLUIDO,
+            nome_arquivo,
+            caminho_arquivo,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn registrar_erro(&self, pool: &PgPool, id: i32, erro: &str) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE cadastro_lote_contra_cheque
+            SET situacao = $1, erro = $2, concluido_em = now()
+            WHERE id = $3"#,
+            LOTE_ERRO,
+            erro,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Nome e caminho em disco do arquivo gerado; None enquanto o lote não foi concluído
+    pub async fn arquivo(&self, pool: &PgPool, id: i32) -> Result<Option<(String, String)>> {
+        let row = sqlx::query!(
+            "SELECT nome_arquivo, caminho_arquivo FROM cadastro_lote_contra_cheque WHERE id = $1",
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(row.nome_arquivo.zip(row.caminho_arquivo))
+    }
+
+    /// Lotes cujo arquivo passou da validade deixam de apontar para ele
+    pub async fn expirar_arquivos(&self, pool: &PgPool, limite: DateTime<Utc>) -> Result<u64> {
+        let res = sqlx::query!(
+            r#"UPDATE cadastro_lote_contra_cheque SET caminho_arquivo = NULL
+            WHERE caminho_arquivo IS NOT NULL AND concluido_em < $1"#,
+            limite
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    /// Lotes que ficaram pela metade quando o servidor foi reiniciado
+    pub async fn interromper_pendentes(&self, pool: &PgPool) -> Result<u64> {
+        let res = sqlx::query!(
+            r#"UPDATE cadastro_lote_contra_cheque
+            SET situacao = $1, erro = 'Processamento interrompido', concluido_em = now()
+            WHERE situacao IN ($2, $3)"#,
+            LOTE_ERRO,
+            LOTE_PENDENTE,
+            LOTE_PROCESSANDO
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+}
+
+
+pub struct EmissaoContraChequeRepository;
+
+impl EmissaoContraChequeRepository {
+    /// Emissão mais recente do contra-cheque da folha
+    pub async fn ultima_da_folha(
+        &self,
+        pool: &PgPool,
+        folha_id: i64,
+    ) -> Result<Option<EmissaoContraCheque>> {
+        Ok(sqlx::query_as!(
+            EmissaoContraCheque,
+            r#"SELECT * FROM cadastro_contra_cheque_emissao
+            WHERE folha_id = $1
+            ORDER BY emitido_em DESC, id DESC
+            LIMIT 1"#,
+            folha_id
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn get_by_codigo(
+        &self,
+        pool: &PgPool,
+        codigo: &str,
+    ) -> Result<Option<EmissaoContraCheque>> {
+        Ok(sqlx::query_as!(
+            EmissaoContraCheque,
+            "SELECT * FROM cadastro_contra_cheque_emissao WHERE codigo = $1",
+            codigo
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Registra a emissão com os valores do contra-cheque impresso
+    pub async fn create(
+        &self,
+        pool: &PgPool,
+        codigo: &str,
+        cabecalho: &CabecalhoContraCheque,
+        detalhe: &FolhaDetalhe,
+    ) -> Result<EmissaoContraCheque> {
+        Ok(sqlx::query_as!(
+            EmissaoContraCheque,
+            r#"INSERT INTO cadastro_contra_cheque_emissao(
+                codigo, folha_id, orgao_nome, servidor_nome, ano, mes,
+                total_proventos, total_descontos, valor_liquido, tipo_folha, sequencia)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING *"#,
+            codigo,
+            detalhe.folha.id,
+            cabecalho.org_nome,
+            cabecalho.serv_nome,
+            detalhe.folha.ano,
+            detalhe.folha.mes,
+            detalhe.total_proventos,
+            detalhe.total_descontos,
+            detalhe.valor_liquido,
+            detalhe.folha.tipo_folha,
+            detalhe.folha.sequencia
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+}
+
+pub struct EventoEsocialRepository;
+
+impl EventoEsocialRepository {
+    /// Servidores com folha mensal no órgão/competência, cada um com as folhas (demonstrativos)
+    /// e as rubricas de valor positivo
+    pub async fn trabalhadores(
+        &self,
+        pool: &PgPool,
+        orgao_id: i32,
+        ano: i32,
+        mes: i32,
+    ) -> Result<Vec<Trabalhador>> {
+        let folhas = sqlx::query!(
+            r#"
+            SELECT f.id, f.servidor_id, s.nome, s.cpf, s.matricula,
+                v.categoria_esocial AS "categoria_esocial?"
+            FROM cadastro_folha f
+            INNER JOIN cadastro_servidor s ON s.id = f.servidor_id
+            LEFT JOIN cadastro_tipovinculo v ON v.id = f.vinculo_id
+            WHERE f.orgao_id = $1 AND f.ano = $2 AND f.mes = $3 AND f.tipo_folha = ANY($4)
+            ORDER BY s.nome, f.servidor_id, array_position($4, f.tipo_folha), f.sequencia, f.id"#,
+            orgao_id,
+            ano,
+            mes,
+            &FOLHAS_DA_COMPETENCIA[..]
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let ids: Vec<i64> = folhas.iter().map(|f| f.id).collect();
+        let itens = sqlx::query!(
+            r#"
+            SELECT i.folha_id, r.codigo, r.tipo, i.valor
+            FROM cadastro_folha_item i
+            INNER JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE i.folha_id = ANY($1) AND i.valor > 0
+            ORDER BY i.folha_id, r.codigo"#,
+            &ids
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut por_folha: HashMap<i64, (Vec<ItemRemuneracao>, BigDecimal)> = HashMap::new();
+        for item in itens {
+            let (lista, liquido) = por_folha
+                .entry(item.folha_id)
+                .or_insert_with(|| (Vec::new(), BigDecimal::zero()));
+            if item.tipo == RUBRICA_PROVENTO {
+                *liquido += &item.valor;
+            } else if item.tipo == RUBRICA_DESCONTO {
+                *liquido -= &item.valor;
+            }
+            lista.push(ItemRemuneracao {
+                codigo: item.codigo,
+                valor: item.valor,
+            });
+        }
+
+        let mut trabalhadores: Vec<Trabalhador> = Vec::new();
+        for folha in folhas {
+            let (itens, valor_liquido) = por_folha
+                .remove(&folha.id)
+                .unwrap_or_else(|| (Vec::new(), BigDecimal::zero()));
+            let demonstrativo = Demonstrativo {
+                folha_id: folha.id,
+                categoria: folha.categoria_esocial,
+                itens,
+                valor_liquido,
+            };
+            match trabalhadores.last_mut() {
+                Some(t) if t.servidor_id == folha.servidor_id => t.demonstrativos.push(demonstrativo),
+                _ => trabalhadores.push(Trabalhador {
+                    servidor_id: folha.servidor_id,
+                    nome: folha.nome,
+                    cpf: folha.cpf,
+                    matricula: folha.matricula,
+                    demonstrativos: vec![demonstrativo],
+                }),
+            }
+        }
+        Ok(trabalhadores)
+    }
+
+    /// Remove os eventos gerados antes para a competência (gerar de novo substitui todos)
+    pub async fn excluir_competencia(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        orgao_id: i32,
+        ano: i32,
+        mes: i32,
+    ) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM cadastro_esocial_evento WHERE orgao_id = $1 AND ano = $2 AND mes = $3",
+            orgao_id,
+            ano,
+            mes
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn salvar(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        evento: &GravarEventoEsocial,
+        usuario: &str,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO cadastro_esocial_evento(orgao_id, ano, mes, tipo, servidor_id, total_folhas,
+                id_evento, xml, situacao, erros, usuario, validado_em)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11,
+                CASE WHEN $9::VARCHAR = 'GERADO' THEN NULL ELSE now() END)"#,
+            evento.orgao_id,
+            evento.ano,
+            evento.mes,
+            evento.tipo,
+            evento.servidor_id,
+            evento.total_folhas,
+            evento.id_evento,
+            evento.xml,
+            evento.situacao,
+            evento.erros,
+            usuario
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// XML guardado: (Id do evento, tipo, XML)
+    pub async fn xml(&self, pool: &PgPool, id: i64) -> Result<(String, String, String)> {
+        let evento = sqlx::query!(
+            "SELECT id_evento, tipo, xml FROM cadastro_esocial_evento WHERE id = $1",
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok((evento.id_evento, evento.tipo, evento.xml))
+    }
+
+    pub async fn atualizar_validacao(
+        &self,
+        pool: &PgPool,
+        id: i64,
+        situacao: &str,
+        erros: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE cadastro_esocial_evento
+            SET situacao = $1::VARCHAR, erros = $2, validado_em = CASE WHEN $1::VARCHAR = 'GERADO' THEN NULL ELSE now() END
+            WHERE id = $3"#,
+            situacao,
+            erros,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// XMLs validados da competência, prontos para assinar: (Id do evento, tipo, XML)
+    pub async fn validados(
+        &self,
+        pool: &PgPool,
+        orgao_id: i32,
+        ano: i32,
+        mes: i32,
+    ) -> Result<Vec<(String, String, String)>> {
+        let eventos = sqlx::query!(
+            r#"SELECT id_evento, tipo, xml FROM cadastro_esocial_evento
+            WHERE orgao_id = $1 AND ano = $2 AND mes = $3 AND situacao = 'VALIDADO'
+            ORDER BY tipo, id_evento"#,
+            orgao_id,
+            ano,
+            mes
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(eventos
+            .into_iter()
+            .map(|e| (e.id_evento, e.tipo, e.xml))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Repository<EventoEsocial, i64> for EventoEsocialRepository {
+    type CreateInput = GerarEsocial;
+    type UpdateInput = GerarEsocial;
+
+    fn table_name(&self) -> &str {
+        "cadastro_esocial_evento e"
+    }
+
+    fn id_column(&self) -> &str {
+        "e.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "e.ano DESC, e.mes DESC, e.tipo ASC, s.nome ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("s.nome", "ILIKE"),
+            ("s.cpf", "ILIKE"),
+            ("e.id_evento", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "e.id, e.orgao_id, e.ano, e.mes, e.tipo, e.servidor_id, e.total_folhas, e.id_evento,
+        e.situacao, e.erros, e.usuario, e.created_at, e.validado_em,
+        org.nome AS org_nome, s.nome AS serv_nome"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_esocial_evento e
+        INNER JOIN cadastro_orgao org ON org.id = e.orgao_id
+        INNER JOIN cadastro_servidor s ON s.id = e.servidor_id
+        "
+    }
+
+    async fn create(&self, _pool: &PgPool, _input: Self::CreateInput) -> Result<EventoEsocial> {
+        anyhow::bail!("Eventos do eSocial são gerados a partir da competência")
+    }
+
+    async fn update(&self, _pool: &PgPool, _id: i64, _input: Self::UpdateInput) -> Result<EventoEsocial> {
+        anyhow::bail!("Eventos do eSocial são gerados a partir da competência")
+    }
+
+    async fn delete(&self, _pool: &PgPool, _id: i64) -> Result<()> {
+        anyhow::bail!("Eventos do eSocial não podem ser excluídos; gere a competência de novo")
+    }
+}
+
+pub struct FilaEsocialRepository;
+
+impl FilaEsocialRepository {
+    /// Já existe admissão (S-2200 ou S-2300) do servidor no órgão, em qualquer situação
+    pub async fn admissao_na_fila(&self, pool: &PgPool, servidor_id: i32, orgao_id: i32) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM cadastro_esocial_fila
+                WHERE servidor_id = $1 AND orgao_id = $2 AND tipo IN ($3, $4)
+            ) AS "existe!""#,
+            servidor_id,
+            orgao_id,
+            ESOCIAL_S2200,
+            ESOCIAL_S2300
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+
+    /// Coloca o evento na fila; a mesma alteração já pendente não é repetida
+    pub async fn enfileirar(
+        &self,
+        pool: &PgPool,
+        orgao_id: i32,
+        servidor_id: i32,
+        tipo: &str,
+        data_evento: NaiveDate,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO cadastro_esocial_fila(orgao_id, servidor_id, tipo, data_evento)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (servidor_id, tipo, data_evento) WHERE situacao = 'PENDENTE' DO NOTHING"#,
+            orgao_id,
+            servidor_id,
+            tipo,
+            data_evento
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Eventos pendentes do órgão, na ordem em que entraram na fila
+    pub async fn pendentes(&self, pool: &PgPool, orgao_id: i32) -> Result<Vec<i64>> {
+        Ok(sqlx::query_scalar!(
+            "SELECT id FROM cadastro_esocial_fila WHERE orgao_id = $1 AND situacao = $2 ORDER BY id",
+            orgao_id,
+            FILA_PENDENTE
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Maior sequencial já usado nos Ids com o prefixo (mesmo CNPJ e segundo), na fila e
+    /// nos eventos periódicos; a re-geração no mesmo segundo continua a partir dele
+    pub async fn ultimo_sequencial(&self, pool: &PgPool, prefixo: &str) -> Result<u32> {
+        let sequencial = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(MAX(RIGHT(id_evento, 5)::INTEGER), 0) AS "sequencial!"
+            FROM (
+                SELECT id_evento FROM cadastro_esocial_fila WHERE id_evento LIKE $1 || '%'
+                UNION ALL
+                SELECT id_evento FROM cadastro_esocial_evento WHERE id_evento LIKE $1 || '%'
+            ) ids
+            "#,
+            prefixo
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(sequencial as u32)
+    }
+
+    /// Cadastro atual do servidor com o cargo, a categoria do vínculo e o salário da última folha
+    pub async fn cadastro(&self, pool: &PgPool, servidor_id: i32) -> Result<CadastroTrabalhador> {
+        let s = sqlx::query!(
+            r#"
+            SELECT s.nome, s.cpf, s.matricula, s.sexo, s.raca_cor, s.grau_instrucao, s.data_nascimento,
+                s.logradouro, s.numero, s.complemento, s.bairro, s.cep, s.data_admissao,
+                s.data_desligamento, s.motivo_desligamento,
+                m.codigo_ibge AS "codigo_municipio?", u.sigla AS "uf?",
+                c.nome AS "cargo?", c.cbo AS "cbo?",
+                v.categoria_esocial AS "categoria?", v.regime_previdencia AS "regime_previdencia?",
+                (SELECT f.salario FROM cadastro_folha f WHERE f.servidor_id = s.id AND f.tipo_folha = 0
+                    ORDER BY f.ano DESC, f.mes DESC, f.id DESC LIMIT 1) AS salario
+            FROM cadastro_servidor s
+            LEFT JOIN cadastro_municipio m ON m.id = s.municipio_id
+            LEFT JOIN cadastro_uf u ON u.id = m.uf_id
+            LEFT JOIN cadastro_cargo c ON c.id = s.cargo_id
+            LEFT JOIN cadastro_tipovinculo v ON v.id = s.vinculo_id
+            WHERE s.id = $1"#,
+            servidor_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(CadastroTrabalhador {
+            nome: s.nome,
+            cpf: s.cpf,
+            matricula: s.matricula,
+            sexo: s.sexo,
+            raca_cor: s.raca_cor,
+            grau_instrucao: s.grau_instrucao,
+            data_nascimento: s.data_nascimento,
+            logradouro: s.logradouro,
+            numero: s.numero,
+            complemento: s.complemento,
+            bairro: s.bairro,
+            cep: s.cep,
+            codigo_municipio: s.codigo_municipio,
+            uf: s.uf,
+            data_admissao: s.data_admissao,
+            cargo: s.cargo,
+            cbo: s.cbo,
+            categoria: s.categoria,
+            regime_previdencia: s.regime_previdencia.unwrap_or(1),
+            salario: s.salario,
+            data_desligamento: s.data_desligamento,
+            motivo_desligamento: s.motivo_desligamento,
+        })
+    }
+
+    /// Grava o XML gerado (ou o motivo da rejeição) no evento da fila
+    pub async fn gravar_geracao(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i64,
+        evento: &EventoGerado,
+        situacao: &str,
+        erros: Option<&str>,
+        usuario: &str,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE cadastro_esocial_fila
+            SET id_evento = $1, xml = $2, situacao = $3, erros = $4, usuario = $5, gerado_em = now()
+            WHERE id = $6"#,
+            evento.id,
+            evento.xml,
+            situacao,
+            erros,
+            usuario,
+            id
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// XML gerado: (Id do evento, XML); None enquanto o evento estiver pendente
+    pub async fn xml(&self, pool: &PgPool, id: i64) -> Result<Option<(String, String)>> {
+        let evento = sqlx::query!(
+            "SELECT id_evento, xml FROM cadastro_esocial_fila WHERE id = $1",
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(evento.id_evento.zip(evento.xml))
+    }
+}
+
+#[async_trait]
+impl Repository<EventoFilaEsocial, i64> for FilaEsocialRepository {
+    type CreateInput = ();
+    type UpdateInput = ();
+
+    fn table_name(&self) -> &str {
+        "cadastro_esocial_fila f"
+    }
+
+    fn id_column(&self) -> &str {
+        "f.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "f.id DESC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("s.nome", "ILIKE"),
+            ("s.cpf", "ILIKE"),
+            ("f.id_evento", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "f.id, f.orgao_id, f.servidor_id, f.tipo, f.data_evento, f.situacao, f.id_evento,
+        f.erros, f.usuario, f.created_at, f.gerado_em,
+        org.nome AS org_nome, s.nome AS serv_nome"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_esocial_fila f
+        INNER JOIN cadastro_orgao org ON org.id = f.orgao_id
+        INNER JOIN cadastro_servidor s ON s.id = f.servidor_id
+        "
+    }
+
+    async fn create(&self, _pool: &PgPool, _input: Self::CreateInput) -> Result<EventoFilaEsocial> {
+        anyhow::bail!("Eventos entram na fila pelas alterações do cadastro do servidor")
+    }
+
+    async fn update(&self, _pool: &PgPool, _id: i64, _input: Self::UpdateInput) -> Result<EventoFilaEsocial> {
+        anyhow::bail!("Eventos entram na fila pelas alterações do cadastro do servidor")
+    }
+
+    async fn delete(&self, _pool: &PgPool, _id: i64) -> Result<()> {
+        anyhow::bail!("Eventos da fila não podem ser excluídos; gere o evento de novo")
+    }
+}
+
+pub struct RemessaCnabRepository;
+
+impl RemessaCnabRepository {
+    /// Líquido de cada folha paga na competência (mensal, férias e complementares; proventos
+    /// menos descontos) com os dados bancários e o endereço do servidor
+    pub async fn creditos(&self, pool: &PgPool, orgao_id: i32, ano: i32, mes: i32) -> Result<Vec<Credito>> {
+        Ok(sqlx::query_as!(
+            Credito,
+            r#"
+            SELECT f.id AS folha_id, f.servidor_id, s.nome, s.cpf, s.banco, s.agencia, s.conta,
+                s.tipo_conta, s.logradouro, s.numero, s.complemento, s.bairro,
+                m.nome AS "cidade?", s.cep, u.sigla AS "uf?",
+                COALESCE(SUM(CASE WHEN r.tipo = $4 THEN i.valor WHEN r.tipo = $5 THEN -i.valor END), 0)
+                    AS "valor!"
+            FROM cadastro_folha f
+            INNER JOIN cadastro_servidor s ON s.id = f.servidor_id
+            LEFT JOIN cadastro_municipio m ON m.id = s.municipio_id
+            LEFT JOIN cadastro_uf u ON u.id = m.uf_id
+            LEFT JOIN cadastro_folha_item i ON i.folha_id = f.id
+            LEFT JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE f.orgao_id = $1 AND f.ano = $2 AND f.mes = $3 AND f.tipo_folha = ANY($6)
+            GROUP BY f.id, s.id, m.nome, u.sigla
+            ORDER BY s.nome, f.id"#,
+            orgao_id,
+            ano,
+            mes,
+            RUBRICA_PROVENTO,
+            RUBRICA_DESCONTO,
+            &FOLHAS_DA_COMPETENCIA[..]
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Reserva o próximo número sequencial de arquivo (NSA) do órgão
+    pub async fn proximo_nsa(&self, tx: &mut Transaction<'_, Postgres>, orgao_id: i32) -> Result<i32> {
+        let nsa = sqlx::query_scalar!(
+            "UPDATE cadastro_orgao SET cnab_nsa = cnab_nsa + 1 WHERE id = $1 RETURNING cnab_nsa",
+            orgao_id
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        Ok(nsa)
+    }
+
+    pub async fn salvar(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        remessa: &GravarRemessaCnab,
+        usuario: &str,
+    ) -> Result<i32> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO cadastro_remessa_cnab(orgao_id, ano, mes, layout, banco, nsa, data_pagamento,
+                total_pagamentos, valor_total, nome_arquivo, conteudo, usuario)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING id"#,
+            remessa.orgao_id,
+            remessa.ano,
+            remessa.mes,
+            remessa.layout,
+            remessa.banco,
+            remessa.nsa,
+            remessa.data_pagamento,
+            remessa.creditos.len() as i32,
+            remessa.valor_total,
+            remessa.nome_arquivo,
+            remessa.conteudo,
+            usuario
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        for credito in &remessa.creditos {
+            sqlx::query!(
+                r#"
+                INSERT INTO cadastro_remessa_cnab_item(remessa_id, folha_id, servidor_id, seu_numero,
+                    banco, agencia, conta, valor)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+                id,
+                credito.folha_id,
+                credito.servidor_id,
+                credito.seu_numero(),
+                credito.banco.as_deref().unwrap_or_default(),
+                credito.agencia.as_deref().unwrap_or_default(),
+                credito.conta.as_deref().unwrap_or_default(),
+                credito.valor
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(id)
+    }
+
+    /// "Seus números" dos créditos da remessa, para conferir o retorno
+    pub async fn seus_numeros(&self, pool: &PgPool, remessa_id: i32) -> Result<HashSet<String>> {
+        let numeros = sqlx::query_scalar!(
+            "SELECT seu_numero FROM cadastro_remessa_cnab_item WHERE remessa_id = $1",
+            remessa_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(numeros.into_iter().collect())
+    }
+
+    /// Grava a situação devolvida pelo banco. Um retorno antigo, só com o agendamento,
+    /// não desfaz o pagamento já efetivado
+    pub async fn atualizar_retorno(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        remessa_id: i32,
+        credito: &CreditoRetorno,
+    ) -> Result<()> {
+        let ocorrencias = credito.ocorrencias.concat();
+        sqlx::query!(
+            r#"
+            UPDATE cadastro_remessa_cnab_item
+            SET situacao = $3::VARCHAR, ocorrencias = $4, mensagem = $5, data_efetivacao = $6,
+                valor_pago = $7, retorno_em = now()
+            WHERE remessa_id = $1 AND seu_numero = $2
+                AND NOT (situacao = $8 AND $3::VARCHAR IN ($9, $10))"#,
+            remessa_id,
+            credito.seu_numero,
+            credito.situacao(),
+            (!ocorrencias.is_empty()).then_some(ocorrencias),
+            Some(credito.mensagem()).filter(|m| !m.is_empty()),
+            credito.data_efetivacao,
+            credito.valor_efetivado(),
+            CREDITO_PAGO,
+            CREDITO_AGENDADO,
+            CREDITO_ENVIADO
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Arquivo guardado: (nome, conteúdo)
+    pub async fn arquivo(&self, pool: &PgPool, id: i32) -> Result<(String, Vec<u8>)> {
+        let remessa = sqlx::query!(
+            "SELECT nome_arquivo, conteudo FROM cadastro_remessa_cnab WHERE id = $1",
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok((remessa.nome_arquivo, remessa.conteudo))
+    }
+}
+
+#[async_trait]
+impl Repository<RemessaCnab, i32> for RemessaCnabRepository {
+    type CreateInput = GerarRemessaCnab;
+    type UpdateInput = GerarRemessaCnab;
+
+    fn table_name(&self) -> &str {
+        "cadastro_remessa_cnab r"
+    }
+
+    fn id_column(&self) -> &str {
+        "r.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "r.id DESC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("org.nome", "ILIKE"),
+            ("r.nome_arquivo", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "r.id, r.orgao_id, r.ano, r.mes, r.layout, r.banco, r.nsa, r.data_pagamento,
+        r.total_pagamentos, r.valor_total, r.nome_arquivo, r.usuario, r.created_at,
+        org.nome AS org_nome,
+        (SELECT COUNT(*) FROM cadastro_remessa_cnab_item i
+            WHERE i.remessa_id = r.id AND i.situacao = 'PAGO') AS pagos,
+        (SELECT COUNT(*) FROM cadastro_remessa_cnab_item i
+            WHERE i.remessa_id = r.id AND i.situacao = 'REJEITADO') AS rejeitados"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_remessa_cnab r
+        INNER JOIN cadastro_orgao org ON org.id = r.orgao_id
+        "
+    }
+
+    async fn create(&self, _pool: &PgPool, _input: Self::CreateInput) -> Result<RemessaCnab> {
+        anyhow::bail!("Remessas CNAB são geradas a partir da competência")
+    }
+
+    async fn update(&self, _pool: &PgPool, _id: i32, _input: Self::UpdateInput) -> Result<RemessaCnab> {
+        anyhow::bail!("Remessas CNAB são geradas a partir da competência")
+    }
+
+    async fn delete(&self, _pool: &PgPool, _id: i32) -> Result<()> {
+        anyhow::bail!("Remessas CNAB não podem ser excluídas")
+    }
+}
+
+pub struct ItemRemessaCnabRepository;
+
+#[async_trait]
+impl Repository<ItemRemessaCnab, i64> for ItemRemessaCnabRepository {
+    type CreateInput = ();
+    type UpdateInput = ();
+
+    fn table_name(&self) -> &str {
+        "cadastro_remessa_cnab_item i"
+    }
+
+    fn id_column(&self) -> &str {
+        "i.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "r.ano DESC, r.mes DESC, r.nsa DESC, s.nome ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("s.nome", "ILIKE"),
+            ("s.cpf", "ILIKE"),
+            ("i.ocorrencias", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "i.id, i.remessa_id, i.folha_id, i.servidor_id, i.seu_numero, i.banco, i.agencia, i.conta,
+        i.valor, i.situacao, i.ocorrencias, i.mensagem, i.data_efetivacao, i.valor_pago, i.retorno_em,
+        r.orgao_id, r.ano, r.mes, r.nsa, org.nome AS org_nome, s.nome AS serv_nome, s.cpf"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_remessa_cnab_item i
+        INNER JOIN cadastro_remessa_cnab r ON r.id = i.remessa_id
+        INNER JOIN cadastro_orgao org ON org.id = r.orgao_id
+        INNER JOIN cadastro_servidor s ON s.id = i.servidor_id
+        "
+    }
+
+    async fn create(&self, _pool: &PgPool, _input: Self::CreateInput) -> Result<ItemRemessaCnab> {
+        anyhow::bail!("Créditos são gerados com a remessa CNAB")
+    }
+
+    async fn update(&self, _pool: &PgPool, _id: i64, _input: Self::UpdateInput) -> Result<ItemRemessaCnab> {
+        anyhow::bail!("A situação do crédito vem do retorno do banco")
+    }
+
+    async fn delete(&self, _pool: &PgPool, _id: i64) -> Result<()> {
+        anyhow::bail!("Créditos da remessa não podem ser excluídos")
+    }
+}
+
+pub struct ConsignatariaRepository;
+
+impl ConsignatariaRepository {
+    /// Consignatária com contratos não pode ser excluída
+    pub async fn em_uso(&self, pool: &PgPool, id: i32) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM cadastro_consignacao WHERE consignataria_id = $1) AS "existe!""#,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+
+    /// A rubrica de desconto é exclusiva de uma consignatária
+    pub async fn rubrica_em_uso(&self, pool: &PgPool, rubrica_id: i32, ignorar_id: Option<i32>) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM cadastro_consignataria WHERE rubrica_id = $1 AND ($2::int IS NULL OR id <> $2)
+            ) AS "existe!""#,
+            rubrica_id,
+            ignorar_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+}
+
+#[async_trait]
+impl Repository<Consignataria, i32> for ConsignatariaRepository {
+    type CreateInput = CreateConsignataria;
+    type UpdateInput = UpdateConsignataria;
+
+    fn table_name(&self) -> &str {
+        "cadastro_consignataria c"
+    }
+
+    fn id_column(&self) -> &str {
+        "c.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "c.nome ASC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("c.nome", "ILIKE"),
+            ("c.cnpj", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "c.id, c.nome, c.cnpj, c.rubrica_id, c.is_active,
+        r.codigo AS rubrica_codigo, r.descricao AS rubrica_descricao"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_consignataria c
+        INNER JOIN cadastro_rubrica r ON r.id = c.rubrica_id
+        "
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Consignataria> {
+        let id = sqlx::query_scalar!(
+            r#"INSERT INTO cadastro_consignataria(nome, cnpj, rubrica_id, is_active)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id"#,
+            input.nome,
+            input.cnpj,
+            input.rubrica_id,
+            input.is_active
+        )
+        .fetch_one(pool)
+        .await?;
+
+        self.get_by_id(pool, id).await
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<Consignataria> {
+        sqlx::query!(
+            r#"
+            UPDATE cadastro_consignataria
+            SET
+                nome = $1,
+                cnpj = $2,
+                rubrica_id = $3,
+                is_active = $4
+            WHERE id = $5"#,
+            input.nome,
+            input.cnpj,
+            input.rubrica_id,
+            input.is_active,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        self.get_by_id(pool, id).await
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_consignataria WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Base da margem consignável: proventos e descontos obrigatórios da última folha do
+/// servidor já calculada e o percentual do órgão
+pub struct BaseMargem {
+    pub folha_id: i64,
+    pub ano: i32,
+    pub mes: i32,
+    pub bruto: BigDecimal,
+    pub descontos_obrigatorios: BigDecimal,
+    pub percentual: BigDecimal,
+}
+
+pub struct ConsignacaoRepository;
+
+impl ConsignacaoRepository {
+    /// Trava o servidor até o fim da transação: averbações e reservas na margem dele
+    /// passam uma de cada vez, cada uma vendo o que a anterior comprometeu
+    pub async fn travar_margem(&self, tx: &mut Transaction<'_, Postgres>, servidor_id: i32) -> Result<()> {
+        sqlx::query_scalar!("SELECT id FROM cadastro_servidor WHERE id = $1 FOR UPDATE", servidor_id)
+            .fetch_one(&mut **tx)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn base_margem(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        servidor_id: i32,
+    ) -> Result<Option<BaseMargem>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT f.id, f.ano, f.mes, o.margem_consignavel,
+                COALESCE(SUM(i.valor) FILTER (WHERE r.tipo = 'P'), 0) AS "bruto!",
+                COALESCE(SUM(i.valor) FILTER (
+                    WHERE r.tipo = 'D' AND (r.codigo_sistema IS NOT NULL OR r.pensao_alimenticia)
+                ), 0) AS "obrigatorios!"
+            FROM cadastro_folha f
+            INNER JOIN cadastro_orgao o ON o.id = f.orgao_id
+            INNER JOIN cadastro_competencia c
+                ON c.orgao_id = f.orgao_id AND c.ano = f.ano AND c.mes = f.mes
+            LEFT JOIN cadastro_folha_item i ON i.folha_id = f.id
+            LEFT JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE f.servidor_id = $1 AND f.tipo_folha = 0 AND c.situacao <> 'ABERTA'
+            GROUP BY f.id, o.margem_consignavel
+            ORDER BY f.ano DESC, f.mes DESC, f.id DESC
+            LIMIT 1
+            "#,
+            servidor_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(row.map(|row| BaseMargem {
+            folha_id: row.id,
+            ano: row.ano,
+            mes: row.mes,
+            bruto: row.bruto,
+            descontos_obrigatorios: row.obrigatorios,
+            percentual: row.margem_consignavel,
+        }))
+    }
+
+    /// Soma das parcelas dos contratos ativos que ainda têm parcela a partir da competência
+    pub async fn comprometida(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        servidor_id: i32,
+        ano: i32,
+        mes: i32,
+        ignorar_id: Option<i32>,
+    ) -> Result<BigDecimal> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(valor_parcela), 0)::numeric(15, 2) AS "total!"
+            FROM cadastro_consignacao
+            WHERE servidor_id = $1 AND situacao = 'ATIVO'
+                AND ano_inicio * 12 + mes_inicio - 1 + total_parcelas - 1 >= $2 * 12 + $3 - 1
+                AND ($4::int IS NULL OR id <> $4)
+            "#,
+            servidor_id,
+            ano,
+            mes,
+            ignorar_id
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    /// Averbação e alteração na transação que travou a margem do servidor
+    pub async fn inserir(&self, tx: &mut Transaction<'_, Postgres>, input: &CreateConsignacao) -> Result<i32> {
+        Ok(sqlx::query_scalar!(
+            r#"INSERT INTO cadastro_consignacao(servidor_id, consignataria_id, contrato, valor_parcela,
+                total_parcelas, ano_inicio, mes_inicio, usuario)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id"#,
+            input.servidor_id,
+            input.consignataria_id,
+            input.contrato,
+            input.valor_parcela,
+            input.total_parcelas,
+            input.ano_inicio,
+            input.mes_inicio,
+            input.usuario
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    pub async fn alterar(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        input: &UpdateConsignacao,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE cadastro_consignacao
+            SET
+                contrato = $1,
+                valor_parcela = $2,
+                total_parcelas = $3,
+                ano_inicio = $4,
+                mes_inicio = $5,
+                situacao = $6
+            WHERE id = $7"#,
+            input.contrato,
+            input.valor_parcela,
+            input.total_parcelas,
+            input.ano_inicio,
+            input.mes_inicio,
+            input.situacao,
+            id
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn contrato_em_uso(
+        &self,
+        pool: &PgPool,
+        consignataria_id: i32,
+        contrato: &str,
+        ignorar_id: Option<i32>,
+    ) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM cadastro_consignacao
+                WHERE consignataria_id = $1 AND contrato = $2 AND ($3::int IS NULL OR id <> $3)
+            ) AS "existe!""#,
+            consignataria_id,
+            contrato,
+            ignorar_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+
+    /// Contrato com parcela já lançada em folha não pode ser excluído, só cancelado
+    pub async fn em_uso(&self, pool: &PgPool, id: i32) -> Result<bool> {
+        let existe = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM cadastro_consignacao_parcela WHERE consignacao_id = $1) AS "existe!""#,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(existe)
+    }
+
+    /// Contratos ativos do servidor; quais têm parcela na competência é decidido no cálculo
+    pub async fn ativos_do_servidor(&self, pool: &PgPool, servidor_id: i32) -> Result<Vec<ParcelaConsignacao>> {
+        Ok(sqlx::query_as!(
+            ParcelaConsignacao,
+            r#"
+            SELECT c.id AS consignacao_id, ct.rubrica_id, c.valor_parcela, c.total_parcelas,
+                c.ano_inicio, c.mes_inicio
+            FROM cadastro_consignacao c
+            INNER JOIN cadastro_consignataria ct ON ct.id = c.consignataria_id
+            WHERE c.servidor_id = $1 AND c.situacao = 'ATIVO'
+            ORDER BY c.id
+            "#,
+            servidor_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Troca as parcelas lançadas na folha pelas informadas (contrato e número da parcela)
+    pub async fn lancar_parcelas(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        folha_id: i64,
+        parcelas: &[(ParcelaConsignacao, i32)],
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"DELETE FROM cadastro_folha_item
+            WHERE id IN (SELECT folha_item_id FROM cadastro_consignacao_parcela WHERE folha_id = $1)"#,
+            folha_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        for (parcela, numero) in parcelas {
+            let item_id = sqlx::query_scalar!(
+                r#"INSERT INTO cadastro_folha_item(folha_id, rubrica_id, referencia, valor, parcela)
+                VALUES ($1, $2, 0, $3, $4)
+                RETURNING id"#,
+                folha_id,
+                parcela.rubrica_id,
+                parcela.valor_parcela,
+                format!("{}/{}", numero, parcela.total_parcelas)
+            )
+            .fetch_one(&mut **tx)
+            .await?;
+
+            sqlx::query!(
+                r#"INSERT INTO cadastro_consignacao_parcela(consignacao_id, folha_id, folha_item_id, numero, valor)
+                VALUES ($1, $2, $3, $4, $5)"#,
+                parcela.consignacao_id,
+                folha_id,
+                item_id,
+                numero,
+                parcela.valor_parcela
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Repository<Consignacao, i32> for ConsignacaoRepository {
+    type CreateInput = CreateConsignacao;
+    type UpdateInput = UpdateConsignacao;
+
+    fn table_name(&self) -> &str {
+        "cadastro_consignacao c"
+    }
+
+    fn id_column(&self) -> &str {
+        "c.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "s.nome ASC, c.ano_inicio DESC, c.mes_inicio DESC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("s.nome", "ILIKE"),
+            ("s.cpf", "ILIKE"),
+            ("c.contrato", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "c.id, c.servidor_id, c.consignataria_id, c.contrato, c.valor_parcela, c.total_parcelas,
+        c.ano_inicio, c.mes_inicio, c.situacao, c.usuario, c.created_at,
+        s.nome AS serv_nome, s.cpf, ct.nome AS consignataria_nome,
+        (SELECT COUNT(*) FROM cadastro_consignacao_parcela p WHERE p.consignacao_id = c.id) AS parcelas_lancadas"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_consignacao c
+        INNER JOIN cadastro_servidor s ON s.id = c.servidor_id
+        INNER JOIN cadastro_consignataria ct ON ct.id = c.consignataria_id
+        "
+    }
+
+    async fn create(&self, pool: &PgPool, input: Self::CreateInput) -> Result<Consignacao> {
+        let mut tx = pool.begin().await?;
+        let id = self.inserir(&mut tx, &input).await?;
+        tx.commit().await?;
+        self.get_by_id(pool, id).await
+    }
+
+    async fn update(&self, pool: &PgPool, id: i32, input: Self::UpdateInput) -> Result<Consignacao> {
+        let mut tx = pool.begin().await?;
+        self.alterar(&mut tx, id, &input).await?;
+        tx.commit().await?;
+        self.get_by_id(pool, id).await
+    }
+
+    async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!("DELETE FROM cadastro_consignacao WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct CredencialConsignatariaRepository;
+
+impl CredencialConsignatariaRepository {
+    pub async fn get_by_consignataria(
+        &self,
+        pool: &PgPool,
+        consignataria_id: i32,
+    ) -> Result<Vec<CredencialConsignataria>> {
+        Ok(sqlx::query_as!(
+            CredencialConsignataria,
+            r#"
+            SELECT id, consignataria_id, chave, segredo_hash, descricao, is_active, usuario,
+                created_at, ultimo_uso
+            FROM cadastro_consignataria_credencial
+            WHERE consignataria_id = $1
+            ORDER BY is_active DESC, created_at DESC
+            "#,
+            consignataria_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Credencial ativa de consignatária ativa; as demais não autenticam
+    pub async fn get_ativa_by_chave(&self, pool: &PgPool, chave: &str) -> Result<Option<CredencialConsignataria>> {
+        Ok(sqlx::query_as!(
+            CredencialConsignataria,
+            r#"
+            SELECT cr.id, cr.consignataria_id, cr.chave, cr.segredo_hash, cr.descricao, cr.is_active,
+                cr.usuario, cr.created_at, cr.ultimo_uso
+            FROM cadastro_consignataria_credencial cr
+            INNER JOIN cadastro_consignataria ct ON ct.id = cr.consignataria_id
+            WHERE cr.chave = $1 AND cr.is_active AND ct.is_active
+            "#,
+            chave
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn create(
+        &self,
+        pool: &PgPool,
+        consignataria_id: i32,
+        chave: &str,
+        segredo_hash: &str,
+        descricao: Option<&str>,
+        usuario: &str,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO cadastro_consignataria_credencial(consignataria_id, chave, segredo_hash, descricao, usuario)
+            VALUES ($1, $2, $3, $4, $5)"#,
+            consignataria_id,
+            chave,
+            segredo_hash,
+            descricao,
+            usuario
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Desativa a credencial e devolve a consignatária dela
+    pub async fn revogar(&self, pool: &PgPool, id: i32) -> Result<i32> {
+        Ok(sqlx::query_scalar!(
+            r#"UPDATE cadastro_consignataria_credencial SET is_active = false
+            WHERE id = $1
+            RETURNING consignataria_id"#,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    pub async fn registrar_uso(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!(
+            "UPDATE cadastro_consignataria_credencial SET ultimo_uso = now() WHERE id = $1",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+pub struct ReservaMargemRepository;
+
+impl ReservaMargemRepository {
+    /// Soma das reservas ainda no prazo; a informada fica de fora (averbação com a própria reserva)
+    pub async fn reservada(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        servidor_id: i32,
+        ignorar_id: Option<i32>,
+    ) -> Result<BigDecimal> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(valor), 0)::numeric(15, 2) AS "total!"
+            FROM cadastro_margem_reserva
+            WHERE servidor_id = $1 AND situacao = 'RESERVADA' AND expira_em > now()
+                AND ($2::int IS NULL OR id <> $2)
+            "#,
+            servidor_id,
+            ignorar_id
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    pub async fn get_by_codigo(&self, pool: &PgPool, codigo: &str) -> Result<Option<ReservaMargem>> {
+        Ok(sqlx::query_as!(
+            ReservaMargem,
+            r#"
+            SELECT id, codigo, consignataria_id, servidor_id, valor, expira_em, situacao,
+                consignacao_id, created_at
+            FROM cadastro_margem_reserva
+            WHERE codigo = $1
+            "#,
+            codigo
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Reserva gravada na transação que travou a margem do servidor
+    pub async fn create(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        codigo: &str,
+        consignataria_id: i32,
+        servidor_id: i32,
+        valor: &BigDecimal,
+        expira_em: DateTime<Utc>,
+    ) -> Result<ReservaMargem> {
+        Ok(sqlx::query_as!(
+            ReservaMargem,
+            r#"
+            INSERT INTO cadastro_margem_reserva(codigo, consignataria_id, servidor_id, valor, expira_em)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, codigo, consignataria_id, servidor_id, valor, expira_em, situacao,
+                consignacao_id, created_at
+            "#,
+            codigo,
+            consignataria_id,
+            servidor_id,
+            valor,
+            expira_em
+        )
+        .fetch_one(&mut **tx)
+        .await?)
+    }
+
+    pub async fn cancelar(&self, pool: &PgPool, id: i32) -> Result<()> {
+        sqlx::query!(
+            "UPDATE cadastro_margem_reserva SET situacao = $2 WHERE id = $1 AND situacao = $3",
+            id,
+            RESERVA_CANCELADA,
+            RESERVA_RESERVADA
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Baixa a reserva com o contrato averbado a partir dela. Reserva já utilizada,
+    /// cancelada ou vencida é recusada, e a transação da averbação é desfeita
+    pub async fn utilizar(&self, tx: &mut Transaction<'_, Postgres>, id: i32, consignacao_id: i32) -> Result<()> {
+        let resultado = sqlx::query!(
+            r#"UPDATE cadastro_margem_reserva SET situacao = $3, consignacao_id = $2
+            WHERE id = $1 AND situacao = $4 AND expira_em > now()"#,
+            id,
+            consignacao_id,
+            RESERVA_UTILIZADA,
+            RESERVA_RESERVADA
+        )
+        .execute(&mut **tx)
+        .await?;
+        if resultado.rows_affected() != 1 {
+            return Err(AppError::Conflict(
+                "A reserva de margem já foi utilizada, cancelada ou venceu".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+pub struct ConsultaMargemRepository;
+
+impl ConsultaMargemRepository {
+    pub async fn registrar(&self, pool: &PgPool, input: &CreateConsultaMargem) -> Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO cadastro_margem_consulta(credencial_id, consignataria_id, operacao, cpf,
+                reserva_codigo, status, disponivel, mensagem, ip)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#,
+            input.credencial_id,
+            input.consignataria_id,
+            input.operacao,
+            input.cpf,
+            input.reserva_codigo,
+            input.status,
+            input.disponivel,
+            input.mensagem,
+            input.ip
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Repository<ConsultaMargem, i64> for ConsultaMargemRepository {
+    type CreateInput = CreateConsultaMargem;
+    type UpdateInput = CreateConsultaMargem;
+
+    fn table_name(&self) -> &str {
+        "cadastro_margem_consulta l"
+    }
+
+    fn id_column(&self) -> &str {
+        "l.id"
+    }
+
+    fn order_by_column(&self) -> &str {
+        "l.created_at DESC, l.id DESC"
+    }
+
+    fn searchable_fields(&self) -> &[(&str, &str)] {
+        &[
+            ("l.cpf", "ILIKE"),
+            ("l.reserva_codigo", "ILIKE"),
+            ("l.ip", "ILIKE"),
+        ]
+    }
+
+    fn select_clause(&self) -> &str {
+        "l.id, l.credencial_id, l.consignataria_id, l.operacao, l.cpf, l.reserva_codigo, l.status,
+        l.disponivel, l.mensagem, l.ip, l.created_at,
+        ct.nome AS consignataria_nome, cr.chave AS credencial_chave"
+    }
+
+    fn from_clause(&self) -> &str {
+        "cadastro_margem_consulta l
+        LEFT JOIN cadastro_consignataria ct ON ct.id = l.consignataria_id
+        LEFT JOIN cadastro_consignataria_credencial cr ON cr.id = l.credencial_id
+        "
+    }
+
+    async fn create(&self, _pool: &PgPool, _input: Self::CreateInput) -> Result<ConsultaMargem> {
+        anyhow::bail!("O log da API de margem é gravado pelas próprias chamadas")
+    }
+
+    async fn update(&self, _pool: &PgPool, _id: i64, _input: Self::UpdateInput) -> Result<ConsultaMargem> {
+        anyhow::bail!("O log da API de margem não pode ser alterado")
+    }
+
+    async fn delete(&self, _pool: &PgPool, _id: i64) -> Result<()> {
+        anyhow::bail!("O log da API de margem não pode ser excluído")
+    }
+}
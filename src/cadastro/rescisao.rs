@@ -0,0 +1,125 @@
+use bigdecimal::BigDecimal;
+use chrono::{Datelike, Months, NaiveDate};
+
+use crate::cadastro::calculo::arredondar;
+
+/// Códigos de sistema das rubricas lançadas na rescisão (cadastro_rubrica.codigo_sistema);
+/// o 13º proporcional usa as rubricas do 13º
+pub const SISTEMA_SALDO_SALARIO: &str = "SALDO_SALARIO";
+pub const SISTEMA_FERIAS_VENCIDAS: &str = "FERIAS_VENCIDAS";
+pub const SISTEMA_FERIAS_PROPORCIONAIS: &str = "FERIAS_PROPORCIONAIS";
+pub const SISTEMA_TERCO_FERIAS_RESCISAO: &str = "TERCO_FERIAS_RESCISAO";
+
+/// Motivo 01 da tabela 19 do eSocial: rescisão com justa causa, que não dá direito ao
+/// 13º nem às férias proporcionais
+pub const MOTIVO_JUSTA_CAUSA: &str = "01";
+
+/// Fração do mês a partir da qual ela conta um avo de férias (CLT, art. 146)
+const DIAS_AVO: i64 = 15;
+
+/// Dias de saldo de salário no mês do desligamento, em mês comercial de 30 dias: quem
+/// sai no último dia do mês recebe o mês inteiro
+pub fn dias_saldo_salario(desligamento: NaiveDate) -> i32 {
+    let ultimo_dia = desligamento
+        .checked_add_months(Months::new(1))
+        .and_then(|data| data.with_day(1))
+        .and_then(|data| data.pred_opt())
+        .is_some_and(|ultimo| ultimo == desligamento);
+    if ultimo_dia { 30 } else { (desligamento.day() as i32).min(30) }
+}
+
+/// A partir do mês do desligamento o servidor só recebe pela rescisão
+pub fn desligado_na_competencia(desligamento: Option<NaiveDate>, ano: i32, mes: i32) -> bool {
+    desligamento.is_some_and(|data| (ano, mes) >= (data.year(), data.month() as i32))
+}
+
+/// Salário / 30 por dia de saldo
+pub fn saldo_salario(salario: &BigDecimal, dias: i32) -> BigDecimal {
+    arredondar(&(salario * BigDecimal::from(dias) / BigDecimal::from(30)))
+}
+
+/// Avos de férias do período aquisitivo em curso no desligamento: cada mês completo e a
+/// fração de pelo menos 15 dias valem 1/12
+pub fn avos_ferias(inicio_periodo: NaiveDate, desligamento: NaiveDate) -> i32 {
+    let mut avos = 0;
+    for mes in 0..12 {
+        let Some(inicio) = inicio_periodo.checked_add_months(Months::new(mes)) else {
+            break;
+        };
+        if inicio > desligamento {
+            break;
+        }
+        let fim = inicio_periodo
+            .checked_add_months(Months::new(mes + 1))
+            .and_then(|proximo| proximo.pred_opt())
+            .map_or(desligamento, |fim| fim.min(desligamento));
+        if (fim - inicio).num_days() + 1 >= DIAS_AVO {
+            avos += 1;
+        }
+    }
+    avos
+}
+
+/// Férias proporcionais: salário / 12 por avo, descontados os dias do período que o
+/// servidor já gozou ou vendeu
+pub fn ferias_proporcionais(salario: &BigDecimal, avos: i32, dias_usados: i32) -> BigDecimal {
+    let devido = arredondar(&(salario * BigDecimal::from(avos) / BigDecimal::from(12)));
+    let usado = saldo_salario(salario, dias_usados);
+    if devido > usado { devido - usado } else { BigDecimal::from(0).with_scale(2) }
+}
+
+/// Terço constitucional sobre as férias indenizadas
+pub fn terco(ferias: &BigDecimal) -> BigDecimal {
+    arredondar(&(ferias / BigDecimal::from(3)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn dec(valor: &str) -> BigDecimal {
+        BigDecimal::from_str(valor).unwrap()
+    }
+
+    fn data(ano: i32, mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(ano, mes, dia).unwrap()
+    }
+
+    #[test]
+    fn test_saldo_salario() {
+        assert_eq!(dias_saldo_salario(data(2025, 3, 12)), 12);
+        assert_eq!(dias_saldo_salario(data(2025, 2, 28)), 30);
+        assert_eq!(dias_saldo_salario(data(2025, 3, 31)), 30);
+        assert_eq!(dias_saldo_salario(data(2025, 3, 30)), 30);
+        assert_eq!(saldo_salario(&dec("3000.00"), 12), dec("1200.00"));
+    }
+
+    #[test]
+    fn test_desligado_na_competencia() {
+        let desligamento = Some(data(2025, 3, 12));
+        assert!(!desligado_na_competencia(desligamento, 2025, 2));
+        assert!(desligado_na_competencia(desligamento, 2025, 3));
+        assert!(desligado_na_competencia(desligamento, 2026, 1));
+        assert!(!desligado_na_competencia(None, 2025, 3));
+    }
+
+    #[test]
+    fn test_avos_ferias() {
+        // período de 10/01: até 09/06 são 5 meses completos; até 24/06, mais 15 dias
+        assert_eq!(avos_ferias(data(2025, 1, 10), data(2025, 6, 9)), 5);
+        assert_eq!(avos_ferias(data(2025, 1, 10), data(2025, 6, 23)), 5);
+        assert_eq!(avos_ferias(data(2025, 1, 10), data(2025, 6, 24)), 6);
+        assert_eq!(avos_ferias(data(2025, 1, 10), data(2025, 1, 20)), 0);
+        assert_eq!(avos_ferias(data(2025, 1, 10), data(2026, 3, 1)), 12);
+    }
+
+    #[test]
+    fn test_ferias_proporcionais() {
+        assert_eq!(ferias_proporcionais(&dec("3000.00"), 5, 0), dec("1250.00"));
+        assert_eq!(ferias_proporcionais(&dec("3000.00"), 5, 10), dec("250.00"));
+        assert_eq!(ferias_proporcionais(&dec("3000.00"), 2, 10), dec("0.00"));
+        assert_eq!(terco(&dec("1250.00")), dec("416.67"));
+    }
+}
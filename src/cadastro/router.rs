@@ -1,22 +1,82 @@
 use axum::{
     Router,
-    routing::{delete, get, post},
+    extract::DefaultBodyLimit,
+    routing::{delete, get, post, put},
 };
 
-use crate::{cadastro::view, state::SharedState};
+use crate::{
+    cadastro::{autenticacao::CAMINHO_AUTENTICACAO, view},
+    state::SharedState,
+};
 
-pub fn router() -> Router<SharedState> {
-    Router::new().merge(router_folha()).merge(uf_router())
+/// Rotas da folha, da competência e dos cadastros, que checam as permissões do usuário
+/// logado (ficam atrás do middleware de autenticação)
+pub fn router_autenticado() -> Router<SharedState> {
+    Router::new()
+        .merge(router_folha())
+        .merge(uf_router())
+        .merge(municipio_router())
+        .merge(orgao_router())
+        .merge(servidor_router())
+        .merge(cargo_router())
+        .merge(setor_router())
+        .merge(departamento_router())
+        .merge(tipo_vinculo_router())
+        .merge(rubrica_router())
+        .merge(servidor_folha_router())
+        .merge(calculo_router())
+        .merge(competencia_router())
+        .merge(esocial_router())
+        .merge(cnab_router())
+        .merge(consignacao_router())
+}
+
+/// Rotas abertas, fora da autenticação de usuário (validação de contra-cheque por terceiros
+/// e API de margem, que autentica pela credencial da consignatária)
+pub fn router_publico() -> Router<SharedState> {
+    Router::new()
+        .route(CAMINHO_AUTENTICACAO, get(view::autenticar_contra_cheque))
+        .route(
+            &format!("{}/{{codigo}}", CAMINHO_AUTENTICACAO),
+            get(view::autenticar_contra_cheque_codigo),
+        )
+        .route("/api/consignacao/margem", get(view::api_consultar_margem))
+        .route("/api/consignacao/reserva", post(view::api_reservar_margem))
+        .route(
+            "/api/consignacao/reserva/{codigo}",
+            delete(view::api_cancelar_reserva),
+        )
 }
 
 fn router_folha() -> Router<SharedState> {
     Router::new()
         .route("/folha", get(view::list_folha))
+        .route("/informe-rendimentos", get(view::informe_rendimentos))
         .merge(api_folha_router())
 }
 
 fn api_folha_router() -> Router<SharedState> {
-    Router::new().route("/imprimir-contra-cheque/{id}", get(view::folha_api_by_id))
+    Router::new()
+        .route("/folha-api", post(view::create_folha))
+        .route(
+            "/folha-api/{id}",
+            get(view::folha_api_by_id)
+                .put(view::update_folha)
+                .delete(view::delete_folha),
+        )
+        .route("/imprimir-contra-cheque/{id}", get(view::imprimir_contra_cheque))
+        .route("/contra-cheque-lote", post(view::gerar_lote_contra_cheque))
+        .route("/contra-cheque-lote/{id}", get(view::lote_contra_cheque_api))
+        .route(
+            "/contra-cheque-lote/{id}/download",
+            get(view::download_lote_contra_cheque),
+        )
+        .route("/folha-item-api", post(view::create_folha_item))
+        .route("/folha-item-api/{folha_id}", get(view::folha_item_api))
+        .route(
+            "/folha-item/{id}",
+            put(view::update_folha_item).delete(view::delete_folha_item),
+        )
 }
 
 fn uf_router() -> Router<SharedState> {
@@ -29,5 +89,264 @@ fn uf_router() -> Router<SharedState> {
 }
 
 fn api_uf_router() -> Router<SharedState> {
-    Router::new().route("/uf-api", get(view::uf_api))
+    Router::new()
+        .route("/uf-api", get(view::uf_api))
+}
+
+fn municipio_router() -> Router<SharedState> {
+    Router::new()
+        .route("/municipio", get(view::list_municipio))
+        .route("/municipio-form", get(view::municipio_form).post(view::create_municipio))
+        .route("/municipio-form/{id}", get(view::get_municipio).post(view::update_municipio))
+        .route("/municipio/{id}", delete(view::delete_municipio))
+        .route(
+            "/municipio/importar-ibge",
+            post(view::importar_ibge_municipio)
+                // a planilha completa (5.570 municípios) tem por volta de 1 MB
+                .layer(DefaultBodyLimit::max(10 * 1024 * 1024)),
+        )
+        .merge(api_municipio_router())
+}
+
+fn api_municipio_router() -> Router<SharedState> {
+    Router::new()
+        .route("/municipio-api", get(view::municipio_api))
+        .route("/municipio-api/{id}", get(view::municipio_api_by_id))
+}
+
+fn orgao_router() -> Router<SharedState> {
+    Router::new()
+        .route("/orgao", get(view::list_orgao))
+        .route("/orgao-form", get(view::orgao_form).post(view::create_orgao))
+        .route("/orgao-form/{id}", get(view::get_orgao).post(view::update_orgao))
+        .route("/orgao/{id}", delete(view::delete_orgao))
+        .route(
+            "/orgao/{id}/logo",
+            get(view::logo_orgao)
+                .post(view::upload_logo_orgao)
+                // arquivo de até 2 MB mais o envelope do multipart
+                .layer(DefaultBodyLimit::max(3 * 1024 * 1024)),
+        )
+        .route("/orgao/{id}/remover-logo", post(view::remover_logo_orgao))
+        .merge(api_orgao_router())
+}
+
+fn api_orgao_router() -> Router<SharedState> {
+    Router::new()
+        .route("/orgao-api", get(view::orgao_api))
+        .route("/orgao-api/{id}", get(view::orgao_api_by_id))
+}
+
+fn servidor_router() -> Router<SharedState> {
+    Router::new()
+        .route("/servidor", get(view::list_servidor))
+        .route("/servidor-form", get(view::servidor_form).post(view::create_servidor))
+        .route("/servidor-form/{id}", get(view::get_servidor).post(view::update_servidor))
+        .route("/servidor/{id}", delete(view::delete_servidor))
+        .route("/servidor/{id}/afastamento", post(view::create_afastamento))
+        .route("/afastamento/{id}/excluir", post(view::delete_afastamento))
+        .merge(api_servidor_router())
+}
+
+/// Eventos do servidor que lançam na folha
+fn servidor_folha_router() -> Router<SharedState> {
+    Router::new()
+        .route("/servidor/{id}/alteracao-salarial", post(view::create_alteracao_salarial))
+        .route("/alteracao-salarial/{id}/excluir", post(view::delete_alteracao_salarial))
+        .route("/servidor/{id}/ferias", post(view::create_ferias))
+        .route("/ferias/{id}/cancelar", post(view::cancelar_ferias))
+        .route("/ferias/{id}/aviso", get(view::imprimir_aviso_ferias))
+        .route("/servidor/{id}/rescisao", post(view::create_rescisao))
+        .route("/servidor/{id}/rescisao/estornar", post(view::estornar_rescisao))
+        .route("/servidor/{id}/rescisao/termo", get(view::imprimir_termo_rescisao))
+}
+
+fn api_servidor_router() -> Router<SharedState> {
+    Router::new()
+        .route("/servidor-api", get(view::servidor_api))
+        .route("/servidor-api/{id}", get(view::servidor_api_by_id))
+}
+
+fn cargo_router() -> Router<SharedState> {
+    Router::new()
+        .route("/cargo", get(view::list_cargo))
+        .route("/cargo-form", get(view::cargo_form).post(view::create_cargo))
+        .route("/cargo-form/{id}", get(view::get_cargo).post(view::update_cargo))
+        .route("/cargo/{id}", delete(view::delete_cargo))
+        .merge(api_cargo_router())
+}
+
+fn api_cargo_router() -> Router<SharedState> {
+    Router::new()
+        .route("/cargo-api", get(view::cargo_api))
+        .route("/cargo-api/{id}", get(view::cargo_api_by_id))
+}
+
+fn setor_router() -> Router<SharedState> {
+    Router::new()
+        .route("/setor", get(view::list_setor))
+        .route("/setor-form", get(view::setor_form).post(view::create_setor))
+        .route("/setor-form/{id}", get(view::get_setor).post(view::update_setor))
+        .route("/setor/{id}", delete(view::delete_setor))
+        .merge(api_setor_router())
+}
+
+fn api_setor_router() -> Router<SharedState> {
+    Router::new()
+        .route("/setor-api", get(view::setor_api))
+        .route("/setor-api/{id}", get(view::setor_api_by_id))
+}
+
+fn departamento_router() -> Router<SharedState> {
+    Router::new()
+        .route("/departamento", get(view::list_departamento))
+        .route("/departamento-form", get(view::departamento_form).post(view::create_departamento))
+        .route("/departamento-form/{id}", get(view::get_departamento).post(view::update_departamento))
+        .route("/departamento/{id}", delete(view::delete_departamento))
+        .merge(api_departamento_router())
+}
+
+fn api_departamento_router() -> Router<SharedState> {
+    Router::new()
+        .route("/departamento-api", get(view::departamento_api))
+        .route("/departamento-api/{id}", get(view::departamento_api_by_id))
+}
+
+fn tipo_vinculo_router() -> Router<SharedState> {
+    Router::new()
+        .route("/tipo-vinculo", get(view::list_tipo_vinculo))
+        .route("/tipo-vinculo-form", get(view::tipo_vinculo_form).post(view::create_tipo_vinculo))
+        .route("/tipo-vinculo-form/{id}", get(view::get_tipo_vinculo).post(view::update_tipo_vinculo))
+        .route("/tipo-vinculo/{id}", delete(view::delete_tipo_vinculo))
+        .merge(api_tipo_vinculo_router())
+}
+
+fn api_tipo_vinculo_router() -> Router<SharedState> {
+    Router::new()
+        .route("/tipo-vinculo-api", get(view::tipo_vinculo_api))
+        .route("/tipo-vinculo-api/{id}", get(view::tipo_vinculo_api_by_id))
+}
+
+fn rubrica_router() -> Router<SharedState> {
+    Router::new()
+        .route("/rubrica", get(view::list_rubrica))
+        .route("/rubrica-form", get(view::rubrica_form).post(view::create_rubrica))
+        .route("/rubrica-form/{id}", get(view::get_rubrica).post(view::update_rubrica))
+        .route("/rubrica/{id}", delete(view::delete_rubrica))
+        .merge(api_rubrica_router())
+}
+
+fn api_rubrica_router() -> Router<SharedState> {
+    Router::new().route("/rubrica-api", get(view::rubrica_api))
+}
+
+fn calculo_router() -> Router<SharedState> {
+    Router::new()
+        .route("/folha/{id}/calcular", post(view::calcular_folha))
+        .route("/folha/{id}/calcular-inss", post(view::calcular_inss_folha))
+        .route("/folha/{id}/calcular-irrf", post(view::calcular_irrf_folha))
+        .route("/calculo/inss-preview", get(view::preview_inss))
+        .route("/calculo/irrf-preview", get(view::preview_irrf))
+        .route(
+            "/tabela-inss-api",
+            get(view::tabela_inss_api).post(view::create_faixa_inss),
+        )
+        .route(
+            "/tabela-inss/{id}",
+            put(view::update_faixa_inss).delete(view::delete_faixa_inss),
+        )
+        .route(
+            "/tabela-irrf-api",
+            get(view::tabela_irrf_api).post(view::create_faixa_irrf),
+        )
+        .route(
+            "/tabela-irrf/{id}",
+            put(view::update_faixa_irrf).delete(view::delete_faixa_irrf),
+        )
+        .route("/parametro-irrf-api", post(view::create_parametro_irrf))
+        .route(
+            "/parametro-irrf/{id}",
+            put(view::update_parametro_irrf).delete(view::delete_parametro_irrf),
+        )
+}
+
+fn competencia_router() -> Router<SharedState> {
+    Router::new()
+        .route(
+            "/competencia",
+            get(view::list_competencia).post(view::abrir_competencia),
+        )
+        .route("/competencia/{id}/calcular", post(view::calcular_competencia))
+        .route("/decimo-terceiro/calcular", post(view::calcular_decimo_terceiro))
+        .route(
+            "/gerar-folha",
+            get(view::previa_gerar_folha).post(view::gerar_folha),
+        )
+        .route("/competencia/{id}/fechar", post(view::fechar_competencia))
+        .route("/competencia/{id}/reabrir", post(view::reabrir_competencia))
+        .route("/competencia/{id}/esocial", post(view::gerar_esocial_competencia))
+        .route("/competencia/{id}/cnab", post(view::gerar_remessa_cnab))
+        .route("/competencia-api", get(view::competencia_api))
+        .route(
+            "/competencia-api/{id}/historico",
+            get(view::competencia_historico_api),
+        )
+}
+
+fn esocial_router() -> Router<SharedState> {
+    Router::new()
+        .route("/esocial", get(view::list_esocial))
+        .route("/esocial/zip", get(view::download_zip_esocial))
+        .route("/esocial/{id}/xml", get(view::download_xml_esocial))
+        .route("/esocial/{id}/validar", post(view::validar_esocial))
+        .route("/esocial-fila", get(view::list_esocial_fila))
+        .route("/esocial-fila/gerar", post(view::gerar_esocial_fila))
+        .route("/esocial-fila/{id}/gerar", post(view::regerar_esocial_fila))
+        .route("/esocial-fila/{id}/xml", get(view::download_xml_esocial_fila))
+}
+
+fn cnab_router() -> Router<SharedState> {
+    Router::new()
+        .route("/cnab", get(view::list_remessa_cnab))
+        .route("/cnab/conciliacao", get(view::list_conciliacao_cnab))
+        .route("/cnab/{id}/arquivo", get(view::download_remessa_cnab))
+        .route("/cnab/{id}/retorno", post(view::importar_retorno_cnab))
+}
+
+fn consignacao_router() -> Router<SharedState> {
+    Router::new()
+        .route("/consignataria", get(view::list_consignataria))
+        .route(
+            "/consignataria-form",
+            get(view::consignataria_form).post(view::create_consignataria),
+        )
+        .route(
+            "/consignataria-form/{id}",
+            get(view::get_consignataria).post(view::update_consignataria),
+        )
+        .route("/consignataria/{id}", delete(view::delete_consignataria))
+        .route("/consignataria-api", get(view::consignataria_api))
+        .route("/consignacao", get(view::list_consignacao))
+        .route(
+            "/consignacao-form",
+            get(view::consignacao_form).post(view::create_consignacao),
+        )
+        .route(
+            "/consignacao-form/{id}",
+            get(view::get_consignacao).post(view::update_consignacao),
+        )
+        .route("/consignacao/{id}", delete(view::delete_consignacao))
+        .route(
+            "/consignacao-api/margem/{servidor_id}",
+            get(view::margem_consignavel_api),
+        )
+        .route(
+            "/consignataria/{id}/credencial",
+            post(view::gerar_credencial_consignataria),
+        )
+        .route(
+            "/consignataria-credencial/{id}/revogar",
+            post(view::revogar_credencial_consignataria),
+        )
+        .route("/consignacao/consultas", get(view::list_consulta_margem))
 }
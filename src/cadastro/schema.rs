@@ -1,6 +1,12 @@
 use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use crate::cadastro::cnab::Credito;
+use crate::utils::serde_utils::{
+    bool_from_str, de_opt_date, de_opt_nullable_string, de_opt_string_to_i32, empty_as_none,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateFolha {
     pub orgao_id: i32,
@@ -16,16 +22,22 @@ pub struct CreateFolha {
     pub setor_id: i32,
     pub departamento_id: i32,
     pub vinculo_id: i32,
+    #[serde(default)]
+    pub dependentes_irrf: i32,
+    /// Tipo da folha (`FOLHA_*`); sem ele, mensal. A complementar recebe o número da
+    /// sequência na gravação
+    #[serde(default)]
+    pub tipo_folha: i16,
 }
 
-/// Schema para atualizar parcialmente
+/// Schema para atualizar parcialmente: o que não vier fica como está na folha
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateFolha {
     pub id: i64,
-    pub orgao_id: i32,
-    pub ano: i32,
-    pub mes: i32,
-    pub servidor_id: i32,
+    pub orgao_id: Option<i32>,
+    pub ano: Option<i32>,
+    pub mes: Option<i32>,
+    pub servidor_id: Option<i32>,
     pub salario: Option<BigDecimal>,
     pub base_fgts: Option<BigDecimal>,
     pub base_inss: Option<BigDecimal>,
@@ -35,33 +47,818 @@ pub struct UpdateFolha {
     pub setor_id: Option<i32>,
     pub departamento_id: Option<i32>,
     pub vinculo_id: Option<i32>,
+    pub dependentes_irrf: Option<i32>,
 }
 
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateUf {
     pub sigla: String,
-    pub nome: String
+    pub nome: String,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub codigo_ibge: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateUf {
     pub id: i32,
     pub sigla: Option<String>,
-    pub nome: Option<String>
+    pub nome: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub codigo_ibge: Option<i32>,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCargo {
+    pub nome: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cbo: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateCargo {
+    pub id: i32,
+    pub nome: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cbo: Option<String>,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSetor {
+    pub nome: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateSetor {
+    pub id: i32,
+    pub nome: String,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateDepartamento {
+    pub nome: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateDepartamento {
+    pub id: i32,
+    pub nome: String,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTipoVinculo {
+    pub nome: String,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub categoria_esocial: Option<i32>,
+    /// 1 = RGPS, 2 = RPPS; sem valor o vínculo fica no RGPS
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub regime_previdencia: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateTipoVinculo {
+    pub id: i32,
+    pub nome: String,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub categoria_esocial: Option<i32>,
+    /// 1 = RGPS, 2 = RPPS; sem valor o vínculo fica no RGPS
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub regime_previdencia: Option<i32>,
 }
 
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateMunicipio {
     pub uf_id: i32,
-    pub nome: String
+    pub nome: String,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub codigo_ibge: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateMunicipio {
     pub id: i32,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
     pub uf_id: Option<i32>,
-    pub nome: Option<String>
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub nome: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub codigo_ibge: Option<i32>,
 }
 
+/// Busca paginada de municípios, opcionalmente restrita a uma UF
+#[derive(Debug, Deserialize)]
+pub struct MunicipioParams {
+    pub find: Option<String>,
+    pub page: Option<i32>,
+    pub page_size: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub uf_id: Option<i32>,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateOrgao {
+    pub nome: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnpj: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cep: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub logradouro: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub numero: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub complemento: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bairro: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub municipio_id: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub telefone: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub is_active: bool,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub esocial_ambiente: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub esocial_tabela_rubrica: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub esocial_lotacao: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnab_banco: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnab_agencia: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnab_conta: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnab_convenio: Option<String>,
+    pub margem_consignavel: BigDecimal,
+}
+
+/// O formulário envia o cadastro completo; o logotipo é enviado à parte
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateOrgao {
+    pub id: i32,
+    pub nome: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnpj: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cep: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub logradouro: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub numero: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub complemento: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bairro: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub municipio_id: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub telefone: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub is_active: bool,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub esocial_ambiente: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub esocial_tabela_rubrica: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub esocial_lotacao: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnab_banco: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnab_agencia: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnab_conta: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnab_convenio: Option<String>,
+    pub margem_consignavel: BigDecimal,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateServidor {
+    pub nome: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub matricula: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cpf: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub pis: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_date")]
+    pub data_nascimento: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "de_opt_date")]
+    pub data_admissao: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cep: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub logradouro: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub numero: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub complemento: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bairro: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub municipio_id: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub banco: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub agencia: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub conta: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub tipo_conta: Option<String>,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub is_active: bool,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub orgao_id: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub cargo_id: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub vinculo_id: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub sexo: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub raca_cor: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub grau_instrucao: Option<String>,
+}
+
+/// O formulário envia o cadastro completo; campos vazios limpam o valor gravado
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateServidor {
+    pub id: i32,
+    pub nome: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub matricula: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cpf: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub pis: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_date")]
+    pub data_nascimento: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "de_opt_date")]
+    pub data_admissao: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cep: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub logradouro: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub numero: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub complemento: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bairro: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub municipio_id: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub banco: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub agencia: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub conta: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub tipo_conta: Option<String>,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub is_active: bool,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub orgao_id: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub cargo_id: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub vinculo_id: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub sexo: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub raca_cor: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub grau_instrucao: Option<String>,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRubrica {
+    pub codigo: String,
+    pub descricao: String,
+    pub tipo: String,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub incide_inss: bool,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub incide_irrf: bool,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub incide_fgts: bool,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub pensao_alimenticia: bool,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub informe_rendimentos: Option<String>,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub fixa: bool,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub is_active: bool,
+}
+
+/// Checkbox desmarcado não é enviado pelo formulário, por isso os flags não são Option
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateRubrica {
+    pub id: i32,
+    pub codigo: Option<String>,
+    pub descricao: Option<String>,
+    pub tipo: Option<String>,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub incide_inss: bool,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub incide_irrf: bool,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub incide_fgts: bool,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub pensao_alimenticia: bool,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub informe_rendimentos: Option<String>,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub fixa: bool,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub is_active: bool,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateFolhaItem {
+    pub folha_id: i64,
+    pub rubrica_id: i32,
+    pub referencia: Option<BigDecimal>,
+    pub valor: BigDecimal,
+    pub parcela: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateFolhaItem {
+    pub id: i64,
+    pub rubrica_id: Option<i32>,
+    pub referencia: Option<BigDecimal>,
+    pub valor: Option<BigDecimal>,
+    /// Ausente mantém a parcela; null ou vazio limpam
+    #[serde(default, deserialize_with = "de_opt_nullable_string")]
+    pub parcela: Option<Option<String>>,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateFaixaInss {
+    pub vigencia: NaiveDate,
+    pub limite: BigDecimal,
+    pub aliquota: BigDecimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateFaixaInss {
+    pub id: i32,
+    pub vigencia: Option<NaiveDate>,
+    pub limite: Option<BigDecimal>,
+    pub aliquota: Option<BigDecimal>,
+}
+
+/// Competência (mês/ano) usada para escolher a tabela vigente
+#[derive(Debug, Deserialize)]
+pub struct CompetenciaParams {
+    pub ano: i32,
+    pub mes: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateFaixaIrrf {
+    pub vigencia: NaiveDate,
+    pub limite: Option<BigDecimal>,
+    pub aliquota: BigDecimal,
+    pub deducao: BigDecimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateFaixaIrrf {
+    pub id: i32,
+    pub vigencia: Option<NaiveDate>,
+    pub limite: Option<BigDecimal>,
+    pub aliquota: Option<BigDecimal>,
+    pub deducao: Option<BigDecimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateParametroIrrf {
+    pub vigencia: NaiveDate,
+    pub deducao_dependente: BigDecimal,
+    pub desconto_simplificado: BigDecimal,
+    pub reducao_limite_isencao: Option<BigDecimal>,
+    pub reducao_valor_isencao: Option<BigDecimal>,
+    pub reducao_limite: Option<BigDecimal>,
+    pub reducao_constante: Option<BigDecimal>,
+    pub reducao_fator: Option<BigDecimal>,
+}
+
+/// Os campos da redução são gravados como vierem: None remove a redução da vigência
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateParametroIrrf {
+    pub id: i32,
+    pub vigencia: Option<NaiveDate>,
+    pub deducao_dependente: Option<BigDecimal>,
+    pub desconto_simplificado: Option<BigDecimal>,
+    pub reducao_limite_isencao: Option<BigDecimal>,
+    pub reducao_valor_isencao: Option<BigDecimal>,
+    pub reducao_limite: Option<BigDecimal>,
+    pub reducao_constante: Option<BigDecimal>,
+    pub reducao_fator: Option<BigDecimal>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InssPreviewParams {
+    pub ano: i32,
+    pub mes: i32,
+    pub base: BigDecimal,
+}
+
+/// Sem `inss`, o desconto é calculado pela tabela do INSS sobre o rendimento
+#[derive(Debug, Deserialize)]
+pub struct IrrfPreviewParams {
+    pub ano: i32,
+    pub mes: i32,
+    pub rendimento: BigDecimal,
+    pub inss: Option<BigDecimal>,
+    #[serde(default)]
+    pub dependentes: i32,
+    pub pensao: Option<BigDecimal>,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCompetencia {
+    pub orgao_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+}
+
+fn formato_lote_padrao() -> String {
+    "ZIP".to_string()
+}
+
+/// Filtro dos contra-cheques gerados em lote; setor e departamento são opcionais
+#[derive(Debug, Clone, Deserialize)]
+pub struct GerarLoteContraCheque {
+    pub orgao_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+    #[serde(default)]
+    pub setor_id: Option<i32>,
+    #[serde(default)]
+    pub departamento_id: Option<i32>,
+    /// Só as folhas de um tipo (`FOLHA_*`); sem ele, todas as da competência
+    #[serde(default)]
+    pub tipo_folha: Option<i16>,
+    /// ZIP (um PDF por servidor) ou PDF (todos num só arquivo)
+    #[serde(default = "formato_lote_padrao")]
+    pub formato: String,
+}
+
+/// Informe de rendimentos do ano: de um servidor (PDF) ou, sem ele, de todo o órgão
+/// no formato pedido. Sem órgão e ano, a tela só mostra o formulário
+#[derive(Debug, Clone, Deserialize)]
+pub struct InformeRendimentosParams {
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub orgao_id: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub ano: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub servidor_id: Option<i32>,
+    #[serde(default = "formato_lote_padrao")]
+    pub formato: String,
+}
+
+/// Código informado na página pública de validação do contra-cheque
+#[derive(Debug, Deserialize)]
+pub struct AutenticarContraChequeParams {
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub codigo: Option<String>,
+}
+
+/// Motivo da transição (obrigatório para reabrir)
+#[derive(Debug, Deserialize)]
+pub struct TransicaoCompetencia {
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub motivo: Option<String>,
+}
+
+/// Geração dos eventos do eSocial da competência; sem data de pagamento só sai o S-1200
+#[derive(Debug, Deserialize)]
+pub struct GerarEsocial {
+    #[serde(default, deserialize_with = "de_opt_date")]
+    pub data_pagamento: Option<NaiveDate>,
+}
+
+/// Geração da remessa CNAB 240 da competência
+#[derive(Debug, Deserialize)]
+pub struct GerarRemessaCnab {
+    pub layout: String,
+    #[serde(default, deserialize_with = "de_opt_date")]
+    pub data_pagamento: Option<NaiveDate>,
+}
+
+/// Busca paginada das remessas CNAB com os filtros da tela
+#[derive(Debug, Deserialize)]
+pub struct RemessaCnabParams {
+    pub find: Option<String>,
+    pub page: Option<i32>,
+    pub page_size: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub orgao_id: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub ano: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub mes: Option<i32>,
+}
+
+/// Conciliação dos créditos das remessas CNAB com os filtros da tela
+#[derive(Debug, Deserialize)]
+pub struct ConciliacaoCnabParams {
+    pub find: Option<String>,
+    pub page: Option<i32>,
+    pub page_size: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub orgao_id: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub ano: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub mes: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub remessa_id: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub situacao: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateConsignataria {
+    pub nome: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnpj: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub rubrica_id: Option<i32>,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub is_active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateConsignataria {
+    pub id: i32,
+    pub nome: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub cnpj: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub rubrica_id: Option<i32>,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub is_active: bool,
+}
+
+/// Novo contrato; o usuário vem da sessão, não do formulário
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateConsignacao {
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub servidor_id: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub consignataria_id: Option<i32>,
+    pub contrato: String,
+    pub valor_parcela: BigDecimal,
+    pub total_parcelas: i32,
+    pub ano_inicio: i32,
+    pub mes_inicio: i32,
+    /// Código da reserva de margem feita pela consignatária na API, se houver
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub reserva: Option<String>,
+    #[serde(skip_deserializing)]
+    pub usuario: Option<String>,
+}
+
+/// Servidor e consignatária não mudam depois de criado o contrato
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateConsignacao {
+    pub id: i32,
+    pub contrato: String,
+    pub valor_parcela: BigDecimal,
+    pub total_parcelas: i32,
+    pub ano_inicio: i32,
+    pub mes_inicio: i32,
+    pub situacao: String,
+}
+
+/// Busca paginada dos contratos de consignação com os filtros da tela
+#[derive(Debug, Deserialize)]
+pub struct ConsignacaoParams {
+    pub find: Option<String>,
+    pub page: Option<i32>,
+    pub page_size: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub servidor_id: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub consignataria_id: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub situacao: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCredencialConsignataria {
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub descricao: Option<String>,
+}
+
+/// Consulta de margem da API, pelo CPF do servidor
+#[derive(Debug, Deserialize)]
+pub struct ConsultarMargemParams {
+    pub cpf: String,
+}
+
+/// Pedido de reserva de margem da API
+#[derive(Debug, Deserialize)]
+pub struct ReservarMargem {
+    pub cpf: String,
+    pub valor_parcela: BigDecimal,
+}
+
+/// Cabeçalhos da chamada à API de margem usados na autenticação e no log
+#[derive(Debug)]
+pub struct OrigemApiMargem {
+    pub authorization: Option<String>,
+    pub ip: Option<String>,
+}
+
+/// Registro de uma chamada à API de margem
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CreateConsultaMargem {
+    pub credencial_id: Option<i32>,
+    pub consignataria_id: Option<i32>,
+    pub operacao: String,
+    pub cpf: Option<String>,
+    pub reserva_codigo: Option<String>,
+    pub status: i16,
+    pub disponivel: Option<BigDecimal>,
+    pub mensagem: Option<String>,
+    pub ip: Option<String>,
+}
+
+/// Busca paginada das folhas; sem o filtro de tipo, lista só as folhas mensais
+#[derive(Debug, Deserialize)]
+pub struct FolhaParams {
+    pub find: Option<String>,
+    pub page: Option<i32>,
+    pub page_size: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub tipo_folha: Option<i32>,
+}
+
+/// Afastamento lançado no cadastro do servidor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAfastamento {
+    pub data_inicio: NaiveDate,
+    #[serde(default, deserialize_with = "de_opt_date")]
+    pub data_fim: Option<NaiveDate>,
+    pub motivo: String,
+    #[serde(default, deserialize_with = "bool_from_str")]
+    pub reduz_avos: bool,
+}
+
+/// Novo salário do servidor a partir da competência, lançado no cadastro
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAlteracaoSalarial {
+    pub ano: i32,
+    pub mes: i32,
+    pub salario: BigDecimal,
+    pub motivo: String,
+}
+
+/// Férias (ou fração) programadas no cadastro do servidor; sem abono, os dias vêm vazios
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateFerias {
+    pub periodo_id: i32,
+    pub data_inicio: NaiveDate,
+    pub dias: i32,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub dias_abono: Option<i32>,
+}
+
+/// Desligamento do servidor a rescindir: data e motivo (tabela 19 do eSocial)
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateRescisao {
+    pub data_desligamento: NaiveDate,
+    pub motivo_desligamento: String,
+}
+
+/// Competência de origem e a nova competência da geração da folha mensal do órgão
+#[derive(Debug, Deserialize)]
+pub struct GerarFolha {
+    pub orgao_id: i32,
+    pub ano_origem: i32,
+    pub mes_origem: i32,
+    pub ano: i32,
+    pub mes: i32,
+}
+
+/// Parcela do 13º a calcular para os servidores do órgão, paga na competência informada
+#[derive(Debug, Deserialize)]
+pub struct CalcularDecimoTerceiro {
+    pub orgao_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+    pub parcela: i16,
+}
+
+/// Busca paginada do log da API de margem com os filtros da tela
+#[derive(Debug, Deserialize)]
+pub struct ConsultaMargemParams {
+    pub find: Option<String>,
+    pub page: Option<i32>,
+    pub page_size: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub consignataria_id: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub operacao: Option<String>,
+}
+
+/// Busca paginada dos eventos do eSocial com os filtros da tela
+#[derive(Debug, Deserialize)]
+pub struct EventoEsocialParams {
+    pub find: Option<String>,
+    pub page: Option<i32>,
+    pub page_size: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub orgao_id: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub ano: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub mes: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub tipo: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub situacao: Option<String>,
+}
+
+/// Busca paginada da fila de eventos não periódicos
+#[derive(Debug, Deserialize)]
+pub struct FilaEsocialParams {
+    pub find: Option<String>,
+    pub page: Option<i32>,
+    pub page_size: Option<i32>,
+    #[serde(default, deserialize_with = "de_opt_string_to_i32")]
+    pub orgao_id: Option<i32>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub tipo: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub situacao: Option<String>,
+}
+
+/// Órgão cuja fila de eventos pendentes será gerada
+#[derive(Debug, Deserialize)]
+pub struct GerarFilaEsocial {
+    pub orgao_id: i32,
+}
+
+/// Competência do órgão cujos eventos validados vão no ZIP
+#[derive(Debug, Deserialize)]
+pub struct ZipEsocialParams {
+    pub orgao_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+}
+
+/// Evento montado para gravar (gerar de novo substitui os eventos da competência)
+#[derive(Debug)]
+pub struct GravarEventoEsocial {
+    pub orgao_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+    pub tipo: &'static str,
+    pub servidor_id: i32,
+    pub total_folhas: i32,
+    pub id_evento: String,
+    pub xml: String,
+    pub situacao: &'static str,
+    pub erros: Option<String>,
+}
+
+/// Remessa CNAB montada para gravar, com um item por folha paga
+#[derive(Debug)]
+pub struct GravarRemessaCnab {
+    pub orgao_id: i32,
+    pub ano: i32,
+    pub mes: i32,
+    pub layout: &'static str,
+    pub banco: String,
+    pub nsa: i32,
+    pub data_pagamento: NaiveDate,
+    pub valor_total: BigDecimal,
+    pub nome_arquivo: String,
+    pub conteudo: Vec<u8>,
+    pub creditos: Vec<Credito>,
+}
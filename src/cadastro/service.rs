@@ -1,52 +1,332 @@
-use sqlx::PgPool;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use sqlx::{PgPool, Postgres, Transaction};
 
 use anyhow::{Ok, Result};
 use axum::Json;
+use axum::http::StatusCode;
+
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use chrono::{DateTime, Datelike, Local, NaiveDate, Utc};
+use serde::Serialize;
+use tracing::error;
+use uuid::Uuid;
 
 use crate::{
     cadastro::{
-        model::{Folha, Municipio, Uf},
-        repository::{FolhaRepository, MunicipioRepository, UfRepository},
-        schema::{CreateFolha, CreateMunicipio, CreateUf, UpdateFolha, UpdateMunicipio, UpdateUf},
+        calculo::{
+            self, EntradaIrrf, ResultadoInss, ResultadoIrrf, SISTEMA_INSS, SISTEMA_IRRF,
+        },
+        autenticacao,
+        cnab::{self, Layout, Pagador},
+        consignacao::{self, Margem},
+        decimo_terceiro::{
+            self, PeriodoAfastamento, SISTEMA_ADIANTAMENTO_13, SISTEMA_DECIMO_TERCEIRO,
+            SISTEMA_DESCONTO_ADIANTAMENTO_13, SISTEMA_INSS_13, SISTEMA_IRRF_13,
+        },
+        esocial::{self, Contrato, Empregador, EventoGerado, ValidadorXsd, Validacao},
+        ferias::{
+            self, SISTEMA_ABONO_FERIAS, SISTEMA_FERIAS, SISTEMA_TERCO_ABONO_FERIAS,
+            SISTEMA_TERCO_FERIAS,
+        },
+        geracao_folha::{self, DIAS_MES, SISTEMA_SALARIO},
+        rescisao::{
+            self, SISTEMA_FERIAS_PROPORCIONAIS, SISTEMA_FERIAS_VENCIDAS, SISTEMA_SALDO_SALARIO,
+            SISTEMA_TERCO_FERIAS_RESCISAO,
+        },
+        ibge, relatorio,
+        informe::{self, InformeRendimentos, LinhaInforme},
+        model::{
+            AutenticacaoContraCheque, CabecalhoContraCheque, Cargo, Departamento, EmissaoContraCheque,
+            COMPETENCIA_ABERTA, COMPETENCIA_CALCULADA, COMPETENCIA_FECHADA, COMPETENCIA_REABERTA,
+            Competencia, CompetenciaHistorico, ContraCheque, ESOCIAL_S1200, ESOCIAL_S1210,
+            EVENTO_GERADO, EVENTO_INVALIDO, EVENTO_VALIDADO, EventoEsocial, EventoFilaEsocial, FILA_GERADO,
+            FILA_REJEITADO, ResultadoFilaEsocial, PERM_ESOCIAL_GERAR, ResultadoEsocial, LOTE_CONCLUIDO, LOTE_ERRO, LOTE_FORMATO_PDF,
+            LOTE_FORMATO_ZIP, LOTE_PROCESSANDO, LoteContraCheque, FaixaInss, FaixaIrrf, Folha, FolhaDetalhe,
+            FolhaItem, Municipio, Orgao, PERM_COMPETENCIA_CALCULAR, PERM_COMPETENCIA_FECHAR,
+            PERM_COMPETENCIA_REABRIR, ParametroIrrf, RUBRICA_DESCONTO, RUBRICA_INFORMATIVA,
+            RUBRICA_PROVENTO, ResultadoImportacaoIbge, Rubrica, Servidor, Setor, Timbre, TipoVinculo, Uf, transicao_permitida, CONTA_CORRENTE,
+            CONTA_POUPANCA, CREDITO_AGENDADO, CREDITO_PAGO, CREDITO_REJEITADO, ItemRemessaCnab, PERM_CNAB_GERAR,
+            RemessaCnab, ResultadoRemessaCnab, ResultadoRetornoCnab, CONSIGNACAO_ATIVO, CONSIGNACAO_CANCELADO,
+            Consignacao, Consignataria, PERM_CONSIGNACAO_AVERBAR, PERM_SERVIDOR_CADASTRAR, PERM_TABELAS_CADASTRAR, API_MARGEM_CANCELAMENTO, API_MARGEM_CONSULTA,
+            API_MARGEM_RESERVA, ConsultaMargem, CredencialConsignataria, MargemConsultada, PERM_CONSIGNATARIA_CREDENCIAL,
+            RESERVA_RESERVADA, ReservaConcedida, ReservaMargem, Afastamento, BaseDecimoTerceiro,
+            DECIMO_TERCEIRO_ADIANTAMENTO, DECIMO_TERCEIRO_FINAL, FOLHA_MENSAL, ResultadoDecimoTerceiro,
+            AvisoFerias, FERIAS_CANCELADA, Ferias, PeriodoAquisitivo, FOLHA_RESCISAO, Rescisao, TermoRescisao,
+            FOLHA_COMPLEMENTAR, FOLHA_FERIAS, FOLHAS_DA_COMPETENCIA, descricao_tipo_folha,
+            AlteracaoSalarial, GERACAO_DESLIGADO, GERACAO_EXISTENTE, GERACAO_GERAR, GERACAO_INATIVO,
+            ItemFixo, ItemGerado, PERM_FOLHA_GERAR, PreviaGeracaoFolha, ServidorGeracao, TotaisCompetencia,
+        },
+        repository::{
+            AfastamentoRepository, AlteracaoSalarialRepository, FeriasRepository, PeriodoAquisitivoRepository, RescisaoRepository, CargoRepository, CompetenciaRepository, ConsignacaoRepository, ConsignatariaRepository, ConsultaMargemRepository, CredencialConsignatariaRepository, ReservaMargemRepository, DepartamentoRepository, EmissaoContraChequeRepository, EventoEsocialRepository, FilaEsocialRepository, FolhaItemRepository, FolhaRepository, ItemRemessaCnabRepository, LoteContraChequeRepository, MunicipioRepository, OrgaoRepository,
+            ParametroIrrfRepository, RemessaCnabRepository, RubricaRepository, ServidorRepository, SetorRepository, TabelaInssRepository,
+            TabelaIrrfRepository, TipoVinculoRepository, UfRepository,
+        },
+        schema::{
+            CalcularDecimoTerceiro, CreateAfastamento, CreateAlteracaoSalarial, GerarFolha, CreateFerias, CreateRescisao, ConciliacaoCnabParams, ConsignacaoParams, ConsultaMargemParams, CreateCargo, CreateConsignacao, CreateConsignataria, CreateConsultaMargem, CreateCredencialConsignataria, OrigemApiMargem, ReservarMargem, CreateCompetencia, CreateDepartamento, CreateFaixaInss, CreateFaixaIrrf, CreateFolha, CreateFolhaItem, CreateMunicipio, CreateOrgao,
+            CreateParametroIrrf, CreateRubrica, CreateServidor, CreateSetor, CreateTipoVinculo, CreateUf, EventoEsocialParams, FilaEsocialParams, GerarEsocial, GerarLoteContraCheque, GerarRemessaCnab, GravarRemessaCnab, InformeRendimentosParams, RemessaCnabParams,
+            GravarEventoEsocial, IrrfPreviewParams, UpdateFaixaInss, UpdateFaixaIrrf,
+            UpdateCargo, UpdateDepartamento, UpdateFolha, UpdateFolhaItem, UpdateMunicipio, UpdateOrgao, UpdateParametroIrrf, UpdateRubrica,
+            UpdateServidor, UpdateSetor, UpdateTipoVinculo, UpdateUf, UpdateConsignacao, UpdateConsignataria,
+        },
     },
+    core::{PERM_BASE_IMPORTAR, UserService},
+    error::{self, AppError},
+    middlewares::CurrentUser,
     repository::{PaginatedResponse, Repository},
+    utils::pdf::decimal_br,
+    utils::validator::{
+        EMAIL_RX, cnpj_valido, cpf_valido, normalizar_cnpj, pis_valido, somente_digitos,
+    },
 };
 
 pub struct FolhaService {
     repo: FolhaRepository,
+    item_repo: FolhaItemRepository,
+    emissao_repo: EmissaoContraChequeRepository,
+    orgao_repo: OrgaoRepository,
+    servidor_repo: ServidorRepository,
+    competencia: CompetenciaService,
 }
 
 impl FolhaService {
     pub fn new() -> Self {
         Self {
             repo: FolhaRepository,
+            item_repo: FolhaItemRepository,
+            emissao_repo: EmissaoContraChequeRepository,
+            orgao_repo: OrgaoRepository,
+            servidor_repo: ServidorRepository,
+            competencia: CompetenciaService::new(),
         }
     }
 
-    pub async fn get_by_id(&self, pool: &PgPool, id: i64) -> Result<Folha> {
-        Ok(self.repo.get_by_id(pool, id).await?)
+    /// Servidor desligado não tem folha mensal a partir do mês do desligamento: o saldo
+    /// de salário desse mês é pago na rescisão
+    async fn verificar_desligamento(&self, pool: &PgPool, servidor_id: i32, ano: i32, mes: i32) -> Result<()> {
+        let servidor = self.servidor_repo.get_by_id(pool, servidor_id).await?;
+        if let Some(desligamento) = servidor.data_desligamento
+            && rescisao::desligado_na_competencia(Some(desligamento), ano, mes)
+        {
+            return Err(AppError::Conflict(format!(
+                "{} foi desligado em {}: o mês do desligamento é pago na rescisão",
+                servidor.nome,
+                desligamento.format("%d/%m/%Y")
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Retorna a folha com os itens (proventos e descontos) que compõem o contra-cheque
+    pub async fn get_by_id(&self, pool: &PgPool, id: i64) -> Result<FolhaDetalhe> {
+        let folha = self.repo.get_by_id(pool, id).await?;
+        let itens = self.item_repo.get_by_folha(pool, id).await?;
+        Ok(FolhaDetalhe::new(folha, itens))
+    }
+
+    /// Folha, itens e cabeçalho (servidor e órgão) impressos no contra-cheque,
+    /// com a emissão que dá o código de verificação
+    pub async fn contra_cheque(&self, pool: &PgPool, id: i64) -> Result<ContraCheque> {
+        let detalhe = self.get_by_id(pool, id).await?;
+        let timbre = Arc::new(self.orgao_repo.timbre(pool, detalhe.folha.orgao_id).await?);
+        self.montar_contra_cheque(pool, detalhe, timbre).await
+    }
+
+    /// Contra-cheques de folhas do mesmo órgão, lendo o timbre uma única vez
+    pub async fn contra_cheques_do_orgao(
+        &self,
+        pool: &PgPool,
+        orgao_id: i32,
+        ids: &[i64],
+    ) -> Result<Vec<ContraCheque>> {
+        let timbre = Arc::new(self.orgao_repo.timbre(pool, orgao_id).await?);
+        let mut contra_cheques = Vec::with_capacity(ids.len());
+        for id in ids {
+            let detalhe = self.get_by_id(pool, *id).await?;
+            contra_cheques.push(self.montar_contra_cheque(pool, detalhe, timbre.clone()).await?);
+        }
+        Ok(contra_cheques)
+    }
+
+    async fn montar_contra_cheque(
+        &self,
+        pool: &PgPool,
+        detalhe: FolhaDetalhe,
+        timbre: Arc<Timbre>,
+    ) -> Result<ContraCheque> {
+        let cabecalho = self.repo.cabecalho_contra_cheque(pool, detalhe.folha.id).await?;
+        let emissao = self.emitir(pool, &cabecalho, &detalhe).await?;
+        Ok(ContraCheque {
+            timbre,
+            cabecalho,
+            detalhe,
+            emissao,
+        })
+    }
+
+    /// Reimpressões com os mesmos valores reaproveitam a última emissão (e o código);
+    /// se a folha mudou desde então, o documento recebe um código novo
+    async fn emitir(
+        &self,
+        pool: &PgPool,
+        cabecalho: &CabecalhoContraCheque,
+        detalhe: &FolhaDetalhe,
+    ) -> Result<EmissaoContraCheque> {
+        if let Some(ultima) = self.emissao_repo.ultima_da_folha(pool, detalhe.folha.id).await? {
+            let mesma = ultima.servidor_nome == cabecalho.serv_nome
+                && ultima.orgao_nome == cabecalho.org_nome
+                && ultima.ano == detalhe.folha.ano
+                && ultima.mes == detalhe.folha.mes
+                && ultima.tipo_folha == detalhe.folha.tipo_folha
+                && ultima.sequencia == detalhe.folha.sequencia
+                && ultima.total_proventos == detalhe.total_proventos
+                && ultima.total_descontos == detalhe.total_descontos
+                && ultima.valor_liquido == detalhe.valor_liquido;
+            if mesma {
+                return Ok(ultima);
+            }
+        }
+
+        self.emissao_repo
+            .create(pool, &autenticacao::gerar_codigo(), cabecalho, detalhe)
+            .await
     }
 
+    /// Contra-cheque da folha em PDF
+    pub async fn contra_cheque_pdf(&self, pool: &PgPool, id: i64) -> Result<Vec<u8>> {
+        let contra_cheque = self.contra_cheque(pool, id).await?;
+        relatorio::contra_cheque(&contra_cheque)
+    }
+
+    /// Folhas do 13º e de rescisão só são gravadas pelo próprio cálculo (e pelo estorno)
+    fn verificar_tipo(tipo_folha: i16) -> Result<()> {
+        if !FOLHAS_DA_COMPETENCIA.contains(&tipo_folha) {
+            return Err(AppError::InvalidInput(
+                "Folhas do 13º e de rescisão são geradas pelo próprio cálculo".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Mensal e férias são uma por servidor na competência; a complementar exige a mensal
+    /// e recebe o próximo número da sequência
     pub async fn create(&self, pool: &PgPool, input: CreateFolha) -> Result<Folha> {
-        Ok(self.repo.create(pool, input).await?)
+        Self::verificar_tipo(input.tipo_folha)?;
+        self.verificar_desligamento(pool, input.servidor_id, input.ano, input.mes)
+            .await?;
+
+        let mut tx = pool.begin().await?;
+        self.competencia
+            .travar_para_gravar(&mut tx, input.orgao_id, input.ano, input.mes, input.tipo_folha)
+            .await?;
+        let competencia = (input.ano, input.mes);
+        if input.tipo_folha == FOLHA_COMPLEMENTAR {
+            if !self
+                .repo
+                .existe_na_competencia(&mut tx, input.orgao_id, input.servidor_id, competencia, FOLHA_MENSAL)
+                .await?
+            {
+                return Err(AppError::InvalidInput(
+                    "A folha complementar exige a folha mensal do servidor na competência".to_string(),
+                )
+                .into());
+            }
+        } else {
+            self.verificar_unica(&mut tx, input.orgao_id, input.servidor_id, competencia, input.tipo_folha)
+                .await?;
+        }
+        let folha = self.repo.criar(&mut tx, input).await?;
+        tx.commit().await?;
+        Ok(folha)
     }
 
+    async fn verificar_unica(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        orgao_id: i32,
+        servidor_id: i32,
+        competencia: (i32, i32),
+        tipo_folha: i16,
+    ) -> Result<()> {
+        if self
+            .repo
+            .existe_na_competencia(tx, orgao_id, servidor_id, competencia, tipo_folha)
+            .await?
+        {
+            return Err(AppError::Conflict(format!(
+                "O servidor já tem folha {} na competência",
+                if tipo_folha == FOLHA_FERIAS { "de férias" } else { "mensal" }
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// A folha pode mudar de servidor ou de competência se o destino ainda não tem folha
+    /// do tipo; a complementar fica presa à sequência da mensal em que foi criada
     pub async fn update(&self, pool: &PgPool, id: i64, input: UpdateFolha) -> Result<Folha> {
-        Ok(self.repo.update(pool, id, input).await?)
+        let antes = self.repo.get_by_id(pool, id).await?;
+        Self::verificar_tipo(antes.tipo_folha)?;
+        let orgao_id = input.orgao_id.unwrap_or(antes.orgao_id);
+        let servidor_id = input.servidor_id.unwrap_or(antes.servidor_id);
+        let (ano, mes) = (input.ano.unwrap_or(antes.ano), input.mes.unwrap_or(antes.mes));
+        self.verificar_desligamento(pool, servidor_id, ano, mes).await?;
+
+        let mut tx = pool.begin().await?;
+        // a folha pode ter mudado de competência: as duas precisam de novo cálculo
+        self.competencia
+            .travar_para_gravar(&mut tx, antes.orgao_id, antes.ano, antes.mes, antes.tipo_folha)
+            .await?;
+        self.competencia
+            .travar_para_gravar(&mut tx, orgao_id, ano, mes, antes.tipo_folha)
+            .await?;
+        if (orgao_id, servidor_id, ano, mes) != (antes.orgao_id, antes.servidor_id, antes.ano, antes.mes) {
+            if antes.tipo_folha == FOLHA_COMPLEMENTAR {
+                return Err(AppError::Conflict(
+                    "A folha complementar não muda de servidor nem de competência: exclua e crie outra"
+                        .to_string(),
+                )
+                .into());
+            }
+            self.verificar_unica(&mut tx, orgao_id, servidor_id, (ano, mes), antes.tipo_folha)
+                .await?;
+        }
+        let folha = self.repo.alterar(&mut tx, id, input).await?;
+        tx.commit().await?;
+        Ok(folha)
     }
 
     pub async fn delete(&self, pool: &PgPool, id: i64) -> Result<()> {
-        Ok(self.repo.delete(pool, id).await?)
+        let folha = self.repo.get_by_id(pool, id).await?;
+        Self::verificar_tipo(folha.tipo_folha)?;
+
+        let mut tx = pool.begin().await?;
+        self.competencia
+            .travar_para_gravar(&mut tx, folha.orgao_id, folha.ano, folha.mes, folha.tipo_folha)
+            .await?;
+        self.repo.excluir(&mut tx, id).await?;
+        tx.commit().await?;
+        Ok(())
     }
 
+    /// Folhas de um dos tipos (`FOLHA_*`)
     pub async fn get_paginated(
         &self,
         pool: &PgPool,
         find: Option<&str>,
+        tipo_folha: i16,
         page: i32,
         page_size: i32,
     ) -> Result<PaginatedResponse<Folha>> {
-        Ok(self.repo.get_paginated(pool, find, page, page_size, None).await?)
+        let filtros = [("f.tipo_folha", tipo_folha.to_string())];
+        Ok(self
+            .repo
+            .get_paginated_filtrado(pool, find, page, page_size, &filtros)
+            .await?)
     }
 }
 
@@ -59,15 +339,31 @@ impl UfService {
         Self { repo: UfRepository }
     }
 
+    /// O código IBGE da UF tem 2 dígitos (11 = RO ... 53 = DF)
+    async fn validar_codigo_ibge(&self, pool: &PgPool, codigo: Option<i32>, id: Option<i32>) -> Result<()> {
+        let Some(codigo) = codigo else {
+            return Ok(());
+        };
+        if ibge::sigla_uf(codigo).is_none() {
+            return Err(AppError::InvalidInput(format!("Código IBGE de UF {} inválido", codigo)).into());
+        }
+        if self.repo.codigo_ibge_em_uso(pool, codigo, id).await? {
+            return Err(AppError::Conflict("Código IBGE já cadastrado para outra UF".to_string()).into());
+        }
+        Ok(())
+    }
+
     pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<Uf> {
         Ok(self.repo.get_by_id(pool, id).await?)
     }
 
     pub async fn create(&self, pool: &PgPool, input: CreateUf) -> Result<Uf> {
+        self.validar_codigo_ibge(pool, input.codigo_ibge, None).await?;
         Ok(self.repo.create(pool, input).await?)
     }
 
     pub async fn update(&self, pool: &PgPool, id: i32, input: UpdateUf) -> Result<Uf> {
+        self.validar_codigo_ibge(pool, input.codigo_ibge, Some(id)).await?;
         Ok(self.repo.update(pool, id, input).await?)
     }
 
@@ -86,6 +382,304 @@ impl UfService {
     }
 }
 
+pub struct CargoService {
+    repo: CargoRepository,
+}
+
+impl CargoService {
+    pub fn new() -> Self {
+        Self { repo: CargoRepository }
+    }
+
+    /// Nome obrigatório; o CBO é gravado só com os 6 dígitos
+    fn normalizar(nome: &mut String, cbo: &mut Option<String>) -> Result<()> {
+        *nome = nome.trim().to_string();
+        if nome.is_empty() {
+            return Err(AppError::InvalidInput("Nome do cargo é obrigatório".to_string()).into());
+        }
+        if let Some(valor) = cbo.as_mut() {
+            *valor = somente_digitos(valor);
+            if valor.len() != 6 {
+                return Err(AppError::InvalidInput("CBO deve ter 6 dígitos".to_string()).into());
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<Cargo> {
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    pub async fn create(&self, pool: &PgPool, usuario: &CurrentUser, mut input: CreateCargo) -> Result<Cargo> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Self::normalizar(&mut input.nome, &mut input.cbo)?;
+        Ok(self.repo.create(pool, input).await?)
+    }
+
+    pub async fn update(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        id: i32,
+        mut input: UpdateCargo,
+    ) -> Result<Cargo> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Self::normalizar(&mut input.nome, &mut input.cbo)?;
+        Ok(self.repo.update(pool, id, input).await?)
+    }
+
+    pub async fn delete(&self, pool: &PgPool, usuario: &CurrentUser, id: i32) -> Result<()> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        if self.repo.em_uso(pool, id).await? {
+            return Err(AppError::Conflict(
+                "Cargo usado em folhas de pagamento não pode ser excluído".to_string(),
+            )
+            .into());
+        }
+        Ok(self.repo.delete(pool, id).await?)
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        find: Option<&str>,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<Cargo>> {
+        Ok(self.repo.get_paginated(pool, find, page, page_size, None).await?)
+    }
+}
+
+pub struct SetorService {
+    repo: SetorRepository,
+}
+
+impl SetorService {
+    pub fn new() -> Self {
+        Self { repo: SetorRepository }
+    }
+
+    fn normalizar(nome: &mut String) -> Result<()> {
+        *nome = nome.trim().to_string();
+        if nome.is_empty() {
+            return Err(AppError::InvalidInput("Nome do setor é obrigatório".to_string()).into());
+        }
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<Setor> {
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    pub async fn create(&self, pool: &PgPool, usuario: &CurrentUser, mut input: CreateSetor) -> Result<Setor> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Self::normalizar(&mut input.nome)?;
+        Ok(self.repo.create(pool, input).await?)
+    }
+
+    pub async fn update(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        id: i32,
+        mut input: UpdateSetor,
+    ) -> Result<Setor> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Self::normalizar(&mut input.nome)?;
+        Ok(self.repo.update(pool, id, input).await?)
+    }
+
+    pub async fn delete(&self, pool: &PgPool, usuario: &CurrentUser, id: i32) -> Result<()> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        if self.repo.em_uso(pool, id).await? {
+            return Err(AppError::Conflict(
+                "Setor usado em folhas de pagamento não pode ser excluído".to_string(),
+            )
+            .into());
+        }
+        Ok(self.repo.delete(pool, id).await?)
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        find: Option<&str>,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<Setor>> {
+        Ok(self.repo.get_paginated(pool, find, page, page_size, None).await?)
+    }
+}
+
+pub struct DepartamentoService {
+    repo: DepartamentoRepository,
+}
+
+impl DepartamentoService {
+    pub fn new() -> Self {
+        Self { repo: DepartamentoRepository }
+    }
+
+    fn normalizar(nome: &mut String) -> Result<()> {
+        *nome = nome.trim().to_string();
+        if nome.is_empty() {
+            return Err(AppError::InvalidInput("Nome do departamento é obrigatório".to_string()).into());
+        }
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<Departamento> {
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    pub async fn create(&self, pool: &PgPool, usuario: &CurrentUser, mut input: CreateDepartamento) -> Result<Departamento> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Self::normalizar(&mut input.nome)?;
+        Ok(self.repo.create(pool, input).await?)
+    }
+
+    pub async fn update(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        id: i32,
+        mut input: UpdateDepartamento,
+    ) -> Result<Departamento> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Self::normalizar(&mut input.nome)?;
+        Ok(self.repo.update(pool, id, input).await?)
+    }
+
+    pub async fn delete(&self, pool: &PgPool, usuario: &CurrentUser, id: i32) -> Result<()> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        if self.repo.em_uso(pool, id).await? {
+            return Err(AppError::Conflict(
+                "Departamento usado em folhas de pagamento não pode ser excluído".to_string(),
+            )
+            .into());
+        }
+        Ok(self.repo.delete(pool, id).await?)
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        find: Option<&str>,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<Departamento>> {
+        Ok(self.repo.get_paginated(pool, find, page, page_size, None).await?)
+    }
+}
+
+pub struct TipoVinculoService {
+    repo: TipoVinculoRepository,
+}
+
+impl TipoVinculoService {
+    pub fn new() -> Self {
+        Self { repo: TipoVinculoRepository }
+    }
+
+    /// Nome obrigatório; a categoria segue a tabela 01 do eSocial (código de 3 dígitos)
+    fn normalizar(
+        nome: &mut String,
+        categoria_esocial: Option<i32>,
+        regime_previdencia: Option<i32>,
+    ) -> Result<()> {
+        *nome = nome.trim().to_string();
+        if nome.is_empty() {
+            return Err(AppError::InvalidInput("Nome do tipo de vínculo é obrigatório".to_string()).into());
+        }
+        if let Some(categoria) = categoria_esocial
+            && !(100..=999).contains(&categoria)
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Categoria eSocial {} inválida",
+                categoria
+            ))
+            .into());
+        }
+        if let Some(regime) = regime_previdencia
+            && regime != 1
+            && regime != 2
+        {
+            return Err(AppError::InvalidInput(
+                "Regime de previdência deve ser 1 (RGPS) ou 2 (RPPS)".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<TipoVinculo> {
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    pub async fn create(&self, pool: &PgPool, usuario: &CurrentUser, mut input: CreateTipoVinculo) -> Result<TipoVinculo> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Self::normalizar(&mut input.nome, input.categoria_esocial, input.regime_previdencia)?;
+        Ok(self.repo.create(pool, input).await?)
+    }
+
+    pub async fn update(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        id: i32,
+        mut input: UpdateTipoVinculo,
+    ) -> Result<TipoVinculo> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Self::normalizar(&mut input.nome, input.categoria_esocial, input.regime_previdencia)?;
+        Ok(self.repo.update(pool, id, input).await?)
+    }
+
+    pub async fn delete(&self, pool: &PgPool, usuario: &CurrentUser, id: i32) -> Result<()> {
+        if !usuario.has_permission(PERM_TABELAS_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        if self.repo.em_uso(pool, id).await? {
+            return Err(AppError::Conflict(
+                "Tipo de vínculo usado em folhas de pagamento não pode ser excluído".to_string(),
+            )
+            .into());
+        }
+        Ok(self.repo.delete(pool, id).await?)
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        find: Option<&str>,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<TipoVinculo>> {
+        Ok(self.repo.get_paginated(pool, find, page, page_size, None).await?)
+    }
+}
+
 pub struct MunicipioService {
     repo: MunicipioRepository,
 }
@@ -97,11 +691,50 @@ impl MunicipioService {
         }
     }
 
+    /// O código IBGE do município tem 7 dígitos e começa pelo código da UF
+    async fn validar_codigo_ibge(
+        &self,
+        pool: &PgPool,
+        codigo: Option<i32>,
+        uf_id: i32,
+        id: Option<i32>,
+    ) -> Result<()> {
+        let Some(codigo) = codigo else {
+            return Ok(());
+        };
+        if !ibge::codigo_municipio_valido(codigo) {
+            return Err(AppError::InvalidInput(format!("Código IBGE de município {} inválido", codigo)).into());
+        }
+        if let Some(codigo_uf) = UfRepository.get_by_id(pool, uf_id).await?.codigo_ibge
+            && codigo / 100_000 != codigo_uf
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Código IBGE {} não pertence à UF do município",
+                codigo
+            ))
+            .into());
+        }
+        if self.repo.codigo_ibge_em_uso(pool, codigo, id).await? {
+            return Err(AppError::Conflict("Código IBGE já cadastrado para outro município".to_string()).into());
+        }
+        Ok(())
+    }
+
+    fn normalizar_nome(nome: &mut String) -> Result<()> {
+        *nome = nome.trim().to_string();
+        if nome.is_empty() {
+            return Err(AppError::InvalidInput("Nome do município é obrigatório".to_string()).into());
+        }
+        Ok(())
+    }
+
     pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<Municipio> {
         Ok(self.repo.get_by_id(pool, id).await?)
     }
 
-    pub async fn create(&self, pool: &PgPool, input: CreateMunicipio) -> Result<Municipio> {
+    pub async fn create(&self, pool: &PgPool, mut input: CreateMunicipio) -> Result<Municipio> {
+        Self::normalizar_nome(&mut input.nome)?;
+        self.validar_codigo_ibge(pool, input.codigo_ibge, input.uf_id, None).await?;
         Ok(self.repo.create(pool, input).await?)
     }
 
@@ -109,22 +742,4372 @@ impl MunicipioService {
         &self,
         pool: &PgPool,
         id: i32,
-        input: UpdateMunicipio,
+        mut input: UpdateMunicipio,
     ) -> Result<Municipio> {
+        if let Some(nome) = input.nome.as_mut() {
+            Self::normalizar_nome(nome)?;
+        }
+        let uf_id = match input.uf_id {
+            Some(uf_id) => uf_id,
+            None => self.repo.get_by_id(pool, id).await?.uf_id,
+        };
+        self.validar_codigo_ibge(pool, input.codigo_ibge, uf_id, Some(id)).await?;
         Ok(self.repo.update(pool, id, input).await?)
     }
 
     pub async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        if self.repo.em_uso(pool, id).await? {
+            return Err(AppError::Conflict(
+                "Município usado no endereço de servidores ou órgãos não pode ser excluído".to_string(),
+            )
+            .into());
+        }
         Ok(self.repo.delete(pool, id).await?)
     }
 
+    /// Importa a planilha de divisão territorial do IBGE (CSV). Tudo numa transação:
+    /// um arquivo com erro não grava nada, e reimportar o mesmo arquivo só atualiza
+    pub async fn importar_ibge(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        conteudo: &[u8],
+    ) -> Result<ResultadoImportacaoIbge> {
+        if !usuario.has_permission(PERM_BASE_IMPORTAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        let dtb = ibge::ler_dtb(conteudo)?;
+
+        let mut tx = pool.begin().await?;
+        let (uf_ids, ufs_inseridas) = UfRepository.upsert_ibge(&mut tx, &dtb.ufs).await?;
+        let (municipios_inseridos, municipios_atualizados) =
+            self.repo.upsert_ibge(&mut tx, &dtb.municipios, &uf_ids).await?;
+        tx.commit().await?;
+
+        Ok(ResultadoImportacaoIbge {
+            ufs_inseridas,
+            ufs_atualizadas: uf_ids.len() as i64 - ufs_inseridas,
+            municipios_inseridos,
+            municipios_atualizados,
+        })
+    }
+
+    /// Busca por nome do município ou da UF; com `uf_id` traz só os municípios da UF
     pub async fn get_paginated(
         &self,
         pool: &PgPool,
         find: Option<&str>,
+        uf_id: Option<i32>,
         page: i32,
         page_size: i32,
     ) -> Result<PaginatedResponse<Municipio>> {
-        Ok(self.repo.get_paginated(pool, find, page, page_size, None).await?)
+        let filtros: Vec<(&str, String)> = uf_id
+            .map(|uf_id| ("m.uf_id", uf_id.to_string()))
+            .into_iter()
+            .collect();
+        Ok(self
+            .repo
+            .get_paginated_filtrado(pool, find, page, page_size, &filtros)
+            .await?)
+    }
+}
+
+/// Tamanho máximo do arquivo de logotipo enviado
+const LOGO_TAMANHO_MAXIMO: usize = 2 * 1024 * 1024;
+/// Maior lado, em pixels, do logotipo gravado
+const LOGO_LADO_MAXIMO: u32 = 400;
+
+pub struct OrgaoService {
+    repo: OrgaoRepository,
+}
+
+impl OrgaoService {
+    pub fn new() -> Self {
+        Self {
+            repo: OrgaoRepository,
+        }
+    }
+
+    /// Valida os dados e grava CNPJ e CEP sem a máscara
+    fn normalizar(
+        nome: &str,
+        cnpj: &mut Option<String>,
+        cep: &mut Option<String>,
+        email: Option<&str>,
+    ) -> Result<()> {
+        if nome.trim().is_empty() {
+            return Err(AppError::InvalidInput("Nome do órgão é obrigatório".to_string()).into());
+        }
+        if let Some(valor) = cnpj.as_mut() {
+            if !cnpj_valido(valor) {
+                return Err(AppError::InvalidInput(format!("CNPJ {} inválido", valor)).into());
+            }
+            *valor = normalizar_cnpj(valor);
+        }
+        if let Some(valor) = cep.as_mut() {
+            *valor = somente_digitos(valor);
+            if valor.len() != 8 {
+                return Err(AppError::InvalidInput("CEP deve ter 8 dígitos".to_string()).into());
+            }
+        }
+        if let Some(email) = email
+            && !EMAIL_RX.is_match(email)
+        {
+            return Err(AppError::InvalidInput(format!("E-mail {} inválido", email)).into());
+        }
+        Ok(())
+    }
+
+    /// Parâmetros do eSocial: ambiente e os códigos das tabelas S-1010 e S-1020
+    fn normalizar_esocial(
+        ambiente: Option<i32>,
+        tabela_rubrica: &mut Option<String>,
+        lotacao: &mut Option<String>,
+    ) -> Result<()> {
+        if let Some(ambiente) = ambiente
+            && ambiente != 1
+            && ambiente != 2
+        {
+            return Err(AppError::InvalidInput(
+                "Ambiente do eSocial deve ser 1 (produção) ou 2 (produção restrita)".to_string(),
+            )
+            .into());
+        }
+        for (valor, tamanho, nome) in [
+            (tabela_rubrica, 8, "Tabela de rubricas do eSocial"),
+            (lotacao, 30, "Lotação tributária do eSocial"),
+        ] {
+            if let Some(codigo) = valor.as_mut() {
+                *codigo = codigo.trim().to_string();
+                if codigo.chars().count() > tamanho {
+                    return Err(AppError::InvalidInput(format!(
+                        "{} deve ter até {} caracteres",
+                        nome, tamanho
+                    ))
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Conta de débito da remessa CNAB: banco com 3 dígitos, agência e conta só com
+    /// dígitos e o DV depois do hífen
+    fn normalizar_cnab(
+        banco: &mut Option<String>,
+        agencia: &mut Option<String>,
+        conta: &mut Option<String>,
+        convenio: &mut Option<String>,
+    ) -> Result<()> {
+        if let Some(valor) = banco.as_mut() {
+            *valor = somente_digitos(valor);
+            if valor.len() != 3 {
+                return Err(AppError::InvalidInput("Banco deve ter 3 dígitos".to_string()).into());
+            }
+        }
+        for (valor, tamanho, nome) in [(agencia, 5, "Agência"), (conta, 12, "Conta")] {
+            if let Some(valor) = valor.as_mut() {
+                *valor = valor.trim().to_uppercase();
+                let (numero, dv) = valor.rsplit_once('-').unwrap_or((valor.as_str(), ""));
+                if numero.is_empty()
+                    || numero.len() > tamanho
+                    || !numero.chars().all(|c| c.is_ascii_digit())
+                    || dv.len() > 1
+                {
+                    return Err(AppError::InvalidInput(format!(
+                        "{} de pagamento deve ter até {} dígitos e o DV depois do hífen",
+                        nome, tamanho
+                    ))
+                    .into());
+                }
+            }
+        }
+        if let Some(valor) = convenio.as_mut() {
+            *valor = valor.trim().to_string();
+            if valor.chars().count() > 20 {
+                return Err(AppError::InvalidInput("Convênio deve ter até 20 caracteres".to_string()).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Percentual da margem consignável, de 0 a 100
+    fn validar_margem(margem: &BigDecimal) -> Result<()> {
+        let cem = BigDecimal::from(100);
+        if margem < &BigDecimal::zero() || margem > &cem {
+            return Err(AppError::InvalidInput(
+                "Margem consignável deve estar entre 0% e 100%".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    async fn validar_cnpj_unico(&self, pool: &PgPool, cnpj: Option<&str>, id: Option<i32>) -> Result<()> {
+        if let Some(cnpj) = cnpj
+            && self.repo.cnpj_em_uso(pool, cnpj, id).await?
+        {
+            return Err(AppError::Conflict("CNPJ já cadastrado para outro órgão".to_string()).into());
+        }
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<Orgao> {
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    pub async fn create(&self, pool: &PgPool, mut input: CreateOrgao) -> Result<Orgao> {
+        Self::normalizar(&input.nome, &mut input.cnpj, &mut input.cep, input.email.as_deref())?;
+        Self::normalizar_esocial(
+            input.esocial_ambiente,
+            &mut input.esocial_tabela_rubrica,
+            &mut input.esocial_lotacao,
+        )?;
+        Self::normalizar_cnab(
+            &mut input.cnab_banco,
+            &mut input.cnab_agencia,
+            &mut input.cnab_conta,
+            &mut input.cnab_convenio,
+        )?;
+        Self::validar_margem(&input.margem_consignavel)?;
+        self.validar_cnpj_unico(pool, input.cnpj.as_deref(), None).await?;
+        Ok(self.repo.create(pool, input).await?)
+    }
+
+    pub async fn update(&self, pool: &PgPool, id: i32, mut input: UpdateOrgao) -> Result<Orgao> {
+        Self::normalizar(&input.nome, &mut input.cnpj, &mut input.cep, input.email.as_deref())?;
+        Self::normalizar_esocial(
+            input.esocial_ambiente,
+            &mut input.esocial_tabela_rubrica,
+            &mut input.esocial_lotacao,
+        )?;
+        Self::normalizar_cnab(
+            &mut input.cnab_banco,
+            &mut input.cnab_agencia,
+            &mut input.cnab_conta,
+            &mut input.cnab_convenio,
+        )?;
+        Self::validar_margem(&input.margem_consignavel)?;
+        self.validar_cnpj_unico(pool, input.cnpj.as_deref(), Some(id)).await?;
+        Ok(self.repo.update(pool, id, input).await?)
+    }
+
+    pub async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        Ok(self.repo.delete(pool, id).await?)
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        find: Option<&str>,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<Orgao>> {
+        Ok(self.repo.get_paginated(pool, find, page, page_size, None).await?)
+    }
+
+    pub async fn logo(&self, pool: &PgPool, id: i32) -> Result<Option<Vec<u8>>> {
+        self.repo.logo(pool, id).await
+    }
+
+    /// Recebe o logotipo em PNG ou JPEG e grava em PNG, reduzido para o cabeçalho
+    pub async fn salvar_logo(&self, pool: &PgPool, id: i32, arquivo: &[u8]) -> Result<()> {
+        let png = Self::converter_logo(arquivo)?;
+        self.repo.salvar_logo(pool, id, Some(&png)).await
+    }
+
+    pub async fn remover_logo(&self, pool: &PgPool, id: i32) -> Result<()> {
+        self.repo.salvar_logo(pool, id, None).await
+    }
+
+    fn converter_logo(arquivo: &[u8]) -> Result<Vec<u8>> {
+        if arquivo.len() > LOGO_TAMANHO_MAXIMO {
+            return Err(AppError::InvalidInput("O logotipo deve ter no máximo 2 MB".to_string()).into());
+        }
+        let invalido = || AppError::InvalidInput("O logotipo deve ser uma imagem PNG ou JPEG".to_string());
+        if !matches!(
+            image::guess_format(arquivo).ok(),
+            Some(image::ImageFormat::Png | image::ImageFormat::Jpeg)
+        ) {
+            return Err(invalido().into());
+        }
+
+        let mut imagem = image::load_from_memory(arquivo).map_err(|_| invalido())?;
+        if imagem.width() > LOGO_LADO_MAXIMO || imagem.height() > LOGO_LADO_MAXIMO {
+            imagem = imagem.thumbnail(LOGO_LADO_MAXIMO, LOGO_LADO_MAXIMO);
+        }
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        imagem.write_to(&mut png, image::ImageFormat::Png)?;
+        Ok(png.into_inner())
+    }
+}
+
+pub struct ServidorService {
+    repo: ServidorRepository,
+    afastamento_repo: AfastamentoRepository,
+    alteracao_repo: AlteracaoSalarialRepository,
+    fila: FilaEsocialService,
+}
+
+impl ServidorService {
+    pub fn new() -> Self {
+        Self {
+            repo: ServidorRepository,
+            afastamento_repo: AfastamentoRepository,
+            alteracao_repo: AlteracaoSalarialRepository,
+            fila: FilaEsocialService::new(),
+        }
+    }
+
+    /// Confere os dígitos verificadores e grava CPF, PIS e CEP só com os dígitos
+    fn normalizar_documentos(
+        cpf: &mut Option<String>,
+        pis: &mut Option<String>,
+        cep: &mut Option<String>,
+    ) -> Result<()> {
+        if let Some(valor) = cpf.as_mut() {
+            if !cpf_valido(valor) {
+                return Err(AppError::InvalidInput(format!("CPF {} inválido", valor)).into());
+            }
+            *valor = somente_digitos(valor);
+        }
+        if let Some(valor) = pis.as_mut() {
+            if !pis_valido(valor) {
+                return Err(AppError::InvalidInput(format!("PIS/PASEP {} inválido", valor)).into());
+            }
+            *valor = somente_digitos(valor);
+        }
+        if let Some(valor) = cep.as_mut() {
+            *valor = somente_digitos(valor);
+            if valor.len() != 8 {
+                return Err(AppError::InvalidInput("CEP deve ter 8 dígitos".to_string()).into());
+            }
+        }
+        Ok(())
+    }
+
+    fn validar(nome: &str, tipo_conta: Option<&str>) -> Result<()> {
+        if nome.trim().is_empty() {
+            return Err(AppError::InvalidInput("Nome do servidor é obrigatório".to_string()).into());
+        }
+        if let Some(tipo) = tipo_conta
+            && ![CONTA_CORRENTE, CONTA_POUPANCA].contains(&tipo)
+        {
+            return Err(AppError::InvalidInput(
+                "Tipo de conta inválido. Use C (corrente) ou P (poupança)".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Códigos das tabelas do eSocial e coerência do desligamento; devolve o grau de
+    /// instrução e o motivo do desligamento com 2 dígitos, como devem ser gravados
+    fn validar_esocial(
+        sexo: Option<&str>,
+        raca_cor: Option<i32>,
+        grau_instrucao: Option<&str>,
+        data_admissao: Option<NaiveDate>,
+        data_desligamento: Option<NaiveDate>,
+        motivo_desligamento: Option<&str>,
+    ) -> Result<(Option<String>, Option<String>)> {
+        if sexo.is_some_and(|s| s != "M" && s != "F") {
+            return Err(AppError::InvalidInput("Sexo deve ser M ou F".to_string()).into());
+        }
+        if raca_cor.is_some_and(|r| !(1..=6).contains(&r)) {
+            return Err(AppError::InvalidInput("Raça/cor deve ser de 1 a 6".to_string()).into());
+        }
+        let grau_instrucao = Self::codigo_esocial(grau_instrucao, 12, "Grau de instrução")?;
+        let motivo_desligamento = Self::codigo_esocial(motivo_desligamento, 99, "Motivo do desligamento")?;
+        if data_desligamento.is_some() != motivo_desligamento.is_some() {
+            return Err(AppError::InvalidInput(
+                "Informe a data e o motivo do desligamento".to_string(),
+            )
+            .into());
+        }
+        if let (Some(admissao), Some(desligamento)) = (data_admissao, data_desligamento)
+            && desligamento < admissao
+        {
+            return Err(AppError::InvalidInput(
+                "Data de desligamento anterior à admissão".to_string(),
+            )
+            .into());
+        }
+        Ok((grau_instrucao, motivo_desligamento))
+    }
+
+    /// Código de tabela do eSocial entre 1 e `maximo`, com 2 dígitos
+    fn codigo_esocial(valor: Option<&str>, maximo: u32, nome: &str) -> Result<Option<String>> {
+        let Some(codigo) = valor else {
+            return Ok(None);
+        };
+        match codigo.trim().parse::<u32>() {
+            Result::Ok(numero) if (1..=maximo).contains(&numero) => Ok(Some(format!("{:02}", numero))),
+            _ => Err(AppError::InvalidInput(format!("{} {} inválido", nome, codigo)).into()),
+        }
+    }
+
+    async fn validar_cpf_unico(&self, pool: &PgPool, cpf: Option<&str>, id: Option<i32>) -> Result<()> {
+        if let Some(cpf) = cpf
+            && self.repo.cpf_em_uso(pool, cpf, id).await?
+        {
+            return Err(AppError::Conflict("CPF já cadastrado para outro servidor".to_string()).into());
+        }
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<Servidor> {
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    /// Grava o servidor e coloca na fila do órgão os eventos do eSocial da alteração
+    pub async fn create(&self, pool: &PgPool, usuario: &CurrentUser, mut input: CreateServidor) -> Result<Servidor> {
+        if !usuario.has_permission(PERM_SERVIDOR_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Self::validar(&input.nome, input.tipo_conta.as_deref())?;
+        Self::normalizar_documentos(&mut input.cpf, &mut input.pis, &mut input.cep)?;
+        let (grau_instrucao, _) = Self::validar_esocial(
+            input.sexo.as_deref(),
+            input.raca_cor,
+            input.grau_instrucao.as_deref(),
+            input.data_admissao,
+            None,
+            None,
+        )?;
+        input.grau_instrucao = grau_instrucao;
+        self.validar_cpf_unico(pool, input.cpf.as_deref(), None).await?;
+        let servidor = self.repo.create(pool, input).await?;
+        self.fila.registrar_alteracao(pool, None, &servidor).await?;
+        Ok(servidor)
+    }
+
+    pub async fn update(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        id: i32,
+        mut input: UpdateServidor,
+    ) -> Result<Servidor> {
+        if !usuario.has_permission(PERM_SERVIDOR_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Self::validar(&input.nome, input.tipo_conta.as_deref())?;
+        Self::normalizar_documentos(&mut input.cpf, &mut input.pis, &mut input.cep)?;
+        // O desligamento só é gravado pela rescisão; aqui apenas confere a admissão com ele
+        let antes = self.repo.get_by_id(pool, id).await?;
+        let (grau_instrucao, _) = Self::validar_esocial(
+            input.sexo.as_deref(),
+            input.raca_cor,
+            input.grau_instrucao.as_deref(),
+            input.data_admissao,
+            antes.data_desligamento,
+            antes.motivo_desligamento.as_deref(),
+        )?;
+        input.grau_instrucao = grau_instrucao;
+        self.validar_cpf_unico(pool, input.cpf.as_deref(), Some(id)).await?;
+        let servidor = self.repo.update(pool, id, input).await?;
+        self.fila.registrar_alteracao(pool, Some(&antes), &servidor).await?;
+        Ok(servidor)
+    }
+
+    pub async fn delete(&self, pool: &PgPool, usuario: &CurrentUser, id: i32) -> Result<()> {
+        if !usuario.has_permission(PERM_SERVIDOR_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Ok(self.repo.delete(pool, id).await?)
+    }
+
+    pub async fn afastamentos(&self, pool: &PgPool, servidor_id: i32) -> Result<Vec<Afastamento>> {
+        Ok(self.afastamento_repo.get_by_servidor(pool, servidor_id).await?)
+    }
+
+    pub async fn create_afastamento(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        servidor_id: i32,
+        mut input: CreateAfastamento,
+    ) -> Result<Afastamento> {
+        if !usuario.has_permission(PERM_SERVIDOR_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        input.motivo = input.motivo.trim().to_string();
+        if input.motivo.is_empty() {
+            return Err(AppError::InvalidInput("Informe o motivo do afastamento".to_string()).into());
+        }
+        if input.data_fim.is_some_and(|fim| fim < input.data_inicio) {
+            return Err(AppError::InvalidInput(
+                "Fim do afastamento anterior ao início".to_string(),
+            )
+            .into());
+        }
+        self.repo.get_by_id(pool, servidor_id).await?;
+        Ok(self.afastamento_repo.create(pool, servidor_id, &input).await?)
+    }
+
+    /// Exclui o afastamento e devolve o servidor dele
+    pub async fn delete_afastamento(&self, pool: &PgPool, usuario: &CurrentUser, id: i32) -> Result<i32> {
+        if !usuario.has_permission(PERM_SERVIDOR_CADASTRAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Ok(self.afastamento_repo.delete(pool, id).await?)
+    }
+
+    pub async fn alteracoes_salariais(&self, pool: &PgPool, servidor_id: i32) -> Result<Vec<AlteracaoSalarial>> {
+        Ok(self.alteracao_repo.get_by_servidor(pool, servidor_id).await?)
+    }
+
+    /// O novo salário vale a partir da competência e entra na primeira folha gerada
+    /// para ela ou depois dela
+    pub async fn create_alteracao_salarial(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        servidor_id: i32,
+        mut input: CreateAlteracaoSalarial,
+    ) -> Result<AlteracaoSalarial> {
+        input.motivo = input.motivo.trim().to_string();
+        if input.motivo.is_empty() {
+            return Err(AppError::InvalidInput("Informe o motivo da alteração salarial".to_string()).into());
+        }
+        if !(1..=12).contains(&input.mes) {
+            return Err(AppError::InvalidInput(format!("Mês {} inválido", input.mes)).into());
+        }
+        if input.salario <= BigDecimal::zero() {
+            return Err(AppError::InvalidInput("Informe o novo salário".to_string()).into());
+        }
+        self.repo.get_by_id(pool, servidor_id).await?;
+        Ok(self
+            .alteracao_repo
+            .create(pool, servidor_id, &input, &usuario.username)
+            .await?)
+    }
+
+    /// Exclui a alteração salarial ainda não aplicada e devolve o servidor dela
+    pub async fn delete_alteracao_salarial(&self, pool: &PgPool, id: i32) -> Result<i32> {
+        self.alteracao_repo.delete_pendente(pool, id).await?.ok_or_else(|| {
+            AppError::Conflict("Alteração salarial já aplicada em folha gerada".to_string()).into()
+        })
+    }
+
+    /// Busca por nome, matrícula ou CPF; o CPF pode ser digitado com a máscara
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        find: Option<&str>,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<Servidor>> {
+        let find = find.map(|termo| {
+            if termo.chars().any(|c| c.is_ascii_digit())
+                && termo.chars().all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+            {
+                somente_digitos(termo)
+            } else {
+                termo.to_string()
+            }
+        });
+        Ok(self
+            .repo
+            .get_paginated(pool, find.as_deref(), page, page_size, None)
+            .await?)
+    }
+}
+
+pub struct RubricaService {
+    repo: RubricaRepository,
+}
+
+impl RubricaService {
+    pub fn new() -> Self {
+        Self {
+            repo: RubricaRepository,
+        }
+    }
+
+    fn validar_tipo(tipo: &str) -> Result<()> {
+        if ![RUBRICA_PROVENTO, RUBRICA_DESCONTO, RUBRICA_INFORMATIVA].contains(&tipo) {
+            return Err(AppError::InvalidInput(
+                "Tipo de rubrica inválido. Use P (provento), D (desconto) ou I (informativa)".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<Rubrica> {
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    fn validar_informe(informe_rendimentos: Option<&str>) -> Result<()> {
+        if let Some(linha) = informe_rendimentos
+            && LinhaInforme::from_codigo(linha).is_none()
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Linha do informe de rendimentos inválida: {}",
+                linha
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    pub async fn create(&self, pool: &PgPool, input: CreateRubrica) -> Result<Rubrica> {
+        Self::validar_tipo(&input.tipo)?;
+        Self::validar_informe(input.informe_rendimentos.as_deref())?;
+        Ok(self.repo.create(pool, input).await?)
+    }
+
+    /// Se a alteração muda o tipo ou as incidências, que definem totais e líquido das folhas
+    fn altera_calculo(rubrica: &Rubrica, input: &UpdateRubrica) -> bool {
+        input.tipo.as_ref().is_some_and(|tipo| *tipo != rubrica.tipo)
+            || (input.incide_inss, input.incide_irrf, input.incide_fgts)
+                != (rubrica.incide_inss, rubrica.incide_irrf, rubrica.incide_fgts)
+    }
+
+    /// Tipo e incidências ficam como estão depois que a rubrica entra numa competência
+    /// fechada: os contra-cheques dela mudariam de totais ao serem emitidos de novo
+    pub async fn update(&self, pool: &PgPool, id: i32, input: UpdateRubrica) -> Result<Rubrica> {
+        if let Some(ref tipo) = input.tipo {
+            Self::validar_tipo(tipo)?;
+        }
+        Self::validar_informe(input.informe_rendimentos.as_deref())?;
+        let rubrica = self.repo.get_by_id(pool, id).await?;
+        if Self::altera_calculo(&rubrica, &input) && self.repo.usada_em_competencia_fechada(pool, id).await? {
+            return Err(AppError::Conflict(format!(
+                "A rubrica {} já foi usada em competência fechada: o tipo e as incidências não podem mudar",
+                rubrica.codigo
+            ))
+            .into());
+        }
+        Ok(self.repo.update(pool, id, input).await?)
+    }
+
+    pub async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        Ok(self.repo.delete(pool, id).await?)
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        find: Option<&str>,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<Rubrica>> {
+        Ok(self.repo.get_paginated(pool, find, page, page_size, None).await?)
+    }
+}
+
+pub struct FolhaItemService {
+    repo: FolhaItemRepository,
+    folha_repo: FolhaRepository,
+    competencia: CompetenciaService,
+}
+
+impl FolhaItemService {
+    pub fn new() -> Self {
+        Self {
+            repo: FolhaItemRepository,
+            folha_repo: FolhaRepository,
+            competencia: CompetenciaService::new(),
+        }
+    }
+
+    pub async fn get_by_folha(&self, pool: &PgPool, folha_id: i64) -> Result<Vec<FolhaItem>> {
+        Ok(self.repo.get_by_folha(pool, folha_id).await?)
+    }
+
+    pub async fn create(&self, pool: &PgPool, input: CreateFolhaItem) -> Result<FolhaItem> {
+        let mut tx = pool.begin().await?;
+        self.travar_folha(&mut tx, input.folha_id).await?;
+        let item = self.repo.criar(&mut tx, input).await?;
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    pub async fn update(&self, pool: &PgPool, id: i64, input: UpdateFolhaItem) -> Result<FolhaItem> {
+        let item = self.repo.get_by_id(pool, id).await?;
+        let mut tx = pool.begin().await?;
+        self.travar_folha(&mut tx, item.folha_id).await?;
+        let item = self.repo.alterar(&mut tx, id, input).await?;
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    pub async fn delete(&self, pool: &PgPool, id: i64) -> Result<()> {
+        let item = self.repo.get_by_id(pool, id).await?;
+        let mut tx = pool.begin().await?;
+        self.travar_folha(&mut tx, item.folha_id).await?;
+        self.repo.excluir(&mut tx, id).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Trava a competência da folha do item na transação da gravação
+    async fn travar_folha(&self, tx: &mut Transaction<'_, Postgres>, folha_id: i64) -> Result<()> {
+        let folha = self.folha_repo.get_em(tx, folha_id).await?;
+        self.competencia
+            .travar_para_gravar(tx, folha.orgao_id, folha.ano, folha.mes, folha.tipo_folha)
+            .await
+    }
+}
+
+pub struct TabelaInssService {
+    repo: TabelaInssRepository,
+}
+
+impl TabelaInssService {
+    pub fn new() -> Self {
+        Self {
+            repo: TabelaInssRepository,
+        }
+    }
+
+    pub async fn get_vigente(&self, pool: &PgPool, ano: i32, mes: i32) -> Result<Vec<FaixaInss>> {
+        let faixas = self.repo.get_vigente(pool, ano, mes).await?;
+        if faixas.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Tabela do INSS não cadastrada para a competência {:02}/{}",
+                mes,
+                ano
+            ));
+        }
+        Ok(faixas)
+    }
+
+    pub async fn create(&self, pool: &PgPool, input: CreateFaixaInss) -> Result<FaixaInss> {
+        Ok(self.repo.create(pool, input).await?)
+    }
+
+    pub async fn update(&self, pool: &PgPool, id: i32, input: UpdateFaixaInss) -> Result<FaixaInss> {
+        Ok(self.repo.update(pool, id, input).await?)
+    }
+
+    pub async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        Ok(self.repo.delete(pool, id).await?)
+    }
+}
+
+pub struct TabelaIrrfService {
+    repo: TabelaIrrfRepository,
+    parametro_repo: ParametroIrrfRepository,
+}
+
+impl TabelaIrrfService {
+    pub fn new() -> Self {
+        Self {
+            repo: TabelaIrrfRepository,
+            parametro_repo: ParametroIrrfRepository,
+        }
+    }
+
+    /// Faixas e parâmetros vigentes na competência
+    pub async fn get_vigente(
+        &self,
+        pool: &PgPool,
+        ano: i32,
+        mes: i32,
+    ) -> Result<(Vec<FaixaIrrf>, ParametroIrrf)> {
+        let faixas = self.repo.get_vigente(pool, ano, mes).await?;
+        let parametro = self.parametro_repo.get_vigente(pool, ano, mes).await?;
+
+        match parametro {
+            Some(parametro) if !faixas.is_empty() => Ok((faixas, parametro)),
+            _ => Err(anyhow::anyhow!(
+                "Tabela do IRRF não cadastrada para a competência {:02}/{}",
+                mes,
+                ano
+            )),
+        }
+    }
+
+    pub async fn create_faixa(&self, pool: &PgPool, input: CreateFaixaIrrf) -> Result<FaixaIrrf> {
+        Ok(self.repo.create(pool, input).await?)
+    }
+
+    pub async fn update_faixa(&self, pool: &PgPool, id: i32, input: UpdateFaixaIrrf) -> Result<FaixaIrrf> {
+        Ok(self.repo.update(pool, id, input).await?)
+    }
+
+    pub async fn delete_faixa(&self, pool: &PgPool, id: i32) -> Result<()> {
+        Ok(self.repo.delete(pool, id).await?)
+    }
+
+    pub async fn create_parametro(
+        &self,
+        pool: &PgPool,
+        input: CreateParametroIrrf,
+    ) -> Result<ParametroIrrf> {
+        Ok(self.parametro_repo.create(pool, input).await?)
+    }
+
+    pub async fn update_parametro(
+        &self,
+        pool: &PgPool,
+        id: i32,
+        input: UpdateParametroIrrf,
+    ) -> Result<ParametroIrrf> {
+        Ok(self.parametro_repo.update(pool, id, input).await?)
+    }
+
+    pub async fn delete_parametro(&self, pool: &PgPool, id: i32) -> Result<()> {
+        Ok(self.parametro_repo.delete(pool, id).await?)
+    }
+}
+
+/// Resultado do cálculo completo da folha
+#[derive(Debug, Serialize)]
+pub struct ResultadoCalculo {
+    pub inss: ResultadoInss,
+    pub irrf: ResultadoIrrf,
+}
+
+/// Cálculo dos descontos legais da folha
+pub struct CalculoService {
+    folha_repo: FolhaRepository,
+    item_repo: FolhaItemRepository,
+    rubrica_repo: RubricaRepository,
+    consignacao_repo: ConsignacaoRepository,
+    ferias_repo: FeriasRepository,
+    tabela_inss: TabelaInssService,
+    tabela_irrf: TabelaIrrfService,
+}
+
+impl CalculoService {
+    pub fn new() -> Self {
+        Self {
+            folha_repo: FolhaRepository,
+            item_repo: FolhaItemRepository,
+            rubrica_repo: RubricaRepository,
+            consignacao_repo: ConsignacaoRepository,
+            ferias_repo: FeriasRepository,
+            tabela_inss: TabelaInssService::new(),
+            tabela_irrf: TabelaIrrfService::new(),
+        }
+    }
+
+    async fn rubrica_sistema(&self, pool: &PgPool, codigo: &str) -> Result<Rubrica> {
+        self.rubrica_repo
+            .get_by_codigo_sistema(pool, codigo)
+            .await
+            .map_err(|_| anyhow::anyhow!("Rubrica de sistema {} não cadastrada", codigo))
+    }
+
+    /// Folha mensal, de férias ou complementar; as do 13º e da rescisão têm cálculo próprio
+    async fn folha_da_competencia(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        folha_id: i64,
+    ) -> Result<Folha> {
+        let folha = self.folha_repo.get_em(tx, folha_id).await?;
+        if folha.tipo_folha == FOLHA_RESCISAO {
+            return Err(AppError::Conflict(
+                "Folha de rescisão: estorne a rescisão do servidor e refaça".to_string(),
+            )
+            .into());
+        }
+        if !FOLHAS_DA_COMPETENCIA.contains(&folha.tipo_folha) {
+            return Err(AppError::Conflict(
+                "Folha do 13º salário: recalcule pela parcela do 13º do órgão".to_string(),
+            )
+            .into());
+        }
+        Ok(folha)
+    }
+
+    /// Folha do cálculo avulso: trava a competência na transação, recusando a fechada, e a
+    /// competência calculada volta a aberta
+    async fn travar_folha(&self, tx: &mut Transaction<'_, Postgres>, folha_id: i64) -> Result<Folha> {
+        let folha = self.folha_da_competencia(tx, folha_id).await?;
+        CompetenciaService::new()
+            .travar_para_gravar(tx, folha.orgao_id, folha.ano, folha.mes, folha.tipo_folha)
+            .await?;
+        Ok(folha)
+    }
+
+    /// Lança as férias e as parcelas de consignação, depois calcula INSS e IRRF, nessa
+    /// ordem (as férias entram nas bases e o INSS é dedução do IRRF). Tudo numa só
+    /// transação: se um passo falhar, a folha fica como estava
+    pub async fn calcular_folha(&self, pool: &PgPool, folha_id: i64) -> Result<ResultadoCalculo> {
+        let mut tx = pool.begin().await?;
+        let folha = self.travar_folha(&mut tx, folha_id).await?;
+        let resultado = self.lancar(pool, &mut tx, &folha).await?;
+        tx.commit().await?;
+        Ok(resultado)
+    }
+
+    /// Cálculo da folha dentro da transação de quem chama, que já travou a competência
+    /// (o da competência calcula todas as folhas numa transação só)
+    pub async fn calcular_folha_em(
+        &self,
+        pool: &PgPool,
+        tx: &mut Transaction<'_, Postgres>,
+        folha_id: i64,
+    ) -> Result<ResultadoCalculo> {
+        let folha = self.folha_da_competencia(tx, folha_id).await?;
+        self.lancar(pool, tx, &folha).await
+    }
+
+    async fn lancar(
+        &self,
+        pool: &PgPool,
+        tx: &mut Transaction<'_, Postgres>,
+        folha: &Folha,
+    ) -> Result<ResultadoCalculo> {
+        self.lancar_ferias(pool, tx, folha).await?;
+        self.lancar_consignacoes(pool, tx, folha).await?;
+        let inss = self.lancar_inss(pool, tx, folha).await?;
+        let irrf = self.lancar_irrf(pool, tx, folha).await?;
+        Ok(ResultadoCalculo { inss, irrf })
+    }
+
+    /// Lança as férias do servidor que começam na competência da folha: os dias de gozo e
+    /// os de abono pelo salário da folha, cada um com o terço constitucional. Férias
+    /// canceladas saem da folha no recálculo. Elas vão para a folha de férias do servidor
+    /// na competência, se houver, senão para a mensal; nas demais a lista fica vazia
+    async fn lancar_ferias(
+        &self,
+        pool: &PgPool,
+        tx: &mut Transaction<'_, Postgres>,
+        folha: &Folha,
+    ) -> Result<usize> {
+        let folha_id = folha.id;
+        let destino = match folha.tipo_folha {
+            FOLHA_FERIAS => true,
+            FOLHA_MENSAL => {
+                let competencia = (folha.ano, folha.mes);
+                !self
+                    .folha_repo
+                    .existe_na_competencia(tx, folha.orgao_id, folha.servidor_id, competencia, FOLHA_FERIAS)
+                    .await?
+            }
+            _ => false,
+        };
+        let ferias = if destino {
+            self.ferias_repo
+                .da_competencia(tx, folha.servidor_id, folha.ano, folha.mes)
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        let rubrica_ferias = self.rubrica_sistema(pool, SISTEMA_FERIAS).await?;
+        let rubrica_terco = self.rubrica_sistema(pool, SISTEMA_TERCO_FERIAS).await?;
+        let rubrica_abono = self.rubrica_sistema(pool, SISTEMA_ABONO_FERIAS).await?;
+        let rubrica_terco_abono = self.rubrica_sistema(pool, SISTEMA_TERCO_ABONO_FERIAS).await?;
+
+        let zero = BigDecimal::zero();
+        let mut dias = 0;
+        let mut dias_abono = 0;
+        let mut total = ferias::ValoresFerias {
+            ferias: zero.clone(),
+            terco: zero.clone(),
+            abono: zero.clone(),
+            terco_abono: zero,
+        };
+        for item in &ferias {
+            let valores = ferias::valores(&folha.salario, item.dias, item.dias_abono);
+            self.ferias_repo
+                .registrar_calculo(tx, item.id, folha_id, &valores)
+                .await?;
+            dias += item.dias;
+            dias_abono += item.dias_abono;
+            total.ferias += valores.ferias;
+            total.terco += valores.terco;
+            total.abono += valores.abono;
+            total.terco_abono += valores.terco_abono;
+        }
+        for (rubrica, referencia, valor) in [
+            (&rubrica_ferias, dias, &total.ferias),
+            (&rubrica_terco, dias, &total.terco),
+            (&rubrica_abono, dias_abono, &total.abono),
+            (&rubrica_terco_abono, dias_abono, &total.terco_abono),
+        ] {
+            self.item_repo
+                .substituir_item_sistema(tx, folha_id, rubrica.id, &BigDecimal::from(referencia), valor)
+                .await?;
+        }
+
+        Ok(ferias.len())
+    }
+
+    /// Substitui as parcelas de consignação da folha pelas dos contratos ativos com parcela
+    /// na competência; contrato cancelado sai da folha no recálculo. As parcelas são
+    /// descontadas só na folha mensal
+    async fn lancar_consignacoes(
+        &self,
+        pool: &PgPool,
+        tx: &mut Transaction<'_, Postgres>,
+        folha: &Folha,
+    ) -> Result<usize> {
+        if folha.tipo_folha != FOLHA_MENSAL {
+            return Ok(0);
+        }
+
+        let parcelas: Vec<_> = self
+            .consignacao_repo
+            .ativos_do_servidor(pool, folha.servidor_id)
+            .await?
+            .into_iter()
+            .filter_map(|contrato| {
+                consignacao::numero_parcela(
+                    contrato.ano_inicio,
+                    contrato.mes_inicio,
+                    contrato.total_parcelas,
+                    folha.ano,
+                    folha.mes,
+                )
+                .map(|numero| (contrato, numero))
+            })
+            .collect();
+
+        self.consignacao_repo
+            .lancar_parcelas(tx, folha.id, &parcelas)
+            .await?;
+
+        Ok(parcelas.len())
+    }
+
+    /// Simula o IRRF; sem o INSS informado, ele é calculado sobre o próprio rendimento
+    pub async fn preview_irrf(&self, pool: &PgPool, input: IrrfPreviewParams) -> Result<ResultadoIrrf> {
+        let inss = match input.inss {
+            Some(inss) => inss,
+            None => {
+                self.preview_inss(pool, input.ano, input.mes, &input.rendimento)
+                    .await?
+                    .desconto
+            }
+        };
+        let (faixas, parametro) = self.tabela_irrf.get_vigente(pool, input.ano, input.mes).await?;
+
+        let entrada = EntradaIrrf {
+            rendimento: input.rendimento,
+            inss,
+            dependentes: input.dependentes,
+            pensao: input.pensao.unwrap_or_default(),
+        };
+        Ok(calculo::calcular_irrf(&entrada, &faixas, &parametro))
+    }
+
+    /// Calcula o IRRF da folha a partir do INSS já lançado: grava base e deduções e
+    /// substitui a linha de desconto. Férias e complementares somam as bases das folhas
+    /// anteriores da competência e retêm só a diferença
+    pub async fn calcular_irrf(&self, pool: &PgPool, folha_id: i64) -> Result<ResultadoIrrf> {
+        let mut tx = pool.begin().await?;
+        let folha = self.travar_folha(&mut tx, folha_id).await?;
+        let resultado = self.lancar_irrf(pool, &mut tx, &folha).await?;
+        tx.commit().await?;
+        Ok(resultado)
+    }
+
+    async fn lancar_irrf(
+        &self,
+        pool: &PgPool,
+        tx: &mut Transaction<'_, Postgres>,
+        folha: &Folha,
+    ) -> Result<ResultadoIrrf> {
+        let folha_id = folha.id;
+        let (faixas, parametro) = self.tabela_irrf.get_vigente(pool, folha.ano, folha.mes).await?;
+        let rubrica = self.rubrica_sistema(pool, SISTEMA_IRRF).await?;
+
+        let mut folhas = self.folha_repo.anteriores(tx, folha).await?;
+        let anterior = self.folha_repo.acumulado(tx, &folhas).await?;
+        folhas.push(folha_id);
+        // lê os itens pela transação, com as férias e o INSS já lançados no cálculo
+        let bases = self.item_repo.base_irrf(tx, &folhas).await?;
+        let entrada = EntradaIrrf {
+            rendimento: bases.rendimento,
+            inss: bases.inss,
+            dependentes: folha.dependentes_irrf,
+            pensao: bases.pensao,
+        };
+        let resultado = calculo::descontar_irrf_anterior(
+            calculo::calcular_irrf(&entrada, &faixas, &parametro),
+            &anterior,
+        );
+
+        self.folha_repo
+            .atualizar_base_irrf(tx, folha_id, &resultado.base, &resultado.deducao)
+            .await?;
+        self.item_repo
+            .substituir_item_sistema(
+                tx,
+                folha_id,
+                rubrica.id,
+                &resultado.aliquota,
+                &resultado.imposto,
+            )
+            .await?;
+
+        Ok(resultado)
+    }
+
+    /// Simula o desconto para uma base qualquer, sem gravar nada
+    pub async fn preview_inss(
+        &self,
+        pool: &PgPool,
+        ano: i32,
+        mes: i32,
+        base: &BigDecimal,
+    ) -> Result<ResultadoInss> {
+        let faixas = self.tabela_inss.get_vigente(pool, ano, mes).await?;
+        Ok(calculo::calcular_inss(base, &faixas))
+    }
+
+    /// Calcula o INSS da folha: grava a base de contribuição e substitui a linha de desconto.
+    /// Férias e complementares contribuem sobre a base somada da competência, descontado o
+    /// que as folhas anteriores já recolheram
+    pub async fn calcular_inss(&self, pool: &PgPool, folha_id: i64) -> Result<ResultadoInss> {
+        let mut tx = pool.begin().await?;
+        let folha = self.travar_folha(&mut tx, folha_id).await?;
+        let resultado = self.lancar_inss(pool, &mut tx, &folha).await?;
+        tx.commit().await?;
+        Ok(resultado)
+    }
+
+    async fn lancar_inss(
+        &self,
+        pool: &PgPool,
+        tx: &mut Transaction<'_, Postgres>,
+        folha: &Folha,
+    ) -> Result<ResultadoInss> {
+        let folha_id = folha.id;
+        let faixas = self.tabela_inss.get_vigente(pool, folha.ano, folha.mes).await?;
+        let rubrica = self.rubrica_sistema(pool, SISTEMA_INSS).await?;
+
+        let mut folhas = self.folha_repo.anteriores(tx, folha).await?;
+        let anterior = self.folha_repo.acumulado(tx, &folhas).await?;
+        folhas.push(folha_id);
+        let base = self.item_repo.base_inss(tx, &folhas).await?;
+        let resultado = calculo::descontar_inss_anterior(calculo::calcular_inss(&base, &faixas), &anterior);
+
+        self.folha_repo
+            .atualizar_base_inss(tx, folha_id, &resultado.base_contribuicao)
+            .await?;
+        self.item_repo
+            .substituir_item_sistema(
+                tx,
+                folha_id,
+                rubrica.id,
+                &resultado.aliquota_efetiva,
+                &resultado.desconto,
+            )
+            .await?;
+
+        Ok(resultado)
+    }
+}
+
+/// Anos aceitos no cálculo do 13º (as datas do ano são montadas a partir dele)
+const ANO_MINIMO_DECIMO_TERCEIRO: i32 = 1900;
+const ANO_MAXIMO_DECIMO_TERCEIRO: i32 = 2999;
+
+/// 13º salário em folhas próprias, fora da folha mensal: o adiantamento é metade do 13º
+/// proporcional aos avos já adquiridos; a parcela final paga o 13º do ano, desconta o
+/// adiantamento e retém INSS e IRRF exclusivos sobre o valor integral
+pub struct DecimoTerceiroService {
+    folha_repo: FolhaRepository,
+    item_repo: FolhaItemRepository,
+    afastamento_repo: AfastamentoRepository,
+    rubrica_repo: RubricaRepository,
+    competencia: CompetenciaService,
+    tabela_inss: TabelaInssService,
+    tabela_irrf: TabelaIrrfService,
+}
+
+impl DecimoTerceiroService {
+    pub fn new() -> Self {
+        Self {
+            folha_repo: FolhaRepository,
+            item_repo: FolhaItemRepository,
+            afastamento_repo: AfastamentoRepository,
+            rubrica_repo: RubricaRepository,
+            competencia: CompetenciaService::new(),
+            tabela_inss: TabelaInssService::new(),
+            tabela_irrf: TabelaIrrfService::new(),
+        }
+    }
+
+    async fn rubrica_sistema(&self, pool: &PgPool, codigo: &str) -> Result<Rubrica> {
+        self.rubrica_repo
+            .get_by_codigo_sistema(pool, codigo)
+            .await
+            .map_err(|_| anyhow::anyhow!("Rubrica de sistema {} não cadastrada", codigo))
+    }
+
+    fn validar(input: &CalcularDecimoTerceiro) -> Result<()> {
+        if !(ANO_MINIMO_DECIMO_TERCEIRO..=ANO_MAXIMO_DECIMO_TERCEIRO).contains(&input.ano) {
+            return Err(AppError::InvalidInput("Ano do 13º inválido".to_string()).into());
+        }
+        let erro = match input.parcela {
+            DECIMO_TERCEIRO_ADIANTAMENTO if !(1..=11).contains(&input.mes) => {
+                "O adiantamento do 13º é pago entre janeiro e novembro"
+            }
+            DECIMO_TERCEIRO_FINAL if input.mes != 12 => "A parcela final do 13º é paga em dezembro",
+            DECIMO_TERCEIRO_ADIANTAMENTO | DECIMO_TERCEIRO_FINAL => return Ok(()),
+            _ => "Parcela do 13º inválida",
+        };
+        Err(AppError::InvalidInput(erro.to_string()).into())
+    }
+
+    /// Calcula a parcela do 13º de todos os servidores ativos do órgão na competência de
+    /// pagamento. O recálculo substitui os valores; servidores que saíram perdem a folha
+    pub async fn calcular(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        input: CalcularDecimoTerceiro,
+    ) -> Result<ResultadoDecimoTerceiro> {
+        if !usuario.has_permission(PERM_COMPETENCIA_CALCULAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Self::validar(&input)?;
+
+        let parcela_final = input.parcela == DECIMO_TERCEIRO_FINAL;
+        if !parcela_final
+            && self
+                .folha_repo
+                .existe_decimo_terceiro(pool, input.orgao_id, input.ano, DECIMO_TERCEIRO_FINAL)
+                .await?
+        {
+            return Err(AppError::Conflict(format!(
+                "A parcela final do 13º de {} já foi calculada: o adiantamento não pode mais ser alterado",
+                input.ano
+            ))
+            .into());
+        }
+
+        let bases = self
+            .folha_repo
+            .bases_decimo_terceiro(pool, input.orgao_id, input.ano, input.mes)
+            .await?;
+        let mut afastamentos: HashMap<i32, Vec<PeriodoAfastamento>> = HashMap::new();
+        for afastamento in self.afastamento_repo.reduzem_avos(pool, input.orgao_id, input.ano).await? {
+            afastamentos
+                .entry(afastamento.servidor_id)
+                .or_default()
+                .push(PeriodoAfastamento {
+                    inicio: afastamento.data_inicio,
+                    fim: afastamento.data_fim,
+                });
+        }
+
+        if parcela_final {
+            self.calcular_final(pool, &input, bases, &afastamentos).await
+        } else {
+            self.calcular_adiantamento(pool, &input, bases, &afastamentos).await
+        }
+    }
+
+    /// A folha existente da parcela está numa competência fechada: o recálculo não a altera
+    fn erro_parcela_fechada(base: &BaseDecimoTerceiro) -> anyhow::Error {
+        AppError::Conflict(format!(
+            "A folha do 13º de {} está numa competência fechada: reabra-a para recalcular",
+            base.nome
+        ))
+        .into()
+    }
+
+    /// Folha do 13º do servidor com o salário e a lotação da última folha mensal;
+    /// None quando ele não tem folha mensal no ano
+    fn folha(input: &CalcularDecimoTerceiro, base: &BaseDecimoTerceiro) -> Option<CreateFolha> {
+        let zero = BigDecimal::zero();
+        Some(CreateFolha {
+            orgao_id: input.orgao_id,
+            ano: input.ano,
+            mes: input.mes,
+            servidor_id: base.servidor_id,
+            salario: base.salario.clone()?,
+            base_fgts: zero.clone(),
+            base_inss: zero.clone(),
+            base_irrf: zero.clone(),
+            ded_irrf: zero,
+            cargo_id: base.cargo_id?,
+            setor_id: base.setor_id?,
+            departamento_id: base.departamento_id?,
+            vinculo_id: base.vinculo_id?,
+            dependentes_irrf: base.dependentes_irrf?,
+            tipo_folha: input.parcela,
+        })
+    }
+
+    async fn calcular_adiantamento(
+        &self,
+        pool: &PgPool,
+        input: &CalcularDecimoTerceiro,
+        bases: Vec<BaseDecimoTerceiro>,
+        afastamentos: &HashMap<i32, Vec<PeriodoAfastamento>>,
+    ) -> Result<ResultadoDecimoTerceiro> {
+        let rubrica = self.rubrica_sistema(pool, SISTEMA_ADIANTAMENTO_13).await?;
+
+        let mut resultado = ResultadoDecimoTerceiro::default();
+        let mut calculados = Vec::new();
+        let mut tx = pool.begin().await?;
+        self.competencia
+            .travar_para_gravar(&mut tx, input.orgao_id, input.ano, input.mes, input.parcela)
+            .await?;
+        for base in &bases {
+            let Some(folha) = Self::folha(input, base) else {
+                resultado.sem_folha.push(base.nome.clone());
+                continue;
+            };
+            let avos = decimo_terceiro::avos(
+                input.ano,
+                input.mes as u32,
+                base.data_admissao,
+                base.data_desligamento,
+                afastamentos.get(&base.servidor_id).map(Vec::as_slice).unwrap_or_default(),
+            );
+            if avos == 0 {
+                resultado.sem_avos += 1;
+                continue;
+            }
+
+            let valor = decimo_terceiro::adiantamento(&folha.salario, avos);
+            let folha_id = self
+                .folha_repo
+                .gravar_por_tipo(&mut tx, &folha)
+                .await?
+                .ok_or_else(|| Self::erro_parcela_fechada(base))?;
+            self.item_repo
+                .substituir_item_sistema(&mut tx, folha_id, rubrica.id, &BigDecimal::from(avos), &valor)
+                .await?;
+
+            resultado.total_liquido += valor;
+            resultado.folhas += 1;
+            calculados.push(base.servidor_id);
+        }
+        self.folha_repo
+            .remover_decimo_terceiro(&mut tx, input.orgao_id, input.ano, input.parcela, &calculados)
+            .await?;
+        tx.commit().await?;
+
+        Ok(resultado)
+    }
+
+    async fn calcular_final(
+        &self,
+        pool: &PgPool,
+        input: &CalcularDecimoTerceiro,
+        bases: Vec<BaseDecimoTerceiro>,
+        afastamentos: &HashMap<i32, Vec<PeriodoAfastamento>>,
+    ) -> Result<ResultadoDecimoTerceiro> {
+        let rubrica_integral = self.rubrica_sistema(pool, SISTEMA_DECIMO_TERCEIRO).await?;
+        let rubrica_adiantamento = self.rubrica_sistema(pool, SISTEMA_DESCONTO_ADIANTAMENTO_13).await?;
+        let rubrica_inss = self.rubrica_sistema(pool, SISTEMA_INSS_13).await?;
+        let rubrica_irrf = self.rubrica_sistema(pool, SISTEMA_IRRF_13).await?;
+        let faixas_inss = self.tabela_inss.get_vigente(pool, input.ano, input.mes).await?;
+        let (faixas_irrf, parametro) = self.tabela_irrf.get_vigente(pool, input.ano, input.mes).await?;
+        let adiantamentos: HashMap<i32, BigDecimal> = self
+            .folha_repo
+            .adiantamentos_decimo_terceiro(pool, input.orgao_id, input.ano)
+            .await?
+            .into_iter()
+            .collect();
+
+        let zero = BigDecimal::zero();
+        let mut resultado = ResultadoDecimoTerceiro::default();
+        let mut calculados = Vec::new();
+        let mut tx = pool.begin().await?;
+        self.competencia
+            .travar_para_gravar(&mut tx, input.orgao_id, input.ano, input.mes, input.parcela)
+            .await?;
+        for base in &bases {
+            let Some(folha) = Self::folha(input, base) else {
+                resultado.sem_folha.push(base.nome.clone());
+                continue;
+            };
+            let avos = decimo_terceiro::avos(
+                input.ano,
+                12,
+                base.data_admissao,
+                base.data_desligamento,
+                afastamentos.get(&base.servidor_id).map(Vec::as_slice).unwrap_or_default(),
+            );
+            if avos == 0 {
+                resultado.sem_avos += 1;
+                continue;
+            }
+
+            let folha_id = self
+                .folha_repo
+                .gravar_por_tipo(&mut tx, &folha)
+                .await?
+                .ok_or_else(|| Self::erro_parcela_fechada(base))?;
+
+            // a pensão alimentícia lançada na folha do 13º é dedução do IRRF, como na mensal
+            let pensao = self.item_repo.base_irrf(&mut tx, &[folha_id]).await?.pensao;
+            let integral = decimo_terceiro::proporcional(&folha.salario, avos);
+            let adiantado = adiantamentos.get(&base.servidor_id).unwrap_or(&zero);
+            let inss = calculo::calcular_inss(&integral, &faixas_inss);
+            let entrada = EntradaIrrf {
+                rendimento: integral.clone(),
+                inss: inss.desconto.clone(),
+                dependentes: folha.dependentes_irrf,
+                pensao: pensao.clone(),
+            };
+            let irrf = calculo::calcular_irrf(&entrada, &faixas_irrf, &parametro);
+
+            self.folha_repo
+                .atualizar_base_inss(&mut tx, folha_id, &inss.base_contribuicao)
+                .await?;
+            self.folha_repo
+                .atualizar_base_irrf(&mut tx, folha_id, &irrf.base, &irrf.deducao)
+                .await?;
+            for (rubrica, referencia, valor) in [
+                (&rubrica_integral, BigDecimal::from(avos), &integral),
+                (&rubrica_adiantamento, zero.clone(), adiantado),
+                (&rubrica_inss, inss.aliquota_efetiva.clone(), &inss.desconto),
+                (&rubrica_irrf, irrf.aliquota.clone(), &irrf.imposto),
+            ] {
+                self.item_repo
+                    .substituir_item_sistema(&mut tx, folha_id, rubrica.id, &referencia, valor)
+                    .await?;
+            }
+
+            resultado.total_liquido += &integral - adiantado - &inss.desconto - &irrf.imposto - &pensao;
+            resultado.folhas += 1;
+            calculados.push(base.servidor_id);
+        }
+        self.folha_repo
+            .remover_decimo_terceiro(&mut tx, input.orgao_id, input.ano, input.parcela, &calculados)
+            .await?;
+        tx.commit().await?;
+
+        Ok(resultado)
+    }
+}
+
+/// Férias do servidor: períodos aquisitivos apurados pela admissão, programação do gozo
+/// (fracionado e com abono pecuniário onde a lei permite) e aviso e recibo. O pagamento
+/// é lançado pelo cálculo da folha da competência em que o gozo começa
+pub struct FeriasService {
+    repo: FeriasRepository,
+    periodo_repo: PeriodoAquisitivoRepository,
+    servidor_repo: ServidorRepository,
+    vinculo_repo: TipoVinculoRepository,
+    competencia: CompetenciaService,
+    orgao_repo: OrgaoRepository,
+}
+
+impl FeriasService {
+    pub fn new() -> Self {
+        Self {
+            repo: FeriasRepository,
+            periodo_repo: PeriodoAquisitivoRepository,
+            servidor_repo: ServidorRepository,
+            vinculo_repo: TipoVinculoRepository,
+            competencia: CompetenciaService::new(),
+            orgao_repo: OrgaoRepository,
+        }
+    }
+
+    /// Períodos aquisitivos do servidor, do mais recente ao mais antigo, atualizados até
+    /// hoje (ou até o desligamento)
+    pub async fn periodos(&self, pool: &PgPool, servidor_id: i32) -> Result<Vec<PeriodoAquisitivo>> {
+        let servidor = self.servidor_repo.get_by_id(pool, servidor_id).await?;
+        if let Some(admissao) = servidor.data_admissao {
+            let ate = servidor
+                .data_desligamento
+                .unwrap_or_else(|| Local::now().date_naive());
+            self.periodo_repo
+                .sincronizar(pool, servidor_id, &ferias::periodos_aquisitivos(admissao, ate))
+                .await?;
+        }
+        Ok(self.periodo_repo.get_by_servidor(pool, servidor_id).await?)
+    }
+
+    pub async fn ferias(&self, pool: &PgPool, servidor_id: i32) -> Result<Vec<Ferias>> {
+        Ok(self.repo.get_by_servidor(pool, servidor_id).await?)
+    }
+
+    /// Programa as férias (ou uma fração delas) num período aquisitivo completo
+    pub async fn programar(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        servidor_id: i32,
+        input: CreateFerias,
+    ) -> Result<Ferias> {
+        let servidor = self.servidor_repo.get_by_id(pool, servidor_id).await?;
+        if servidor.data_desligamento.is_some() {
+            return Err(AppError::Conflict(format!("{} está desligado", servidor.nome)).into());
+        }
+        let periodo = self.periodo_repo.get_by_id(pool, input.periodo_id).await?;
+        if periodo.servidor_id != servidor_id {
+            return Err(AppError::InvalidInput(
+                "Período aquisitivo de outro servidor".to_string(),
+            )
+            .into());
+        }
+        if input.data_inicio <= periodo.data_fim {
+            return Err(AppError::InvalidInput(format!(
+                "O período aquisitivo só se completa em {}: as férias começam depois disso",
+                periodo.data_fim.format("%d/%m/%Y")
+            ))
+            .into());
+        }
+
+        let dias_abono = input.dias_abono.unwrap_or_default();
+        if dias_abono < 0 {
+            return Err(AppError::InvalidInput("Dias de abono inválidos".to_string()).into());
+        }
+        if dias_abono > 0 {
+            let categoria = match servidor.vinculo_id {
+                Some(vinculo_id) => self.vinculo_repo.get_by_id(pool, vinculo_id).await?.categoria_esocial,
+                None => None,
+            };
+            if !ferias::abono_permitido(categoria) {
+                return Err(AppError::InvalidInput(
+                    "O vínculo do servidor não permite abono pecuniário (só celetistas)".to_string(),
+                )
+                .into());
+            }
+        }
+        let fracoes = self.repo.fracoes(pool, periodo.id).await?;
+        let gozos: Vec<i32> = fracoes.iter().map(|(dias, _)| *dias).collect();
+        let abono: i32 = fracoes.iter().map(|(_, abono)| abono).sum();
+        ferias::validar_fracionamento(periodo.dias_direito, &gozos, abono, input.dias, dias_abono)?;
+
+        let data_fim = ferias::data_fim(input.data_inicio, input.dias);
+        if self
+            .repo
+            .sobreposta(pool, servidor_id, input.data_inicio, data_fim)
+            .await?
+        {
+            return Err(AppError::Conflict(
+                "O servidor já tem férias programadas nesse intervalo".to_string(),
+            )
+            .into());
+        }
+
+        // o pagamento cai na competência do início do gozo, que não pode estar fechada
+        let (ano, mes) = (input.data_inicio.year(), input.data_inicio.month() as i32);
+        let mut tx = pool.begin().await?;
+        if let Some(orgao_id) = servidor.orgao_id {
+            self.competencia
+                .travar_para_gravar(&mut tx, orgao_id, ano, mes, FOLHA_MENSAL)
+                .await?;
+        }
+        let id = self
+            .repo
+            .create(&mut tx, servidor_id, &input, data_fim, &usuario.username)
+            .await?;
+        tx.commit().await?;
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    /// Cancela as férias e devolve o servidor delas; se já estavam lançadas, a folha
+    /// (de competência ainda aberta) é recalculada sem elas na mesma transação
+    pub async fn cancelar(&self, pool: &PgPool, id: i32) -> Result<i32> {
+        let ferias = self.repo.get_by_id(pool, id).await?;
+        if ferias.situacao == FERIAS_CANCELADA {
+            return Err(AppError::Conflict("Férias já canceladas".to_string()).into());
+        }
+
+        let mut tx = pool.begin().await?;
+        if let Some(folha_id) = ferias.folha_id {
+            let folha = FolhaRepository.get_em(&mut tx, folha_id).await?;
+            self.competencia
+                .travar_para_gravar(&mut tx, folha.orgao_id, folha.ano, folha.mes, folha.tipo_folha)
+                .await?;
+        }
+        self.repo.cancelar(&mut tx, id).await?;
+        if let Some(folha_id) = ferias.folha_id {
+            CalculoService::new().calcular_folha_em(pool, &mut tx, folha_id).await?;
+        }
+        tx.commit().await?;
+        Ok(ferias.servidor_id)
+    }
+
+    /// Aviso e recibo de férias em PDF, com os valores quando já calculadas
+    pub async fn aviso_pdf(&self, pool: &PgPool, id: i32) -> Result<Vec<u8>> {
+        let ferias = self.repo.get_by_id(pool, id).await?;
+        let servidor = self.servidor_repo.get_by_id(pool, ferias.servidor_id).await?;
+        let orgao_id = servidor
+            .orgao_id
+            .ok_or_else(|| AppError::InvalidInput(format!("{} está sem órgão", servidor.nome)))?;
+        let timbre = self.orgao_repo.timbre(pool, orgao_id).await?;
+
+        relatorio::aviso_ferias(&AvisoFerias { timbre, servidor, ferias }, Utc::now())
+    }
+}
+
+/// Rescisão do servidor: as verbas do desligamento (saldo de salário, 13º proporcional,
+/// férias vencidas e proporcionais com o terço) numa folha própria da competência do
+/// desligamento, que também é gravado no cadastro. O estorno desfaz as duas coisas
+pub struct RescisaoService {
+    repo: RescisaoRepository,
+    servidor_repo: ServidorRepository,
+    folha_repo: FolhaRepository,
+    item_repo: FolhaItemRepository,
+    rubrica_repo: RubricaRepository,
+    afastamento_repo: AfastamentoRepository,
+    periodo_repo: PeriodoAquisitivoRepository,
+    ferias_repo: FeriasRepository,
+    orgao_repo: OrgaoRepository,
+    competencia: CompetenciaService,
+    tabela_inss: TabelaInssService,
+    tabela_irrf: TabelaIrrfService,
+    fila: FilaEsocialService,
+}
+
+impl RescisaoService {
+    pub fn new() -> Self {
+        Self {
+            repo: RescisaoRepository,
+            servidor_repo: ServidorRepository,
+            folha_repo: FolhaRepository,
+            item_repo: FolhaItemRepository,
+            rubrica_repo: RubricaRepository,
+            afastamento_repo: AfastamentoRepository,
+            periodo_repo: PeriodoAquisitivoRepository,
+            ferias_repo: FeriasRepository,
+            orgao_repo: OrgaoRepository,
+            competencia: CompetenciaService::new(),
+            tabela_inss: TabelaInssService::new(),
+            tabela_irrf: TabelaIrrfService::new(),
+            fila: FilaEsocialService::new(),
+        }
+    }
+
+    async fn rubrica_sistema(&self, pool: &PgPool, codigo: &str) -> Result<Rubrica> {
+        self.rubrica_repo
+            .get_by_codigo_sistema(pool, codigo)
+            .await
+            .map_err(|_| anyhow::anyhow!("Rubrica de sistema {} não cadastrada", codigo))
+    }
+
+    pub async fn get_by_servidor(&self, pool: &PgPool, servidor_id: i32) -> Result<Option<Rescisao>> {
+        Ok(self.repo.get_by_servidor(pool, servidor_id).await?)
+    }
+
+    /// Calcula a rescisão com o salário e a lotação da última folha mensal e desliga o
+    /// servidor. Na justa causa não há 13º nem férias proporcionais
+    pub async fn rescindir(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        servidor_id: i32,
+        input: CreateRescisao,
+    ) -> Result<Rescisao> {
+        if !usuario.has_permission(PERM_COMPETENCIA_CALCULAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        let antes = self.servidor_repo.get_by_id(pool, servidor_id).await?;
+        if self.repo.get_by_servidor(pool, servidor_id).await?.is_some() {
+            return Err(AppError::Conflict(format!(
+                "A rescisão de {} já foi calculada: estorne-a para refazer",
+                antes.nome
+            ))
+            .into());
+        }
+        let desligamento = input.data_desligamento;
+        let (_, motivo) = ServidorService::validar_esocial(
+            antes.sexo.as_deref(),
+            antes.raca_cor,
+            antes.grau_instrucao.as_deref(),
+            antes.data_admissao,
+            Some(desligamento),
+            Some(&input.motivo_desligamento),
+        )?;
+        let motivo = motivo.unwrap_or_default();
+        let admissao = antes.data_admissao.ok_or_else(|| {
+            AppError::InvalidInput(format!("{} está sem data de admissão", antes.nome))
+        })?;
+
+        let (ano, mes) = (desligamento.year(), desligamento.month() as i32);
+        if let Some((ano_mensal, mes_mensal)) = self
+            .folha_repo
+            .mensal_a_partir(pool, servidor_id, ano, mes)
+            .await?
+        {
+            return Err(AppError::Conflict(format!(
+                "Exclua a folha mensal de {:02}/{}: o mês do desligamento é pago na rescisão",
+                mes_mensal, ano_mensal
+            ))
+            .into());
+        }
+        let base = self
+            .folha_repo
+            .ultima_mensal(pool, servidor_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!(
+                    "{} não tem folha mensal de onde tirar salário e lotação",
+                    antes.nome
+                ))
+            })?;
+
+        let zero = BigDecimal::zero();
+        let salario = &base.salario;
+        let justa_causa = motivo == rescisao::MOTIVO_JUSTA_CAUSA;
+
+        // saldo de salário, com INSS e IRRF da competência
+        let dias_saldo = rescisao::dias_saldo_salario(desligamento);
+        let saldo = rescisao::saldo_salario(salario, dias_saldo);
+        let faixas_inss = self.tabela_inss.get_vigente(pool, ano, mes).await?;
+        let (faixas_irrf, parametro) = self.tabela_irrf.get_vigente(pool, ano, mes).await?;
+        let inss = calculo::calcular_inss(&saldo, &faixas_inss);
+        let irrf = calculo::calcular_irrf(
+            &EntradaIrrf {
+                rendimento: saldo.clone(),
+                inss: inss.desconto.clone(),
+                dependentes: base.dependentes_irrf,
+                pensao: zero.clone(),
+            },
+            &faixas_irrf,
+            &parametro,
+        );
+
+        // 13º proporcional até o desligamento, menos o adiantamento, com tributação exclusiva
+        let (adiantado, final_pago) = self
+            .folha_repo
+            .decimo_terceiro_pago(pool, servidor_id, ano)
+            .await?;
+        let avos_13 = if justa_causa || final_pago {
+            0
+        } else {
+            let afastamentos: Vec<PeriodoAfastamento> = self
+                .afastamento_repo
+                .reduzem_avos(pool, base.orgao_id, ano)
+                .await?
+                .into_iter()
+                .filter(|afastamento| afastamento.servidor_id == servidor_id)
+                .map(|afastamento| PeriodoAfastamento {
+                    inicio: afastamento.data_inicio,
+                    fim: afastamento.data_fim,
+                })
+                .collect();
+            decimo_terceiro::avos(ano, mes as u32, Some(admissao), Some(desligamento), &afastamentos)
+        };
+        let decimo = decimo_terceiro::proporcional(salario, avos_13);
+        let adiantado = if final_pago { zero.clone() } else { adiantado };
+        let inss_13 = calculo::calcular_inss(&decimo, &faixas_inss);
+        let irrf_13 = calculo::calcular_irrf(
+            &EntradaIrrf {
+                rendimento: decimo.clone(),
+                inss: inss_13.desconto.clone(),
+                dependentes: base.dependentes_irrf,
+                pensao: zero.clone(),
+            },
+            &faixas_irrf,
+            &parametro,
+        );
+
+        // férias: o saldo dos períodos completos é vencido; o período em curso dá os avos
+        // proporcionais. Férias que começariam depois do desligamento não contam
+        self.periodo_repo
+            .sincronizar(pool, servidor_id, &ferias::periodos_aquisitivos(admissao, desligamento))
+            .await?;
+        let mut usados: HashMap<i32, i32> = HashMap::new();
+        for f in self.ferias_repo.get_by_servidor(pool, servidor_id).await? {
+            if f.situacao != FERIAS_CANCELADA && f.data_inicio <= desligamento {
+                *usados.entry(f.periodo_id).or_default() += f.dias + f.dias_abono;
+            }
+        }
+        let mut dias_vencidas = 0;
+        let mut avos_ferias = 0;
+        let mut proporcionais = zero.clone();
+        for periodo in self.periodo_repo.get_by_servidor(pool, servidor_id).await? {
+            let usado = usados.get(&periodo.id).copied().unwrap_or_default();
+            if periodo.data_fim <= desligamento {
+                dias_vencidas += (periodo.dias_direito - usado).max(0);
+            } else if !justa_causa {
+                avos_ferias = rescisao::avos_ferias(periodo.data_inicio, desligamento);
+                proporcionais = rescisao::ferias_proporcionais(salario, avos_ferias, usado);
+            }
+        }
+        let vencidas = rescisao::saldo_salario(salario, dias_vencidas);
+        let terco = rescisao::terco(&(&vencidas + &proporcionais));
+
+        let lancamentos = [
+            (SISTEMA_SALDO_SALARIO, BigDecimal::from(dias_saldo), &saldo),
+            (SISTEMA_INSS, inss.aliquota_efetiva.clone(), &inss.desconto),
+            (SISTEMA_IRRF, irrf.aliquota.clone(), &irrf.imposto),
+            (SISTEMA_DECIMO_TERCEIRO, BigDecimal::from(avos_13), &decimo),
+            (SISTEMA_DESCONTO_ADIANTAMENTO_13, zero.clone(), &adiantado),
+            (SISTEMA_INSS_13, inss_13.aliquota_efetiva.clone(), &inss_13.desconto),
+            (SISTEMA_IRRF_13, irrf_13.aliquota.clone(), &irrf_13.imposto),
+            (SISTEMA_FERIAS_VENCIDAS, BigDecimal::from(dias_vencidas), &vencidas),
+            (SISTEMA_FERIAS_PROPORCIONAIS, BigDecimal::from(avos_ferias), &proporcionais),
+            (SISTEMA_TERCO_FERIAS_RESCISAO, zero.clone(), &terco),
+        ];
+        let mut rubricas = Vec::with_capacity(lancamentos.len());
+        for (codigo, _, _) in &lancamentos {
+            rubricas.push(self.rubrica_sistema(pool, codigo).await?.id);
+        }
+
+        let folha = CreateFolha {
+            orgao_id: base.orgao_id,
+            ano,
+            mes,
+            servidor_id,
+            salario: base.salario.clone(),
+            base_fgts: zero.clone(),
+            base_inss: zero.clone(),
+            base_irrf: zero.clone(),
+            ded_irrf: zero.clone(),
+            cargo_id: base.cargo_id,
+            setor_id: base.setor_id,
+            departamento_id: base.departamento_id,
+            vinculo_id: base.vinculo_id,
+            dependentes_irrf: base.dependentes_irrf,
+            tipo_folha: FOLHA_RESCISAO,
+        };
+        let mut tx = pool.begin().await?;
+        self.competencia
+            .travar_para_gravar(&mut tx, base.orgao_id, ano, mes, FOLHA_RESCISAO)
+            .await?;
+        let folha_id = self
+            .folha_repo
+            .gravar_por_tipo(&mut tx, &folha)
+            .await?
+            .ok_or_else(|| {
+                AppError::Conflict(format!(
+                    "{} já tem folha de rescisão em {} numa competência fechada",
+                    antes.nome, ano
+                ))
+            })?;
+        self.folha_repo
+            .atualizar_base_inss(&mut tx, folha_id, &inss.base_contribuicao)
+            .await?;
+        self.folha_repo
+            .atualizar_base_irrf(&mut tx, folha_id, &irrf.base, &irrf.deducao)
+            .await?;
+        for (rubrica_id, (_, referencia, valor)) in rubricas.into_iter().zip(&lancamentos) {
+            self.item_repo
+                .substituir_item_sistema(&mut tx, folha_id, rubrica_id, referencia, valor)
+                .await?;
+        }
+        self.ferias_repo
+            .cancelar_programadas_apos(&mut tx, servidor_id, desligamento)
+            .await?;
+        self.servidor_repo
+            .desligar(&mut tx, servidor_id, Some(desligamento), Some(&motivo))
+            .await?;
+        let rescisao = self
+            .repo
+            .create(
+                &mut tx,
+                &Rescisao {
+                    id: 0,
+                    servidor_id,
+                    folha_id,
+                    data_desligamento: desligamento,
+                    motivo_desligamento: motivo,
+                    dias_saldo_salario: dias_saldo,
+                    avos_decimo_terceiro: avos_13,
+                    dias_ferias_vencidas: dias_vencidas,
+                    avos_ferias_proporcionais: avos_ferias,
+                    usuario: Some(usuario.username.clone()),
+                    created_at: Utc::now(),
+                },
+            )
+            .await?;
+        tx.commit().await?;
+
+        let depois = self.servidor_repo.get_by_id(pool, servidor_id).await?;
+        self.fila.registrar_alteracao(pool, Some(&antes), &depois).await?;
+        Ok(rescisao)
+    }
+
+    /// Desfaz a rescisão de competência ainda não fechada: exclui a folha e limpa o
+    /// desligamento do cadastro. Férias canceladas pela rescisão continuam canceladas
+    pub async fn estornar(&self, pool: &PgPool, usuario: &CurrentUser, servidor_id: i32) -> Result<()> {
+        if !usuario.has_permission(PERM_COMPETENCIA_CALCULAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        let rescisao = self
+            .repo
+            .get_by_servidor(pool, servidor_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        let antes = self.servidor_repo.get_by_id(pool, servidor_id).await?;
+
+        let mut tx = pool.begin().await?;
+        let folha = self.folha_repo.get_em(&mut tx, rescisao.folha_id).await?;
+        self.competencia
+            .travar_para_gravar(&mut tx, folha.orgao_id, folha.ano, folha.mes, folha.tipo_folha)
+            .await?;
+        self.repo.estornar(&mut tx, rescisao.folha_id).await?;
+        self.servidor_repo.desligar(&mut tx, servidor_id, None, None).await?;
+        tx.commit().await?;
+
+        let depois = self.servidor_repo.get_by_id(pool, servidor_id).await?;
+        self.fila.registrar_alteracao(pool, Some(&antes), &depois).await?;
+        Ok(())
+    }
+
+    /// Termo de rescisão em PDF com as verbas da folha da rescisão
+    pub async fn termo_pdf(&self, pool: &PgPool, servidor_id: i32) -> Result<Vec<u8>> {
+        let rescisao = self
+            .repo
+            .get_by_servidor(pool, servidor_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        let detalhe = FolhaService::new().get_by_id(pool, rescisao.folha_id).await?;
+        let cabecalho = self.folha_repo.cabecalho_contra_cheque(pool, rescisao.folha_id).await?;
+        let timbre = self.orgao_repo.timbre(pool, detalhe.folha.orgao_id).await?;
+
+        relatorio::termo_rescisao(
+            &TermoRescisao {
+                timbre,
+                cabecalho,
+                detalhe,
+                rescisao,
+            },
+            Utc::now(),
+        )
+    }
+}
+
+/// Geração da folha mensal de uma competência a partir das folhas mensais de outra: os
+/// servidores ativos e não desligados, com as rubricas fixas e as alterações salariais
+/// pendentes. A prévia mostra o que será gravado; a geração refaz a prévia e grava tudo
+/// numa transação só
+pub struct GeracaoFolhaService {
+    folha_repo: FolhaRepository,
+    item_repo: FolhaItemRepository,
+    alteracao_repo: AlteracaoSalarialRepository,
+    competencia: CompetenciaService,
+}
+
+impl GeracaoFolhaService {
+    pub fn new() -> Self {
+        Self {
+            folha_repo: FolhaRepository,
+            item_repo: FolhaItemRepository,
+            alteracao_repo: AlteracaoSalarialRepository,
+            competencia: CompetenciaService::new(),
+        }
+    }
+
+    /// O que a geração faria com cada servidor da origem, sem gravar nada
+    pub async fn previa(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        input: &GerarFolha,
+    ) -> Result<PreviaGeracaoFolha> {
+        if !usuario.has_permission(PERM_FOLHA_GERAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        if !(1..=12).contains(&input.mes) || !(1..=12).contains(&input.mes_origem) {
+            return Err(AppError::InvalidInput("Mês inválido".to_string()).into());
+        }
+        if (input.ano, input.mes) <= (input.ano_origem, input.mes_origem) {
+            return Err(AppError::InvalidInput(
+                "A competência gerada deve ser posterior à de origem".to_string(),
+            )
+            .into());
+        }
+
+        let origens = self.folha_repo.origem_geracao(pool, input).await?;
+        if origens.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "O órgão não tem folhas mensais em {:02}/{}",
+                input.mes_origem, input.ano_origem
+            ))
+            .into());
+        }
+
+        let folhas: Vec<i64> = origens.iter().map(|origem| origem.folha_id).collect();
+        let servidores: Vec<i32> = origens.iter().map(|origem| origem.servidor_id).collect();
+        let mut fixos: HashMap<i64, Vec<ItemFixo>> = HashMap::new();
+        for item in self.item_repo.fixos(pool, &folhas).await? {
+            fixos.entry(item.folha_id).or_default().push(item);
+        }
+        let mut pendentes: HashMap<i32, Vec<AlteracaoSalarial>> = HashMap::new();
+        for alteracao in self.alteracao_repo.pendentes(pool, &servidores).await? {
+            pendentes.entry(alteracao.servidor_id).or_default().push(alteracao);
+        }
+
+        let mut linhas = Vec::with_capacity(origens.len());
+        for origem in origens {
+            let situacao = if origem.gerada {
+                GERACAO_EXISTENTE
+            } else if rescisao::desligado_na_competencia(origem.data_desligamento, input.ano, input.mes) {
+                GERACAO_DESLIGADO
+            } else if !origem.servidor_ativo {
+                GERACAO_INATIVO
+            } else {
+                GERACAO_GERAR
+            };
+
+            let (salario, alteracoes, itens) = if situacao == GERACAO_GERAR {
+                let (salario, alteracoes) = geracao_folha::salario_vigente(
+                    &origem.salario,
+                    pendentes.get(&origem.servidor_id).map_or(&[], Vec::as_slice),
+                    input.ano,
+                    input.mes,
+                );
+                let itens = fixos
+                    .remove(&origem.folha_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|item| {
+                        // o salário contratual acompanha o salário da nova folha
+                        let salario_contratual = item.codigo_sistema.as_deref() == Some(SISTEMA_SALARIO);
+                        ItemGerado {
+                            rubrica_id: item.rubrica_id,
+                            codigo: item.codigo,
+                            descricao: item.descricao,
+                            referencia: if salario_contratual { BigDecimal::from(DIAS_MES) } else { item.referencia },
+                            valor: if salario_contratual { salario.clone() } else { item.valor.clone() },
+                            valor_anterior: item.valor,
+                        }
+                    })
+                    .collect();
+                (salario, alteracoes, itens)
+            } else {
+                (origem.salario.clone(), Vec::new(), Vec::new())
+            };
+
+            linhas.push(ServidorGeracao {
+                servidor_id: origem.servidor_id,
+                nome: origem.servidor_nome.clone(),
+                situacao,
+                salario_anterior: origem.salario.clone(),
+                salario,
+                alteracoes,
+                itens,
+                origem,
+            });
+        }
+
+        Ok(PreviaGeracaoFolha {
+            orgao_id: input.orgao_id,
+            ano_origem: input.ano_origem,
+            mes_origem: input.mes_origem,
+            ano: input.ano,
+            mes: input.mes,
+            gerar: linhas.iter().filter(|linha| linha.situacao == GERACAO_GERAR).count(),
+            servidores: linhas,
+        })
+    }
+
+    /// Grava as folhas da prévia: se uma falhar, nenhuma é gerada. A competência de destino
+    /// fica travada na transação e não pode estar fechada
+    pub async fn gerar(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        input: &GerarFolha,
+    ) -> Result<PreviaGeracaoFolha> {
+        let previa = self.previa(pool, usuario, input).await?;
+        if previa.gerar == 0 {
+            return Err(AppError::Conflict(format!(
+                "Nenhuma folha a gerar em {:02}/{}",
+                input.mes, input.ano
+            ))
+            .into());
+        }
+        let zero = BigDecimal::zero();
+        let mut tx = pool.begin().await?;
+        self.competencia
+            .travar_para_gravar(&mut tx, input.orgao_id, input.ano, input.mes, FOLHA_MENSAL)
+            .await?;
+        for linha in previa.servidores.iter().filter(|linha| linha.situacao == GERACAO_GERAR) {
+            let origem = &linha.origem;
+            let folha = CreateFolha {
+                orgao_id: input.orgao_id,
+                ano: input.ano,
+                mes: input.mes,
+                servidor_id: linha.servidor_id,
+                salario: linha.salario.clone(),
+                base_fgts: zero.clone(),
+                base_inss: zero.clone(),
+                base_irrf: zero.clone(),
+                ded_irrf: zero.clone(),
+                cargo_id: origem.cargo_id,
+                setor_id: origem.setor_id,
+                departamento_id: origem.departamento_id,
+                vinculo_id: origem.vinculo_id,
+                dependentes_irrf: origem.dependentes_irrf,
+                tipo_folha: FOLHA_MENSAL,
+            };
+            let folha_id = self.folha_repo.gerar(&mut tx, &folha).await?;
+            for item in &linha.itens {
+                self.item_repo
+                    .inserir(&mut tx, folha_id, item.rubrica_id, &item.referencia, &item.valor)
+                    .await?;
+            }
+            self.alteracao_repo
+                .aplicar(&mut tx, &linha.alteracoes, folha_id)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(previa)
+    }
+}
+
+/// Ciclo da competência: aberta -> calculada -> fechada -> reaberta -> calculada ...
+pub struct CompetenciaService {
+    repo: CompetenciaRepository,
+}
+
+impl CompetenciaService {
+    pub fn new() -> Self {
+        Self {
+            repo: CompetenciaRepository,
+        }
+    }
+
+    fn erro_fechada() -> anyhow::Error {
+        AppError::Conflict(
+            "Competência fechada: reabra a competência para alterar a folha".to_string(),
+        )
+        .into()
+    }
+
+    pub async fn historico(&self, pool: &PgPool, id: i32) -> Result<Vec<CompetenciaHistorico>> {
+        Ok(self.repo.historico(pool, id).await?)
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        find: Option<&str>,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<Competencia>> {
+        Ok(self.repo.get_paginated(pool, find, page, page_size, None).await?)
+    }
+
+    /// Abre a competência do órgão, se ainda não existir; o registro no histórico
+    /// é gravado na mesma transação
+    pub async fn abrir(
+        &self,
+        pool: &PgPool,
+        usuario: Option<&str>,
+        input: CreateCompetencia,
+    ) -> Result<Competencia> {
+        if !(1..=12).contains(&input.mes) {
+            return Err(AppError::InvalidInput("Mês da competência inválido".to_string()).into());
+        }
+
+        let mut tx = pool.begin().await?;
+        let Some(competencia) = self.repo.inserir(&mut tx, &input).await? else {
+            tx.rollback().await?;
+            return self
+                .repo
+                .get_by_orgao_ano_mes(pool, input.orgao_id, input.ano, input.mes)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Competência não encontrada"));
+        };
+        self.repo
+            .registrar_historico(&mut tx, competencia.id, None, COMPETENCIA_ABERTA, usuario, None)
+            .await?;
+        tx.commit().await?;
+
+        Ok(competencia)
+    }
+
+    /// Na transação que grava numa folha (ou nos itens dela): recusa competência fechada e
+    /// trava a competência até o commit, para um fechamento concorrente esperar a gravação.
+    /// Competência calculada volta antes a aberta (ou reaberta) e precisa ser calculada de novo;
+    /// as folhas do 13º e da rescisão não entram nos totais e não contam. A linha já fica
+    /// travada pela própria atualização e duas gravações não se bloqueiam uma à outra
+    pub async fn travar_para_gravar(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        orgao_id: i32,
+        ano: i32,
+        mes: i32,
+        tipo_folha: i16,
+    ) -> Result<()> {
+        if FOLHAS_DA_COMPETENCIA.contains(&tipo_folha) {
+            self.alterada_em(tx, orgao_id, ano, mes).await?;
+        }
+        match self.repo.situacao_travada(tx, orgao_id, ano, mes).await? {
+            Some(situacao) if situacao == COMPETENCIA_FECHADA => Err(Self::erro_fechada()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn alterada_em(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        orgao_id: i32,
+        ano: i32,
+        mes: i32,
+    ) -> Result<()> {
+        if let Some((id, situacao)) = self.repo.descalcular(tx, orgao_id, ano, mes).await? {
+            self.repo
+                .registrar_historico(
+                    tx,
+                    id,
+                    Some(COMPETENCIA_CALCULADA),
+                    &situacao,
+                    None,
+                    Some("Folha alterada depois do cálculo"),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Valida a permissão e, com a competência travada na transação, a transição
+    async fn validar_transicao(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        usuario: &CurrentUser,
+        id: i32,
+        nova: &str,
+        permissao: &str,
+    ) -> Result<Competencia> {
+        if !usuario.has_permission(permissao) {
+            return Err(AppError::PermissionDenied.into());
+        }
+
+        let competencia = self.repo.get_para_transicao(tx, id).await?;
+        if !transicao_permitida(&competencia.situacao, nova) {
+            return Err(AppError::Conflict(format!(
+                "Competência {:02}/{} está {}: não é possível passar para {}",
+                competencia.mes, competencia.ano, competencia.situacao, nova
+            ))
+            .into());
+        }
+        Ok(competencia)
+    }
+
+    async fn registrar_transicao(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        usuario: &CurrentUser,
+        competencia: &Competencia,
+        nova: &str,
+        totais: Option<&TotaisCompetencia>,
+        motivo: Option<&str>,
+    ) -> Result<()> {
+        self.repo
+            .atualizar_situacao(tx, competencia.id, nova, totais, &usuario.username)
+            .await?;
+        self.repo
+            .registrar_historico(
+                tx,
+                competencia.id,
+                Some(&competencia.situacao),
+                nova,
+                Some(&usuario.username),
+                motivo,
+            )
+            .await
+    }
+
+    /// Calcula INSS e IRRF de todas as folhas da competência numa só transação: se
+    /// uma folha falhar, nenhuma fica recalculada e a situação não muda
+    pub async fn calcular(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        id: i32,
+        motivo: Option<&str>,
+    ) -> Result<Competencia> {
+        let mut tx = pool.begin().await?;
+        let competencia = self
+            .validar_transicao(&mut tx, usuario, id, COMPETENCIA_CALCULADA, PERM_COMPETENCIA_CALCULAR)
+            .await?;
+
+        self.calcular_folhas(pool, &mut tx, id).await?;
+
+        self.registrar_transicao(&mut tx, usuario, &competencia, COMPETENCIA_CALCULADA, None, motivo)
+            .await?;
+        tx.commit().await?;
+
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    /// Calcula as folhas da competência em ordem, pela transação: cada uma soma as bases
+    /// e o que as anteriores já gravaram nela
+    async fn calcular_folhas(&self, pool: &PgPool, tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<()> {
+        let calculo = CalculoService::new();
+        for folha_id in self.repo.folhas(tx, id).await? {
+            calculo
+                .calcular_folha_em(pool, tx, folha_id)
+                .await
+                .map_err(|err| anyhow::anyhow!("Folha {}: {}", folha_id, err))?;
+        }
+        Ok(())
+    }
+
+    /// Fecha a competência e congela os totais enviados ao banco e ao tribunal de contas.
+    /// Folhas e itens ficam travados da leitura dos totais até a gravação
+    pub async fn fechar(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        id: i32,
+        motivo: Option<&str>,
+    ) -> Result<Competencia> {
+        let mut tx = pool.begin().await?;
+        let competencia = self
+            .validar_transicao(&mut tx, usuario, id, COMPETENCIA_FECHADA, PERM_COMPETENCIA_FECHAR)
+            .await?;
+
+        self.repo.travar_folhas(&mut tx, id).await?;
+        let totais = self.repo.totais(&mut tx, id).await?;
+
+        self.registrar_transicao(&mut tx, usuario, &competencia, COMPETENCIA_FECHADA, Some(&totais), motivo)
+            .await?;
+        tx.commit().await?;
+
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    /// Reabre competência fechada; o motivo é obrigatório e os totais do fechamento são mantidos
+    pub async fn reabrir(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        id: i32,
+        motivo: Option<&str>,
+    ) -> Result<Competencia> {
+        let Some(motivo) = motivo.map(str::trim).filter(|motivo| !motivo.is_empty()) else {
+            return Err(AppError::InvalidInput(
+                "Informe o motivo da reabertura".to_string(),
+            )
+            .into());
+        };
+
+        let mut tx = pool.begin().await?;
+        let competencia = self
+            .validar_transicao(&mut tx, usuario, id, COMPETENCIA_REABERTA, PERM_COMPETENCIA_REABRIR)
+            .await?;
+
+        self.registrar_transicao(&mut tx, usuario, &competencia, COMPETENCIA_REABERTA, None, Some(motivo))
+            .await?;
+        tx.commit().await?;
+
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+}
+
+/// Até este número de folhas o lote é gerado na hora; acima disso vai para segundo plano
+const LIMITE_LOTE_IMEDIATO: usize = 30;
+
+/// Horas em que o arquivo de um lote concluído fica disponível para download
+pub const VALIDADE_LOTE_HORAS: i64 = 24;
+
+/// Arquivo gerado para download
+#[derive(Debug)]
+pub struct Arquivo {
+    pub nome: String,
+    pub conteudo: Vec<u8>,
+}
+
+/// Arquivo do lote gravado em disco, aberto para ser enviado aos poucos no download
+#[derive(Debug)]
+pub struct ArquivoLote {
+    pub nome: String,
+    pub arquivo: tokio::fs::File,
+}
+
+pub enum ResultadoLote {
+    /// Lote pequeno, gerado na hora
+    Arquivo(ArquivoLote),
+    /// Lote grande, processado em segundo plano
+    Agendado(LoteContraCheque),
+}
+
+pub struct LoteContraChequeService {
+    repo: LoteContraChequeRepository,
+    folha_repo: FolhaRepository,
+}
+
+impl LoteContraChequeService {
+    pub fn new() -> Self {
+        Self {
+            repo: LoteContraChequeRepository,
+            folha_repo: FolhaRepository,
+        }
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<LoteContraCheque> {
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    /// Gera os contra-cheques do órgão na competência (opcionalmente de um setor ou
+    /// departamento) como ZIP ou PDF único
+    pub async fn gerar(
+        &self,
+        pool: &PgPool,
+        usuario: &str,
+        mut input: GerarLoteContraCheque,
+    ) -> Result<ResultadoLote> {
+        input.formato = input.formato.to_uppercase();
+        if input.formato != LOTE_FORMATO_ZIP && input.formato != LOTE_FORMATO_PDF {
+            return Err(AppError::InvalidInput("Formato deve ser ZIP ou PDF".to_string()).into());
+        }
+        if !(1..=12).contains(&input.mes) {
+            return Err(AppError::InvalidInput("Mês da competência inválido".to_string()).into());
+        }
+
+        let ids = self.folha_repo.ids_para_lote(pool, &input).await?;
+        if ids.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Nenhuma folha encontrada para o filtro informado".to_string(),
+            )
+            .into());
+        }
+
+        if ids.len() <= LIMITE_LOTE_IMEDIATO {
+            let (nome, caminho) = gerar_arquivo_lote(pool, &input, &ids).await?;
+            // aberto, o arquivo continua legível depois de removido da pasta
+            let arquivo = tokio::fs::File::open(&caminho).await?;
+            tokio::fs::remove_file(&caminho).await?;
+            return Ok(ResultadoLote::Arquivo(ArquivoLote { nome, arquivo }));
+        }
+
+        let lote = self
+            .repo
+            .create(pool, &input, ids.len() as i32, usuario)
+            .await?;
+
+        let pool = pool.clone();
+        let id = lote.id;
+        tokio::spawn(async move { processar_lote(&pool, id, input, ids).await });
+
+        Ok(ResultadoLote::Agendado(lote))
+    }
+
+    /// Arquivo do lote concluído
+    pub async fn download(&self, pool: &PgPool, id: i32) -> Result<ArquivoLote> {
+        let lote = self.repo.get_by_id(pool, id).await?;
+        if lote.situacao == LOTE_ERRO {
+            return Err(AppError::Conflict(format!(
+                "O lote terminou com erro: {}",
+                lote.erro.unwrap_or_default()
+            ))
+            .into());
+        }
+
+        if lote.situacao != LOTE_CONCLUIDO {
+            return Err(AppError::Conflict("O lote ainda está sendo processado".to_string()).into());
+        }
+        let Some((nome, caminho)) = self.repo.arquivo(pool, id).await? else {
+            return Err(AppError::Conflict(format!(
+                "O arquivo do lote fica disponível por {} horas: gere o lote novamente",
+                VALIDADE_LOTE_HORAS
+            ))
+            .into());
+        };
+        match tokio::fs::File::open(&caminho).await {
+            Result::Ok(arquivo) => Ok(ArquivoLote { nome, arquivo }),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(AppError::Conflict(
+                "O arquivo do lote não está mais disponível: gere o lote novamente".to_string(),
+            )
+            .into()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Apaga da pasta dos lotes os arquivos com mais de VALIDADE_LOTE_HORAS, inclusive os
+    /// que sobraram de lotes interrompidos, e tira dos lotes o caminho vencido
+    pub async fn limpar_expirados(&self, pool: &PgPool) -> Result<u64> {
+        let validade = chrono::Duration::hours(VALIDADE_LOTE_HORAS);
+        self.repo.expirar_arquivos(pool, Utc::now() - validade).await?;
+
+        let pasta = pasta_lotes();
+        let mut entradas = match tokio::fs::read_dir(&pasta).await {
+            Result::Ok(entradas) => entradas,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err.into()),
+        };
+        let limite = std::time::SystemTime::now() - validade.to_std()?;
+        let mut removidos = 0;
+        while let Some(entrada) = entradas.next_entry().await? {
+            let modificado = entrada.metadata().await?.modified()?;
+            if modificado < limite {
+                tokio::fs::remove_file(entrada.path()).await?;
+                removidos += 1;
+            }
+        }
+        Ok(removidos)
+    }
+
+    /// Marca como erro os lotes que estavam em andamento quando o servidor parou
+    pub async fn interromper_pendentes(&self, pool: &PgPool) -> Result<u64> {
+        Ok(self.repo.interromper_pendentes(pool).await?)
+    }
+}
+
+async fn processar_lote(pool: &PgPool, id: i32, input: GerarLoteContraCheque, ids: Vec<i64>) {
+    let repo = LoteContraChequeRepository;
+
+    let resultado = async {
+        repo.atualizar_situacao(pool, id, LOTE_PROCESSANDO).await?;
+        let (nome, caminho) = gerar_arquivo_lote(pool, &input, &ids).await?;
+        repo.concluir(pool, id, &nome, &caminho.to_string_lossy()).await
+    }
+    .await;
+
+    if let Err(err) = resultado {
+        error!("Erro ao gerar lote de contra-cheques {}: {}", id, err);
+        if let Err(err) = repo.registrar_erro(pool, id, &err.to_string()).await {
+            error!("Erro ao registrar falha do lote {}: {}", id, err);
+        }
+    }
+}
+
+/// Pasta dos arquivos de lote; o banco guarda só o caminho
+fn pasta_lotes() -> PathBuf {
+    std::env::temp_dir().join("folhaweb-lotes")
+}
+
+/// Grava o ZIP ou o PDF do lote em disco e devolve o nome de download e o caminho
+async fn gerar_arquivo_lote(
+    pool: &PgPool,
+    input: &GerarLoteContraCheque,
+    ids: &[i64],
+) -> Result<(String, PathBuf)> {
+    let contra_cheques = FolhaService::new()
+        .contra_cheques_do_orgao(pool, input.orgao_id, ids)
+        .await?;
+
+    let titulo = format!(
+        "contra-cheques-{}-{}-{:02}",
+        input.orgao_id, input.ano, input.mes
+    );
+    let pdf = input.formato == LOTE_FORMATO_PDF;
+    let nome = format!("{}.{}", titulo, if pdf { "pdf" } else { "zip" });
+    let pasta = pasta_lotes();
+    tokio::fs::create_dir_all(&pasta).await?;
+    let caminho = pasta.join(format!("{}-{}", Uuid::new_v4(), nome));
+    let emitido_em = Utc::now();
+
+    // montar centenas de PDFs é trabalho de CPU; fora das threads do runtime
+    let destino = caminho.clone();
+    let gravado = tokio::task::spawn_blocking(move || {
+        let arquivo = std::fs::File::create(&destino)?;
+        if pdf {
+            relatorio::contra_cheques_pdf(arquivo, &titulo, &contra_cheques, emitido_em)
+        } else {
+            relatorio::contra_cheques_zip(arquivo, &contra_cheques)?.sync_all()?;
+            Ok(())
+        }
+    })
+    .await?;
+
+    if let Err(err) = gravado {
+        let _ = tokio::fs::remove_file(&caminho).await;
+        return Err(err);
+    }
+    Ok((nome, caminho))
+}
+
+/// Informe anual de rendimentos dos servidores, com o órgão como fonte pagadora. O
+/// informe é de uma página e não grava emissão, por isso o lote do órgão sai na hora
+pub struct InformeRendimentosService {
+    folha_repo: FolhaRepository,
+    orgao_repo: OrgaoRepository,
+}
+
+impl InformeRendimentosService {
+    pub fn new() -> Self {
+        Self {
+            folha_repo: FolhaRepository,
+            orgao_repo: OrgaoRepository,
+        }
+    }
+
+    /// PDF do servidor informado ou, sem servidor, o lote do órgão como ZIP ou PDF único
+    pub async fn gerar(&self, pool: &PgPool, params: &InformeRendimentosParams) -> Result<Arquivo> {
+        let (Some(orgao_id), Some(ano)) = (params.orgao_id, params.ano) else {
+            return Err(AppError::InvalidInput("Informe o órgão e o ano-calendário".to_string()).into());
+        };
+        if !(2000..=Local::now().year()).contains(&ano) {
+            return Err(AppError::InvalidInput(format!("Ano-calendário inválido: {}", ano)).into());
+        }
+        let formato = params.formato.to_uppercase();
+        if formato != LOTE_FORMATO_ZIP && formato != LOTE_FORMATO_PDF {
+            return Err(AppError::InvalidInput("Formato deve ser ZIP ou PDF".to_string()).into());
+        }
+
+        let linhas = self
+            .folha_repo
+            .valores_informe(pool, orgao_id, ano, params.servidor_id)
+            .await?;
+        let timbre = Arc::new(self.orgao_repo.timbre(pool, orgao_id).await?);
+        let informes = informe::montar(ano, timbre, linhas);
+        if informes.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "Nenhum rendimento a informar no órgão em {}",
+                ano
+            ))
+            .into());
+        }
+
+        let servidor = params.servidor_id.is_some();
+        let emitido_em = Utc::now();
+        tokio::task::spawn_blocking(move || Self::arquivo(informes, servidor, &formato, orgao_id, ano, emitido_em))
+            .await?
+    }
+
+    fn arquivo(
+        informes: Vec<InformeRendimentos>,
+        servidor: bool,
+        formato: &str,
+        orgao_id: i32,
+        ano: i32,
+        emitido_em: DateTime<Utc>,
+    ) -> Result<Arquivo> {
+        if servidor && let Some(informe) = informes.first() {
+            return Ok(Arquivo {
+                nome: format!("informe-rendimentos-{}-{}.pdf", ano, relatorio::nome_arquivo(&informe.nome)),
+                conteudo: relatorio::informe_rendimentos(informe, emitido_em)?,
+            });
+        }
+
+        let nome = format!("informes-rendimentos-{}-{}", orgao_id, ano);
+        if formato == LOTE_FORMATO_PDF {
+            let conteudo = relatorio::informes_rendimentos_pdf(&nome, &informes, emitido_em)?;
+            Ok(Arquivo {
+                nome: format!("{}.pdf", nome),
+                conteudo,
+            })
+        } else {
+            let conteudo = relatorio::informes_rendimentos_zip(&informes, emitido_em)?;
+            Ok(Arquivo {
+                nome: format!("{}.zip", nome),
+                conteudo,
+            })
+        }
+    }
+}
+
+/// Validação pública dos contra-cheques emitidos
+pub struct AutenticacaoContraChequeService {
+    repo: EmissaoContraChequeRepository,
+}
+
+impl AutenticacaoContraChequeService {
+    pub fn new() -> Self {
+        Self {
+            repo: EmissaoContraChequeRepository,
+        }
+    }
+
+    /// Dados do contra-cheque emitido com o código; None se o código não existir
+    pub async fn autenticar(
+        &self,
+        pool: &PgPool,
+        codigo: &str,
+    ) -> Result<Option<AutenticacaoContraCheque>> {
+        let Some(codigo) = autenticacao::normalizar_codigo(codigo) else {
+            return Ok(None);
+        };
+
+        let emissao = self.repo.get_by_codigo(pool, &codigo).await?;
+        Ok(emissao.map(|e| AutenticacaoContraCheque {
+            codigo: e.codigo,
+            orgao_nome: e.orgao_nome,
+            servidor: autenticacao::mascarar_nome(&e.servidor_nome),
+            competencia: relatorio::competencia_por_extenso(e.ano, e.mes),
+            folha: match descricao_tipo_folha(e.tipo_folha, e.sequencia) {
+                descricao if descricao.is_empty() => "MENSAL".to_string(),
+                descricao => descricao,
+            },
+            valor_liquido: e.valor_liquido,
+            emitido_em: e.emitido_em,
+        }))
+    }
+}
+
+/// Maior sequencial do Id do evento (5 dígitos) dentro do mesmo segundo de geração
+const ESOCIAL_SEQUENCIAL_MAXIMO: u32 = 99_999;
+
+/// Geração e validação dos eventos periódicos do eSocial (S-1200 e S-1210) da
+/// competência fechada. A transmissão (assinatura e envio) fica fora do sistema
+pub struct EsocialService {
+    repo: EventoEsocialRepository,
+    competencia_repo: CompetenciaRepository,
+    orgao_repo: OrgaoRepository,
+}
+
+impl EsocialService {
+    pub fn new() -> Self {
+        Self {
+            repo: EventoEsocialRepository,
+            competencia_repo: CompetenciaRepository,
+            orgao_repo: OrgaoRepository,
+        }
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        params: &EventoEsocialParams,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<EventoEsocial>> {
+        let filtros: Vec<(&str, String)> = [
+            ("e.orgao_id", params.orgao_id.map(|v| v.to_string())),
+            ("e.ano", params.ano.map(|v| v.to_string())),
+            ("e.mes", params.mes.map(|v| v.to_string())),
+            ("e.tipo", params.tipo.clone()),
+            ("e.situacao", params.situacao.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(campo, valor)| valor.map(|v| (campo, v)))
+        .collect();
+        Ok(self
+            .repo
+            .get_paginated_filtrado(pool, params.find.as_deref(), page, page_size, &filtros)
+            .await?)
+    }
+
+    /// Órgão como empregador; o CNPJ é obrigatório em todos os eventos
+    fn empregador(orgao: &Orgao) -> Result<Empregador> {
+        let cnpj = orgao.cnpj.as_deref().map(somente_digitos).unwrap_or_default();
+        if cnpj.len() != 14 {
+            return Err(AppError::InvalidInput(format!(
+                "Órgão {} sem CNPJ: cadastre o CNPJ antes de gerar o eSocial",
+                orgao.nome
+            ))
+            .into());
+        }
+        Ok(Empregador {
+            cnpj,
+            ambiente: orgao.esocial_ambiente,
+            tabela_rubrica: orgao.esocial_tabela_rubrica.clone().unwrap_or_default(),
+            lotacao: orgao.esocial_lotacao.clone().unwrap_or_default(),
+        })
+    }
+
+    /// Situação e erros do evento: pendências do cadastro invalidam sem chamar o XSD;
+    /// sem validador disponível o evento fica só gerado
+    fn situacao(
+        validador: &ValidadorXsd,
+        tipo: &str,
+        evento: &EventoGerado,
+    ) -> (&'static str, Option<String>) {
+        if !evento.pendencias.is_empty() {
+            return (EVENTO_INVALIDO, Some(evento.pendencias.join("\n")));
+        }
+        Self::situacao_validacao(validador.validar(tipo, &evento.xml))
+    }
+
+    fn situacao_validacao(validacao: Validacao) -> (&'static str, Option<String>) {
+        match validacao {
+            Validacao::Valido => (EVENTO_VALIDADO, None),
+            Validacao::Invalido(erros) => (EVENTO_INVALIDO, Some(erros.join("\n"))),
+            Validacao::Indisponivel(motivo) => (EVENTO_GERADO, Some(motivo)),
+        }
+    }
+
+    /// Gera o S-1200 de cada servidor com folha na competência e, com a data de
+    /// pagamento, o S-1210. Gerar de novo substitui os eventos anteriores
+    pub async fn gerar(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        competencia_id: i32,
+        input: GerarEsocial,
+    ) -> Result<ResultadoEsocial> {
+        if !usuario.has_permission(PERM_ESOCIAL_GERAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+
+        let competencia = self.competencia_repo.get_by_id(pool, competencia_id).await?;
+        if competencia.situacao != COMPETENCIA_FECHADA {
+            return Err(AppError::Conflict(format!(
+                "Competência {:02}/{} está {}: feche a competência antes de gerar o eSocial",
+                competencia.mes, competencia.ano, competencia.situacao
+            ))
+            .into());
+        }
+        let (ano, mes) = (competencia.ano, competencia.mes);
+
+        if let Some(data_pagamento) = input.data_pagamento
+            && (data_pagamento.year(), data_pagamento.month() as i32) < (ano, mes)
+        {
+            return Err(AppError::InvalidInput(
+                "Data de pagamento anterior à competência".to_string(),
+            )
+            .into());
+        }
+
+        let orgao = self.orgao_repo.get_by_id(pool, competencia.orgao_id).await?;
+        let empregador = Self::empregador(&orgao)?;
+        if empregador.tabela_rubrica.is_empty() || empregador.lotacao.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "Órgão {}: informe a tabela de rubricas e a lotação tributária do eSocial",
+                orgao.nome
+            ))
+            .into());
+        }
+
+        let trabalhadores = self
+            .repo
+            .trabalhadores(pool, competencia.orgao_id, ano, mes)
+            .await?;
+        if trabalhadores.is_empty() {
+            return Err(AppError::InvalidInput("Competência sem folhas".to_string()).into());
+        }
+        let por_servidor = if input.data_pagamento.is_some() { 2 } else { 1 };
+        if trabalhadores.len() * por_servidor > ESOCIAL_SEQUENCIAL_MAXIMO as usize {
+            return Err(AppError::InvalidInput(format!(
+                "Competência com mais de {} eventos",
+                ESOCIAL_SEQUENCIAL_MAXIMO
+            ))
+            .into());
+        }
+
+        // montar e validar centenas de XMLs com o xmllint é bloqueante
+        let orgao_id = competencia.orgao_id;
+        let eventos = tokio::task::spawn_blocking(move || {
+            let validador = ValidadorXsd::new();
+            let gerado_em = Local::now().naive_local();
+            let mut sequencial = 0;
+            let mut proximo_id = || {
+                sequencial += 1;
+                esocial::id_evento(&empregador.cnpj, gerado_em, sequencial)
+            };
+
+            let mut eventos = Vec::new();
+            for trabalhador in &trabalhadores {
+                let mut gerados = vec![(
+                    ESOCIAL_S1200,
+                    esocial::s1200(proximo_id(), &empregador, ano, mes, trabalhador),
+                )];
+                if let Some(data_pagamento) = input.data_pagamento {
+                    gerados.push((
+                        ESOCIAL_S1210,
+                        esocial::s1210(proximo_id(), &empregador, ano, mes, data_pagamento, trabalhador),
+                    ));
+                }
+                for (tipo, evento) in gerados {
+                    let (situacao, erros) = Self::situacao(&validador, tipo, &evento);
+                    eventos.push(GravarEventoEsocial {
+                        orgao_id,
+                        ano,
+                        mes,
+                        tipo,
+                        servidor_id: trabalhador.servidor_id,
+                        total_folhas: trabalhador.demonstrativos.len() as i32,
+                        id_evento: evento.id,
+                        xml: evento.xml,
+                        situacao,
+                        erros,
+                    });
+                }
+            }
+            eventos
+        })
+        .await?;
+
+        let mut resultado = ResultadoEsocial::default();
+        let mut tx = pool.begin().await?;
+        self.repo.excluir_competencia(&mut tx, orgao_id, ano, mes).await?;
+        for evento in &eventos {
+            self.repo.salvar(&mut tx, evento, &usuario.username).await?;
+            match evento.situacao {
+                EVENTO_VALIDADO => resultado.validados += 1,
+                EVENTO_INVALIDO => resultado.invalidos += 1,
+                _ => resultado.gerados += 1,
+            }
+        }
+        tx.commit().await?;
+
+        Ok(resultado)
+    }
+
+    /// Valida de novo o XML guardado (depois de instalar os XSD, por exemplo)
+    pub async fn validar(&self, pool: &PgPool, usuario: &CurrentUser, id: i64) -> Result<String> {
+        if !usuario.has_permission(PERM_ESOCIAL_GERAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+
+        let (_, tipo, xml) = self.repo.xml(pool, id).await?;
+        let (situacao, erros) = tokio::task::spawn_blocking(move || {
+            Self::situacao_validacao(ValidadorXsd::new().validar(&tipo, &xml))
+        })
+        .await?;
+
+        self.repo
+            .atualizar_validacao(pool, id, situacao, erros.as_deref())
+            .await?;
+        Ok(situacao.to_string())
+    }
+
+    pub async fn xml(&self, pool: &PgPool, id: i64) -> Result<Arquivo> {
+        let (id_evento, _, xml) = self.repo.xml(pool, id).await?;
+        Ok(Arquivo {
+            nome: format!("{}.xml", id_evento),
+            conteudo: xml.into_bytes(),
+        })
+    }
+
+    /// ZIP com os eventos validados da competência, prontos para assinar e transmitir
+    pub async fn zip_validados(&self, pool: &PgPool, orgao_id: i32, ano: i32, mes: i32) -> Result<Arquivo> {
+        let eventos = self.repo.validados(pool, orgao_id, ano, mes).await?;
+        if eventos.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "Nenhum evento validado em {:02}/{}",
+                mes, ano
+            ))
+            .into());
+        }
+        Ok(Arquivo {
+            nome: format!("esocial-{}-{}-{:02}.zip", orgao_id, ano, mes),
+            conteudo: esocial::eventos_zip(&eventos)?,
+        })
+    }
+}
+
+/// Fila por órgão dos eventos não periódicos (S-2200, S-2206, S-2299 e S-2300). As
+/// alterações do cadastro do servidor entram como pendentes; a geração monta o XML
+/// com o cadastro atual e valida contra o XSD
+pub struct FilaEsocialService {
+    repo: FilaEsocialRepository,
+    orgao_repo: OrgaoRepository,
+    vinculo_repo: TipoVinculoRepository,
+}
+
+impl FilaEsocialService {
+    pub fn new() -> Self {
+        Self {
+            repo: FilaEsocialRepository,
+            orgao_repo: OrgaoRepository,
+            vinculo_repo: TipoVinculoRepository,
+        }
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        params: &FilaEsocialParams,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<EventoFilaEsocial>> {
+        let filtros: Vec<(&str, String)> = [
+            ("f.orgao_id", params.orgao_id.map(|v| v.to_string())),
+            ("f.tipo", params.tipo.clone()),
+            ("f.situacao", params.situacao.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(campo, valor)| valor.map(|v| (campo, v)))
+        .collect();
+        Ok(self
+            .repo
+            .get_paginated_filtrado(pool, params.find.as_deref(), page, page_size, &filtros)
+            .await?)
+    }
+
+    /// Enfileira os eventos que a alteração do cadastro exige (ver `esocial::eventos_da_alteracao`)
+    pub async fn registrar_alteracao(
+        &self,
+        pool: &PgPool,
+        antes: Option<&Servidor>,
+        depois: &Servidor,
+    ) -> Result<()> {
+        let Some(orgao_id) = depois.orgao_id else {
+            return Ok(());
+        };
+        let categoria = match depois.vinculo_id {
+            Some(vinculo_id) => self.vinculo_repo.get_by_id(pool, vinculo_id).await?.categoria_esocial,
+            None => None,
+        };
+        let contrato = |servidor: &Servidor| Contrato {
+            orgao_id: servidor.orgao_id,
+            data_admissao: servidor.data_admissao,
+            cargo_id: servidor.cargo_id,
+            vinculo_id: servidor.vinculo_id,
+            categoria,
+            data_desligamento: servidor.data_desligamento,
+        };
+
+        let admissao_na_fila = self.repo.admissao_na_fila(pool, depois.id, orgao_id).await?;
+        let eventos = esocial::eventos_da_alteracao(
+            antes.map(contrato).as_ref(),
+            &contrato(depois),
+            admissao_na_fila,
+            Local::now().date_naive(),
+        );
+        for (tipo, data_evento) in eventos {
+            self.repo
+                .enfileirar(pool, orgao_id, depois.id, tipo, data_evento)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Gera o XML de todos os eventos pendentes do órgão
+    pub async fn gerar_pendentes(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        orgao_id: i32,
+    ) -> Result<ResultadoFilaEsocial> {
+        if !usuario.has_permission(PERM_ESOCIAL_GERAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+
+        let mut eventos = Vec::new();
+        for id in self.repo.pendentes(pool, orgao_id).await? {
+            eventos.push(self.repo.get_by_id(pool, id).await?);
+        }
+        if eventos.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Nenhum evento pendente na fila do órgão".to_string(),
+            )
+            .into());
+        }
+        self.gerar(pool, usuario, orgao_id, eventos).await
+    }
+
+    /// Gera de novo um evento (rejeitado ou já gerado) com o cadastro atual e um novo Id
+    pub async fn regerar(&self, pool: &PgPool, usuario: &CurrentUser, id: i64) -> Result<ResultadoFilaEsocial> {
+        if !usuario.has_permission(PERM_ESOCIAL_GERAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+
+        let evento = self.repo.get_by_id(pool, id).await?;
+        self.gerar(pool, usuario, evento.orgao_id, vec![evento]).await
+    }
+
+    async fn gerar(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        orgao_id: i32,
+        eventos: Vec<EventoFilaEsocial>,
+    ) -> Result<ResultadoFilaEsocial> {
+        if eventos.len() > ESOCIAL_SEQUENCIAL_MAXIMO as usize {
+            return Err(AppError::InvalidInput(format!(
+                "Fila com mais de {} eventos pendentes",
+                ESOCIAL_SEQUENCIAL_MAXIMO
+            ))
+            .into());
+        }
+        let orgao = self.orgao_repo.get_by_id(pool, orgao_id).await?;
+        let empregador = EsocialService::empregador(&orgao)?;
+
+        let mut entradas = Vec::new();
+        for evento in eventos {
+            let cadastro = self.repo.cadastro(pool, evento.servidor_id).await?;
+            entradas.push((evento, cadastro));
+        }
+        let gerado_em = Local::now().naive_local();
+        let ultimo = self
+            .repo
+            .ultimo_sequencial(pool, &esocial::prefixo_id_evento(&empregador.cnpj, gerado_em))
+            .await?;
+        if ultimo + entradas.len() as u32 > ESOCIAL_SEQUENCIAL_MAXIMO {
+            return Err(AppError::Conflict(
+                "Limite de Ids do eSocial neste segundo atingido, tente de novo".to_string(),
+            )
+            .into());
+        }
+
+        // o xmllint é bloqueante
+        let gerados = tokio::task::spawn_blocking(move || {
+            let validador = ValidadorXsd::new();
+            entradas
+                .into_iter()
+                .zip(ultimo + 1..)
+                .map(|((evento, cadastro), sequencial)| {
+                    let id_evento = esocial::id_evento(&empregador.cnpj, gerado_em, sequencial);
+                    let gerado =
+                        esocial::nao_periodico(&evento.tipo, id_evento, &empregador, &cadastro, evento.data_evento);
+                    let (situacao, erros) = if !gerado.pendencias.is_empty() {
+                        (FILA_REJEITADO, Some(gerado.pendencias.join("\n")))
+                    } else {
+                        match validador.validar(&evento.tipo, &gerado.xml) {
+                            Validacao::Valido => (FILA_GERADO, None),
+                            Validacao::Invalido(erros) => (FILA_REJEITADO, Some(erros.join("\n"))),
+                            // sem XSD o XML fica gerado, com o aviso de que não foi validado
+                            Validacao::Indisponivel(motivo) => (FILA_GERADO, Some(motivo)),
+                        }
+                    };
+                    (evento.id, gerado, situacao, erros)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await?;
+
+        let mut resultado = ResultadoFilaEsocial::default();
+        let mut tx = pool.begin().await?;
+        for (id, gerado, situacao, erros) in &gerados {
+            self.repo
+                .gravar_geracao(&mut tx, *id, gerado, situacao, erros.as_deref(), &usuario.username)
+                .await?;
+            if *situacao == FILA_REJEITADO {
+                resultado.rejeitados += 1;
+            } else {
+                resultado.gerados += 1;
+            }
+        }
+        tx.commit().await?;
+
+        Ok(resultado)
+    }
+
+    pub async fn xml(&self, pool: &PgPool, id: i64) -> Result<Arquivo> {
+        let Some((id_evento, xml)) = self.repo.xml(pool, id).await? else {
+            return Err(AppError::InvalidInput("Evento ainda não gerado".to_string()).into());
+        };
+        Ok(Arquivo {
+            nome: format!("{}.xml", id_evento),
+            conteudo: xml.into_bytes(),
+        })
+    }
+}
+
+pub struct RemessaCnabService {
+    repo: RemessaCnabRepository,
+    item_repo: ItemRemessaCnabRepository,
+    competencia_repo: CompetenciaRepository,
+    orgao_repo: OrgaoRepository,
+}
+
+impl RemessaCnabService {
+    pub fn new() -> Self {
+        Self {
+            repo: RemessaCnabRepository,
+            item_repo: ItemRemessaCnabRepository,
+            competencia_repo: CompetenciaRepository,
+            orgao_repo: OrgaoRepository,
+        }
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        params: &RemessaCnabParams,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<RemessaCnab>> {
+        let filtros: Vec<(&str, String)> = [
+            ("r.orgao_id", params.orgao_id.map(|v| v.to_string())),
+            ("r.ano", params.ano.map(|v| v.to_string())),
+            ("r.mes", params.mes.map(|v| v.to_string())),
+        ]
+        .into_iter()
+        .filter_map(|(campo, valor)| valor.map(|v| (campo, v)))
+        .collect();
+        Ok(self
+            .repo
+            .get_paginated_filtrado(pool, params.find.as_deref(), page, page_size, &filtros)
+            .await?)
+    }
+
+    /// Órgão como pagador: CNPJ, conta de débito e convênio, no banco exigido pela variação
+    fn pagador(layout: Layout, orgao: &Orgao) -> Result<Pagador> {
+        let cnpj = orgao.cnpj.as_deref().map(somente_digitos).unwrap_or_default();
+        if cnpj.len() != 14 {
+            return Err(AppError::InvalidInput(format!(
+                "Órgão {} sem CNPJ: cadastre o CNPJ antes de gerar a remessa",
+                orgao.nome
+            ))
+            .into());
+        }
+        let (Some(banco), Some(agencia), Some(conta), Some(convenio)) = (
+            orgao.cnab_banco.clone(),
+            orgao.cnab_agencia.clone(),
+            orgao.cnab_conta.clone(),
+            orgao.cnab_convenio.clone(),
+        ) else {
+            return Err(AppError::InvalidInput(format!(
+                "Órgão {}: informe o banco, a agência, a conta e o convênio de pagamento",
+                orgao.nome
+            ))
+            .into());
+        };
+        if let Some(exigido) = layout.banco()
+            && exigido != banco
+        {
+            return Err(AppError::InvalidInput(format!(
+                "O leiaute {} exige a conta de débito no banco {}",
+                layout.codigo(),
+                exigido
+            ))
+            .into());
+        }
+        if let Some(erro) = layout.erro_convenio(&convenio) {
+            return Err(AppError::InvalidInput(erro).into());
+        }
+
+        Ok(Pagador {
+            banco,
+            cnpj,
+            convenio,
+            agencia,
+            conta,
+            nome: orgao.nome.clone(),
+            logradouro: orgao.logradouro.clone().unwrap_or_default(),
+            numero: orgao.numero.clone().unwrap_or_default(),
+            complemento: orgao.complemento.clone().unwrap_or_default(),
+            cidade: orgao.municipio_nome.clone().unwrap_or_default(),
+            cep: orgao.cep.clone().unwrap_or_default(),
+            uf: orgao.uf_sigla.clone().unwrap_or_default(),
+        })
+    }
+
+    /// Remessa de crédito dos líquidos da competência fechada. Folhas sem líquido a
+    /// pagar ou sem dados bancários ficam de fora e voltam no resultado; cada geração
+    /// usa o próximo NSA do órgão
+    pub async fn gerar(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        competencia_id: i32,
+        input: GerarRemessaCnab,
+    ) -> Result<ResultadoRemessaCnab> {
+        if !usuario.has_permission(PERM_CNAB_GERAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+
+        let layout = Layout::from_codigo(&input.layout)
+            .ok_or_else(|| AppError::InvalidInput("Leiaute CNAB inválido".to_string()))?;
+        let data_pagamento = input
+            .data_pagamento
+            .ok_or_else(|| AppError::InvalidInput("Informe a data de pagamento".to_string()))?;
+        if data_pagamento < Local::now().date_naive() {
+            return Err(AppError::InvalidInput("Data de pagamento já passou".to_string()).into());
+        }
+
+        let competencia = self.competencia_repo.get_by_id(pool, competencia_id).await?;
+        if competencia.situacao != COMPETENCIA_FECHADA {
+            return Err(AppError::Conflict(format!(
+                "Competência {:02}/{} está {}: feche a competência antes de gerar a remessa",
+                competencia.mes, competencia.ano, competencia.situacao
+            ))
+            .into());
+        }
+        let (ano, mes) = (competencia.ano, competencia.mes);
+
+        let orgao = self.orgao_repo.get_by_id(pool, competencia.orgao_id).await?;
+        let pagador = Self::pagador(layout, &orgao)?;
+
+        let creditos = self.repo.creditos(pool, orgao.id, ano, mes).await?;
+        if creditos.is_empty() {
+            return Err(AppError::InvalidInput("Competência sem folhas".to_string()).into());
+        }
+        let mut ignorados = Vec::new();
+        let mut pagaveis = Vec::new();
+        for credito in creditos {
+            match credito.pendencia() {
+                Some(pendencia) => ignorados.push(format!("{}: {}", credito.nome, pendencia)),
+                None => pagaveis.push(credito),
+            }
+        }
+        if pagaveis.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "Nenhuma folha a pagar: {}",
+                ignorados.join("; ")
+            ))
+            .into());
+        }
+        let valor_total: BigDecimal = pagaveis.iter().map(|c| &c.valor).sum();
+
+        let mut tx = pool.begin().await?;
+        let nsa = self.repo.proximo_nsa(&mut tx, orgao.id).await?;
+        let conteudo = cnab::remessa(
+            layout,
+            &pagador,
+            nsa,
+            Local::now().naive_local(),
+            data_pagamento,
+            &pagaveis,
+        );
+        let remessa = GravarRemessaCnab {
+            orgao_id: orgao.id,
+            ano,
+            mes,
+            layout: layout.codigo(),
+            banco: pagador.banco.clone(),
+            nsa,
+            data_pagamento,
+            valor_total: valor_total.clone(),
+            nome_arquivo: format!("cnab240-{}-{}-{:02}-{:06}.rem", orgao.id, ano, mes, nsa),
+            conteudo: conteudo.into_bytes(),
+            creditos: pagaveis,
+        };
+        let id = self.repo.salvar(&mut tx, &remessa, &usuario.username).await?;
+        tx.commit().await?;
+
+        Ok(ResultadoRemessaCnab {
+            id,
+            nsa,
+            pagamentos: remessa.creditos.len(),
+            valor_total,
+            ignorados,
+        })
+    }
+
+    pub async fn arquivo(&self, pool: &PgPool, id: i32) -> Result<Arquivo> {
+        let (nome, conteudo) = self.repo.arquivo(pool, id).await?;
+        Ok(Arquivo { nome, conteudo })
+    }
+
+    /// Importa o retorno do banco para a remessa: cada segmento A é casado pelo "seu
+    /// número" com o crédito da folha e recebe a situação das ocorrências
+    pub async fn importar_retorno(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        remessa_id: i32,
+        conteudo: &[u8],
+    ) -> Result<ResultadoRetornoCnab> {
+        if !usuario.has_permission(PERM_CNAB_GERAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+
+        let retorno = cnab::ler_retorno(conteudo)?;
+        if retorno.creditos.is_empty() {
+            return Err(AppError::InvalidInput("Retorno sem pagamentos (segmento A)".to_string()).into());
+        }
+        let remessa = self.repo.get_by_id(pool, remessa_id).await?;
+        let orgao = self.orgao_repo.get_by_id(pool, remessa.orgao_id).await?;
+        if retorno.banco != remessa.banco {
+            return Err(AppError::InvalidInput(format!(
+                "Retorno do banco {}, mas a remessa foi enviada ao banco {}",
+                retorno.banco, remessa.banco
+            ))
+            .into());
+        }
+        if orgao.cnpj.as_deref().map(somente_digitos).as_deref() != Some(retorno.cnpj.as_str()) {
+            return Err(AppError::InvalidInput(format!(
+                "Retorno do CNPJ {}, que não é o do órgão {}",
+                retorno.cnpj, orgao.nome
+            ))
+            .into());
+        }
+
+        let seus_numeros = self.repo.seus_numeros(pool, remessa_id).await?;
+        let mut resultado = ResultadoRetornoCnab::default();
+        let mut tx = pool.begin().await?;
+        for credito in &retorno.creditos {
+            if !seus_numeros.contains(&credito.seu_numero) {
+                resultado.nao_encontrados.push(credito.seu_numero.clone());
+                continue;
+            }
+            self.repo.atualizar_retorno(&mut tx, remessa_id, credito).await?;
+            match credito.situacao() {
+                CREDITO_PAGO => resultado.pagos += 1,
+                CREDITO_AGENDADO => resultado.agendados += 1,
+                CREDITO_REJEITADO => resultado.rejeitados += 1,
+                _ => {}
+            }
+        }
+        tx.commit().await?;
+
+        Ok(resultado)
+    }
+
+    /// Créditos das remessas com a situação do retorno (os rejeitados a corrigir, por exemplo)
+    pub async fn get_conciliacao(
+        &self,
+        pool: &PgPool,
+        params: &ConciliacaoCnabParams,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<ItemRemessaCnab>> {
+        let filtros: Vec<(&str, String)> = [
+            ("r.orgao_id", params.orgao_id.map(|v| v.to_string())),
+            ("r.ano", params.ano.map(|v| v.to_string())),
+            ("r.mes", params.mes.map(|v| v.to_string())),
+            ("i.remessa_id", params.remessa_id.map(|v| v.to_string())),
+            ("i.situacao", params.situacao.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(campo, valor)| valor.map(|v| (campo, v)))
+        .collect();
+        Ok(self
+            .item_repo
+            .get_paginated_filtrado(pool, params.find.as_deref(), page, page_size, &filtros)
+            .await?)
+    }
+}
+
+pub struct ConsignatariaService {
+    repo: ConsignatariaRepository,
+    rubrica_repo: RubricaRepository,
+    credencial_repo: CredencialConsignatariaRepository,
+}
+
+impl ConsignatariaService {
+    pub fn new() -> Self {
+        Self {
+            repo: ConsignatariaRepository,
+            rubrica_repo: RubricaRepository,
+            credencial_repo: CredencialConsignatariaRepository,
+        }
+    }
+
+    fn normalizar(nome: &mut String, cnpj: &mut Option<String>) -> Result<()> {
+        *nome = nome.trim().to_string();
+        if nome.is_empty() {
+            return Err(AppError::InvalidInput("Nome da consignatária é obrigatório".to_string()).into());
+        }
+        if let Some(valor) = cnpj.as_mut() {
+            if !cnpj_valido(valor) {
+                return Err(AppError::InvalidInput(format!("CNPJ {} inválido", valor)).into());
+            }
+            *valor = normalizar_cnpj(valor);
+        }
+        Ok(())
+    }
+
+    /// A rubrica recebe só as parcelas lançadas pelo cálculo: desconto comum, fora do
+    /// cálculo automático e de nenhuma outra consignatária
+    async fn validar_rubrica(&self, pool: &PgPool, rubrica_id: Option<i32>, id: Option<i32>) -> Result<()> {
+        let Some(rubrica_id) = rubrica_id else {
+            return Err(AppError::InvalidInput("Informe a rubrica de desconto".to_string()).into());
+        };
+        let rubrica = self.rubrica_repo.get_by_id(pool, rubrica_id).await?;
+        if rubrica.tipo != RUBRICA_DESCONTO || rubrica.codigo_sistema.is_some() || rubrica.pensao_alimenticia {
+            return Err(AppError::InvalidInput(format!(
+                "Rubrica {} deve ser um desconto comum (sem cálculo automático nem pensão alimentícia)",
+                rubrica.codigo
+            ))
+            .into());
+        }
+        if self.repo.rubrica_em_uso(pool, rubrica_id, id).await? {
+            return Err(AppError::Conflict(format!(
+                "Rubrica {} já é usada por outra consignatária",
+                rubrica.codigo
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<Consignataria> {
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    pub async fn create(&self, pool: &PgPool, mut input: CreateConsignataria) -> Result<Consignataria> {
+        Self::normalizar(&mut input.nome, &mut input.cnpj)?;
+        self.validar_rubrica(pool, input.rubrica_id, None).await?;
+        Ok(self.repo.create(pool, input).await?)
+    }
+
+    pub async fn update(&self, pool: &PgPool, id: i32, mut input: UpdateConsignataria) -> Result<Consignataria> {
+        Self::normalizar(&mut input.nome, &mut input.cnpj)?;
+        self.validar_rubrica(pool, input.rubrica_id, Some(id)).await?;
+        Ok(self.repo.update(pool, id, input).await?)
+    }
+
+    pub async fn delete(&self, pool: &PgPool, id: i32) -> Result<()> {
+        if self.repo.em_uso(pool, id).await? {
+            return Err(AppError::Conflict(
+                "Consignatária com contratos não pode ser excluída; desative-a".to_string(),
+            )
+            .into());
+        }
+        Ok(self.repo.delete(pool, id).await?)
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        find: Option<&str>,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<Consignataria>> {
+        Ok(self.repo.get_paginated(pool, find, page, page_size, None).await?)
+    }
+
+    pub async fn credenciais(&self, pool: &PgPool, id: i32) -> Result<Vec<CredencialConsignataria>> {
+        Ok(self.credencial_repo.get_by_consignataria(pool, id).await?)
+    }
+
+    /// Gera uma credencial da API de margem e devolve o token, que só é exibido agora:
+    /// do segredo fica gravado apenas o hash
+    pub async fn gerar_credencial(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        id: i32,
+        input: CreateCredencialConsignataria,
+    ) -> Result<String> {
+        if !usuario.has_permission(PERM_CONSIGNATARIA_CREDENCIAL) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        let consignataria = self.repo.get_by_id(pool, id).await?;
+        if !consignataria.is_active {
+            return Err(AppError::InvalidInput(format!(
+                "Consignatária {} está inativa",
+                consignataria.nome
+            ))
+            .into());
+        }
+
+        let (chave, segredo) = consignacao::gerar_credencial();
+        let segredo_hash = UserService::get_password_hash(&segredo)
+            .map_err(|err| anyhow::anyhow!("Erro ao gerar hash do segredo: {}", err))?;
+        let descricao = input.descricao.map(|d| d.trim().chars().take(100).collect::<String>());
+        self.credencial_repo
+            .create(pool, id, &chave, &segredo_hash, descricao.as_deref(), &usuario.username)
+            .await?;
+
+        Ok(format!("{}.{}", chave, segredo))
+    }
+
+    /// Revoga a credencial e devolve a consignatária dela
+    pub async fn revogar_credencial(&self, pool: &PgPool, usuario: &CurrentUser, credencial_id: i32) -> Result<i32> {
+        if !usuario.has_permission(PERM_CONSIGNATARIA_CREDENCIAL) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        Ok(self.credencial_repo.revogar(pool, credencial_id).await?)
+    }
+}
+
+/// Contratos de consignação e controle da margem consignável
+pub struct ConsignacaoService {
+    repo: ConsignacaoRepository,
+    consignataria_repo: ConsignatariaRepository,
+    reserva_repo: ReservaMargemRepository,
+}
+
+impl ConsignacaoService {
+    pub fn new() -> Self {
+        Self {
+            repo: ConsignacaoRepository,
+            consignataria_repo: ConsignatariaRepository,
+            reserva_repo: ReservaMargemRepository,
+        }
+    }
+
+    fn validar_contrato(
+        contrato: &mut String,
+        valor_parcela: &BigDecimal,
+        total_parcelas: i32,
+        mes_inicio: i32,
+    ) -> Result<()> {
+        *contrato = contrato.trim().to_string();
+        if contrato.is_empty() || contrato.chars().count() > 30 {
+            return Err(AppError::InvalidInput(
+                "Número do contrato é obrigatório e deve ter até 30 caracteres".to_string(),
+            )
+            .into());
+        }
+        if *valor_parcela <= BigDecimal::zero() {
+            return Err(AppError::InvalidInput("Valor da parcela deve ser maior que zero".to_string()).into());
+        }
+        if total_parcelas < 1 {
+            return Err(AppError::InvalidInput("Informe a quantidade de parcelas".to_string()).into());
+        }
+        if !(1..=12).contains(&mes_inicio) {
+            return Err(AppError::InvalidInput("Mês da primeira parcela inválido".to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Margem consignável do servidor pela última folha calculada; o contrato e a reserva
+    /// informados ficam fora da parte comprometida (alteração do próprio contrato, averbação
+    /// com a reserva feita para ele)
+    pub async fn margem(
+        &self,
+        pool: &PgPool,
+        servidor_id: i32,
+        ignorar_id: Option<i32>,
+        ignorar_reserva: Option<i32>,
+    ) -> Result<Margem> {
+        let mut tx = pool.begin().await?;
+        let margem = self.margem_em(&mut tx, servidor_id, ignorar_id, ignorar_reserva).await?;
+        tx.commit().await?;
+        Ok(margem)
+    }
+
+    /// Trava a margem do servidor até o fim da transação e a lê: o que for averbado ou
+    /// reservado nessa transação não corre junto com outra operação na mesma margem
+    async fn margem_travada(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        servidor_id: i32,
+        ignorar_id: Option<i32>,
+        ignorar_reserva: Option<i32>,
+    ) -> Result<Margem> {
+        self.repo.travar_margem(tx, servidor_id).await?;
+        self.margem_em(tx, servidor_id, ignorar_id, ignorar_reserva).await
+    }
+
+    async fn margem_em(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        servidor_id: i32,
+        ignorar_id: Option<i32>,
+        ignorar_reserva: Option<i32>,
+    ) -> Result<Margem> {
+        let Some(base) = self.repo.base_margem(tx, servidor_id).await? else {
+            return Err(AppError::InvalidInput(
+                "Servidor sem folha calculada: não há como apurar a margem consignável".to_string(),
+            )
+            .into());
+        };
+        let comprometida = self
+            .repo
+            .comprometida(tx, servidor_id, base.ano, base.mes, ignorar_id)
+            .await?;
+        let reservada = self
+            .reserva_repo
+            .reservada(tx, servidor_id, ignorar_reserva)
+            .await?;
+        let (margem, disponivel) = consignacao::calcular_margem(
+            &base.bruto,
+            &base.descontos_obrigatorios,
+            &base.percentual,
+            &(&comprometida + &reservada),
+        );
+
+        Ok(Margem {
+            folha_id: base.folha_id,
+            ano: base.ano,
+            mes: base.mes,
+            bruto: base.bruto,
+            descontos_obrigatorios: base.descontos_obrigatorios,
+            percentual: base.percentual,
+            margem,
+            comprometida,
+            reservada,
+            disponivel,
+        })
+    }
+
+    /// Recusa a parcela que não cabe na margem disponível, lida com a margem travada
+    async fn verificar_margem(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        servidor_id: i32,
+        valor_parcela: &BigDecimal,
+        ignorar_id: Option<i32>,
+        ignorar_reserva: Option<i32>,
+    ) -> Result<()> {
+        let margem = self.margem_travada(tx, servidor_id, ignorar_id, ignorar_reserva).await?;
+        if *valor_parcela > margem.disponivel {
+            return Err(AppError::Conflict(format!(
+                "Parcela de R$ {} excede a margem consignável disponível de R$ {} (margem de R$ {} na folha {:02}/{}, R$ {} já comprometidos e R$ {} reservados)",
+                decimal_br(valor_parcela),
+                decimal_br(&margem.disponivel),
+                decimal_br(&margem.margem),
+                margem.mes,
+                margem.ano,
+                decimal_br(&margem.comprometida),
+                decimal_br(&margem.reservada)
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    async fn validar_contrato_unico(
+        &self,
+        pool: &PgPool,
+        consignataria_id: i32,
+        contrato: &str,
+        id: Option<i32>,
+    ) -> Result<()> {
+        if self.repo.contrato_em_uso(pool, consignataria_id, contrato, id).await? {
+            return Err(AppError::Conflict(format!(
+                "Contrato {} já cadastrado para a consignatária",
+                contrato
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, pool: &PgPool, id: i32) -> Result<Consignacao> {
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    /// Averba o contrato se a parcela couber na margem
+    pub async fn create(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        mut input: CreateConsignacao,
+    ) -> Result<Consignacao> {
+        if !usuario.has_permission(PERM_CONSIGNACAO_AVERBAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        let (Some(servidor_id), Some(consignataria_id)) = (input.servidor_id, input.consignataria_id) else {
+            return Err(AppError::InvalidInput("Informe o servidor e a consignatária".to_string()).into());
+        };
+        Self::validar_contrato(
+            &mut input.contrato,
+            &input.valor_parcela,
+            input.total_parcelas,
+            input.mes_inicio,
+        )?;
+
+        let consignataria = self.consignataria_repo.get_by_id(pool, consignataria_id).await?;
+        if !consignataria.is_active {
+            return Err(AppError::InvalidInput(format!(
+                "Consignatária {} está inativa",
+                consignataria.nome
+            ))
+            .into());
+        }
+        self.validar_contrato_unico(pool, consignataria_id, &input.contrato, None).await?;
+
+        let mut tx = pool.begin().await?;
+        self.repo.travar_margem(&mut tx, servidor_id).await?;
+        // com a margem travada, a reserva não é usada por outra averbação no meio do caminho
+        let reserva = match input.reserva.as_deref() {
+            Some(codigo) => Some(self.reserva_valida(pool, codigo, servidor_id, consignataria_id).await?),
+            None => None,
+        };
+        self.verificar_margem(
+            &mut tx,
+            servidor_id,
+            &input.valor_parcela,
+            None,
+            reserva.as_ref().map(|r| r.id),
+        )
+        .await?;
+
+        input.usuario = Some(usuario.username.clone());
+        let id = self.repo.inserir(&mut tx, &input).await?;
+        if let Some(reserva) = reserva {
+            self.reserva_repo.utilizar(&mut tx, reserva.id, id).await?;
+        }
+        tx.commit().await?;
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    /// Reserva feita na API pela mesma consignatária, para o mesmo servidor, e ainda no prazo
+    async fn reserva_valida(
+        &self,
+        pool: &PgPool,
+        codigo: &str,
+        servidor_id: i32,
+        consignataria_id: i32,
+    ) -> Result<ReservaMargem> {
+        let reserva = match autenticacao::normalizar_codigo(codigo) {
+            Some(codigo) => self.reserva_repo.get_by_codigo(pool, &codigo).await?,
+            None => None,
+        };
+        let Some(reserva) = reserva.filter(|r| r.servidor_id == servidor_id && r.consignataria_id == consignataria_id)
+        else {
+            return Err(AppError::InvalidInput(format!(
+                "Reserva {} não encontrada para o servidor e a consignatária",
+                codigo
+            ))
+            .into());
+        };
+        if reserva.situacao != RESERVA_RESERVADA {
+            return Err(AppError::Conflict(format!(
+                "Reserva {} já está {}",
+                reserva.codigo, reserva.situacao
+            ))
+            .into());
+        }
+        if reserva.expira_em <= Utc::now() {
+            return Err(AppError::Conflict(format!(
+                "Reserva {} venceu em {}",
+                reserva.codigo,
+                reserva.expira_em.with_timezone(&Local).format("%d/%m/%Y %H:%M")
+            ))
+            .into());
+        }
+        Ok(reserva)
+    }
+
+    /// Altera o contrato; enquanto ativo, a nova parcela também precisa caber na margem
+    pub async fn update(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        id: i32,
+        mut input: UpdateConsignacao,
+    ) -> Result<Consignacao> {
+        if !usuario.has_permission(PERM_CONSIGNACAO_AVERBAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        if input.situacao != CONSIGNACAO_ATIVO && input.situacao != CONSIGNACAO_CANCELADO {
+            return Err(AppError::InvalidInput("Situação deve ser ATIVO ou CANCELADO".to_string()).into());
+        }
+        Self::validar_contrato(
+            &mut input.contrato,
+            &input.valor_parcela,
+            input.total_parcelas,
+            input.mes_inicio,
+        )?;
+
+        let atual = self.repo.get_by_id(pool, id).await?;
+        self.validar_contrato_unico(pool, atual.consignataria_id, &input.contrato, Some(id))
+            .await?;
+        let mut tx = pool.begin().await?;
+        if input.situacao == CONSIGNACAO_ATIVO {
+            self.verificar_margem(&mut tx, atual.servidor_id, &input.valor_parcela, Some(id), None)
+                .await?;
+        }
+        self.repo.alterar(&mut tx, id, &input).await?;
+        tx.commit().await?;
+        Ok(self.repo.get_by_id(pool, id).await?)
+    }
+
+    pub async fn delete(&self, pool: &PgPool, usuario: &CurrentUser, id: i32) -> Result<()> {
+        if !usuario.has_permission(PERM_CONSIGNACAO_AVERBAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        if self.repo.em_uso(pool, id).await? {
+            return Err(AppError::Conflict(
+                "Contrato com parcelas lançadas em folha não pode ser excluído; cancele-o".to_string(),
+            )
+            .into());
+        }
+        Ok(self.repo.delete(pool, id).await?)
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        params: &ConsignacaoParams,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<Consignacao>> {
+        let filtros: Vec<(&str, String)> = [
+            ("c.servidor_id", params.servidor_id.map(|v| v.to_string())),
+            ("c.consignataria_id", params.consignataria_id.map(|v| v.to_string())),
+            ("c.situacao", params.situacao.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(campo, valor)| valor.map(|v| (campo, v)))
+        .collect();
+        Ok(self
+            .repo
+            .get_paginated_filtrado(pool, params.find.as_deref(), page, page_size, &filtros)
+            .await?)
+    }
+}
+
+/// API de margem consignável das consignatárias: autentica pela credencial, apura a margem
+/// pelo mesmo cálculo da averbação e registra todas as chamadas
+pub struct MargemApiService {
+    credencial_repo: CredencialConsignatariaRepository,
+    reserva_repo: ReservaMargemRepository,
+    consulta_repo: ConsultaMargemRepository,
+    servidor_repo: ServidorRepository,
+    consignacao: ConsignacaoService,
+}
+
+impl MargemApiService {
+    pub fn new() -> Self {
+        Self {
+            credencial_repo: CredencialConsignatariaRepository,
+            reserva_repo: ReservaMargemRepository,
+            consulta_repo: ConsultaMargemRepository,
+            servidor_repo: ServidorRepository,
+            consignacao: ConsignacaoService::new(),
+        }
+    }
+
+    /// Credencial do cabeçalho Authorization; qualquer falha responde como não autenticado
+    async fn autenticar(&self, pool: &PgPool, origem: &OrigemApiMargem) -> Result<CredencialConsignataria> {
+        let Some((chave, segredo)) = origem.authorization.as_deref().and_then(consignacao::separar_token) else {
+            return Err(AppError::UserNotAuthenticated.into());
+        };
+        let Some(credencial) = self.credencial_repo.get_ativa_by_chave(pool, chave).await? else {
+            return Err(AppError::UserNotAuthenticated.into());
+        };
+        if !UserService::verify_password(segredo, &credencial.segredo_hash).unwrap_or(false) {
+            return Err(AppError::UserNotAuthenticated.into());
+        }
+        self.credencial_repo.registrar_uso(pool, credencial.id).await?;
+        Ok(credencial)
+    }
+
+    /// Servidor ativo do CPF, com o CPF só com os dígitos
+    async fn servidor(&self, pool: &PgPool, cpf: &str) -> Result<(i32, String, String)> {
+        let cpf = somente_digitos(cpf);
+        if !cpf_valido(&cpf) {
+            return Err(AppError::InvalidInput("CPF inválido".to_string()).into());
+        }
+        let Some((id, nome)) = self.servidor_repo.get_ativo_by_cpf(pool, &cpf).await? else {
+            return Err(AppError::NotFound.into());
+        };
+        Ok((id, nome, cpf))
+    }
+
+    fn registro(
+        origem: &OrigemApiMargem,
+        credencial: Option<&CredencialConsignataria>,
+        operacao: &str,
+        cpf: Option<&str>,
+    ) -> CreateConsultaMargem {
+        CreateConsultaMargem {
+            credencial_id: credencial.map(|c| c.id),
+            consignataria_id: credencial.map(|c| c.consignataria_id),
+            operacao: operacao.to_string(),
+            cpf: cpf.map(somente_digitos).filter(|cpf| cpf.len() == 11),
+            ip: origem.ip.as_deref().map(|ip| ip.chars().take(45).collect()),
+            ..Default::default()
+        }
+    }
+
+    /// Grava a chamada no log com o status devolvido; erro ao gravar não muda a resposta
+    async fn registrar<T>(
+        &self,
+        pool: &PgPool,
+        mut registro: CreateConsultaMargem,
+        resultado: &Result<T>,
+        sucesso: StatusCode,
+    ) {
+        let status = match resultado {
+            Result::Ok(_) => sucesso,
+            Err(err) => {
+                registro.mensagem = Some(err.to_string());
+                error::status_code(err)
+            }
+        };
+        registro.status = status.as_u16() as i16;
+
+        if let Err(err) = self.consulta_repo.registrar(pool, &registro).await {
+            error!("Erro ao registrar chamada da API de margem: {}", err);
+        }
+    }
+
+    pub async fn consultar(&self, pool: &PgPool, origem: &OrigemApiMargem, cpf: &str) -> Result<MargemConsultada> {
+        let credencial = self.autenticar(pool, origem).await;
+        let registro = Self::registro(origem, credencial.as_ref().ok(), API_MARGEM_CONSULTA, Some(cpf));
+
+        let resultado = match credencial {
+            Result::Ok(_) => self.consultar_margem(pool, cpf).await,
+            Err(err) => Err(err),
+        };
+        let registro = CreateConsultaMargem {
+            disponivel: resultado.as_ref().ok().map(|m| m.margem_disponivel.clone()),
+            ..registro
+        };
+        self.registrar(pool, registro, &resultado, StatusCode::OK).await;
+        resultado
+    }
+
+    async fn consultar_margem(&self, pool: &PgPool, cpf: &str) -> Result<MargemConsultada> {
+        let (servidor_id, nome, cpf) = self.servidor(pool, cpf).await?;
+        let margem = self.consignacao.margem(pool, servidor_id, None, None).await?;
+
+        Ok(MargemConsultada {
+            cpf,
+            nome: autenticacao::mascarar_nome(&nome),
+            ano: margem.ano,
+            mes: margem.mes,
+            margem_disponivel: margem.disponivel,
+        })
+    }
+
+    pub async fn reservar(
+        &self,
+        pool: &PgPool,
+        origem: &OrigemApiMargem,
+        input: ReservarMargem,
+    ) -> Result<ReservaConcedida> {
+        let credencial = self.autenticar(pool, origem).await;
+        let registro = Self::registro(origem, credencial.as_ref().ok(), API_MARGEM_RESERVA, Some(&input.cpf));
+
+        let resultado = match credencial {
+            Result::Ok(credencial) => self.reservar_margem(pool, &credencial, &input).await,
+            Err(err) => Err(err),
+        };
+        let registro = CreateConsultaMargem {
+            reserva_codigo: resultado.as_ref().ok().map(|r| r.codigo.clone()),
+            disponivel: resultado.as_ref().ok().map(|r| r.margem_disponivel.clone()),
+            ..registro
+        };
+        self.registrar(pool, registro, &resultado, StatusCode::CREATED).await;
+        resultado
+    }
+
+    /// Registra o pedido de reserva com corpo inválido, que não chega a ser processado
+    pub async fn recusar_reserva(&self, pool: &PgPool, origem: &OrigemApiMargem, motivo: &str) -> anyhow::Error {
+        let credencial = self.autenticar(pool, origem).await;
+        let registro = Self::registro(origem, credencial.as_ref().ok(), API_MARGEM_RESERVA, None);
+
+        let resultado: Result<()> = match credencial {
+            Result::Ok(_) => Err(AppError::InvalidInput(motivo.to_string()).into()),
+            Err(err) => Err(err),
+        };
+        self.registrar(pool, registro, &resultado, StatusCode::CREATED).await;
+        resultado.unwrap_err()
+    }
+
+    /// Separa a parcela se ela couber na margem disponível
+    async fn reservar_margem(
+        &self,
+        pool: &PgPool,
+        credencial: &CredencialConsignataria,
+        input: &ReservarMargem,
+    ) -> Result<ReservaConcedida> {
+        let valor = input.valor_parcela.with_scale_round(2, RoundingMode::HalfUp);
+        if valor <= BigDecimal::zero() {
+            return Err(AppError::InvalidInput("Valor da parcela deve ser maior que zero".to_string()).into());
+        }
+        let (servidor_id, _, cpf) = self.servidor(pool, &input.cpf).await?;
+
+        // mesma trava das averbações: duas reservas não dividem a mesma margem disponível
+        let mut tx = pool.begin().await?;
+        let margem = self.consignacao.margem_travada(&mut tx, servidor_id, None, None).await?;
+        if valor > margem.disponivel {
+            return Err(AppError::Conflict(format!(
+                "Margem insuficiente: parcela de R$ {} e margem disponível de R$ {}",
+                decimal_br(&valor),
+                decimal_br(&margem.disponivel)
+            ))
+            .into());
+        }
+
+        let expira_em = Utc::now() + chrono::Duration::hours(consignacao::VALIDADE_RESERVA_HORAS);
+        let reserva = self
+            .reserva_repo
+            .create(
+                &mut tx,
+                &autenticacao::gerar_codigo(),
+                credencial.consignataria_id,
+                servidor_id,
+                &valor,
+                expira_em,
+            )
+            .await?;
+        tx.commit().await?;
+
+        Ok(ReservaConcedida {
+            codigo: reserva.codigo,
+            cpf,
+            margem_disponivel: (&margem.disponivel - &reserva.valor).with_scale(2),
+            valor: reserva.valor.with_scale(2),
+            expira_em: reserva.expira_em,
+        })
+    }
+
+    pub async fn cancelar_reserva(&self, pool: &PgPool, origem: &OrigemApiMargem, codigo: &str) -> Result<()> {
+        let credencial = self.autenticar(pool, origem).await;
+        let mut registro = Self::registro(origem, credencial.as_ref().ok(), API_MARGEM_CANCELAMENTO, None);
+        registro.reserva_codigo = autenticacao::normalizar_codigo(codigo);
+
+        let resultado = match credencial {
+            Result::Ok(credencial) => self.cancelar(pool, &credencial, codigo).await,
+            Err(err) => Err(err),
+        };
+        if let Result::Ok(cpf) = &resultado {
+            registro.cpf = Some(cpf.clone());
+        }
+        self.registrar(pool, registro, &resultado, StatusCode::NO_CONTENT).await;
+        resultado.map(|_| ())
+    }
+
+    /// Cancela a reserva da própria consignatária e devolve o CPF do servidor
+    async fn cancelar(&self, pool: &PgPool, credencial: &CredencialConsignataria, codigo: &str) -> Result<String> {
+        let reserva = match autenticacao::normalizar_codigo(codigo) {
+            Some(codigo) => self.reserva_repo.get_by_codigo(pool, &codigo).await?,
+            None => None,
+        };
+        // reserva de outra consignatária responde como inexistente
+        let Some(reserva) = reserva.filter(|r| r.consignataria_id == credencial.consignataria_id) else {
+            return Err(AppError::NotFound.into());
+        };
+        if reserva.situacao != RESERVA_RESERVADA {
+            return Err(AppError::Conflict(format!(
+                "Reserva {} já está {}",
+                reserva.codigo, reserva.situacao
+            ))
+            .into());
+        }
+        self.reserva_repo.cancelar(pool, reserva.id).await?;
+
+        let servidor = self.servidor_repo.get_by_id(pool, reserva.servidor_id).await?;
+        Ok(servidor.cpf.unwrap_or_default())
+    }
+
+    pub async fn get_paginated(
+        &self,
+        pool: &PgPool,
+        params: &ConsultaMargemParams,
+        page: i32,
+        page_size: i32,
+    ) -> Result<PaginatedResponse<ConsultaMargem>> {
+        let filtros: Vec<(&str, String)> = [
+            ("l.consignataria_id", params.consignataria_id.map(|v| v.to_string())),
+            ("l.operacao", params.operacao.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(campo, valor)| valor.map(|v| (campo, v)))
+        .collect();
+        Ok(self
+            .consulta_repo
+            .get_paginated_filtrado(pool, params.find.as_deref(), page, page_size, &filtros)
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalido(resultado: Result<()>) -> bool {
+        matches!(
+            resultado.unwrap_err().downcast_ref::<AppError>(),
+            Some(AppError::InvalidInput(_))
+        )
+    }
+
+    #[test]
+    fn test_validar_tipo_rubrica() {
+        for tipo in [RUBRICA_PROVENTO, RUBRICA_DESCONTO, RUBRICA_INFORMATIVA] {
+            assert!(RubricaService::validar_tipo(tipo).is_ok());
+        }
+        // minúscula ou tipo desconhecido é erro de entrada (400), não erro interno
+        assert!(invalido(RubricaService::validar_tipo("p")));
+        assert!(invalido(RubricaService::validar_tipo("X")));
+        assert!(invalido(RubricaService::validar_tipo("")));
+    }
+
+    #[test]
+    fn test_rubrica_altera_calculo() {
+        let rubrica = Rubrica {
+            id: 1,
+            codigo: "1".to_string(),
+            descricao: "SALARIO".to_string(),
+            tipo: RUBRICA_PROVENTO.to_string(),
+            incide_inss: true,
+            incide_irrf: true,
+            incide_fgts: false,
+            is_active: true,
+            codigo_sistema: None,
+            pensao_alimenticia: false,
+            informe_rendimentos: None,
+            fixa: true,
+        };
+        let input = |tipo: Option<&str>, incide_fgts| UpdateRubrica {
+            id: 1,
+            codigo: None,
+            descricao: Some("SALARIO BASE".to_string()),
+            tipo: tipo.map(str::to_string),
+            incide_inss: true,
+            incide_irrf: true,
+            incide_fgts,
+            pensao_alimenticia: false,
+            informe_rendimentos: None,
+            fixa: false,
+            is_active: true,
+        };
+        // descrição, fixa e situação podem mudar mesmo em rubrica de competência fechada
+        assert!(!RubricaService::altera_calculo(&rubrica, &input(None, false)));
+        assert!(!RubricaService::altera_calculo(&rubrica, &input(Some(RUBRICA_PROVENTO), false)));
+        assert!(RubricaService::altera_calculo(&rubrica, &input(Some(RUBRICA_DESCONTO), false)));
+        assert!(RubricaService::altera_calculo(&rubrica, &input(None, true)));
+    }
+
+    #[test]
+    fn test_normalizar_cargo() {
+        let mut nome = "  MOTORISTA ".to_string();
+        let mut cbo = Some("7823-05".to_string());
+        CargoService::normalizar(&mut nome, &mut cbo).unwrap();
+        assert_eq!(nome, "MOTORISTA");
+        assert_eq!(cbo.as_deref(), Some("782305"));
+
+        let mut sem_cbo = None;
+        assert!(CargoService::normalizar(&mut nome, &mut sem_cbo).is_ok());
+        assert!(invalido(CargoService::normalizar(&mut nome, &mut Some("78230".to_string()))));
+        assert!(invalido(CargoService::normalizar(&mut "   ".to_string(), &mut None)));
+    }
+
+    #[test]
+    fn test_normalizar_setor_e_departamento() {
+        let mut nome = " EDUCACAO  ".to_string();
+        SetorService::normalizar(&mut nome).unwrap();
+        assert_eq!(nome, "EDUCACAO");
+        DepartamentoService::normalizar(&mut nome).unwrap();
+        assert_eq!(nome, "EDUCACAO");
+
+        assert!(invalido(SetorService::normalizar(&mut " ".to_string())));
+        assert!(invalido(DepartamentoService::normalizar(&mut String::new())));
+    }
+
+    #[test]
+    fn test_normalizar_tipo_vinculo() {
+        let mut nome = " TEMPO DETERMINADO ".to_string();
+        TipoVinculoService::normalizar(&mut nome, Some(106), Some(1)).unwrap();
+        assert_eq!(nome, "TEMPO DETERMINADO");
+        assert!(TipoVinculoService::normalizar(&mut nome, None, None).is_ok());
+        assert!(TipoVinculoService::normalizar(&mut nome, Some(301), Some(2)).is_ok());
+
+        // categoria da tabela 01 tem 3 dígitos; regime é 1 (RGPS) ou 2 (RPPS)
+        assert!(invalido(TipoVinculoService::normalizar(&mut nome, Some(99), None)));
+        assert!(invalido(TipoVinculoService::normalizar(&mut nome, Some(1000), None)));
+        assert!(invalido(TipoVinculoService::normalizar(&mut nome, None, Some(3))));
+        assert!(invalido(TipoVinculoService::normalizar(&mut "".to_string(), None, None)));
+    }
+
+    #[test]
+    fn test_validar_informe_rubrica() {
+        assert!(RubricaService::validar_informe(None).is_ok());
+        assert!(RubricaService::validar_informe(Some("TRIBUTAVEL")).is_ok());
+        assert!(invalido(RubricaService::validar_informe(Some("OUTRA"))));
+    }
+
+    #[test]
+    fn test_validar_esocial() {
+        let admissao = NaiveDate::from_ymd_opt(2020, 3, 1);
+        let desligamento = NaiveDate::from_ymd_opt(2025, 6, 30);
+        let (grau, motivo) =
+            ServidorService::validar_esocial(Some("F"), Some(1), Some(" 7"), admissao, desligamento, Some("2"))
+                .unwrap();
+        assert_eq!(grau.as_deref(), Some("07"));
+        assert_eq!(motivo.as_deref(), Some("02"));
+        assert_eq!(
+            ServidorService::validar_esocial(None, None, None, None, None, None).unwrap(),
+            (None, None)
+        );
+
+        let validar = |grau, desligamento, motivo| {
+            ServidorService::validar_esocial(None, None, grau, admissao, desligamento, motivo).map(|_| ())
+        };
+        assert!(invalido(validar(Some("13"), None, None)));
+        assert!(invalido(validar(None, desligamento, None)));
+        assert!(invalido(validar(None, desligamento, Some("100"))));
+        assert!(invalido(validar(None, NaiveDate::from_ymd_opt(2019, 1, 1), Some("01"))));
+    }
+
+    #[tokio::test]
+    async fn test_reabrir_exige_motivo() {
+        // o motivo é conferido antes de qualquer acesso ao banco
+        let pool = PgPool::connect_lazy("postgres://folhaweb@127.0.0.1:1/folhaweb").unwrap();
+        let usuario = CurrentUser {
+            id: 1,
+            username: "admin".to_string(),
+            is_superuser: true,
+            permissions: Vec::new(),
+        };
+        let service = CompetenciaService::new();
+        for motivo in [None, Some(""), Some("   "), Some("\n\t")] {
+            let resultado = service.reabrir(&pool, &usuario, 1, motivo).await.map(|_| ());
+            assert!(invalido(resultado), "{:?}", motivo);
+        }
+    }
+
+    /// Banco do DATABASE_URL, o mesmo que o build usa; o teste grava tudo numa transação
+    /// que é desfeita no fim
+    async fn banco() -> PgPool {
+        dotenv::dotenv().ok();
+        PgPool::connect(&std::env::var("DATABASE_URL").expect("DATABASE_URL must be set"))
+            .await
+            .unwrap()
+    }
+
+    async fn inserir_id(tx: &mut Transaction<'_, Postgres>, sql: &str) -> i32 {
+        sqlx::query_scalar(sql).fetch_one(&mut **tx).await.unwrap()
+    }
+
+    async fn inserir_folha(
+        tx: &mut Transaction<'_, Postgres>,
+        ids: (i32, i32, i32),
+        tipo_folha: i16,
+        sequencia: i16,
+        salario: &BigDecimal,
+    ) -> i64 {
+        let (orgao_id, servidor_id, lotacao_id) = ids;
+        let folha_id: i64 = sqlx::query_scalar(
+            r#"INSERT INTO cadastro_folha(orgao_id, ano, mes, servidor_id, salario, base_fgts, base_inss,
+                base_irrf, ded_irrf, cargo_id, setor_id, departamento_id, vinculo_id, tipo_folha, sequencia)
+            VALUES ($1, 2025, 3, $2, $3, 0, 0, 0, 0, $4, $4, $4, $4, $5, $6) RETURNING id"#,
+        )
+        .bind(orgao_id)
+        .bind(servidor_id)
+        .bind(salario)
+        .bind(lotacao_id)
+        .bind(tipo_folha)
+        .bind(sequencia)
+        .fetch_one(&mut **tx)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"INSERT INTO cadastro_folha_item(folha_id, rubrica_id, referencia, valor)
+            SELECT $1, id, 30, $2 FROM cadastro_rubrica WHERE codigo_sistema = $3"#,
+        )
+        .bind(folha_id)
+        .bind(salario)
+        .bind(SISTEMA_SALARIO)
+        .execute(&mut **tx)
+        .await
+        .unwrap();
+        folha_id
+    }
+
+    async fn retido(tx: &mut Transaction<'_, Postgres>, folha_id: i64, codigo_sistema: &str) -> BigDecimal {
+        sqlx::query_scalar(
+            r#"SELECT COALESCE(SUM(i.valor), 0) FROM cadastro_folha_item i
+            INNER JOIN cadastro_rubrica r ON r.id = i.rubrica_id
+            WHERE i.folha_id = $1 AND r.codigo_sistema = $2"#,
+        )
+        .bind(folha_id)
+        .bind(codigo_sistema)
+        .fetch_one(&mut **tx)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_calcular_competencia_com_complementar() {
+        let pool = banco().await;
+        let mut tx = pool.begin().await.unwrap();
+
+        let orgao_id = inserir_id(&mut tx, "INSERT INTO cadastro_orgao(nome) VALUES ('TESTE') RETURNING id").await;
+        let servidor_id = inserir_id(
+            &mut tx,
+            &format!("INSERT INTO cadastro_servidor(nome, orgao_id) VALUES ('TESTE', {}) RETURNING id", orgao_id),
+        )
+        .await;
+        let competencia_id = inserir_id(
+            &mut tx,
+            &format!("INSERT INTO cadastro_competencia(orgao_id, ano, mes) VALUES ({}, 2025, 3) RETURNING id", orgao_id),
+        )
+        .await;
+        // lotação da folha: as chaves só são conferidas no commit, que não acontece
+        let ids = (orgao_id, servidor_id, 0);
+        let salario = BigDecimal::from(3000);
+        let complemento = BigDecimal::from(2000);
+        // a complementar é criada antes, mas é calculada depois da mensal
+        let complementar = inserir_folha(&mut tx, ids, FOLHA_COMPLEMENTAR, 1, &complemento).await;
+        let mensal = inserir_folha(&mut tx, ids, FOLHA_MENSAL, 0, &salario).await;
+
+        let competencia = CompetenciaService::new();
+        competencia.calcular_folhas(&pool, &mut tx, competencia_id).await.unwrap();
+
+        // a complementar retém a diferença entre o devido sobre a base somada e o que a
+        // mensal, calculada na mesma transação, já reteve
+        let faixas = TabelaInssService::new().get_vigente(&pool, 2025, 3).await.unwrap();
+        let (faixas_irrf, parametro) = TabelaIrrfService::new().get_vigente(&pool, 2025, 3).await.unwrap();
+        let devido = |rendimento: &BigDecimal| {
+            let inss = calculo::calcular_inss(rendimento, &faixas).desconto;
+            let entrada = EntradaIrrf {
+                rendimento: rendimento.clone(),
+                inss: inss.clone(),
+                dependentes: 0,
+                pensao: BigDecimal::zero(),
+            };
+            (inss, calculo::calcular_irrf(&entrada, &faixas_irrf, &parametro).imposto)
+        };
+        let (inss_mensal, irrf_mensal) = devido(&salario);
+        let (inss_total, irrf_total) = devido(&(&salario + &complemento));
+        assert!(irrf_mensal > BigDecimal::zero());
+
+        assert_eq!(retido(&mut tx, mensal, SISTEMA_INSS).await, inss_mensal);
+        assert_eq!(retido(&mut tx, mensal, SISTEMA_IRRF).await, irrf_mensal);
+        assert_eq!(retido(&mut tx, complementar, SISTEMA_INSS).await, &inss_total - &inss_mensal);
+        assert_eq!(retido(&mut tx, complementar, SISTEMA_IRRF).await, &irrf_total - &irrf_mensal);
+
+        tx.rollback().await.unwrap();
+    }
+
+    #[test]
+    fn test_validar_decimo_terceiro() {
+        let input = |ano, mes, parcela| CalcularDecimoTerceiro { orgao_id: 1, ano, mes, parcela };
+        assert!(DecimoTerceiroService::validar(&input(2025, 11, DECIMO_TERCEIRO_ADIANTAMENTO)).is_ok());
+        assert!(DecimoTerceiroService::validar(&input(2025, 12, DECIMO_TERCEIRO_FINAL)).is_ok());
+        assert!(invalido(DecimoTerceiroService::validar(&input(2025, 12, DECIMO_TERCEIRO_ADIANTAMENTO))));
+        assert!(invalido(DecimoTerceiroService::validar(&input(2025, 11, DECIMO_TERCEIRO_FINAL))));
+        // ano fora do intervalo é erro de entrada, não pânico ao montar as datas dos avos
+        assert!(invalido(DecimoTerceiroService::validar(&input(i32::MAX, 12, DECIMO_TERCEIRO_FINAL))));
+        assert!(invalido(DecimoTerceiroService::validar(&input(-5, 11, DECIMO_TERCEIRO_ADIANTAMENTO))));
     }
 }
@@ -5,33 +5,53 @@ use axum_messages::Messages;
 
 use anyhow::Result;
 use axum::Json;
+use axum::body::Body;
 use axum::{
     Extension, Form,
-    extract::{Multipart, Path, Query, State},
-    http::StatusCode,
+    extract::{Multipart, Path, Query, State, rejection::JsonRejection},
+    http::{HeaderMap, StatusCode, header},
     response::{Html, IntoResponse, Redirect, Response},
 };
 use bigdecimal::BigDecimal;
+use chrono::{Datelike, Local};
 use minijinja::context;
 
 use serde_json::Value;
+use tokio_util::io::ReaderStream;
 use tracing::debug;
 
-use crate::cadastro::model::{Folha, Uf};
-use crate::cadastro::schema::{CreateUf, UpdateUf};
-use crate::cadastro::service::{FolhaService, UfService};
+use crate::cadastro::calculo::{ResultadoInss, ResultadoIrrf};
+use crate::cadastro::consignacao::Margem;
+use crate::cadastro::model::{
+    Cargo, Competencia, CompetenciaHistorico, Consignataria, Departamento, FaixaInss, FaixaIrrf, Folha, FolhaDetalhe, FolhaItem,
+    LoteContraCheque, Municipio, Orgao, ParametroIrrf, ResultadoFilaEsocial, Rubrica, Servidor, Setor, TipoVinculo, Uf,
+};
+use crate::cadastro::schema::{
+    AutenticarContraChequeParams, CalcularDecimoTerceiro, CompetenciaParams, CreateAfastamento, CreateAlteracaoSalarial, GerarFolha, CreateFerias, CreateRescisao, FolhaParams, ConciliacaoCnabParams, ConsignacaoParams, ConsultaMargemParams, ConsultarMargemParams, CreateConsignacao, CreateConsignataria, CreateCredencialConsignataria, OrigemApiMargem, ReservarMargem, CreateMunicipio, MunicipioParams, UpdateMunicipio, CreateCargo, CreateCompetencia, CreateDepartamento, CreateFaixaInss, CreateFaixaIrrf, CreateFolha, CreateFolhaItem, CreateParametroIrrf,
+    EventoEsocialParams, FilaEsocialParams, GerarEsocial, GerarFilaEsocial, GerarLoteContraCheque, GerarRemessaCnab, InformeRendimentosParams, RemessaCnabParams, ZipEsocialParams,
+    CreateOrgao, CreateRubrica, CreateServidor, CreateSetor, CreateTipoVinculo, CreateUf, InssPreviewParams, IrrfPreviewParams, UpdateFaixaInss,
+    TransicaoCompetencia, UpdateFaixaIrrf, UpdateFolha, UpdateFolhaItem, UpdateParametroIrrf, UpdateRubrica,
+    UpdateCargo, UpdateConsignacao, UpdateConsignataria, UpdateDepartamento, UpdateOrgao, UpdateServidor, UpdateSetor, UpdateTipoVinculo, UpdateUf,
+};
+use crate::cadastro::service::{
+    AutenticacaoContraChequeService, CalculoService, CargoService, CompetenciaService, ConsignacaoService, ConsignatariaService, DecimoTerceiroService, DepartamentoService, GeracaoFolhaService, FeriasService, RescisaoService, EsocialService, FilaEsocialService, FolhaItemService, InformeRendimentosService, FolhaService, LoteContraChequeService, MargemApiService, MunicipioService, OrgaoService,
+    ArquivoLote, RemessaCnabService, ResultadoCalculo, ResultadoLote, RubricaService, ServidorService, SetorService, TabelaInssService, TabelaIrrfService, TipoVinculoService, UfService,
+};
+use crate::error;
 use crate::middlewares::CurrentUser;
 use crate::repository::{ListParams, PaginatedResponse, PaginationQuery};
 use crate::state::SharedState;
+use crate::utils::pdf::decimal_br;
 
 const PATH: &str = "cadastro";
 
 pub async fn list_folha(
     State(state): State<SharedState>,
-    Query(params): Query<ListParams>,
+    Query(params): Query<FolhaParams>,
     messages: Messages,
 ) -> impl IntoResponse {
     let service = FolhaService::new();
+    let tipo_folha = params.tipo_folha.unwrap_or_default() as i16;
 
     // Coletar mensagens do axum_messages
     let messages_vec: Vec<_> = messages
@@ -49,6 +69,7 @@ pub async fn list_folha(
         .get_paginated(
             &state.db,
             params.find.as_deref(),
+            tipo_folha,
             params.page.unwrap_or(1),
             params.page_size.unwrap_or(10),
         )
@@ -62,7 +83,9 @@ pub async fn list_folha(
                 total_pages => paginated_response.total_pages,
                 page_size => paginated_response.page_size,
                 total_records => paginated_response.total_records,
-                find => params.find.unwrap_or_default(),
+                find => params.find.clone().unwrap_or_default(),
+                tipo_folha => tipo_folha,
+                filtros => format!("&tipo_folha={}", tipo_folha),
                 messages => messages_vec,
             };
 
@@ -97,7 +120,7 @@ api regiao
 pub async fn folha_api_by_id(
     Path(id): Path<i64>,
     State(state): State<SharedState>,
-) -> Result<Json<Folha>, StatusCode> {
+) -> Result<Json<FolhaDetalhe>, StatusCode> {
     let service = FolhaService::new();
     let res = service.get_by_id(&state.db, id).await.map_err(|err| {
         debug!("error:{}", err);
@@ -107,6 +130,197 @@ pub async fn folha_api_by_id(
     Ok(Json(res))
 }
 
+/// Grava a folha; o serviço confere o tipo, a competência aberta e o desligamento
+pub async fn create_folha(
+    State(state): State<SharedState>,
+    Json(input): Json<CreateFolha>,
+) -> Result<Json<Folha>, StatusCode> {
+    let service = FolhaService::new();
+    let res = service.create(&state.db, input).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn update_folha(
+    Path(id): Path<i64>,
+    State(state): State<SharedState>,
+    Json(input): Json<UpdateFolha>,
+) -> Result<Json<Folha>, StatusCode> {
+    let service = FolhaService::new();
+    let res = service.update(&state.db, id, input).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn delete_folha(
+    Path(id): Path<i64>,
+    State(state): State<SharedState>,
+) -> Result<StatusCode, StatusCode> {
+    let service = FolhaService::new();
+    service.delete(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Contra-cheque da folha em PDF, aberto no navegador
+pub async fn imprimir_contra_cheque(
+    Path(id): Path<i64>,
+    State(state): State<SharedState>,
+) -> Result<Response, StatusCode> {
+    let service = FolhaService::new();
+    let pdf = service.contra_cheque_pdf(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(arquivo_response(&format!("contra-cheque-{}.pdf", id), pdf, true))
+}
+
+/// Resposta com o arquivo gerado; `inline` abre no navegador em vez de baixar
+fn arquivo_response(nome: &str, conteudo: impl IntoResponse, inline: bool) -> Response {
+    let content_type = if nome.ends_with(".zip") {
+        "application/zip"
+    } else if nome.ends_with(".xml") {
+        "application/xml"
+    } else if nome.ends_with(".rem") {
+        "text/plain"
+    } else {
+        "application/pdf"
+    };
+    let disposicao = if inline { "inline" } else { "attachment" };
+
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("{}; filename=\"{}\"", disposicao, nome),
+            ),
+        ],
+        conteudo,
+    )
+        .into_response()
+}
+
+/// Contra-cheques do órgão/competência em ZIP ou PDF único. Lotes pequenos voltam
+/// direto no corpo da resposta; os grandes retornam 202 com o lote a acompanhar
+pub async fn gerar_lote_contra_cheque(
+    State(state): State<SharedState>,
+    Extension(usuario): Extension<CurrentUser>,
+    Json(input): Json<GerarLoteContraCheque>,
+) -> Result<Response, (StatusCode, String)> {
+    let service = LoteContraChequeService::new();
+    let resultado = service
+        .gerar(&state.db, &usuario.username, input)
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            (error::status_code(&err), err.to_string())
+        })?;
+
+    Ok(match resultado {
+        ResultadoLote::Arquivo(arquivo) => lote_response(arquivo),
+        ResultadoLote::Agendado(lote) => (StatusCode::ACCEPTED, Json(lote)).into_response(),
+    })
+}
+
+pub async fn lote_contra_cheque_api(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Json<LoteContraCheque>, StatusCode> {
+    let service = LoteContraChequeService::new();
+    let lote = service.get_by_id(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(lote))
+}
+
+pub async fn download_lote_contra_cheque(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Response, (StatusCode, String)> {
+    let service = LoteContraChequeService::new();
+    let arquivo = service.download(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        (error::status_code(&err), err.to_string())
+    })?;
+
+    Ok(lote_response(arquivo))
+}
+
+/// O arquivo do lote vai do disco para a resposta aos poucos, sem ser lido inteiro
+fn lote_response(lote: ArquivoLote) -> Response {
+    let corpo = Body::from_stream(ReaderStream::new(lote.arquivo));
+    arquivo_response(&lote.nome, corpo, false)
+}
+
+/*
+autenticação pública do contra-cheque
+*/
+pub async fn autenticar_contra_cheque(
+    State(state): State<SharedState>,
+    Query(params): Query<AutenticarContraChequeParams>,
+) -> Response {
+    pagina_autenticacao(&state, params.codigo).await
+}
+
+/// Destino do QR code impresso no contra-cheque
+pub async fn autenticar_contra_cheque_codigo(
+    State(state): State<SharedState>,
+    Path(codigo): Path<String>,
+) -> Response {
+    pagina_autenticacao(&state, Some(codigo)).await
+}
+
+async fn pagina_autenticacao(state: &SharedState, codigo: Option<String>) -> Response {
+    let resultado = match &codigo {
+        Some(codigo) => {
+            let service = AutenticacaoContraChequeService::new();
+            match service.autenticar(&state.db, codigo).await {
+                Ok(resultado) => resultado,
+                Err(err) => {
+                    debug!("Erro ao autenticar contra-cheque: {}", err);
+                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                }
+            }
+        }
+        None => None,
+    };
+
+    let context = minijinja::context! {
+        codigo => codigo,
+        contra_cheque => resultado,
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/autenticar_contra_cheque.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Html(html).into_response(),
+            Err(err) => {
+                debug!("Erro ao renderizar template: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        },
+        Err(err) => {
+            debug!("Erro ao carregar template: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
 /*
 ==========================================
             Uf
@@ -370,3 +584,4365 @@ pub async fn uf_api(
 
     Ok(Json(res))
 }
+
+/*
+==========================================
+            Município
+==========================================
+*/
+
+pub async fn list_municipio(
+    State(state): State<SharedState>,
+    Query(params): Query<MunicipioParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = MunicipioService::new();
+
+    // UF escolhida no filtro, para mostrar o nome no combobox
+    let uf = match params.uf_id {
+        Some(uf_id) => UfService::new().get_by_id(&state.db, uf_id).await.ok(),
+        None => None,
+    };
+
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            params.find.as_deref(),
+            params.uf_id,
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                filtros => params.uf_id.map(|uf_id| format!("&uf_id={}", uf_id)),
+                uf => uf,
+                messages => messages_vec
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/municipio_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        messages.error(format!("Erro ao renderizar template: {}", err));
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    messages.error(format!("Erro ao carregar template: {}", err));
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar município: {}", err);
+            messages.error(format!("Erro ao carregar município: {}", err));
+            Redirect::to(&format!("/{}/municipio", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn municipio_form(
+    State(state): State<SharedState>,
+    messages: Messages,
+) -> Result<Html<String>, impl IntoResponse> {
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let context = minijinja::context! {
+        messages => messages_vec
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/municipio_form.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Ok(Html(html)),
+            Err(err) => {
+                messages.error(format!("Erro ao renderizar template: {}", err));
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Erro ao renderizar template: {}", err),
+                )
+                    .into_response())
+            }
+        },
+        Err(err) => {
+            messages.error(format!("Erro ao carregar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Erro ao carregar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn create_municipio(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Form(body): Form<CreateMunicipio>,
+) -> Response {
+    let service = MunicipioService::new();
+
+    match service.create(&state.db, body).await {
+        Ok(municipio) => {
+            messages.success("Município criado com sucesso!");
+            Redirect::to(&format!("/{}/municipio-form/{}", PATH, municipio.id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao criar município: {}", err));
+            Redirect::to(&format!("/{}/municipio-form", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn get_municipio(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+    let service = MunicipioService::new();
+
+    // Carregar o template
+    let template = match state
+        .templates
+        .get_template(&format!("{}/municipio_form.html", PATH))
+    {
+        Ok(t) => t,
+        Err(err) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao carregar template: {}", err),
+            )
+                .into_response());
+        }
+    };
+
+    let municipio = match service.get_by_id(&state.db, id).await {
+        Ok(p) => p,
+        Err(e) => {
+            debug!("Erro ao buscar município: {}", e);
+            messages.error(format!("Erro ao buscar município: {}", e));
+            return Err(Redirect::to(&format!("/{}/municipio-form", PATH)).into_response());
+        }
+    };
+
+    // Preparar o contexto
+    let ctx = context! {
+        row => municipio,
+        messages => messages_vec,
+    };
+
+    match template.render(&ctx) {
+        Ok(html) => Ok(Html(html)),
+        Err(err) => {
+            messages.error(format!("Falha ao renderizar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao renderizar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn update_municipio(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(input): Form<UpdateMunicipio>,
+) -> Response {
+    let service = MunicipioService::new();
+
+    match service.update(&state.db, id, input).await {
+        Ok(_) => {
+            messages.success("Município atualizado com sucesso!");
+            Redirect::to(&format!("/{}/municipio-form/{}", PATH, id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao atualizar município: {}", err));
+            Redirect::to(&format!("/{}/municipio-form/{}", PATH, id)).into_response()
+        }
+    }
+}
+
+pub async fn delete_municipio(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = MunicipioService::new();
+
+    match service.delete(&state.db, id).await {
+        Ok(()) => {
+            messages.success("Município excluído com sucesso!");
+            Redirect::to(&format!("/{}/municipio", PATH)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao excluir município: {}", err));
+            Redirect::to(&format!("/{}/municipio", PATH)).into_response()
+        }
+    }
+}
+
+/// Lookup dos comboboxes; `uf_id` restringe aos municípios da UF
+pub async fn municipio_api(
+    Query(q): Query<MunicipioParams>,
+    State(state): State<SharedState>,
+) -> Result<Json<PaginatedResponse<Municipio>>, StatusCode> {
+    let service = MunicipioService::new();
+    let res = service
+        .get_paginated(
+            &state.db,
+            q.find.as_deref(),
+            q.uf_id,
+            q.page.unwrap_or(1),
+            q.page_size.unwrap_or(10),
+        )
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn municipio_api_by_id(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Json<Municipio>, StatusCode> {
+    let service = MunicipioService::new();
+    let res = service.get_by_id(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+/// Upload da planilha de divisão territorial do IBGE (CSV), campo "arquivo"
+pub async fn importar_ibge_municipio(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    mut multipart: Multipart,
+) -> Response {
+    let service = MunicipioService::new();
+
+    let resultado = async {
+        let mut arquivo = None;
+        while let Some(campo) = multipart.next_field().await? {
+            if campo.name() == Some("arquivo") {
+                arquivo = Some(campo.bytes().await?);
+            }
+        }
+        match arquivo {
+            Some(bytes) if !bytes.is_empty() => service.importar_ibge(&state.db, &current_user, &bytes).await,
+            _ => Err(anyhow::anyhow!("Selecione o arquivo do IBGE")),
+        }
+    }
+    .await;
+    match resultado {
+        Ok(r) => messages.success(format!(
+            "Importação concluída: {} UFs novas e {} atualizadas; {} municípios novos e {} atualizados",
+            r.ufs_inseridas, r.ufs_atualizadas, r.municipios_inseridos, r.municipios_atualizados
+        )),
+        Err(err) => messages.error(format!("Erro ao importar arquivo do IBGE: {}", err)),
+    };
+    Redirect::to(&format!("/{}/municipio", PATH)).into_response()
+}
+
+/*
+==========================================
+            Rubrica
+==========================================
+*/
+
+pub async fn list_rubrica(
+    State(state): State<SharedState>,
+    Query(params): Query<ListParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = RubricaService::new();
+
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            params.find.as_deref(),
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                messages => messages_vec
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/rubrica_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        messages.error(format!("Erro ao renderizar template: {}", err));
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    messages.error(format!("Erro ao carregar template: {}", err));
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar rubrica: {}", err);
+            messages.error(format!("Erro ao carregar rubrica: {}", err));
+            Redirect::to(&format!("/{}/rubrica", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn rubrica_form(
+    State(state): State<SharedState>,
+    messages: Messages,
+) -> Result<Html<String>, impl IntoResponse> {
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let context = minijinja::context! {
+        messages => messages_vec
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/rubrica_form.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Ok(Html(html)),
+            Err(err) => {
+                messages.error(format!("Erro ao renderizar template: {}", err));
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Erro ao renderizar template: {}", err),
+                )
+                    .into_response())
+            }
+        },
+        Err(err) => {
+            messages.error(format!("Erro ao carregar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Erro ao carregar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn create_rubrica(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Form(body): Form<CreateRubrica>,
+) -> Response {
+    let service = RubricaService::new();
+
+    match service.create(&state.db, body).await {
+        Ok(rubrica) => {
+            messages.success("Rubrica criada com sucesso!");
+            Redirect::to(&format!("/{}/rubrica-form/{}", PATH, rubrica.id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao criar rubrica: {}", err));
+            Redirect::to(&format!("/{}/rubrica-form", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn get_rubrica(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+    let service = RubricaService::new();
+
+    // Carregar o template
+    let template = match state
+        .templates
+        .get_template(&format!("{}/rubrica_form.html", PATH))
+    {
+        Ok(t) => t,
+        Err(err) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao carregar template: {}", err),
+            )
+                .into_response());
+        }
+    };
+
+    let rubrica = match service.get_by_id(&state.db, id).await {
+        Ok(p) => p,
+        Err(e) => {
+            debug!("Erro ao buscar rubrica: {}", e);
+            messages.error(format!("Erro ao buscar rubrica: {}", e));
+            return Err(Redirect::to(&format!("/{}/rubrica-form", PATH)).into_response());
+        }
+    };
+
+    // Preparar o contexto
+    let ctx = context! {
+        row => rubrica,
+        messages => messages_vec,
+    };
+
+    match template.render(&ctx) {
+        Ok(html) => Ok(Html(html)),
+        Err(err) => {
+            messages.error(format!("Falha ao renderizar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao renderizar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn update_rubrica(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(input): Form<UpdateRubrica>,
+) -> Response {
+    let service = RubricaService::new();
+
+    match service.update(&state.db, id, input).await {
+        Ok(_) => {
+            messages.success("Rubrica atualizada com sucesso!");
+            Redirect::to(&format!("/{}/rubrica-form/{}", PATH, id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao atualizar rubrica: {}", err));
+            Redirect::to(&format!("/{}/rubrica-form/{}", PATH, id)).into_response()
+        }
+    }
+}
+
+pub async fn delete_rubrica(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = RubricaService::new();
+
+    match service.delete(&state.db, id).await {
+        Ok(()) => {
+            messages.success("Rubrica excluída com sucesso!");
+            Redirect::to(&format!("/{}/rubrica", PATH)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao excluir rubrica: {}", err));
+            Redirect::to(&format!("/{}/rubrica", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn rubrica_api(
+    Query(q): Query<PaginationQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<PaginatedResponse<Rubrica>>, StatusCode> {
+    let service = RubricaService::new();
+    let res = service
+        .get_paginated(
+            &state.db,
+            q.find.as_deref(),
+            q.page.unwrap_or(1) as i32,
+            q.page_size.unwrap_or(10) as i32,
+        )
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+/*
+==========================================
+            Órgão
+==========================================
+*/
+
+pub async fn list_orgao(
+    State(state): State<SharedState>,
+    Query(params): Query<ListParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = OrgaoService::new();
+
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            params.find.as_deref(),
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                messages => messages_vec
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/orgao_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        messages.error(format!("Erro ao renderizar template: {}", err));
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    messages.error(format!("Erro ao carregar template: {}", err));
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar órgão: {}", err);
+            messages.error(format!("Erro ao carregar órgão: {}", err));
+            Redirect::to(&format!("/{}/orgao", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn orgao_form(
+    State(state): State<SharedState>,
+    messages: Messages,
+) -> Result<Html<String>, impl IntoResponse> {
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let context = minijinja::context! {
+        messages => messages_vec
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/orgao_form.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Ok(Html(html)),
+            Err(err) => {
+                messages.error(format!("Erro ao renderizar template: {}", err));
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Erro ao renderizar template: {}", err),
+                )
+                    .into_response())
+            }
+        },
+        Err(err) => {
+            messages.error(format!("Erro ao carregar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Erro ao carregar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn create_orgao(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Form(body): Form<CreateOrgao>,
+) -> Response {
+    let service = OrgaoService::new();
+
+    match service.create(&state.db, body).await {
+        Ok(orgao) => {
+            messages.success("Órgão criado com sucesso!");
+            Redirect::to(&format!("/{}/orgao-form/{}", PATH, orgao.id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao criar órgão: {}", err));
+            Redirect::to(&format!("/{}/orgao-form", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn get_orgao(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+    let service = OrgaoService::new();
+
+    // Carregar o template
+    let template = match state
+        .templates
+        .get_template(&format!("{}/orgao_form.html", PATH))
+    {
+        Ok(t) => t,
+        Err(err) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao carregar template: {}", err),
+            )
+                .into_response());
+        }
+    };
+
+    let orgao = match service.get_by_id(&state.db, id).await {
+        Ok(p) => p,
+        Err(e) => {
+            debug!("Erro ao buscar órgão: {}", e);
+            messages.error(format!("Erro ao buscar órgão: {}", e));
+            return Err(Redirect::to(&format!("/{}/orgao-form", PATH)).into_response());
+        }
+    };
+
+    // Preparar o contexto
+    let ctx = context! {
+        row => orgao,
+        messages => messages_vec,
+    };
+
+    match template.render(&ctx) {
+        Ok(html) => Ok(Html(html)),
+        Err(err) => {
+            messages.error(format!("Falha ao renderizar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao renderizar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn update_orgao(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(input): Form<UpdateOrgao>,
+) -> Response {
+    let service = OrgaoService::new();
+
+    match service.update(&state.db, id, input).await {
+        Ok(_) => {
+            messages.success("Órgão atualizado com sucesso!");
+            Redirect::to(&format!("/{}/orgao-form/{}", PATH, id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao atualizar órgão: {}", err));
+            Redirect::to(&format!("/{}/orgao-form/{}", PATH, id)).into_response()
+        }
+    }
+}
+
+pub async fn delete_orgao(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = OrgaoService::new();
+
+    match service.delete(&state.db, id).await {
+        Ok(()) => {
+            messages.success("Órgão excluído com sucesso!");
+            Redirect::to(&format!("/{}/orgao", PATH)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao excluir órgão: {}", err));
+            Redirect::to(&format!("/{}/orgao", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn orgao_api(
+    Query(q): Query<PaginationQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<PaginatedResponse<Orgao>>, StatusCode> {
+    let service = OrgaoService::new();
+    let res = service
+        .get_paginated(
+            &state.db,
+            q.find.as_deref(),
+            q.page.unwrap_or(1) as i32,
+            q.page_size.unwrap_or(10) as i32,
+        )
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn orgao_api_by_id(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Json<Orgao>, StatusCode> {
+    let service = OrgaoService::new();
+    let res = service.get_by_id(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+/// Logotipo do órgão (PNG), usado na pré-visualização do formulário
+pub async fn logo_orgao(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Response, StatusCode> {
+    let service = OrgaoService::new();
+    let logo = service
+        .logo(&state.db, id)
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            error::status_code(&err)
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(([(header::CONTENT_TYPE, "image/png")], logo).into_response())
+}
+
+pub async fn upload_logo_orgao(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    mut multipart: Multipart,
+) -> Response {
+    let service = OrgaoService::new();
+
+    let resultado = async {
+        let mut arquivo = None;
+        while let Some(campo) = multipart.next_field().await? {
+            if campo.name() == Some("logo") {
+                arquivo = Some(campo.bytes().await?);
+            }
+        }
+        match arquivo {
+            Some(bytes) if !bytes.is_empty() => service.salvar_logo(&state.db, id, &bytes).await,
+            _ => Err(anyhow::anyhow!("Selecione o arquivo do logotipo")),
+        }
+    }
+    .await;
+    match resultado {
+        Ok(()) => messages.success("Logotipo atualizado com sucesso!"),
+        Err(err) => messages.error(format!("Erro ao enviar logotipo: {}", err)),
+    };
+    Redirect::to(&format!("/{}/orgao-form/{}", PATH, id)).into_response()
+}
+
+pub async fn remover_logo_orgao(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = OrgaoService::new();
+
+    match service.remover_logo(&state.db, id).await {
+        Ok(()) => messages.success("Logotipo removido com sucesso!"),
+        Err(err) => messages.error(format!("Erro ao remover logotipo: {}", err)),
+    };
+    Redirect::to(&format!("/{}/orgao-form/{}", PATH, id)).into_response()
+}
+
+/*
+==========================================
+            Servidor
+==========================================
+*/
+
+pub async fn list_servidor(
+    State(state): State<SharedState>,
+    Query(params): Query<ListParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = ServidorService::new();
+
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            params.find.as_deref(),
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                messages => messages_vec
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/servidor_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        messages.error(format!("Erro ao renderizar template: {}", err));
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    messages.error(format!("Erro ao carregar template: {}", err));
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar servidor: {}", err);
+            messages.error(format!("Erro ao carregar servidor: {}", err));
+            Redirect::to(&format!("/{}/servidor", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn servidor_form(
+    State(state): State<SharedState>,
+    messages: Messages,
+) -> Result<Html<String>, impl IntoResponse> {
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let context = minijinja::context! {
+        messages => messages_vec
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/servidor_form.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Ok(Html(html)),
+            Err(err) => {
+                messages.error(format!("Erro ao renderizar template: {}", err));
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Erro ao renderizar template: {}", err),
+                )
+                    .into_response())
+            }
+        },
+        Err(err) => {
+            messages.error(format!("Erro ao carregar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Erro ao carregar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn create_servidor(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Form(body): Form<CreateServidor>,
+) -> Response {
+    let service = ServidorService::new();
+
+    match service.create(&state.db, &current_user, body).await {
+        Ok(servidor) => {
+            messages.success("Servidor criado com sucesso!");
+            Redirect::to(&format!("/{}/servidor-form/{}", PATH, servidor.id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao criar servidor: {}", err));
+            Redirect::to(&format!("/{}/servidor-form", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn get_servidor(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+    let service = ServidorService::new();
+
+    // Carregar o template
+    let template = match state
+        .templates
+        .get_template(&format!("{}/servidor_form.html", PATH))
+    {
+        Ok(t) => t,
+        Err(err) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao carregar template: {}", err),
+            )
+                .into_response());
+        }
+    };
+
+    let servidor = match service.get_by_id(&state.db, id).await {
+        Ok(p) => p,
+        Err(e) => {
+            debug!("Erro ao buscar servidor: {}", e);
+            messages.error(format!("Erro ao buscar servidor: {}", e));
+            return Err(Redirect::to(&format!("/{}/servidor-form", PATH)).into_response());
+        }
+    };
+    let afastamentos = service.afastamentos(&state.db, id).await.unwrap_or_else(|e| {
+        debug!("Erro ao buscar afastamentos: {}", e);
+        Vec::new()
+    });
+    let alteracoes = service.alteracoes_salariais(&state.db, id).await.unwrap_or_else(|e| {
+        debug!("Erro ao buscar alterações salariais: {}", e);
+        Vec::new()
+    });
+    let ferias_service = FeriasService::new();
+    let periodos = ferias_service.periodos(&state.db, id).await.unwrap_or_else(|e| {
+        debug!("Erro ao buscar períodos aquisitivos: {}", e);
+        Vec::new()
+    });
+    let ferias = ferias_service.ferias(&state.db, id).await.unwrap_or_else(|e| {
+        debug!("Erro ao buscar férias: {}", e);
+        Vec::new()
+    });
+    let rescisao = RescisaoService::new()
+        .get_by_servidor(&state.db, id)
+        .await
+        .unwrap_or_else(|e| {
+            debug!("Erro ao buscar rescisão: {}", e);
+            None
+        });
+
+    // Preparar o contexto
+    let ctx = context! {
+        row => servidor,
+        afastamentos => afastamentos,
+        alteracoes => alteracoes,
+        periodos => periodos,
+        ferias => ferias,
+        rescisao => rescisao,
+        hoje => Local::now().date_naive(),
+        messages => messages_vec,
+    };
+
+    match template.render(&ctx) {
+        Ok(html) => Ok(Html(html)),
+        Err(err) => {
+            messages.error(format!("Falha ao renderizar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao renderizar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn create_afastamento(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(body): Form<CreateAfastamento>,
+) -> Response {
+    let service = ServidorService::new();
+
+    match service.create_afastamento(&state.db, &current_user, id, body).await {
+        Ok(_) => messages.success("Afastamento registrado com sucesso!"),
+        Err(err) => messages.error(format!("Erro ao registrar afastamento: {}", err)),
+    };
+    Redirect::to(&format!("/{}/servidor-form/{}", PATH, id)).into_response()
+}
+
+pub async fn delete_afastamento(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = ServidorService::new();
+
+    match service.delete_afastamento(&state.db, &current_user, id).await {
+        Ok(servidor_id) => {
+            messages.success("Afastamento excluído com sucesso!");
+            Redirect::to(&format!("/{}/servidor-form/{}", PATH, servidor_id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao excluir afastamento: {}", err));
+            Redirect::to(&format!("/{}/servidor", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn create_alteracao_salarial(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(body): Form<CreateAlteracaoSalarial>,
+) -> Response {
+    let service = ServidorService::new();
+
+    match service
+        .create_alteracao_salarial(&state.db, &current_user, id, body)
+        .await
+    {
+        Ok(alteracao) => messages.success(format!(
+            "Alteração salarial registrada: vale a partir da folha de {:02}/{}",
+            alteracao.mes, alteracao.ano
+        )),
+        Err(err) => messages.error(format!("Erro ao registrar alteração salarial: {}", err)),
+    };
+    Redirect::to(&format!("/{}/servidor-form/{}", PATH, id)).into_response()
+}
+
+pub async fn delete_alteracao_salarial(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = ServidorService::new();
+
+    match service.delete_alteracao_salarial(&state.db, id).await {
+        Ok(servidor_id) => {
+            messages.success("Alteração salarial excluída com sucesso!");
+            Redirect::to(&format!("/{}/servidor-form/{}", PATH, servidor_id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao excluir alteração salarial: {}", err));
+            Redirect::to(&format!("/{}/servidor", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn create_ferias(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(body): Form<CreateFerias>,
+) -> Response {
+    let service = FeriasService::new();
+
+    match service.programar(&state.db, &current_user, id, body).await {
+        Ok(ferias) => messages.success(format!(
+            "Férias programadas de {} a {}; pagamento na folha de {:02}/{}",
+            ferias.data_inicio.format("%d/%m/%Y"),
+            ferias.data_fim.format("%d/%m/%Y"),
+            ferias.mes,
+            ferias.ano
+        )),
+        Err(err) => messages.error(format!("Erro ao programar férias: {}", err)),
+    };
+    Redirect::to(&format!("/{}/servidor-form/{}", PATH, id)).into_response()
+}
+
+pub async fn cancelar_ferias(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = FeriasService::new();
+
+    match service.cancelar(&state.db, id).await {
+        Ok(servidor_id) => {
+            messages.success("Férias canceladas com sucesso!");
+            Redirect::to(&format!("/{}/servidor-form/{}", PATH, servidor_id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao cancelar férias: {}", err));
+            Redirect::to(&format!("/{}/servidor", PATH)).into_response()
+        }
+    }
+}
+
+/// Aviso e recibo de férias em PDF, aberto no navegador
+pub async fn imprimir_aviso_ferias(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Response, StatusCode> {
+    let service = FeriasService::new();
+    let pdf = service.aviso_pdf(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(arquivo_response(&format!("aviso-ferias-{}.pdf", id), pdf, true))
+}
+
+pub async fn create_rescisao(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(body): Form<CreateRescisao>,
+) -> Response {
+    let service = RescisaoService::new();
+
+    match service.rescindir(&state.db, &current_user, id, body).await {
+        Ok(rescisao) => messages.success(format!(
+            "Rescisão calculada: desligamento em {}",
+            rescisao.data_desligamento.format("%d/%m/%Y")
+        )),
+        Err(err) => messages.error(format!("Erro ao calcular rescisão: {}", err)),
+    };
+    Redirect::to(&format!("/{}/servidor-form/{}", PATH, id)).into_response()
+}
+
+pub async fn estornar_rescisao(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = RescisaoService::new();
+
+    match service.estornar(&state.db, &current_user, id).await {
+        Ok(_) => messages.success("Rescisão estornada com sucesso!"),
+        Err(err) => messages.error(format!("Erro ao estornar rescisão: {}", err)),
+    };
+    Redirect::to(&format!("/{}/servidor-form/{}", PATH, id)).into_response()
+}
+
+/// Termo de rescisão em PDF, aberto no navegador
+pub async fn imprimir_termo_rescisao(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Response, StatusCode> {
+    let service = RescisaoService::new();
+    let pdf = service.termo_pdf(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(arquivo_response(&format!("termo-rescisao-{}.pdf", id), pdf, true))
+}
+
+pub async fn update_servidor(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(input): Form<UpdateServidor>,
+) -> Response {
+    let service = ServidorService::new();
+
+    match service.update(&state.db, &current_user, id, input).await {
+        Ok(_) => {
+            messages.success("Servidor atualizado com sucesso!");
+            Redirect::to(&format!("/{}/servidor-form/{}", PATH, id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao atualizar servidor: {}", err));
+            Redirect::to(&format!("/{}/servidor-form/{}", PATH, id)).into_response()
+        }
+    }
+}
+
+pub async fn delete_servidor(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = ServidorService::new();
+
+    match service.delete(&state.db, &current_user, id).await {
+        Ok(()) => {
+            messages.success("Servidor excluído com sucesso!");
+            Redirect::to(&format!("/{}/servidor", PATH)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao excluir servidor: {}", err));
+            Redirect::to(&format!("/{}/servidor", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn servidor_api(
+    Query(q): Query<PaginationQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<PaginatedResponse<Servidor>>, StatusCode> {
+    let service = ServidorService::new();
+    let res = service
+        .get_paginated(
+            &state.db,
+            q.find.as_deref(),
+            q.page.unwrap_or(1) as i32,
+            q.page_size.unwrap_or(10) as i32,
+        )
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn servidor_api_by_id(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Json<Servidor>, StatusCode> {
+    let service = ServidorService::new();
+    let res = service.get_by_id(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+/*
+==========================================
+            Cargo
+==========================================
+*/
+
+pub async fn list_cargo(
+    State(state): State<SharedState>,
+    Query(params): Query<ListParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = CargoService::new();
+
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            params.find.as_deref(),
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                messages => messages_vec
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/cargo_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        messages.error(format!("Erro ao renderizar template: {}", err));
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    messages.error(format!("Erro ao carregar template: {}", err));
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar cargo: {}", err);
+            messages.error(format!("Erro ao carregar cargo: {}", err));
+            Redirect::to(&format!("/{}/cargo", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn cargo_form(
+    State(state): State<SharedState>,
+    messages: Messages,
+) -> Result<Html<String>, impl IntoResponse> {
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let context = minijinja::context! {
+        messages => messages_vec
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/cargo_form.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Ok(Html(html)),
+            Err(err) => {
+                messages.error(format!("Erro ao renderizar template: {}", err));
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Erro ao renderizar template: {}", err),
+                )
+                    .into_response())
+            }
+        },
+        Err(err) => {
+            messages.error(format!("Erro ao carregar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Erro ao carregar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn create_cargo(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Form(body): Form<CreateCargo>,
+) -> Response {
+    let service = CargoService::new();
+
+    match service.create(&state.db, &current_user, body).await {
+        Ok(cargo) => {
+            messages.success("Cargo criado com sucesso!");
+            Redirect::to(&format!("/{}/cargo-form/{}", PATH, cargo.id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao criar cargo: {}", err));
+            Redirect::to(&format!("/{}/cargo-form", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn get_cargo(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+    let service = CargoService::new();
+
+    // Carregar o template
+    let template = match state
+        .templates
+        .get_template(&format!("{}/cargo_form.html", PATH))
+    {
+        Ok(t) => t,
+        Err(err) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao carregar template: {}", err),
+            )
+                .into_response());
+        }
+    };
+
+    let cargo = match service.get_by_id(&state.db, id).await {
+        Ok(p) => p,
+        Err(e) => {
+            debug!("Erro ao buscar cargo: {}", e);
+            messages.error(format!("Erro ao buscar cargo: {}", e));
+            return Err(Redirect::to(&format!("/{}/cargo-form", PATH)).into_response());
+        }
+    };
+
+    // Preparar o contexto
+    let ctx = context! {
+        row => cargo,
+        messages => messages_vec,
+    };
+
+    match template.render(&ctx) {
+        Ok(html) => Ok(Html(html)),
+        Err(err) => {
+            messages.error(format!("Falha ao renderizar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao renderizar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn update_cargo(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(input): Form<UpdateCargo>,
+) -> Response {
+    let service = CargoService::new();
+
+    match service.update(&state.db, &current_user, id, input).await {
+        Ok(_) => {
+            messages.success("Cargo atualizado com sucesso!");
+            Redirect::to(&format!("/{}/cargo-form/{}", PATH, id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao atualizar cargo: {}", err));
+            Redirect::to(&format!("/{}/cargo-form/{}", PATH, id)).into_response()
+        }
+    }
+}
+
+pub async fn delete_cargo(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = CargoService::new();
+
+    match service.delete(&state.db, &current_user, id).await {
+        Ok(()) => {
+            messages.success("Cargo excluído com sucesso!");
+            Redirect::to(&format!("/{}/cargo", PATH)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao excluir cargo: {}", err));
+            Redirect::to(&format!("/{}/cargo", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn cargo_api(
+    Query(q): Query<PaginationQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<PaginatedResponse<Cargo>>, StatusCode> {
+    let service = CargoService::new();
+    let res = service
+        .get_paginated(
+            &state.db,
+            q.find.as_deref(),
+            q.page.unwrap_or(1) as i32,
+            q.page_size.unwrap_or(10) as i32,
+        )
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn cargo_api_by_id(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Json<Cargo>, StatusCode> {
+    let service = CargoService::new();
+    let res = service.get_by_id(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+/*
+==========================================
+            Setor
+==========================================
+*/
+
+pub async fn list_setor(
+    State(state): State<SharedState>,
+    Query(params): Query<ListParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = SetorService::new();
+
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            params.find.as_deref(),
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                messages => messages_vec
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/setor_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        messages.error(format!("Erro ao renderizar template: {}", err));
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    messages.error(format!("Erro ao carregar template: {}", err));
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar setor: {}", err);
+            messages.error(format!("Erro ao carregar setor: {}", err));
+            Redirect::to(&format!("/{}/setor", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn setor_form(
+    State(state): State<SharedState>,
+    messages: Messages,
+) -> Result<Html<String>, impl IntoResponse> {
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let context = minijinja::context! {
+        messages => messages_vec
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/setor_form.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Ok(Html(html)),
+            Err(err) => {
+                messages.error(format!("Erro ao renderizar template: {}", err));
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Erro ao renderizar template: {}", err),
+                )
+                    .into_response())
+            }
+        },
+        Err(err) => {
+            messages.error(format!("Erro ao carregar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Erro ao carregar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn create_setor(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Form(body): Form<CreateSetor>,
+) -> Response {
+    let service = SetorService::new();
+
+    match service.create(&state.db, &current_user, body).await {
+        Ok(setor) => {
+            messages.success("Setor criado com sucesso!");
+            Redirect::to(&format!("/{}/setor-form/{}", PATH, setor.id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao criar setor: {}", err));
+            Redirect::to(&format!("/{}/setor-form", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn get_setor(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+    let service = SetorService::new();
+
+    // Carregar o template
+    let template = match state
+        .templates
+        .get_template(&format!("{}/setor_form.html", PATH))
+    {
+        Ok(t) => t,
+        Err(err) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao carregar template: {}", err),
+            )
+                .into_response());
+        }
+    };
+
+    let setor = match service.get_by_id(&state.db, id).await {
+        Ok(p) => p,
+        Err(e) => {
+            debug!("Erro ao buscar setor: {}", e);
+            messages.error(format!("Erro ao buscar setor: {}", e));
+            return Err(Redirect::to(&format!("/{}/setor-form", PATH)).into_response());
+        }
+    };
+
+    // Preparar o contexto
+    let ctx = context! {
+        row => setor,
+        messages => messages_vec,
+    };
+
+    match template.render(&ctx) {
+        Ok(html) => Ok(Html(html)),
+        Err(err) => {
+            messages.error(format!("Falha ao renderizar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao renderizar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn update_setor(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(input): Form<UpdateSetor>,
+) -> Response {
+    let service = SetorService::new();
+
+    match service.update(&state.db, &current_user, id, input).await {
+        Ok(_) => {
+            messages.success("Setor atualizado com sucesso!");
+            Redirect::to(&format!("/{}/setor-form/{}", PATH, id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao atualizar setor: {}", err));
+            Redirect::to(&format!("/{}/setor-form/{}", PATH, id)).into_response()
+        }
+    }
+}
+
+pub async fn delete_setor(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = SetorService::new();
+
+    match service.delete(&state.db, &current_user, id).await {
+        Ok(()) => {
+            messages.success("Setor excluído com sucesso!");
+            Redirect::to(&format!("/{}/setor", PATH)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao excluir setor: {}", err));
+            Redirect::to(&format!("/{}/setor", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn setor_api(
+    Query(q): Query<PaginationQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<PaginatedResponse<Setor>>, StatusCode> {
+    let service = SetorService::new();
+    let res = service
+        .get_paginated(
+            &state.db,
+            q.find.as_deref(),
+            q.page.unwrap_or(1) as i32,
+            q.page_size.unwrap_or(10) as i32,
+        )
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn setor_api_by_id(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Json<Setor>, StatusCode> {
+    let service = SetorService::new();
+    let res = service.get_by_id(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+/*
+==========================================
+            Departamento
+==========================================
+*/
+
+pub async fn list_departamento(
+    State(state): State<SharedState>,
+    Query(params): Query<ListParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = DepartamentoService::new();
+
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            params.find.as_deref(),
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                messages => messages_vec
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/departamento_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        messages.error(format!("Erro ao renderizar template: {}", err));
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    messages.error(format!("Erro ao carregar template: {}", err));
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar departamento: {}", err);
+            messages.error(format!("Erro ao carregar departamento: {}", err));
+            Redirect::to(&format!("/{}/departamento", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn departamento_form(
+    State(state): State<SharedState>,
+    messages: Messages,
+) -> Result<Html<String>, impl IntoResponse> {
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let context = minijinja::context! {
+        messages => messages_vec
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/departamento_form.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Ok(Html(html)),
+            Err(err) => {
+                messages.error(format!("Erro ao renderizar template: {}", err));
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Erro ao renderizar template: {}", err),
+                )
+                    .into_response())
+            }
+        },
+        Err(err) => {
+            messages.error(format!("Erro ao carregar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Erro ao carregar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn create_departamento(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Form(body): Form<CreateDepartamento>,
+) -> Response {
+    let service = DepartamentoService::new();
+
+    match service.create(&state.db, &current_user, body).await {
+        Ok(departamento) => {
+            messages.success("Departamento criado com sucesso!");
+            Redirect::to(&format!("/{}/departamento-form/{}", PATH, departamento.id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao criar departamento: {}", err));
+            Redirect::to(&format!("/{}/departamento-form", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn get_departamento(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+    let service = DepartamentoService::new();
+
+    // Carregar o template
+    let template = match state
+        .templates
+        .get_template(&format!("{}/departamento_form.html", PATH))
+    {
+        Ok(t) => t,
+        Err(err) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao carregar template: {}", err),
+            )
+                .into_response());
+        }
+    };
+
+    let departamento = match service.get_by_id(&state.db, id).await {
+        Ok(p) => p,
+        Err(e) => {
+            debug!("Erro ao buscar departamento: {}", e);
+            messages.error(format!("Erro ao buscar departamento: {}", e));
+            return Err(Redirect::to(&format!("/{}/departamento-form", PATH)).into_response());
+        }
+    };
+
+    // Preparar o contexto
+    let ctx = context! {
+        row => departamento,
+        messages => messages_vec,
+    };
+
+    match template.render(&ctx) {
+        Ok(html) => Ok(Html(html)),
+        Err(err) => {
+            messages.error(format!("Falha ao renderizar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao renderizar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn update_departamento(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(input): Form<UpdateDepartamento>,
+) -> Response {
+    let service = DepartamentoService::new();
+
+    match service.update(&state.db, &current_user, id, input).await {
+        Ok(_) => {
+            messages.success("Departamento atualizado com sucesso!");
+            Redirect::to(&format!("/{}/departamento-form/{}", PATH, id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao atualizar departamento: {}", err));
+            Redirect::to(&format!("/{}/departamento-form/{}", PATH, id)).into_response()
+        }
+    }
+}
+
+pub async fn delete_departamento(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = DepartamentoService::new();
+
+    match service.delete(&state.db, &current_user, id).await {
+        Ok(()) => {
+            messages.success("Departamento excluído com sucesso!");
+            Redirect::to(&format!("/{}/departamento", PATH)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao excluir departamento: {}", err));
+            Redirect::to(&format!("/{}/departamento", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn departamento_api(
+    Query(q): Query<PaginationQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<PaginatedResponse<Departamento>>, StatusCode> {
+    let service = DepartamentoService::new();
+    let res = service
+        .get_paginated(
+            &state.db,
+            q.find.as_deref(),
+            q.page.unwrap_or(1) as i32,
+            q.page_size.unwrap_or(10) as i32,
+        )
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn departamento_api_by_id(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Json<Departamento>, StatusCode> {
+    let service = DepartamentoService::new();
+    let res = service.get_by_id(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+/*
+==========================================
+            Tipo de Vínculo
+==========================================
+*/
+
+pub async fn list_tipo_vinculo(
+    State(state): State<SharedState>,
+    Query(params): Query<ListParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = TipoVinculoService::new();
+
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            params.find.as_deref(),
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                messages => messages_vec
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/tipo_vinculo_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        messages.error(format!("Erro ao renderizar template: {}", err));
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    messages.error(format!("Erro ao carregar template: {}", err));
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar tipo de vínculo: {}", err);
+            messages.error(format!("Erro ao carregar tipo de vínculo: {}", err));
+            Redirect::to(&format!("/{}/tipo-vinculo", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn tipo_vinculo_form(
+    State(state): State<SharedState>,
+    messages: Messages,
+) -> Result<Html<String>, impl IntoResponse> {
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let context = minijinja::context! {
+        messages => messages_vec
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/tipo_vinculo_form.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Ok(Html(html)),
+            Err(err) => {
+                messages.error(format!("Erro ao renderizar template: {}", err));
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Erro ao renderizar template: {}", err),
+                )
+                    .into_response())
+            }
+        },
+        Err(err) => {
+            messages.error(format!("Erro ao carregar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Erro ao carregar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn create_tipo_vinculo(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Form(body): Form<CreateTipoVinculo>,
+) -> Response {
+    let service = TipoVinculoService::new();
+
+    match service.create(&state.db, &current_user, body).await {
+        Ok(tipo_vinculo) => {
+            messages.success("Tipo de vínculo criado com sucesso!");
+            Redirect::to(&format!("/{}/tipo-vinculo-form/{}", PATH, tipo_vinculo.id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao criar tipo de vínculo: {}", err));
+            Redirect::to(&format!("/{}/tipo-vinculo-form", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn get_tipo_vinculo(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+    let service = TipoVinculoService::new();
+
+    // Carregar o template
+    let template = match state
+        .templates
+        .get_template(&format!("{}/tipo_vinculo_form.html", PATH))
+    {
+        Ok(t) => t,
+        Err(err) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao carregar template: {}", err),
+            )
+                .into_response());
+        }
+    };
+
+    let tipo_vinculo = match service.get_by_id(&state.db, id).await {
+        Ok(p) => p,
+        Err(e) => {
+            debug!("Erro ao buscar tipo de vínculo: {}", e);
+            messages.error(format!("Erro ao buscar tipo de vínculo: {}", e));
+            return Err(Redirect::to(&format!("/{}/tipo-vinculo-form", PATH)).into_response());
+        }
+    };
+
+    // Preparar o contexto
+    let ctx = context! {
+        row => tipo_vinculo,
+        messages => messages_vec,
+    };
+
+    match template.render(&ctx) {
+        Ok(html) => Ok(Html(html)),
+        Err(err) => {
+            messages.error(format!("Falha ao renderizar template: {}", err));
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao renderizar template: {}", err),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn update_tipo_vinculo(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(input): Form<UpdateTipoVinculo>,
+) -> Response {
+    let service = TipoVinculoService::new();
+
+    match service.update(&state.db, &current_user, id, input).await {
+        Ok(_) => {
+            messages.success("Tipo de vínculo atualizado com sucesso!");
+            Redirect::to(&format!("/{}/tipo-vinculo-form/{}", PATH, id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao atualizar tipo de vínculo: {}", err));
+            Redirect::to(&format!("/{}/tipo-vinculo-form/{}", PATH, id)).into_response()
+        }
+    }
+}
+
+pub async fn delete_tipo_vinculo(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = TipoVinculoService::new();
+
+    match service.delete(&state.db, &current_user, id).await {
+        Ok(()) => {
+            messages.success("Tipo de vínculo excluído com sucesso!");
+            Redirect::to(&format!("/{}/tipo-vinculo", PATH)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao excluir tipo de vínculo: {}", err));
+            Redirect::to(&format!("/{}/tipo-vinculo", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn tipo_vinculo_api(
+    Query(q): Query<PaginationQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<PaginatedResponse<TipoVinculo>>, StatusCode> {
+    let service = TipoVinculoService::new();
+    let res = service
+        .get_paginated(
+            &state.db,
+            q.find.as_deref(),
+            q.page.unwrap_or(1) as i32,
+            q.page_size.unwrap_or(10) as i32,
+        )
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn tipo_vinculo_api_by_id(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Json<TipoVinculo>, StatusCode> {
+    let service = TipoVinculoService::new();
+    let res = service.get_by_id(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+/*
+==========================================
+            Itens da Folha
+==========================================
+*/
+
+pub async fn folha_item_api(
+    Path(folha_id): Path<i64>,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<FolhaItem>>, StatusCode> {
+    let service = FolhaItemService::new();
+    let res = service
+        .get_by_folha(&state.db, folha_id)
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            error::status_code(&err)
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn create_folha_item(
+    State(state): State<SharedState>,
+    Json(input): Json<CreateFolhaItem>,
+) -> Result<Json<FolhaItem>, StatusCode> {
+    let service = FolhaItemService::new();
+    let res = service.create(&state.db, input).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn update_folha_item(
+    Path(id): Path<i64>,
+    State(state): State<SharedState>,
+    Json(input): Json<UpdateFolhaItem>,
+) -> Result<Json<FolhaItem>, StatusCode> {
+    let service = FolhaItemService::new();
+    let res = service.update(&state.db, id, input).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn delete_folha_item(
+    Path(id): Path<i64>,
+    State(state): State<SharedState>,
+) -> Result<StatusCode, StatusCode> {
+    let service = FolhaItemService::new();
+    service.delete(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/*
+==========================================
+            Cálculo (INSS e IRRF)
+==========================================
+*/
+
+pub async fn calcular_folha(
+    Path(id): Path<i64>,
+    State(state): State<SharedState>,
+) -> Result<Json<ResultadoCalculo>, StatusCode> {
+    let service = CalculoService::new();
+    let res = service.calcular_folha(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn calcular_inss_folha(
+    Path(id): Path<i64>,
+    State(state): State<SharedState>,
+) -> Result<Json<ResultadoInss>, StatusCode> {
+    let service = CalculoService::new();
+    let res = service.calcular_inss(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn preview_inss(
+    Query(q): Query<InssPreviewParams>,
+    State(state): State<SharedState>,
+) -> Result<Json<ResultadoInss>, StatusCode> {
+    let service = CalculoService::new();
+    let res = service
+        .preview_inss(&state.db, q.ano, q.mes, &q.base)
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn tabela_inss_api(
+    Query(q): Query<CompetenciaParams>,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<FaixaInss>>, StatusCode> {
+    let service = TabelaInssService::new();
+    let res = service
+        .get_vigente(&state.db, q.ano, q.mes)
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::NOT_FOUND
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn create_faixa_inss(
+    State(state): State<SharedState>,
+    Json(input): Json<CreateFaixaInss>,
+) -> Result<Json<FaixaInss>, StatusCode> {
+    let service = TabelaInssService::new();
+    let res = service.create(&state.db, input).await.map_err(|err| {
+        debug!("error:{}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn update_faixa_inss(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+    Json(input): Json<UpdateFaixaInss>,
+) -> Result<Json<FaixaInss>, StatusCode> {
+    let service = TabelaInssService::new();
+    let res = service.update(&state.db, id, input).await.map_err(|err| {
+        debug!("error:{}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn delete_faixa_inss(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<StatusCode, StatusCode> {
+    let service = TabelaInssService::new();
+    service.delete(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn calcular_irrf_folha(
+    Path(id): Path<i64>,
+    State(state): State<SharedState>,
+) -> Result<Json<ResultadoIrrf>, StatusCode> {
+    let service = CalculoService::new();
+    let res = service.calcular_irrf(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn preview_irrf(
+    Query(q): Query<IrrfPreviewParams>,
+    State(state): State<SharedState>,
+) -> Result<Json<ResultadoIrrf>, StatusCode> {
+    let service = CalculoService::new();
+    let res = service.preview_irrf(&state.db, q).await.map_err(|err| {
+        debug!("error:{}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(res))
+}
+
+/// Faixas e parâmetros do IRRF vigentes na competência
+pub async fn tabela_irrf_api(
+    Query(q): Query<CompetenciaParams>,
+    State(state): State<SharedState>,
+) -> Result<Json<Value>, StatusCode> {
+    let service = TabelaIrrfService::new();
+    let (faixas, parametro) = service
+        .get_vigente(&state.db, q.ano, q.mes)
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::NOT_FOUND
+        })?;
+
+    Ok(Json(serde_json::json!({
+        "faixas": faixas,
+        "parametro": parametro,
+    })))
+}
+
+pub async fn create_faixa_irrf(
+    State(state): State<SharedState>,
+    Json(input): Json<CreateFaixaIrrf>,
+) -> Result<Json<FaixaIrrf>, StatusCode> {
+    let service = TabelaIrrfService::new();
+    let res = service.create_faixa(&state.db, input).await.map_err(|err| {
+        debug!("error:{}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn update_faixa_irrf(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+    Json(input): Json<UpdateFaixaIrrf>,
+) -> Result<Json<FaixaIrrf>, StatusCode> {
+    let service = TabelaIrrfService::new();
+    let res = service.update_faixa(&state.db, id, input).await.map_err(|err| {
+        debug!("error:{}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn delete_faixa_irrf(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<StatusCode, StatusCode> {
+    let service = TabelaIrrfService::new();
+    service.delete_faixa(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn create_parametro_irrf(
+    State(state): State<SharedState>,
+    Json(input): Json<CreateParametroIrrf>,
+) -> Result<Json<ParametroIrrf>, StatusCode> {
+    let service = TabelaIrrfService::new();
+    let res = service.create_parametro(&state.db, input).await.map_err(|err| {
+        debug!("error:{}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn update_parametro_irrf(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+    Json(input): Json<UpdateParametroIrrf>,
+) -> Result<Json<ParametroIrrf>, StatusCode> {
+    let service = TabelaIrrfService::new();
+    let res = service
+        .update_parametro(&state.db, id, input)
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn delete_parametro_irrf(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<StatusCode, StatusCode> {
+    let service = TabelaIrrfService::new();
+    service.delete_parametro(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/*
+==========================================
+            Competência
+==========================================
+*/
+
+pub async fn list_competencia(
+    State(state): State<SharedState>,
+    Query(params): Query<ListParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = CompetenciaService::new();
+
+    let messages_vec: Vec<_> = messages
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            params.find.as_deref(),
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                messages => messages_vec,
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/competencia_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar competências: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn abrir_competencia(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Form(body): Form<CreateCompetencia>,
+) -> Response {
+    let service = CompetenciaService::new();
+
+    match service
+        .abrir(&state.db, Some(&current_user.username), body)
+        .await
+    {
+        Ok(competencia) => {
+            messages.success(format!(
+                "Competência {:02}/{} {}",
+                competencia.mes,
+                competencia.ano,
+                competencia.situacao.to_lowercase()
+            ));
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao abrir competência: {}", err));
+        }
+    }
+    Redirect::to(&format!("/{}/competencia", PATH)).into_response()
+}
+
+pub async fn calcular_competencia(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(body): Form<TransicaoCompetencia>,
+) -> Response {
+    let service = CompetenciaService::new();
+
+    match service
+        .calcular(&state.db, &current_user, id, body.motivo.as_deref())
+        .await
+    {
+        Ok(_) => {
+            messages.success("Competência calculada com sucesso!");
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao calcular competência: {}", err));
+        }
+    }
+    Redirect::to(&format!("/{}/competencia", PATH)).into_response()
+}
+
+pub async fn calcular_decimo_terceiro(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Form(body): Form<CalcularDecimoTerceiro>,
+) -> Response {
+    let service = DecimoTerceiroService::new();
+    let parcela = body.parcela;
+
+    match service.calcular(&state.db, &current_user, body).await {
+        Ok(resultado) => {
+            let mut messages = messages.success(format!(
+                "13º calculado: {} folhas, líquido total de R$ {}",
+                resultado.folhas,
+                decimal_br(&resultado.total_liquido)
+            ));
+            if resultado.sem_avos > 0 {
+                messages = messages.info(format!("{} servidores sem avos no período", resultado.sem_avos));
+            }
+            if !resultado.sem_folha.is_empty() {
+                messages.warning(format!(
+                    "Sem folha mensal no ano, não calculados: {}",
+                    resultado.sem_folha.join(", ")
+                ));
+            }
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao calcular 13º: {}", err));
+            return Redirect::to(&format!("/{}/competencia", PATH)).into_response();
+        }
+    }
+    Redirect::to(&format!("/{}/folha?tipo_folha={}", PATH, parcela)).into_response()
+}
+
+/// Prévia da geração da folha: servidores que terão folha, os que ficam de fora e o que
+/// muda da competência de origem, para conferir antes de gravar
+pub async fn previa_gerar_folha(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<GerarFolha>,
+    messages: Messages,
+) -> Response {
+    let service = GeracaoFolhaService::new();
+
+    let previa = match service.previa(&state.db, &current_user, &params).await {
+        Ok(previa) => previa,
+        Err(err) => {
+            messages.error(format!("Erro ao gerar a prévia da folha: {}", err));
+            return Redirect::to(&format!("/{}/competencia", PATH)).into_response();
+        }
+    };
+
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let context = minijinja::context! {
+        previa => previa,
+        messages => messages_vec,
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/gerar_folha.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Html(html).into_response(),
+            Err(err) => {
+                debug!("Erro ao renderizar template: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        },
+        Err(err) => {
+            debug!("Erro ao carregar template: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn gerar_folha(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Form(body): Form<GerarFolha>,
+) -> Response {
+    let service = GeracaoFolhaService::new();
+
+    match service.gerar(&state.db, &current_user, &body).await {
+        Ok(previa) => messages.success(format!(
+            "{} folha(s) mensal(is) gerada(s) em {:02}/{} a partir de {:02}/{}",
+            previa.gerar, previa.mes, previa.ano, previa.mes_origem, previa.ano_origem
+        )),
+        Err(err) => messages.error(format!("Erro ao gerar a folha: {}", err)),
+    };
+    Redirect::to(&format!("/{}/competencia", PATH)).into_response()
+}
+
+pub async fn fechar_competencia(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(body): Form<TransicaoCompetencia>,
+) -> Response {
+    let service = CompetenciaService::new();
+
+    match service
+        .fechar(&state.db, &current_user, id, body.motivo.as_deref())
+        .await
+    {
+        Ok(_) => {
+            messages.success("Competência fechada com sucesso!");
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao fechar competência: {}", err));
+        }
+    }
+    Redirect::to(&format!("/{}/competencia", PATH)).into_response()
+}
+
+pub async fn reabrir_competencia(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(body): Form<TransicaoCompetencia>,
+) -> Response {
+    let service = CompetenciaService::new();
+
+    match service
+        .reabrir(&state.db, &current_user, id, body.motivo.as_deref())
+        .await
+    {
+        Ok(_) => {
+            messages.success("Competência reaberta com sucesso!");
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao reabrir competência: {}", err));
+        }
+    }
+    Redirect::to(&format!("/{}/competencia", PATH)).into_response()
+}
+
+/*
+eventos do eSocial
+*/
+
+/// Gera os eventos S-1200 (e S-1210, com a data de pagamento) da competência fechada
+pub async fn gerar_esocial_competencia(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(body): Form<GerarEsocial>,
+) -> Response {
+    let service = EsocialService::new();
+
+    match service.gerar(&state.db, &current_user, id, body).await {
+        Ok(resultado) if resultado.invalidos > 0 => {
+            messages.warning(format!(
+                "eSocial gerado: {} validados, {} inválidos e {} sem validação. Corrija os inválidos e gere de novo.",
+                resultado.validados, resultado.invalidos, resultado.gerados
+            ));
+        }
+        Ok(resultado) => {
+            messages.success(format!(
+                "eSocial gerado: {} validados e {} sem validação.",
+                resultado.validados, resultado.gerados
+            ));
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao gerar eSocial: {}", err));
+        }
+    }
+    Redirect::to(&format!("/{}/competencia", PATH)).into_response()
+}
+
+pub async fn list_esocial(
+    State(state): State<SharedState>,
+    Query(params): Query<EventoEsocialParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = EsocialService::new();
+
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            &params,
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    // filtros repetidos nos links da paginação
+    let filtros: String = [
+        ("orgao_id", params.orgao_id.map(|v| v.to_string())),
+        ("ano", params.ano.map(|v| v.to_string())),
+        ("mes", params.mes.map(|v| v.to_string())),
+        ("tipo", params.tipo.clone()),
+        ("situacao", params.situacao.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(campo, valor)| valor.map(|v| format!("&{}={}", campo, v)))
+    .collect();
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                filtros => filtros,
+                orgao_id => params.orgao_id,
+                ano => params.ano,
+                mes => params.mes,
+                tipo => params.tipo,
+                situacao => params.situacao,
+                messages => messages_vec,
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/esocial_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar eventos do eSocial: {}", err);
+            messages.error(format!("Erro ao carregar eventos do eSocial: {}", err));
+            Redirect::to(&format!("/{}/competencia", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn validar_esocial(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i64>,
+) -> Response {
+    let service = EsocialService::new();
+
+    match service.validar(&state.db, &current_user, id).await {
+        Ok(situacao) => {
+            messages.info(format!("Evento validado de novo: {}", situacao));
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao validar evento: {}", err));
+        }
+    }
+    Redirect::to(&format!("/{}/esocial", PATH)).into_response()
+}
+
+pub async fn download_xml_esocial(
+    Path(id): Path<i64>,
+    State(state): State<SharedState>,
+) -> Result<Response, (StatusCode, String)> {
+    let service = EsocialService::new();
+    let arquivo = service.xml(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        (error::status_code(&err), err.to_string())
+    })?;
+
+    Ok(arquivo_response(&arquivo.nome, arquivo.conteudo, false))
+}
+
+/// ZIP com os XMLs validados da competência
+pub async fn download_zip_esocial(
+    Query(params): Query<ZipEsocialParams>,
+    State(state): State<SharedState>,
+) -> Result<Response, (StatusCode, String)> {
+    let service = EsocialService::new();
+    let arquivo = service
+        .zip_validados(&state.db, params.orgao_id, params.ano, params.mes)
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            (error::status_code(&err), err.to_string())
+        })?;
+
+    Ok(arquivo_response(&arquivo.nome, arquivo.conteudo, false))
+}
+
+/// Tela do informe de rendimentos; com órgão e ano, devolve o PDF do servidor ou o
+/// lote do órgão, e em caso de erro volta à tela com a mensagem
+pub async fn informe_rendimentos(
+    State(state): State<SharedState>,
+    Query(params): Query<InformeRendimentosParams>,
+    messages: Messages,
+) -> Response {
+    if params.orgao_id.is_some() || params.ano.is_some() {
+        let service = InformeRendimentosService::new();
+        match service.gerar(&state.db, &params).await {
+            Ok(arquivo) => return arquivo_response(&arquivo.nome, arquivo.conteudo, false),
+            Err(err) => {
+                debug!("Erro ao gerar informe de rendimentos: {}", err);
+                messages.error(format!("Erro ao gerar informe de rendimentos: {}", err));
+                return Redirect::to(&format!("/{}/informe-rendimentos", PATH)).into_response();
+            }
+        }
+    }
+
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    // em fevereiro o informe é o do ano anterior
+    let context = minijinja::context! {
+        ano => Local::now().year() - 1,
+        messages => messages_vec,
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/informe_rendimentos.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Html(html).into_response(),
+            Err(err) => {
+                debug!("Erro ao renderizar template: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        },
+        Err(err) => {
+            debug!("Erro ao carregar template: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Fila dos eventos não periódicos (admissão, alteração contratual e desligamento)
+pub async fn list_esocial_fila(
+    State(state): State<SharedState>,
+    Query(params): Query<FilaEsocialParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = FilaEsocialService::new();
+
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            &params,
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    // filtros repetidos nos links da paginação
+    let filtros: String = [
+        ("orgao_id", params.orgao_id.map(|v| v.to_string())),
+        ("tipo", params.tipo.clone()),
+        ("situacao", params.situacao.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(campo, valor)| valor.map(|v| format!("&{}={}", campo, v)))
+    .collect();
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                filtros => filtros,
+                orgao_id => params.orgao_id,
+                tipo => params.tipo,
+                situacao => params.situacao,
+                messages => messages_vec,
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/esocial_fila_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar fila do eSocial: {}", err);
+            messages.error(format!("Erro ao carregar fila do eSocial: {}", err));
+            Redirect::to(&format!("/{}/esocial", PATH)).into_response()
+        }
+    }
+}
+
+fn mensagem_fila(messages: Messages, resultado: ResultadoFilaEsocial) {
+    if resultado.rejeitados > 0 {
+        messages.warning(format!(
+            "Fila do eSocial: {} gerados e {} rejeitados. Corrija o cadastro e gere os rejeitados de novo.",
+            resultado.gerados, resultado.rejeitados
+        ));
+    } else {
+        messages.success(format!("Fila do eSocial: {} gerados.", resultado.gerados));
+    }
+}
+
+/// Gera o XML dos eventos pendentes do órgão
+pub async fn gerar_esocial_fila(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Form(body): Form<GerarFilaEsocial>,
+) -> Response {
+    let service = FilaEsocialService::new();
+
+    match service.gerar_pendentes(&state.db, &current_user, body.orgao_id).await {
+        Ok(resultado) => mensagem_fila(messages, resultado),
+        Err(err) => {
+            messages.error(format!("Erro ao gerar fila do eSocial: {}", err));
+        }
+    }
+    Redirect::to(&format!("/{}/esocial-fila?orgao_id={}", PATH, body.orgao_id)).into_response()
+}
+
+pub async fn regerar_esocial_fila(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i64>,
+) -> Response {
+    let service = FilaEsocialService::new();
+
+    match service.regerar(&state.db, &current_user, id).await {
+        Ok(resultado) => mensagem_fila(messages, resultado),
+        Err(err) => {
+            messages.error(format!("Erro ao gerar evento: {}", err));
+        }
+    }
+    Redirect::to(&format!("/{}/esocial-fila", PATH)).into_response()
+}
+
+pub async fn download_xml_esocial_fila(
+    Path(id): Path<i64>,
+    State(state): State<SharedState>,
+) -> Result<Response, (StatusCode, String)> {
+    let service = FilaEsocialService::new();
+    let arquivo = service.xml(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        (error::status_code(&err), err.to_string())
+    })?;
+
+    Ok(arquivo_response(&arquivo.nome, arquivo.conteudo, false))
+}
+
+/// Gera a remessa CNAB 240 de pagamento da competência fechada
+pub async fn gerar_remessa_cnab(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(body): Form<GerarRemessaCnab>,
+) -> Response {
+    let service = RemessaCnabService::new();
+
+    match service.gerar(&state.db, &current_user, id, body).await {
+        Ok(resultado) => {
+            let texto = format!(
+                "Remessa NSA {} gerada com {} pagamentos (R$ {}).",
+                resultado.nsa,
+                resultado.pagamentos,
+                decimal_br(&resultado.valor_total)
+            );
+            if resultado.ignorados.is_empty() {
+                messages.success(texto);
+            } else {
+                messages.warning(format!("{} Ficaram de fora: {}.", texto, resultado.ignorados.join("; ")));
+            }
+            Redirect::to(&format!("/{}/cnab", PATH)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao gerar remessa CNAB: {}", err));
+            Redirect::to(&format!("/{}/competencia", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn list_remessa_cnab(
+    State(state): State<SharedState>,
+    Query(params): Query<RemessaCnabParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = RemessaCnabService::new();
+
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            &params,
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    // filtros repetidos nos links da paginação
+    let filtros: String = [
+        ("orgao_id", params.orgao_id.map(|v| v.to_string())),
+        ("ano", params.ano.map(|v| v.to_string())),
+        ("mes", params.mes.map(|v| v.to_string())),
+    ]
+    .into_iter()
+    .filter_map(|(campo, valor)| valor.map(|v| format!("&{}={}", campo, v)))
+    .collect();
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                filtros => filtros,
+                orgao_id => params.orgao_id,
+                ano => params.ano,
+                mes => params.mes,
+                messages => messages_vec,
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/remessa_cnab_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar remessas CNAB: {}", err);
+            messages.error(format!("Erro ao carregar remessas CNAB: {}", err));
+            Redirect::to(&format!("/{}/competencia", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn download_remessa_cnab(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Response, (StatusCode, String)> {
+    let service = RemessaCnabService::new();
+    let arquivo = service.arquivo(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        (error::status_code(&err), err.to_string())
+    })?;
+
+    Ok(arquivo_response(&arquivo.nome, arquivo.conteudo, false))
+}
+
+/// Importa o retorno do banco e leva à conciliação dos créditos da remessa
+pub async fn importar_retorno_cnab(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    mut multipart: Multipart,
+) -> Response {
+    let service = RemessaCnabService::new();
+
+    let resultado = async {
+        let mut arquivo = None;
+        while let Some(campo) = multipart.next_field().await? {
+            if campo.name() == Some("arquivo") {
+                arquivo = Some(campo.bytes().await?);
+            }
+        }
+        match arquivo {
+            Some(bytes) if !bytes.is_empty() => {
+                service.importar_retorno(&state.db, &current_user, id, &bytes).await
+            }
+            _ => Err(anyhow::anyhow!("Selecione o arquivo de retorno")),
+        }
+    }
+    .await;
+    match resultado {
+        Ok(r) => {
+            let texto = format!(
+                "Retorno importado: {} pagos, {} agendados e {} rejeitados.",
+                r.pagos, r.agendados, r.rejeitados
+            );
+            if r.nao_encontrados.is_empty() {
+                messages.success(texto);
+            } else {
+                messages.warning(format!(
+                    "{} Créditos que não são desta remessa: {}.",
+                    texto,
+                    r.nao_encontrados.join(", ")
+                ));
+            }
+            Redirect::to(&format!("/{}/cnab/conciliacao?remessa_id={}", PATH, id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao importar retorno: {}", err));
+            Redirect::to(&format!("/{}/cnab", PATH)).into_response()
+        }
+    }
+}
+
+/// Créditos das remessas com a situação do retorno, para corrigir os rejeitados
+pub async fn list_conciliacao_cnab(
+    State(state): State<SharedState>,
+    Query(params): Query<ConciliacaoCnabParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = RemessaCnabService::new();
+
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_conciliacao(
+            &state.db,
+            &params,
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    // filtros repetidos nos links da paginação
+    let filtros: String = [
+        ("orgao_id", params.orgao_id.map(|v| v.to_string())),
+        ("ano", params.ano.map(|v| v.to_string())),
+        ("mes", params.mes.map(|v| v.to_string())),
+        ("remessa_id", params.remessa_id.map(|v| v.to_string())),
+        ("situacao", params.situacao.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(campo, valor)| valor.map(|v| format!("&{}={}", campo, v)))
+    .collect();
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                filtros => filtros,
+                orgao_id => params.orgao_id,
+                ano => params.ano,
+                mes => params.mes,
+                remessa_id => params.remessa_id,
+                situacao => params.situacao,
+                messages => messages_vec,
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/conciliacao_cnab_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar conciliação CNAB: {}", err);
+            messages.error(format!("Erro ao carregar conciliação: {}", err));
+            Redirect::to(&format!("/{}/cnab", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn competencia_api(
+    Query(q): Query<PaginationQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<PaginatedResponse<Competencia>>, StatusCode> {
+    let service = CompetenciaService::new();
+    let res = service
+        .get_paginated(
+            &state.db,
+            q.find.as_deref(),
+            q.page.unwrap_or(1) as i32,
+            q.page_size.unwrap_or(10) as i32,
+        )
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+pub async fn competencia_historico_api(
+    Path(id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<CompetenciaHistorico>>, StatusCode> {
+    let service = CompetenciaService::new();
+    let res = service.historico(&state.db, id).await.map_err(|err| {
+        debug!("error:{}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(res))
+}
+
+/*
+==========================================
+            Consignatária
+==========================================
+*/
+
+pub async fn list_consignataria(
+    State(state): State<SharedState>,
+    Query(params): Query<ListParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = ConsignatariaService::new();
+
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            params.find.as_deref(),
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                messages => messages_vec
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/consignataria_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar consignatárias: {}", err);
+            messages.error(format!("Erro ao carregar consignatárias: {}", err));
+            Redirect::to("/").into_response()
+        }
+    }
+}
+
+pub async fn consignataria_form(
+    State(state): State<SharedState>,
+    messages: Messages,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let context = minijinja::context! {
+        messages => messages_vec
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/consignataria_form.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Ok(Html(html)),
+            Err(err) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Erro ao renderizar template: {}", err),
+            )
+                .into_response()),
+        },
+        Err(err) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Erro ao carregar template: {}", err),
+        )
+            .into_response()),
+    }
+}
+
+pub async fn create_consignataria(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Form(body): Form<CreateConsignataria>,
+) -> Response {
+    let service = ConsignatariaService::new();
+
+    match service.create(&state.db, body).await {
+        Ok(consignataria) => {
+            messages.success("Consignatária criada com sucesso!");
+            Redirect::to(&format!("/{}/consignataria-form/{}", PATH, consignataria.id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao criar consignatária: {}", err));
+            Redirect::to(&format!("/{}/consignataria-form", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn get_consignataria(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+    let service = ConsignatariaService::new();
+
+    let template = match state
+        .templates
+        .get_template(&format!("{}/consignataria_form.html", PATH))
+    {
+        Ok(t) => t,
+        Err(err) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao carregar template: {}", err),
+            )
+                .into_response());
+        }
+    };
+
+    let consignataria = match service.get_by_id(&state.db, id).await {
+        Ok(c) => c,
+        Err(e) => {
+            debug!("Erro ao buscar consignatária: {}", e);
+            messages.error(format!("Erro ao buscar consignatária: {}", e));
+            return Err(Redirect::to(&format!("/{}/consignataria", PATH)).into_response());
+        }
+    };
+
+    let credenciais = match service.credenciais(&state.db, id).await {
+        Ok(credenciais) => credenciais,
+        Err(e) => {
+            debug!("Erro ao buscar credenciais: {}", e);
+            Vec::new()
+        }
+    };
+
+    let ctx = context! {
+        row => consignataria,
+        credenciais => credenciais,
+        messages => messages_vec,
+    };
+
+    match template.render(&ctx) {
+        Ok(html) => Ok(Html(html)),
+        Err(err) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Falha ao renderizar template: {}", err),
+        )
+            .into_response()),
+    }
+}
+
+pub async fn update_consignataria(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(input): Form<UpdateConsignataria>,
+) -> Response {
+    let service = ConsignatariaService::new();
+
+    match service.update(&state.db, id, input).await {
+        Ok(_) => messages.success("Consignatária atualizada com sucesso!"),
+        Err(err) => messages.error(format!("Erro ao atualizar consignatária: {}", err)),
+    };
+    Redirect::to(&format!("/{}/consignataria-form/{}", PATH, id)).into_response()
+}
+
+pub async fn delete_consignataria(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = ConsignatariaService::new();
+
+    match service.delete(&state.db, id).await {
+        Ok(()) => messages.success("Consignatária excluída com sucesso!"),
+        Err(err) => messages.error(format!("Erro ao excluir consignatária: {}", err)),
+    };
+    Redirect::to(&format!("/{}/consignataria", PATH)).into_response()
+}
+
+pub async fn consignataria_api(
+    Query(q): Query<PaginationQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<PaginatedResponse<Consignataria>>, StatusCode> {
+    let service = ConsignatariaService::new();
+    let res = service
+        .get_paginated(
+            &state.db,
+            q.find.as_deref(),
+            q.page.unwrap_or(1) as i32,
+            q.page_size.unwrap_or(10) as i32,
+        )
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(res))
+}
+
+/*
+==========================================
+            Consignação
+==========================================
+*/
+
+pub async fn list_consignacao(
+    State(state): State<SharedState>,
+    Query(params): Query<ConsignacaoParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = ConsignacaoService::new();
+
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            &params,
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    // filtros repetidos nos links da paginação
+    let filtros: String = [
+        ("servidor_id", params.servidor_id.map(|v| v.to_string())),
+        ("consignataria_id", params.consignataria_id.map(|v| v.to_string())),
+        ("situacao", params.situacao.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(campo, valor)| valor.map(|v| format!("&{}={}", campo, v)))
+    .collect();
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                filtros => filtros,
+                servidor_id => params.servidor_id,
+                consignataria_id => params.consignataria_id,
+                situacao => params.situacao,
+                messages => messages_vec,
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/consignacao_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar consignações: {}", err);
+            messages.error(format!("Erro ao carregar consignações: {}", err));
+            Redirect::to("/").into_response()
+        }
+    }
+}
+
+pub async fn consignacao_form(
+    State(state): State<SharedState>,
+    messages: Messages,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    // a primeira parcela costuma cair na competência seguinte
+    let hoje = Local::now().date_naive();
+    let (ano_inicio, mes_inicio) = if hoje.month() == 12 {
+        (hoje.year() + 1, 1)
+    } else {
+        (hoje.year(), hoje.month() as i32 + 1)
+    };
+
+    let context = minijinja::context! {
+        ano_inicio => ano_inicio,
+        mes_inicio => mes_inicio,
+        messages => messages_vec
+    };
+
+    match state
+        .templates
+        .get_template(&format!("{}/consignacao_form.html", PATH))
+    {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Ok(Html(html)),
+            Err(err) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Erro ao renderizar template: {}", err),
+            )
+                .into_response()),
+        },
+        Err(err) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Erro ao carregar template: {}", err),
+        )
+            .into_response()),
+    }
+}
+
+pub async fn create_consignacao(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Form(body): Form<CreateConsignacao>,
+) -> Response {
+    let service = ConsignacaoService::new();
+
+    match service.create(&state.db, &current_user, body).await {
+        Ok(consignacao) => {
+            messages.success("Contrato averbado com sucesso!");
+            Redirect::to(&format!("/{}/consignacao-form/{}", PATH, consignacao.id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao averbar contrato: {}", err));
+            Redirect::to(&format!("/{}/consignacao-form", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn get_consignacao(
+    State(state): State<SharedState>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, impl IntoResponse> {
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+    let service = ConsignacaoService::new();
+
+    let template = match state
+        .templates
+        .get_template(&format!("{}/consignacao_form.html", PATH))
+    {
+        Ok(t) => t,
+        Err(err) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao carregar template: {}", err),
+            )
+                .into_response());
+        }
+    };
+
+    let consignacao = match service.get_by_id(&state.db, id).await {
+        Ok(c) => c,
+        Err(e) => {
+            debug!("Erro ao buscar contrato: {}", e);
+            messages.error(format!("Erro ao buscar contrato: {}", e));
+            return Err(Redirect::to(&format!("/{}/consignacao", PATH)).into_response());
+        }
+    };
+
+    // margem sem o próprio contrato: o quanto a parcela dele pode chegar
+    let margem = service
+        .margem(&state.db, consignacao.servidor_id, Some(id), None)
+        .await
+        .ok();
+
+    let ctx = context! {
+        row => consignacao,
+        margem => margem,
+        messages => messages_vec,
+    };
+
+    match template.render(&ctx) {
+        Ok(html) => Ok(Html(html)),
+        Err(err) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Falha ao renderizar template: {}", err),
+        )
+            .into_response()),
+    }
+}
+
+pub async fn update_consignacao(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(input): Form<UpdateConsignacao>,
+) -> Response {
+    let service = ConsignacaoService::new();
+
+    match service.update(&state.db, &current_user, id, input).await {
+        Ok(_) => messages.success("Contrato atualizado com sucesso!"),
+        Err(err) => messages.error(format!("Erro ao atualizar contrato: {}", err)),
+    };
+    Redirect::to(&format!("/{}/consignacao-form/{}", PATH, id)).into_response()
+}
+
+pub async fn delete_consignacao(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = ConsignacaoService::new();
+
+    match service.delete(&state.db, &current_user, id).await {
+        Ok(()) => messages.success("Contrato excluído com sucesso!"),
+        Err(err) => messages.error(format!("Erro ao excluir contrato: {}", err)),
+    };
+    Redirect::to(&format!("/{}/consignacao", PATH)).into_response()
+}
+
+/// Margem consignável do servidor pela última folha calculada
+pub async fn margem_consignavel_api(
+    Path(servidor_id): Path<i32>,
+    State(state): State<SharedState>,
+) -> Result<Json<Margem>, (StatusCode, String)> {
+    let service = ConsignacaoService::new();
+    let res = service
+        .margem(&state.db, servidor_id, None, None)
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            (error::status_code(&err), err.to_string())
+        })?;
+
+    Ok(Json(res))
+}
+
+// ------------------------------------------------------------
+// API de margem consignável (consignatárias)
+// ------------------------------------------------------------
+
+pub async fn gerar_credencial_consignataria(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+    Form(body): Form<CreateCredencialConsignataria>,
+) -> Response {
+    let service = ConsignatariaService::new();
+
+    match service.gerar_credencial(&state.db, &current_user, id, body).await {
+        Ok(token) => messages.success(format!(
+            "Credencial gerada. Copie o token agora, ele não será exibido novamente: {}",
+            token
+        )),
+        Err(err) => messages.error(format!("Erro ao gerar credencial: {}", err)),
+    };
+    Redirect::to(&format!("/{}/consignataria-form/{}", PATH, id)).into_response()
+}
+
+pub async fn revogar_credencial_consignataria(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    Path(id): Path<i32>,
+) -> Response {
+    let service = ConsignatariaService::new();
+
+    match service.revogar_credencial(&state.db, &current_user, id).await {
+        Ok(consignataria_id) => {
+            messages.success("Credencial revogada com sucesso!");
+            Redirect::to(&format!("/{}/consignataria-form/{}", PATH, consignataria_id)).into_response()
+        }
+        Err(err) => {
+            messages.error(format!("Erro ao revogar credencial: {}", err));
+            Redirect::to(&format!("/{}/consignataria", PATH)).into_response()
+        }
+    }
+}
+
+pub async fn list_consulta_margem(
+    State(state): State<SharedState>,
+    Query(params): Query<ConsultaMargemParams>,
+    messages: Messages,
+) -> impl IntoResponse {
+    let service = MargemApiService::new();
+
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let result = service
+        .get_paginated(
+            &state.db,
+            &params,
+            params.page.unwrap_or(1),
+            params.page_size.unwrap_or(10),
+        )
+        .await;
+
+    // filtros repetidos nos links da paginação
+    let filtros: String = [
+        ("consignataria_id", params.consignataria_id.map(|v| v.to_string())),
+        ("operacao", params.operacao.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(campo, valor)| valor.map(|v| format!("&{}={}", campo, v)))
+    .collect();
+
+    match result {
+        Ok(paginated_response) => {
+            let context = minijinja::context! {
+                rows => paginated_response.data,
+                current_page => paginated_response.page,
+                total_pages => paginated_response.total_pages,
+                page_size => paginated_response.page_size,
+                total_records => paginated_response.total_records,
+                find => params.find.unwrap_or_default(),
+                filtros => filtros,
+                consignataria_id => params.consignataria_id,
+                operacao => params.operacao,
+                messages => messages_vec,
+            };
+
+            match state
+                .templates
+                .get_template(&format!("{}/consulta_margem_list.html", PATH))
+            {
+                Ok(template) => match template.render(context) {
+                    Ok(html) => Html(html).into_response(),
+                    Err(err) => {
+                        debug!("Erro ao renderizar template: {}", err);
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                },
+                Err(err) => {
+                    debug!("Erro ao carregar template: {}", err);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+        Err(err) => {
+            debug!("Erro ao buscar consultas de margem: {}", err);
+            messages.error(format!("Erro ao carregar consultas de margem: {}", err));
+            Redirect::to("/").into_response()
+        }
+    }
+}
+
+/// Credencial e endereço de origem da chamada (atrás de proxy, o primeiro do X-Forwarded-For)
+fn origem_api_margem(headers: &HeaderMap) -> OrigemApiMargem {
+    let valor = |nome: &str| headers.get(nome).and_then(|h| h.to_str().ok());
+
+    OrigemApiMargem {
+        authorization: valor("authorization").map(str::to_string),
+        ip: valor("x-forwarded-for")
+            .and_then(|ips| ips.split(',').next())
+            .or_else(|| valor("x-real-ip"))
+            .map(|ip| ip.trim().to_string()),
+    }
+}
+
+/// Erro da API em JSON, com o status do erro do service
+fn erro_api_margem(err: anyhow::Error) -> Response {
+    let status = error::status_code(&err);
+    let corpo = Json(serde_json::json!({ "error": err.to_string() }));
+
+    if status == StatusCode::UNAUTHORIZED {
+        (status, [(header::WWW_AUTHENTICATE, "Bearer")], corpo).into_response()
+    } else {
+        (status, corpo).into_response()
+    }
+}
+
+/// GET /api/consignacao/margem?cpf=...
+pub async fn api_consultar_margem(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(params): Query<ConsultarMargemParams>,
+) -> Response {
+    let service = MargemApiService::new();
+
+    match service
+        .consultar(&state.db, &origem_api_margem(&headers), &params.cpf)
+        .await
+    {
+        Ok(margem) => Json(margem).into_response(),
+        Err(err) => erro_api_margem(err),
+    }
+}
+
+/// POST /api/consignacao/reserva
+pub async fn api_reservar_margem(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Result<Json<ReservarMargem>, JsonRejection>,
+) -> Response {
+    let service = MargemApiService::new();
+    let origem = origem_api_margem(&headers);
+
+    let Json(input) = match body {
+        Ok(body) => body,
+        Err(rejeicao) => {
+            let err = service
+                .recusar_reserva(&state.db, &origem, &rejeicao.body_text())
+                .await;
+            return erro_api_margem(err);
+        }
+    };
+
+    match service.reservar(&state.db, &origem, input).await {
+        Ok(reserva) => (StatusCode::CREATED, Json(reserva)).into_response(),
+        Err(err) => erro_api_margem(err),
+    }
+}
+
+/// DELETE /api/consignacao/reserva/{codigo}
+pub async fn api_cancelar_reserva(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(codigo): Path<String>,
+) -> Response {
+    let service = MargemApiService::new();
+
+    match service
+        .cancelar_reserva(&state.db, &origem_api_margem(&headers), &codigo)
+        .await
+    {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => erro_api_margem(err),
+    }
+}
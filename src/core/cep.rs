@@ -0,0 +1,243 @@
+use std::{collections::BTreeMap, env, sync::LazyLock, time::Duration};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    error::AppError,
+    utils::csv::{decodificar, detectar_separador, dividir_linha, normalizar_cabecalho},
+};
+
+pub const ORIGEM_IMPORTACAO: &str = "IMPORTACAO";
+
+/// Cabeçalhos aceitos para cada coluna, já normalizados (minúsculas, sem acento, "_")
+const COLUNAS_CEP: [&str; 2] = ["cep", "codigo_postal"];
+const COLUNAS_LOGRADOURO: [&str; 3] = ["logradouro", "endereco", "rua"];
+const COLUNAS_COMPLEMENTO: [&str; 1] = ["complemento"];
+const COLUNAS_BAIRRO: [&str; 1] = ["bairro"];
+const COLUNAS_LOCALIDADE: [&str; 4] = ["localidade", "cidade", "municipio", "nome_municipio"];
+const COLUNAS_UF: [&str; 2] = ["uf", "sigla_uf"];
+const COLUNAS_IBGE: [&str; 4] = ["ibge", "codigo_ibge", "cod_ibge", "codigo_municipio"];
+
+/// Endereço de um CEP como vem da base importada ou do provedor remoto
+#[derive(Debug, PartialEq)]
+pub struct DadosCep {
+    pub cep: String,
+    pub logradouro: String,
+    pub complemento: String,
+    pub bairro: String,
+    pub localidade: String,
+    pub uf: String,
+    pub codigo_ibge: Option<i32>,
+}
+
+/// "77.001-002" -> "77001002"; None se não sobrarem exatamente 8 dígitos
+pub fn normalizar_cep(cep: &str) -> Option<String> {
+    let digitos: String = cep.chars().filter(|c| c.is_ascii_digit()).collect();
+    let so_digitos_e_mascara = cep.trim().chars().all(|c| c.is_ascii_digit() || c == '-' || c == '.');
+    (digitos.len() == 8 && so_digitos_e_mascara).then_some(digitos)
+}
+
+/// Lê a base de CEPs em CSV. Além do CEP, cada linha precisa do código IBGE do
+/// município ou da cidade com a UF (o código é resolvido pelo cadastro na gravação)
+pub fn ler_base_cep(conteudo: &[u8]) -> Result<Vec<DadosCep>> {
+    let texto = decodificar(conteudo);
+    let mut linhas = texto.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+
+    let (_, cabecalho) = linhas
+        .next()
+        .ok_or_else(|| AppError::InvalidInput("Arquivo de CEPs vazio".to_string()))?;
+    let separador = detectar_separador(cabecalho);
+    let colunas: Vec<String> = dividir_linha(cabecalho, separador)
+        .iter()
+        .map(|c| normalizar_cabecalho(c))
+        .collect();
+    let posicao = |nomes: &[&str]| {
+        nomes
+            .iter()
+            .find_map(|nome| colunas.iter().position(|c| c == nome))
+    };
+
+    let col_ibge = posicao(&COLUNAS_IBGE);
+    let col_localidade = posicao(&COLUNAS_LOCALIDADE);
+    let col_uf = posicao(&COLUNAS_UF);
+    let col_cep = match posicao(&COLUNAS_CEP) {
+        Some(cep) if col_ibge.is_some() || (col_localidade.is_some() && col_uf.is_some()) => cep,
+        _ => {
+            return Err(AppError::InvalidInput(
+                "Cabeçalho do arquivo de CEPs deve ter a coluna CEP e a coluna IBGE ou as colunas Cidade e UF"
+                    .to_string(),
+            )
+            .into());
+        }
+    };
+    let col_logradouro = posicao(&COLUNAS_LOGRADOURO);
+    let col_complemento = posicao(&COLUNAS_COMPLEMENTO);
+    let col_bairro = posicao(&COLUNAS_BAIRRO);
+
+    let mut ceps = BTreeMap::new();
+    for (indice, linha) in linhas {
+        let numero = indice + 1;
+        let campos = dividir_linha(linha, separador);
+        let campo = |posicao: Option<usize>| {
+            posicao
+                .and_then(|p| campos.get(p))
+                .map(|c| c.trim())
+                .unwrap_or_default()
+        };
+        let erro = |mensagem: &str| AppError::InvalidInput(format!("Linha {}: {}", numero, mensagem));
+
+        let cep = normalizar_cep(campo(Some(col_cep))).ok_or_else(|| erro("CEP inválido"))?;
+        let codigo_ibge = match campo(col_ibge) {
+            "" => None,
+            codigo => Some(
+                codigo
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|c| (1_000_000..=9_999_999).contains(c))
+                    .ok_or_else(|| erro("código IBGE do município inválido"))?,
+            ),
+        };
+        let localidade = campo(col_localidade);
+        let uf = campo(col_uf).to_uppercase();
+        if codigo_ibge.is_none() && (localidade.is_empty() || uf.len() != 2) {
+            return Err(erro("informe o código IBGE ou a cidade e a UF").into());
+        }
+
+        ceps.insert(
+            cep.clone(),
+            DadosCep {
+                cep,
+                logradouro: campo(col_logradouro).to_string(),
+                complemento: campo(col_complemento).to_string(),
+                bairro: campo(col_bairro).to_string(),
+                localidade: localidade.to_string(),
+                uf,
+                codigo_ibge,
+            },
+        );
+    }
+
+    if ceps.is_empty() {
+        return Err(AppError::InvalidInput("Arquivo sem CEPs".to_string()).into());
+    }
+    Ok(ceps.into_values().collect())
+}
+
+/// Consulta de CEP fora da base local; o resultado fica em cache na tabela core_cep
+#[async_trait]
+pub trait ProvedorCep: Send + Sync {
+    /// Gravado na coluna origem das linhas em cache
+    fn nome(&self) -> &str;
+
+    /// Ok(None) quando o provedor responde que o CEP não existe
+    async fn buscar(&self, cep: &str) -> Result<Option<DadosCep>>;
+}
+
+static CLIENTE_HTTP: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .expect("cliente HTTP do provedor de CEP")
+});
+
+/// ViaCEP ou serviço com a mesma API (GET {url}/{cep}/json/)
+pub struct ViaCep {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct RespostaViaCep {
+    #[serde(default)]
+    logradouro: String,
+    #[serde(default)]
+    complemento: String,
+    #[serde(default)]
+    bairro: String,
+    #[serde(default)]
+    localidade: String,
+    #[serde(default)]
+    uf: String,
+    #[serde(default)]
+    ibge: String,
+    // {"erro": true} (ou "true" nas versões novas) para CEP inexistente
+    erro: Option<Value>,
+}
+
+#[async_trait]
+impl ProvedorCep for ViaCep {
+    fn nome(&self) -> &str {
+        "VIACEP"
+    }
+
+    async fn buscar(&self, cep: &str) -> Result<Option<DadosCep>> {
+        let resposta = CLIENTE_HTTP
+            .get(format!("{}/{}/json/", self.url.trim_end_matches('/'), cep))
+            .send()
+            .await?;
+        // a API responde 400 para formato inválido; o CEP já chega normalizado
+        let resposta: RespostaViaCep = resposta.error_for_status()?.json().await?;
+        if resposta
+            .erro
+            .is_some_and(|e| e != Value::Bool(false) && e != Value::String("false".to_string()))
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(DadosCep {
+            cep: cep.to_string(),
+            logradouro: resposta.logradouro,
+            complemento: resposta.complemento,
+            bairro: resposta.bairro,
+            localidade: resposta.localidade,
+            uf: resposta.uf,
+            codigo_ibge: resposta.ibge.parse().ok(),
+        }))
+    }
+}
+
+/// Provedor remoto escolhido em CEP_PROVEDOR; sem a variável a busca fica só na base local
+pub fn provedor_configurado() -> Option<Box<dyn ProvedorCep>> {
+    match env::var("CEP_PROVEDOR").ok()?.trim().to_lowercase().as_str() {
+        "viacep" => Some(Box::new(ViaCep {
+            url: env::var("CEP_PROVEDOR_URL").unwrap_or_else(|_| "https://viacep.com.br/ws".to_string()),
+        })),
+        "" => None,
+        outro => {
+            tracing::warn!("Provedor de CEP desconhecido em CEP_PROVEDOR: {}", outro);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalizar_cep() {
+        assert_eq!(normalizar_cep("77001-002").as_deref(), Some("77001002"));
+        assert_eq!(normalizar_cep(" 77.001-002 ").as_deref(), Some("77001002"));
+        assert_eq!(normalizar_cep("7700100"), None);
+        assert_eq!(normalizar_cep("77001-00a"), None);
+    }
+
+    #[test]
+    fn test_ler_base_cep() {
+        let csv = "CEP;Logradouro;Bairro;Cidade;UF;IBGE\n\
+                   77001-002;\"Quadra 104 Norte; Rua NE 5\";Plano Diretor Norte;Palmas;TO;1721000\n\
+                   77500000;;;Porto Nacional;to;\n";
+        let ceps = ler_base_cep(csv.as_bytes()).unwrap();
+        assert_eq!(ceps.len(), 2);
+        assert_eq!(ceps[0].logradouro, "Quadra 104 Norte; Rua NE 5");
+        assert_eq!(ceps[0].codigo_ibge, Some(1721000));
+        assert_eq!(ceps[1].uf, "TO");
+        assert_eq!(ceps[1].codigo_ibge, None);
+
+        assert!(ler_base_cep("CEP;IBGE\n7700100;1721000\n".as_bytes()).is_err());
+        assert!(ler_base_cep("CEP;Cidade\n77001002;Palmas\n".as_bytes()).is_err());
+        assert!(ler_base_cep("CEP;Cidade;UF\n77001002;;TO\n".as_bytes()).is_err());
+    }
+}
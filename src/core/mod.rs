@@ -2,5 +2,10 @@ mod schema;
 mod service;
 mod model;
 mod repository;
+mod cep;
+mod view;
+mod router;
 
+pub use model::PERM_BASE_IMPORTAR;
+pub use router::{router, router_autenticado};
 pub use service::UserService;
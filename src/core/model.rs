@@ -18,3 +18,46 @@ pub struct User {
     pub is_active: bool,
     pub date_joined: DateTime<Utc>,
 }
+
+/// Permissão de administração para importar as bases nacionais (CEP e municípios do IBGE)
+pub const PERM_BASE_IMPORTAR: &str = "base.importar";
+
+/// CEP da base local com o município do cadastro, quando o código IBGE bate.
+/// Os nomes seguem o JSON do ViaCEP, usado pelo componente de endereço
+#[derive(Debug, Serialize, FromRow)]
+pub struct EnderecoCep {
+    pub cep: String,
+    pub logradouro: String,
+    pub complemento: String,
+    pub bairro: String,
+    pub localidade: String,
+    pub uf: String,
+    #[serde(rename = "ibge")]
+    pub codigo_ibge: Option<i32>,
+    pub municipio_id: Option<i32>,
+    pub origem: String,
+    pub atualizado_em: DateTime<Utc>,
+}
+
+/// Município do cadastro localizado pelo código IBGE
+#[derive(Debug, Serialize, FromRow)]
+pub struct MunicipioIbge {
+    pub id: i32,
+    pub nome: String,
+    pub uf: String,
+}
+
+/// Quantidades da base local de CEPs, por origem
+#[derive(Debug, Default, Serialize)]
+pub struct ResumoBaseCep {
+    pub importados: i64,
+    pub em_cache: i64,
+    pub sem_municipio: i64,
+}
+
+/// Totais da importação da base de CEPs
+#[derive(Debug, Default, Serialize)]
+pub struct ResultadoImportacaoCep {
+    pub inseridos: i64,
+    pub atualizados: i64,
+}
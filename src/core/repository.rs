@@ -1,4 +1,4 @@
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 
 use async_trait::async_trait;
 
@@ -8,7 +8,8 @@ use uuid::Uuid;
 
 use crate::{
     core::{
-        model::User,
+        cep::{DadosCep, ORIGEM_IMPORTACAO},
+        model::{EnderecoCep, MunicipioIbge, ResumoBaseCep, User},
         schema::{CreateUser, UpdateUser},
     },
     repository::Repository,
@@ -133,3 +134,166 @@ impl Repository<User, i64> for UserRepository {
         Ok(())
     }
 }
+
+pub struct CepRepository;
+
+impl CepRepository {
+    pub async fn get_by_cep(&self, pool: &PgPool, cep: &str) -> Result<Option<EnderecoCep>> {
+        Ok(sqlx::query_as!(
+            EnderecoCep,
+            r#"
+            SELECT c.cep, c.logradouro, c.complemento, c.bairro,
+                COALESCE(m.nome, c.localidade) AS "localidade!",
+                COALESCE(m.sigla, c.uf) AS "uf!",
+                COALESCE(c.codigo_ibge, m.codigo_ibge) AS codigo_ibge,
+                m.id AS "municipio_id?", c.origem, c.atualizado_em
+            FROM core_cep c
+            LEFT JOIN LATERAL (
+                -- CEP sem código IBGE (município ainda não importado quando o CEP foi) sai pela cidade e UF
+                SELECT m.id, m.nome, m.codigo_ibge, u.sigla
+                FROM cadastro_municipio m
+                JOIN cadastro_uf u ON u.id = m.uf_id
+                WHERE m.codigo_ibge = c.codigo_ibge
+                   OR (c.codigo_ibge IS NULL AND LOWER(m.nome) = LOWER(c.localidade) AND UPPER(u.sigla) = c.uf)
+                LIMIT 1
+            ) m ON TRUE
+            WHERE c.cep = $1"#,
+            cep
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn municipio_por_ibge(&self, pool: &PgPool, codigo: i32) -> Result<Option<MunicipioIbge>> {
+        Ok(sqlx::query_as!(
+            MunicipioIbge,
+            r#"
+            SELECT m.id, m.nome, u.sigla AS uf
+            FROM cadastro_municipio m
+            JOIN cadastro_uf u ON u.id = m.uf_id
+            WHERE m.codigo_ibge = $1"#,
+            codigo
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Guarda a resposta do provedor remoto; nunca sobrescreve um CEP importado
+    pub async fn salvar_cache(&self, pool: &PgPool, dados: &DadosCep, origem: &str) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO core_cep(cep, logradouro, complemento, bairro, localidade, uf, codigo_ibge, origem, atualizado_em)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW())
+            ON CONFLICT (cep) DO UPDATE SET
+                logradouro = EXCLUDED.logradouro,
+                complemento = EXCLUDED.complemento,
+                bairro = EXCLUDED.bairro,
+                localidade = EXCLUDED.localidade,
+                uf = EXCLUDED.uf,
+                codigo_ibge = EXCLUDED.codigo_ibge,
+                origem = EXCLUDED.origem,
+                atualizado_em = NOW()
+            WHERE core_cep.origem <> $9"#,
+            dados.cep,
+            dados.logradouro,
+            dados.complemento,
+            dados.bairro,
+            dados.localidade,
+            dados.uf,
+            dados.codigo_ibge,
+            origem,
+            ORIGEM_IMPORTACAO
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Upsert de um lote da importação; sem código IBGE, o município é achado
+    /// no cadastro pela cidade e UF. Retorna (inseridos, atualizados)
+    pub async fn upsert_importacao(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        ceps: &[DadosCep],
+    ) -> Result<(i64, i64)> {
+        let mut cep = Vec::with_capacity(ceps.len());
+        let mut logradouro = Vec::with_capacity(ceps.len());
+        let mut complemento = Vec::with_capacity(ceps.len());
+        let mut bairro = Vec::with_capacity(ceps.len());
+        let mut localidade = Vec::with_capacity(ceps.len());
+        let mut uf = Vec::with_capacity(ceps.len());
+        let mut codigo_ibge = Vec::with_capacity(ceps.len());
+        for dados in ceps {
+            cep.push(dados.cep.clone());
+            logradouro.push(dados.logradouro.clone());
+            complemento.push(dados.complemento.clone());
+            bairro.push(dados.bairro.clone());
+            localidade.push(dados.localidade.clone());
+            uf.push(dados.uf.clone());
+            codigo_ibge.push(dados.codigo_ibge);
+        }
+
+        let linhas = sqlx::query_scalar!(
+            r#"
+            INSERT INTO core_cep(cep, logradouro, complemento, bairro, localidade, uf, codigo_ibge, origem, atualizado_em)
+            SELECT d.cep, d.logradouro, d.complemento, d.bairro, d.localidade, d.uf,
+                COALESCE(d.codigo_ibge, (
+                    SELECT m.codigo_ibge FROM cadastro_municipio m
+                    JOIN cadastro_uf u ON u.id = m.uf_id
+                    WHERE LOWER(m.nome) = LOWER(d.localidade) AND UPPER(u.sigla) = d.uf
+                    LIMIT 1
+                )),
+                $8, NOW()
+            FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[], $7::int[])
+                AS d(cep, logradouro, complemento, bairro, localidade, uf, codigo_ibge)
+            ON CONFLICT (cep) DO UPDATE SET
+                logradouro = EXCLUDED.logradouro,
+                complemento = EXCLUDED.complemento,
+                bairro = EXCLUDED.bairro,
+                localidade = EXCLUDED.localidade,
+                uf = EXCLUDED.uf,
+                codigo_ibge = EXCLUDED.codigo_ibge,
+                origem = EXCLUDED.origem,
+                atualizado_em = NOW()
+            RETURNING (xmax = 0) AS "inserido!""#,
+            &cep,
+            &logradouro,
+            &complemento,
+            &bairro,
+            &localidade,
+            &uf,
+            &codigo_ibge as &[Option<i32>],
+            ORIGEM_IMPORTACAO
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        let inseridos = linhas.iter().filter(|inserido| **inserido).count() as i64;
+        Ok((inseridos, linhas.len() as i64 - inseridos))
+    }
+
+    pub async fn resumo(&self, pool: &PgPool) -> Result<ResumoBaseCep> {
+        let resumo = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE c.origem = $1) AS "importados!",
+                COUNT(*) FILTER (WHERE c.origem <> $1) AS "em_cache!",
+                COUNT(*) FILTER (WHERE NOT EXISTS (
+                    SELECT 1 FROM cadastro_municipio m
+                    JOIN cadastro_uf u ON u.id = m.uf_id
+                    WHERE m.codigo_ibge = c.codigo_ibge
+                       OR (c.codigo_ibge IS NULL AND LOWER(m.nome) = LOWER(c.localidade) AND UPPER(u.sigla) = c.uf)
+                )) AS "sem_municipio!"
+            FROM core_cep c"#,
+            ORIGEM_IMPORTACAO
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(ResumoBaseCep {
+            importados: resumo.importados,
+            em_cache: resumo.em_cache,
+            sem_municipio: resumo.sem_municipio,
+        })
+    }
+}
@@ -0,0 +1,34 @@
+use axum::{
+    Router,
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+};
+
+use crate::{core::view, state::SharedState};
+
+pub fn router() -> Router<SharedState> {
+    Router::new().merge(cep_router())
+}
+
+/// Administração da base de CEPs e consulta de municípios (ficam atrás do middleware
+/// de autenticação)
+pub fn router_autenticado() -> Router<SharedState> {
+    Router::new()
+        .merge(base_cep_router())
+        .route("/cidades-por-ibge", get(view::cidade_por_ibge))
+}
+
+fn cep_router() -> Router<SharedState> {
+    Router::new().route("/buscar-cep", get(view::buscar_cep))
+}
+
+fn base_cep_router() -> Router<SharedState> {
+    Router::new()
+        .route("/cep", get(view::cep_page))
+        .route(
+            "/cep/importar",
+            post(view::importar_cep)
+                // a base nacional (cerca de 1 milhão de CEPs) passa de 100 MB em CSV
+                .layer(DefaultBodyLimit::max(200 * 1024 * 1024)),
+        )
+}
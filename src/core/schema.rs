@@ -206,6 +206,19 @@ pub struct RolePermissionViewSchema {
     pub name: String, //name  permissao
 }
 
+/*
+Parâmetros GET do componente de endereço (components/endereco_cep.html)
+*/
+#[derive(Deserialize)]
+pub struct BuscarCepParams {
+    pub cep: String,
+}
+
+#[derive(Deserialize)]
+pub struct CidadePorIbgeParams {
+    pub ibge_id: i32,
+}
+
 /*
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
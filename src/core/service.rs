@@ -1,7 +1,8 @@
 #[warn(unused_imports)]
 use crate::core::{
-    model::User,
-    repository::UserRepository,
+    cep::{self, ProvedorCep, ORIGEM_IMPORTACAO},
+    model::{EnderecoCep, MunicipioIbge, PERM_BASE_IMPORTAR, ResultadoImportacaoCep, ResumoBaseCep, User},
+    repository::{CepRepository, UserRepository},
     schema::{CreateUser, UpdateUser},
 };
 use anyhow::Result;
@@ -15,10 +16,13 @@ use otpauth::TOTP;
 use password_hash::rand_core::OsRng;
 use rand::Rng;
 use sqlx::PgPool;
+use tracing::warn;
 //use validator::Validate;
 
 use crate::{
     core::schema::{UpdateUserPassword},
+    error::AppError,
+    middlewares::CurrentUser,
     repository::{PaginatedResponse, Repository},
 };
 
@@ -88,15 +92,23 @@ impl UserService {
     } */
 
     /*
-    retorna todas as permissões de um usuário
+    retorna todas as permissões de um usuário (pelos perfis atribuídos)
     */
-    /* pub async fn get_user_permissions(db: &PgPool, user_id: i64) -> Vec<String> {
-        let list_permissao = match UserRolesService::get_user_permissions(&db, user_id).await {
-            Ok(permissions) => permissions,
-            Err(_) => return vec![],
-        };
-        list_permissao
-    } */
+    pub async fn get_user_permissions(db: &PgPool, user_id: i32) -> Vec<String> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT p.name
+            FROM core_user_roles ur
+            INNER JOIN core_role_permission rp ON rp.role_id = ur.role_id
+            INNER JOIN core_permission p ON p.id = rp.permission_id
+            WHERE ur.user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_all(db)
+        .await
+        .unwrap_or_default()
+    }
 
     pub async fn get_paginated(
         &self,
@@ -198,9 +210,9 @@ impl UserService {
     }
 
     pub async fn get_by_username(pool: &PgPool, username: &str) -> Result<User> {
-        let query = format!("SELECT * FROM users WHERE username = $1 LIMIT 1");
+        let query = "SELECT * FROM auth_user WHERE username = $1 LIMIT 1";
 
-        Ok(sqlx::query_as(&query)
+        Ok(sqlx::query_as(query)
             .bind(username)
             .fetch_one(pool)
             .await?)
@@ -253,3 +265,107 @@ impl UserService {
         }
     } */
 }
+
+/// CEPs gravados por transação na importação (limite de parâmetros do UNNEST fica folgado)
+const LOTE_IMPORTACAO_CEP: usize = 5_000;
+
+/// Dias que a resposta do provedor remoto vale antes de ser consultada de novo
+const VALIDADE_CACHE_CEP_DIAS: i64 = 90;
+
+pub struct CepService {
+    repo: CepRepository,
+    provedor: Option<Box<dyn ProvedorCep>>,
+    validade_cache: chrono::Duration,
+}
+
+impl CepService {
+    pub fn new() -> Self {
+        let dias = std::env::var("CEP_CACHE_DIAS")
+            .ok()
+            .and_then(|d| d.trim().parse().ok())
+            .unwrap_or(VALIDADE_CACHE_CEP_DIAS);
+        Self {
+            repo: CepRepository,
+            provedor: cep::provedor_configurado(),
+            validade_cache: chrono::Duration::days(dias),
+        }
+    }
+
+    /// Busca na base local; o provedor remoto (se configurado) só é consultado para
+    /// CEP que não está na base ou cujo cache venceu. Com o provedor fora do ar,
+    /// o cache vencido continua valendo
+    pub async fn buscar(&self, pool: &PgPool, cep: &str) -> Result<EnderecoCep> {
+        let cep = cep::normalizar_cep(cep)
+            .ok_or_else(|| AppError::InvalidInput("CEP deve ter 8 dígitos".to_string()))?;
+
+        let local = self.repo.get_by_cep(pool, &cep).await?;
+        let Some(provedor) = &self.provedor else {
+            return local.ok_or_else(|| AppError::NotFound.into());
+        };
+        let local = match local {
+            Some(endereco)
+                if endereco.origem == ORIGEM_IMPORTACAO
+                    || endereco.atualizado_em > Utc::now() - self.validade_cache =>
+            {
+                return Ok(endereco);
+            }
+            local => local,
+        };
+
+        match provedor.buscar(&cep).await {
+            Ok(Some(dados)) => {
+                self.repo.salvar_cache(pool, &dados, provedor.nome()).await?;
+                self.repo
+                    .get_by_cep(pool, &cep)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound.into())
+            }
+            Ok(None) => Err(AppError::NotFound.into()),
+            Err(err) => {
+                warn!("Provedor de CEP {} indisponível: {}", provedor.nome(), err);
+                local.ok_or_else(|| AppError::NotFound.into())
+            }
+        }
+    }
+
+    /// Nome do provedor remoto configurado, para a tela da base de CEPs
+    pub fn provedor(&self) -> Option<&str> {
+        self.provedor.as_ref().map(|p| p.nome())
+    }
+
+    pub async fn municipio_por_ibge(&self, pool: &PgPool, codigo: i32) -> Result<MunicipioIbge> {
+        self.repo
+            .municipio_por_ibge(pool, codigo)
+            .await?
+            .ok_or_else(|| AppError::NotFound.into())
+    }
+
+    pub async fn resumo(&self, pool: &PgPool) -> Result<ResumoBaseCep> {
+        self.repo.resumo(pool).await
+    }
+
+    /// Importa a base de CEPs (CSV) numa única transação: um erro em qualquer
+    /// linha não deixa a base pela metade
+    pub async fn importar(
+        &self,
+        pool: &PgPool,
+        usuario: &CurrentUser,
+        conteudo: &[u8],
+    ) -> Result<ResultadoImportacaoCep> {
+        if !usuario.has_permission(PERM_BASE_IMPORTAR) {
+            return Err(AppError::PermissionDenied.into());
+        }
+        let ceps = cep::ler_base_cep(conteudo)?;
+
+        let mut resultado = ResultadoImportacaoCep::default();
+        let mut tx = pool.begin().await?;
+        for lote in ceps.chunks(LOTE_IMPORTACAO_CEP) {
+            let (inseridos, atualizados) = self.repo.upsert_importacao(&mut tx, lote).await?;
+            resultado.inseridos += inseridos;
+            resultado.atualizados += atualizados;
+        }
+        tx.commit().await?;
+
+        Ok(resultado)
+    }
+}
@@ -0,0 +1,129 @@
+use axum::{
+    Extension, Json,
+    extract::{Multipart, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use axum_messages::Messages;
+use tracing::debug;
+
+use crate::core::model::{EnderecoCep, MunicipioIbge};
+use crate::core::schema::{BuscarCepParams, CidadePorIbgeParams};
+use crate::core::service::CepService;
+use crate::error;
+use crate::middlewares::CurrentUser;
+use crate::state::SharedState;
+
+const PATH: &str = "core";
+
+/*
+==========================================
+            CEP
+==========================================
+*/
+
+/// Situação da base local de CEPs, consulta avulsa e importação
+pub async fn cep_page(State(state): State<SharedState>, messages: Messages) -> impl IntoResponse {
+    let service = CepService::new();
+
+    // Coletar mensagens do axum_messages
+    let messages_vec: Vec<_> = messages
+        .clone()
+        .map(|m| {
+            serde_json::json!({
+                "level": m.level.to_string(),
+                "text": m.to_string()
+            })
+        })
+        .collect();
+
+    let resumo = match service.resumo(&state.db).await {
+        Ok(resumo) => resumo,
+        Err(err) => {
+            debug!("Erro ao carregar base de CEPs: {}", err);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let context = minijinja::context! {
+        resumo => resumo,
+        provedor => service.provedor(),
+        messages => messages_vec
+    };
+
+    match state.templates.get_template(&format!("{}/cep.html", PATH)) {
+        Ok(template) => match template.render(context) {
+            Ok(html) => Html(html).into_response(),
+            Err(err) => {
+                debug!("Erro ao renderizar template: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        },
+        Err(err) => {
+            debug!("Erro ao carregar template: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// GET /core/buscar-cep?cep=00000-000, no formato do ViaCEP mais o município do cadastro
+pub async fn buscar_cep(
+    State(state): State<SharedState>,
+    Query(params): Query<BuscarCepParams>,
+) -> Result<Json<EnderecoCep>, StatusCode> {
+    let service = CepService::new();
+    let res = service.buscar(&state.db, &params.cep).await.map_err(|err| {
+        debug!("error:{}", err);
+        error::status_code(&err)
+    })?;
+
+    Ok(Json(res))
+}
+
+pub async fn cidade_por_ibge(
+    State(state): State<SharedState>,
+    Query(params): Query<CidadePorIbgeParams>,
+) -> Result<Json<MunicipioIbge>, StatusCode> {
+    let service = CepService::new();
+    let res = service
+        .municipio_por_ibge(&state.db, params.ibge_id)
+        .await
+        .map_err(|err| {
+            debug!("error:{}", err);
+            error::status_code(&err)
+        })?;
+
+    Ok(Json(res))
+}
+
+/// Upload da base de CEPs (CSV), campo "arquivo"
+pub async fn importar_cep(
+    State(state): State<SharedState>,
+    Extension(current_user): Extension<CurrentUser>,
+    messages: Messages,
+    mut multipart: Multipart,
+) -> Response {
+    let service = CepService::new();
+
+    let resultado = async {
+        let mut arquivo = None;
+        while let Some(campo) = multipart.next_field().await? {
+            if campo.name() == Some("arquivo") {
+                arquivo = Some(campo.bytes().await?);
+            }
+        }
+        match arquivo {
+            Some(bytes) if !bytes.is_empty() => service.importar(&state.db, &current_user, &bytes).await,
+            _ => Err(anyhow::anyhow!("Selecione o arquivo de CEPs")),
+        }
+    }
+    .await;
+    match resultado {
+        Ok(r) => messages.success(format!(
+            "Importação concluída: {} CEPs novos e {} atualizados",
+            r.inseridos, r.atualizados
+        )),
+        Err(err) => messages.error(format!("Erro ao importar arquivo de CEPs: {}", err)),
+    };
+    Redirect::to(&format!("/{}/cep", PATH)).into_response()
+}
@@ -17,6 +17,7 @@ pub enum AppError {
     VerificationFailed,
     PermissionDenied,
     UserNotAuthenticated,
+    Conflict(String),
 }
 
 impl fmt::Display for AppError {
@@ -30,6 +31,7 @@ impl fmt::Display for AppError {
             AppError::VerificationFailed => write!(f, "OTP verification failed"),
             AppError::PermissionDenied => write!(f, "You are not allowed to perform this action"),
             AppError::UserNotAuthenticated => write!(f, "Authentication required. Please log in."),
+            AppError::Conflict(msg) => write!(f, "{}", msg),
         }
     }
 }
@@ -80,7 +82,26 @@ impl IntoResponse for AppError {
                 });
                 (StatusCode::UNAUTHORIZED, body).into_response()
             }
+            AppError::Conflict(msg) => {
+                let body = Json(ErrorResponse { error: msg });
+                (StatusCode::CONFLICT, body).into_response()
+            }
+        }
+    }
+}
+
+/// Status HTTP para erros dos services (anyhow); um AppError mantém o status dele
+pub fn status_code(err: &anyhow::Error) -> StatusCode {
+    match err.downcast_ref::<AppError>() {
+        Some(AppError::InvalidInput(_)) => StatusCode::BAD_REQUEST,
+        Some(AppError::NotFound) => StatusCode::NOT_FOUND,
+        Some(AppError::PermissionDenied) => StatusCode::FORBIDDEN,
+        Some(AppError::UserNotAuthenticated) => StatusCode::UNAUTHORIZED,
+        Some(AppError::Conflict(_)) => StatusCode::CONFLICT,
+        _ if matches!(err.downcast_ref::<sqlx::Error>(), Some(sqlx::Error::RowNotFound)) => {
+            StatusCode::NOT_FOUND
         }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
     }
 }
 
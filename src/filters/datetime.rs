@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use minijinja::{Error, ErrorKind, value::Value};
 use serde::de;
 use tracing::debug;
@@ -39,3 +39,19 @@ pub fn format_datetime_filter(value: Value) -> Result<Value, Error> {
         "Valor deve ser timestamp (número) ou string de data",
     ))
 }
+
+// Data sem hora (AAAA-MM-DD) no formato 07/08/2025; vazio quando não informada
+pub fn format_date_filter(value: Value) -> Result<Value, Error> {
+    let Some(s) = value.as_str() else {
+        return Ok(Value::from(""));
+    };
+
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|d| Value::from(d.format("%d/%m/%Y").to_string()))
+        .map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidOperation,
+                format!("Formato de data inválido: {}", e),
+            )
+        })
+}
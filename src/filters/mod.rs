@@ -4,7 +4,7 @@ mod number;
 mod string;
 
 pub use array::*;
-pub use datetime::format_datetime_filter;
+pub use datetime::{format_date_filter, format_datetime_filter};
 pub use number::{currency_float, format_number, format_number_int};
 pub use string::*;
 
@@ -17,12 +17,16 @@ use crate::filters::number::{currency_brl, format_decimal};
 pub fn register_filters(env: &mut Environment) {
     // Datetime filters
     env.add_filter("format_datetime", format_datetime_filter);
+    env.add_filter("format_date", format_date_filter);
 
     // String filters
     env.add_filter("uppercase", uppercase);
     env.add_filter("lowercase", lowercase);
     env.add_filter("truncate", truncate);
     env.add_filter("capitalize_first", capitalize_first);
+    env.add_filter("format_cpf", format_cpf);
+    env.add_filter("format_pis", format_pis);
+    env.add_filter("format_cnpj", format_cnpj);
 
     // Number filters
     env.add_filter("format_decimal", format_decimal);
@@ -1,5 +1,7 @@
 use minijinja::{Error, ErrorKind, Value};
 
+use crate::utils::validator::{formatar_cnpj, formatar_cpf, formatar_pis};
+
 pub fn uppercase(value: Value) -> Result<Value, Error> {
     let s = value
         .as_str()
@@ -38,3 +40,18 @@ pub fn capitalize_first(value: Value) -> Result<Value, Error> {
         }
     }
 }
+
+/// CPF gravado só com dígitos exibido com a máscara; vazio quando não informado
+pub fn format_cpf(value: Value) -> Result<Value, Error> {
+    Ok(Value::from(value.as_str().map(formatar_cpf).unwrap_or_default()))
+}
+
+/// PIS/PASEP gravado só com dígitos exibido com a máscara; vazio quando não informado
+pub fn format_pis(value: Value) -> Result<Value, Error> {
+    Ok(Value::from(value.as_str().map(formatar_pis).unwrap_or_default()))
+}
+
+/// CNPJ gravado sem máscara exibido com a máscara; vazio quando não informado
+pub fn format_cnpj(value: Value) -> Result<Value, Error> {
+    Ok(Value::from(value.as_str().map(formatar_cnpj).unwrap_or_default()))
+}
@@ -225,7 +225,7 @@ pub async fn api_get_board(
         )),
         Err(err) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::error(format!(
+            Json(ApiResponse::<()>::error(format!(
                 "Erro ao carregar quadro: {}",
                 err
             ))),
@@ -246,7 +246,7 @@ pub async fn api_create_board(
             .field_errors()
             .iter()
             .flat_map(|(field, field_errors)| {
-                field_errors.iter().map(|error| {
+                field_errors.iter().map(move |error| {
                     error
                         .message
                         .as_ref()
@@ -258,7 +258,7 @@ pub async fn api_create_board(
 
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ApiResponse::error(error_messages.join(", "))),
+            Json(ApiResponse::<()>::error(error_messages.join(", "))),
         ));
     }
 
@@ -275,7 +275,7 @@ pub async fn api_create_board(
         ))),
         Err(err) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::error(format!("Erro ao criar quadro: {}", err))),
+            Json(ApiResponse::<()>::error(format!("Erro ao criar quadro: {}", err))),
         )),
     }
 }
@@ -294,7 +294,7 @@ pub async fn api_create_column(
             .field_errors()
             .iter()
             .flat_map(|(field, field_errors)| {
-                field_errors.iter().map(|error| {
+                field_errors.iter().map(move |error| {
                     error
                         .message
                         .as_ref()
@@ -306,7 +306,7 @@ pub async fn api_create_column(
 
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ApiResponse::error(error_messages.join(", "))),
+            Json(ApiResponse::<()>::error(error_messages.join(", "))),
         ));
     }
 
@@ -323,7 +323,7 @@ pub async fn api_create_column(
         ))),
         Err(err) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::error(format!("Erro ao criar coluna: {}", err))),
+            Json(ApiResponse::<()>::error(format!("Erro ao criar coluna: {}", err))),
         )),
     }
 }
@@ -342,7 +342,7 @@ pub async fn api_create_card(
             .field_errors()
             .iter()
             .flat_map(|(field, field_errors)| {
-                field_errors.iter().map(|error| {
+                field_errors.iter().map(move |error| {
                     error
                         .message
                         .as_ref()
@@ -354,7 +354,7 @@ pub async fn api_create_card(
 
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ApiResponse::error(error_messages.join(", "))),
+            Json(ApiResponse::<()>::error(error_messages.join(", "))),
         ));
     }
 
@@ -374,7 +374,7 @@ pub async fn api_create_card(
         ))),
         Err(err) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::error(format!("Erro ao criar card: {}", err))),
+            Json(ApiResponse::<()>::error(format!("Erro ao criar card: {}", err))),
         )),
     }
 }
@@ -399,11 +399,11 @@ pub async fn api_move_card(
         ))),
         Ok(None) => Err((
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::error("Card não encontrado".to_string())),
+            Json(ApiResponse::<()>::error("Card não encontrado".to_string())),
         )),
         Err(err) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::error(format!("Erro ao mover card: {}", err))),
+            Json(ApiResponse::<()>::error(format!("Erro ao mover card: {}", err))),
         )),
     }
 }
@@ -27,7 +27,7 @@ use time::{Duration, OffsetDateTime, format_description::well_known::Rfc2822};
 use tokio;
 use tower_http::{cors::CorsLayer, services::ServeDir, trace::TraceLayer};
 use tower_sessions::{MemoryStore, SessionManagerLayer};
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 use tracing_subscriber::{fmt::format, layer::SubscriberExt, util::SubscriberInitExt};
 
 use dotenv::dotenv;
@@ -36,8 +36,11 @@ use sqlx::postgres::PgPoolOptions;
 use axum_messages::{Messages, MessagesManagerLayer};
 
 use crate::{
-    cadastro::router as router_cadastro,
-    core::UserService,
+    cadastro::{
+        LoteContraChequeService, router_autenticado as router_cadastro,
+        router_publico as router_cadastro_publico,
+    },
+    core::{UserService, router as router_core, router_autenticado as router_core_autenticado},
     filters::register_filters,
     kanban::router as router_kanban,
     middlewares::handle_forbidden,
@@ -55,18 +58,9 @@ async fn main() {
         .await
         .expect("Failed to connect to the database");
 
-    // Carrega os templates
-    // Crie o ambiente MiniJinja
-    let mut env = Environment::new();
-    env.set_loader(path_loader("templates"));
-    // Registre os filtros
-    register_filters(&mut env);
-
-    let templates = Arc::new(env);
-
     let state = Arc::new(AppState {
         db: Arc::new(db_pool),
-        templates,
+        templates: Arc::new(carregar_templates()),
     });
 
     // Initialize tracing
@@ -77,27 +71,73 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    // Lotes de contra-cheque em andamento não terminam depois de um reinício
+    if let Err(err) = LoteContraChequeService::new()
+        .interromper_pendentes(&state.db)
+        .await
+    {
+        error!("Erro ao encerrar lotes de contra-cheque pendentes: {}", err);
+    }
+
+    // Os arquivos dos lotes ficam em disco só durante a validade; confere a cada hora
+    let db = state.db.clone();
+    tokio::spawn(async move {
+        let service = LoteContraChequeService::new();
+        let mut intervalo = tokio::time::interval(std::time::Duration::from_secs(60 * 60));
+        loop {
+            intervalo.tick().await;
+            if let Err(err) = service.limpar_expirados(&db).await {
+                error!("Erro ao apagar arquivos de lotes de contra-cheque vencidos: {}", err);
+            }
+        }
+    });
+
     let listener = tokio::net::TcpListener::bind("0.0.0.0:2000").await.unwrap();
 
+    info!("Starting server on http://0.0.0.0:2000");
+    debug!("Server running");
+    //println!("Server running on http://0.0.0.0:2000");
+    axum::serve(listener, app(state)).await.unwrap();
+}
+
+fn carregar_templates() -> Environment<'static> {
+    // Carrega os templates
+    // Crie o ambiente MiniJinja
+    let mut env = Environment::new();
+    env.set_loader(path_loader("templates"));
+    // Registre os filtros
+    register_filters(&mut env);
+    env
+}
+
+/// Rotas da aplicação; as da folha, da competência e dos cadastros passam pelo
+/// middleware de autenticação
+fn app(state: SharedState) -> Router {
     let server_dir = ServeDir::new("static");
 
     let session_store = MemoryStore::default();
     let session_layer = SessionManagerLayer::new(session_store).with_secure(false);
 
+    let rotas_cadastro = router_cadastro().layer(middleware::from_fn_with_state(
+        state.clone(),
+        middlewares::autenticar,
+    ));
+    let rotas_core = router_core().merge(router_core_autenticado().layer(
+        middleware::from_fn_with_state(state.clone(), middlewares::autenticar),
+    ));
+
     let rotas_privadas = Router::new()
         .route("/home", get(index))
         .route("/logout", get(logout))
-        .nest("/cadastro", router_cadastro())
+        .nest("/cadastro", rotas_cadastro)
+        .nest("/core", rotas_core)
         .nest("/kanban", router_kanban());
-    /* .layer(middleware::from_fn_with_state(
-        state.clone(),
-        middlewares::autenticar,
-    )); */
 
-    let app = Router::new()
+    Router::new()
         .route("/", get(set_messages_handler))
         .route("/read-messages", get(read_messages_handler))
         .route("/login", get(get_login).post(login))
+        .merge(router_cadastro_publico())
         .nest_service("/static", server_dir)
         .layer(TraceLayer::new_for_http())
         .layer(middleware::from_fn(handle_forbidden)) // Middleware para 403
@@ -105,12 +145,7 @@ async fn main() {
         .fallback(page_not_found_handler)
         .layer(MessagesManagerLayer)
         .layer(session_layer)
-        .with_state(state.clone());
-
-    info!("Starting server on http://0.0.0.0:2000");
-    debug!("Server running");
-    //println!("Server running on http://0.0.0.0:2000");
-    axum::serve(listener, app).await.unwrap();
+        .with_state(state)
 }
 
 async fn set_messages_handler(messages: Messages) -> impl IntoResponse {
@@ -327,3 +362,382 @@ async fn logout() -> impl IntoResponse {
 
     response
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::{Body, to_bytes},
+        http::{
+            Method, Request,
+            header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, LOCATION, WWW_AUTHENTICATE},
+        },
+        response::Response,
+    };
+    use tower::ServiceExt;
+
+    /// Sem banco: o que é barrado pelo middleware não chega a consultá-lo
+    fn app_teste() -> Router {
+        let db = PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_secs(1))
+            .connect_lazy("postgres://folhaweb@127.0.0.1:1/folhaweb")
+            .unwrap();
+
+        app(Arc::new(AppState {
+            db: Arc::new(db),
+            templates: Arc::new(carregar_templates()),
+        }))
+    }
+
+    async fn enviar(request: Request<Body>) -> Response {
+        app_teste().oneshot(request).await.unwrap()
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    async fn corpo(response: Response) -> String {
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    /// Resposta do middleware `autenticar`, e não do handler da rota
+    async fn barrada(response: Response) -> bool {
+        let login = response
+            .headers()
+            .get(LOCATION)
+            .is_some_and(|location| location == "/login");
+        let status = response.status();
+
+        login || (status == StatusCode::UNAUTHORIZED && corpo(response).await == "Token ausente")
+    }
+
+    #[tokio::test]
+    async fn test_rota_privada_sem_token() {
+        let response = enviar(get("/cadastro/folha-api/1")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(corpo(response).await, "Token ausente");
+
+        let response = enviar(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri("/cadastro/folha-api/1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_rota_privada_com_token_invalido() {
+        let response = enviar(
+            Request::builder()
+                .uri("/cadastro/folha-api/1")
+                .header(AUTHORIZATION, "Bearer invalido")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(corpo(response).await, "Token inválido");
+    }
+
+    #[tokio::test]
+    async fn test_navegacao_sem_token_vai_para_login() {
+        let response = enviar(
+            Request::builder()
+                .uri("/cadastro/competencia")
+                .header(ACCEPT, "text/html,application/xhtml+xml")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(response.headers()[LOCATION], "/login");
+    }
+
+    #[tokio::test]
+    async fn test_cadastros_sensiveis_exigem_autenticacao() {
+        let requests = [
+            get("/cadastro/servidor"),
+            get("/cadastro/servidor-form/1"),
+            get("/cadastro/servidor-api"),
+            get("/cadastro/servidor-api/1"),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/servidor/1/afastamento")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("data_inicio=2025-01-01&motivo=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/afastamento/1/excluir")
+                .body(Body::empty())
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/orgao-form")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("nome=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/orgao-form/1")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("nome=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/orgao/1/logo")
+                .header(CONTENT_TYPE, "multipart/form-data; boundary=x")
+                .body(Body::from("--x--\r\n"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/orgao/1/remover-logo")
+                .body(Body::empty())
+                .unwrap(),
+            Request::builder()
+                .method(Method::DELETE)
+                .uri("/cadastro/orgao/1")
+                .body(Body::empty())
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/rubrica-form")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("codigo=1&descricao=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/rubrica-form/1")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("codigo=1&descricao=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::DELETE)
+                .uri("/cadastro/rubrica/1")
+                .body(Body::empty())
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/cargo-form")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("nome=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/cargo-form/1")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("nome=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::DELETE)
+                .uri("/cadastro/cargo/1")
+                .body(Body::empty())
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/setor-form")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("nome=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/setor-form/1")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("nome=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::DELETE)
+                .uri("/cadastro/setor/1")
+                .body(Body::empty())
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/departamento-form")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("nome=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/departamento-form/1")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("nome=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::DELETE)
+                .uri("/cadastro/departamento/1")
+                .body(Body::empty())
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/tipo-vinculo-form")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("nome=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/tipo-vinculo-form/1")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("nome=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::DELETE)
+                .uri("/cadastro/tipo-vinculo/1")
+                .body(Body::empty())
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/uf-form")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("sigla=TO&nome=Tocantins&codigo_ibge=17"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::DELETE)
+                .uri("/cadastro/uf/1")
+                .body(Body::empty())
+                .unwrap(),
+            get("/core/cep"),
+            get("/core/cidades-por-ibge?ibge_id=1721000"),
+            get("/cadastro/municipio"),
+            get("/cadastro/municipio-api"),
+            get("/cadastro/municipio-api/1"),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/municipio-form")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("nome=x"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::DELETE)
+                .uri("/cadastro/municipio/1")
+                .body(Body::empty())
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/cadastro/municipio/importar-ibge")
+                .header(CONTENT_TYPE, "multipart/form-data; boundary=x")
+                .body(Body::from("--x--\r\n"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/core/cep/importar")
+                .header(CONTENT_TYPE, "multipart/form-data; boundary=x")
+                .body(Body::from("--x--\r\n"))
+                .unwrap(),
+        ];
+        for request in requests {
+            let uri = request.uri().to_string();
+            assert!(barrada(enviar(request).await).await, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_autenticacao_contra_cheque_publica() {
+        let response = enviar(get("/autenticar-contra-cheque")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = enviar(get("/autenticar-contra-cheque/ABCD-1234")).await;
+        assert!(!barrada(response).await);
+    }
+
+    #[tokio::test]
+    async fn test_api_consignacao_publica() {
+        // Sem credencial da consignatária o próprio handler responde, com o desafio Bearer
+        let requests = [
+            get("/api/consignacao/margem?cpf=12345678909"),
+            Request::builder()
+                .method(Method::POST)
+                .uri("/api/consignacao/reserva")
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from("{}"))
+                .unwrap(),
+            Request::builder()
+                .method(Method::DELETE)
+                .uri("/api/consignacao/reserva/ABCD1234")
+                .body(Body::empty())
+                .unwrap(),
+        ];
+
+        for request in requests {
+            let uri = request.uri().to_string();
+            let response = enviar(request).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+            assert_eq!(response.headers()[WWW_AUTHENTICATE], "Bearer", "{}", uri);
+            assert!(!barrada(response).await, "{}", uri);
+        }
+    }
+
+    /// Com o banco do DATABASE_URL: o usuário logado chega ao handler e a UF é gravada
+    /// com o código IBGE. O usuário e a UF criados são excluídos no fim
+    #[tokio::test]
+    async fn test_salvar_uf_com_codigo_ibge() {
+        dotenv().ok();
+        let db = sqlx::PgPool::connect(&env::var("DATABASE_URL").expect("DATABASE_URL must be set"))
+            .await
+            .unwrap();
+        let app = app(Arc::new(AppState {
+            db: Arc::new(db.clone()),
+            templates: Arc::new(carregar_templates()),
+        }));
+
+        let usuario = "teste_salvar_uf";
+        sqlx::query(
+            "INSERT INTO auth_user (id, password, last_login, is_superuser, username, first_name,
+                last_name, email, is_staff, is_active, date_joined)
+            SELECT COALESCE(MAX(id), 0) + 1, '', now(), true, $1, '', '', '', false, true, now()
+            FROM auth_user
+            ON CONFLICT (username) DO NOTHING",
+        )
+        .bind(usuario)
+        .execute(&db)
+        .await
+        .unwrap();
+        // código de UF válido que nenhuma UF da base usa
+        let codigo: i32 = sqlx::query_scalar(
+            "SELECT c FROM unnest(ARRAY[11, 12, 13, 14, 15, 16, 17, 21, 22, 23, 24, 25, 26, 27,
+                28, 29, 31, 32, 33, 35, 41, 42, 43, 50, 51, 52, 53]) c
+            WHERE c NOT IN (SELECT codigo_ibge FROM cadastro_uf WHERE codigo_ibge IS NOT NULL)
+            LIMIT 1",
+        )
+        .fetch_one(&db)
+        .await
+        .expect("todos os códigos IBGE de UF já estão em uso na base");
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/cadastro/uf-form")
+                    .header(AUTHORIZATION, format!("Bearer {}", middlewares::gerar_token(usuario)))
+                    .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from(format!("sigla=ZZ&nome=UF+de+teste&codigo_ibge={}", codigo)))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let location = response.headers().get(LOCATION).map(|l| l.to_str().unwrap().to_string());
+        let gravada: Option<(i32, Option<i32>)> = sqlx::query_as(
+            "SELECT id, codigo_ibge FROM cadastro_uf WHERE sigla = 'ZZ' AND nome = 'UF de teste'",
+        )
+        .fetch_optional(&db)
+        .await
+        .unwrap();
+
+        if let Some((id, _)) = gravada {
+            sqlx::query("DELETE FROM cadastro_uf WHERE id = $1").bind(id).execute(&db).await.unwrap();
+        }
+        sqlx::query("DELETE FROM auth_user WHERE username = $1")
+            .bind(usuario)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let (id, codigo_gravado) = gravada.expect("UF não gravada");
+        assert_eq!(codigo_gravado, Some(codigo));
+        assert_eq!(location, Some(format!("/cadastro/uf-form/{}", id)));
+    }
+}
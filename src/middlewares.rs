@@ -1,4 +1,5 @@
 ///use crate::permissao::{User, UserService};
+use crate::core::UserService;
 use crate::state::SharedState;
 use axum::response::Response as ResponseExt;
 use axum::{
@@ -6,7 +7,7 @@ use axum::{
     extract::State,
     http::{
         Request, Response, StatusCode,
-        header::{AUTHORIZATION, COOKIE},
+        header::{ACCEPT, AUTHORIZATION, COOKIE},
     },
     middleware::Next,
     response::Html,
@@ -22,9 +23,38 @@ static SECRET: &[u8] = b"chave_secreta_super_segura";
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CurrentUser {
     //pub current_user: User,
+    pub id: i32,
+    pub username: String,
+    pub is_superuser: bool,
     pub permissions: Vec<String>,
 }
 
+impl CurrentUser {
+    /// Superusuário tem todas as permissões
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.is_superuser
+            || self
+                .permissions
+                .iter()
+                .any(|perm| perm.eq_ignore_ascii_case(permission))
+    }
+}
+
+/// Navegação do browser volta para o login; chamadas de API recebem 401
+fn nao_autenticado(req: &Request<Body>, mensagem: &'static str) -> Response<Body> {
+    let navegacao = req
+        .headers()
+        .get(ACCEPT)
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+
+    if navegacao {
+        Redirect::to("/login").into_response()
+    } else {
+        (StatusCode::UNAUTHORIZED, mensagem).into_response()
+    }
+}
+
 // Middleware de autenticação JWT
 pub async fn autenticar(
     State(state): State<SharedState>,
@@ -78,34 +108,34 @@ pub async fn autenticar(
                     let mut req = req;
                     req.extensions_mut().insert(data.claims.clone());
 
-                   /*  //busca usuario:
-                    let mut user = UserService::get_by_username(&*state.db, &data.claims.sub)
-                        .await
-                        .map_err(|_| Redirect::to("/login").into_response());
+                    //busca usuario:
+                    let user = match UserService::get_by_username(&state.db, &data.claims.sub).await {
+                        Ok(user) if user.is_active => user,
+                        _ => return nao_autenticado(&req, "Usuário inativo"),
+                    };
 
-                    let user_id = user.as_mut().unwrap().id;
                     // Busca permissões do usuário
-                    let permissions = UserService::get_user_permissions(&*state.db, user_id).await; */
+                    let permissions = UserService::get_user_permissions(&state.db, user.id).await;
 
                     // Adiciona o usuário logado às extensões
-                    /* req.extensions_mut().insert(CurrentUser {
-                        current_user: user.unwrap(),
+                    req.extensions_mut().insert(CurrentUser {
+                        id: user.id,
+                        username: user.username,
+                        is_superuser: user.is_superuser,
                         permissions,
-                    }); */
+                    });
 
                     next.run(req).await
                 }
                 Err(e) => {
                     debug!("Erro ao decodificar token: {}", e);
-                    //(StatusCode::UNAUTHORIZED, "Token inválido").into_response()
-                    Redirect::to("/login").into_response()
+                    nao_autenticado(&req, "Token inválido")
                 }
             }
         }
         None => {
             debug!("Token não encontrado no header Authorization nem no cookie");
-            //(StatusCode::UNAUTHORIZED, "Token ausente").into_response()
-            Redirect::to("/login").into_response()
+            nao_autenticado(&req, "Token ausente")
         }
     }
 }
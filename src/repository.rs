@@ -43,6 +43,127 @@ pub struct IdParams {
     pub id: Option<i64>,
 }
 
+/// Monta o WHERE da busca: o termo é procurado em qualquer um dos campos pesquisáveis
+/// (OR) e precisa casar com todos os `filter_fields_and`. Cada campo recebe o seu
+/// parâmetro; nos campos com `=` a coluna é comparada como texto
+fn clausula_busca(
+    search_fields: &[(&str, &str)],
+    filter_fields_and: Option<&[(&str, &str)]>,
+    term: &str,
+) -> (String, Vec<String>) {
+    let mut params = Vec::new();
+    let mut condicao = |field: &str, op: &str| {
+        params.push(if op == "ILIKE" {
+            format!("%{}%", term)
+        } else {
+            term.to_string()
+        });
+        let field = if op == "=" {
+            format!("{}::text", field)
+        } else {
+            field.to_string()
+        };
+        format!("{} {} ${}", field, op, params.len())
+    };
+
+    let mut parts = Vec::new();
+    let or_parts: Vec<String> = search_fields
+        .iter()
+        .map(|(field, op)| condicao(field, op))
+        .collect();
+    if !or_parts.is_empty() {
+        parts.push(format!("({})", or_parts.join(" OR ")));
+    }
+    for (field, op) in filter_fields_and.unwrap_or_default() {
+        parts.push(condicao(field, op));
+    }
+
+    if parts.is_empty() {
+        (String::new(), params)
+    } else {
+        (format!("WHERE {}", parts.join(" AND ")), params)
+    }
+}
+
+/// Acrescenta ao WHERE da busca os filtros de igualdade (coluna, valor), sempre com AND.
+/// A coluna é comparada como texto, como nos campos `=` da busca
+fn clausula_filtros(
+    where_clause: String,
+    mut params: Vec<String>,
+    filtros: &[(&str, String)],
+) -> (String, Vec<String>) {
+    let mut parts = Vec::new();
+    for (campo, valor) in filtros {
+        params.push(valor.clone());
+        parts.push(format!("{}::text = ${}", campo, params.len()));
+    }
+
+    if parts.is_empty() {
+        (where_clause, params)
+    } else if where_clause.is_empty() {
+        (format!("WHERE {}", parts.join(" AND ")), params)
+    } else {
+        (format!("{} AND {}", where_clause, parts.join(" AND ")), params)
+    }
+}
+
+/// Executa a contagem e a página da consulta (sem LIMIT/OFFSET, que são acrescentados
+/// depois dos parâmetros da busca)
+async fn paginar<T>(
+    pool: &PgPool,
+    count_query: &str,
+    data_query: &str,
+    params: &[String],
+    page: i32,
+    page_size: i32,
+) -> Result<PaginatedResponse<T>>
+where
+    T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+{
+    let page = page.max(1);
+    let page_size = page_size.min(100);
+    let offset = (page - 1) * page_size;
+
+    // === COUNT ===
+    let mut count = sqlx::query_as(count_query);
+    for param in params {
+        count = count.bind(param);
+    }
+    let total: (i64,) = count.fetch_one(pool).await?;
+
+    // === DATA ===
+    let data_query = format!(
+        "{} LIMIT ${} OFFSET ${}",
+        data_query,
+        params.len() + 1,
+        params.len() + 2
+    );
+
+    let mut query = sqlx::query_as::<_, T>(&data_query);
+    for param in params {
+        query = query.bind(param);
+    }
+    let data = query
+        .bind(page_size as i64)
+        .bind(offset as i64)
+        .fetch_all(pool)
+        .await?;
+
+    let total_pages: i32 = if total.0 == 0 {
+        1
+    } else {
+        ((total.0 as f32) / (page_size as f32)).ceil() as i32
+    };
+
+    Ok(PaginatedResponse {
+        data,
+        total_records: total.0,
+        page,
+        page_size,
+        total_pages,
+    })
+}
+
 #[async_trait]
 pub trait Repository<T, ID>
 where
@@ -95,102 +216,50 @@ where
     where
         T: for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Send + Unpin,
     {
-        let page = page.max(1);
-        let page_size = page_size.min(100);
-        let offset = (page - 1) * page_size;
-
-        // Construir WHERE clause com parâmetros seguros
-        let (where_clause, params) = if let Some(term) = find {
-            let search_fields = self.searchable_fields();
-            let mut field_parts = Vec::new();
-            let mut params = Vec::new();
-            for (i, (field, op)) in search_fields.iter().enumerate() {
-                let placeholder = format!("${}", params.len() + 1);
-                field_parts.push(format!("{} {} {}", field, op, placeholder));
-
-                if *op == "ILIKE" {
-                    params.push(format!("%{}%", term));
-                } else {
-                    params.push(term.to_string());
-                }
-            }
-
-            // --- AND fields ---
-            if let Some(and_fields) = filter_fields_and {
-                for (field, op) in and_fields {
-                    let placeholder = format!("${}", params.len() + 1);
-                    field_parts.push(format!("{} {} {}", field, op, placeholder));
-
-                    if *op == "ILIKE" {
-                        params.push(format!("%{}%", term));
-                    } else {
-                        params.push(term.to_string());
-                    }
-                }
-            }
-
-            // Junta OR e AND corretamente
-            let where_str = format!("WHERE ({})", field_parts.join(" AND "));
-            (where_str, params)
-        } else {
-            (String::new(), vec![])
+        let (where_clause, params) = match find {
+            Some(term) => clausula_busca(self.searchable_fields(), filter_fields_and, term),
+            None => (String::new(), vec![]),
         };
 
-        // === COUNT ===
-        let count_query = format!(
-            "SELECT COUNT(*) FROM {} {}",
+        let count_query = format!("SELECT COUNT(*) FROM {} {}", self.from_clause(), where_clause);
+        let data_query = format!(
+            "SELECT {} FROM {} {} ORDER BY {} DESC",
+            self.select_clause(),
             self.from_clause(),
-            where_clause
+            where_clause,
+            self.id_column()
         );
+        paginar(pool, &count_query, &data_query, &params, page, page_size).await
+    }
 
-        let total: (i64,) = if params.is_empty() {
-            sqlx::query_as(&count_query).fetch_one(pool).await?
-        } else {
-            sqlx::query_as(&count_query)
-                .bind(&params[0])
-                .fetch_one(pool)
-                .await?
+    /// Igual ao `get_paginated`, mas restringe o resultado pelos filtros de igualdade
+    /// (ex.: municípios de uma UF) e ordena por `order_by_column`
+    async fn get_paginated_filtrado(
+        &self,
+        pool: &PgPool,
+        find: Option<&str>,
+        page: i32,
+        page_size: i32,
+        filtros: &[(&str, String)],
+    ) -> Result<PaginatedResponse<T>>
+    where
+        T: for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Send + Unpin,
+    {
+        let (where_clause, params) = match find {
+            Some(term) => clausula_busca(self.searchable_fields(), None, term),
+            None => (String::new(), vec![]),
         };
+        let (where_clause, params) = clausula_filtros(where_clause, params, filtros);
 
-        // === DATA ===
+        let count_query = format!("SELECT COUNT(*) FROM {} {}", self.from_clause(), where_clause);
         let data_query = format!(
-            "SELECT {} FROM {} {} ORDER BY {} DESC LIMIT ${} OFFSET ${}",
+            "SELECT {} FROM {} {} ORDER BY {}",
             self.select_clause(),
             self.from_clause(),
             where_clause,
-            self.id_column(),
-            if params.is_empty() { 1 } else { 2 },
-            if params.is_empty() { 2 } else { 3 }
+            self.order_by_column()
         );
-
-        let data = if params.is_empty() {
-            sqlx::query_as::<_, T>(&data_query)
-                .bind(page_size as i64)
-                .bind(offset as i64)
-                .fetch_all(pool)
-                .await?
-        } else {
-            sqlx::query_as::<_, T>(&data_query)
-                .bind(&params[0])
-                .bind(page_size as i64)
-                .bind(offset as i64)
-                .fetch_all(pool)
-                .await?
-        };
-
-        let total_pages: i32 = if total.0 == 0 {
-            1
-        } else {
-            ((total.0 as f32) / (page_size as f32)).ceil() as i32
-        };
-
-        Ok(PaginatedResponse {
-            data,
-            total_records: total.0,
-            page,
-            page_size,
-            total_pages,
-        })
+        paginar(pool, &count_query, &data_query, &params, page, page_size).await
     }
 
     async fn get_by_id(&self, pool: &PgPool, id: ID) -> anyhow::Result<T> {
@@ -224,101 +293,50 @@ where
     where
         T: for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Send + Unpin,
     {
-        let page = page.max(1);
-        let page_size = page_size.min(100);
-        let offset = (page - 1) * page_size;
-
-        // Construir WHERE clause com parâmetros seguros
-        let (where_clause, params) = if let Some(term) = find {
-            let search_fields = self.searchable_fields();
-            let mut field_parts = Vec::new();
-            let mut params = Vec::new();
-            for (i, (field, op)) in search_fields.iter().enumerate() {
-                let placeholder = format!("${}", params.len() + 1);
-                field_parts.push(format!("{} {} {}", field, op, placeholder));
-
-                if *op == "ILIKE" {
-                    params.push(format!("%{}%", term));
-                } else {
-                    params.push(term.to_string());
-                }
-            }
-
-            // --- AND fields ---
-            if let Some(and_fields) = filter_fields_and {
-                for (field, op) in and_fields {
-                    let placeholder = format!("${}", params.len() + 1);
-                    field_parts.push(format!("{} {} {}", field, op, placeholder));
-
-                    if *op == "ILIKE" {
-                        params.push(format!("%{}%", term));
-                    } else {
-                        params.push(term.to_string());
-                    }
-                }
-            }
-
-            // Junta OR e AND corretamente
-            let where_str = format!("WHERE ({})", field_parts.join(" AND "));
-            (where_str, params)
-        } else {
-            (String::new(), vec![])
-        };
-
-        // === COUNT ===
-        let count_query = format!(
-            "SELECT COUNT(*) FROM {} {}",
-            self.from_clause(),
-            where_clause
-        );
-
-        let total: (i64,) = if params.is_empty() {
-            sqlx::query_as(&count_query).fetch_one(pool).await?
-        } else {
-            sqlx::query_as(&count_query)
-                .bind(&params[0])
-                .fetch_one(pool)
-                .await?
+        let (where_clause, params) = match find {
+            Some(term) => clausula_busca(self.searchable_fields(), filter_fields_and, term),
+            None => (String::new(), vec![]),
         };
 
-        // === DATA ===
+        let count_query = format!("SELECT COUNT(*) FROM {} {}", self.from_clause(), where_clause);
         let data_query = format!(
-            "SELECT {} FROM {} {} ORDER BY {} LIMIT ${} OFFSET ${}",
+            "SELECT {} FROM {} {} ORDER BY {}",
             self.select_clause_view(),
             self.from_clause_view(),
             where_clause,
-            self.order_by_column(),
-            if params.is_empty() { 1 } else { 2 },
-            if params.is_empty() { 2 } else { 3 }
+            self.order_by_column()
         );
+        paginar(pool, &count_query, &data_query, &params, page, page_size).await
+    }
+}
 
-        let data = if params.is_empty() {
-            sqlx::query_as::<_, T>(&data_query)
-                .bind(page_size as i64)
-                .bind(offset as i64)
-                .fetch_all(pool)
-                .await?
-        } else {
-            sqlx::query_as::<_, T>(&data_query)
-                .bind(&params[0])
-                .bind(page_size as i64)
-                .bind(offset as i64)
-                .fetch_all(pool)
-                .await?
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let total_pages: i32 = if total.0 == 0 {
-            1
-        } else {
-            ((total.0 as f32) / (page_size as f32)).ceil() as i32
-        };
+    #[test]
+    fn test_clausula_busca() {
+        let (sql, params) = clausula_busca(
+            &[("f.ano", "="), ("serv.nome", "ILIKE")],
+            Some(&[("f.orgao_id", "=")]),
+            "2025",
+        );
+        assert_eq!(
+            sql,
+            "WHERE (f.ano::text = $1 OR serv.nome ILIKE $2) AND f.orgao_id::text = $3"
+        );
+        assert_eq!(params, vec!["2025", "%2025%", "2025"]);
+    }
+
+    #[test]
+    fn test_clausula_filtros() {
+        let (sql, params) = clausula_busca(&[("m.nome", "ILIKE")], None, "rio");
+        let (sql, params) = clausula_filtros(sql, params, &[("m.uf_id", "12".to_string())]);
+        assert_eq!(sql, "WHERE (m.nome ILIKE $1) AND m.uf_id::text = $2");
+        assert_eq!(params, vec!["%rio%", "12"]);
 
-        Ok(PaginatedResponse {
-            data,
-            total_records: total.0,
-            page,
-            page_size,
-            total_pages,
-        })
+        let (sql, params) = clausula_filtros(String::new(), vec![], &[("m.uf_id", "12".to_string())]);
+        assert_eq!(sql, "WHERE m.uf_id::text = $1");
+        assert_eq!(params, vec!["12"]);
     }
 }
@@ -0,0 +1,59 @@
+//! Leitura dos CSVs de importação (planilhas salvas pelo Excel ou exportadas de sistemas)
+
+/// Arquivos chegam em UTF-8 ou em Latin-1 (planilha salva pelo Excel); remove o BOM
+pub fn decodificar(conteudo: &[u8]) -> String {
+    let texto = match std::str::from_utf8(conteudo) {
+        Ok(texto) => texto.to_string(),
+        Err(_) => conteudo.iter().map(|b| *b as char).collect(),
+    };
+    texto.trim_start_matches('\u{feff}').to_string()
+}
+
+pub fn detectar_separador(cabecalho: &str) -> char {
+    [';', '\t', ',']
+        .into_iter()
+        .max_by_key(|sep| cabecalho.matches(*sep).count())
+        .unwrap_or(';')
+}
+
+/// Divide a linha no separador, respeitando campos entre aspas ("" = aspas literal)
+pub fn dividir_linha(linha: &str, separador: char) -> Vec<String> {
+    let mut campos = Vec::new();
+    let mut atual = String::new();
+    let mut entre_aspas = false;
+    let mut chars = linha.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if entre_aspas && chars.peek() == Some(&'"') => {
+                atual.push('"');
+                chars.next();
+            }
+            '"' => entre_aspas = !entre_aspas,
+            c if c == separador && !entre_aspas => campos.push(std::mem::take(&mut atual)),
+            c => atual.push(c),
+        }
+    }
+    campos.push(atual);
+    campos
+}
+
+/// "Código Município Completo" -> "codigo_municipio_completo"
+pub fn normalizar_cabecalho(cabecalho: &str) -> String {
+    let mut normalizado = String::new();
+    for c in cabecalho.trim().to_lowercase().chars() {
+        let c = match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+            'é' | 'ê' | 'è' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            c if c.is_ascii_alphanumeric() => c,
+            _ => '_',
+        };
+        if c != '_' || !normalizado.ends_with('_') {
+            normalizado.push(c);
+        }
+    }
+    normalizado.trim_matches('_').to_string()
+}
@@ -1,2 +1,4 @@
 pub mod serde_utils;
 pub mod validator;
+pub mod pdf;
+pub mod csv;
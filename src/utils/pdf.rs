@@ -0,0 +1,348 @@
+use std::io::{BufWriter, Write};
+
+use anyhow::{Result, anyhow};
+use bigdecimal::{BigDecimal, RoundingMode};
+use chrono::{DateTime, Utc};
+use printpdf::{
+    BuiltinFont, Color, ColorBits, ColorSpace, Image, ImageFilter, ImageTransform, ImageXObject,
+    IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point, Px,
+    Rect, Rgb, path::PaintMode,
+};
+use image::{ExtendedColorType, codecs::jpeg::JpegEncoder};
+use qrcode::{Color as CorQr, EcLevel, QrCode};
+
+/// Dimensões da folha A4 em milímetros
+pub const A4_LARGURA: f32 = 210.0;
+pub const A4_ALTURA: f32 = 297.0;
+
+/// Conversão de pontos tipográficos para milímetros
+const PT_EM_MM: f32 = 25.4 / 72.0;
+
+/// Larguras (em milésimos do corpo) dos caracteres ASCII 32..=126 da Helvetica
+const LARGURAS_HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // ' '..'/'
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, // '0'..'?'
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, // '@'..'O'
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556, // 'P'..'_'
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, // '`'..'o'
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584, // 'p'..'~'
+];
+
+/// Larguras dos caracteres ASCII 32..=126 da Helvetica-Bold
+const LARGURAS_HELVETICA_NEGRITO: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, // ' '..'/'
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, // '0'..'?'
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778, // '@'..'O'
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556, // 'P'..'_'
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611, // '`'..'o'
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584, // 'p'..'~'
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alinhamento {
+    Esquerda,
+    Centro,
+    Direita,
+}
+
+/// Letra sem acento; na Helvetica as letras acentuadas têm a largura da letra base
+pub fn letra_base(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+        'Á' | 'À' | 'Â' | 'Ã' | 'Ä' => 'A',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'É' | 'È' | 'Ê' | 'Ë' => 'E',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' => 'O',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+        'ç' => 'c',
+        'Ç' => 'C',
+        'ñ' => 'n',
+        'Ñ' => 'N',
+        _ => c,
+    }
+}
+
+/// Largura do texto em milímetros nas fontes padrão Helvetica
+pub fn largura_texto(texto: &str, tamanho: f32, negrito: bool) -> f32 {
+    let tabela = if negrito {
+        &LARGURAS_HELVETICA_NEGRITO
+    } else {
+        &LARGURAS_HELVETICA
+    };
+
+    let milesimos: u32 = texto
+        .chars()
+        .map(|c| match letra_base(c) as u32 {
+            n @ 32..=126 => tabela[(n - 32) as usize] as u32,
+            0xAA | 0xBA => 365, // ª º
+            0xB0 => 400,        // °
+            _ => 556,
+        })
+        .sum();
+
+    milesimos as f32 / 1000.0 * tamanho * PT_EM_MM
+}
+
+/// Corta o texto com reticências para caber na largura informada
+pub fn ajustar_texto(texto: &str, largura: f32, tamanho: f32, negrito: bool) -> String {
+    if largura_texto(texto, tamanho, negrito) <= largura {
+        return texto.to_string();
+    }
+
+    let mut ajustado: String = texto.to_string();
+    while !ajustado.is_empty() && largura_texto(&format!("{}...", ajustado), tamanho, negrito) > largura {
+        ajustado.pop();
+    }
+    format!("{}...", ajustado.trim_end())
+}
+
+/// Formata o valor no padrão brasileiro com duas casas ("1.234,56")
+pub fn decimal_br(valor: &BigDecimal) -> String {
+    let texto = valor.with_scale_round(2, RoundingMode::HalfUp).to_string();
+    let (sinal, texto) = match texto.strip_prefix('-') {
+        Some(resto) => ("-", resto),
+        None => ("", texto.as_str()),
+    };
+    let (inteiro, fracao) = texto.split_once('.').unwrap_or((texto, "00"));
+
+    let mut milhar = String::new();
+    for (i, c) in inteiro.chars().enumerate() {
+        if i > 0 && (inteiro.len() - i) % 3 == 0 {
+            milhar.push('.');
+        }
+        milhar.push(c);
+    }
+
+    format!("{}{},{}", sinal, milhar, fracao)
+}
+
+/// Documento PDF em A4 com as fontes Helvetica; as coordenadas são em milímetros
+/// a partir do canto superior esquerdo da página
+pub struct Documento {
+    doc: PdfDocumentReference,
+    camada: PdfLayerReference,
+    regular: IndirectFontRef,
+    negrito: IndirectFontRef,
+}
+
+impl Documento {
+    /// Cria o documento com a primeira página. Identificador e datas vêm de quem
+    /// emite, para que o mesmo documento gere sempre o mesmo conteúdo
+    pub fn new(titulo: &str, identificador: &str, emitido_em: DateTime<Utc>) -> Result<Self> {
+        let data = time::OffsetDateTime::from_unix_timestamp(emitido_em.timestamp())?;
+        let (doc, pagina, camada) =
+            PdfDocument::new(titulo, Mm(A4_LARGURA), Mm(A4_ALTURA), "conteudo");
+        let doc = doc
+            .with_document_id(identificador.to_string())
+            .with_creation_date(data)
+            .with_mod_date(data)
+            .with_metadata_date(data);
+
+        let regular = doc
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(|e| anyhow!("Erro ao carregar fonte: {}", e))?;
+        let negrito = doc
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(|e| anyhow!("Erro ao carregar fonte: {}", e))?;
+        let camada = doc.get_page(pagina).get_layer(camada);
+
+        Ok(Self {
+            doc,
+            camada,
+            regular,
+            negrito,
+        })
+    }
+
+    pub fn nova_pagina(&mut self) {
+        let (pagina, camada) = self
+            .doc
+            .add_page(Mm(A4_LARGURA), Mm(A4_ALTURA), "conteudo");
+        self.camada = self.doc.get_page(pagina).get_layer(camada);
+    }
+
+    /// Escreve o texto com a linha de base em `y`; `x` é a borda esquerda, o centro
+    /// ou a borda direita, conforme o alinhamento
+    pub fn texto(&self, texto: &str, x: f32, y: f32, tamanho: f32, negrito: bool, alinhamento: Alinhamento) {
+        let largura = largura_texto(texto, tamanho, negrito);
+        let x = match alinhamento {
+            Alinhamento::Esquerda => x,
+            Alinhamento::Centro => x - largura / 2.0,
+            Alinhamento::Direita => x - largura,
+        };
+        let fonte = if negrito { &self.negrito } else { &self.regular };
+
+        self.camada
+            .use_text(texto, tamanho, Mm(x), Mm(A4_ALTURA - y), fonte);
+    }
+
+    pub fn linha(&self, x1: f32, y1: f32, x2: f32, y2: f32, espessura: f32) {
+        self.camada.set_outline_thickness(espessura);
+        self.camada.add_line(Line {
+            points: vec![
+                (Point::new(Mm(x1), Mm(A4_ALTURA - y1)), false),
+                (Point::new(Mm(x2), Mm(A4_ALTURA - y2)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    /// Retângulo com o canto superior esquerdo em (`x`, `y`)
+    pub fn retangulo(&self, x: f32, y: f32, largura: f32, altura: f32, preenchido: bool) {
+        let modo = if preenchido {
+            PaintMode::Fill
+        } else {
+            PaintMode::Stroke
+        };
+        self.camada.add_rect(
+            Rect::new(
+                Mm(x),
+                Mm(A4_ALTURA - y - altura),
+                Mm(x + largura),
+                Mm(A4_ALTURA - y),
+            )
+            .with_mode(modo),
+        );
+    }
+
+    /// QR code vetorial com o canto superior esquerdo em (`x`, `y`) e lado `tamanho`
+    pub fn qrcode(&self, conteudo: &str, x: f32, y: f32, tamanho: f32) -> Result<()> {
+        let codigo = QrCode::with_error_correction_level(conteudo, EcLevel::M)
+            .map_err(|e| anyhow!("Erro ao gerar QR code: {}", e))?;
+        let largura = codigo.width();
+        let modulo = tamanho / largura as f32;
+        let cores = codigo.to_colors();
+
+        // cada sequência de módulos escuros da linha vira um único retângulo
+        for linha in 0..largura {
+            let escuro = |coluna: usize| cores[linha * largura + coluna] == CorQr::Dark;
+            let mut coluna = 0;
+            while coluna < largura {
+                if !escuro(coluna) {
+                    coluna += 1;
+                    continue;
+                }
+                let inicio = coluna;
+                while coluna < largura && escuro(coluna) {
+                    coluna += 1;
+                }
+                self.retangulo(
+                    x + inicio as f32 * modulo,
+                    y + linha as f32 * modulo,
+                    (coluna - inicio) as f32 * modulo,
+                    modulo,
+                    true,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Imagem (PNG ou JPEG) dentro da caixa com canto superior esquerdo em (`x`, `y`),
+    /// mantendo a proporção; a transparência é composta sobre fundo branco
+    pub fn imagem(&self, arquivo: &[u8], x: f32, y: f32, largura: f32, altura: f32) -> Result<()> {
+        let rgba = image::load_from_memory(arquivo)
+            .map_err(|e| anyhow!("Erro ao ler imagem: {}", e))?
+            .to_rgba8();
+        let (largura_px, altura_px) = rgba.dimensions();
+        if largura_px == 0 || altura_px == 0 {
+            return Ok(());
+        }
+
+        let mut rgb = Vec::with_capacity((largura_px * altura_px * 3) as usize);
+        for pixel in rgba.pixels() {
+            let [r, g, b, a] = pixel.0;
+            for canal in [r, g, b] {
+                let composto = (canal as u32 * a as u32 + 255 * (255 - a as u32)) / 255;
+                rgb.push(composto as u8);
+            }
+        }
+
+        // o printpdf grava pixels crus sem compressão; em JPEG o logotipo fica com poucos KB
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg, 90)
+            .encode(&rgb, largura_px, altura_px, ExtendedColorType::Rgb8)
+            .map_err(|e| anyhow!("Erro ao converter imagem: {}", e))?;
+
+        // resolução que faz a imagem ocupar a caixa sem distorcer
+        let dpi = (largura_px as f32 * 25.4 / largura).max(altura_px as f32 * 25.4 / altura);
+        let altura_real = altura_px as f32 * 25.4 / dpi;
+
+        Image::from(ImageXObject {
+            width: Px(largura_px as usize),
+            height: Px(altura_px as usize),
+            color_space: ColorSpace::Rgb,
+            bits_per_component: ColorBits::Bit8,
+            interpolate: true,
+            image_data: jpeg,
+            image_filter: Some(ImageFilter::DCT),
+            smask: None,
+            clipping_bbox: None,
+        })
+        .add_to_layer(
+            self.camada.clone(),
+            ImageTransform {
+                translate_x: Some(Mm(x)),
+                translate_y: Some(Mm(A4_ALTURA - y - altura_real)),
+                dpi: Some(dpi),
+                ..Default::default()
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Cor de preenchimento (texto e retângulos preenchidos), em RGB de 0 a 1
+    pub fn cor_preenchimento(&self, r: f32, g: f32, b: f32) {
+        self.camada
+            .set_fill_color(Color::Rgb(Rgb::new(r, g, b, None)));
+    }
+
+    pub fn salvar(self) -> Result<Vec<u8>> {
+        self.doc
+            .save_to_bytes()
+            .map_err(|e| anyhow!("Erro ao gerar PDF: {}", e))
+    }
+
+    /// Grava o PDF direto no destino (ex.: arquivo), sem devolver o conteúdo
+    pub fn salvar_em<W: Write>(self, destino: W) -> Result<()> {
+        let mut destino = BufWriter::new(destino);
+        self.doc
+            .save(&mut destino)
+            .map_err(|e| anyhow!("Erro ao gerar PDF: {}", e))?;
+        destino.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_decimal_br() {
+        let valor = |s: &str| decimal_br(&BigDecimal::from_str(s).unwrap());
+
+        assert_eq!(valor("0"), "0,00");
+        assert_eq!(valor("650.57"), "650,57");
+        assert_eq!(valor("1234.5"), "1.234,50");
+        assert_eq!(valor("1234567.005"), "1.234.567,01");
+        assert_eq!(valor("-48.79"), "-48,79");
+    }
+
+    #[test]
+    fn test_largura_texto() {
+        // "Total" em Helvetica 10: (611 + 556 + 278 + 556 + 222) / 1000 * 10pt
+        let esperado = 2.223 * 10.0 * PT_EM_MM;
+        assert!((largura_texto("Total", 10.0, false) - esperado).abs() < 0.001);
+        // acentuadas ocupam o mesmo que a letra base
+        assert_eq!(largura_texto("Ação", 9.0, true), largura_texto("Acao", 9.0, true));
+    }
+}
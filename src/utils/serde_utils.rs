@@ -1,6 +1,7 @@
 use std::str::FromStr;
 
 use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
 use serde::{Deserialize, Deserializer, Serializer};
 /*
 utilizado nos shemas para converter checkbox em booleano
@@ -87,4 +88,26 @@ where
 {
     let opt = Option::<String>::deserialize(deserializer)?;
     Ok(opt.filter(|s| !s.trim().is_empty()))
-}
\ No newline at end of file
+}
+
+/// Atualização parcial de texto opcional: usado com `#[serde(default)]`, campo ausente
+/// fica None (mantém o valor gravado); null ou texto vazio viram Some(None) (limpam)
+pub fn de_opt_nullable_string<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    empty_as_none(deserializer).map(Some)
+}
+/// Data do input type="date" (AAAA-MM-DD); campo vazio vira None
+pub fn de_opt_date<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s {
+        Some(ref v) if !v.trim().is_empty() => NaiveDate::parse_from_str(v.trim(), "%Y-%m-%d")
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
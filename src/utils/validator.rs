@@ -14,4 +14,160 @@ pub fn validate_optional_email(email: &Option<String>) -> Result<(), ValidationE
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Mantém só os dígitos (CPF, PIS, CEP digitados com máscara)
+pub fn somente_digitos(valor: &str) -> String {
+    valor.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+fn digitos(valor: &str, tamanho: usize) -> Option<Vec<u32>> {
+    let digitos: Vec<u32> = somente_digitos(valor)
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect();
+    (digitos.len() == tamanho).then_some(digitos)
+}
+
+/// Dígito verificador módulo 11 a partir dos pesos informados
+fn digito_mod11(digitos: &[u32], pesos: &[u32]) -> u32 {
+    let soma: u32 = digitos.iter().zip(pesos).map(|(d, p)| d * p).sum();
+    match 11 - soma % 11 {
+        10 | 11 => 0,
+        dv => dv,
+    }
+}
+
+/// CPF com 11 dígitos e os dois dígitos verificadores corretos; aceita a máscara
+/// 000.000.000-00 e recusa sequências repetidas (111.111.111-11)
+pub fn cpf_valido(cpf: &str) -> bool {
+    let Some(d) = digitos(cpf, 11) else {
+        return false;
+    };
+    if d.iter().all(|&x| x == d[0]) {
+        return false;
+    }
+
+    let dv1 = digito_mod11(&d[..9], &[10, 9, 8, 7, 6, 5, 4, 3, 2]);
+    let dv2 = digito_mod11(&d[..10], &[11, 10, 9, 8, 7, 6, 5, 4, 3, 2]);
+    d[9] == dv1 && d[10] == dv2
+}
+
+/// PIS/PASEP/NIT com 11 dígitos e dígito verificador correto; aceita a máscara 000.00000.00-0
+pub fn pis_valido(pis: &str) -> bool {
+    let Some(d) = digitos(pis, 11) else {
+        return false;
+    };
+    if d.iter().all(|&x| x == d[0]) {
+        return false;
+    }
+
+    d[10] == digito_mod11(&d[..10], &[3, 2, 9, 8, 7, 6, 5, 4, 3, 2])
+}
+
+/// CNPJ sem a máscara, com as letras em maiúsculas (o CNPJ alfanumérico tem letras
+/// nas 12 primeiras posições)
+pub fn normalizar_cnpj(cnpj: &str) -> String {
+    cnpj.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// CNPJ numérico ou alfanumérico com os dígitos verificadores corretos; aceita a máscara
+/// 00.000.000/0000-00. Cada posição vale o código ASCII menos 48, então os dígitos
+/// mantêm o valor do cálculo tradicional
+pub fn cnpj_valido(cnpj: &str) -> bool {
+    let cnpj = normalizar_cnpj(cnpj);
+    let valores: Vec<u32> = cnpj.chars().map(|c| c as u32 - 48).collect();
+    if valores.len() != 14 || !cnpj[12..].chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    if valores.iter().all(|&x| x == valores[0]) {
+        return false;
+    }
+
+    let dv1 = digito_mod11(&valores[..12], &[5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2]);
+    let dv2 = digito_mod11(&valores[..13], &[6, 5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2]);
+    valores[12] == dv1 && valores[13] == dv2
+}
+
+/// CNPJ com a máscara 00.000.000/0000-00; valores fora do padrão voltam como estão
+pub fn formatar_cnpj(cnpj: &str) -> String {
+    let c = normalizar_cnpj(cnpj);
+    if c.len() != 14 {
+        return cnpj.to_string();
+    }
+    format!("{}.{}.{}/{}-{}", &c[..2], &c[2..5], &c[5..8], &c[8..12], &c[12..])
+}
+
+/// CEP com a máscara 00000-000; valores fora do padrão voltam como estão
+pub fn formatar_cep(cep: &str) -> String {
+    let d = somente_digitos(cep);
+    if d.len() != 8 {
+        return cep.to_string();
+    }
+    format!("{}-{}", &d[..5], &d[5..])
+}
+
+/// CPF com a máscara 000.000.000-00; valores fora do padrão voltam como estão
+pub fn formatar_cpf(cpf: &str) -> String {
+    let d = somente_digitos(cpf);
+    if d.len() != 11 {
+        return cpf.to_string();
+    }
+    format!("{}.{}.{}-{}", &d[..3], &d[3..6], &d[6..9], &d[9..])
+}
+
+/// PIS/PASEP com a máscara 000.00000.00-0; valores fora do padrão voltam como estão
+pub fn formatar_pis(pis: &str) -> String {
+    let d = somente_digitos(pis);
+    if d.len() != 11 {
+        return pis.to_string();
+    }
+    format!("{}.{}.{}-{}", &d[..3], &d[3..8], &d[8..10], &d[10..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpf_valido() {
+        assert!(cpf_valido("529.982.247-25"));
+        assert!(cpf_valido("52998224725"));
+        assert!(!cpf_valido("529.982.247-24"));
+        assert!(!cpf_valido("111.111.111-11"));
+        assert!(!cpf_valido("5299822472"));
+    }
+
+    #[test]
+    fn test_pis_valido() {
+        assert!(pis_valido("120.54012.73-6"));
+        assert!(pis_valido("12054012736"));
+        assert!(!pis_valido("120.54012.73-7"));
+        assert!(!pis_valido("000.00000.00-0"));
+    }
+
+    #[test]
+    fn test_cnpj_valido() {
+        assert!(cnpj_valido("11.222.333/0001-81"));
+        assert!(cnpj_valido("11222333000181"));
+        assert!(!cnpj_valido("11.222.333/0001-80"));
+        assert!(!cnpj_valido("00.000.000/0000-00"));
+        // alfanumérico
+        assert!(cnpj_valido("12.ABC.345/01DE-35"));
+        assert!(cnpj_valido("12abc34501de35"));
+        assert!(!cnpj_valido("12.ABC.345/01DE-36"));
+        assert!(!cnpj_valido("12.ABC.345/01DE-3X"));
+    }
+
+    #[test]
+    fn test_formatar_documentos() {
+        assert_eq!(formatar_cpf("52998224725"), "529.982.247-25");
+        assert_eq!(formatar_pis("12054012736"), "120.54012.73-6");
+        assert_eq!(formatar_cpf("123"), "123");
+        assert_eq!(formatar_cnpj("11222333000181"), "11.222.333/0001-81");
+        assert_eq!(formatar_cep("77000000"), "77000-000");
+    }
+}